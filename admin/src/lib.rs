@@ -0,0 +1,581 @@
+//! Administration of a deployed near-ft contract from the command line.
+//!
+//! Every command maps to one privileged method of the contract, callable by the owner and the
+//! accounts granted its role. Before anything is signed, the command queries the views describing
+//! the state it is about to change and previews the call: what it changes, and why it would
+//! fail. `--dry-run` stops there, and a call whose preview has warnings is only sent with
+//! `--force`.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::path::PathBuf;
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use near_primitives::types::AccountId;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// The basis points making up 100%, as the contract counts them.
+const BPS_DENOMINATOR: u64 = 10_000;
+/// The highest transfer fee the contract accepts, in basis points.
+const MAX_TRANSFER_FEE_BPS: u64 = 500;
+
+/// The groups of methods the contract can pause, mirroring its `PauseFlags`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PauseFlags {
+    pub transfers: bool,
+    pub mints: bool,
+    pub storage: bool,
+}
+
+/// An administration command.
+#[derive(Debug, PartialEq)]
+pub enum AdminCommand {
+    Mint {
+        receiver_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+    },
+    /// Pauses the given groups of methods, or all of them with `None`.
+    Pause {
+        flags: Option<PauseFlags>,
+    },
+    Unpause,
+    /// Turns the fee switch on with the given `FeeConfig`, or off with `None`.
+    SetFees {
+        fee_config: Option<Value>,
+    },
+    Freeze {
+        account_id: AccountId,
+        reason_code: u16,
+        duration_sec: u64,
+    },
+    Unfreeze {
+        account_id: AccountId,
+    },
+}
+
+/// A method call on the contract, with its JSON arguments.
+#[derive(Debug, PartialEq)]
+pub struct MethodCall {
+    pub method_name: &'static str,
+    pub args: Value,
+}
+
+impl MethodCall {
+    fn new(method_name: &'static str, args: Value) -> Self {
+        Self { method_name, args }
+    }
+}
+
+impl fmt::Display for MethodCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.method_name, self.args)
+    }
+}
+
+/// What a command would do, computed from the current state of the contract.
+#[derive(Debug, Default, PartialEq)]
+pub struct Preview {
+    /// The state changes, one per line.
+    pub changes: Vec<String>,
+    /// The reasons the call would fail or be a mistake.
+    pub warnings: Vec<String>,
+}
+
+impl fmt::Display for Preview {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            writeln!(f, "  no change")?;
+        }
+        for change in self.changes.iter() {
+            writeln!(f, "  {}", change)?;
+        }
+        for warning in self.warnings.iter() {
+            writeln!(f, "  warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+impl AdminCommand {
+    /// Returns the change method call performing the command. Every one of them takes a
+    /// deposit of 1 yoctoNEAR.
+    pub fn call(&self) -> MethodCall {
+        match self {
+            Self::Mint {
+                receiver_id,
+                amount,
+                memo,
+            } => MethodCall::new(
+                "ft_mint",
+                json!({ "receiver_id": receiver_id, "amount": amount.to_string(), "memo": memo }),
+            ),
+            Self::Pause { flags: None } => MethodCall::new("pause", json!({})),
+            Self::Pause { flags: Some(flags) } => {
+                MethodCall::new("set_pause_flags", json!({ "flags": flags }))
+            }
+            Self::Unpause => MethodCall::new("unpause", json!({})),
+            Self::SetFees { fee_config } => {
+                MethodCall::new("set_fee_config", json!({ "fee_config": fee_config }))
+            }
+            Self::Freeze {
+                account_id,
+                reason_code,
+                duration_sec,
+            } => MethodCall::new(
+                "freeze_account",
+                json!({
+                    "account_id": account_id,
+                    "reason_code": reason_code,
+                    "duration_sec": duration_sec,
+                }),
+            ),
+            Self::Unfreeze { account_id } => {
+                MethodCall::new("unfreeze_account", json!({ "account_id": account_id }))
+            }
+        }
+    }
+
+    /// Returns the view calls the preview of the command signed by `signer_id` is computed
+    /// from, in the order `preview` expects their results.
+    pub fn views(&self, signer_id: &AccountId) -> Vec<MethodCall> {
+        match self {
+            Self::Mint { receiver_id, .. } => vec![
+                MethodCall::new("ft_balance_of", json!({ "account_id": receiver_id })),
+                MethodCall::new("ft_total_supply", json!({})),
+                MethodCall::new("storage_balance_of", json!({ "account_id": receiver_id })),
+                MethodCall::new("get_pause_flags", json!({})),
+                has_role(signer_id, "minter"),
+            ],
+            Self::Pause { .. } | Self::Unpause => vec![
+                MethodCall::new("get_pause_flags", json!({})),
+                has_role(signer_id, "pauser"),
+            ],
+            Self::SetFees { fee_config } => {
+                let mut views = vec![
+                    MethodCall::new("get_fee_config", json!({})),
+                    has_role(signer_id, "fee_admin"),
+                ];
+                // Both buckets must be registered to receive their share.
+                for key in ["stakers_pool_id", "treasury_id"] {
+                    if let Some(account_id) = fee_config.as_ref().and_then(|config| config.get(key))
+                    {
+                        views.push(MethodCall::new(
+                            "storage_balance_of",
+                            json!({ "account_id": account_id }),
+                        ));
+                    }
+                }
+                views
+            }
+            Self::Freeze { account_id, .. } | Self::Unfreeze { account_id } => vec![
+                MethodCall::new("get_freeze", json!({ "account_id": account_id })),
+                has_role(signer_id, "freeze_admin"),
+            ],
+        }
+    }
+
+    /// Previews the command signed by `signer_id`, given the results of `views`.
+    pub fn preview(&self, signer_id: &AccountId, views: &[Value]) -> Preview {
+        let mut preview = Preview::default();
+        match self {
+            Self::Mint {
+                receiver_id,
+                amount,
+                ..
+            } => {
+                let [balance, total_supply, storage_balance, pause_flags, is_minter] = views else {
+                    panic!("The mint preview expects 5 views");
+                };
+                let balance = parse_amount(balance);
+                let total_supply = parse_amount(total_supply);
+                preview.changes.push(format!(
+                    "balance of {}: {} -> {}",
+                    receiver_id,
+                    balance,
+                    balance.saturating_add(*amount)
+                ));
+                preview.changes.push(format!(
+                    "total supply: {} -> {}",
+                    total_supply,
+                    total_supply.saturating_add(*amount)
+                ));
+                if *amount == 0 {
+                    preview
+                        .warnings
+                        .push("The amount should be a positive number".to_string());
+                }
+                if total_supply.checked_add(*amount).is_none() {
+                    preview
+                        .warnings
+                        .push("The total supply would overflow".to_string());
+                }
+                if storage_balance.is_null() {
+                    preview
+                        .warnings
+                        .push(format!("{} is not registered", receiver_id));
+                }
+                if pause_flags["mints"] == true {
+                    preview.warnings.push("Mints are paused".to_string());
+                }
+                preview
+                    .warnings
+                    .extend(role_warning(signer_id, "minter", is_minter));
+            }
+            Self::Pause { .. } | Self::Unpause => {
+                let [current, is_pauser] = views else {
+                    panic!("The pause preview expects 2 views");
+                };
+                let current: PauseFlags =
+                    serde_json::from_value(current.clone()).unwrap_or_default();
+                let flags = match self {
+                    Self::Pause { flags: Some(flags) } => flags.clone(),
+                    Self::Pause { flags: None } => PauseFlags {
+                        transfers: true,
+                        mints: true,
+                        storage: true,
+                    },
+                    _ => PauseFlags::default(),
+                };
+                for (group, from, to) in [
+                    ("transfers", current.transfers, flags.transfers),
+                    ("mints", current.mints, flags.mints),
+                    ("storage", current.storage, flags.storage),
+                ] {
+                    if from != to {
+                        preview.changes.push(format!(
+                            "{}: {} -> {}",
+                            group,
+                            paused_label(from),
+                            paused_label(to)
+                        ));
+                    }
+                }
+                preview
+                    .warnings
+                    .extend(role_warning(signer_id, "pauser", is_pauser));
+            }
+            Self::SetFees { fee_config } => {
+                let [current, is_fee_admin, buckets @ ..] = views else {
+                    panic!("The fee preview expects the current config and the signer's role");
+                };
+                if current != fee_config.as_ref().unwrap_or(&Value::Null) {
+                    preview.changes.push(format!(
+                        "fee config: {} -> {}",
+                        current,
+                        fee_config.as_ref().unwrap_or(&Value::Null)
+                    ));
+                }
+                if let Some(config) = fee_config.as_ref() {
+                    preview.warnings.extend(fee_config_errors(config));
+                    for (key, storage_balance) in
+                        ["stakers_pool_id", "treasury_id"].into_iter().zip(buckets)
+                    {
+                        if storage_balance.is_null() {
+                            preview
+                                .warnings
+                                .push(format!("{} {} is not registered", key, config[key]));
+                        }
+                    }
+                }
+                preview
+                    .warnings
+                    .extend(role_warning(signer_id, "fee_admin", is_fee_admin));
+            }
+            Self::Freeze {
+                account_id,
+                reason_code,
+                duration_sec,
+            } => {
+                let [freeze, is_freeze_admin] = views else {
+                    panic!("The freeze preview expects 2 views");
+                };
+                if !freeze.is_null() {
+                    preview.changes.push(format!(
+                        "{}: replaces the freeze with reason code {} expiring at {}",
+                        account_id, freeze["reason_code"], freeze["expires_at"]
+                    ));
+                }
+                preview.changes.push(format!(
+                    "{}: frozen for {} seconds with reason code {}",
+                    account_id, duration_sec, reason_code
+                ));
+                preview
+                    .warnings
+                    .extend(role_warning(signer_id, "freeze_admin", is_freeze_admin));
+            }
+            Self::Unfreeze { account_id } => {
+                let [freeze, is_freeze_admin] = views else {
+                    panic!("The unfreeze preview expects 2 views");
+                };
+                if freeze.is_null() {
+                    preview
+                        .warnings
+                        .push(format!("{} is not frozen", account_id));
+                } else {
+                    preview.changes.push(format!(
+                        "{}: unfrozen (reason code {})",
+                        account_id, freeze["reason_code"]
+                    ));
+                }
+                preview
+                    .warnings
+                    .extend(role_warning(signer_id, "freeze_admin", is_freeze_admin));
+            }
+        }
+        preview
+    }
+}
+
+fn parse_amount(amount: &Value) -> u128 {
+    amount
+        .as_str()
+        .and_then(|amount| amount.parse().ok())
+        .unwrap_or_default()
+}
+
+fn paused_label(paused: bool) -> &'static str {
+    if paused {
+        "paused"
+    } else {
+        "running"
+    }
+}
+
+/// Returns the reasons the contract would reject a fee config, as `set_fee_config` checks it.
+fn fee_config_errors(config: &Value) -> Vec<String> {
+    let mut errors = vec![];
+    if config["fee_bps"].as_u64().unwrap_or(u64::MAX) > MAX_TRANSFER_FEE_BPS {
+        errors.push("The fee can't be higher than 5%".to_string());
+    }
+    let split: u64 = [
+        "stakers_bps",
+        "treasury_bps",
+        "burn_bps",
+        "rebates_bps",
+        "insurance_bps",
+    ]
+    .into_iter()
+    .filter_map(|key| config["split"][key].as_u64())
+    .sum();
+    if split != BPS_DENOMINATOR {
+        errors.push("The fee split should add up to 100%".to_string());
+    }
+    errors
+}
+
+/// The view telling whether `signer_id` can call the methods of `role`, as the owner or a
+/// grantee.
+fn has_role(signer_id: &AccountId, role: &str) -> MethodCall {
+    MethodCall::new("has_role", json!({ "account_id": signer_id, "role": role }))
+}
+
+fn role_warning(signer_id: &AccountId, role: &str, has_role: &Value) -> Option<String> {
+    (has_role != true).then(|| {
+        format!(
+            "{} doesn't have the {} role",
+            signer_id,
+            role.replace('_', " ")
+        )
+    })
+}
+
+/// The options of an `ft-admin` invocation.
+#[derive(Debug)]
+pub struct Options {
+    /// The JSON RPC endpoint of the network.
+    pub rpc_url: String,
+    pub contract_id: AccountId,
+    pub signer_id: AccountId,
+    /// The credentials of the signer, in the format of near-cli.
+    pub key_file: PathBuf,
+    /// Whether to stop after the preview.
+    pub dry_run: bool,
+    /// Whether to send the call even though its preview has warnings.
+    pub force: bool,
+    pub command: AdminCommand,
+}
+
+/// Returns the command line interface of `ft-admin`.
+pub fn cli() -> Command {
+    let account_id = || value_parser!(AccountId);
+    Command::new("ft-admin")
+        .about("Calls the administration methods of a near-ft contract")
+        .arg(
+            Arg::new("network")
+                .long("network")
+                .default_value("testnet")
+                .help("`testnet`, `mainnet` or the URL of a JSON RPC endpoint"),
+        )
+        .arg(
+            Arg::new("contract")
+                .long("contract")
+                .required(true)
+                .value_parser(account_id())
+                .help("The account of the contract"),
+        )
+        .arg(
+            Arg::new("signer")
+                .long("signer")
+                .required(true)
+                .value_parser(account_id())
+                .help("The account signing the call"),
+        )
+        .arg(
+            Arg::new("key-file")
+                .long("key-file")
+                .value_parser(value_parser!(PathBuf))
+                .help("The credentials of the signer [default: ~/.near-credentials/<network>/<signer>.json]"),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .action(ArgAction::SetTrue)
+                .help("Previews the call without sending it"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .help("Sends the call even though its preview has warnings"),
+        )
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("mint")
+                .about("Mints new tokens (minter only)")
+                .arg(Arg::new("receiver_id").required(true).value_parser(account_id()))
+                .arg(
+                    Arg::new("amount")
+                        .required(true)
+                        .value_parser(value_parser!(u128))
+                        .help("The amount in the smallest unit of the token"),
+                )
+                .arg(Arg::new("memo").long("memo")),
+        )
+        .subcommand(
+            Command::new("pause")
+                .about("Pauses the given groups of methods, or all of them")
+                .arg(Arg::new("transfers").long("transfers").action(ArgAction::SetTrue))
+                .arg(Arg::new("mints").long("mints").action(ArgAction::SetTrue))
+                .arg(Arg::new("storage").long("storage").action(ArgAction::SetTrue)),
+        )
+        .subcommand(Command::new("unpause").about("Unpauses every group of methods"))
+        .subcommand(
+            Command::new("set-fees")
+                .about("Turns the fee switch on with the given config, or off with `off`")
+                .arg(
+                    Arg::new("fee_config")
+                        .required(true)
+                        .help("The `FeeConfig` as JSON, or `off`"),
+                ),
+        )
+        .subcommand(
+            Command::new("freeze")
+                .about("Freezes an account (owner or freeze admin only)")
+                .arg(Arg::new("account_id").required(true).value_parser(account_id()))
+                .arg(
+                    Arg::new("reason-code")
+                        .long("reason-code")
+                        .required(true)
+                        .value_parser(value_parser!(u16)),
+                )
+                .arg(
+                    Arg::new("duration-sec")
+                        .long("duration-sec")
+                        .required(true)
+                        .value_parser(value_parser!(u64)),
+                ),
+        )
+        .subcommand(
+            Command::new("unfreeze")
+                .about("Lifts the freeze of an account (owner or freeze admin only)")
+                .arg(Arg::new("account_id").required(true).value_parser(account_id())),
+        )
+}
+
+/// Parses the command line of `ft-admin`, program name included.
+pub fn parse_args<I, T>(args: I) -> Result<Options, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let mut cli = cli();
+    let matches = cli.try_get_matches_from_mut(args)?;
+    let network = matches.get_one::<String>("network").unwrap();
+    let signer_id = matches.get_one::<AccountId>("signer").unwrap().clone();
+    let rpc_url = match network.as_str() {
+        "testnet" => "https://rpc.testnet.near.org".to_string(),
+        "mainnet" => "https://rpc.mainnet.near.org".to_string(),
+        url => url.to_string(),
+    };
+    let key_file = match matches.get_one::<PathBuf>("key-file") {
+        Some(key_file) => key_file.clone(),
+        None if network == "testnet" || network == "mainnet" => {
+            let home = std::env::var_os("HOME").unwrap_or_default();
+            PathBuf::from(home)
+                .join(".near-credentials")
+                .join(network)
+                .join(format!("{}.json", signer_id))
+        }
+        None => {
+            return Err(cli.error(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "--key-file is required with a custom network",
+            ))
+        }
+    };
+    let command = match matches.subcommand() {
+        Some(("mint", args)) => AdminCommand::Mint {
+            receiver_id: args.get_one::<AccountId>("receiver_id").unwrap().clone(),
+            amount: *args.get_one::<u128>("amount").unwrap(),
+            memo: args.get_one::<String>("memo").cloned(),
+        },
+        Some(("pause", args)) => {
+            let flags = PauseFlags {
+                transfers: args.get_flag("transfers"),
+                mints: args.get_flag("mints"),
+                storage: args.get_flag("storage"),
+            };
+            AdminCommand::Pause {
+                flags: (flags != PauseFlags::default()).then_some(flags),
+            }
+        }
+        Some(("unpause", _)) => AdminCommand::Unpause,
+        Some(("set-fees", args)) => AdminCommand::SetFees {
+            fee_config: parse_fee_config(&mut cli, args)?,
+        },
+        Some(("freeze", args)) => AdminCommand::Freeze {
+            account_id: args.get_one::<AccountId>("account_id").unwrap().clone(),
+            reason_code: *args.get_one::<u16>("reason-code").unwrap(),
+            duration_sec: *args.get_one::<u64>("duration-sec").unwrap(),
+        },
+        Some(("unfreeze", args)) => AdminCommand::Unfreeze {
+            account_id: args.get_one::<AccountId>("account_id").unwrap().clone(),
+        },
+        _ => unreachable!("A subcommand is required"),
+    };
+    Ok(Options {
+        rpc_url,
+        contract_id: matches.get_one::<AccountId>("contract").unwrap().clone(),
+        signer_id,
+        key_file,
+        dry_run: matches.get_flag("dry-run"),
+        force: matches.get_flag("force"),
+        command,
+    })
+}
+
+fn parse_fee_config(cli: &mut Command, args: &ArgMatches) -> Result<Option<Value>, clap::Error> {
+    let fee_config = args.get_one::<String>("fee_config").unwrap();
+    if fee_config == "off" {
+        return Ok(None);
+    }
+    match serde_json::from_str::<Value>(fee_config) {
+        Ok(config) if config.is_object() => Ok(Some(config)),
+        _ => Err(cli.error(
+            clap::error::ErrorKind::InvalidValue,
+            "The fee config should be a JSON object or `off`",
+        )),
+    }
+}
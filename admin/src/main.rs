@@ -0,0 +1,151 @@
+//! Previews and sends the administration calls of a near-ft contract.
+//!
+//! Usage: `cargo run -p near-ft-admin -- --contract <id> --signer <id> [--dry-run] <command>`,
+//! see `--help` for the commands.
+
+use std::error::Error;
+use std::process::ExitCode;
+
+use near_crypto::{InMemorySigner, Signer};
+use near_ft_admin::{parse_args, MethodCall};
+use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::action::{Action, FunctionCallAction};
+use near_primitives::transaction::{SignedTransaction, Transaction, TransactionV0};
+use near_primitives::types::{AccountId, BlockReference, Finality, FunctionArgs};
+use near_primitives::views::{FinalExecutionStatus, QueryRequest, TxExecutionStatus};
+use serde_json::Value;
+
+/// The gas attached to the administration calls.
+const CALL_GAS: u64 = 30_000_000_000_000;
+/// The deposit every administration method requires, in yoctoNEAR.
+const CALL_DEPOSIT: u128 = 1;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let options = match parse_args(std::env::args_os()) {
+        Ok(options) => options,
+        Err(error) => error.exit(),
+    };
+    match run(options).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(options: near_ft_admin::Options) -> Result<(), Box<dyn Error>> {
+    let client = JsonRpcClient::connect(&options.rpc_url);
+    let command = &options.command;
+    let call = command.call();
+
+    let mut views = vec![];
+    for view_call in command.views(&options.signer_id) {
+        views.push(view(&client, &options.contract_id, &view_call).await?);
+    }
+    let preview = command.preview(&options.signer_id, &views);
+    println!("{} on {}", call, options.contract_id);
+    print!("{}", preview);
+
+    if options.dry_run {
+        return Ok(());
+    }
+    if !preview.warnings.is_empty() && !options.force {
+        return Err("The preview has warnings, pass --force to send the call anyway".into());
+    }
+    let signer: Signer = InMemorySigner::from_file(&options.key_file)
+        .map_err(|error| format!("Failed to read {}: {}", options.key_file.display(), error))?
+        .into();
+    send(
+        &client,
+        &signer,
+        &options.signer_id,
+        &options.contract_id,
+        &call,
+    )
+    .await
+}
+
+/// Calls a view method of the contract and returns its JSON result.
+async fn view(
+    client: &JsonRpcClient,
+    contract_id: &AccountId,
+    view_call: &MethodCall,
+) -> Result<Value, Box<dyn Error>> {
+    let response = client
+        .call(methods::query::RpcQueryRequest {
+            block_reference: BlockReference::Finality(Finality::Final),
+            request: QueryRequest::CallFunction {
+                account_id: contract_id.clone(),
+                method_name: view_call.method_name.to_string(),
+                args: FunctionArgs::from(serde_json::to_vec(&view_call.args)?),
+            },
+        })
+        .await
+        .map_err(|error| format!("{} failed: {}", view_call.method_name, error))?;
+    match response.kind {
+        QueryResponseKind::CallResult(result) => Ok(serde_json::from_slice(&result.result)?),
+        _ => Err(format!("{} returned an unexpected response", view_call.method_name).into()),
+    }
+}
+
+/// Signs the call with the signer's key and waits for its final outcome.
+async fn send(
+    client: &JsonRpcClient,
+    signer: &Signer,
+    signer_id: &AccountId,
+    contract_id: &AccountId,
+    call: &MethodCall,
+) -> Result<(), Box<dyn Error>> {
+    let access_key = client
+        .call(methods::query::RpcQueryRequest {
+            block_reference: BlockReference::Finality(Finality::Final),
+            request: QueryRequest::ViewAccessKey {
+                account_id: signer_id.clone(),
+                public_key: signer.public_key(),
+            },
+        })
+        .await?;
+    let QueryResponseKind::AccessKey(access_key_view) = access_key.kind else {
+        return Err("The access key query returned an unexpected response".into());
+    };
+
+    let transaction = Transaction::V0(TransactionV0 {
+        signer_id: signer_id.clone(),
+        public_key: signer.public_key(),
+        nonce: access_key_view.nonce + 1,
+        receiver_id: contract_id.clone(),
+        block_hash: access_key.block_hash,
+        actions: vec![Action::FunctionCall(Box::new(FunctionCallAction {
+            method_name: call.method_name.to_string(),
+            args: serde_json::to_vec(&call.args)?,
+            gas: CALL_GAS,
+            deposit: CALL_DEPOSIT,
+        }))],
+    });
+    let (hash, _) = transaction.get_hash_and_size();
+    let signed_transaction = SignedTransaction::new(signer.sign(hash.as_ref()), transaction);
+
+    let response = client
+        .call(methods::send_tx::RpcSendTransactionRequest {
+            signed_transaction,
+            wait_until: TxExecutionStatus::Final,
+        })
+        .await?;
+    let outcome = response
+        .final_execution_outcome
+        .ok_or("The transaction has no outcome")?
+        .into_outcome();
+    match outcome.status {
+        FinalExecutionStatus::SuccessValue(_) => {
+            println!("sent in transaction {}", outcome.transaction.hash);
+            Ok(())
+        }
+        FinalExecutionStatus::Failure(error) => {
+            Err(format!("transaction {} failed: {}", outcome.transaction.hash, error).into())
+        }
+        status => Err(format!("transaction {} is {:?}", outcome.transaction.hash, status).into()),
+    }
+}
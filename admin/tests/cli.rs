@@ -0,0 +1,222 @@
+use near_ft_admin::{parse_args, AdminCommand, PauseFlags};
+use serde_json::{json, Value};
+
+fn parse(args: &[&str]) -> near_ft_admin::Options {
+    let mut command_line = vec![
+        "ft-admin",
+        "--contract",
+        "token.testnet",
+        "--signer",
+        "owner.testnet",
+    ];
+    command_line.extend_from_slice(args);
+    parse_args(command_line).unwrap()
+}
+
+#[test]
+fn test_commands_map_to_admin_methods() {
+    let options = parse(&[
+        "--dry-run",
+        "mint",
+        "alice.testnet",
+        "100",
+        "--memo",
+        "grant",
+    ]);
+    assert!(options.dry_run);
+    assert_eq!(options.rpc_url, "https://rpc.testnet.near.org");
+    assert!(options
+        .key_file
+        .ends_with(".near-credentials/testnet/owner.testnet.json"));
+    let call = options.command.call();
+    assert_eq!(call.method_name, "ft_mint");
+    assert_eq!(
+        call.args,
+        json!({ "receiver_id": "alice.testnet", "amount": "100", "memo": "grant" })
+    );
+
+    assert_eq!(parse(&["pause"]).command.call().method_name, "pause");
+    let call = parse(&["pause", "--mints"]).command.call();
+    assert_eq!(call.method_name, "set_pause_flags");
+    assert_eq!(
+        call.args,
+        json!({ "flags": { "transfers": false, "mints": true, "storage": false } })
+    );
+
+    assert_eq!(
+        parse(&["set-fees", "off"]).command,
+        AdminCommand::SetFees { fee_config: None }
+    );
+    let call = parse(&[
+        "freeze",
+        "bob.testnet",
+        "--reason-code",
+        "7",
+        "--duration-sec",
+        "60",
+    ])
+    .command
+    .call();
+    assert_eq!(call.method_name, "freeze_account");
+    assert_eq!(
+        call.args,
+        json!({ "account_id": "bob.testnet", "reason_code": 7, "duration_sec": 60 })
+    );
+}
+
+#[test]
+fn test_invalid_command_lines_are_rejected() {
+    let base = [
+        "ft-admin",
+        "--contract",
+        "token.testnet",
+        "--signer",
+        "owner.testnet",
+    ];
+    let with = |args: &[&'static str]| {
+        let mut command_line = base.to_vec();
+        command_line.extend_from_slice(args);
+        parse_args(command_line)
+    };
+    assert!(with(&["mint", "Not An Account", "1"]).is_err());
+    assert!(with(&["mint", "alice.testnet", "-1"]).is_err());
+    assert!(with(&["set-fees", "[1, 2]"]).is_err());
+    // Custom networks have no default credentials.
+    assert!(with(&["--network", "http://localhost:3030", "unpause"]).is_err());
+}
+
+#[test]
+fn test_mint_preview() {
+    let command = parse(&["mint", "alice.testnet", "100"]).command;
+    let signer_id = "owner.testnet".parse().unwrap();
+    let storage_balance = json!({ "total": "1250000000000000000000", "available": "0" });
+
+    let preview = command.preview(
+        &signer_id,
+        &[
+            json!("50"),
+            json!("1000"),
+            storage_balance,
+            serde_json::to_value(PauseFlags::default()).unwrap(),
+            json!(true),
+        ],
+    );
+    assert_eq!(
+        preview.changes,
+        vec![
+            "balance of alice.testnet: 50 -> 150",
+            "total supply: 1000 -> 1100"
+        ]
+    );
+    assert!(preview.warnings.is_empty());
+
+    let paused = PauseFlags {
+        mints: true,
+        ..Default::default()
+    };
+    let preview = command.preview(
+        &signer_id,
+        &[
+            json!("0"),
+            json!("1000"),
+            Value::Null,
+            serde_json::to_value(paused).unwrap(),
+            json!(false),
+        ],
+    );
+    assert_eq!(
+        preview.warnings,
+        vec![
+            "alice.testnet is not registered",
+            "Mints are paused",
+            "owner.testnet doesn't have the minter role"
+        ]
+    );
+}
+
+#[test]
+fn test_pause_and_fee_previews() {
+    let signer_id = "owner.testnet".parse().unwrap();
+    let current = json!({ "transfers": false, "mints": true, "storage": false });
+
+    let preview = parse(&["pause"])
+        .command
+        .preview(&signer_id, &[current.clone(), json!(true)]);
+    assert_eq!(
+        preview.changes,
+        vec!["transfers: running -> paused", "storage: running -> paused"]
+    );
+    let preview = parse(&["pause", "--mints"])
+        .command
+        .preview(&signer_id, &[current, json!(false)]);
+    assert!(preview.changes.is_empty());
+    assert_eq!(
+        preview.to_string(),
+        "  no change\n  warning: owner.testnet doesn't have the pauser role\n"
+    );
+
+    let fee_config = json!({
+        "fee_bps": 100,
+        "split": { "stakers_bps": 5000, "treasury_bps": 4000, "burn_bps": 0 },
+        "stakers_pool_id": "pool.testnet",
+        "treasury_id": "treasury.testnet"
+    });
+    let command = parse(&["set-fees", &fee_config.to_string()]).command;
+    assert_eq!(command.views(&signer_id).len(), 4);
+    let preview = command.preview(
+        &signer_id,
+        &[
+            Value::Null,
+            json!(true),
+            json!({ "total": "1", "available": "0" }),
+            Value::Null,
+        ],
+    );
+    assert_eq!(
+        preview.changes,
+        vec![format!("fee config: null -> {}", fee_config)]
+    );
+    assert_eq!(
+        preview.warnings,
+        vec![
+            "The fee split should add up to 100%",
+            "treasury_id \"treasury.testnet\" is not registered"
+        ]
+    );
+}
+
+#[test]
+fn test_freeze_previews() {
+    let signer_id = "owner.testnet".parse().unwrap();
+    let freeze = json!({ "reason_code": 3, "frozen_at": "0", "expires_at": "10" });
+
+    let preview = parse(&[
+        "freeze",
+        "bob.testnet",
+        "--reason-code",
+        "7",
+        "--duration-sec",
+        "60",
+    ])
+    .command
+    .preview(&signer_id, &[freeze.clone(), json!(true)]);
+    assert_eq!(
+        preview.changes,
+        vec![
+            "bob.testnet: replaces the freeze with reason code 3 expiring at \"10\"",
+            "bob.testnet: frozen for 60 seconds with reason code 7"
+        ]
+    );
+    assert!(preview.warnings.is_empty());
+
+    let preview = parse(&["unfreeze", "bob.testnet"])
+        .command
+        .preview(&signer_id, &[Value::Null, json!(false)]);
+    assert_eq!(
+        preview.warnings,
+        vec![
+            "bob.testnet is not frozen",
+            "owner.testnet doesn't have the freeze admin role"
+        ]
+    );
+}
@@ -0,0 +1,272 @@
+//! TypeScript bindings of the contract, generated from its ABI.
+//!
+//! The ABI describes every public method with the JSON schemas of its arguments and result,
+//! as produced by the serde derives of the contract. The bindings mirror them: one type per
+//! schema definition, one argument interface per method, and a client calling the methods
+//! through a transport the front-end provides (near-api-js, a wallet selector, ...).
+//!
+//! Integers are typed as `number`, as serde encodes them. Amounts (`U128`, `U64`) are strings.
+
+use std::fmt::Write;
+
+use serde_json::{Map, Value};
+
+/// The prelude of the generated file: the transport the client calls the contract through.
+const PRELUDE: &str = "\
+// Generated by `cargo run -p near-ft-bindings`. Do not edit.
+
+export interface CallOptions {
+  /** The gas attached to the call, in gas units. */
+  gas?: string;
+  /** The deposit attached to the call, in yoctoNEAR. */
+  attachedDeposit?: string;
+}
+
+export interface NearFtTransport {
+  view<T>(methodName: string, args: object): Promise<T>;
+  call<T>(methodName: string, args: object, options?: CallOptions): Promise<T>;
+}
+";
+
+/// Generates the TypeScript bindings of the contract described by `abi`.
+pub fn generate_typescript(abi: &Value) -> String {
+    let mut out = String::from(PRELUDE);
+
+    let definitions = abi
+        .pointer("/body/root_schema/definitions")
+        .and_then(Value::as_object);
+    for (name, schema) in definitions.into_iter().flatten() {
+        out.push('\n');
+        write_doc(&mut out, "", schema.get("description"));
+        writeln!(out, "export type {} = {};", name, ts_type(schema)).unwrap();
+    }
+
+    let functions: Vec<&Value> = abi
+        .pointer("/body/functions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|function| !has_modifier(function, "private") && !has_modifier(function, "init"))
+        .collect();
+    for function in functions.iter() {
+        let args = function_args(function);
+        if args.is_empty() {
+            continue;
+        }
+        writeln!(
+            out,
+            "\nexport interface {}Args {{",
+            pascal_case(name(function))
+        )
+        .unwrap();
+        for arg in args {
+            let schema = &arg["type_schema"];
+            let optional = if is_nullable(schema) { "?" } else { "" };
+            let name = arg["name"].as_str().unwrap_or_default();
+            writeln!(out, "  {}{}: {};", name, optional, ts_type(schema)).unwrap();
+        }
+        out.push_str("}\n");
+    }
+
+    out.push_str("\nexport class NearFtClient {\n");
+    out.push_str("  constructor(private readonly transport: NearFtTransport) {}\n");
+    for function in functions.iter() {
+        let name = name(function);
+        let result = function
+            .pointer("/result/type_schema")
+            .map_or_else(|| "void".to_string(), ts_type);
+        let (params, args) = if function_args(function).is_empty() {
+            (String::new(), "{}".to_string())
+        } else {
+            (
+                format!("args: {}Args", pascal_case(name)),
+                "args".to_string(),
+            )
+        };
+        out.push('\n');
+        write_doc(&mut out, "  ", function.get("doc"));
+        if function["kind"] == "view" {
+            writeln!(out, "  {}({}): Promise<{}> {{", name, params, result).unwrap();
+            writeln!(
+                out,
+                "    return this.transport.view(\"{}\", {});",
+                name, args
+            )
+            .unwrap();
+        } else {
+            let params = if params.is_empty() {
+                "options?: CallOptions".to_string()
+            } else {
+                format!("{}, options?: CallOptions", params)
+            };
+            writeln!(out, "  {}({}): Promise<{}> {{", name, params, result).unwrap();
+            writeln!(
+                out,
+                "    return this.transport.call(\"{}\", {}, options);",
+                name, args
+            )
+            .unwrap();
+        }
+        out.push_str("  }\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Converts a JSON schema to the matching TypeScript type.
+pub fn ts_type(schema: &Value) -> String {
+    let Some(schema) = schema.as_object() else {
+        // The `true` schema accepts anything.
+        return "unknown".to_string();
+    };
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return union(values.iter().map(Value::to_string));
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(variants) = schema.get(key).and_then(Value::as_array) {
+            return union(variants.iter().map(ts_type));
+        }
+    }
+    if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+        return parts.iter().map(ts_type).collect::<Vec<_>>().join(" & ");
+    }
+    match schema.get("type") {
+        Some(Value::String(kind)) => ts_primitive(kind, schema),
+        Some(Value::Array(kinds)) => union(
+            kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|kind| ts_primitive(kind, schema)),
+        ),
+        _ => "unknown".to_string(),
+    }
+}
+
+fn ts_primitive(kind: &str, schema: &Map<String, Value>) -> String {
+    match kind {
+        "string" => "string".to_string(),
+        "integer" | "number" => "number".to_string(),
+        "boolean" => "boolean".to_string(),
+        "null" => "null".to_string(),
+        "array" => match schema.get("items") {
+            Some(Value::Array(items)) => {
+                format!(
+                    "[{}]",
+                    items.iter().map(ts_type).collect::<Vec<_>>().join(", ")
+                )
+            }
+            Some(items) => format!("{}[]", wrap(ts_type(items))),
+            None => "unknown[]".to_string(),
+        },
+        "object" => ts_object(schema),
+        _ => "unknown".to_string(),
+    }
+}
+
+fn ts_object(schema: &Map<String, Value>) -> String {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let properties = schema.get("properties").and_then(Value::as_object);
+    if let Some(properties) = properties.filter(|properties| !properties.is_empty()) {
+        let fields: Vec<String> = properties
+            .iter()
+            .map(|(name, property)| {
+                let optional = if required.contains(&name.as_str()) {
+                    ""
+                } else {
+                    "?"
+                };
+                format!("{}{}: {}", name, optional, ts_type(property))
+            })
+            .collect();
+        return format!("{{ {} }}", fields.join("; "));
+    }
+    match schema.get("additionalProperties") {
+        Some(Value::Bool(false)) => "Record<string, never>".to_string(),
+        Some(values) => format!("Record<string, {}>", ts_type(values)),
+        None => "Record<string, unknown>".to_string(),
+    }
+}
+
+fn union(types: impl Iterator<Item = String>) -> String {
+    let mut variants: Vec<String> = vec![];
+    for variant in types {
+        if !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+    match variants.len() {
+        0 => "never".to_string(),
+        _ => variants.join(" | "),
+    }
+}
+
+/// Wraps a union in parentheses so that it can be made an array.
+fn wrap(ts_type: String) -> String {
+    if ts_type.contains(" | ") || ts_type.contains(" & ") {
+        format!("({})", ts_type)
+    } else {
+        ts_type
+    }
+}
+
+fn is_nullable(schema: &Value) -> bool {
+    ts_type(schema)
+        .split(" | ")
+        .any(|variant| variant == "null")
+}
+
+fn name(function: &Value) -> &str {
+    function["name"].as_str().unwrap_or_default()
+}
+
+fn has_modifier(function: &Value, modifier: &str) -> bool {
+    function["modifiers"]
+        .as_array()
+        .is_some_and(|modifiers| modifiers.iter().any(|value| value == modifier))
+}
+
+fn function_args(function: &Value) -> &[Value] {
+    function
+        .pointer("/params/args")
+        .and_then(Value::as_array)
+        .map_or(&[], Vec::as_slice)
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect()
+}
+
+fn write_doc(out: &mut String, indent: &str, doc: Option<&Value>) {
+    let Some(doc) = doc.and_then(Value::as_str) else {
+        return;
+    };
+    writeln!(out, "{}/**", indent).unwrap();
+    for line in doc.trim().lines() {
+        let line = format!("{} * {}", indent, line.trim());
+        writeln!(out, "{}", line.trim_end()).unwrap();
+    }
+    writeln!(out, "{} */", indent).unwrap();
+}
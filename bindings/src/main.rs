@@ -0,0 +1,33 @@
+//! Builds the ABI of the contract and writes its TypeScript bindings.
+//!
+//! Usage: `cargo run -p near-ft-bindings [-- <output path>]`, writing to `bindings/near-ft.ts`
+//! by default.
+
+use std::path::PathBuf;
+
+use cargo_near_build::abi::AbiOpts;
+use cargo_near_build::camino::Utf8PathBuf;
+
+fn main() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let output = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| crate_dir.join("near-ft.ts"));
+    let manifest_path = Utf8PathBuf::from_path_buf(crate_dir.join("../Cargo.toml"))
+        .expect("The manifest path should be UTF-8");
+
+    let abi_path = cargo_near_build::abi::build(AbiOpts {
+        manifest_path: Some(manifest_path),
+        ..Default::default()
+    })
+    .expect("Failed to build the ABI of the contract");
+    let abi: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(&abi_path).expect("Failed to read the ABI of the contract"),
+    )
+    .expect("The ABI of the contract should be JSON");
+
+    std::fs::write(&output, near_ft_bindings::generate_typescript(&abi))
+        .expect("Failed to write the bindings");
+    eprintln!("Bindings written to {}", output.display());
+}
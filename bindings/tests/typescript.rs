@@ -0,0 +1,92 @@
+use near_ft_bindings::{generate_typescript, ts_type};
+use serde_json::json;
+
+#[test]
+fn test_schemas_map_to_typescript_types() {
+    assert_eq!(ts_type(&json!({ "type": "string" })), "string");
+    assert_eq!(
+        ts_type(&json!({ "type": ["string", "null"] })),
+        "string | null"
+    );
+    assert_eq!(
+        ts_type(&json!({ "anyOf": [{ "$ref": "#/definitions/U128" }, { "type": "null" }] })),
+        "U128 | null"
+    );
+    assert_eq!(
+        ts_type(&json!({
+            "type": "array",
+            "items": [{ "$ref": "#/definitions/AccountId" }, { "type": "integer" }]
+        })),
+        "[AccountId, number]"
+    );
+    assert_eq!(
+        ts_type(&json!({
+            "type": "object",
+            "required": ["amount"],
+            "properties": {
+                "amount": { "$ref": "#/definitions/U128" },
+                "memo": { "type": ["string", "null"] }
+            }
+        })),
+        "{ amount: U128; memo?: string | null }"
+    );
+    assert_eq!(
+        ts_type(&json!({ "type": "string", "enum": ["sender_pays", "receiver_pays"] })),
+        r#""sender_pays" | "receiver_pays""#
+    );
+}
+
+#[test]
+fn test_client_calls_every_public_method() {
+    let abi = json!({
+        "body": {
+            "functions": [
+                {
+                    "name": "ft_transfer",
+                    "doc": " Transfers tokens.",
+                    "kind": "call",
+                    "modifiers": ["payable"],
+                    "params": {
+                        "serialization_type": "json",
+                        "args": [
+                            { "name": "receiver_id", "type_schema": { "$ref": "#/definitions/AccountId" } },
+                            { "name": "memo", "type_schema": { "type": ["string", "null"] } }
+                        ]
+                    }
+                },
+                {
+                    "name": "ft_total_supply",
+                    "kind": "view",
+                    "result": { "serialization_type": "json", "type_schema": { "$ref": "#/definitions/U128" } }
+                },
+                {
+                    "name": "ft_resolve_transfer",
+                    "kind": "call",
+                    "modifiers": ["private"]
+                }
+            ],
+            "root_schema": {
+                "definitions": {
+                    "AccountId": { "type": "string" },
+                    "U128": { "description": "A u128 as a string.", "type": "string" }
+                }
+            }
+        }
+    });
+
+    let bindings = generate_typescript(&abi);
+
+    assert!(bindings.contains("export type AccountId = string;"));
+    assert!(bindings.contains("/**\n * A u128 as a string.\n */\nexport type U128 = string;"));
+    assert!(bindings.contains(
+        "export interface FtTransferArgs {\n  receiver_id: AccountId;\n  memo?: string | null;\n}"
+    ));
+    assert!(bindings.contains(
+        "  ft_transfer(args: FtTransferArgs, options?: CallOptions): Promise<void> {\n    \
+         return this.transport.call(\"ft_transfer\", args, options);"
+    ));
+    assert!(bindings.contains(
+        "  ft_total_supply(): Promise<U128> {\n    return this.transport.view(\"ft_total_supply\", {});"
+    ));
+    assert!(!bindings.contains("ft_resolve_transfer"));
+}
@@ -0,0 +1,77 @@
+//! The JSON arguments of the methods of the interface.
+
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, NearToken};
+
+use crate::ft_core::FeeMode;
+
+/// The arguments of `ft_transfer`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtTransferArgs {
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub memo: Option<String>,
+    pub valid_until: Option<U64>,
+    pub min_received: Option<U128>,
+    pub fee_mode: Option<FeeMode>,
+}
+
+/// The arguments of `ft_transfer_call`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtTransferCallArgs {
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub memo: Option<String>,
+    pub msg: String,
+    pub valid_until: Option<U64>,
+    pub min_received: Option<U128>,
+    pub fee_mode: Option<FeeMode>,
+}
+
+/// The arguments of `ft_balance_of`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtBalanceOfArgs {
+    pub account_id: AccountId,
+}
+
+/// The arguments of `ft_on_transfer`, as received by the receiver of `ft_transfer_call`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtOnTransferArgs {
+    pub sender_id: AccountId,
+    pub amount: U128,
+    pub msg: String,
+}
+
+/// The arguments of `storage_deposit`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageDepositArgs {
+    pub account_id: Option<AccountId>,
+    pub registration_only: Option<bool>,
+}
+
+/// The arguments of `storage_withdraw`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageWithdrawArgs {
+    pub amount: Option<NearToken>,
+}
+
+/// The arguments of `storage_unregister`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageUnregisterArgs {
+    pub force: Option<bool>,
+}
+
+/// The arguments of `storage_balance_of`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceOfArgs {
+    pub account_id: AccountId,
+}
@@ -0,0 +1,25 @@
+//! The interface of the near-ft contract, for the contracts calling it.
+//!
+//! Depending on `near-ft` itself would link the whole contract, its exported methods included,
+//! into the caller's wasm. This crate only holds what a caller needs: the `#[ext_contract]`
+//! traits of the NEP-141 core and of the NEP-145 storage management, the receiver trait a
+//! contract implements to accept `ft_transfer_call`, and the types of their arguments and
+//! results. The contract implements these very traits, so the two can't drift apart.
+//!
+//! ```ignore
+//! use near_ft_interface::ext_ft_core;
+//!
+//! ext_ft_core::ext(token_id)
+//!     .with_attached_deposit(NearToken::from_yoctonear(1))
+//!     .ft_transfer(receiver_id, U128(100), None, None, None, None);
+//! ```
+//!
+//! The structs of `args` are the JSON arguments of the same methods, for the calls made without
+//! the traits, such as the function call actions of a batch.
+
+pub mod args;
+pub mod ft_core;
+pub mod storage;
+
+pub use crate::ft_core::*;
+pub use crate::storage::*;
@@ -0,0 +1,112 @@
+//! The NEP-145 storage management methods.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{ext_contract, AccountId, NearSchema, NearToken};
+
+// The structure that will be returned for the methods:
+// * `storage_deposit`
+// * `storage_withdraw`
+// * `storage_balance_of`
+// The `total` and `available` values are string representations of unsigned
+// 128-bit integers showing the balance of a specific account in yoctoⓃ.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    pub total: NearToken,
+    pub available: NearToken,
+}
+
+// The below structure will be returned for the method `storage_balance_bounds`.
+// Both `min` and `max` are string representations of unsigned 128-bit integers.
+//
+// `min` is the amount of tokens required to start using this contract at all
+// (eg to register with the contract). If a new contract user attaches `min`
+// NEAR to a `storage_deposit` call, subsequent calls to `storage_balance_of`
+// for this user must show their `total` equal to `min` and `available=0` .
+//
+// A contract may implement `max` equal to `min` if it only charges for initial
+// registration, and does not adjust per-user storage over time. A contract
+// which implements `max` must refund deposits that would increase a user's
+// storage balance beyond this amount.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    pub min: NearToken,
+    pub max: Option<NearToken>,
+}
+
+#[ext_contract(ext_storage_management)]
+pub trait StorageManagement {
+    /************************************/
+    /* CHANGE METHODS on fungible token */
+    /************************************/
+    // Payable method that receives an attached deposit of Ⓝ for a given account.
+    //
+    // If `account_id` is omitted, the deposit MUST go toward predecessor account.
+    // If provided, deposit MUST go toward this account. If invalid, contract MUST
+    // panic.
+    //
+    // If `registration_only=true`, contract MUST refund above the minimum balance
+    // if the account wasn't registered and refund full deposit if already
+    // registered.
+    //
+    // The `storage_balance_of.total` + `attached_deposit` in excess of
+    // `storage_balance_bounds.max` must be refunded to predecessor account.
+    //
+    // Returns the StorageBalance structure showing updated balances.
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance;
+
+    // Withdraw specified amount of available Ⓝ for predecessor account.
+    //
+    // This method is safe to call. It MUST NOT remove data.
+    //
+    // `amount` is sent as a string representing an unsigned 128-bit integer. If
+    // omitted, contract MUST refund full `available` balance. If `amount` exceeds
+    // predecessor account's available balance, contract MUST panic.
+    //
+    // If predecessor account not registered, contract MUST panic.
+    //
+    // MUST require exactly 1 yoctoNEAR attached balance to prevent restricted
+    // function-call access-key call (UX wallet security)
+    //
+    // Returns the StorageBalance structure showing updated balances.
+    fn storage_withdraw(&mut self, amount: Option<NearToken>) -> StorageBalance;
+
+    // Unregisters the predecessor account and returns the storage NEAR deposit.
+    //
+    // If the predecessor account is not registered, the function MUST return
+    // `false` without panic.
+    //
+    // If `force=true` the function SHOULD ignore existing account data, such as
+    // non-zero balances on an FT contract (that is, it should burn such balances),
+    // and close the account. Otherwise, MUST panic if caller has existing account
+    // data, such as a positive registered balance (eg token holdings).
+    //
+    // Burning a balance above `force_unregister_threshold` additionally needs a
+    // `request_force_unregister` call at least `FORCE_UNREGISTER_DELAY_SEC` earlier.
+    //
+    // Requires exactly 1 yoctoⓃ attached.
+    //
+    // Returns `true` iff the account was successfully unregistered.
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool;
+
+    /****************/
+    /* VIEW METHODS */
+    /****************/
+    // Returns minimum and maximum allowed balance amounts to interact with this
+    // contract. See StorageBalanceBounds.
+    fn storage_balance_bounds() -> StorageBalanceBounds;
+
+    // Returns the StorageBalance structure of the valid `account_id`
+    // provided. Must panic if `account_id` is invalid.
+    //
+    // If `account_id` is not registered, must return `null`.
+    fn storage_balance_of(account_id: AccountId) -> Option<StorageBalance>;
+}
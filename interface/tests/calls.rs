@@ -0,0 +1,96 @@
+use near_ft_interface::args::{FtTransferArgs, FtTransferCallArgs, StorageDepositArgs};
+use near_ft_interface::{ext_ft_core, ext_storage_management, FeeMode};
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+// Returns the method name, JSON arguments and deposit of the function call made by a promise.
+fn function_call(promise: near_sdk::Promise) -> (String, serde_json::Value, NearToken) {
+    drop(promise);
+    let receipts = get_created_receipts();
+    match &receipts.last().unwrap().actions[..] {
+        [MockAction::FunctionCallWeight {
+            method_name,
+            args,
+            attached_deposit,
+            ..
+        }] => (
+            String::from_utf8(method_name.clone()).unwrap(),
+            serde_json::from_slice(args).unwrap(),
+            *attached_deposit,
+        ),
+        actions => panic!("Unexpected actions {:?}", actions),
+    }
+}
+
+#[test]
+fn test_ft_transfer_matches_its_args() {
+    testing_env!(VMContextBuilder::new().build());
+    let (method_name, args, deposit) = function_call(
+        ext_ft_core::ext(accounts(0))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .ft_transfer(
+                accounts(1),
+                U128(100),
+                Some("Payout".to_string()),
+                None,
+                None,
+                Some(FeeMode::ReceiverPays),
+            ),
+    );
+    assert_eq!(method_name, "ft_transfer");
+    assert_eq!(deposit, NearToken::from_yoctonear(1));
+    let args: FtTransferArgs = serde_json::from_value(args).unwrap();
+    assert_eq!(
+        args,
+        FtTransferArgs {
+            receiver_id: accounts(1),
+            amount: U128(100),
+            memo: Some("Payout".to_string()),
+            valid_until: None,
+            min_received: None,
+            fee_mode: Some(FeeMode::ReceiverPays),
+        }
+    );
+}
+
+#[test]
+fn test_ft_transfer_call_matches_its_args() {
+    testing_env!(VMContextBuilder::new().build());
+    let (method_name, args, _) = function_call(
+        ext_ft_core::ext(accounts(0))
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .ft_transfer_call(
+                accounts(1),
+                U128(100),
+                None,
+                "stake".to_string(),
+                None,
+                Some(U128(90)),
+                None,
+            ),
+    );
+    assert_eq!(method_name, "ft_transfer_call");
+    let args: FtTransferCallArgs = serde_json::from_value(args).unwrap();
+    assert_eq!(args.msg, "stake");
+    assert_eq!(args.min_received, Some(U128(90)));
+}
+
+#[test]
+fn test_storage_deposit_matches_its_args() {
+    testing_env!(VMContextBuilder::new().build());
+    let (method_name, args, _) = function_call(
+        ext_storage_management::ext(accounts(0))
+            .with_attached_deposit(NearToken::from_millinear(100))
+            .storage_deposit(Some(accounts(1)), Some(true)),
+    );
+    assert_eq!(method_name, "storage_deposit");
+    assert_eq!(
+        serde_json::from_value::<StorageDepositArgs>(args).unwrap(),
+        StorageDepositArgs {
+            account_id: Some(accounts(1)),
+            registration_only: Some(true),
+        }
+    );
+}
@@ -0,0 +1,227 @@
+//! Ownership and roles.
+//!
+//! The ownership is handed over in two steps: the owner proposes an account with
+//! `propose_owner`, which becomes the owner once it calls `accept_ownership`, so the contract
+//! can't be given to an account nobody controls. The owner grants roles delegating the
+//! privileged methods of the mint, pause, freeze, metadata, fee and insurance modules, and holds
+//! every role itself.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Can call `ft_mint` and `ft_burn`.
+    Minter,
+    /// Can pause and unpause the contract.
+    Pauser,
+    /// Can freeze and unfreeze accounts.
+    FreezeAdmin,
+    /// Can update the token metadata.
+    MetadataAdmin,
+    /// Can configure the transfer fees.
+    FeeAdmin,
+    /// Can approve insurance payouts.
+    Adjudicator,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [
+        Role::Minter,
+        Role::Pauser,
+        Role::FreezeAdmin,
+        Role::MetadataAdmin,
+        Role::FeeAdmin,
+        Role::Adjudicator,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Role::Minter => "minter",
+            Role::Pauser => "pauser",
+            Role::FreezeAdmin => "freeze admin",
+            Role::MetadataAdmin => "metadata admin",
+            Role::FeeAdmin => "fee admin",
+            Role::Adjudicator => "adjudicator",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+// The roles granted to an account, as a bit set.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, Default, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct RoleSet(u8);
+
+impl RoleSet {
+    pub fn contains(&self, role: Role) -> bool {
+        self.0 & role.bit() != 0
+    }
+
+    pub fn insert(&mut self, role: Role) {
+        self.0 |= role.bit();
+    }
+
+    pub fn remove(&mut self, role: Role) {
+        self.0 &= !role.bit();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn roles(&self) -> Vec<Role> {
+        Role::ALL
+            .into_iter()
+            .filter(|role| self.contains(*role))
+            .collect()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Proposes `new_owner_id` as the next owner, or cancels the proposal when `None` is passed
+    /// (owner only). The ownership is transferred once the proposed account accepts it.
+    #[payable]
+    pub fn propose_owner(&mut self, new_owner_id: Option<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.pending_owner_id = new_owner_id;
+        self.internal_record_owner_activity();
+    }
+
+    /// Accepts the ownership proposed to the caller.
+    #[payable]
+    pub fn accept_ownership(&mut self) {
+        assert_one_yocto();
+        let caller_id = env::predecessor_account_id();
+        require!(
+            self.pending_owner_id.as_ref() == Some(&caller_id),
+            "The ownership wasn't proposed to the caller"
+        );
+        OwnershipTransferred {
+            old_owner_id: &self.owner_id,
+            new_owner_id: &caller_id,
+        }
+        .emit();
+        self.owner_id = caller_id;
+        self.pending_owner_id = None;
+        self.internal_record_owner_activity();
+    }
+
+    /// Grants a role to an account (owner only).
+    #[payable]
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+        assert_one_yocto();
+        self.assert_owner();
+        let roles = self.account_roles.get(&account_id).unwrap_or_default();
+        if roles.contains(role) {
+            return;
+        }
+        self.internal_grant_role(&account_id, role);
+        RoleGranted {
+            account_id: &account_id,
+            role: &role,
+        }
+        .emit();
+    }
+
+    /// Revokes a role from an account (owner only).
+    #[payable]
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.internal_revoke_role(&account_id, role);
+    }
+
+    /// Gives up one of the caller's roles.
+    #[payable]
+    pub fn renounce_role(&mut self, role: Role) {
+        assert_one_yocto();
+        self.internal_revoke_role(&env::predecessor_account_id(), role);
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    /// Returns the account the ownership was proposed to, if any.
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+
+    /// Returns the roles granted to an account. The owner's implicit roles aren't listed.
+    pub fn get_roles(&self, account_id: AccountId) -> Vec<Role> {
+        self.account_roles
+            .get(&account_id)
+            .unwrap_or_default()
+            .roles()
+    }
+
+    /// Returns whether an account can call the methods of a role, as the owner or a grantee.
+    pub fn has_role(&self, account_id: AccountId, role: Role) -> bool {
+        account_id == self.owner_id
+            || self
+                .account_roles
+                .get(&account_id)
+                .is_some_and(|roles| roles.contains(role))
+    }
+}
+
+impl Contract {
+    /// Internal method for asserting that the predecessor is the owner or was granted `role`.
+    pub(crate) fn assert_role(&self, role: Role) {
+        if !self.has_role(env::predecessor_account_id(), role) {
+            env::panic_str(&format!(
+                "Only the owner and the {}s can call this method",
+                role.name()
+            ));
+        }
+    }
+
+    /// Internal method for granting a role, without logging it.
+    pub(crate) fn internal_grant_role(&mut self, account_id: &AccountId, role: Role) {
+        let mut roles = self.account_roles.get(account_id).unwrap_or_default();
+        roles.insert(role);
+        self.account_roles.insert(account_id, &roles);
+    }
+
+    fn internal_revoke_role(&mut self, account_id: &AccountId, role: Role) {
+        let Some(mut roles) = self.account_roles.get(account_id) else {
+            return;
+        };
+        if !roles.contains(role) {
+            return;
+        }
+        roles.remove(role);
+        if roles.is_empty() {
+            self.account_roles.remove(account_id);
+        } else {
+            self.account_roles.insert(account_id, &roles);
+        }
+        RoleRevoked {
+            account_id,
+            role: &role,
+        }
+        .emit();
+    }
+}
@@ -0,0 +1,70 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LazyOption;
+use near_sdk::env;
+
+use crate::*;
+
+// The rarely-read data of an account, serialized into a blob of its own so that the records
+// read on every transfer (balance, freeze, ...) stay small. The blob is only loaded by the
+// methods needing it, and removed once it's empty. The data written before the blobs existed
+// is read from the records it was kept in until the first update of the blob moves it there.
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct AccountBlob {
+    /// The appeal filed by the account against its freeze, if any.
+    pub freeze_appeal: Option<FreezeAppeal>,
+}
+
+impl AccountBlob {
+    fn is_empty(&self) -> bool {
+        self.freeze_appeal.is_none()
+    }
+}
+
+impl Contract {
+    /// Internal method for loading the blob of an account, or its legacy data if it has none.
+    pub(crate) fn internal_account_blob(&self, account_id: &AccountId) -> AccountBlob {
+        Self::internal_account_blob_slot(account_id)
+            .get()
+            .unwrap_or_else(|| self.internal_legacy_account_blob(account_id))
+    }
+
+    /// Internal method for updating the blob of an account.
+    pub(crate) fn internal_update_account_blob(
+        &mut self,
+        account_id: &AccountId,
+        update: impl FnOnce(&mut AccountBlob),
+    ) {
+        let mut slot = Self::internal_account_blob_slot(account_id);
+        let mut blob = match slot.get() {
+            Some(blob) => blob,
+            None => {
+                let blob = self.internal_legacy_account_blob(account_id);
+                self.internal_upgrade_freeze_record(account_id);
+                blob
+            }
+        };
+        update(&mut blob);
+        if blob.is_empty() {
+            slot.remove();
+        } else {
+            slot.set(&blob);
+        }
+    }
+
+    // The data of an account written before the blobs existed, in the records it was kept in.
+    fn internal_legacy_account_blob(&self, account_id: &AccountId) -> AccountBlob {
+        AccountBlob {
+            freeze_appeal: self.internal_legacy_freeze_appeal(account_id),
+        }
+    }
+
+    fn internal_account_blob_slot(account_id: &AccountId) -> LazyOption<AccountBlob> {
+        LazyOption::new(
+            StorageKey::AccountBlob {
+                account_hash: env::sha256_array(account_id.as_bytes()),
+            },
+            None,
+        )
+    }
+}
@@ -0,0 +1,293 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::versioned::Versioned;
+use crate::*;
+
+pub type AirdropId = u64;
+
+// How the allocations of an airdrop vest. A claim then creates a vesting grant for the claimer,
+// starting at the claim, instead of transferring the tokens.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct AirdropVesting {
+    pub cliff_sec: u64,
+    pub duration_sec: u64,
+    pub transfer_policy: GrantTransferPolicy,
+}
+
+// An airdrop committing to its allocations with a merkle root. Each leaf is
+// `sha256(borsh(index, account_id, amount))` and each inner node `sha256(left || right)`, the
+// bits of `index` telling on which side of its sibling a node is. The allocations are escrowed
+// by the contract account when the airdrop is created, and every account claims its own.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Airdrop {
+    pub merkle_root: Base64VecU8,
+    /// The sum of the allocations. Escrowed at creation.
+    pub total: U128,
+    /// The amount of tokens already claimed.
+    pub claimed: U128,
+    /// Whether the allocations can still be claimed.
+    pub open: bool,
+    /// How the claimed allocations vest, or `None` if they are transferred right away.
+    pub vesting: Option<AirdropVesting>,
+}
+
+/// The airdrops stored before they could vest.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct AirdropV1 {
+    pub merkle_root: Base64VecU8,
+    pub total: U128,
+    pub claimed: U128,
+    pub open: bool,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedAirdrop {
+    V1(AirdropV1),
+    V2(Airdrop),
+}
+
+impl Versioned for Airdrop {
+    type Record = VersionedAirdrop;
+
+    fn from_record(record: VersionedAirdrop) -> Self {
+        match record {
+            VersionedAirdrop::V1(airdrop) => Airdrop {
+                merkle_root: airdrop.merkle_root,
+                total: airdrop.total,
+                claimed: airdrop.claimed,
+                open: airdrop.open,
+                vesting: None,
+            },
+            VersionedAirdrop::V2(airdrop) => airdrop,
+        }
+    }
+
+    fn into_record(self) -> VersionedAirdrop {
+        VersionedAirdrop::V2(self)
+    }
+}
+
+// An allocation of an airdrop, along with the vesting grant its claim created, if any.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AirdropClaimView {
+    pub claimed: bool,
+    pub grant: Option<GrantToken>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Publishes an airdrop and escrows its `total` allocation from the owner's balance. With
+    /// `vesting`, the claimed allocations vest instead of being transferred.
+    #[payable]
+    pub fn create_airdrop(
+        &mut self,
+        merkle_root: Base64VecU8,
+        total: U128,
+        vesting: Option<AirdropVesting>,
+    ) -> AirdropId {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(
+            merkle_root.0.len() == 32,
+            "The merkle root should be 32 bytes long"
+        );
+        if let Some(vesting) = vesting.as_ref() {
+            require!(
+                vesting.duration_sec > 0,
+                "The vesting duration should be positive"
+            );
+            require!(
+                vesting.cliff_sec <= vesting.duration_sec,
+                "The cliff should be shorter than the vesting duration"
+            );
+        }
+        let total = U128(Self::internal_to_shares(total.0));
+        self.internal_transfer(
+            &self.owner_id.clone(),
+            &env::current_account_id(),
+            total.0,
+            Some("Airdrop allocation"),
+        );
+
+        let airdrop_id = self.next_airdrop_id;
+        self.next_airdrop_id += 1;
+        self.airdrops.insert(
+            &airdrop_id,
+            &Airdrop {
+                merkle_root,
+                total,
+                claimed: U128(0),
+                open: true,
+                vesting,
+            },
+        );
+        airdrop_id
+    }
+
+    /// Closes an airdrop and returns its unclaimed allocations to the owner.
+    #[payable]
+    pub fn close_airdrop(&mut self, airdrop_id: AirdropId) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut airdrop = self.internal_get_airdrop(airdrop_id);
+        require!(airdrop.open, "The airdrop is already closed");
+
+        airdrop.open = false;
+        let unclaimed = airdrop.total.0.saturating_sub(airdrop.claimed.0);
+        self.airdrops.insert(&airdrop_id, &airdrop);
+
+        if unclaimed > 0 {
+            self.internal_transfer(
+                &env::current_account_id(),
+                &self.owner_id.clone(),
+                unclaimed,
+                Some("Unclaimed airdrop allocation"),
+            );
+        }
+    }
+
+    /// Claims the caller's allocation of `amount` tokens at `index`, proven by the siblings of
+    /// the nodes on the path from its leaf to the root. Each allocation can only be claimed once.
+    /// If the airdrop vests, the allocation goes to a vesting grant of the caller, which must be
+    /// registered.
+    pub fn claim_airdrop(
+        &mut self,
+        airdrop_id: AirdropId,
+        index: u64,
+        amount: U128,
+        proof: Vec<Base64VecU8>,
+    ) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let mut airdrop = self.internal_get_airdrop(airdrop_id);
+        require!(airdrop.open, "The airdrop is closed");
+        require!(
+            !self.is_airdrop_claimed(airdrop_id, index),
+            "The allocation was already claimed"
+        );
+        require!(
+            Self::internal_airdrop_root(index, &account_id, amount.0, &proof)
+                .is_some_and(|root| root.as_slice() == airdrop.merkle_root.0.as_slice()),
+            "The allocation proof is invalid"
+        );
+
+        let shares = Self::internal_to_shares(amount.0);
+        let claimed = airdrop
+            .claimed
+            .0
+            .checked_add(shares)
+            .filter(|claimed| *claimed <= airdrop.total.0)
+            .unwrap_or_else(|| env::panic_str("The airdrop allocation is exceeded"));
+        airdrop.claimed = U128(claimed);
+        self.airdrops.insert(&airdrop_id, &airdrop);
+        let word = (airdrop_id, index / 64);
+        let bits = self.airdrop_claims.get(&word).unwrap_or(0);
+        self.airdrop_claims
+            .insert(&word, &(bits | 1 << (index % 64)));
+
+        match airdrop.vesting {
+            Some(vesting) => {
+                self.internal_unwrap_balance_of(&account_id);
+                let grant_id = self.internal_create_vesting_grant(
+                    account_id.clone(),
+                    shares,
+                    env::block_timestamp(),
+                    vesting.cliff_sec,
+                    vesting.duration_sec,
+                    vesting.transfer_policy,
+                );
+                self.airdrop_grants.insert(&(airdrop_id, index), &grant_id);
+            }
+            None => self.internal_transfer(
+                &env::current_account_id(),
+                &account_id,
+                shares,
+                Some(&format!("Airdrop {} claim", airdrop_id)),
+            ),
+        }
+        AirdropClaim {
+            airdrop_id,
+            account_id: &account_id,
+            amount: &amount,
+        }
+        .emit();
+        amount
+    }
+
+    /// Returns an airdrop, or `null` if it doesn't exist.
+    pub fn get_airdrop(&self, airdrop_id: AirdropId) -> Option<Airdrop> {
+        self.airdrops.get(&airdrop_id).map(|mut airdrop| {
+            airdrop.total = U128(Self::internal_to_amount(airdrop.total.0));
+            airdrop.claimed = U128(Self::internal_to_amount(airdrop.claimed.0));
+            airdrop
+        })
+    }
+
+    /// Returns whether the allocation at `index` of an airdrop was claimed.
+    pub fn is_airdrop_claimed(&self, airdrop_id: AirdropId, index: u64) -> bool {
+        self.airdrop_claims
+            .get(&(airdrop_id, index / 64))
+            .is_some_and(|bits| bits & 1 << (index % 64) != 0)
+    }
+
+    /// Returns whether the allocation at `index` of an airdrop was claimed, and the current
+    /// state of its vesting grant if the airdrop vests.
+    pub fn get_airdrop_claim(&self, airdrop_id: AirdropId, index: u64) -> AirdropClaimView {
+        AirdropClaimView {
+            claimed: self.is_airdrop_claimed(airdrop_id, index),
+            grant: self
+                .airdrop_grants
+                .get(&(airdrop_id, index))
+                .and_then(|grant_id| {
+                    self.grants
+                        .get(&grant_id)
+                        .map(|grant| Self::internal_grant_token(grant_id, grant))
+                }),
+        }
+    }
+}
+
+impl Contract {
+    /// Internal method for getting an airdrop, panicking if it doesn't exist.
+    pub(crate) fn internal_get_airdrop(&self, airdrop_id: AirdropId) -> Airdrop {
+        self.airdrops
+            .get(&airdrop_id)
+            .unwrap_or_else(|| env::panic_str("The airdrop doesn't exist"))
+    }
+
+    /// Internal method for computing the root a proof leads to from the leaf of an allocation,
+    /// or `None` if the proof is malformed.
+    fn internal_airdrop_root(
+        index: u64,
+        account_id: &AccountId,
+        amount: u128,
+        proof: &[Base64VecU8],
+    ) -> Option<MerkleHash> {
+        if proof.len() > 64 || (proof.len() < 64 && index >> proof.len() != 0) {
+            return None;
+        }
+        let leaf = env::sha256_array(
+            &near_sdk::borsh::to_vec(&(index, account_id, amount)).unwrap_or_else(|_| env::abort()),
+        );
+        proof
+            .iter()
+            .enumerate()
+            .try_fold(leaf, |node, (level, sibling)| {
+                let sibling: MerkleHash = sibling.0.as_slice().try_into().ok()?;
+                Some(if index >> level & 1 == 0 {
+                    Self::internal_hash_pair(&node, &sibling)
+                } else {
+                    Self::internal_hash_pair(&sibling, &node)
+                })
+            })
+    }
+}
@@ -0,0 +1,412 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::UnorderedMap;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema, NearToken, StorageUsage};
+
+use crate::*;
+
+/// The maximum number of allowances that can be set or revoked in a single batch
+const MAX_APPROVAL_BATCH_SIZE: usize = 100;
+/// The shortest time an expiring allowance can be granted for, in nanoseconds (an hour), so
+/// the hygiene bounty can't be farmed with allowances expiring right away.
+const MIN_ALLOWANCE_LIFETIME: u64 = 3_600 * 1_000_000_000;
+
+// An amount a spender is allowed to spend on behalf of an owner, until `expires_at` if set.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Allowance {
+    pub amount: U128,
+    /// The timestamp (in nanoseconds) after which the allowance can't be used anymore.
+    pub expires_at: Option<U64>,
+}
+
+// The pool paying a small bounty to whoever purges expired allowances. Its balance is escrowed
+// by the contract account.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct HygienePool {
+    /// Tokens left in the pool.
+    pub balance: U128,
+    /// The bounty paid for every expired allowance purged.
+    pub bounty_per_allowance: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Allows `spender_id` to spend up to `amount` of the caller's tokens until `expires_at`,
+    /// replacing any previous allowance. An amount of zero revokes the allowance. An expiring
+    /// allowance must last at least an hour.
+    ///
+    /// The caller must be registered. The methods granting allowances charge the storage they
+    /// use to the attached deposit, refunding the rest of it, and the storage is refunded to
+    /// the owner when the allowance is removed.
+    #[payable]
+    pub fn ft_approve(&mut self, spender_id: AccountId, amount: U128, expires_at: Option<U64>) {
+        let owner_id = env::predecessor_account_id();
+        let initial_storage_usage = self.internal_assert_can_approve(&owner_id);
+        Self::internal_assert_allowance_lifetime(expires_at);
+        self.internal_set_allowance(&owner_id, &spender_id, amount.0, expires_at);
+        Self::internal_settle_storage(&owner_id, initial_storage_usage);
+    }
+
+    /// Replaces the allowance of `spender_id` with `new_amount` only if it's still
+    /// `expected_current`, so that a spender can't use the old allowance and then the new one
+    /// by front-running the change.
+    #[payable]
+    pub fn ft_approve_from_to(
+        &mut self,
+        spender_id: AccountId,
+        expected_current: U128,
+        new_amount: U128,
+        expires_at: Option<U64>,
+    ) {
+        let owner_id = env::predecessor_account_id();
+        let initial_storage_usage = self.internal_assert_can_approve(&owner_id);
+        Self::internal_assert_allowance_lifetime(expires_at);
+        require!(
+            self.ft_allowance(owner_id.clone(), spender_id.clone()).0 == expected_current.0,
+            "The allowance changed"
+        );
+        self.internal_set_allowance(&owner_id, &spender_id, new_amount.0, expires_at);
+        Self::internal_settle_storage(&owner_id, initial_storage_usage);
+    }
+
+    /// Raises the allowance of `spender_id` by `amount`, keeping its expiry unless `expires_at`
+    /// sets a new one. An expired allowance starts over from zero, and needs a new expiry.
+    #[payable]
+    pub fn ft_increase_allowance(
+        &mut self,
+        spender_id: AccountId,
+        amount: U128,
+        expires_at: Option<U64>,
+    ) {
+        let owner_id = env::predecessor_account_id();
+        let initial_storage_usage = self.internal_assert_can_approve(&owner_id);
+        Self::internal_assert_allowance_lifetime(expires_at);
+        let allowance = self
+            .allowances
+            .get(&owner_id)
+            .and_then(|allowances| allowances.get(&spender_id));
+        let live = allowance.as_ref().filter(|allowance| {
+            !Self::internal_allowance_expired(allowance, env::block_timestamp())
+        });
+        require!(
+            allowance.is_none() || live.is_some() || expires_at.is_some(),
+            "The allowance has expired, a new expiry should be set"
+        );
+        let increased = live
+            .map_or(0, |allowance| allowance.amount.0)
+            .checked_add(amount.0)
+            .unwrap_or_else(|| env::panic_str("Allowance overflow"));
+        let expires_at = expires_at.or_else(|| live.and_then(|allowance| allowance.expires_at));
+        self.internal_set_allowance(&owner_id, &spender_id, increased, expires_at);
+        Self::internal_settle_storage(&owner_id, initial_storage_usage);
+    }
+
+    /// Lowers the allowance of `spender_id` by `amount`, keeping its expiry. The allowance is
+    /// revoked when it drops to zero.
+    #[payable]
+    pub fn ft_decrease_allowance(&mut self, spender_id: AccountId, amount: U128) {
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+        let allowance = self
+            .internal_get_allowance(&owner_id, &spender_id)
+            .unwrap_or_else(|| env::panic_str("There is no allowance"));
+        let decreased = allowance.amount.0.saturating_sub(amount.0);
+        let initial_storage_usage = env::storage_usage();
+        self.internal_set_allowance(&owner_id, &spender_id, decreased, allowance.expires_at);
+        Self::internal_refund_storage(&owner_id, initial_storage_usage);
+    }
+
+    /// Transfers `amount` of `owner_id`'s tokens to `receiver_id` on behalf of the owner. The
+    /// allowance of the caller is consumed before the transfer is made, and the owner pays the
+    /// transfer fee.
+    #[payable]
+    pub fn ft_transfer_from(
+        &mut self,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let spender_id = env::predecessor_account_id();
+        let allowance = self
+            .internal_get_allowance(&owner_id, &spender_id)
+            .unwrap_or_else(|| env::panic_str("There is no allowance"));
+        let remaining = allowance
+            .amount
+            .0
+            .checked_sub(amount.0)
+            .unwrap_or_else(|| env::panic_str("The allowance is too low"));
+        let initial_storage_usage = env::storage_usage();
+        self.internal_set_allowance(&owner_id, &spender_id, remaining, allowance.expires_at);
+        Self::internal_refund_storage(&owner_id, initial_storage_usage);
+        self.internal_transfer_with_fee(
+            &owner_id,
+            &receiver_id,
+            Self::internal_to_shares(amount.0),
+            memo.as_deref(),
+        );
+    }
+
+    /// Sets the allowance of every given spender at once, all expiring at `expires_at`.
+    #[payable]
+    pub fn ft_approve_batch(&mut self, approvals: Vec<(AccountId, U128)>, expires_at: Option<U64>) {
+        require!(
+            approvals.len() <= MAX_APPROVAL_BATCH_SIZE,
+            "Too many approvals in a single batch"
+        );
+        let owner_id = env::predecessor_account_id();
+        let initial_storage_usage = self.internal_assert_can_approve(&owner_id);
+        Self::internal_assert_allowance_lifetime(expires_at);
+        for (spender_id, amount) in approvals.iter() {
+            self.internal_set_allowance(&owner_id, spender_id, amount.0, expires_at);
+        }
+        Self::internal_settle_storage(&owner_id, initial_storage_usage);
+    }
+
+    /// Revokes the allowance of `spender_id` over the caller's tokens.
+    #[payable]
+    pub fn ft_revoke(&mut self, spender_id: AccountId) {
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+        let initial_storage_usage = env::storage_usage();
+        self.internal_set_allowance(&owner_id, &spender_id, 0, None);
+        Self::internal_refund_storage(&owner_id, initial_storage_usage);
+    }
+
+    /// Revokes the allowances of every given spender at once.
+    #[payable]
+    pub fn ft_revoke_batch(&mut self, spender_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        require!(
+            spender_ids.len() <= MAX_APPROVAL_BATCH_SIZE,
+            "Too many revocations in a single batch"
+        );
+        let owner_id = env::predecessor_account_id();
+        let initial_storage_usage = env::storage_usage();
+        for spender_id in spender_ids.iter() {
+            self.internal_set_allowance(&owner_id, spender_id, 0, None);
+        }
+        Self::internal_refund_storage(&owner_id, initial_storage_usage);
+    }
+
+    /// Purges up to `limit` expired allowances granted by `owner_id`. Anyone can call this
+    /// method and registered callers other than the owner earn the hygiene bounty for every
+    /// allowance purged, as long as the pool can pay it. The storage released is refunded to the
+    /// owner. Returns the number of allowances purged.
+    pub fn sweep_expired_allowances(&mut self, owner_id: AccountId, limit: Option<u64>) -> u64 {
+        let Some(mut allowances) = self.allowances.get(&owner_id) else {
+            return 0;
+        };
+        let now = env::block_timestamp();
+        let expired: Vec<AccountId> = allowances
+            .iter()
+            .filter(|(_, allowance)| Self::internal_allowance_expired(allowance, now))
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(spender_id, _)| spender_id)
+            .collect();
+        if expired.is_empty() {
+            return 0;
+        }
+
+        let initial_storage_usage = env::storage_usage();
+        for spender_id in expired.iter() {
+            allowances.remove(spender_id);
+        }
+        if allowances.is_empty() {
+            self.allowances.remove(&owner_id);
+        } else {
+            self.allowances.insert(&owner_id, &allowances);
+        }
+        Self::internal_refund_storage(&owner_id, initial_storage_usage);
+        let revocations: Vec<AllowanceRevoked> = expired
+            .iter()
+            .map(|spender_id| AllowanceRevoked {
+                owner_id: &owner_id,
+                spender_id,
+            })
+            .collect();
+        AllowanceRevoked::emit_many(&revocations);
+
+        let caller_id = env::predecessor_account_id();
+        let bounty = std::cmp::min(
+            self.hygiene_pool.balance.0,
+            self.hygiene_pool
+                .bounty_per_allowance
+                .0
+                .saturating_mul(expired.len() as u128),
+        );
+        if bounty > 0 && caller_id != owner_id && self.accounts.contains_key(&caller_id) {
+            self.hygiene_pool.balance = U128(self.hygiene_pool.balance.0.saturating_sub(bounty));
+            self.internal_transfer(
+                &env::current_account_id(),
+                &caller_id,
+                bounty,
+                Some("Allowance hygiene bounty"),
+            );
+        }
+        expired.len() as u64
+    }
+
+    /// Moves `amount` tokens from the caller into the hygiene pool.
+    #[payable]
+    pub fn fund_hygiene_pool(&mut self, amount: U128) {
+        assert_one_yocto();
+        self.internal_transfer(
+            &env::predecessor_account_id(),
+            &env::current_account_id(),
+            amount.0,
+            Some("Allowance hygiene pool funding"),
+        );
+        self.hygiene_pool.balance = U128(
+            self.hygiene_pool
+                .balance
+                .0
+                .checked_add(amount.0)
+                .unwrap_or_else(|| env::panic_str("Balance overflow")),
+        );
+    }
+
+    /// Sets the bounty paid for every expired allowance purged.
+    #[payable]
+    pub fn set_hygiene_bounty(&mut self, bounty_per_allowance: U128) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.hygiene_pool.bounty_per_allowance = bounty_per_allowance;
+    }
+
+    /// Returns the balance and the bounty of the hygiene pool.
+    pub fn get_hygiene_pool(&self) -> HygienePool {
+        self.hygiene_pool.clone()
+    }
+
+    /// Returns the amount `spender_id` is allowed to spend on behalf of `owner_id` right now.
+    pub fn ft_allowance(&self, owner_id: AccountId, spender_id: AccountId) -> U128 {
+        self.internal_get_allowance(&owner_id, &spender_id)
+            .map_or(U128(0), |allowance| allowance.amount)
+    }
+
+    /// Paginates through the allowances granted by an account, including the expired ones
+    /// that weren't purged yet.
+    pub fn allowances_of(
+        &self,
+        owner_id: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, Allowance)> {
+        let Some(allowances) = self.allowances.get(&owner_id) else {
+            return vec![];
+        };
+        allowances
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Internal method for checking that `owner_id` can grant allowances: the call must be
+    /// signed with a full access key and the owner must be registered. Returns the storage
+    /// usage to settle the call against.
+    fn internal_assert_can_approve(&self, owner_id: &AccountId) -> StorageUsage {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        self.internal_registered_balance_of(owner_id);
+        env::storage_usage()
+    }
+
+    /// Internal method for checking that a new allowance expiry leaves the allowance at least
+    /// `MIN_ALLOWANCE_LIFETIME`.
+    fn internal_assert_allowance_lifetime(expires_at: Option<U64>) {
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at.0 >= env::block_timestamp().saturating_add(MIN_ALLOWANCE_LIFETIME),
+                "An expiring allowance should last at least an hour"
+            );
+        }
+    }
+
+    /// Internal method for setting (or, with a zero amount, removing) an allowance.
+    pub(crate) fn internal_set_allowance(
+        &mut self,
+        owner_id: &AccountId,
+        spender_id: &AccountId,
+        amount: u128,
+        expires_at: Option<U64>,
+    ) {
+        require!(owner_id != spender_id, "The owner can't approve itself");
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at.0 > env::block_timestamp(),
+                "The allowance expiry should be in the future"
+            );
+        }
+        let mut allowances = self.allowances.get(owner_id).unwrap_or_else(|| {
+            UnorderedMap::new(StorageKey::AllowancesPerOwner {
+                account_hash: env::sha256_array(owner_id.as_bytes()),
+            })
+        });
+
+        if amount == 0 {
+            if allowances.remove(spender_id).is_none() {
+                return;
+            }
+            AllowanceRevoked {
+                owner_id,
+                spender_id,
+            }
+            .emit();
+        } else {
+            allowances.insert(
+                spender_id,
+                &Allowance {
+                    amount: U128(amount),
+                    expires_at,
+                },
+            );
+            AllowanceSet {
+                owner_id,
+                spender_id,
+                amount: &U128(amount),
+            }
+            .emit();
+        }
+
+        if allowances.is_empty() {
+            self.allowances.remove(owner_id);
+        } else {
+            self.allowances.insert(owner_id, &allowances);
+        }
+    }
+
+    /// Internal method for getting the allowance of `spender_id` over the tokens of `owner_id`,
+    /// unless it has expired.
+    pub(crate) fn internal_get_allowance(
+        &self,
+        owner_id: &AccountId,
+        spender_id: &AccountId,
+    ) -> Option<Allowance> {
+        self.allowances
+            .get(owner_id)
+            .and_then(|allowances| allowances.get(spender_id))
+            .filter(|allowance| {
+                !Self::internal_allowance_expired(allowance, env::block_timestamp())
+            })
+    }
+
+    /// Internal method for checking whether an allowance has expired at `now`. An allowance
+    /// can't be used from its expiry on.
+    pub(crate) fn internal_allowance_expired(allowance: &Allowance, now: u64) -> bool {
+        allowance
+            .expires_at
+            .is_some_and(|expires_at| now >= expires_at.0)
+    }
+}
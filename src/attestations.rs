@@ -0,0 +1,174 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, near_bindgen, require, Gas, NearSchema, Promise,
+    PromiseResult,
+};
+
+use crate::*;
+
+const GAS_FOR_IS_ATTESTED: Gas = Gas::from_tgas(10);
+const GAS_FOR_ON_ATTESTATION_CHECKED: Gas = Gas::from_tgas(5);
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+#[ext_contract(ext_attestation_registry)]
+pub trait AttestationRegistry {
+    /// Returns whether `account_id` holds a valid attestation of the given credential
+    /// (proof of personhood, accreditation, ...).
+    fn is_attested(&self, account_id: AccountId, credential: String) -> bool;
+}
+
+// The attestation registry consulted before the gated actions, and the actions it gates.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct AttestationConfig {
+    /// The registry contract implementing `is_attested`.
+    pub registry_id: AccountId,
+    /// The credential accounts must hold.
+    pub credential: String,
+    /// How long (in seconds) the result of a registry check is trusted.
+    pub ttl_sec: u64,
+    /// Transfers above this amount need the sender to be attested.
+    pub large_transfer_threshold: Option<U128>,
+    /// Whether buying bonds needs the buyer to be attested.
+    pub gate_bonds: bool,
+}
+
+// The cached result of the last registry check of an account.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct CachedAttestation {
+    pub attested: bool,
+    /// The timestamp (in nanoseconds) of the check.
+    pub checked_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the attestation registry and the actions it gates, or removes every gate when
+    /// `None` is passed. Changing the registry or the credential drops the cached results.
+    #[payable]
+    pub fn set_attestation_config(&mut self, config: Option<AttestationConfig>) {
+        assert_one_yocto();
+        self.assert_owner();
+        let same_credential = match (self.attestation_config.as_ref(), config.as_ref()) {
+            (Some(current), Some(config)) => {
+                current.registry_id == config.registry_id && current.credential == config.credential
+            }
+            _ => false,
+        };
+        if !same_credential {
+            self.attestation_epoch += 1;
+        }
+        self.attestation_config = config;
+    }
+
+    /// Checks an account against the attestation registry and caches the result. Anyone can
+    /// call this method, usually the account itself before a gated action.
+    pub fn refresh_attestation(&mut self, account_id: AccountId) -> Promise {
+        let config = self
+            .attestation_config
+            .clone()
+            .unwrap_or_else(|| env::panic_str("There is no attestation registry"));
+        self.internal_promise_budget()
+            .call(GAS_FOR_IS_ATTESTED, ZERO_TOKEN)
+            .call(GAS_FOR_ON_ATTESTATION_CHECKED, ZERO_TOKEN)
+            .check();
+        ext_attestation_registry::ext(config.registry_id)
+            .with_static_gas(GAS_FOR_IS_ATTESTED)
+            .is_attested(account_id.clone(), config.credential)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_ATTESTATION_CHECKED)
+                    .on_attestation_checked(account_id, self.attestation_epoch),
+            )
+    }
+
+    /// Callback caching the result of a registry check. A failed check counts as not attested.
+    #[private]
+    pub fn on_attestation_checked(&mut self, account_id: AccountId, epoch: u64) -> bool {
+        let attested = matches!(
+            env::promise_result(0),
+            PromiseResult::Successful(value)
+                if near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(false)
+        );
+        // The registry or the credential changed while the check was in flight.
+        if epoch != self.attestation_epoch {
+            return false;
+        }
+        self.attestation_cache.insert(
+            &account_id,
+            &(
+                epoch,
+                CachedAttestation {
+                    attested,
+                    checked_at: U64(env::block_timestamp()),
+                },
+            ),
+        );
+        attested
+    }
+
+    /// Returns the attestation registry configuration, if any.
+    pub fn get_attestation_config(&self) -> Option<AttestationConfig> {
+        self.attestation_config.clone()
+    }
+
+    /// Returns the cached result of the last registry check of an account, if it's still valid.
+    pub fn get_cached_attestation(&self, account_id: AccountId) -> Option<CachedAttestation> {
+        self.internal_cached_attestation(&account_id)
+    }
+}
+
+impl Contract {
+    /// Internal method for asserting that an account is attested, according to a cached
+    /// registry check that didn't outlive its TTL.
+    pub(crate) fn internal_assert_attested(&self, account_id: &AccountId) {
+        require!(
+            self.internal_cached_attestation(account_id)
+                .is_some_and(|attestation| attestation.attested),
+            "The account is not attested, call refresh_attestation first"
+        );
+    }
+
+    /// Internal method for asserting that a transfer of `amount` tokens doesn't need the
+    /// sender to be attested, or that it is.
+    pub(crate) fn internal_assert_attested_for_transfer(
+        &self,
+        sender_id: &AccountId,
+        amount: u128,
+    ) {
+        let threshold = self
+            .attestation_config
+            .as_ref()
+            .and_then(|config| config.large_transfer_threshold);
+        if threshold.is_some_and(|threshold| amount > threshold.0) {
+            self.internal_assert_attested(sender_id);
+        }
+    }
+
+    /// Internal method for asserting that an account is attested when bonds are gated.
+    pub(crate) fn internal_assert_attested_for_bonds(&self, account_id: &AccountId) {
+        if self
+            .attestation_config
+            .as_ref()
+            .is_some_and(|config| config.gate_bonds)
+        {
+            self.internal_assert_attested(account_id);
+        }
+    }
+
+    fn internal_cached_attestation(&self, account_id: &AccountId) -> Option<CachedAttestation> {
+        let config = self.attestation_config.as_ref()?;
+        let (epoch, attestation) = self.attestation_cache.get(account_id)?;
+        let expires_at = attestation
+            .checked_at
+            .0
+            .saturating_add(config.ttl_sec.saturating_mul(NANOS_PER_SECOND));
+        (epoch == self.attestation_epoch && env::block_timestamp() < expires_at)
+            .then_some(attestation)
+    }
+}
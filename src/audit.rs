@@ -0,0 +1,146 @@
+//! Accounting audit mode.
+//!
+//! Built with the `audit` feature, the contract checks its accounting on every balance change:
+//! `internal_deposit` and `internal_withdraw` keep a running sum of the balances, which can
+//! never go negative nor, with the streamed tokens, exceed the total supply. Mints raise the
+//! total supply before depositing and burns lower it after withdrawing, so the bound holds
+//! between the two halves of every operation too. The audit also keeps the set of the accounts
+//! whose balance changed, so that `ft_total_registered_supply` can recompute the sum of the
+//! balances from scratch and compare it with the running sum and the total supply. Accounts
+//! registered before the audit mode was deployed are added with `audit_track_accounts`.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LazyOption, UnorderedSet};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, NearSchema};
+
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct AuditState {
+    /// The running sum of the balances of the tracked accounts.
+    registered_supply: u128,
+    /// The accounts whose balance changed since the audit mode was deployed.
+    accounts: UnorderedSet<AccountId>,
+}
+
+impl Default for AuditState {
+    fn default() -> Self {
+        Self {
+            registered_supply: 0,
+            accounts: UnorderedSet::new(StorageKey::Audit(AuditKey::Accounts)),
+        }
+    }
+}
+
+// The supply recomputed from the balances of the tracked accounts.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RegisteredSupplyView {
+    /// The sum of the balances of the tracked accounts, read one by one.
+    pub recomputed: U128,
+    /// The running sum kept by `internal_deposit` and `internal_withdraw`.
+    pub running: U128,
+    /// The tokens held in the streams rather than in an account.
+    pub streamed: U128,
+    pub total_supply: U128,
+    /// Whether the recomputed sum matches the running sum, and with the streamed tokens, the
+    /// total supply.
+    pub consistent: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Recomputes the sum of the balances of the tracked accounts and compares it with the
+    /// running sum and the total supply.
+    pub fn ft_total_registered_supply(&self) -> RegisteredSupplyView {
+        let state = Self::internal_audit_state();
+        let recomputed = state
+            .accounts
+            .iter()
+            .map(|account_id| self.accounts.get(&account_id).unwrap_or(0))
+            .fold(0u128, u128::saturating_add);
+        RegisteredSupplyView {
+            recomputed: U128(recomputed),
+            running: U128(state.registered_supply),
+            streamed: U128(self.streamed_supply),
+            total_supply: U128(self.total_supply),
+            consistent: recomputed == state.registered_supply
+                && recomputed.checked_add(self.streamed_supply) == Some(self.total_supply),
+        }
+    }
+
+    /// Adds accounts registered before the audit mode was deployed to the audit, with their
+    /// current balance (owner only). Accounts already tracked are skipped.
+    #[payable]
+    pub fn audit_track_accounts(&mut self, account_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut state = Self::internal_audit_state();
+        for account_id in account_ids {
+            let Some(balance) = self.accounts.get(&account_id) else {
+                continue;
+            };
+            if state.accounts.insert(&account_id) {
+                state.registered_supply = state.registered_supply.saturating_add(balance);
+            }
+        }
+        Self::internal_set_audit_state(&state);
+    }
+}
+
+impl Contract {
+    /// Internal method for auditing a deposit of `amount` into an account, called by
+    /// `internal_deposit` once the balance is updated.
+    pub(crate) fn internal_audit_deposit(&self, account_id: &AccountId, amount: u128) {
+        let mut state = Self::internal_audit_state();
+        if !state.accounts.insert(account_id) {
+            state.registered_supply = state
+                .registered_supply
+                .checked_add(amount)
+                .unwrap_or_else(|| env::panic_str("Audit: the balances overflow"));
+        } else {
+            // A newly tracked account brings its whole balance along.
+            state.registered_supply = state
+                .registered_supply
+                .saturating_add(self.accounts.get(account_id).unwrap_or(0));
+        }
+        if state
+            .registered_supply
+            .checked_add(self.streamed_supply)
+            .is_none_or(|supply| supply > self.total_supply)
+        {
+            env::panic_str("Audit: the balances exceed the total supply");
+        }
+        Self::internal_set_audit_state(&state);
+    }
+
+    /// Internal method for auditing a withdrawal of `amount` from an account, called by
+    /// `internal_withdraw` once the balance is updated.
+    pub(crate) fn internal_audit_withdraw(&self, account_id: &AccountId, amount: u128) {
+        let mut state = Self::internal_audit_state();
+        if !state.accounts.insert(account_id) {
+            state.registered_supply = state
+                .registered_supply
+                .checked_sub(amount)
+                .unwrap_or_else(|| env::panic_str("Audit: the balances went negative"));
+        } else {
+            state.registered_supply = state
+                .registered_supply
+                .saturating_add(self.accounts.get(account_id).unwrap_or(0));
+        }
+        Self::internal_set_audit_state(&state);
+    }
+
+    fn internal_audit_state() -> AuditState {
+        LazyOption::<AuditState>::new(StorageKey::Audit(AuditKey::AuditState), None)
+            .get()
+            .unwrap_or_default()
+    }
+
+    fn internal_set_audit_state(state: &AuditState) {
+        LazyOption::<AuditState>::new(StorageKey::Audit(AuditKey::AuditState), None).set(state);
+    }
+}
@@ -0,0 +1,350 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, Gas, NearSchema, Promise};
+
+use crate::ft_core::ext_ft_core;
+use crate::internal::pro_rata;
+use crate::versioned::versioned_record;
+use crate::*;
+
+const GAS_FOR_BOND_PROCEEDS_TRANSFER: Gas = Gas::from_tgas(10);
+const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+
+pub type BondMarketId = u64;
+
+// A bond market sells this token at a discount against NEAR (`quote_token_id == None`)
+// or a whitelisted fungible token. Payouts are escrowed by the contract account when the
+// market is created and vest linearly over `vesting_days` after each purchase.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BondMarket {
+    /// The token accepted as payment, or `None` for NEAR.
+    pub quote_token_id: Option<AccountId>,
+    /// Tokens paid out for every `price_denominator` units of the quote token, before the discount.
+    pub price_numerator: U128,
+    /// See `price_numerator`.
+    pub price_denominator: U128,
+    /// The discount in basis points added on top of the base payout.
+    pub discount_bps: u16,
+    /// The number of days a bond payout vests over.
+    pub vesting_days: u32,
+    /// The maximum amount of tokens this market can pay out. Escrowed at creation.
+    pub capacity: U128,
+    /// The amount of tokens already sold through this market.
+    pub sold: U128,
+    /// The quote token (or NEAR) received and not yet withdrawn by the owner.
+    pub proceeds: U128,
+    /// Whether the market still accepts new bonds.
+    pub open: bool,
+}
+
+versioned_record!(VersionedBondMarket, BondMarket);
+
+// A bond bought by an account, vesting linearly between `start` and `end`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BondPosition {
+    pub market_id: BondMarketId,
+    pub payout: U128,
+    pub claimed: U128,
+    pub start: U64,
+    pub end: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Opens a bond market and escrows `capacity` tokens from the owner's balance.
+    #[payable]
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_bond_market(
+        &mut self,
+        quote_token_id: Option<AccountId>,
+        price_numerator: U128,
+        price_denominator: U128,
+        discount_bps: u16,
+        vesting_days: u32,
+        capacity: U128,
+    ) -> BondMarketId {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(
+            price_numerator.0 > 0 && price_denominator.0 > 0,
+            "The bond price should be a positive ratio"
+        );
+        require!(
+            (discount_bps as u128) < BPS_DENOMINATOR,
+            "The discount should be lower than 100%"
+        );
+
+        // Escrow the whole capacity so that every bond sold is backed by existing tokens.
+        let capacity = U128(Self::internal_to_shares(capacity.0));
+        self.internal_transfer(
+            &self.owner_id.clone(),
+            &env::current_account_id(),
+            capacity.0,
+            Some("Bond market capacity"),
+        );
+
+        let market_id = self.next_bond_market_id;
+        self.next_bond_market_id += 1;
+        self.bond_markets.insert(
+            &market_id,
+            &BondMarket {
+                quote_token_id,
+                price_numerator,
+                price_denominator,
+                discount_bps,
+                vesting_days,
+                capacity,
+                sold: U128(0),
+                proceeds: U128(0),
+                open: true,
+            },
+        );
+        market_id
+    }
+
+    /// Closes a market and returns its unsold capacity to the owner.
+    #[payable]
+    pub fn close_bond_market(&mut self, market_id: BondMarketId) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut market = self.internal_get_bond_market(market_id);
+        require!(market.open, "The bond market is already closed");
+
+        market.open = false;
+        let unsold = market.capacity.0.saturating_sub(market.sold.0);
+        self.bond_markets.insert(&market_id, &market);
+
+        if unsold > 0 {
+            self.internal_transfer(
+                &env::current_account_id(),
+                &self.owner_id.clone(),
+                unsold,
+                Some("Unsold bond capacity"),
+            );
+        }
+    }
+
+    /// Buys a bond from a NEAR-quoted market with the attached deposit. Panics if the
+    /// payout would be lower than `min_payout`.
+    #[payable]
+    pub fn bond_with_near(
+        &mut self,
+        market_id: BondMarketId,
+        min_payout: Option<U128>,
+    ) -> BondPosition {
+        let market = self.internal_get_bond_market(market_id);
+        require!(
+            market.quote_token_id.is_none(),
+            "This bond market doesn't accept NEAR"
+        );
+        let amount_in = env::attached_deposit().as_yoctonear();
+        self.internal_bond(
+            &env::predecessor_account_id(),
+            market_id,
+            amount_in,
+            min_payout.map(|min| min.0),
+        )
+    }
+
+    /// Claims every vested payout of the caller's bonds and returns the claimed amount.
+    pub fn claim_bonds(&mut self) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let mut positions = self.bond_positions.get(&account_id).unwrap_or_default();
+        let now = env::block_timestamp();
+
+        let mut claimed = 0u128;
+        for position in positions.iter_mut() {
+            let claimable =
+                Self::internal_bond_vested(position, now).saturating_sub(position.claimed.0);
+            position.claimed = U128(position.claimed.0.saturating_add(claimable));
+            claimed = claimed.saturating_add(claimable);
+        }
+        // Fully claimed bonds don't need to be kept around.
+        positions.retain(|position| position.claimed.0 < position.payout.0);
+        if positions.is_empty() {
+            self.bond_positions.remove(&account_id);
+        } else {
+            self.bond_positions.insert(&account_id, &positions);
+        }
+
+        if claimed > 0 {
+            self.supply.locked_in_vesting = self.supply.locked_in_vesting.saturating_sub(claimed);
+            self.internal_transfer(
+                &env::current_account_id(),
+                &account_id,
+                claimed,
+                Some("Bond claim"),
+            );
+            BondClaim {
+                account_id: &account_id,
+                amount: &U128(Self::internal_to_amount(claimed)),
+            }
+            .emit();
+        }
+        U128(Self::internal_to_amount(claimed))
+    }
+
+    /// Sends the proceeds collected by a market to the owner.
+    #[payable]
+    pub fn withdraw_bond_proceeds(&mut self, market_id: BondMarketId) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut market = self.internal_get_bond_market(market_id);
+        let proceeds = market.proceeds.0;
+        require!(proceeds > 0, "There are no proceeds to withdraw");
+        if market.quote_token_id.is_some() {
+            self.internal_promise_budget()
+                .call(GAS_FOR_BOND_PROCEEDS_TRANSFER, NearToken::from_yoctonear(1))
+                .check();
+        }
+        market.proceeds = U128(0);
+        self.bond_markets.insert(&market_id, &market);
+
+        match market.quote_token_id {
+            None => {
+                Promise::new(self.owner_id.clone()).transfer(NearToken::from_yoctonear(proceeds))
+            }
+            Some(quote_token_id) => ext_ft_core::ext(quote_token_id)
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_BOND_PROCEEDS_TRANSFER)
+                .ft_transfer(
+                    self.owner_id.clone(),
+                    U128(proceeds),
+                    Some(format!("Proceeds of bond market {}", market_id)),
+                    None,
+                    None,
+                    None,
+                ),
+        }
+    }
+
+    /// Returns a bond market, or `null` if it doesn't exist.
+    pub fn get_bond_market(&self, market_id: BondMarketId) -> Option<BondMarket> {
+        self.bond_markets.get(&market_id).map(|mut market| {
+            market.capacity = U128(Self::internal_to_amount(market.capacity.0));
+            market.sold = U128(Self::internal_to_amount(market.sold.0));
+            market
+        })
+    }
+
+    /// Quotes the payout of a bond for `amount_in` units of the market's quote token.
+    pub fn quote_bond(&self, market_id: BondMarketId, amount_in: U128) -> U128 {
+        U128(Self::internal_bond_payout(
+            &self.internal_get_bond_market(market_id),
+            amount_in.0,
+        ))
+    }
+
+    /// Returns the bonds of an account along with the amount that can be claimed right now.
+    pub fn get_bond_positions(&self, account_id: AccountId) -> (Vec<BondPosition>, U128) {
+        let mut positions = self.bond_positions.get(&account_id).unwrap_or_default();
+        let now = env::block_timestamp();
+        let claimable = positions.iter().fold(0u128, |total, position| {
+            total.saturating_add(
+                Self::internal_bond_vested(position, now).saturating_sub(position.claimed.0),
+            )
+        });
+        for position in positions.iter_mut() {
+            position.payout = U128(Self::internal_to_amount(position.payout.0));
+            position.claimed = U128(Self::internal_to_amount(position.claimed.0));
+        }
+        (positions, U128(Self::internal_to_amount(claimable)))
+    }
+}
+
+impl Contract {
+    /// Internal method for getting a bond market, panicking if it doesn't exist.
+    pub(crate) fn internal_get_bond_market(&self, market_id: BondMarketId) -> BondMarket {
+        self.bond_markets
+            .get(&market_id)
+            .unwrap_or_else(|| env::panic_str("The bond market doesn't exist"))
+    }
+
+    /// Internal method for selling a bond to `account_id` for `amount_in` units of the quote token.
+    /// The caller is responsible for having received the quote token.
+    pub(crate) fn internal_bond(
+        &mut self,
+        account_id: &AccountId,
+        market_id: BondMarketId,
+        amount_in: u128,
+        min_payout: Option<u128>,
+    ) -> BondPosition {
+        // Bonds vest to registered accounts only, so the claim can't fail later on.
+        self.internal_unwrap_balance_of(account_id);
+        self.internal_assert_attested_for_bonds(account_id);
+        let mut market = self.internal_get_bond_market(market_id);
+        require!(market.open, "The bond market is closed");
+
+        let payout_amount = Self::internal_bond_payout(&market, amount_in);
+        if let Some(min_payout) = min_payout {
+            require!(
+                payout_amount >= min_payout,
+                "The bond payout is lower than the minimum"
+            );
+        }
+        let payout = Self::internal_to_shares(payout_amount);
+        require!(payout > 0, "The bond payout is zero");
+        let sold = market.sold.0.saturating_add(payout);
+        require!(
+            sold <= market.capacity.0,
+            "The bond market capacity is exceeded"
+        );
+
+        market.sold = U128(sold);
+        self.supply.locked_in_vesting = self.supply.locked_in_vesting.saturating_add(payout);
+        market.proceeds = U128(market.proceeds.0.saturating_add(amount_in));
+        self.bond_markets.insert(&market_id, &market);
+
+        let start = env::block_timestamp();
+        let mut position = BondPosition {
+            market_id,
+            payout: U128(payout),
+            claimed: U128(0),
+            start: U64(start),
+            end: U64(start + market.vesting_days as u64 * NANOS_PER_DAY),
+        };
+        let mut positions = self.bond_positions.get(account_id).unwrap_or_default();
+        positions.push(position.clone());
+        self.bond_positions.insert(account_id, &positions);
+
+        BondPurchase {
+            account_id,
+            market_id,
+            amount_in: &U128(amount_in),
+            payout: &U128(payout_amount),
+        }
+        .emit();
+        position.payout = U128(payout_amount);
+        position
+    }
+
+    /// Internal method for computing the discounted payout for `amount_in` units of the quote token.
+    pub(crate) fn internal_bond_payout(market: &BondMarket, amount_in: u128) -> u128 {
+        amount_in
+            .checked_mul(market.price_numerator.0)
+            .and_then(|value| value.checked_mul(BPS_DENOMINATOR + market.discount_bps as u128))
+            .unwrap_or_else(|| env::panic_str("Bond payout overflow"))
+            / market.price_denominator.0
+            / BPS_DENOMINATOR
+    }
+
+    /// Internal method for computing how much of a bond has vested at `now`.
+    pub(crate) fn internal_bond_vested(position: &BondPosition, now: u64) -> u128 {
+        if now >= position.end.0 {
+            return position.payout.0;
+        }
+        if now <= position.start.0 {
+            return 0;
+        }
+        pro_rata(
+            position.payout.0,
+            (now - position.start.0) as u128,
+            (position.end.0 - position.start.0) as u128,
+        )
+    }
+}
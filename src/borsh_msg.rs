@@ -0,0 +1,84 @@
+//! Borsh-encoded `msg` payloads.
+//!
+//! `ft_transfer_call` passes its `msg` as a JSON string, which receivers then parse. Receivers
+//! handling many transfers can opt in to Borsh instead: `ft_transfer_call_borsh` then calls their
+//! `ft_on_transfer_borsh` with Borsh arguments and the raw `msg` bytes, which is cheaper to
+//! serialize and to parse. The unused amount is still returned in JSON, like `ft_on_transfer`
+//! does, so both calls are resolved by `ft_resolve_transfer`.
+
+// `ft_transfer_call_borsh` takes the arguments of `ft_transfer_call`.
+#![allow(clippy::too_many_arguments)]
+
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::{env, ext_contract, near_bindgen, require, PromiseOrValue};
+
+use crate::*;
+
+#[ext_contract(ext_ft_borsh_receiver)]
+pub trait FungibleTokenBorshReceiver {
+    /// Called by the token contract after `ft_transfer_call_borsh` was initiated by
+    /// `sender_id`, like `ft_on_transfer` but with Borsh arguments. Returns the amount of unused
+    /// tokens that should be returned to the sender, in a decimal string representation.
+    fn ft_on_transfer_borsh(
+        &mut self,
+        #[serializer(borsh)] sender_id: AccountId,
+        #[serializer(borsh)] amount: u128,
+        #[serializer(borsh)] msg: Vec<u8>,
+    ) -> PromiseOrValue<U128>;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Makes the caller receive the `msg` of the transfer calls in Borsh, through
+    /// `ft_on_transfer_borsh`, or stops it. The caller must be registered.
+    pub fn set_borsh_msg_receiver(&mut self, enabled: bool) {
+        let account_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&account_id);
+        if enabled {
+            self.borsh_msg_receivers.insert(&account_id);
+        } else {
+            self.borsh_msg_receivers.remove(&account_id);
+        }
+    }
+
+    /// Returns whether an account receives the `msg` of the transfer calls in Borsh.
+    pub fn is_borsh_msg_receiver(&self, account_id: AccountId) -> bool {
+        self.borsh_msg_receivers.contains(&account_id)
+    }
+
+    /// Same as `ft_transfer_call`, for a receiver that opted in to Borsh, `msg` being passed
+    /// as is to its `ft_on_transfer_borsh`.
+    #[payable]
+    pub fn ft_transfer_call_borsh(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: Base64VecU8,
+        valid_until: Option<U64>,
+        min_received: Option<U128>,
+        fee_mode: Option<FeeMode>,
+    ) -> PromiseOrValue<U128> {
+        require!(
+            self.borsh_msg_receivers.contains(&receiver_id),
+            "The receiver doesn't accept Borsh messages"
+        );
+        let (sender_id, amount) = self.internal_start_transfer_call(
+            &receiver_id,
+            amount,
+            memo,
+            valid_until,
+            min_received,
+            fee_mode,
+        );
+        ext_ft_borsh_receiver::ext(receiver_id.clone())
+            .with_static_gas(GAS_FOR_FT_TRANSFER_CALL)
+            .ft_on_transfer_borsh(sender_id.clone(), Self::internal_to_amount(amount), msg.0)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
+                    .ft_resolve_transfer(&sender_id, receiver_id, U128(amount)),
+            )
+            .into()
+    }
+}
@@ -0,0 +1,174 @@
+//! Bug bounty.
+//!
+//! Anyone can fund the bounty pool with their tokens, which the contract account holds and the
+//! owner can't take back: they can only be paid out to researchers. A researcher first commits
+//! to a vulnerability with `submit_disclosure`, passing `sha256(borsh(researcher_id, details))`,
+//! which timestamps the finding without revealing it. Once the issue is fixed, they reveal the
+//! details with `reveal_and_claim`, and the owner pays the bounty out of the pool or rejects it.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, borsh, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+pub type DisclosureId = u64;
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, PartialEq, Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum DisclosureStatus {
+    /// Only the commitment is known.
+    Committed,
+    /// The details were revealed and wait for the owner's decision.
+    Revealed,
+    Paid,
+    Rejected,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Disclosure {
+    pub researcher_id: AccountId,
+    /// `sha256(borsh(researcher_id, details))`.
+    pub hash: Base64VecU8,
+    /// The timestamp (in nanoseconds) of the commitment.
+    pub submitted_at: U64,
+    /// The details, once revealed.
+    pub details: Option<String>,
+    /// The bounty paid, if any.
+    pub payout: Option<U128>,
+    pub status: DisclosureStatus,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Moves `amount` of the caller's tokens to the bounty pool.
+    #[payable]
+    pub fn fund_bounty_pool(&mut self, amount: U128) -> U128 {
+        assert_one_yocto();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let amount = Self::internal_to_shares(amount.0);
+        self.internal_transfer(
+            &env::predecessor_account_id(),
+            &env::current_account_id(),
+            amount,
+            Some("Bug bounty"),
+        );
+        self.bounty_pool = self
+            .bounty_pool
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+        U128(Self::internal_to_amount(self.bounty_pool))
+    }
+
+    /// Commits the caller to a vulnerability with the hash of its details, without revealing
+    /// them. The caller must be registered to be paid.
+    pub fn submit_disclosure(&mut self, hash: Base64VecU8) -> DisclosureId {
+        require!(hash.0.len() == 32, "The hash should be 32 bytes long");
+        let researcher_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&researcher_id);
+        let disclosure_id = self.next_disclosure_id;
+        self.next_disclosure_id += 1;
+        self.disclosures.insert(
+            &disclosure_id,
+            &Disclosure {
+                researcher_id,
+                hash,
+                submitted_at: U64(env::block_timestamp()),
+                details: None,
+                payout: None,
+                status: DisclosureStatus::Committed,
+            },
+        );
+        disclosure_id
+    }
+
+    /// Reveals the details of the caller's disclosure, which must match its commitment, and
+    /// claims the bounty.
+    pub fn reveal_and_claim(&mut self, disclosure_id: DisclosureId, details: String) {
+        let mut disclosure = self.internal_get_disclosure(disclosure_id);
+        require!(
+            env::predecessor_account_id() == disclosure.researcher_id,
+            "Only the researcher can reveal the disclosure"
+        );
+        require!(
+            disclosure.status == DisclosureStatus::Committed,
+            "The disclosure was already revealed"
+        );
+        let data =
+            borsh::to_vec(&(&disclosure.researcher_id, &details)).unwrap_or_else(|_| env::abort());
+        require!(
+            env::sha256_array(&data).as_slice() == disclosure.hash.0.as_slice(),
+            "The details don't match the commitment"
+        );
+        disclosure.details = Some(details);
+        disclosure.status = DisclosureStatus::Revealed;
+        self.disclosures.insert(&disclosure_id, &disclosure);
+    }
+
+    /// Pays `amount` out of the pool for a revealed disclosure (owner only).
+    #[payable]
+    pub fn approve_bounty_payout(&mut self, disclosure_id: DisclosureId, amount: U128) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut disclosure = self.internal_get_revealed_disclosure(disclosure_id);
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let shares = Self::internal_to_shares(amount.0);
+        self.bounty_pool = self
+            .bounty_pool
+            .checked_sub(shares)
+            .unwrap_or_else(|| env::panic_str("The bounty pool is too low"));
+        self.internal_transfer(
+            &env::current_account_id(),
+            &disclosure.researcher_id,
+            shares,
+            Some("Bug bounty"),
+        );
+        disclosure.payout = Some(amount);
+        disclosure.status = DisclosureStatus::Paid;
+        self.disclosures.insert(&disclosure_id, &disclosure);
+    }
+
+    /// Rejects a revealed disclosure, e.g. a duplicate or an out of scope one (owner only).
+    #[payable]
+    pub fn reject_disclosure(&mut self, disclosure_id: DisclosureId) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut disclosure = self.internal_get_revealed_disclosure(disclosure_id);
+        disclosure.status = DisclosureStatus::Rejected;
+        self.disclosures.insert(&disclosure_id, &disclosure);
+    }
+
+    /// Returns a disclosure, or `null` if it doesn't exist.
+    pub fn get_disclosure(&self, disclosure_id: DisclosureId) -> Option<Disclosure> {
+        self.disclosures.get(&disclosure_id)
+    }
+
+    /// Returns the tokens left in the bounty pool.
+    pub fn get_bounty_pool(&self) -> U128 {
+        U128(Self::internal_to_amount(self.bounty_pool))
+    }
+}
+
+impl Contract {
+    fn internal_get_disclosure(&self, disclosure_id: DisclosureId) -> Disclosure {
+        self.disclosures
+            .get(&disclosure_id)
+            .unwrap_or_else(|| env::panic_str("The disclosure doesn't exist"))
+    }
+
+    fn internal_get_revealed_disclosure(&self, disclosure_id: DisclosureId) -> Disclosure {
+        let disclosure = self.internal_get_disclosure(disclosure_id);
+        require!(
+            disclosure.status == DisclosureStatus::Revealed,
+            "The disclosure isn't waiting for a decision"
+        );
+        disclosure
+    }
+}
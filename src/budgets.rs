@@ -0,0 +1,173 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// The maximum number of budgets an account can define
+const MAX_BUDGETS_PER_ACCOUNT: usize = 20;
+
+/// The maximum length of a budget label
+const MAX_BUDGET_LABEL_LEN: usize = 32;
+
+// A labeled spending budget of an organizational account. Once an account defines a budget,
+// every transfer it sends must reference one of its budgets by starting its memo with
+// `#<label>` (e.g. `#payroll March salaries`), and is counted against that budget's cap for
+// the current period.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Budget {
+    pub label: String,
+    /// The maximum amount that can be spent per period.
+    pub cap: U128,
+    /// The length of a period in seconds.
+    pub period_sec: u64,
+    /// The timestamp (in nanoseconds) when the first period started.
+    pub start_at: U64,
+    /// The period `spent` refers to, counted from `start_at`.
+    pub period: u64,
+    /// The amount spent during `period`.
+    pub spent: U128,
+    /// The amount spent since the budget was created.
+    pub total_spent: U128,
+}
+
+// The report returned for a budget, as of the current period.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BudgetReport {
+    #[serde(flatten)]
+    pub budget: Budget,
+    /// The amount that can still be spent during the current period.
+    pub remaining: U128,
+    /// The timestamp (in nanoseconds) when the current period ends.
+    pub period_ends_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creates a budget of the caller, or updates the cap and period of an existing one. The
+    /// amount spent during the current period is kept when the period length doesn't change.
+    #[payable]
+    pub fn set_budget(&mut self, label: String, cap: U128, period_sec: u64) {
+        assert_one_yocto();
+        require!(
+            !label.is_empty()
+                && label.len() <= MAX_BUDGET_LABEL_LEN
+                && !label.contains(char::is_whitespace),
+            "The budget label should be a single word of at most 32 bytes"
+        );
+        require!(period_sec > 0, "The budget period should be positive");
+
+        let account_id = env::predecessor_account_id();
+        let mut budgets = self.budgets.get(&account_id).unwrap_or_default();
+        match budgets.iter_mut().find(|budget| budget.label == label) {
+            Some(budget) => {
+                if budget.period_sec != period_sec {
+                    budget.period_sec = period_sec;
+                    budget.start_at = U64(env::block_timestamp());
+                    budget.period = 0;
+                    budget.spent = U128(0);
+                }
+                budget.cap = cap;
+            }
+            None => {
+                require!(budgets.len() < MAX_BUDGETS_PER_ACCOUNT, "Too many budgets");
+                budgets.push(Budget {
+                    label,
+                    cap,
+                    period_sec,
+                    start_at: U64(env::block_timestamp()),
+                    period: 0,
+                    spent: U128(0),
+                    total_spent: U128(0),
+                });
+            }
+        }
+        self.budgets.insert(&account_id, &budgets);
+    }
+
+    /// Removes a budget of the caller. Transfers don't need to reference a budget anymore once
+    /// the last one is removed.
+    #[payable]
+    pub fn remove_budget(&mut self, label: String) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut budgets = self.budgets.get(&account_id).unwrap_or_default();
+        let count = budgets.len();
+        budgets.retain(|budget| budget.label != label);
+        require!(budgets.len() < count, "The budget doesn't exist");
+        if budgets.is_empty() {
+            self.budgets.remove(&account_id);
+        } else {
+            self.budgets.insert(&account_id, &budgets);
+        }
+    }
+
+    /// Returns the budgets of an account along with what is left of them for the current period.
+    pub fn get_budgets(&self, account_id: AccountId) -> Vec<BudgetReport> {
+        let now = env::block_timestamp();
+        self.budgets
+            .get(&account_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|mut budget| {
+                Self::internal_roll_budget(&mut budget, now);
+                let period_duration = budget.period_sec.saturating_mul(NANOS_PER_SECOND);
+                BudgetReport {
+                    remaining: U128(budget.cap.0.saturating_sub(budget.spent.0)),
+                    period_ends_at: U64(
+                        budget.start_at.0 + (budget.period + 1).saturating_mul(period_duration)
+                    ),
+                    budget,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Internal method for counting a transfer of `amount` tokens against the budget referenced
+    /// by its memo. Does nothing for accounts without budgets.
+    pub(crate) fn internal_spend_budget(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+    ) {
+        let Some(mut budgets) = self.budgets.get(account_id) else {
+            return;
+        };
+        let label = memo
+            .and_then(|memo| memo.split_whitespace().next())
+            .and_then(|tag| tag.strip_prefix('#'))
+            .unwrap_or_else(|| {
+                env::panic_str("Transfers of this account must reference a budget in their memo")
+            });
+        let budget = budgets
+            .iter_mut()
+            .find(|budget| budget.label == label)
+            .unwrap_or_else(|| env::panic_str("The budget doesn't exist"));
+
+        Self::internal_roll_budget(budget, env::block_timestamp());
+        budget.spent = U128(budget.spent.0.saturating_add(amount));
+        require!(budget.spent.0 <= budget.cap.0, "The budget is exceeded");
+        budget.total_spent = U128(budget.total_spent.0.saturating_add(amount));
+        self.budgets.insert(account_id, &budgets);
+    }
+
+    /// Internal method for starting a new period (and resetting the amount spent) once the
+    /// current one is over.
+    fn internal_roll_budget(budget: &mut Budget, now: u64) {
+        let period_duration = budget.period_sec.saturating_mul(NANOS_PER_SECOND);
+        let period = now.saturating_sub(budget.start_at.0) / period_duration;
+        if period > budget.period {
+            budget.period = period;
+            budget.spent = U128(0);
+        }
+    }
+}
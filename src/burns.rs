@@ -0,0 +1,102 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::versioned::versioned_record;
+use crate::*;
+
+/// The number of accounts kept on the burn leaderboard
+const BURN_LEADERBOARD_SIZE: usize = 100;
+
+pub type BurnReceiptId = u64;
+
+// The proof that an account burned tokens, for burn-to-access mechanics. Other contracts can
+// check it with a `verify_burn` view call.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BurnReceipt {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub memo: Option<String>,
+    /// The timestamp (in nanoseconds) of the burn.
+    pub burned_at: U64,
+}
+
+versioned_record!(VersionedBurnReceipt, BurnReceipt);
+
+#[near_bindgen]
+impl Contract {
+    /// Burns `amount` of the caller's tokens and returns the id of the receipt proving it. The
+    /// receipt records the amount of tokens burned, whatever the index moves to later.
+    #[payable]
+    pub fn burn_with_receipt(&mut self, amount: U128, memo: Option<String>) -> BurnReceiptId {
+        assert_one_yocto();
+        let amount = amount.0;
+        require!(amount > 0, "The amount should be a positive number");
+        let account_id = env::predecessor_account_id();
+        self.internal_burn(
+            &account_id,
+            Self::internal_to_shares(amount),
+            memo.as_deref(),
+        );
+
+        let receipt_id = self.next_burn_receipt_id;
+        self.next_burn_receipt_id += 1;
+        self.burn_receipts.insert(
+            &receipt_id,
+            &BurnReceipt {
+                account_id,
+                amount: U128(amount),
+                memo,
+                burned_at: U64(env::block_timestamp()),
+            },
+        );
+        receipt_id
+    }
+
+    /// Returns the receipt with the given id, or `null` if no such burn happened.
+    pub fn verify_burn(&self, receipt_id: BurnReceiptId) -> Option<BurnReceipt> {
+        self.burn_receipts.get(&receipt_id)
+    }
+
+    /// Returns the total amount of tokens burned by an account, fee burns included.
+    pub fn burned_by(&self, account_id: AccountId) -> U128 {
+        U128(self.burned_by_account.get(&account_id).unwrap_or(0))
+    }
+
+    /// Returns up to `limit` accounts that burned the most tokens, in decreasing order.
+    pub fn top_burners(&self, limit: Option<u64>) -> Vec<(AccountId, U128)> {
+        let mut leaderboard = self.burn_leaderboard.get().unwrap_or_default();
+        leaderboard.truncate(limit.unwrap_or(10) as usize);
+        leaderboard
+            .into_iter()
+            .map(|(account_id, burned)| (account_id, U128(burned)))
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Internal method for adding a burn of `amount` tokens to the total of an account and
+    /// updating the leaderboard. Called on every burn.
+    pub(crate) fn internal_track_burn(&mut self, account_id: &AccountId, amount: u128) {
+        let total = self.burned_by(account_id.clone()).0.saturating_add(amount);
+        self.burned_by_account.insert(account_id, &total);
+
+        let mut leaderboard = self.burn_leaderboard.get().unwrap_or_default();
+        let is_full = leaderboard.len() >= BURN_LEADERBOARD_SIZE;
+        if is_full
+            && leaderboard
+                .last()
+                .is_some_and(|(_, lowest)| total <= *lowest)
+        {
+            return;
+        }
+        leaderboard.retain(|(burner_id, _)| burner_id != account_id);
+        let position = leaderboard.partition_point(|(_, burned)| *burned >= total);
+        leaderboard.insert(position, (account_id.clone(), total));
+        leaderboard.truncate(BURN_LEADERBOARD_SIZE);
+        self.burn_leaderboard.set(&leaderboard);
+    }
+}
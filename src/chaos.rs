@@ -0,0 +1,100 @@
+//! Failure injection for the sandbox tests, only compiled with the `chaos` feature.
+//!
+//! A copy of the contract built with the feature can be used as the receiver of
+//! `ft_transfer_call`, misbehaving as told by the `{"chaos": ...}` message. This exercises every
+//! branch of `ft_resolve_transfer` without writing a dedicated receiver contract.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, Gas, NearSchema, Promise, PromiseOrValue};
+
+use crate::ft_core::ext_ft_core;
+use crate::*;
+
+const GAS_FOR_CHAOS_CALLBACK: Gas = Gas::from_tgas(5);
+const GAS_FOR_CHAOS_FORWARD: Gas = Gas::from_tgas(10);
+
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum ChaosBehavior {
+    /// Panics, so the whole amount must be refunded.
+    Panic,
+    /// Burns all the gas it was given, like a receiver timing out.
+    ExhaustGas,
+    /// Returns `unused` as the unused amount, which may be more than what was sent.
+    Refund { unused: U128 },
+    /// Returns a value that isn't an amount.
+    InvalidValue,
+    /// Sends the tokens away to `to`, then asks for all of them back.
+    ForwardAndRefund { to: AccountId },
+    /// Deletes the receiver account in favor of `beneficiary_id` and asks for a full refund.
+    DeleteSelf { beneficiary_id: AccountId },
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns something `ft_resolve_transfer` can't parse as an amount.
+    #[private]
+    pub fn chaos_invalid_value(&self) -> String {
+        "not an amount".to_string()
+    }
+
+    /// Asks for the whole amount back once it has been forwarded.
+    #[private]
+    pub fn chaos_refund_all(&self, amount: U128) -> U128 {
+        amount
+    }
+}
+
+impl Contract {
+    /// Internal method for misbehaving as the receiver of `amount` tokens from the token
+    /// contract `token_id`.
+    pub(crate) fn internal_chaos_on_transfer(
+        &mut self,
+        token_id: AccountId,
+        amount: U128,
+        behavior: ChaosBehavior,
+    ) -> PromiseOrValue<U128> {
+        match behavior {
+            ChaosBehavior::Panic => env::panic_str("Chaos receiver panicked"),
+            ChaosBehavior::ExhaustGas => {
+                let mut counter: u64 = 0;
+                loop {
+                    counter = counter.wrapping_add(env::used_gas().as_gas());
+                    env::storage_has_key(&counter.to_le_bytes());
+                }
+            }
+            ChaosBehavior::Refund { unused } => PromiseOrValue::Value(unused),
+            ChaosBehavior::InvalidValue => {
+                self.internal_promise_budget()
+                    .call(GAS_FOR_CHAOS_CALLBACK, ZERO_TOKEN)
+                    .check();
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_CHAOS_CALLBACK)
+                    .chaos_invalid_value()
+                    .into()
+            }
+            ChaosBehavior::ForwardAndRefund { to } => {
+                self.internal_promise_budget()
+                    .call(GAS_FOR_CHAOS_FORWARD, NearToken::from_yoctonear(1))
+                    .call(GAS_FOR_CHAOS_CALLBACK, ZERO_TOKEN)
+                    .check();
+                ext_ft_core::ext(token_id)
+                    .with_attached_deposit(NearToken::from_yoctonear(1))
+                    .with_static_gas(GAS_FOR_CHAOS_FORWARD)
+                    .ft_transfer(to, amount, None, None, None, None)
+                    .then(
+                        Self::ext(env::current_account_id())
+                            .with_static_gas(GAS_FOR_CHAOS_CALLBACK)
+                            .chaos_refund_all(amount),
+                    )
+                    .into()
+            }
+            ChaosBehavior::DeleteSelf { beneficiary_id } => {
+                Promise::new(env::current_account_id()).delete_account(beneficiary_id);
+                PromiseOrValue::Value(amount)
+            }
+        }
+    }
+}
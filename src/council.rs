@@ -0,0 +1,279 @@
+//! Council proposals for the privileged actions.
+//!
+//! A single owner key is too risky for a token with real value. The owner sets a council and
+//! hands the ownership over to the contract account itself: the owner proposes it with
+//! `propose_owner`, and the council accepts with an `AcceptOwnership` proposal. From then on, the privileged actions are proposed by a
+//! council member and executed once `threshold` members approved them, before the proposal
+//! expires. The contract executes an action by calling its own owner method, so that the
+//! action goes through the same checks and logs the same events as when the owner calls it.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{
+    assert_one_yocto, env, is_promise_success, near_bindgen, require, Gas, NearSchema, Promise,
+};
+
+use crate::*;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+const GAS_FOR_COUNCIL_ACTION: Gas = Gas::from_tgas(30);
+const GAS_FOR_RESOLVE_COUNCIL_ACTION: Gas = Gas::from_tgas(5);
+
+pub type CouncilProposalId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct CouncilConfig {
+    /// The accounts allowed to propose and approve actions.
+    pub members: Vec<AccountId>,
+    /// The number of members approving a proposal for it to be executable.
+    pub threshold: u32,
+    /// How long a proposal can be approved and executed, in seconds.
+    pub proposal_ttl_sec: u64,
+}
+
+// The privileged actions the council executes, each calling the owner method of the same name.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum CouncilAction {
+    FtMint {
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    },
+    SetPauseFlags {
+        flags: PauseFlags,
+    },
+    RenameToken {
+        name: Option<String>,
+        symbol: Option<String>,
+    },
+    SetIcon {
+        icon: Option<String>,
+    },
+    ProposeOwner {
+        new_owner_id: Option<AccountId>,
+    },
+    AcceptOwnership,
+    SetCouncil {
+        config: Option<CouncilConfig>,
+    },
+}
+
+impl CouncilAction {
+    /// The method executing the action, along with its JSON arguments.
+    fn method_call(&self) -> (&'static str, near_sdk::serde_json::Value) {
+        match self {
+            CouncilAction::FtMint {
+                receiver_id,
+                amount,
+                memo,
+            } => (
+                "ft_mint",
+                json!({ "receiver_id": receiver_id, "amount": amount, "memo": memo }),
+            ),
+            CouncilAction::SetPauseFlags { flags } => {
+                ("set_pause_flags", json!({ "flags": flags }))
+            }
+            CouncilAction::RenameToken { name, symbol } => {
+                ("rename_token", json!({ "name": name, "symbol": symbol }))
+            }
+            CouncilAction::SetIcon { icon } => ("set_icon", json!({ "icon": icon })),
+            CouncilAction::ProposeOwner { new_owner_id } => {
+                ("propose_owner", json!({ "new_owner_id": new_owner_id }))
+            }
+            CouncilAction::AcceptOwnership => ("accept_ownership", json!({})),
+            CouncilAction::SetCouncil { config } => ("set_council", json!({ "config": config })),
+        }
+    }
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, PartialEq, Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum CouncilProposalStatus {
+    Pending,
+    /// The action is being executed.
+    InProgress,
+    Executed,
+    /// The execution of the action failed, e.g. because the contract isn't the owner.
+    Failed,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct CouncilProposal {
+    pub action: CouncilAction,
+    /// The members who approved the proposal, starting with its proposer.
+    pub approvals: Vec<AccountId>,
+    /// The timestamp (in nanoseconds) after which the proposal can't be approved nor executed.
+    pub expires_at: U64,
+    pub status: CouncilProposalStatus,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the council, or removes it when `None` is passed (owner only).
+    #[payable]
+    pub fn set_council(&mut self, config: Option<CouncilConfig>) {
+        assert_one_yocto();
+        self.assert_owner();
+        if let Some(config) = &config {
+            require!(
+                config.threshold > 0 && config.threshold as usize <= config.members.len(),
+                "The threshold should be between 1 and the size of the council"
+            );
+            require!(
+                config.proposal_ttl_sec > 0,
+                "The proposal TTL should be positive"
+            );
+        }
+        self.council = config;
+    }
+
+    /// Proposes an action, approved by the caller (council members only).
+    #[payable]
+    pub fn propose_council_action(&mut self, action: CouncilAction) -> CouncilProposalId {
+        assert_one_yocto();
+        let config = self.internal_assert_council_member();
+        let proposal_id = self.next_council_proposal_id;
+        self.next_council_proposal_id += 1;
+        self.council_proposals.insert(
+            &proposal_id,
+            &CouncilProposal {
+                action,
+                approvals: vec![env::predecessor_account_id()],
+                expires_at: U64(env::block_timestamp() + config.proposal_ttl_sec * NANOS_PER_SEC),
+                status: CouncilProposalStatus::Pending,
+            },
+        );
+        proposal_id
+    }
+
+    /// Approves a pending proposal (council members only).
+    #[payable]
+    pub fn approve_council_proposal(&mut self, proposal_id: CouncilProposalId) {
+        assert_one_yocto();
+        self.internal_assert_council_member();
+        let mut proposal = self.internal_get_pending_council_proposal(proposal_id);
+        let account_id = env::predecessor_account_id();
+        require!(
+            !proposal.approvals.contains(&account_id),
+            "The account already approved the proposal"
+        );
+        proposal.approvals.push(account_id);
+        self.council_proposals.insert(&proposal_id, &proposal);
+    }
+
+    /// Executes a pending proposal approved by enough council members. Anyone can call it.
+    pub fn execute_council_proposal(&mut self, proposal_id: CouncilProposalId) -> Promise {
+        let config = self
+            .council
+            .clone()
+            .unwrap_or_else(|| env::panic_str("The council isn't set"));
+        let mut proposal = self.internal_get_pending_council_proposal(proposal_id);
+        // Members removed since they approved don't count anymore.
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|account_id| config.members.contains(account_id))
+            .count();
+        require!(
+            approvals >= config.threshold as usize,
+            "The proposal doesn't have enough approvals"
+        );
+        self.internal_promise_budget()
+            .call(GAS_FOR_COUNCIL_ACTION, NearToken::from_yoctonear(1))
+            .call(GAS_FOR_RESOLVE_COUNCIL_ACTION, ZERO_TOKEN)
+            .check();
+        proposal.status = CouncilProposalStatus::InProgress;
+        self.council_proposals.insert(&proposal_id, &proposal);
+
+        let (method_name, args) = proposal.action.method_call();
+        Promise::new(env::current_account_id())
+            .function_call(
+                method_name.to_string(),
+                args.to_string().into_bytes(),
+                NearToken::from_yoctonear(1),
+                GAS_FOR_COUNCIL_ACTION,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_COUNCIL_ACTION)
+                    .council_resolve_proposal(proposal_id),
+            )
+    }
+
+    /// Records whether the action of a proposal was executed.
+    #[private]
+    pub fn council_resolve_proposal(
+        &mut self,
+        proposal_id: CouncilProposalId,
+    ) -> CouncilProposalStatus {
+        let mut proposal = self
+            .council_proposals
+            .get(&proposal_id)
+            .unwrap_or_else(|| env::abort());
+        proposal.status = if is_promise_success() {
+            CouncilProposalStatus::Executed
+        } else {
+            CouncilProposalStatus::Failed
+        };
+        self.council_proposals.insert(&proposal_id, &proposal);
+        proposal.status
+    }
+
+    /// Returns the council, or `null` if it isn't set.
+    pub fn get_council(&self) -> Option<CouncilConfig> {
+        self.council.clone()
+    }
+
+    /// Returns a council proposal, or `null` if it doesn't exist.
+    pub fn get_council_proposal(&self, proposal_id: CouncilProposalId) -> Option<CouncilProposal> {
+        self.council_proposals.get(&proposal_id)
+    }
+}
+
+impl Contract {
+    /// Internal method for asserting that the caller is a council member, returning the council.
+    fn internal_assert_council_member(&self) -> CouncilConfig {
+        let config = self
+            .council
+            .clone()
+            .unwrap_or_else(|| env::panic_str("The council isn't set"));
+        require!(
+            config.members.contains(&env::predecessor_account_id()),
+            "Only a council member can call this method"
+        );
+        config
+    }
+
+    /// Internal method for getting a proposal that can still be approved and executed.
+    fn internal_get_pending_council_proposal(
+        &self,
+        proposal_id: CouncilProposalId,
+    ) -> CouncilProposal {
+        let proposal = self
+            .council_proposals
+            .get(&proposal_id)
+            .unwrap_or_else(|| env::panic_str("The proposal doesn't exist"));
+        require!(
+            proposal.status == CouncilProposalStatus::Pending,
+            "The proposal isn't pending"
+        );
+        require!(
+            env::block_timestamp() <= proposal.expires_at.0,
+            "The proposal has expired"
+        );
+        proposal
+    }
+}
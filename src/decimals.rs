@@ -0,0 +1,106 @@
+//! Conversions between this token's amounts and their bridged representation.
+//!
+//! Bridged copies of the token usually have fewer decimals (18 on EVM chains, 6 on some
+//! others), so moving an amount across truncates its lowest digits. The conversions below
+//! return that truncated dust explicitly instead of rounding it away, and
+//! `ft_transfer_to_bridge` sends it to the dust account.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+// An amount converted to a bridged representation, along with the dust that can't be bridged.
+#[derive(Serialize, Deserialize, NearSchema, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BridgedAmount {
+    /// The amount in the smallest unit of the bridged representation.
+    pub amount: U128,
+    /// The part of the amount, in the smallest unit of this token, lost to the conversion.
+    pub dust: U128,
+}
+
+/// Converts `amount` from `decimals` to `bridged_decimals`, which can't be more than
+/// `decimals`, returning the bridged amount and the dust left over.
+pub fn scale_to_bridged(amount: u128, decimals: u8, bridged_decimals: u8) -> (u128, u128) {
+    let factor = decimals_factor(decimals, bridged_decimals);
+    (amount / factor, amount % factor)
+}
+
+/// Converts `bridged_amount` from `bridged_decimals` back to `decimals`. The conversion is
+/// exact, but panics if the amount doesn't fit.
+pub fn scale_from_bridged(bridged_amount: u128, decimals: u8, bridged_decimals: u8) -> u128 {
+    bridged_amount
+        .checked_mul(decimals_factor(decimals, bridged_decimals))
+        .unwrap_or_else(|| env::panic_str("Bridged amount overflow"))
+}
+
+fn decimals_factor(decimals: u8, bridged_decimals: u8) -> u128 {
+    require!(
+        bridged_decimals <= decimals,
+        "The bridged representation can't have more decimals than the token"
+    );
+    10u128
+        .checked_pow((decimals - bridged_decimals) as u32)
+        .unwrap_or_else(|| env::panic_str("Too many decimals"))
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Converts an amount of this token to a representation with `bridged_decimals` decimals.
+    pub fn to_bridged_amount(&self, amount: U128, bridged_decimals: u8) -> BridgedAmount {
+        let (bridged, dust) =
+            scale_to_bridged(amount.0, self.internal_decimals(), bridged_decimals);
+        BridgedAmount {
+            amount: U128(bridged),
+            dust: U128(dust),
+        }
+    }
+
+    /// Transfers the part of `amount` representable with `bridged_decimals` decimals to a
+    /// bridge, and the dust left over to the dust account. Without a dust account, the dust
+    /// stays with the caller.
+    #[payable]
+    pub fn ft_transfer_to_bridge(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        bridged_decimals: u8,
+        memo: Option<String>,
+    ) -> BridgedAmount {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        let bridged = self.to_bridged_amount(amount, bridged_decimals);
+        let representable = amount.0 - bridged.dust.0;
+        require!(representable > 0, "The amount can't be bridged");
+        self.internal_transfer_with_fee(
+            &sender_id,
+            &receiver_id,
+            Self::internal_to_shares(representable),
+            memo.as_deref(),
+        );
+        self.internal_route_dust(
+            &sender_id,
+            Self::internal_to_shares(bridged.dust.0),
+            "Bridged amount remainder",
+        );
+        bridged
+    }
+
+    /// Converts an amount with `bridged_decimals` decimals to an amount of this token.
+    pub fn from_bridged_amount(&self, amount: U128, bridged_decimals: u8) -> U128 {
+        U128(scale_from_bridged(
+            amount.0,
+            self.internal_decimals(),
+            bridged_decimals,
+        ))
+    }
+}
+
+impl Contract {
+    /// Internal method for getting the number of decimals of the token.
+    pub(crate) fn internal_decimals(&self) -> u8 {
+        self.metadata.get().unwrap().decimals
+    }
+}
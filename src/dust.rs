@@ -0,0 +1,50 @@
+use near_sdk::{assert_one_yocto, near_bindgen};
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the account receiving the remainders too small to be represented, or unsets it
+    /// when `None` is passed. The account must be registered.
+    #[payable]
+    pub fn set_dust_account(&mut self, dust_account_id: Option<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+        if let Some(dust_account_id) = dust_account_id.as_ref() {
+            self.internal_unwrap_balance_of(dust_account_id);
+        }
+        self.dust_account_id = dust_account_id;
+    }
+
+    /// Returns the account receiving the remainders too small to be represented, if any.
+    pub fn get_dust_account(&self) -> Option<AccountId> {
+        self.dust_account_id.clone()
+    }
+}
+
+impl Contract {
+    /// Internal method for sending `amount` tokens of dust from `from_id` to the dust account.
+    /// Returns whether the dust was routed, which it isn't when no dust account is set.
+    pub(crate) fn internal_route_dust(
+        &mut self,
+        from_id: &AccountId,
+        amount: u128,
+        reason: &str,
+    ) -> bool {
+        let Some(dust_account_id) = self.dust_account_id.clone() else {
+            return false;
+        };
+        if amount == 0 || from_id == &dust_account_id {
+            return true;
+        }
+        self.internal_contract_transfer(from_id, &dust_account_id, amount, Some(reason));
+        DustRouted {
+            from_id,
+            dust_account_id: &dust_account_id,
+            amount: &U128(Self::internal_to_amount(amount)),
+            reason,
+        }
+        .emit();
+        true
+    }
+}
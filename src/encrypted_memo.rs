@@ -0,0 +1,76 @@
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require};
+
+use crate::*;
+
+/// The length of a memo encryption key (x25519)
+const MEMO_KEY_LEN: usize = 32;
+
+/// The maximum size of an encrypted memo, to keep the event log small
+const MAX_ENCRYPTED_MEMO_LEN: usize = 512;
+
+#[near_bindgen]
+impl Contract {
+    /// Registers the public key senders should seal memos for the caller with, or removes it
+    /// when `None` is passed. The attached deposit must cover the storage of the key, the rest
+    /// of it is refunded, and the storage is refunded when the key is removed.
+    #[payable]
+    pub fn register_memo_key(&mut self, public_key: Option<Base64VecU8>) {
+        let account_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&account_id);
+        let initial_storage_usage = env::storage_usage();
+        match public_key {
+            Some(public_key) => {
+                require!(
+                    public_key.0.len() == MEMO_KEY_LEN,
+                    "The memo key should be 32 bytes"
+                );
+                self.memo_keys.insert(&account_id, &public_key.0);
+            }
+            None => {
+                self.memo_keys.remove(&account_id);
+            }
+        }
+        Self::internal_settle_storage(&account_id, initial_storage_usage);
+    }
+
+    /// Returns the key memos for an account are sealed with, if any. Falls back to the
+    /// account's x25519 payment key when it didn't register a dedicated memo key.
+    pub fn get_memo_key(&self, account_id: AccountId) -> Option<Base64VecU8> {
+        self.internal_memo_key(&account_id).map(Base64VecU8)
+    }
+
+    /// Transfers tokens like `ft_transfer`, attaching a memo sealed off-chain for the receiver's
+    /// memo key. The sealed memo is only written to the event log, never to the contract state.
+    #[payable]
+    pub fn ft_transfer_with_encrypted_memo(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        encrypted_memo: Base64VecU8,
+    ) {
+        assert_one_yocto();
+        require!(
+            self.internal_memo_key(&receiver_id).is_some(),
+            "The receiver has no memo key"
+        );
+        require!(
+            encrypted_memo.0.len() <= MAX_ENCRYPTED_MEMO_LEN,
+            "The encrypted memo is too long"
+        );
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer_with_fee(
+            &sender_id,
+            &receiver_id,
+            Self::internal_to_shares(amount.0),
+            None,
+        );
+
+        EncryptedMemo {
+            sender_id: &sender_id,
+            receiver_id: &receiver_id,
+            encrypted_memo: &encrypted_memo,
+        }
+        .emit();
+    }
+}
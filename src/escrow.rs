@@ -0,0 +1,223 @@
+//! Escrowed transfers.
+//!
+//! `ft_transfer_escrow` holds the tokens in the contract account until the receiver accepts
+//! them, which it can do before the timeout only. After it, the sender reclaims them. The
+//! receiver doesn't have to be registered to be sent tokens this way: it registers when
+//! accepting them, out of the attached deposit if need be. A mistyped receiver then only costs
+//! the sender the time to reclaim the tokens.
+//!
+//! The transfer fee is escrowed along with the amount, so that the receiver can accept the
+//! transfer whatever is left in the sender's balance, and the sender pays for the storage of the
+//! escrowed transfer until it's accepted or reclaimed.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, NearSchema, NearToken};
+
+use crate::storage::StorageManagement;
+use crate::versioned::Versioned;
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+pub type EscrowId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct EscrowedTransfer {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    /// The timestamp (in nanoseconds) after which the receiver can't accept the transfer
+    /// anymore, and the sender can reclaim it.
+    pub expires_at: U64,
+    /// The fee escrowed along with the amount.
+    pub fee: U128,
+}
+
+// An escrowed transfer made before its fee was escrowed.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct EscrowedTransferV1 {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub expires_at: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedEscrowedTransfer {
+    V1(EscrowedTransferV1),
+    V2(EscrowedTransfer),
+}
+
+impl Versioned for EscrowedTransfer {
+    type Record = VersionedEscrowedTransfer;
+
+    fn from_record(record: VersionedEscrowedTransfer) -> Self {
+        match record {
+            VersionedEscrowedTransfer::V1(transfer) => Self {
+                sender_id: transfer.sender_id,
+                receiver_id: transfer.receiver_id,
+                amount: transfer.amount,
+                expires_at: transfer.expires_at,
+                fee: U128(0),
+            },
+            VersionedEscrowedTransfer::V2(transfer) => transfer,
+        }
+    }
+
+    fn into_record(self) -> VersionedEscrowedTransfer {
+        VersionedEscrowedTransfer::V2(self)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Escrows `amount` of the caller's tokens and the transfer fee for `receiver_id`, which has
+    /// `timeout_seconds` to accept them. The receiver doesn't need to be registered. The
+    /// attached deposit (at least 1 yoctoNEAR) must cover the storage of the escrowed transfer;
+    /// the rest of it is refunded.
+    #[payable]
+    pub fn ft_transfer_escrow(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        timeout_seconds: u64,
+    ) -> EscrowId {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(timeout_seconds > 0, "The timeout should be positive");
+        let amount = U128(Self::internal_to_shares(amount.0));
+        self.internal_authorize_transfer(&sender_id, &receiver_id, amount.0, None, None);
+
+        let escrow_id = self.next_escrow_id;
+        self.next_escrow_id += 1;
+        let fee =
+            self.internal_transfer_fee(&sender_id, &receiver_id, amount.0, FeeMode::SenderPays);
+        self.internal_transfer(
+            &sender_id,
+            &env::current_account_id(),
+            amount
+                .0
+                .checked_add(fee)
+                .unwrap_or_else(|| env::panic_str("Balance overflow")),
+            Some(&format!("Escrow {}", escrow_id)),
+        );
+        let expires_at =
+            U64(env::block_timestamp() + timeout_seconds.saturating_mul(NANOS_PER_SECOND));
+        TransferEscrowed {
+            escrow_id,
+            sender_id: &sender_id,
+            receiver_id: &receiver_id,
+            amount: &U128(Self::internal_to_amount(amount.0)),
+            expires_at: &expires_at,
+        }
+        .emit();
+        self.internal_add_pending_references(&[&sender_id, &receiver_id]);
+        self.escrows.insert(
+            &escrow_id,
+            &EscrowedTransfer {
+                sender_id,
+                receiver_id,
+                amount,
+                expires_at,
+                fee: U128(fee),
+            },
+        );
+        Self::internal_charge_storage(initial_storage_usage);
+        escrow_id
+    }
+
+    /// Accepts an escrowed transfer before its timeout. Must be called by the receiver, which is
+    /// registered out of the attached deposit if it isn't yet; any excess is kept as its storage
+    /// balance, like `storage_deposit` does. The fee escrowed with the transfer is charged, even if
+    /// the fee config changed since.
+    #[payable]
+    pub fn accept_escrow(&mut self, escrow_id: EscrowId) {
+        self.internal_assert_not_paused(Pausable::Transfers);
+        let transfer = self.internal_take_escrow(escrow_id);
+        require!(
+            env::predecessor_account_id() == transfer.receiver_id,
+            "Only the receiver can accept the transfer"
+        );
+        require!(
+            env::block_timestamp() <= transfer.expires_at.0,
+            "The escrowed transfer has expired"
+        );
+        if !self.accounts.contains_key(&transfer.receiver_id) {
+            self.storage_deposit(Some(transfer.receiver_id.clone()), None);
+            log!("Registered {} to accept the transfer", transfer.receiver_id);
+        } else {
+            assert_one_yocto();
+        }
+
+        self.internal_transfer(
+            &env::current_account_id(),
+            &transfer.receiver_id,
+            transfer.amount.0,
+            Some(&format!("Escrow {}", escrow_id)),
+        );
+        self.internal_settle_escrowed_transfer(
+            &transfer.sender_id,
+            &transfer.receiver_id,
+            transfer.amount.0,
+            transfer.fee.0,
+            &format!("Fee of escrow {}", escrow_id),
+        );
+    }
+
+    /// Returns the tokens and the fee of an escrowed transfer that timed out to its sender,
+    /// which must be the caller.
+    pub fn reclaim_escrow(&mut self, escrow_id: EscrowId) {
+        let transfer = self.internal_take_escrow(escrow_id);
+        require!(
+            env::predecessor_account_id() == transfer.sender_id,
+            "Only the sender can reclaim the transfer"
+        );
+        require!(
+            env::block_timestamp() > transfer.expires_at.0,
+            "The escrowed transfer hasn't expired yet"
+        );
+        self.internal_transfer(
+            &env::current_account_id(),
+            &transfer.sender_id,
+            transfer.amount.0 + transfer.fee.0,
+            Some(&format!("Reclaim of escrow {}", escrow_id)),
+        );
+    }
+
+    /// Returns an escrowed transfer, or `null` if it doesn't exist (anymore).
+    pub fn get_escrow(&self, escrow_id: EscrowId) -> Option<EscrowedTransfer> {
+        self.escrows.get(&escrow_id).map(|mut transfer| {
+            transfer.amount = U128(Self::internal_to_amount(transfer.amount.0));
+            transfer.fee = U128(Self::internal_to_amount(transfer.fee.0));
+            transfer
+        })
+    }
+}
+
+impl Contract {
+    /// Internal method for removing an escrowed transfer, refunding its storage to the sender.
+    fn internal_take_escrow(&mut self, escrow_id: EscrowId) -> EscrowedTransfer {
+        let initial_storage_usage = env::storage_usage();
+        let transfer = self
+            .escrows
+            .remove(&escrow_id)
+            .unwrap_or_else(|| env::panic_str("The escrowed transfer doesn't exist"));
+        self.internal_remove_pending_references(&[&transfer.sender_id, &transfer.receiver_id]);
+        Self::internal_refund_storage(&transfer.sender_id, initial_storage_usage);
+        transfer
+    }
+}
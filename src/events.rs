@@ -11,13 +11,29 @@
 //!
 //! These events can be logged by calling `.emit()` on them if a single event, or calling
 //! [`FtMint::emit_many`], [`FtTransfer::emit_many`],
-//! or [`FtBurn::emit_many`] respectively.
+//! or [`FtBurn::emit_many`] respectively. Batch operations collect their events in an
+//! [`FtEventBatch`] instead, logging a single event of each kind with the data of all their
+//! operations rather than one event per operation.
+//!
+//! Events of the contract's own extensions (liquidity mining, ...) are not part of any NEP
+//! and are logged under the `near_ft` standard instead, using the same nep-297 envelope.
+//! Each of them is versioned on its own, so that indexers built on a version keep working:
+//! adding a field bumps the minor version, while removing, renaming or retyping one is a new
+//! major version. The fields of every version are recorded in `tests/event_schemas.json`,
+//! and the tests fail when an event doesn't match its record or breaks an older version.
+//!
+//! Vesting grant positions are surfaced as NEP-171 tokens, so their mints and transfers are
+//! logged as [`NftMint`] and [`NftTransfer`] under the `nep171` standard.
 
+use near_sdk::json_types::{Base64VecU8, U128, U64};
 use near_sdk::serde::Serialize;
 use near_sdk::serde_json;
 use near_sdk::AccountId;
 
-use near_sdk::{env, NearToken};
+use near_sdk::env;
+
+use crate::access::Role;
+use crate::guard::PauseFlags;
 
 #[derive(Serialize, Debug)]
 #[serde(tag = "standard")]
@@ -25,6 +41,8 @@ use near_sdk::{env, NearToken};
 #[serde(rename_all = "snake_case")]
 pub(crate) enum NearEvent<'a> {
     Nep141(Nep141Event<'a>),
+    Nep171(Nep171Event<'a>),
+    NearFt(NearFtEvent<'a>),
 }
 
 impl<'a> NearEvent<'a> {
@@ -52,7 +70,7 @@ impl<'a> NearEvent<'a> {
 #[derive(Serialize, Debug, Clone)]
 pub struct FtMint<'a> {
     pub owner_id: &'a AccountId,
-    pub amount: &'a NearToken,
+    pub amount: &'a U128,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub memo: Option<&'a str>,
 }
@@ -78,7 +96,7 @@ impl FtMint<'_> {
 pub struct FtTransfer<'a> {
     pub old_owner_id: &'a AccountId,
     pub new_owner_id: &'a AccountId,
-    pub amount: &'a NearToken,
+    pub amount: &'a U128,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub memo: Option<&'a str>,
 }
@@ -97,6 +115,116 @@ impl FtTransfer<'_> {
     }
 }
 
+/// Data to log for an FT burn event. To log this event, call [`.emit()`](FtBurn::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct FtBurn<'a> {
+    pub owner_id: &'a AccountId,
+    pub amount: &'a U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl FtBurn<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an FT burn event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`FtBurn`] represents the data of each burn.
+    pub fn emit_many(data: &[FtBurn<'_>]) {
+        new_141_v1(Nep141EventKind::FtBurn(data)).emit()
+    }
+}
+
+/// The mints, transfers and burns of a batch operation, logged together by
+/// [`.emit()`](FtEventBatch::emit): one `ft_mint`, one `ft_transfer` and one `ft_burn` event at
+/// most, each with the data of all the operations of its kind, in order.
+#[must_use = "don't forget to `.emit()` the batch"]
+#[derive(Debug, Default)]
+pub struct FtEventBatch {
+    mints: Vec<(AccountId, U128, Option<String>)>,
+    transfers: Vec<(AccountId, AccountId, U128, Option<String>)>,
+    burns: Vec<(AccountId, U128, Option<String>)>,
+}
+
+impl FtEventBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a mint to the batch.
+    pub fn mint(&mut self, owner_id: &AccountId, amount: u128, memo: Option<&str>) {
+        self.mints
+            .push((owner_id.clone(), U128(amount), memo.map(str::to_string)));
+    }
+
+    /// Adds a transfer to the batch.
+    pub fn transfer(
+        &mut self,
+        old_owner_id: &AccountId,
+        new_owner_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+    ) {
+        self.transfers.push((
+            old_owner_id.clone(),
+            new_owner_id.clone(),
+            U128(amount),
+            memo.map(str::to_string),
+        ));
+    }
+
+    /// Adds a burn to the batch.
+    pub fn burn(&mut self, owner_id: &AccountId, amount: u128, memo: Option<&str>) {
+        self.burns
+            .push((owner_id.clone(), U128(amount), memo.map(str::to_string)));
+    }
+
+    /// Logs the events of the batch, skipping the kinds it has no data for.
+    pub fn emit(self) {
+        if !self.mints.is_empty() {
+            let data: Vec<FtMint> = self
+                .mints
+                .iter()
+                .map(|(owner_id, amount, memo)| FtMint {
+                    owner_id,
+                    amount,
+                    memo: memo.as_deref(),
+                })
+                .collect();
+            FtMint::emit_many(&data);
+        }
+        if !self.transfers.is_empty() {
+            let data: Vec<FtTransfer> = self
+                .transfers
+                .iter()
+                .map(|(old_owner_id, new_owner_id, amount, memo)| FtTransfer {
+                    old_owner_id,
+                    new_owner_id,
+                    amount,
+                    memo: memo.as_deref(),
+                })
+                .collect();
+            FtTransfer::emit_many(&data);
+        }
+        if !self.burns.is_empty() {
+            let data: Vec<FtBurn> = self
+                .burns
+                .iter()
+                .map(|(owner_id, amount, memo)| FtBurn {
+                    owner_id,
+                    amount,
+                    memo: memo.as_deref(),
+                })
+                .collect();
+            FtBurn::emit_many(&data);
+        }
+    }
+}
+
 #[derive(Serialize, Debug)]
 pub(crate) struct Nep141Event<'a> {
     version: &'static str,
@@ -111,6 +239,7 @@ pub(crate) struct Nep141Event<'a> {
 enum Nep141EventKind<'a> {
     FtMint(&'a [FtMint<'a>]),
     FtTransfer(&'a [FtTransfer<'a>]),
+    FtBurn(&'a [FtBurn<'a>]),
 }
 
 fn new_141<'a>(version: &'static str, event_kind: Nep141EventKind<'a>) -> NearEvent<'a> {
@@ -123,3 +252,1089 @@ fn new_141<'a>(version: &'static str, event_kind: Nep141EventKind<'a>) -> NearEv
 fn new_141_v1(event_kind: Nep141EventKind) -> NearEvent {
     new_141("1.0.0", event_kind)
 }
+
+/// Data to log for an NFT mint event. To log this event, call [`.emit()`](NftMint::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct NftMint<'a> {
+    pub owner_id: &'a AccountId,
+    pub token_ids: &'a [&'a str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl NftMint<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an NFT mint event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`NftMint`] represents the data of each mint.
+    pub fn emit_many(data: &[NftMint<'_>]) {
+        new_171_v1(Nep171EventKind::NftMint(data)).emit()
+    }
+}
+
+/// Data to log for an NFT transfer event. To log this event,
+/// call [`.emit()`](NftTransfer::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct NftTransfer<'a> {
+    pub old_owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+    pub token_ids: &'a [&'a str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl NftTransfer<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an NFT transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`NftTransfer`] represents the data of each transfer.
+    pub fn emit_many(data: &[NftTransfer<'_>]) {
+        new_171_v1(Nep171EventKind::NftTransfer(data)).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct Nep171Event<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: Nep171EventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum Nep171EventKind<'a> {
+    NftMint(&'a [NftMint<'a>]),
+    NftTransfer(&'a [NftTransfer<'a>]),
+}
+
+fn new_171<'a>(version: &'static str, event_kind: Nep171EventKind<'a>) -> NearEvent<'a> {
+    NearEvent::Nep171(Nep171Event {
+        version,
+        event_kind,
+    })
+}
+
+fn new_171_v1(event_kind: Nep171EventKind) -> NearEvent {
+    new_171("1.0.0", event_kind)
+}
+
+/// Data to log for a liquidity mining emission. To log this event,
+/// call [`.emit()`](MiningEmission::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MiningEmission<'a> {
+    pub program_id: u64,
+    pub pool_id: &'a AccountId,
+    pub epochs: u32,
+    pub amount: &'a U128,
+    pub refunded: &'a U128,
+}
+
+impl MiningEmission<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a mining emission event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MiningEmission`] represents the data of each emission.
+    pub fn emit_many(data: &[MiningEmission<'_>]) {
+        new_near_ft(NearFtEventKind::MiningEmission(data)).emit()
+    }
+}
+
+/// Data to log when a liquidity mining emission failed and will be retried. To log this event,
+/// call [`.emit()`](MiningEmissionFailed::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MiningEmissionFailed<'a> {
+    pub program_id: u64,
+    pub pool_id: &'a AccountId,
+    pub epochs: u32,
+    pub amount: &'a U128,
+}
+
+impl MiningEmissionFailed<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a failed mining emission event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MiningEmissionFailed`] represents the data of each failed emission.
+    pub fn emit_many(data: &[MiningEmissionFailed<'_>]) {
+        new_near_ft(NearFtEventKind::MiningEmissionFailed(data)).emit()
+    }
+}
+
+/// Data to log when a bond is bought. To log this event,
+/// call [`.emit()`](BondPurchase::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct BondPurchase<'a> {
+    pub account_id: &'a AccountId,
+    pub market_id: u64,
+    pub amount_in: &'a U128,
+    pub payout: &'a U128,
+}
+
+impl BondPurchase<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a bond purchase event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`BondPurchase`] represents the data of each purchase.
+    pub fn emit_many(data: &[BondPurchase<'_>]) {
+        new_near_ft(NearFtEventKind::BondPurchase(data)).emit()
+    }
+}
+
+/// Data to log when vested bond payouts are claimed. To log this event,
+/// call [`.emit()`](BondClaim::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct BondClaim<'a> {
+    pub account_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl BondClaim<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a bond claim event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`BondClaim`] represents the data of each claim.
+    pub fn emit_many(data: &[BondClaim<'_>]) {
+        new_near_ft(NearFtEventKind::BondClaim(data)).emit()
+    }
+}
+
+/// Data to log when a transfer fee is split between the buckets. To log this event,
+/// call [`.emit()`](FeeDistribution::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct FeeDistribution<'a> {
+    pub payer_id: &'a AccountId,
+    pub to_stakers: &'a U128,
+    pub to_treasury: &'a U128,
+    pub burned: &'a U128,
+    pub to_rebates: &'a U128,
+    pub to_insurance: &'a U128,
+}
+
+impl FeeDistribution<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a fee distribution event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`FeeDistribution`] represents the data of each distribution.
+    pub fn emit_many(data: &[FeeDistribution<'_>]) {
+        new_near_ft(NearFtEventKind::FeeDistribution(data)).emit()
+    }
+}
+
+/// Data to log when an old account is merged into a new one. To log this event,
+/// call [`.emit()`](AccountMerge::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountMerge<'a> {
+    pub old_account_id: &'a AccountId,
+    pub new_account_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl AccountMerge<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an account merge event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`AccountMerge`] represents the data of each merge.
+    pub fn emit_many(data: &[AccountMerge<'_>]) {
+        new_near_ft(NearFtEventKind::AccountMerge(data)).emit()
+    }
+}
+
+/// Data to log for a memo sealed for the receiver of a transfer. To log this event,
+/// call [`.emit()`](EncryptedMemo::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct EncryptedMemo<'a> {
+    pub sender_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub encrypted_memo: &'a Base64VecU8,
+}
+
+impl EncryptedMemo<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an encrypted memo event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`EncryptedMemo`] represents the data of each memo.
+    pub fn emit_many(data: &[EncryptedMemo<'_>]) {
+        new_near_ft(NearFtEventKind::EncryptedMemo(data)).emit()
+    }
+}
+
+/// Data to log when a transfer above the co-signing threshold is queued for confirmation. To log this event,
+/// call [`.emit()`](TransferQueued::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct TransferQueued<'a> {
+    pub transfer_id: u64,
+    pub sender_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub amount: &'a U128,
+    pub co_signer_id: &'a AccountId,
+}
+
+impl TransferQueued<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a queued transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`TransferQueued`] represents the data of each transfer.
+    pub fn emit_many(data: &[TransferQueued<'_>]) {
+        new_near_ft(NearFtEventKind::TransferQueued(data)).emit()
+    }
+}
+
+/// Data to log when an owner sets the allowance of a spender. To log this event,
+/// call [`.emit()`](AllowanceSet::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct AllowanceSet<'a> {
+    pub owner_id: &'a AccountId,
+    pub spender_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl AllowanceSet<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an allowance event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`AllowanceSet`] represents the data of each allowance.
+    pub fn emit_many(data: &[AllowanceSet<'_>]) {
+        new_near_ft(NearFtEventKind::AllowanceSet(data)).emit()
+    }
+}
+
+/// Data to log when the allowance of a spender is revoked. To log this event,
+/// call [`.emit()`](AllowanceRevoked::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct AllowanceRevoked<'a> {
+    pub owner_id: &'a AccountId,
+    pub spender_id: &'a AccountId,
+}
+
+impl AllowanceRevoked<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a revoked allowance event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`AllowanceRevoked`] represents the data of each revocation.
+    pub fn emit_many(data: &[AllowanceRevoked<'_>]) {
+        new_near_ft(NearFtEventKind::AllowanceRevoked(data)).emit()
+    }
+}
+
+/// Data to log when an account is frozen, or its freeze is renewed. To log this event,
+/// call [`.emit()`](AccountFrozen::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountFrozen<'a> {
+    pub account_id: &'a AccountId,
+    pub reason_code: u16,
+    pub expires_at: &'a U64,
+}
+
+impl AccountFrozen<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an account freeze event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`AccountFrozen`] represents the data of each freeze.
+    pub fn emit_many(data: &[AccountFrozen<'_>]) {
+        new_near_ft(NearFtEventKind::AccountFrozen(data)).emit()
+    }
+}
+
+/// Data to log when an account is unfrozen before its freeze expires. To log this event,
+/// call [`.emit()`](AccountUnfrozen::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountUnfrozen<'a> {
+    pub account_id: &'a AccountId,
+}
+
+impl AccountUnfrozen<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an account unfreeze event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`AccountUnfrozen`] represents the data of each unfreeze.
+    pub fn emit_many(data: &[AccountUnfrozen<'_>]) {
+        new_near_ft(NearFtEventKind::AccountUnfrozen(data)).emit()
+    }
+}
+
+/// Data to log for a transfer between VASPs carrying a pointer to its travel rule data. To log this event,
+/// call [`.emit()`](TravelRuleTransfer::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct TravelRuleTransfer<'a> {
+    pub sender_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub amount: &'a U128,
+    pub travel_rule_ref: &'a str,
+}
+
+impl TravelRuleTransfer<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a travel rule transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`TravelRuleTransfer`] represents the data of each transfer.
+    pub fn emit_many(data: &[TravelRuleTransfer<'_>]) {
+        new_near_ft(NearFtEventKind::TravelRuleTransfer(data)).emit()
+    }
+}
+
+/// Data to log when receipt tokens of a lock are minted, transferred or redeemed. Mints have
+/// no `old_owner_id` and redemptions no `new_owner_id`. To log this event,
+/// call [`.emit()`](ReceiptTransfer::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct ReceiptTransfer<'a> {
+    pub lock_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_owner_id: Option<&'a AccountId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_owner_id: Option<&'a AccountId>,
+    pub amount: &'a U128,
+}
+
+impl ReceiptTransfer<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a receipt token transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`ReceiptTransfer`] represents the data of each transfer.
+    pub fn emit_many(data: &[ReceiptTransfer<'_>]) {
+        new_near_ft(NearFtEventKind::ReceiptTransfer(data)).emit()
+    }
+}
+
+/// Data to log when a royalty is paid to the issuer of a transferred position, i.e. receipt
+/// tokens of a lock or a vesting grant. To log this event, call
+/// [`.emit()`](PositionRoyalty::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct PositionRoyalty<'a> {
+    pub position: &'a str,
+    pub position_id: u64,
+    pub payer_id: &'a AccountId,
+    pub issuer_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl PositionRoyalty<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a position royalty event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`PositionRoyalty`] represents the data of each royalty.
+    pub fn emit_many(data: &[PositionRoyalty<'_>]) {
+        new_near_ft(NearFtEventKind::PositionRoyalty(data)).emit()
+    }
+}
+
+/// Data to log when a treasury spend above the threshold is queued for the approver's
+/// co-signature. To log this event, call [`.emit()`](TreasurySpendQueued::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct TreasurySpendQueued<'a> {
+    pub spend_id: u64,
+    pub treasury_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub amount: &'a U128,
+    pub approver_id: &'a AccountId,
+    pub expires_at: U64,
+}
+
+impl TreasurySpendQueued<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a queued treasury spend event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`TreasurySpendQueued`] represents the data of each spend.
+    pub fn emit_many(data: &[TreasurySpendQueued<'_>]) {
+        new_near_ft(NearFtEventKind::TreasurySpendQueued(data)).emit()
+    }
+}
+
+/// Data to log when a keeper is paid for a maintenance call. To log this event,
+/// call [`.emit()`](KeeperReward::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct KeeperReward<'a> {
+    pub keeper_id: &'a AccountId,
+    pub task: &'a str,
+    pub amount: &'a U128,
+}
+
+impl KeeperReward<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a keeper reward event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`KeeperReward`] represents the data of each reward.
+    pub fn emit_many(data: &[KeeperReward<'_>]) {
+        new_near_ft(NearFtEventKind::KeeperReward(data)).emit()
+    }
+}
+
+/// Data to log when the factory deploys a new token instance. To log this event,
+/// call [`.emit()`](TokenDeployed::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct TokenDeployed<'a> {
+    pub token_id: &'a AccountId,
+    pub owner_id: &'a AccountId,
+    pub total_supply: &'a U128,
+}
+
+impl TokenDeployed<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a token deployment event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`TokenDeployed`] represents the data of each deployed token.
+    pub fn emit_many(data: &[TokenDeployed<'_>]) {
+        new_near_ft(NearFtEventKind::TokenDeployed(data)).emit()
+    }
+}
+
+/// Data to log when a remainder too small to be represented is sent to the dust account. To log this event,
+/// call [`.emit()`](DustRouted::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct DustRouted<'a> {
+    pub from_id: &'a AccountId,
+    pub dust_account_id: &'a AccountId,
+    pub amount: &'a U128,
+    pub reason: &'a str,
+}
+
+impl DustRouted<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a dust event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`DustRouted`] represents the data of each remainder.
+    pub fn emit_many(data: &[DustRouted<'_>]) {
+        new_near_ft(NearFtEventKind::DustRouted(data)).emit()
+    }
+}
+
+/// Data to log when the owner pauses or unpauses some of the contract methods. To log this event,
+/// call [`.emit()`](PauseChanged::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct PauseChanged<'a> {
+    #[serde(flatten)]
+    pub flags: &'a PauseFlags,
+}
+
+impl PauseChanged<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a pause event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`PauseChanged`] represents the data of each change.
+    pub fn emit_many(data: &[PauseChanged<'_>]) {
+        new_near_ft(NearFtEventKind::PauseChanged(data)).emit()
+    }
+}
+
+/// Data to log when an account claims its allocation of an airdrop. To log this event,
+/// call [`.emit()`](AirdropClaim::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct AirdropClaim<'a> {
+    pub airdrop_id: u64,
+    pub account_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl AirdropClaim<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an airdrop claim event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`AirdropClaim`] represents the data of each claim.
+    pub fn emit_many(data: &[AirdropClaim<'_>]) {
+        new_near_ft(NearFtEventKind::AirdropClaim(data)).emit()
+    }
+}
+
+/// Data to log when the owner updates the token metadata, so that wallets can refresh their cached copy. To log this event,
+/// call [`.emit()`](MetadataUpdated::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct MetadataUpdated<'a> {
+    pub updated_fields: &'a [&'a str],
+}
+
+impl MetadataUpdated<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a metadata update event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`MetadataUpdated`] represents the data of each update.
+    pub fn emit_many(data: &[MetadataUpdated<'_>]) {
+        new_near_ft(NearFtEventKind::MetadataUpdated(data)).emit()
+    }
+}
+
+/// Data to log when the ownership of the contract is transferred, accepted by the proposed owner
+/// or rotated by the recovery council. To log this event,
+/// call [`.emit()`](OwnershipTransferred::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct OwnershipTransferred<'a> {
+    pub old_owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+}
+
+impl OwnershipTransferred<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an ownership transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`OwnershipTransferred`] represents the data of each transfer.
+    pub fn emit_many(data: &[OwnershipTransferred<'_>]) {
+        new_near_ft(NearFtEventKind::OwnershipTransferred(data)).emit()
+    }
+}
+
+/// Data to log when the owner grants a role to an account. To log this event,
+/// call [`.emit()`](RoleGranted::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RoleGranted<'a> {
+    pub account_id: &'a AccountId,
+    pub role: &'a Role,
+}
+
+impl RoleGranted<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a role grant event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RoleGranted`] represents the data of each grant.
+    pub fn emit_many(data: &[RoleGranted<'_>]) {
+        new_near_ft(NearFtEventKind::RoleGranted(data)).emit()
+    }
+}
+
+/// Data to log when a role is revoked from an account or renounced by it. To log this event,
+/// call [`.emit()`](RoleRevoked::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RoleRevoked<'a> {
+    pub account_id: &'a AccountId,
+    pub role: &'a Role,
+}
+
+impl RoleRevoked<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a role revocation event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RoleRevoked`] represents the data of each revocation.
+    pub fn emit_many(data: &[RoleRevoked<'_>]) {
+        new_near_ft(NearFtEventKind::RoleRevoked(data)).emit()
+    }
+}
+
+/// Data to log when the recovery council proposes a new owner for an inactive owner. To log this event,
+/// call [`.emit()`](OwnerRecoveryProposed::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct OwnerRecoveryProposed<'a> {
+    pub proposer_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+}
+
+impl OwnerRecoveryProposed<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an owner recovery proposal event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`OwnerRecoveryProposed`] represents the data of each proposal.
+    pub fn emit_many(data: &[OwnerRecoveryProposed<'_>]) {
+        new_near_ft(NearFtEventKind::OwnerRecoveryProposed(data)).emit()
+    }
+}
+
+/// Data to log when the owner vetoes a recovery by showing activity. To log this event,
+/// call [`.emit()`](OwnerRecoveryVetoed::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct OwnerRecoveryVetoed<'a> {
+    pub owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+}
+
+impl OwnerRecoveryVetoed<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an owner recovery veto event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`OwnerRecoveryVetoed`] represents the data of each veto.
+    pub fn emit_many(data: &[OwnerRecoveryVetoed<'_>]) {
+        new_near_ft(NearFtEventKind::OwnerRecoveryVetoed(data)).emit()
+    }
+}
+
+/// Data to log when a sender opens a token stream. To log this event,
+/// call [`.emit()`](StreamOpened::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct StreamOpened<'a> {
+    pub stream_id: u64,
+    pub sender_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub tokens_per_second: &'a U128,
+    pub deposit: &'a U128,
+}
+
+impl StreamOpened<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a stream opening event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`StreamOpened`] represents the data of each stream.
+    pub fn emit_many(data: &[StreamOpened<'_>]) {
+        new_near_ft(NearFtEventKind::StreamOpened(data)).emit()
+    }
+}
+
+/// Data to log when the receiver of a stream withdraws the accrued tokens. To log this event,
+/// call [`.emit()`](StreamWithdrawn::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct StreamWithdrawn<'a> {
+    pub stream_id: u64,
+    pub receiver_id: &'a AccountId,
+    pub amount: &'a U128,
+}
+
+impl StreamWithdrawn<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a stream withdrawal event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`StreamWithdrawn`] represents the data of each withdrawal.
+    pub fn emit_many(data: &[StreamWithdrawn<'_>]) {
+        new_near_ft(NearFtEventKind::StreamWithdrawn(data)).emit()
+    }
+}
+
+/// Data to log when a stream is cancelled and settled between its parties. To log this event,
+/// call [`.emit()`](StreamCancelled::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct StreamCancelled<'a> {
+    pub stream_id: u64,
+    pub cancelled_by: &'a AccountId,
+    pub receiver_amount: &'a U128,
+    pub sender_refund: &'a U128,
+}
+
+impl StreamCancelled<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a stream cancellation event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`StreamCancelled`] represents the data of each cancellation.
+    pub fn emit_many(data: &[StreamCancelled<'_>]) {
+        new_near_ft(NearFtEventKind::StreamCancelled(data)).emit()
+    }
+}
+
+/// Data to log when the owner takes a snapshot of the balances. To log this event,
+/// call [`.emit()`](SnapshotTaken::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct SnapshotTaken<'a> {
+    pub snapshot_id: u64,
+    pub block_height: &'a U64,
+}
+
+impl SnapshotTaken<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a snapshot event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`SnapshotTaken`] represents the data of each snapshot.
+    pub fn emit_many(data: &[SnapshotTaken<'_>]) {
+        new_near_ft(NearFtEventKind::SnapshotTaken(data)).emit()
+    }
+}
+
+/// Data to log when a transfer is escrowed until its receiver accepts it. To log this event,
+/// call [`.emit()`](TransferEscrowed::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct TransferEscrowed<'a> {
+    pub escrow_id: u64,
+    pub sender_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub amount: &'a U128,
+    pub expires_at: &'a U64,
+}
+
+impl TransferEscrowed<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an escrowed transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`TransferEscrowed`] represents the data of each transfer.
+    pub fn emit_many(data: &[TransferEscrowed<'_>]) {
+        new_near_ft(NearFtEventKind::TransferEscrowed(data)).emit()
+    }
+}
+
+/// Data to log when an account exports its balance to the successor contract. To log this event,
+/// call [`.emit()`](BalanceVoucherExported::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct BalanceVoucherExported<'a> {
+    pub nonce: u64,
+    pub account_id: &'a AccountId,
+    pub amount: &'a U128,
+    pub successor_id: &'a AccountId,
+}
+
+impl BalanceVoucherExported<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an exported balance voucher event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`BalanceVoucherExported`] represents the data of each voucher.
+    pub fn emit_many(data: &[BalanceVoucherExported<'_>]) {
+        new_near_ft(NearFtEventKind::BalanceVoucherExported(data)).emit()
+    }
+}
+
+/// Data to log when an undercollateralized vault is liquidated. To log this event,
+/// call [`.emit()`](VaultLiquidated::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct VaultLiquidated<'a> {
+    pub account_id: &'a AccountId,
+    pub liquidator_id: &'a AccountId,
+    pub debt: &'a U128,
+    pub collateral: &'a U128,
+}
+
+impl VaultLiquidated<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a vault liquidation event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`VaultLiquidated`] represents the data of each liquidation.
+    pub fn emit_many(data: &[VaultLiquidated<'_>]) {
+        new_near_ft(NearFtEventKind::VaultLiquidated(data)).emit()
+    }
+}
+
+/// An oracle answer diverging from the median of the other answers of its feed. To log this event,
+/// call [`.emit()`](OracleDivergence::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct OracleDivergence<'a> {
+    pub feed_id: &'a str,
+    pub oracle_id: &'a AccountId,
+    pub price: &'a U128,
+    pub median: &'a U128,
+}
+
+impl OracleDivergence<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an oracle divergence event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`OracleDivergence`] represents the data of each divergence.
+    pub fn emit_many(data: &[OracleDivergence<'_>]) {
+        new_near_ft(NearFtEventKind::OracleDivergence(data)).emit()
+    }
+}
+
+/// The rebase index moving, which scales every balance and the total supply. To log this event,
+/// call [`.emit()`](Rebase::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct Rebase<'a> {
+    pub old_index: &'a U128,
+    pub new_index: &'a U128,
+    pub total_supply: &'a U128,
+}
+
+impl Rebase<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a rebase event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`Rebase`] represents the data of each rebase.
+    pub fn emit_many(data: &[Rebase<'_>]) {
+        new_near_ft(NearFtEventKind::Rebase(data)).emit()
+    }
+}
+
+/// Data to log when a transfer is scheduled until its unlock time. To log this event,
+/// call [`.emit()`](TransferScheduled::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct TransferScheduled<'a> {
+    pub scheduled_id: u64,
+    pub sender_id: &'a AccountId,
+    pub receiver_id: &'a AccountId,
+    pub amount: &'a U128,
+    pub execute_after: &'a U64,
+}
+
+impl TransferScheduled<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a scheduled transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`TransferScheduled`] represents the data of each transfer.
+    pub fn emit_many(data: &[TransferScheduled<'_>]) {
+        new_near_ft(NearFtEventKind::TransferScheduled(data)).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct NearFtEvent<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: NearFtEventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+enum NearFtEventKind<'a> {
+    MiningEmission(&'a [MiningEmission<'a>]),
+    MiningEmissionFailed(&'a [MiningEmissionFailed<'a>]),
+    BondPurchase(&'a [BondPurchase<'a>]),
+    BondClaim(&'a [BondClaim<'a>]),
+    FeeDistribution(&'a [FeeDistribution<'a>]),
+    AccountMerge(&'a [AccountMerge<'a>]),
+    EncryptedMemo(&'a [EncryptedMemo<'a>]),
+    TransferQueued(&'a [TransferQueued<'a>]),
+    AllowanceSet(&'a [AllowanceSet<'a>]),
+    AllowanceRevoked(&'a [AllowanceRevoked<'a>]),
+    AccountFrozen(&'a [AccountFrozen<'a>]),
+    AccountUnfrozen(&'a [AccountUnfrozen<'a>]),
+    TravelRuleTransfer(&'a [TravelRuleTransfer<'a>]),
+    ReceiptTransfer(&'a [ReceiptTransfer<'a>]),
+    PositionRoyalty(&'a [PositionRoyalty<'a>]),
+    TreasurySpendQueued(&'a [TreasurySpendQueued<'a>]),
+    KeeperReward(&'a [KeeperReward<'a>]),
+    TokenDeployed(&'a [TokenDeployed<'a>]),
+    DustRouted(&'a [DustRouted<'a>]),
+    PauseChanged(&'a [PauseChanged<'a>]),
+    AirdropClaim(&'a [AirdropClaim<'a>]),
+    MetadataUpdated(&'a [MetadataUpdated<'a>]),
+    OwnershipTransferred(&'a [OwnershipTransferred<'a>]),
+    RoleGranted(&'a [RoleGranted<'a>]),
+    RoleRevoked(&'a [RoleRevoked<'a>]),
+    OwnerRecoveryProposed(&'a [OwnerRecoveryProposed<'a>]),
+    OwnerRecoveryVetoed(&'a [OwnerRecoveryVetoed<'a>]),
+    StreamOpened(&'a [StreamOpened<'a>]),
+    StreamWithdrawn(&'a [StreamWithdrawn<'a>]),
+    StreamCancelled(&'a [StreamCancelled<'a>]),
+    SnapshotTaken(&'a [SnapshotTaken<'a>]),
+    TransferEscrowed(&'a [TransferEscrowed<'a>]),
+    BalanceVoucherExported(&'a [BalanceVoucherExported<'a>]),
+    VaultLiquidated(&'a [VaultLiquidated<'a>]),
+    OracleDivergence(&'a [OracleDivergence<'a>]),
+    Rebase(&'a [Rebase<'a>]),
+    TransferScheduled(&'a [TransferScheduled<'a>]),
+}
+
+impl NearFtEventKind<'_> {
+    /// The version of the event's schema.
+    fn version(&self) -> &'static str {
+        match self {
+            Self::MiningEmission(_) => "1.0.0",
+            Self::MiningEmissionFailed(_) => "1.0.0",
+            Self::BondPurchase(_) => "1.0.0",
+            Self::BondClaim(_) => "1.0.0",
+            Self::FeeDistribution(_) => "1.2.0",
+            Self::AccountMerge(_) => "1.0.0",
+            Self::EncryptedMemo(_) => "1.0.0",
+            Self::TransferQueued(_) => "1.0.0",
+            Self::AllowanceSet(_) => "1.0.0",
+            Self::AllowanceRevoked(_) => "1.0.0",
+            Self::AccountFrozen(_) => "1.0.0",
+            Self::AccountUnfrozen(_) => "1.0.0",
+            Self::TravelRuleTransfer(_) => "1.0.0",
+            Self::ReceiptTransfer(_) => "1.0.0",
+            Self::PositionRoyalty(_) => "1.0.0",
+            Self::TreasurySpendQueued(_) => "1.0.0",
+            Self::KeeperReward(_) => "1.0.0",
+            Self::TokenDeployed(_) => "1.0.0",
+            Self::DustRouted(_) => "1.0.0",
+            Self::PauseChanged(_) => "1.0.0",
+            Self::AirdropClaim(_) => "1.0.0",
+            Self::MetadataUpdated(_) => "1.0.0",
+            Self::OwnershipTransferred(_) => "1.0.0",
+            Self::RoleGranted(_) => "1.0.0",
+            Self::RoleRevoked(_) => "1.0.0",
+            Self::OwnerRecoveryProposed(_) => "1.0.0",
+            Self::OwnerRecoveryVetoed(_) => "1.0.0",
+            Self::StreamOpened(_) => "1.0.0",
+            Self::StreamWithdrawn(_) => "1.0.0",
+            Self::StreamCancelled(_) => "1.0.0",
+            Self::SnapshotTaken(_) => "1.0.0",
+            Self::TransferEscrowed(_) => "1.0.0",
+            Self::BalanceVoucherExported(_) => "1.0.0",
+            Self::VaultLiquidated(_) => "1.0.0",
+            Self::OracleDivergence(_) => "1.0.0",
+            Self::Rebase(_) => "1.0.0",
+            Self::TransferScheduled(_) => "1.0.0",
+        }
+    }
+}
+
+fn new_near_ft(event_kind: NearFtEventKind) -> NearEvent {
+    NearEvent::NearFt(NearFtEvent {
+        version: event_kind.version(),
+        event_kind,
+    })
+}
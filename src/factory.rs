@@ -0,0 +1,158 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{
+    assert_one_yocto, env, log, near_bindgen, require, Gas, NearSchema, Promise, PromiseResult,
+};
+
+use crate::*;
+
+const GAS_FOR_TOKEN_INIT: Gas = Gas::from_tgas(50);
+const GAS_FOR_ON_TOKEN_DEPLOYED: Gas = Gas::from_tgas(10);
+
+// An entry of the registry of the tokens launched by the factory, so that wallets can discover
+// them without scanning the chain.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FactoryToken {
+    pub token_id: AccountId,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub owner_id: AccountId,
+    /// The timestamp (in nanoseconds) when the token was deployed.
+    pub deployed_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Stores the wasm the factory deploys with `deploy_token` (owner only). Upload this
+    /// contract's own build to launch instances of it.
+    #[payable]
+    pub fn set_factory_code(&mut self, code: Base64VecU8) {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(!code.0.is_empty(), "The code is empty");
+        self.factory_code.set(&code.0);
+    }
+
+    /// Returns the account a token deployed with `prefix` gets: `<prefix>.<factory>`. It can be
+    /// handed out before the token is deployed.
+    pub fn get_factory_token_id(&self, prefix: String) -> AccountId {
+        require!(
+            !prefix.contains('.'),
+            "The prefix should be a single account ID part"
+        );
+        format!("{}.{}", prefix, env::current_account_id())
+            .parse()
+            .unwrap_or_else(|_| env::panic_str("The token account ID is invalid"))
+    }
+
+    /// Creates the `<prefix>.<factory>` account, deploys the factory code to it and initializes
+    /// it with `new` (owner only). The attached deposit funds the new account and must cover the
+    /// storage of the code. Emits a `token_deployed` event once the token is initialized, or
+    /// refunds the deposit if the deployment failed.
+    #[payable]
+    pub fn deploy_token(
+        &mut self,
+        prefix: String,
+        owner_id: AccountId,
+        total_supply: U128,
+        metadata: FungibleTokenMetadata,
+    ) -> Promise {
+        self.assert_owner();
+        let token_id = self.get_factory_token_id(prefix);
+        let code = self
+            .factory_code
+            .get()
+            .unwrap_or_else(|| env::panic_str("The factory code isn't set"));
+        let deposit = env::attached_deposit();
+        require!(
+            deposit >= env::storage_byte_cost().saturating_mul(code.len() as u128),
+            "The attached deposit doesn't cover the storage of the code"
+        );
+
+        self.internal_promise_budget()
+            .call(GAS_FOR_TOKEN_INIT, ZERO_TOKEN)
+            .call(GAS_FOR_ON_TOKEN_DEPLOYED, ZERO_TOKEN)
+            .check();
+        let token = FactoryToken {
+            token_id: token_id.clone(),
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            decimals: metadata.decimals,
+            owner_id: owner_id.clone(),
+            // Set by the callback, once the token is deployed
+            deployed_at: U64(0),
+        };
+        let args = json!({
+            "owner_id": owner_id,
+            "total_supply": total_supply,
+            "metadata": metadata,
+        });
+        Promise::new(token_id)
+            .create_account()
+            .transfer(deposit)
+            .deploy_contract(code)
+            .function_call(
+                "new".to_string(),
+                args.to_string().into_bytes(),
+                ZERO_TOKEN,
+                GAS_FOR_TOKEN_INIT,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_TOKEN_DEPLOYED)
+                    .on_token_deployed(token, total_supply, env::predecessor_account_id(), deposit),
+            )
+    }
+
+    /// Callback of `deploy_token`, adding the token to the registry once it's deployed.
+    /// Returns whether the token was deployed.
+    #[private]
+    pub fn on_token_deployed(
+        &mut self,
+        token: FactoryToken,
+        total_supply: U128,
+        creator_id: AccountId,
+        deposit: NearToken,
+    ) -> bool {
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => {
+                TokenDeployed {
+                    token_id: &token.token_id,
+                    owner_id: &token.owner_id,
+                    total_supply: &total_supply,
+                }
+                .emit();
+                self.factory_tokens.push(&FactoryToken {
+                    deployed_at: U64(env::block_timestamp()),
+                    ..token
+                });
+                true
+            }
+            PromiseResult::Failed => {
+                // The failed batch refunded the deposit to the factory.
+                log!(
+                    "The deployment of {} failed, refunding the deposit",
+                    token.token_id
+                );
+                if deposit.gt(&ZERO_TOKEN) {
+                    Promise::new(creator_id).transfer(deposit);
+                }
+                false
+            }
+        }
+    }
+
+    /// Lists the tokens launched by the factory, in deployment order.
+    pub fn list_tokens(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<FactoryToken> {
+        self.factory_tokens
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
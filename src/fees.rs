@@ -0,0 +1,467 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+pub use near_ft_interface::FeeMode;
+
+use crate::internal::pro_rata;
+use crate::*;
+
+/// The highest transfer fee the owner can set, in basis points (5%).
+pub const MAX_TRANSFER_FEE_BPS: u16 = 500;
+
+// How the collected transfer fee is split between the buckets. The shares are expressed in
+// basis points of the fee and must add up to 100%.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeSplit {
+    pub stakers_bps: u16,
+    pub treasury_bps: u16,
+    pub burn_bps: u16,
+    /// The share kept by the contract account to pay the fee rebates of high-volume accounts.
+    #[serde(default)]
+    pub rebates_bps: u16,
+    /// The share kept by the contract account to fund the insurance payouts.
+    #[serde(default)]
+    pub insurance_bps: u16,
+}
+
+// The fee charged on every `ft_transfer` and `ft_transfer_call`, on top of the amount
+// received by the receiver.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeConfig {
+    /// The fee in basis points of the transferred amount.
+    pub fee_bps: u16,
+    /// How the fee is split between the buckets.
+    pub split: FeeSplit,
+    /// The account (usually a staking contract) receiving the stakers share.
+    pub stakers_pool_id: AccountId,
+    /// The account receiving the treasury share.
+    pub treasury_id: AccountId,
+}
+
+// The running totals of every bucket since the fee switch was turned on.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeAccumulators {
+    pub to_stakers: U128,
+    pub to_treasury: U128,
+    pub burned: U128,
+    pub to_rebates: U128,
+    pub to_insurance: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Turns the fee switch on with the given configuration, or off when `None` is passed.
+    #[payable]
+    pub fn set_fee_config(&mut self, fee_config: Option<FeeConfig>) {
+        assert_one_yocto();
+        self.assert_role(Role::FeeAdmin);
+        if let Some(config) = fee_config.as_ref() {
+            require!(
+                config.fee_bps <= MAX_TRANSFER_FEE_BPS,
+                "The fee can't be higher than 5%"
+            );
+            let split = &config.split;
+            require!(
+                split.stakers_bps as u128
+                    + split.treasury_bps as u128
+                    + split.burn_bps as u128
+                    + split.rebates_bps as u128
+                    + split.insurance_bps as u128
+                    == BPS_DENOMINATOR,
+                "The fee split should add up to 100%"
+            );
+            // Both buckets receive tokens on every transfer, so they must be able to hold them.
+            self.internal_unwrap_balance_of(&config.stakers_pool_id);
+            self.internal_unwrap_balance_of(&config.treasury_id);
+        }
+        self.fee_config = fee_config;
+    }
+
+    /// Charges a fee of `basis_points` on every transfer, sent in full to `collector_id`. This is
+    /// the fee switch with a single bucket: the collector receives both the stakers and the
+    /// treasury shares. The collector must be registered.
+    #[payable]
+    pub fn set_transfer_fee(&mut self, basis_points: u16, collector_id: AccountId) {
+        self.set_fee_config(Some(FeeConfig {
+            fee_bps: basis_points,
+            split: FeeSplit {
+                stakers_bps: 0,
+                treasury_bps: BPS_DENOMINATOR as u16,
+                burn_bps: 0,
+                rebates_bps: 0,
+                insurance_bps: 0,
+            },
+            stakers_pool_id: collector_id.clone(),
+            treasury_id: collector_id,
+        }));
+    }
+
+    /// Returns the current fee configuration, or `null` if the fee switch is off.
+    pub fn get_fee_config(&self) -> Option<FeeConfig> {
+        self.fee_config.clone()
+    }
+
+    /// Sets the fee (in basis points) charged on transfers involving accounts of the given
+    /// category, e.g. `dex_pool`, `bridge` or `retail`. Passing `None` removes the category.
+    #[payable]
+    pub fn set_fee_category_rate(&mut self, category: String, fee_bps: Option<u16>) {
+        assert_one_yocto();
+        self.assert_role(Role::FeeAdmin);
+        match fee_bps {
+            Some(fee_bps) => {
+                require!(
+                    fee_bps <= MAX_TRANSFER_FEE_BPS,
+                    "The fee can't be higher than 5%"
+                );
+                self.fee_category_rates.insert(&category, &fee_bps);
+            }
+            None => {
+                self.fee_category_rates.remove(&category);
+            }
+        }
+    }
+
+    /// Assigns a counterparty category to an account, or removes it when `None` is passed.
+    #[payable]
+    pub fn set_account_fee_category(&mut self, account_id: AccountId, category: Option<String>) {
+        assert_one_yocto();
+        self.assert_role(Role::FeeAdmin);
+        match category {
+            Some(category) => {
+                require!(
+                    self.fee_category_rates.get(&category).is_some(),
+                    "The fee category doesn't exist"
+                );
+                self.account_fee_categories.insert(&account_id, &category);
+            }
+            None => {
+                self.account_fee_categories.remove(&account_id);
+            }
+        }
+    }
+
+    /// Returns every fee category along with its rate in basis points.
+    pub fn get_fee_category_rates(&self) -> Vec<(String, u16)> {
+        self.fee_category_rates.to_vec()
+    }
+
+    /// Returns the counterparty category of an account, if any.
+    pub fn get_account_fee_category(&self, account_id: AccountId) -> Option<String> {
+        self.account_fee_categories.get(&account_id)
+    }
+
+    /// Returns the fee (in basis points) that a transfer from `sender_id` to `receiver_id`
+    /// would be charged right now.
+    pub fn get_transfer_fee_bps(&self, sender_id: AccountId, receiver_id: AccountId) -> u16 {
+        self.fee_config.as_ref().map_or(0, |config| {
+            self.internal_fee_bps(config, &sender_id, &receiver_id)
+        })
+    }
+
+    /// Returns the total amount sent to every fee bucket.
+    pub fn get_fee_accumulators(&self) -> FeeAccumulators {
+        self.fee_accumulators.clone()
+    }
+}
+
+impl Contract {
+    /// Internal method for resolving the fee rate of a transfer. The receiver's category is
+    /// looked up first, then the sender's, falling back to the default rate of the config.
+    pub(crate) fn internal_fee_bps(
+        &self,
+        config: &FeeConfig,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+    ) -> u16 {
+        [receiver_id, sender_id]
+            .into_iter()
+            .find_map(|account_id| {
+                self.account_fee_categories
+                    .get(account_id)
+                    .and_then(|category| self.fee_category_rates.get(&category))
+            })
+            .unwrap_or(config.fee_bps)
+    }
+
+    /// Internal method for computing the fee of a transfer of `amount` tokens, paid by the
+    /// party chosen by `fee_mode`.
+    pub(crate) fn internal_transfer_fee(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        fee_mode: FeeMode,
+    ) -> u128 {
+        let Some(config) = self.fee_config.as_ref() else {
+            return 0;
+        };
+        // The buckets themselves don't pay fees, otherwise they would pay themselves.
+        let payer_id = match fee_mode {
+            FeeMode::SenderPays => sender_id,
+            FeeMode::ReceiverPays => receiver_id,
+        };
+        if payer_id == &config.stakers_pool_id || payer_id == &config.treasury_id {
+            return 0;
+        }
+        let fee_bps = self.internal_fee_bps(config, sender_id, receiver_id);
+        pro_rata(amount, fee_bps as u128, BPS_DENOMINATOR)
+    }
+
+    /// Internal method for computing the amount `receiver_id` is credited by a transfer of
+    /// `amount` tokens, once the fee is deducted if the receiver pays it, and the burn set by
+    /// the tokenomics.
+    pub(crate) fn internal_received_amount(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        fee_mode: FeeMode,
+    ) -> u128 {
+        let received = amount.saturating_sub(self.internal_transfer_burn(amount));
+        match fee_mode {
+            FeeMode::SenderPays => received,
+            FeeMode::ReceiverPays => received.saturating_sub(self.internal_transfer_fee(
+                sender_id,
+                receiver_id,
+                amount,
+                fee_mode,
+            )),
+        }
+    }
+
+    /// Internal method for rejecting a transfer crediting the receiver less than `min_received`.
+    pub(crate) fn internal_assert_min_received(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        fee_mode: FeeMode,
+        min_received: Option<u128>,
+    ) {
+        if let Some(min_received) = min_received {
+            require!(
+                self.internal_received_amount(sender_id, receiver_id, amount, fee_mode)
+                    >= min_received,
+                "The receiver would be credited less than the minimum received"
+            );
+        }
+    }
+
+    /// Internal method for performing a user transfer, charging the fee on top of `amount`.
+    /// Panics if the sender's transfer policy doesn't allow the transfer.
+    pub(crate) fn internal_transfer_with_fee(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+    ) {
+        self.internal_transfer_with_fee_mode(
+            sender_id,
+            receiver_id,
+            amount,
+            memo,
+            FeeMode::SenderPays,
+        );
+    }
+
+    /// Internal method for performing a user transfer, charging the fee to the party chosen by
+    /// `fee_mode` and burning the share set by the tokenomics from the receiver. Returns the
+    /// amount credited to the receiver.
+    pub(crate) fn internal_transfer_with_fee_mode(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+        fee_mode: FeeMode,
+    ) -> u128 {
+        self.internal_authorize_transfer(sender_id, receiver_id, amount, memo, None);
+        self.internal_transfer(sender_id, receiver_id, amount, memo);
+        self.internal_settle_transfer(sender_id, receiver_id, amount, fee_mode)
+    }
+
+    /// Internal method for charging the fee of a user transfer of `amount` tokens already
+    /// credited to the receiver, and burning the share set by the tokenomics from the receiver.
+    /// Returns the amount left to the receiver.
+    pub(crate) fn internal_settle_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        fee_mode: FeeMode,
+    ) -> u128 {
+        let fee = self.internal_charge_transfer_fee(sender_id, receiver_id, amount, fee_mode);
+        let burned = self.internal_burn_on_transfer(receiver_id, amount);
+        match fee_mode {
+            FeeMode::SenderPays => amount - burned,
+            FeeMode::ReceiverPays => amount.saturating_sub(fee).saturating_sub(burned),
+        }
+    }
+
+    /// Internal method for settling a queued transfer of `amount` tokens already credited to the
+    /// receiver out of the contract account: the fee escrowed when it was queued is charged, and
+    /// the share set by the tokenomics is burned from the receiver. Nothing was escrowed for the
+    /// transfers queued without a fee, or before the fees were escrowed: they're charged as of
+    /// the settlement. Returns the amount left to the receiver.
+    pub(crate) fn internal_settle_escrowed_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        fee: u128,
+        memo: &str,
+    ) -> u128 {
+        if fee > 0 {
+            self.internal_charge_escrowed_fee(sender_id, amount, fee, memo);
+        } else {
+            self.internal_charge_transfer_fee(sender_id, receiver_id, amount, FeeMode::SenderPays);
+        }
+        amount - self.internal_burn_on_transfer(receiver_id, amount)
+    }
+
+    /// Internal method for charging the fee of a transfer of `amount` tokens to the sender or
+    /// the receiver and splitting it between the stakers pool, the treasury, the rebate pool,
+    /// the insurance fund and a burn. Returns the charged fee.
+    pub(crate) fn internal_charge_transfer_fee(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        fee_mode: FeeMode,
+    ) -> u128 {
+        let payer_id = match fee_mode {
+            FeeMode::SenderPays => sender_id,
+            FeeMode::ReceiverPays => receiver_id,
+        };
+        match self.fee_config.as_ref() {
+            None => return 0,
+            // The buckets themselves don't pay fees, otherwise they would pay themselves.
+            Some(config)
+                if payer_id == &config.stakers_pool_id || payer_id == &config.treasury_id =>
+            {
+                return 0
+            }
+            Some(_) => {}
+        }
+        let fee = self.internal_transfer_fee(sender_id, receiver_id, amount, fee_mode);
+        self.internal_track_rebate_volume(payer_id, amount, fee);
+        self.internal_split_fee(payer_id, payer_id, fee);
+        fee
+    }
+
+    /// Internal method for charging the fee of a transfer of `amount` tokens escrowed by the
+    /// contract account when the transfer was queued, as it was computed then. The fee goes back
+    /// to the payer if the fee switch has been turned off since.
+    pub(crate) fn internal_charge_escrowed_fee(
+        &mut self,
+        payer_id: &AccountId,
+        amount: u128,
+        fee: u128,
+        memo: &str,
+    ) {
+        if self.fee_config.is_none() {
+            // The payer may have been frozen since: the refund is the contract's own move.
+            if fee > 0 {
+                self.internal_contract_transfer(
+                    &env::current_account_id(),
+                    payer_id,
+                    fee,
+                    Some(memo),
+                );
+            }
+            return;
+        }
+        self.internal_track_rebate_volume(payer_id, amount, fee);
+        self.internal_split_fee(payer_id, &env::current_account_id(), fee);
+    }
+
+    /// Internal method for splitting the fee paid by `payer_id`, held by `holder_id`, between
+    /// the stakers pool, the treasury, the rebate pool, the insurance fund and a burn.
+    fn internal_split_fee(&mut self, payer_id: &AccountId, holder_id: &AccountId, fee: u128) {
+        if fee == 0 {
+            return;
+        }
+        // Only clone the config (and its account IDs) once there is a fee to split.
+        let config = self
+            .fee_config
+            .clone()
+            .unwrap_or_else(|| env::panic_str("The fee switch is off"));
+
+        // The rounding remainder goes to the dust account, or is burned when there is none, so
+        // the shares always add up to the fee.
+        let share = |bps: u16| pro_rata(fee, bps as u128, BPS_DENOMINATOR);
+        let to_stakers = share(config.split.stakers_bps);
+        let to_treasury = share(config.split.treasury_bps);
+        let to_rebates = share(config.split.rebates_bps);
+        let to_insurance = share(config.split.insurance_bps);
+        let mut burned = share(config.split.burn_bps);
+        let dust = fee
+            .saturating_sub(to_stakers)
+            .saturating_sub(to_treasury)
+            .saturating_sub(to_rebates)
+            .saturating_sub(to_insurance)
+            .saturating_sub(burned);
+        if !self.internal_route_dust(holder_id, dust, "Fee split remainder") {
+            burned += dust;
+        }
+
+        self.internal_pay_fee_share(holder_id, &config.stakers_pool_id, to_stakers);
+        self.internal_pay_fee_share(holder_id, &config.treasury_id, to_treasury);
+        if to_rebates > 0 {
+            self.internal_pay_fee_share(holder_id, &env::current_account_id(), to_rebates);
+            self.rebate_pool = self.rebate_pool.saturating_add(to_rebates);
+        }
+        if to_insurance > 0 {
+            self.internal_pay_fee_share(holder_id, &env::current_account_id(), to_insurance);
+            self.insurance_fund = self.insurance_fund.saturating_add(to_insurance);
+        }
+        if burned > 0 {
+            self.internal_burn(holder_id, burned, Some("Fee burn"));
+        }
+
+        // The accumulators and the event count the tokens the shares were worth when charged.
+        let to_stakers = Self::internal_to_amount(to_stakers);
+        let to_treasury = Self::internal_to_amount(to_treasury);
+        let burned = Self::internal_to_amount(burned);
+        let to_rebates = Self::internal_to_amount(to_rebates);
+        let to_insurance = Self::internal_to_amount(to_insurance);
+        let accumulators = &mut self.fee_accumulators;
+        accumulators.to_stakers = U128(accumulators.to_stakers.0.saturating_add(to_stakers));
+        accumulators.to_treasury = U128(accumulators.to_treasury.0.saturating_add(to_treasury));
+        accumulators.burned = U128(accumulators.burned.0.saturating_add(burned));
+        accumulators.to_rebates = U128(accumulators.to_rebates.0.saturating_add(to_rebates));
+        accumulators.to_insurance = U128(accumulators.to_insurance.0.saturating_add(to_insurance));
+
+        FeeDistribution {
+            payer_id,
+            to_stakers: &U128(to_stakers),
+            to_treasury: &U128(to_treasury),
+            burned: &U128(burned),
+            to_rebates: &U128(to_rebates),
+            to_insurance: &U128(to_insurance),
+        }
+        .emit();
+    }
+
+    /// Internal method for moving a share of a fee to its bucket. The share already sits in
+    /// the bucket when it's the holder, like the contract paying for a transfer it receives.
+    fn internal_pay_fee_share(
+        &mut self,
+        holder_id: &AccountId,
+        bucket_id: &AccountId,
+        share: u128,
+    ) {
+        if share > 0 && holder_id != bucket_id {
+            self.internal_contract_transfer(holder_id, bucket_id, share, Some("Fee"));
+        }
+    }
+}
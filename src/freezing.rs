@@ -0,0 +1,214 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::versioned::Versioned;
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// The maximum length of an appeal note
+const MAX_APPEAL_NOTE_LEN: usize = 1024;
+
+// A note filed by a frozen account to contest its freeze.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FreezeAppeal {
+    pub note: String,
+    /// The timestamp (in nanoseconds) when the appeal was filed.
+    pub filed_at: U64,
+}
+
+// A freeze of an account. Frozen accounts can't send or receive tokens until the freeze
+// expires, unless it's renewed before that. The freeze is read on every transfer, so the
+// appeal of the account is kept in its blob instead.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Freeze {
+    /// The code of the reason the account is frozen for, as defined by the issuer's policy.
+    pub reason_code: u16,
+    /// The timestamp (in nanoseconds) when the account was frozen.
+    pub frozen_at: U64,
+    /// The timestamp (in nanoseconds) when the freeze expires.
+    pub expires_at: U64,
+}
+
+// A freeze made before the appeals moved to the account blobs.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct FreezeV1 {
+    pub reason_code: u16,
+    pub frozen_at: U64,
+    pub expires_at: U64,
+    pub appeal: Option<FreezeAppeal>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedFreeze {
+    V1(FreezeV1),
+    V2(Freeze),
+}
+
+impl Versioned for Freeze {
+    type Record = VersionedFreeze;
+
+    // The appeal of a `V1` freeze is read by `internal_legacy_freeze_appeal` until it's moved
+    // to the account blob.
+    fn from_record(record: VersionedFreeze) -> Self {
+        match record {
+            VersionedFreeze::V1(freeze) => Self {
+                reason_code: freeze.reason_code,
+                frozen_at: freeze.frozen_at,
+                expires_at: freeze.expires_at,
+            },
+            VersionedFreeze::V2(freeze) => freeze,
+        }
+    }
+
+    fn into_record(self) -> VersionedFreeze {
+        VersionedFreeze::V2(self)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Freezes an account for `duration_sec` seconds for the given reason, replacing any
+    /// previous freeze (and its appeal). Must be called by the owner or a freeze admin.
+    #[payable]
+    pub fn freeze_account(&mut self, account_id: AccountId, reason_code: u16, duration_sec: u64) {
+        assert_one_yocto();
+        self.assert_role(Role::FreezeAdmin);
+        let now = env::block_timestamp();
+        let freeze = Freeze {
+            reason_code,
+            frozen_at: U64(now),
+            expires_at: U64(Self::internal_freeze_expiry(now, duration_sec)),
+        };
+        self.frozen_accounts.insert(&account_id, &freeze);
+        self.internal_update_account_blob(&account_id, |blob| blob.freeze_appeal = None);
+        AccountFrozen {
+            account_id: &account_id,
+            reason_code,
+            expires_at: &freeze.expires_at,
+        }
+        .emit();
+    }
+
+    /// Extends a freeze that didn't expire yet to `duration_sec` seconds from now.
+    #[payable]
+    pub fn renew_freeze(&mut self, account_id: AccountId, duration_sec: u64) {
+        assert_one_yocto();
+        self.assert_role(Role::FreezeAdmin);
+        let mut freeze = self.internal_get_active_freeze(&account_id);
+        // Rewriting the freeze would drop an appeal filed before the account blobs existed.
+        self.internal_update_account_blob(&account_id, |_| {});
+        freeze.expires_at = U64(Self::internal_freeze_expiry(
+            env::block_timestamp(),
+            duration_sec,
+        ));
+        self.frozen_accounts.insert(&account_id, &freeze);
+        AccountFrozen {
+            account_id: &account_id,
+            reason_code: freeze.reason_code,
+            expires_at: &freeze.expires_at,
+        }
+        .emit();
+    }
+
+    /// Lifts the freeze of an account.
+    #[payable]
+    pub fn unfreeze_account(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_role(Role::FreezeAdmin);
+        require!(
+            self.frozen_accounts.remove(&account_id).is_some(),
+            "The account is not frozen"
+        );
+        self.internal_update_account_blob(&account_id, |blob| blob.freeze_appeal = None);
+        AccountUnfrozen {
+            account_id: &account_id,
+        }
+        .emit();
+    }
+
+    /// Files (or replaces) the caller's appeal against its freeze.
+    pub fn file_freeze_appeal(&mut self, note: String) {
+        let account_id = env::predecessor_account_id();
+        require!(
+            note.len() <= MAX_APPEAL_NOTE_LEN,
+            "The appeal note is too long"
+        );
+        self.internal_get_active_freeze(&account_id);
+        let appeal = FreezeAppeal {
+            note,
+            filed_at: U64(env::block_timestamp()),
+        };
+        self.internal_update_account_blob(&account_id, |blob| blob.freeze_appeal = Some(appeal));
+    }
+
+    /// Returns whether an account is frozen right now.
+    pub fn is_frozen(&self, account_id: AccountId) -> bool {
+        self.internal_active_freeze(&account_id).is_some()
+    }
+
+    /// Returns the freeze of an account if it didn't expire yet.
+    pub fn get_freeze(&self, account_id: AccountId) -> Option<Freeze> {
+        self.internal_active_freeze(&account_id)
+    }
+
+    /// Returns the appeal an account filed against its freeze, if any.
+    pub fn get_freeze_appeal(&self, account_id: AccountId) -> Option<FreezeAppeal> {
+        self.internal_account_blob(&account_id).freeze_appeal
+    }
+}
+
+impl Contract {
+    /// Internal method for getting the freeze of an account if it didn't expire yet.
+    pub(crate) fn internal_active_freeze(&self, account_id: &AccountId) -> Option<Freeze> {
+        self.frozen_accounts
+            .get(account_id)
+            .filter(|freeze| env::block_timestamp() < freeze.expires_at.0)
+    }
+
+    /// Internal method for getting the appeal kept in the freeze of an account, if it was filed
+    /// before the appeals moved to the account blobs.
+    pub(crate) fn internal_legacy_freeze_appeal(
+        &self,
+        account_id: &AccountId,
+    ) -> Option<FreezeAppeal> {
+        match self.frozen_accounts.get_record(account_id)? {
+            VersionedFreeze::V1(freeze) => freeze.appeal,
+            VersionedFreeze::V2(_) => None,
+        }
+    }
+
+    /// Internal method for rewriting the freeze of an account as the current version, once its
+    /// legacy appeal is in the account blob.
+    pub(crate) fn internal_upgrade_freeze_record(&mut self, account_id: &AccountId) {
+        if let Some(record @ VersionedFreeze::V1(_)) = self.frozen_accounts.get_record(account_id) {
+            self.frozen_accounts
+                .insert(account_id, &Freeze::from_record(record));
+        }
+    }
+
+    /// Internal method for asserting that an account is not frozen.
+    pub(crate) fn internal_assert_not_frozen(&self, account_id: &AccountId) {
+        if self.internal_active_freeze(account_id).is_some() {
+            self.internal_panic(ErrorCode::AccountFrozen(account_id));
+        }
+    }
+
+    fn internal_get_active_freeze(&self, account_id: &AccountId) -> Freeze {
+        self.internal_active_freeze(account_id)
+            .unwrap_or_else(|| env::panic_str("The account is not frozen"))
+    }
+
+    fn internal_freeze_expiry(now: u64, duration_sec: u64) -> u64 {
+        require!(duration_sec > 0, "The freeze duration should be positive");
+        now.saturating_add(duration_sec.saturating_mul(NANOS_PER_SECOND))
+    }
+}
@@ -1,143 +1,250 @@
-use near_sdk::{assert_one_yocto, env, ext_contract, Gas, PromiseOrValue, PromiseResult};
+// The NEP-141 transfer methods take the standard arguments plus the optional transfer options.
+#![allow(clippy::too_many_arguments)]
 
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_one_yocto, env, log, require, Gas, NearSchema, PromiseOrValue, PromiseResult,
+};
+
+pub use near_ft_interface::ft_core::{
+    ext_ft_core, ext_ft_receiver, FeeMode, FungibleTokenCore, FungibleTokenReceiver, TransferArg,
+};
+
+use crate::storage::StorageManagement;
+use crate::versioned::Versioned;
 use crate::*;
 
-const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas::from_tgas(5);
-const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas::from_tgas(25).saturating_add(GAS_FOR_RESOLVE_TRANSFER);
-
-#[ext_contract(ext_ft_core)]
-pub trait FungibleTokenCore {
-    /// Transfers positive `amount` of tokens from the `env::predecessor_account_id` to `receiver_id`.
-    /// Both accounts must be registered with the contract for transfer to succeed. (See [NEP-145](https://github.com/near/NEPs/discussions/145))
-    /// This method must to be able to accept attached deposits, and must not panic on attached deposit.
-    /// Exactly 1 yoctoNEAR must be attached.
-    /// See [the Security section](https://github.com/near/NEPs/issues/141#user-content-security) of the standard.
-    ///
-    /// Arguments:
-    /// - `receiver_id` - the account ID of the receiver.
-    /// - `amount` - the amount of tokens to transfer. Must be a positive number in decimal string representation.
-    /// - `memo` - an optional string field in a free form to associate a memo with this transfer.
-    fn ft_transfer(&mut self, receiver_id: AccountId, amount: NearToken, memo: Option<String>);
-
-    /// Transfers positive `amount` of tokens from the `env::predecessor_account_id` to `receiver_id` account. Then
-    /// calls `ft_on_transfer` method on `receiver_id` contract and attaches a callback to resolve this transfer.
-    /// `ft_on_transfer` method must return the amount of tokens unused by the receiver contract, the remaining tokens
-    /// must be refunded to the `predecessor_account_id` at the resolve transfer callback.
-    ///
-    /// Token contract must pass all the remaining unused gas to the `ft_on_transfer` call.
-    ///
-    /// Malicious or invalid behavior by the receiver's contract:
-    /// - If the receiver contract promise fails or returns invalid value, the full transfer amount must be refunded.
-    /// - If the receiver contract overspent the tokens, and the `receiver_id` balance is lower than the required refund
-    /// amount, the remaining balance must be refunded. See [the Security section](https://github.com/near/NEPs/issues/141#user-content-security) of the standard.
-    ///
-    /// Both accounts must be registered with the contract for transfer to succeed. (See #145)
-    /// This method must to be able to accept attached deposits, and must not panic on attached deposit. Exactly 1 yoctoNEAR must be attached. See [the Security
-    /// section](https://github.com/near/NEPs/issues/141#user-content-security) of the standard.
-    ///
-    /// Arguments:
-    /// - `receiver_id` - the account ID of the receiver contract. This contract will be called.
-    /// - `amount` - the amount of tokens to transfer. Must be a positive number in a decimal string representation.
-    /// - `memo` - an optional string field in a free form to associate a memo with this transfer.
-    /// - `msg` - a string message that will be passed to `ft_on_transfer` contract call.
-    ///
-    /// Returns a promise which will result in the amount of tokens withdrawn from sender's account.
-    fn ft_transfer_call(
-        &mut self,
-        receiver_id: AccountId,
-        amount: NearToken,
-        memo: Option<String>,
-        msg: String,
-    ) -> PromiseOrValue<NearToken>;
+pub(crate) const GAS_FOR_RESOLVE_TRANSFER: Gas = Gas::from_tgas(5);
+pub(crate) const GAS_FOR_FT_TRANSFER_CALL: Gas =
+    Gas::from_tgas(25).saturating_add(GAS_FOR_RESOLVE_TRANSFER);
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+/// The gas kept for the rest of a method once its promises are created.
+const GAS_FOR_PROMISE_CREATION: Gas = Gas::from_tgas(5);
+
+pub type PendingTransferId = u64;
+
+/// The maximum number of transfers in a single `ft_transfer_batch` call
+const MAX_TRANSFER_BATCH_SIZE: usize = 100;
+
+// The second signer an account requires for its transfers above `threshold`. Once set, only the
+// co-signer can change or remove it, so a compromised account key alone can't move large amounts.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct CoSignerConfig {
+    /// The account confirming the transfers above the threshold.
+    pub co_signer_id: AccountId,
+    /// Transfers of more than this amount need to be confirmed.
+    pub threshold: U128,
+    /// The number of seconds the co-signer has to confirm a transfer.
+    pub window_sec: u64,
+}
+
+// A transfer waiting for the co-signer's confirmation. The amount and the fee are escrowed by
+// the contract account until the transfer is confirmed or cancelled.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingTransfer {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub memo: Option<String>,
+    /// The account that has to confirm the transfer.
+    pub co_signer_id: AccountId,
+    /// The timestamp (in nanoseconds) after which the transfer can't be confirmed anymore.
+    pub expires_at: U64,
+    /// The fee escrowed along with the amount.
+    pub fee: U128,
+}
+
+// A pending transfer queued before its fee was escrowed.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct PendingTransferV1 {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub memo: Option<String>,
+    pub co_signer_id: AccountId,
+    pub expires_at: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedPendingTransfer {
+    V1(PendingTransferV1),
+    V2(PendingTransfer),
+}
+
+impl Versioned for PendingTransfer {
+    type Record = VersionedPendingTransfer;
+
+    fn from_record(record: VersionedPendingTransfer) -> Self {
+        match record {
+            VersionedPendingTransfer::V1(transfer) => Self {
+                sender_id: transfer.sender_id,
+                receiver_id: transfer.receiver_id,
+                amount: transfer.amount,
+                memo: transfer.memo,
+                co_signer_id: transfer.co_signer_id,
+                expires_at: transfer.expires_at,
+                fee: U128(0),
+            },
+            VersionedPendingTransfer::V2(transfer) => transfer,
+        }
+    }
 
-    /// Returns the total supply of the token in a decimal string representation.
-    fn ft_total_supply(&self) -> U128;
+    fn into_record(self) -> VersionedPendingTransfer {
+        VersionedPendingTransfer::V2(self)
+    }
+}
+
+// The limits of every cross-contract call the contract makes, callbacks included.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct CallCaps {
+    /// The most gas attached to a call, in TGas.
+    pub max_gas_tgas: u64,
+    /// The most NEAR attached to a call.
+    pub max_deposit: NearToken,
+}
 
-    /// Returns the balance of the account. If the account doesn't exist must returns `"0"`.
-    fn ft_balance_of(&self, account_id: AccountId) -> NearToken;
+/// The calls a method is about to make, checked against the call caps and the prepaid gas left
+/// before the promises are created. An underfunded call then fails with an error code, rather
+/// than with a runtime error once the method already changed the state.
+#[must_use]
+pub(crate) struct PromiseBudget<'a> {
+    contract: &'a Contract,
+    gas: Gas,
+}
+
+impl PromiseBudget<'_> {
+    /// Adds a call with `gas` and `deposit` attached, failing if it exceeds the call caps.
+    pub(crate) fn call(mut self, gas: Gas, deposit: NearToken) -> Self {
+        if let Some(caps) = &self.contract.call_caps {
+            if gas > Gas::from_tgas(caps.max_gas_tgas) {
+                self.contract
+                    .internal_panic(ErrorCode::CallGasCapExceeded(caps.max_gas_tgas));
+            }
+            if deposit > caps.max_deposit {
+                self.contract
+                    .internal_panic(ErrorCode::CallDepositCapExceeded(caps.max_deposit));
+            }
+        }
+        self.gas = self.gas.saturating_add(gas);
+        self
+    }
+
+    /// Fails if the prepaid gas left doesn't cover the calls.
+    pub(crate) fn check(self) {
+        let required = self.gas.saturating_add(GAS_FOR_PROMISE_CREATION);
+        let left = env::prepaid_gas().saturating_sub(env::used_gas());
+        if left < required {
+            let required_tgas = required.as_gas().div_ceil(Gas::from_tgas(1).as_gas());
+            self.contract
+                .internal_panic(ErrorCode::NotEnoughGas(required_tgas));
+        }
+    }
 }
 
 #[near_bindgen]
 impl FungibleTokenCore for Contract {
     #[payable]
-    fn ft_transfer(&mut self, receiver_id: AccountId, amount: NearToken, memo: Option<String>) {
+    fn ft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        valid_until: Option<U64>,
+        min_received: Option<U128>,
+        fee_mode: Option<FeeMode>,
+    ) {
         // Assert that the user attached exactly 1 yoctoNEAR. This is for security and so that the user will be required to sign with a FAK.
-        assert_one_yocto();
-        // The sender is the user who called the method
-        let sender_id = env::predecessor_account_id();
-        // Transfer the tokens
-        self.internal_transfer(&sender_id, &receiver_id, amount, memo);
+        // Transfers waiting for a co-signer also pay the storage of their pending record.
+        if self
+            .internal_co_signer_required(
+                &env::predecessor_account_id(),
+                Self::internal_to_shares(amount.0),
+            )
+            .is_some()
+        {
+            require!(
+                env::attached_deposit() >= NearToken::from_yoctonear(1),
+                "Requires attached deposit of at least 1 yoctoNEAR"
+            );
+        } else {
+            assert_one_yocto();
+        }
+        self.internal_ft_transfer(
+            receiver_id,
+            amount,
+            memo,
+            valid_until,
+            min_received,
+            fee_mode,
+        );
     }
-
     #[payable]
     fn ft_transfer_call(
         &mut self,
         receiver_id: AccountId,
-        amount: NearToken,
+        amount: U128,
         memo: Option<String>,
         msg: String,
-    ) -> PromiseOrValue<NearToken> {
-        // Assert that the user attached exactly 1 yoctoNEAR. This is for security and so that the user will be required to sign with a FAK.
-        assert_one_yocto();
-        // The sender is the user who called the method
-        let sender_id = env::predecessor_account_id();
-        // Transfer the tokens
-        self.internal_transfer(&sender_id, &receiver_id, amount, memo);
+        valid_until: Option<U64>,
+        min_received: Option<U128>,
+        fee_mode: Option<FeeMode>,
+    ) -> PromiseOrValue<U128> {
+        let (sender_id, amount) = self.internal_start_transfer_call(
+            &receiver_id,
+            amount,
+            memo,
+            valid_until,
+            min_received,
+            fee_mode,
+        );
+        // Receivers netting their transfer calls are notified of them at the end of the window.
+        if self.netting_windows.contains_key(&receiver_id) {
+            self.internal_net_transfer_call(sender_id, receiver_id, amount, msg);
+            return PromiseOrValue::Value(U128(Self::internal_to_amount(amount)));
+        }
 
         // Initiating receiver's call and the callback
         // Defaulting GAS weight to 1, no attached deposit, and static GAS equal to the GAS for ft transfer call.
         ext_ft_receiver::ext(receiver_id.clone())
             .with_static_gas(GAS_FOR_FT_TRANSFER_CALL)
-            .ft_on_transfer(sender_id.clone(), amount.into(), msg)
+            .ft_on_transfer(
+                sender_id.clone(),
+                U128(Self::internal_to_amount(amount)),
+                msg,
+            )
             // We then resolve the promise and call ft_resolve_transfer on our own contract
             // Defaulting GAS weight to 1, no attached deposit, and static GAS equal to the GAS for resolve transfer
             .then(
                 Self::ext(env::current_account_id())
                     .with_static_gas(GAS_FOR_RESOLVE_TRANSFER)
-                    .ft_resolve_transfer(&sender_id, receiver_id, amount),
+                    .ft_resolve_transfer(&sender_id, receiver_id, U128(amount)),
             )
             .into()
     }
 
     fn ft_total_supply(&self) -> U128 {
         // Return the total supply
-        U128(self.total_supply.as_yoctonear())
+        U128(Self::internal_to_amount(self.total_supply))
     }
 
-    fn ft_balance_of(&self, account_id: AccountId) -> NearToken {
-        // Return the balance of the account
-        self.accounts.get(&account_id).unwrap_or(ZERO_TOKEN)
+    fn ft_balance_of(account_id: AccountId) -> U128 {
+        // Return the balance of the account, without loading the contract state
+        U128(Self::internal_to_amount(
+            Self::stateless_accounts().get(&account_id).unwrap_or(0),
+        ))
     }
 }
 
-#[ext_contract(ext_ft_receiver)]
-pub trait FungibleTokenReceiver {
-    /// Called by fungible token contract after `ft_transfer_call` was initiated by
-    /// `sender_id` of the given `amount` with the transfer message given in `msg` field.
-    /// The `amount` of tokens were already transferred to this contract account and ready to be used.
-    ///
-    /// The method must return the amount of tokens that are *not* used/accepted by this contract from the transferred
-    /// amount. Examples:
-    /// - The transferred amount was `500`, the contract completely takes it and must return `0`.
-    /// - The transferred amount was `500`, but this transfer call only needs `450` for the action passed in the `msg`
-    ///   field, then the method must return `50`.
-    /// - The transferred amount was `500`, but the action in `msg` field has expired and the transfer must be
-    ///   cancelled. The method must return `500` or panic.
-    ///
-    /// Arguments:
-    /// - `sender_id` - the account ID that initiated the transfer.
-    /// - `amount` - the amount of tokens that were transferred to this account in a decimal string representation.
-    /// - `msg` - a string message that was passed with this transfer call.
-    ///
-    /// Returns the amount of unused tokens that should be returned to sender, in a decimal string representation.
-    fn ft_on_transfer(
-        &mut self,
-        sender_id: AccountId,
-        amount: NearToken,
-        msg: String,
-    ) -> PromiseOrValue<NearToken>;
-}
-
 #[near_bindgen]
 impl Contract {
     // Finalize an `ft_transfer_call` chain of cross-contract calls.
@@ -156,6 +263,8 @@ impl Contract {
     // * If promise chain failed, contract MUST revert token transfer
     // * If promise chain resolves with a non-zero amount given as a string,
     //   contract MUST return this amount of tokens to `sender_id`
+    // * If `sender_id` unregistered in the meantime, the tokens to return are
+    //   burned, and the full `amount` counts as spent
     //
     // Arguments:
     // * `sender_id`: the sender of `ft_transfer_call`
@@ -172,38 +281,64 @@ impl Contract {
         &mut self,
         sender_id: &AccountId,
         receiver_id: AccountId,
-        amount: NearToken,
-    ) -> NearToken {
+        amount: U128,
+    ) -> U128 {
+        let amount = amount.0;
         // Get the unused amount from the `ft_on_transfer` call result.
         let unused_amount = match env::promise_result(0) {
             // If the promise was successful, get the return value
             PromiseResult::Successful(value) => {
                 // If we can properly parse the value, the unused amount is equal to whatever is smaller - the unused amount or the original amount (to prevent malicious contracts)
-                if let Ok(unused_amount) = near_sdk::serde_json::from_slice::<NearToken>(&value) {
-                    std::cmp::min(amount, unused_amount)
+                if let Ok(unused_amount) = near_sdk::serde_json::from_slice::<U128>(&value) {
+                    std::cmp::min(amount, Self::internal_to_shares(unused_amount.0))
                 // If we can't properly parse the value, the original amount is returned.
                 } else {
+                    self.metrics.failed_transfer_calls += 1;
                     amount
                 }
             }
             // If the promise wasn't successful, return the original amount.
-            PromiseResult::Failed => amount,
+            PromiseResult::Failed => {
+                self.metrics.failed_transfer_calls += 1;
+                amount
+            }
         };
+        let used_amount =
+            self.internal_refund_unused(sender_id, &receiver_id, amount, unused_amount);
+        U128(Self::internal_to_amount(used_amount))
+    }
+}
 
+impl Contract {
+    /// Internal method for refunding the sender of a transfer call the `unused_amount` of the
+    /// `amount` it sent, as much as the receiver still has. Returns the amount spent.
+    pub(crate) fn internal_refund_unused(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        unused_amount: u128,
+    ) -> u128 {
         // If there is some unused amount, we should refund the sender
-        if unused_amount.gt(&ZERO_TOKEN) {
+        if unused_amount > 0 {
             // Get the receiver's balance. We can only refund the sender if the receiver has enough balance.
-            let receiver_balance = self.accounts.get(&receiver_id).unwrap_or(ZERO_TOKEN);
-            if receiver_balance.gt(&ZERO_TOKEN) {
+            let receiver_balance = self.accounts.get(receiver_id).unwrap_or(0);
+            if receiver_balance > 0 {
                 // The amount to refund is the smaller of the unused amount and the receiver's balance as we can only refund up to what the receiver currently has.
                 let refund_amount = std::cmp::min(receiver_balance, unused_amount);
 
-                // Refund the sender for the unused amount.
-                self.internal_transfer(
-                    &receiver_id,
-                    &sender_id,
+                // Refund the sender for the unused amount. If the sender unregistered in the
+                // meantime, the refund has nowhere to go and is burned instead.
+                if !self.accounts.contains_key(sender_id) {
+                    log!("The account of the sender was deleted");
+                    self.internal_burn(receiver_id, refund_amount, Some("Refund"));
+                    return amount;
+                }
+                self.internal_contract_transfer(
+                    receiver_id,
+                    sender_id,
                     refund_amount,
-                    Some("Refund".to_string()),
+                    Some("Refund"),
                 );
 
                 // Return what was actually used (the amount sent - refund)
@@ -218,3 +353,462 @@ impl Contract {
         amount
     }
 }
+
+#[near_bindgen]
+impl Contract {
+    /// Same as `ft_transfer`, registering the receiver first if it isn't, out of the attached
+    /// deposit. The excess deposit is refunded, like `storage_deposit` does, and the whole
+    /// deposit if the receiver is already registered. At least 1 yoctoNEAR must be attached.
+    #[payable]
+    pub fn ft_transfer_and_register(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        self.internal_assert_not_co_signed(&env::predecessor_account_id(), amount.0);
+        self.storage_deposit(Some(receiver_id.clone()), Some(true));
+        self.internal_ft_transfer(receiver_id, amount, memo, None, None, None);
+    }
+
+    /// Transfers tokens from the caller to every receiver at once, and logs them as a single
+    /// `ft_transfer` event. Each transfer is then charged its fee and the burn set by the
+    /// tokenomics like an `ft_transfer`, logged after it as their own events. Every receiver
+    /// must be registered, otherwise none of the transfers is made.
+    #[payable]
+    pub fn ft_transfer_batch(&mut self, transfers: Vec<TransferArg>) {
+        assert_one_yocto();
+        require!(!transfers.is_empty(), "There are no transfers");
+        require!(
+            transfers.len() <= MAX_TRANSFER_BATCH_SIZE,
+            "Too many transfers in a single batch"
+        );
+        let sender_id = env::predecessor_account_id();
+        for transfer in transfers.iter() {
+            self.internal_sponsor_receiver(&sender_id, &transfer.receiver_id);
+            self.internal_registered_balance_of(&transfer.receiver_id);
+        }
+
+        let mut amounts = Vec::with_capacity(transfers.len());
+        for transfer in transfers.iter() {
+            let memo = transfer.memo.as_deref();
+            let amount = Self::internal_to_shares(transfer.amount.0);
+            self.internal_authorize_transfer(&sender_id, &transfer.receiver_id, amount, memo, None);
+            self.internal_move_balance(&sender_id, &transfer.receiver_id, amount);
+            amounts.push(amount);
+        }
+        let logged: Vec<U128> = amounts
+            .iter()
+            .map(|amount| U128(Self::internal_to_amount(*amount)))
+            .collect();
+        let events: Vec<FtTransfer> = transfers
+            .iter()
+            .zip(logged.iter())
+            .map(|(transfer, amount)| FtTransfer {
+                old_owner_id: &sender_id,
+                new_owner_id: &transfer.receiver_id,
+                amount,
+                memo: transfer.memo.as_deref(),
+            })
+            .collect();
+        FtTransfer::emit_many(&events);
+
+        for (transfer, amount) in transfers.iter().zip(amounts) {
+            self.internal_settle_transfer(
+                &sender_id,
+                &transfer.receiver_id,
+                amount,
+                FeeMode::SenderPays,
+            );
+        }
+    }
+
+    /// Requires `config.co_signer_id` to confirm the transfers of `account_id` above
+    /// `config.threshold`, or removes the requirement when `None` is passed. Must be called by
+    /// the account itself while it has no co-signer, and by its current co-signer afterwards.
+    #[payable]
+    pub fn set_co_signer(&mut self, account_id: AccountId, config: Option<CoSignerConfig>) {
+        assert_one_yocto();
+        let caller_id = env::predecessor_account_id();
+        match self.co_signers.get(&account_id) {
+            Some(current) => require!(
+                caller_id == current.co_signer_id,
+                "Only the current co-signer can change the co-signer"
+            ),
+            None => require!(
+                caller_id == account_id,
+                "Only the account can set its co-signer"
+            ),
+        }
+        match config {
+            Some(config) => {
+                require!(
+                    config.co_signer_id != account_id,
+                    "The account can't be its own co-signer"
+                );
+                require!(
+                    config.window_sec > 0,
+                    "The confirmation window should be positive"
+                );
+                self.co_signers.insert(&account_id, &config);
+            }
+            None => {
+                self.co_signers.remove(&account_id);
+            }
+        }
+    }
+
+    /// Executes a pending transfer. Must be called by its co-signer before it expires. The fee
+    /// escrowed when the transfer was queued is charged, even if the fee config changed since.
+    #[payable]
+    pub fn confirm_transfer(&mut self, transfer_id: PendingTransferId) {
+        assert_one_yocto();
+        self.internal_assert_not_paused(Pausable::Transfers);
+        let transfer = self.internal_take_pending_transfer(transfer_id);
+        require!(
+            env::predecessor_account_id() == transfer.co_signer_id,
+            "Only the co-signer can confirm the transfer"
+        );
+        require!(
+            env::block_timestamp() <= transfer.expires_at.0,
+            "The pending transfer has expired"
+        );
+
+        self.internal_transfer(
+            &env::current_account_id(),
+            &transfer.receiver_id,
+            transfer.amount.0,
+            transfer.memo.as_deref(),
+        );
+        self.internal_settle_escrowed_transfer(
+            &transfer.sender_id,
+            &transfer.receiver_id,
+            transfer.amount.0,
+            transfer.fee.0,
+            &format!("Fee of pending transfer {}", transfer_id),
+        );
+    }
+
+    /// Cancels a pending transfer and returns the escrowed tokens to the sender. The sender and
+    /// the co-signer can cancel it at any time, anyone else once it has expired.
+    pub fn cancel_pending_transfer(&mut self, transfer_id: PendingTransferId) {
+        let transfer = self.internal_take_pending_transfer(transfer_id);
+        let caller_id = env::predecessor_account_id();
+        require!(
+            caller_id == transfer.sender_id
+                || caller_id == transfer.co_signer_id
+                || env::block_timestamp() > transfer.expires_at.0,
+            "Only the sender or the co-signer can cancel the transfer before it expires"
+        );
+        if caller_id != transfer.sender_id && caller_id != transfer.co_signer_id {
+            let bounty = self.keeper_bounties.expiry_cleanup.0;
+            self.internal_reward_keeper(&caller_id, "pending_transfer_expiry", bounty);
+        }
+
+        self.internal_transfer(
+            &env::current_account_id(),
+            &transfer.sender_id,
+            transfer.amount.0 + transfer.fee.0,
+            Some(&format!("Cancellation of pending transfer {}", transfer_id)),
+        );
+    }
+
+    /// Returns the co-signer configuration of an account, if any.
+    pub fn get_co_signer(&self, account_id: AccountId) -> Option<CoSignerConfig> {
+        self.co_signers.get(&account_id)
+    }
+
+    /// Returns a pending transfer, or `null` if it doesn't exist (anymore).
+    pub fn get_pending_transfer(&self, transfer_id: PendingTransferId) -> Option<PendingTransfer> {
+        self.pending_transfers
+            .get(&transfer_id)
+            .map(|mut transfer| {
+                transfer.amount = U128(Self::internal_to_amount(transfer.amount.0));
+                transfer.fee = U128(Self::internal_to_amount(transfer.fee.0));
+                transfer
+            })
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the limits of the cross-contract calls, or removes them when `None` is passed (owner
+    /// only).
+    #[payable]
+    pub fn set_call_caps(&mut self, caps: Option<CallCaps>) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.call_caps = caps;
+    }
+
+    pub fn get_call_caps(&self) -> Option<CallCaps> {
+        self.call_caps.clone()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Mints `amount` new tokens to `receiver_id`, increasing the total supply. Must be called
+    /// by the owner or a minter.
+    #[payable]
+    pub fn ft_mint(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        self.internal_assert_not_paused(Pausable::Mints);
+        self.assert_role(Role::Minter);
+        self.internal_assert_not_wrapped();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let amount = Self::internal_to_shares(amount.0);
+        self.internal_use_mint_limits(&env::predecessor_account_id(), amount);
+        self.internal_mint(&receiver_id, amount, memo.as_deref());
+    }
+
+    /// Burns `amount` of the caller's tokens, decreasing the total supply. Must be called by the
+    /// owner or a minter.
+    #[payable]
+    pub fn ft_burn(&mut self, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        self.internal_assert_not_paused(Pausable::Mints);
+        self.assert_role(Role::Minter);
+        require!(amount.0 > 0, "The amount should be a positive number");
+        self.internal_burn(
+            &env::predecessor_account_id(),
+            Self::internal_to_shares(amount.0),
+            memo.as_deref(),
+        );
+    }
+}
+
+impl Contract {
+    /// Internal method for an `ft_transfer` from the caller, once its deposit is checked.
+    pub(crate) fn internal_ft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        valid_until: Option<U64>,
+        min_received: Option<U128>,
+        fee_mode: Option<FeeMode>,
+    ) {
+        // Reject transactions that lingered past the sender's deadline
+        Self::internal_assert_valid_until(valid_until);
+        // The sender is the user who called the method
+        let sender_id = env::predecessor_account_id();
+        let fee_mode = fee_mode.unwrap_or_default();
+        let amount = Self::internal_to_shares(amount.0);
+        // Let the sponsorship pool register a first-time receiver
+        self.internal_sponsor_receiver(&sender_id, &receiver_id);
+        // Reject the transfer if the fees would leave the receiver with less than expected
+        self.internal_assert_min_received(
+            &sender_id,
+            &receiver_id,
+            amount,
+            fee_mode,
+            min_received.map(|min| Self::internal_to_shares(min.0)),
+        );
+        // Transfers above the sender's co-signing threshold wait for the co-signer's confirmation
+        if let Some(config) = self.internal_co_signer_required(&sender_id, amount) {
+            require!(
+                fee_mode == FeeMode::SenderPays,
+                "Transfers waiting for a co-signer can't be paid by the receiver"
+            );
+            self.internal_assert_valid_memo(memo.as_deref());
+            self.internal_apply_transfer_policy(
+                &sender_id,
+                &receiver_id,
+                amount,
+                memo.as_deref(),
+                None,
+            );
+            self.internal_queue_transfer(sender_id, receiver_id, amount, memo, config);
+            return;
+        }
+        // Transfer the tokens, charging the transfer fee if the fee switch is on
+        self.internal_transfer_with_fee_mode(
+            &sender_id,
+            &receiver_id,
+            amount,
+            memo.as_deref(),
+            fee_mode,
+        );
+    }
+
+    /// Internal method for the transfer of an `ft_transfer_call`, before the receiver is called.
+    /// Returns the sender and the amount credited to the receiver, which is all it's told about.
+    pub(crate) fn internal_start_transfer_call(
+        &mut self,
+        receiver_id: &AccountId,
+        amount: U128,
+        memo: Option<String>,
+        valid_until: Option<U64>,
+        min_received: Option<U128>,
+        fee_mode: Option<FeeMode>,
+    ) -> (AccountId, u128) {
+        // Assert that the user attached exactly 1 yoctoNEAR. This is for security and so that the user will be required to sign with a FAK.
+        assert_one_yocto();
+        // Reject transactions that lingered past the sender's deadline
+        Self::internal_assert_valid_until(valid_until);
+        // Reject the receivers the owner doesn't let the token flow into
+        self.internal_assert_call_receiver(receiver_id);
+        // The sender is the user who called the method
+        let sender_id = env::predecessor_account_id();
+        let fee_mode = fee_mode.unwrap_or_default();
+        let amount = Self::internal_to_shares(amount.0);
+        // Let the sponsorship pool register a first-time receiver
+        self.internal_sponsor_receiver(&sender_id, receiver_id);
+        // Reject the transfer if the fees would leave the receiver with less than expected
+        self.internal_assert_min_received(
+            &sender_id,
+            receiver_id,
+            amount,
+            fee_mode,
+            min_received.map(|min| Self::internal_to_shares(min.0)),
+        );
+        self.internal_promise_budget()
+            .call(GAS_FOR_FT_TRANSFER_CALL, ZERO_TOKEN)
+            .call(GAS_FOR_RESOLVE_TRANSFER, ZERO_TOKEN)
+            .check();
+        // Transfer the tokens, charging the transfer fee if the fee switch is on
+        let amount = self.internal_transfer_with_fee_mode(
+            &sender_id,
+            receiver_id,
+            amount,
+            memo.as_deref(),
+            fee_mode,
+        );
+        (sender_id, amount)
+    }
+
+    /// Internal method for starting the budget of the calls a method is about to make.
+    pub(crate) fn internal_promise_budget(&self) -> PromiseBudget<'_> {
+        PromiseBudget {
+            contract: self,
+            gas: Gas::from_gas(0),
+        }
+    }
+
+    /// Internal method for rejecting a transfer submitted with a deadline that has passed.
+    pub(crate) fn internal_assert_valid_until(valid_until: Option<U64>) {
+        if let Some(valid_until) = valid_until {
+            require!(
+                env::block_timestamp() <= valid_until.0,
+                "The transfer deadline has passed"
+            );
+        }
+    }
+
+    /// Internal method for checking whether a transfer of `amount` tokens from `sender_id` needs
+    /// to be confirmed by a co-signer, returning the sender's configuration if so.
+    pub(crate) fn internal_co_signer_required(
+        &self,
+        sender_id: &AccountId,
+        amount: u128,
+    ) -> Option<CoSignerConfig> {
+        self.co_signers
+            .get(sender_id)
+            .filter(|config| amount > config.threshold.0)
+    }
+
+    /// Internal method for rejecting a transfer of `amount` tokens from `sender_id` that would
+    /// wait for a co-signer, on the paths whose deposit can't pay for the pending record.
+    pub(crate) fn internal_assert_not_co_signed(&self, sender_id: &AccountId, amount: u128) {
+        require!(
+            self.internal_co_signer_required(sender_id, Self::internal_to_shares(amount))
+                .is_none(),
+            "Transfers above the co-signing threshold must be made with ft_transfer"
+        );
+    }
+
+    /// Internal method for authorizing a user transfer that has to be executed right away,
+    /// rejecting the memos the indexers couldn't handle. Called on every user transfer path
+    /// that can't be deferred.
+    pub(crate) fn internal_authorize_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+        travel_rule_ref: Option<&str>,
+    ) {
+        self.internal_assert_valid_memo(memo);
+        self.internal_apply_transfer_policy(sender_id, receiver_id, amount, memo, travel_rule_ref);
+        require!(
+            self.internal_co_signer_required(sender_id, amount)
+                .is_none(),
+            "Transfers above the co-signing threshold must be made with ft_transfer"
+        );
+    }
+
+    /// Internal method for escrowing a transfer and its fee until the co-signer confirms it. The
+    /// storage of the pending transfer is charged to the attached deposit.
+    pub(crate) fn internal_queue_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: u128,
+        memo: Option<String>,
+        config: CoSignerConfig,
+    ) -> PendingTransferId {
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        self.internal_unwrap_balance_of(&receiver_id);
+        let initial_storage_usage = env::storage_usage();
+
+        let transfer_id = self.next_pending_transfer_id;
+        self.next_pending_transfer_id += 1;
+        let fee = self.internal_transfer_fee(&sender_id, &receiver_id, amount, FeeMode::SenderPays);
+        self.internal_transfer(
+            &sender_id,
+            &env::current_account_id(),
+            amount + fee,
+            Some(&format!("Pending transfer {}", transfer_id)),
+        );
+
+        let expires_at =
+            env::block_timestamp() + config.window_sec.saturating_mul(NANOS_PER_SECOND);
+        TransferQueued {
+            transfer_id,
+            sender_id: &sender_id,
+            receiver_id: &receiver_id,
+            amount: &U128(Self::internal_to_amount(amount)),
+            co_signer_id: &config.co_signer_id,
+        }
+        .emit();
+        self.internal_add_pending_references(&[&sender_id, &receiver_id]);
+        self.pending_transfers.insert(
+            &transfer_id,
+            &PendingTransfer {
+                sender_id,
+                receiver_id,
+                amount: U128(amount),
+                memo,
+                co_signer_id: config.co_signer_id,
+                expires_at: U64(expires_at),
+                fee: U128(fee),
+            },
+        );
+        Self::internal_charge_storage(initial_storage_usage);
+        transfer_id
+    }
+
+    /// Internal method for removing a pending transfer, panicking if it doesn't exist. Its
+    /// storage is refunded to the sender.
+    fn internal_take_pending_transfer(
+        &mut self,
+        transfer_id: PendingTransferId,
+    ) -> PendingTransfer {
+        let initial_storage_usage = env::storage_usage();
+        let transfer = self
+            .pending_transfers
+            .remove(&transfer_id)
+            .unwrap_or_else(|| env::panic_str("The pending transfer doesn't exist"));
+        self.internal_remove_pending_references(&[&transfer.sender_id, &transfer.receiver_id]);
+        Self::internal_refund_storage(&transfer.sender_id, initial_storage_usage);
+        transfer
+    }
+}
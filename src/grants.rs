@@ -0,0 +1,375 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::internal::pro_rata;
+use crate::versioned::versioned_record;
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+pub type GrantId = u64;
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum GrantTransferPolicy {
+    /// The grant stays with its first holder.
+    NonTransferable,
+    /// The grant can be transferred once its cliff has passed.
+    AfterCliff,
+    /// The grant can be transferred at any time.
+    Transferable,
+}
+
+// A vesting grant escrowed by the contract account. The grant is a position owned by its
+// holder: the holder claims the vested tokens and, if the transfer policy allows it, can move
+// the grant to another account (a new wallet, an OTC buyer, ...). Grants are also surfaced as
+// NEP-171 tokens whose ID is the grant ID.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingGrant {
+    /// The account that funded the grant, receiving the royalties of its transfers.
+    pub issuer_id: AccountId,
+    pub holder_id: AccountId,
+    pub amount: U128,
+    pub claimed: U128,
+    /// The timestamp (in nanoseconds) when the grant starts vesting.
+    pub start_at: U64,
+    /// The timestamp (in nanoseconds) before which nothing can be claimed.
+    pub cliff_at: U64,
+    /// The timestamp (in nanoseconds) when the grant is fully vested.
+    pub end_at: U64,
+    pub transfer_policy: GrantTransferPolicy,
+}
+
+versioned_record!(VersionedVestingGrant, VestingGrant);
+
+// A grant in the shape of a NEP-171 token.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantToken {
+    pub token_id: String,
+    pub owner_id: AccountId,
+    pub grant: VestingGrant,
+    /// The tokens vested and not claimed yet.
+    pub claimable: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Grants `amount` tokens from the owner's balance to `holder_id`, vesting linearly over
+    /// `duration_sec` seconds from `start_at` (defaults to now) with a `cliff_sec` seconds cliff.
+    #[payable]
+    pub fn create_vesting_grant(
+        &mut self,
+        holder_id: AccountId,
+        amount: U128,
+        start_at: Option<U64>,
+        cliff_sec: u64,
+        duration_sec: u64,
+        transfer_policy: GrantTransferPolicy,
+    ) -> GrantId {
+        assert_one_yocto();
+        self.assert_owner();
+        let amount = Self::internal_to_shares(amount.0);
+        require!(duration_sec > 0, "The vesting duration should be positive");
+        require!(
+            cliff_sec <= duration_sec,
+            "The cliff should be shorter than the vesting duration"
+        );
+        self.internal_unwrap_balance_of(&holder_id);
+
+        self.internal_transfer(
+            &self.owner_id.clone(),
+            &env::current_account_id(),
+            amount,
+            Some(&format!("Vesting grant {}", self.next_grant_id)),
+        );
+        let start_at = start_at.map_or_else(env::block_timestamp, |start_at| start_at.0);
+        self.internal_create_vesting_grant(
+            holder_id,
+            amount,
+            start_at,
+            cliff_sec,
+            duration_sec,
+            transfer_policy,
+        )
+    }
+
+    /// Claims the vested tokens of a grant. Must be called by its holder.
+    pub fn claim_vesting_grant(&mut self, grant_id: GrantId) -> U128 {
+        let mut grant = self.internal_get_grant(grant_id);
+        require!(
+            env::predecessor_account_id() == grant.holder_id,
+            "Only the holder can claim the grant"
+        );
+        let claimable = Self::internal_grant_claimable(&grant);
+        require!(claimable > 0, "Nothing is claimable yet");
+
+        grant.claimed = U128(grant.claimed.0.saturating_add(claimable));
+        self.grants.insert(&grant_id, &grant);
+        self.supply.locked_in_vesting = self.supply.locked_in_vesting.saturating_sub(claimable);
+        self.internal_transfer(
+            &env::current_account_id(),
+            &grant.holder_id,
+            claimable,
+            Some(&format!("Vesting grant {} claim", grant_id)),
+        );
+        U128(Self::internal_to_amount(claimable))
+    }
+
+    /// Moves a grant to another registered account, if its transfer policy allows it. Must be
+    /// called by its holder.
+    #[payable]
+    pub fn transfer_grant(
+        &mut self,
+        grant_id: GrantId,
+        receiver_id: AccountId,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.internal_transfer_grant(grant_id, &receiver_id, memo.as_deref());
+    }
+
+    /// NEP-171 transfer of a grant. Approvals are not supported.
+    #[payable]
+    pub fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: String,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        require!(approval_id.is_none(), "Approvals are not supported");
+        let grant_id = Self::internal_parse_grant_token_id(&token_id);
+        self.internal_transfer_grant(grant_id, &receiver_id, memo.as_deref());
+    }
+
+    /// NEP-171 view of a grant, or `null` if it doesn't exist.
+    pub fn nft_token(&self, token_id: String) -> Option<GrantToken> {
+        let grant_id = token_id.parse::<GrantId>().ok()?;
+        self.grants
+            .get(&grant_id)
+            .map(|grant| Self::internal_grant_token(grant_id, grant))
+    }
+
+    /// NEP-181 style enumeration of the grants held by an account.
+    pub fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<GrantToken> {
+        self.grants_per_holder
+            .get(&account_id)
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index.map_or(0, |from_index| from_index.0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .filter_map(|grant_id| {
+                self.grants
+                    .get(&grant_id)
+                    .map(|grant| Self::internal_grant_token(grant_id, grant))
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Internal method for getting a grant, panicking if it doesn't exist.
+    pub(crate) fn internal_get_grant(&self, grant_id: GrantId) -> VestingGrant {
+        self.grants
+            .get(&grant_id)
+            .unwrap_or_else(|| env::panic_str("The vesting grant doesn't exist"))
+    }
+
+    /// Internal method for granting `amount` shares, already held by the contract account, to
+    /// `holder_id` on behalf of the owner.
+    pub(crate) fn internal_create_vesting_grant(
+        &mut self,
+        holder_id: AccountId,
+        amount: u128,
+        start_at: u64,
+        cliff_sec: u64,
+        duration_sec: u64,
+        transfer_policy: GrantTransferPolicy,
+    ) -> GrantId {
+        let grant_id = self.next_grant_id;
+        self.next_grant_id += 1;
+        self.supply.locked_in_vesting = self.supply.locked_in_vesting.saturating_add(amount);
+
+        let grant = VestingGrant {
+            issuer_id: self.owner_id.clone(),
+            holder_id,
+            amount: U128(amount),
+            claimed: U128(0),
+            start_at: U64(start_at),
+            cliff_at: U64(start_at.saturating_add(cliff_sec.saturating_mul(NANOS_PER_SECOND))),
+            end_at: U64(start_at.saturating_add(duration_sec.saturating_mul(NANOS_PER_SECOND))),
+            transfer_policy,
+        };
+        self.grants.insert(&grant_id, &grant);
+        self.internal_add_grant_to_holder(&grant.holder_id, grant_id);
+        NftMint {
+            owner_id: &grant.holder_id,
+            token_ids: &[&grant_id.to_string()],
+            memo: None,
+        }
+        .emit();
+        grant_id
+    }
+
+    /// Internal method for moving a grant from the predecessor to `receiver_id`.
+    pub(crate) fn internal_transfer_grant(
+        &mut self,
+        grant_id: GrantId,
+        receiver_id: &AccountId,
+        memo: Option<&str>,
+    ) {
+        let mut grant = self.internal_get_grant(grant_id);
+        let sender_id = env::predecessor_account_id();
+        require!(
+            sender_id == grant.holder_id,
+            "Only the holder can transfer the grant"
+        );
+        require!(
+            &sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        if let Some(error) = Self::internal_grant_transfer_error(&grant) {
+            env::panic_str(error);
+        }
+        self.internal_unwrap_balance_of(receiver_id);
+        self.internal_assert_not_frozen(&sender_id);
+        self.internal_assert_not_frozen(receiver_id);
+        let royalty_bps = self.position_royalties.grants_bps;
+        self.internal_pay_position_royalty(
+            "grant",
+            grant_id,
+            &sender_id,
+            &grant.issuer_id.clone(),
+            grant.amount.0.saturating_sub(grant.claimed.0),
+            royalty_bps,
+        );
+
+        let mut sender_grants = self.grants_per_holder.get(&sender_id).unwrap_or_default();
+        sender_grants.retain(|id| *id != grant_id);
+        if sender_grants.is_empty() {
+            self.grants_per_holder.remove(&sender_id);
+        } else {
+            self.grants_per_holder.insert(&sender_id, &sender_grants);
+        }
+        self.internal_add_grant_to_holder(receiver_id, grant_id);
+        grant.holder_id = receiver_id.clone();
+        self.grants.insert(&grant_id, &grant);
+
+        NftTransfer {
+            old_owner_id: &sender_id,
+            new_owner_id: receiver_id,
+            token_ids: &[&grant_id.to_string()],
+            memo,
+        }
+        .emit();
+    }
+
+    /// Internal method for moving the grants of a merged account to the account it's merged
+    /// into. The grants their transfer policy keeps with their holder stay with the old account,
+    /// which can still claim them. Returns the IDs of the grants moved.
+    pub(crate) fn internal_merge_grants(
+        &mut self,
+        old_account_id: &AccountId,
+        new_account_id: &AccountId,
+    ) -> Vec<GrantId> {
+        let Some(old_grants) = self.grants_per_holder.get(old_account_id) else {
+            return vec![];
+        };
+        let (moved, kept): (Vec<GrantId>, Vec<GrantId>) =
+            old_grants.into_iter().partition(|grant_id| {
+                Self::internal_grant_transfer_error(&self.internal_get_grant(*grant_id)).is_none()
+            });
+        if moved.is_empty() {
+            return moved;
+        }
+        for grant_id in moved.iter() {
+            let mut grant = self.internal_get_grant(*grant_id);
+            grant.holder_id = new_account_id.clone();
+            self.grants.insert(grant_id, &grant);
+            self.internal_add_grant_to_holder(new_account_id, *grant_id);
+        }
+        if kept.is_empty() {
+            self.grants_per_holder.remove(old_account_id);
+        } else {
+            self.grants_per_holder.insert(old_account_id, &kept);
+        }
+
+        let token_ids: Vec<String> = moved.iter().map(|grant_id| grant_id.to_string()).collect();
+        NftTransfer {
+            old_owner_id: old_account_id,
+            new_owner_id: new_account_id,
+            token_ids: &token_ids.iter().map(String::as_str).collect::<Vec<_>>(),
+            memo: Some("Account merge"),
+        }
+        .emit();
+        moved
+    }
+
+    /// Internal method for telling why the transfer policy of a grant doesn't allow moving it
+    /// right now, if it doesn't.
+    fn internal_grant_transfer_error(grant: &VestingGrant) -> Option<&'static str> {
+        match grant.transfer_policy {
+            GrantTransferPolicy::NonTransferable => Some("The vesting grant is not transferable"),
+            GrantTransferPolicy::AfterCliff if env::block_timestamp() < grant.cliff_at.0 => {
+                Some("The vesting grant can't be transferred before its cliff")
+            }
+            GrantTransferPolicy::AfterCliff | GrantTransferPolicy::Transferable => None,
+        }
+    }
+
+    /// Internal method for computing the tokens of a grant vested and not claimed yet.
+    pub(crate) fn internal_grant_claimable(grant: &VestingGrant) -> u128 {
+        let now = env::block_timestamp();
+        let vested = if now < grant.cliff_at.0 {
+            0
+        } else if now >= grant.end_at.0 {
+            grant.amount.0
+        } else {
+            pro_rata(
+                grant.amount.0,
+                (now - grant.start_at.0) as u128,
+                (grant.end_at.0 - grant.start_at.0) as u128,
+            )
+        };
+        vested.saturating_sub(grant.claimed.0)
+    }
+
+    fn internal_add_grant_to_holder(&mut self, holder_id: &AccountId, grant_id: GrantId) {
+        let mut grants = self.grants_per_holder.get(holder_id).unwrap_or_default();
+        grants.push(grant_id);
+        self.grants_per_holder.insert(holder_id, &grants);
+    }
+
+    fn internal_parse_grant_token_id(token_id: &str) -> GrantId {
+        token_id
+            .parse()
+            .unwrap_or_else(|_| env::panic_str("The token ID should be a grant ID"))
+    }
+
+    pub(crate) fn internal_grant_token(grant_id: GrantId, mut grant: VestingGrant) -> GrantToken {
+        let claimable = Self::internal_grant_claimable(&grant);
+        grant.amount = U128(Self::internal_to_amount(grant.amount.0));
+        grant.claimed = U128(Self::internal_to_amount(grant.claimed.0));
+        GrantToken {
+            token_id: grant_id.to_string(),
+            owner_id: grant.holder_id.clone(),
+            claimable: U128(Self::internal_to_amount(claimable)),
+            grant,
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, near_bindgen, NearSchema};
+
+use crate::*;
+
+// The groups of methods the owner can pause, in an emergency for example.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseFlags {
+    /// Whether the user transfers are paused.
+    pub transfers: bool,
+    /// Whether `ft_mint` and `ft_burn` are paused.
+    pub mints: bool,
+    /// Whether the storage registrations and unregistrations are paused.
+    pub storage: bool,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Pausable {
+    Transfers,
+    Mints,
+    Storage,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Pauses every pausable method.
+    #[payable]
+    pub fn pause(&mut self) {
+        self.set_pause_flags(PauseFlags {
+            transfers: true,
+            mints: true,
+            storage: true,
+        });
+    }
+
+    /// Unpauses every pausable method.
+    #[payable]
+    pub fn unpause(&mut self) {
+        self.set_pause_flags(PauseFlags::default());
+    }
+
+    /// Pauses and unpauses the groups of methods individually.
+    #[payable]
+    pub fn set_pause_flags(&mut self, flags: PauseFlags) {
+        assert_one_yocto();
+        self.assert_role(Role::Pauser);
+        if flags == self.pause_flags {
+            return;
+        }
+        PauseChanged { flags: &flags }.emit();
+        self.pause_flags = flags;
+    }
+
+    /// Returns which groups of methods are paused.
+    pub fn get_pause_flags(&self) -> PauseFlags {
+        self.pause_flags.clone()
+    }
+}
+
+impl Contract {
+    /// Internal method for asserting that a group of methods isn't paused.
+    pub(crate) fn internal_assert_not_paused(&self, group: Pausable) {
+        let (paused, code) = match group {
+            Pausable::Transfers => (self.pause_flags.transfers, ErrorCode::TransfersPaused),
+            Pausable::Mints => (self.pause_flags.mints, ErrorCode::MintsPaused),
+            Pausable::Storage => (self.pause_flags.storage, ErrorCode::StoragePaused),
+        };
+        if paused {
+            self.internal_panic(code);
+        }
+    }
+}
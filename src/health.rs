@@ -0,0 +1,124 @@
+//! Health check for the monitoring.
+//!
+//! `health_check` gathers in a single view call what the operators get paged for: the paused
+//! methods, the broken invariants, the stale prices, the ongoing migration to a successor and
+//! the quarantine limiting the transfers. The status is `paused` while the transfers are paused,
+//! `degraded` while any other issue is reported, and `ok` otherwise.
+
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, NearSchema};
+
+use crate::*;
+
+#[derive(Serialize, Deserialize, NearSchema, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    /// Some methods are paused, limited or unsafe, but the tokens can still move.
+    Degraded,
+    /// The transfers are paused.
+    Paused,
+}
+
+#[derive(Serialize, Deserialize, NearSchema, Clone, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum HealthIssue {
+    /// The owner paused some groups of methods.
+    Paused { flags: PauseFlags },
+    /// The total supply exceeds the supply cap.
+    SupplyAboveCap {
+        total_supply: U128,
+        max_supply: U128,
+    },
+    /// The tokens held in the streams exceed the total supply.
+    StreamedAboveSupply {
+        streamed_supply: U128,
+        total_supply: U128,
+    },
+    /// The contract account can't cover its storage along with the NEAR it holds for others.
+    NearShortfall { shortfall: NearToken },
+    /// The balances don't add up to the running sum of the audit mode.
+    RegisteredSupplyDrift,
+    /// The collateralized mode has no fresh price of NEAR.
+    StaleCollateralPrice,
+    /// The holders are migrating to a successor contract.
+    Migrating { successor_id: AccountId },
+    /// The token was renamed, so the large transfers are limited until `ends_at`.
+    RenameQuarantine { ends_at: U64 },
+}
+
+#[derive(Serialize, Deserialize, NearSchema, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub issues: Vec<HealthIssue>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the status of the contract along with the issues it's derived from. Built with
+    /// the `audit` feature, the check recomputes the sum of the balances like
+    /// `ft_total_registered_supply` does.
+    pub fn health_check(&self) -> HealthReport {
+        let mut issues = vec![];
+
+        let flags = self.pause_flags.clone();
+        if flags != PauseFlags::default() {
+            issues.push(HealthIssue::Paused { flags });
+        }
+
+        if let Some(max_supply) = self.max_supply {
+            let total_supply = Self::internal_to_amount(self.total_supply);
+            if total_supply > max_supply {
+                issues.push(HealthIssue::SupplyAboveCap {
+                    total_supply: U128(total_supply),
+                    max_supply: U128(max_supply),
+                });
+            }
+        }
+        if self.streamed_supply > self.total_supply {
+            issues.push(HealthIssue::StreamedAboveSupply {
+                streamed_supply: U128(self.streamed_supply),
+                total_supply: U128(self.total_supply),
+            });
+        }
+        let required = env::storage_byte_cost()
+            .saturating_mul(env::storage_usage().into())
+            .saturating_add(self.internal_owed_near());
+        if env::account_balance() < required {
+            issues.push(HealthIssue::NearShortfall {
+                shortfall: required.saturating_sub(env::account_balance()),
+            });
+        }
+        #[cfg(feature = "audit")]
+        if !self.ft_total_registered_supply().consistent {
+            issues.push(HealthIssue::RegisteredSupplyDrift);
+        }
+
+        #[cfg(feature = "stablecoin")]
+        if self.internal_collateral_price_is_stale() {
+            issues.push(HealthIssue::StaleCollateralPrice);
+        }
+
+        if let Some(successor_id) = self.successor_id.clone() {
+            issues.push(HealthIssue::Migrating { successor_id });
+        }
+        if let Some(quarantine) = self.internal_active_rename_quarantine(env::block_timestamp()) {
+            issues.push(HealthIssue::RenameQuarantine {
+                ends_at: quarantine.ends_at,
+            });
+        }
+
+        let status = if self.pause_flags.transfers {
+            HealthStatus::Paused
+        } else if issues.is_empty() {
+            HealthStatus::Ok
+        } else {
+            HealthStatus::Degraded
+        };
+        HealthReport { status, issues }
+    }
+}
@@ -0,0 +1,207 @@
+//! Insurance fund.
+//!
+//! The `insurance_bps` share of the transfer fees is kept by the contract account in the
+//! insurance fund, along with the tokens slashed from frozen accounts, as a backstop for the
+//! users hurt by an incident. An adjudicator approves a payout to an affected account, up to the
+//! cap per claim, which reserves it in the fund. Anyone can execute the payout once its timelock
+//! elapsed, leaving the owner, or the holders with a referendum, time to cancel a payout that
+//! shouldn't be made.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+/// The shortest timelock of the payouts the owner can set, in seconds (1 day).
+pub const MIN_INSURANCE_TIMELOCK_SEC: u64 = 24 * 60 * 60;
+
+pub type InsuranceClaimId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct InsuranceConfig {
+    /// The most a single claim can pay out.
+    pub max_payout_per_claim: U128,
+    /// How long an approved payout waits before it can be executed, in seconds.
+    pub timelock_sec: u64,
+}
+
+// A payout approved by an adjudicator, reserved in the fund until it's executed or cancelled.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct InsuranceClaim {
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    /// What happened, e.g. a link to the incident report.
+    pub incident: String,
+    pub approved_by: AccountId,
+    /// The timestamp (in nanoseconds) from which the payout can be executed.
+    pub executable_at: U64,
+}
+
+// The state of the insurance fund.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InsuranceFundView {
+    pub config: Option<InsuranceConfig>,
+    /// The tokens available for new payouts.
+    pub balance: U128,
+    /// The tokens of the approved payouts not executed yet.
+    pub reserved: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the cap per claim and the timelock of the payouts, or stops new payouts when `None`
+    /// is passed (owner only). The payouts already approved keep their timelock.
+    #[payable]
+    pub fn set_insurance_config(&mut self, config: Option<InsuranceConfig>) {
+        assert_one_yocto();
+        self.assert_owner();
+        if let Some(config) = &config {
+            require!(
+                config.max_payout_per_claim.0 > 0,
+                "The cap per claim should be positive"
+            );
+            require!(
+                config.timelock_sec >= MIN_INSURANCE_TIMELOCK_SEC,
+                "The payout timelock is too short"
+            );
+        }
+        self.insurance_config = config;
+    }
+
+    /// Approves a payout of `amount` to `receiver_id` for an incident, executable once the
+    /// timelock elapsed (owner and adjudicators only).
+    #[payable]
+    pub fn approve_insurance_payout(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        incident: String,
+    ) -> InsuranceClaimId {
+        assert_one_yocto();
+        self.assert_role(Role::Adjudicator);
+        let config = self
+            .insurance_config
+            .clone()
+            .unwrap_or_else(|| env::panic_str("The insurance payouts are off"));
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let amount = U128(Self::internal_to_shares(amount.0));
+        require!(
+            amount.0 <= config.max_payout_per_claim.0,
+            "The payout exceeds the cap per claim"
+        );
+        require!(
+            amount.0 <= self.insurance_fund,
+            "The insurance fund is too low"
+        );
+        self.internal_unwrap_balance_of(&receiver_id);
+        self.insurance_fund -= amount.0;
+        self.insurance_reserved = self
+            .insurance_reserved
+            .checked_add(amount.0)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+
+        let claim_id = self.next_insurance_claim_id;
+        self.next_insurance_claim_id += 1;
+        self.insurance_claims.insert(
+            &claim_id,
+            &InsuranceClaim {
+                receiver_id,
+                amount,
+                incident,
+                approved_by: env::predecessor_account_id(),
+                executable_at: U64(env::block_timestamp() + config.timelock_sec * NANOS_PER_SECOND),
+            },
+        );
+        claim_id
+    }
+
+    /// Pays out an approved claim whose timelock elapsed. Anyone can call it.
+    pub fn execute_insurance_payout(&mut self, claim_id: InsuranceClaimId) {
+        let claim = self.internal_get_insurance_claim(claim_id);
+        require!(
+            env::block_timestamp() >= claim.executable_at.0,
+            "The payout is still timelocked"
+        );
+        self.insurance_claims.remove(&claim_id);
+        self.insurance_reserved -= claim.amount.0;
+        self.internal_transfer(
+            &env::current_account_id(),
+            &claim.receiver_id,
+            claim.amount.0,
+            Some("Insurance payout"),
+        );
+    }
+
+    /// Cancels an approved payout, returning it to the fund (owner only).
+    #[payable]
+    pub fn cancel_insurance_payout(&mut self, claim_id: InsuranceClaimId) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.internal_cancel_insurance_payout(claim_id);
+    }
+
+    /// Moves `amount` of a frozen account's tokens into the insurance fund (owner and freeze
+    /// admins only).
+    #[payable]
+    pub fn slash_to_insurance(&mut self, account_id: AccountId, amount: U128, reason: String) {
+        assert_one_yocto();
+        self.assert_role(Role::FreezeAdmin);
+        require!(
+            self.internal_active_freeze(&account_id).is_some(),
+            "Only frozen accounts can be slashed"
+        );
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let amount = Self::internal_to_shares(amount.0);
+        self.internal_contract_transfer(
+            &account_id,
+            &env::current_account_id(),
+            amount,
+            Some(&reason),
+        );
+        self.insurance_fund = self
+            .insurance_fund
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+    }
+
+    /// Returns the configuration, the balance and the reserved tokens of the insurance fund.
+    pub fn get_insurance_fund(&self) -> InsuranceFundView {
+        InsuranceFundView {
+            config: self.insurance_config.clone(),
+            balance: U128(Self::internal_to_amount(self.insurance_fund)),
+            reserved: U128(Self::internal_to_amount(self.insurance_reserved)),
+        }
+    }
+
+    /// Returns an approved payout, or `null` if it doesn't exist (anymore).
+    pub fn get_insurance_claim(&self, claim_id: InsuranceClaimId) -> Option<InsuranceClaim> {
+        self.insurance_claims.get(&claim_id).map(|mut claim| {
+            claim.amount = U128(Self::internal_to_amount(claim.amount.0));
+            claim
+        })
+    }
+}
+
+impl Contract {
+    /// Internal method for cancelling an approved payout, returning it to the fund.
+    pub(crate) fn internal_cancel_insurance_payout(&mut self, claim_id: InsuranceClaimId) {
+        let claim = self.internal_get_insurance_claim(claim_id);
+        self.insurance_claims.remove(&claim_id);
+        self.insurance_reserved -= claim.amount.0;
+        self.insurance_fund += claim.amount.0;
+    }
+
+    fn internal_get_insurance_claim(&self, claim_id: InsuranceClaimId) -> InsuranceClaim {
+        self.insurance_claims
+            .get(&claim_id)
+            .unwrap_or_else(|| env::panic_str("The claim doesn't exist"))
+    }
+}
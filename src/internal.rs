@@ -1,42 +1,308 @@
 use near_sdk::{env, require};
-use std::str::FromStr;
 
 use crate::*;
 
+/// Computes `amount * part / whole` without overflowing the intermediate product, for ratios
+/// where `part <= whole` (elapsed over total duration, basis points over 10 000, ...).
+pub(crate) fn pro_rata(amount: u128, part: u128, whole: u128) -> u128 {
+    amount / whole * part + amount % whole * part / whole
+}
+
+// Outside of the rebasing mode (see `src/rebase.rs`), the balances are stored as token amounts.
+#[cfg(not(feature = "rebase"))]
 impl Contract {
+    pub(crate) fn internal_to_amount(shares: u128) -> u128 {
+        shares
+    }
+
+    pub(crate) fn internal_to_shares(amount: u128) -> u128 {
+        amount
+    }
+
+    pub(crate) fn internal_record_snapshot_index(_snapshot_id: SnapshotId) {}
+
+    pub(crate) fn internal_to_amount_at(shares: u128, _snapshot_id: SnapshotId) -> u128 {
+        shares
+    }
+}
+
+impl Contract {
+    /// Internal method for creating the state of a contract owned by `owner_id`, without any
+    /// token, metadata or record. Nothing is written to the storage.
+    pub(crate) fn internal_new_state(owner_id: AccountId) -> Self {
+        Self {
+            owner_id,
+            total_supply: 0,
+            // Storage keys are simply the prefixes used for the collections. This helps avoid data collision
+            accounts: LookupMap::new(StorageKey::Core(CoreKey::Accounts)),
+            metadata: LazyOption::new(StorageKey::Core(CoreKey::Metadata), None),
+            mining_programs: UnorderedMap::new(StorageKey::Mining(MiningKey::MiningPrograms)),
+            next_mining_program_id: 0,
+            bond_markets: VersionedLookupMap::new(StorageKey::Bonds(BondsKey::BondMarkets)),
+            next_bond_market_id: 0,
+            bond_positions: LookupMap::new(StorageKey::Bonds(BondsKey::BondPositions)),
+            fee_config: None,
+            fee_accumulators: FeeAccumulators::default(),
+            fee_category_rates: UnorderedMap::new(StorageKey::Fees(FeesKey::FeeCategoryRates)),
+            account_fee_categories: LookupMap::new(StorageKey::Fees(FeesKey::AccountFeeCategories)),
+            relay_keys: LookupMap::new(StorageKey::Signatures(SignaturesKey::RelayKeys)),
+            relay_nonces: LookupMap::new(StorageKey::Signatures(SignaturesKey::RelayNonces)),
+            storage_sponsors: LookupMap::new(StorageKey::Storage(
+                StorageManagementKey::StorageSponsors,
+            )),
+            metrics: Metrics::default(),
+            balance_leaf_indexes: LookupMap::new(StorageKey::Merkle(MerkleKey::BalanceLeafIndexes)),
+            next_balance_leaf_index: 0,
+            balance_tree: LookupMap::new(StorageKey::Merkle(MerkleKey::BalanceTree)),
+            memo_keys: LookupMap::new(StorageKey::EncryptedMemo(EncryptedMemoKey::MemoKeys)),
+            payment_pubkeys: LookupMap::new(StorageKey::PaymentKeys(
+                PaymentKeysKey::PaymentPubkeys,
+            )),
+            payment_intents: VersionedLookupMap::new(StorageKey::PaymentIntents(
+                PaymentIntentsKey::PaymentIntents,
+            )),
+            next_payment_intent_id: 0,
+            co_signers: LookupMap::new(StorageKey::CoSigning(CoSigningKey::CoSigners)),
+            pending_transfers: VersionedLookupMap::new(StorageKey::CoSigning(
+                CoSigningKey::PendingTransfers,
+            )),
+            next_pending_transfer_id: 0,
+            wallet_policies: LookupMap::new(StorageKey::WalletPolicy(
+                WalletPolicyKey::WalletPolicies,
+            )),
+            wallet_policy_changes: LookupMap::new(StorageKey::WalletPolicy(
+                WalletPolicyKey::WalletPolicyChanges,
+            )),
+            budgets: LookupMap::new(StorageKey::Budgets(BudgetsKey::Budgets)),
+            allowances: LookupMap::new(StorageKey::Approvals(ApprovalsKey::Allowances)),
+            hygiene_pool: HygienePool::default(),
+            supply: SupplyCounters::default(),
+            supply_categories: LookupMap::new(StorageKey::Supply(SupplyKey::SupplyCategories)),
+            supply_history: Vector::new(StorageKey::Supply(SupplyKey::SupplyHistory)),
+            burned_by_account: LookupMap::new(StorageKey::Burns(BurnsKey::BurnedByAccount)),
+            burn_leaderboard: LazyOption::new(StorageKey::Burns(BurnsKey::BurnLeaderboard), None),
+            burn_receipts: VersionedLookupMap::new(StorageKey::Burns(BurnsKey::BurnReceipts)),
+            next_burn_receipt_id: 0,
+            mint_proposers: UnorderedSet::new(StorageKey::MintRequests(
+                MintRequestsKey::MintProposers,
+            )),
+            mint_approvers: UnorderedSet::new(StorageKey::MintRequests(
+                MintRequestsKey::MintApprovers,
+            )),
+            mint_approval_threshold: 1,
+            mint_requests: Vector::new(StorageKey::MintRequests(MintRequestsKey::MintRequests)),
+            frozen_accounts: VersionedLookupMap::new(StorageKey::Freezing(
+                FreezingKey::FrozenAccounts,
+            )),
+            jurisdiction_attestors: UnorderedSet::new(StorageKey::Jurisdictions(
+                JurisdictionsKey::JurisdictionAttestors,
+            )),
+            account_jurisdictions: LookupMap::new(StorageKey::Jurisdictions(
+                JurisdictionsKey::AccountJurisdictions,
+            )),
+            jurisdiction_rules: UnorderedMap::new(StorageKey::Jurisdictions(
+                JurisdictionsKey::JurisdictionRules,
+            )),
+            vasps: LookupMap::new(StorageKey::TravelRule(TravelRuleKey::Vasps)),
+            travel_rule_threshold: None,
+            attestation_config: None,
+            attestation_epoch: 0,
+            attestation_cache: LookupMap::new(StorageKey::Attestations(
+                AttestationsKey::AttestationCache,
+            )),
+            rebate_program: None,
+            rebate_pool: 0,
+            rebate_accounts: VersionedLookupMap::new(StorageKey::Rebates(
+                RebatesKey::RebateAccounts,
+            )),
+            relayer_usage: UnorderedMap::new(StorageKey::RelayerGas(RelayerGasKey::RelayerUsage)),
+            relayer_gas_pool: ZERO_TOKEN,
+            relayer_gas_price: DEFAULT_RELAYER_GAS_PRICE,
+            locks: VersionedLookupMap::new(StorageKey::Locks(LocksKey::Locks)),
+            next_lock_id: 0,
+            receipt_balances: LookupMap::new(StorageKey::Locks(LocksKey::ReceiptBalances)),
+            grants: VersionedLookupMap::new(StorageKey::Grants(GrantsKey::Grants)),
+            next_grant_id: 0,
+            grants_per_holder: LookupMap::new(StorageKey::Grants(GrantsKey::GrantsPerHolder)),
+            position_royalties: PositionRoyalties::default(),
+            multicall_targets: UnorderedSet::new(StorageKey::Multicall(
+                MulticallKey::MulticallTargets,
+            )),
+            treasury_guard: None,
+            treasury_spends: UnorderedMap::new(StorageKey::Treasury(TreasuryKey::TreasurySpends)),
+            next_treasury_spend_id: 0,
+            jobs: LookupMap::new(StorageKey::Jobs(JobsKey::Jobs)),
+            next_job_id: 0,
+            job_items: LookupMap::new(StorageKey::Jobs(JobsKey::JobItems)),
+            keeper_bounties: KeeperBounties::default(),
+            keeper_pool: 0,
+            force_unregister_requests: LookupMap::new(StorageKey::Storage(
+                StorageManagementKey::ForceUnregisterRequests,
+            )),
+            force_unregister_threshold: 0,
+            factory_code: LazyOption::new(StorageKey::Factory(FactoryKey::FactoryCode), None),
+            factory_tokens: Vector::new(StorageKey::Factory(FactoryKey::FactoryTokens)),
+            rename_quarantine: LazyOption::new(
+                StorageKey::Quarantine(QuarantineKey::RenameQuarantine),
+                None,
+            ),
+            quarantine_large_transfers: LookupMap::new(StorageKey::Quarantine(
+                QuarantineKey::QuarantineLargeTransfers,
+            )),
+            dust_account_id: None,
+            pause_flags: PauseFlags::default(),
+            account_labels: LookupMap::new(StorageKey::Labels(LabelsKey::AccountLabels)),
+            account_locales: LookupMap::new(StorageKey::Locales(LocalesKey::AccountLocales)),
+            airdrops: VersionedLookupMap::new(StorageKey::Airdrop(AirdropKey::Airdrops)),
+            next_airdrop_id: 0,
+            airdrop_claims: LookupMap::new(StorageKey::Airdrop(AirdropKey::AirdropClaims)),
+            state_version: STATE_VERSION,
+            account_preferences: VersionedLookupMap::new(StorageKey::Preferences(
+                PreferencesKey::AccountPreferences,
+            )),
+            pending_owner_id: None,
+            account_roles: LookupMap::new(StorageKey::Access(AccessKey::AccountRoles)),
+            randomness_rounds: VersionedLookupMap::new(StorageKey::Rand(RandKey::RandomnessRounds)),
+            next_randomness_round_id: 0,
+            randomness_commitments: LookupMap::new(StorageKey::Rand(
+                RandKey::RandomnessCommitments,
+            )),
+            wrapped: false,
+            recovery_config: None,
+            owner_recovery: None,
+            owner_active_at: env::block_timestamp(),
+            streams: VersionedLookupMap::new(StorageKey::Streams(StreamsKey::Streams)),
+            next_stream_id: 0,
+            streamed_supply: 0,
+            snapshots: Vector::new(StorageKey::Snapshot(SnapshotKey::Snapshots)),
+            balance_checkpoints: LookupMap::new(StorageKey::Snapshot(
+                SnapshotKey::BalanceCheckpoints,
+            )),
+            call_caps: None,
+            borsh_msg_receivers: LookupSet::new(StorageKey::BorshMsg(
+                BorshMsgKey::BorshMsgReceivers,
+            )),
+            max_supply: None,
+            mint_limits: None,
+            daily_mints: LookupMap::new(StorageKey::MintLimits(MintLimitsKey::DailyMints)),
+            netting_windows: LookupMap::new(StorageKey::Netting(NettingKey::NettingWindows)),
+            netted_batches: LookupMap::new(StorageKey::Netting(NettingKey::NettedBatches)),
+            escrows: VersionedLookupMap::new(StorageKey::Escrow(EscrowKey::Escrows)),
+            next_escrow_id: 0,
+            keeper_tips: LookupMap::new(StorageKey::Keepers(KeepersKey::KeeperTips)),
+            successor_id: None,
+            balance_vouchers: LookupMap::new(StorageKey::Vouchers(VouchersKey::BalanceVouchers)),
+            next_voucher_nonce: 0,
+            template: None,
+            council: None,
+            council_proposals: LookupMap::new(StorageKey::Council(CouncilKey::Proposals)),
+            next_council_proposal_id: 0,
+            tokenomics: LazyOption::new(StorageKey::Core(CoreKey::Tokenomics), None),
+            psm_config: None,
+            psm_reserves: 0,
+            psm_debt: 0,
+            sponsorship_pool: ZERO_TOKEN,
+            sponsorship_cap: DEFAULT_SPONSORSHIP_CAP,
+            sponsored_registrations: LookupMap::new(StorageKey::Sponsorship(
+                SponsorshipKey::Registrations,
+            )),
+            sponsored_accounts: LookupSet::new(StorageKey::Sponsorship(SponsorshipKey::Accounts)),
+            insurance_config: None,
+            insurance_fund: 0,
+            insurance_reserved: 0,
+            insurance_claims: LookupMap::new(StorageKey::Insurance(InsuranceKey::Claims)),
+            next_insurance_claim_id: 0,
+            bounty_pool: 0,
+            disclosures: LookupMap::new(StorageKey::Bounty(BountyKey::Disclosures)),
+            next_disclosure_id: 0,
+            referendum_config: None,
+            referenda: LookupMap::new(StorageKey::Referendum(ReferendumKey::Referenda)),
+            referendum_stakes: LookupMap::new(StorageKey::Referendum(ReferendumKey::Stakes)),
+            open_referenda: LookupMap::new(StorageKey::Referendum(ReferendumKey::OpenReferenda)),
+            next_referendum_id: 0,
+            receiver_list_mode: ReceiverListMode::Open,
+            listed_receivers: LookupSet::new(StorageKey::ReceiverList(ReceiverListKey::Receivers)),
+            reservations: VersionedLookupMap::new(StorageKey::Reservations(
+                ReservationsKey::Reservations,
+            )),
+            next_reservation_id: 0,
+            storage_sponsorship_total: ZERO_TOKEN,
+            next_fee_quote_id: 0,
+            fee_quotes: LookupMap::new(StorageKey::Quotes(QuotesKey::Quotes)),
+            storage_depositors: LookupMap::new(StorageKey::Storage(
+                StorageManagementKey::Depositors,
+            )),
+            storage_sweep_stats: StorageSweepStats::default(),
+            airdrop_grants: LookupMap::new(StorageKey::Airdrop(AirdropKey::AirdropGrants)),
+            memo_config: MemoConfig::default(),
+            price_feeds: LookupMap::new(StorageKey::Oracle(OracleKey::PriceFeeds)),
+            scheduled_transfers: VersionedLookupMap::new(StorageKey::Scheduled(
+                ScheduledKey::ScheduledTransfers,
+            )),
+            next_scheduled_transfer_id: 0,
+            pending_references: LookupMap::new(StorageKey::Storage(
+                StorageManagementKey::PendingReferences,
+            )),
+        }
+    }
+
+    /// Internal method for asserting that the predecessor is the owner of the contract.
+    pub(crate) fn assert_owner(&self) {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the owner can call this method"
+        );
+    }
+
     /// Internal method for force getting the balance of an account. If the account doesn't have a balance, panic with a custom message.
-    pub(crate) fn internal_unwrap_balance_of(&self, account_id: &AccountId) -> NearToken {
+    pub(crate) fn internal_unwrap_balance_of(&self, account_id: &AccountId) -> u128 {
         match self.accounts.get(account_id) {
             Some(balance) => balance,
             None => {
-                env::panic_str(format!("The account {} is not registered", &account_id).as_str())
+                env::panic_str(format!("The account {} is not registered", account_id).as_str())
             }
         }
     }
 
+    /// Internal method for getting the balance of an account, failing in the caller's language
+    /// if it isn't registered. Only for change methods, see `internal_panic`.
+    pub(crate) fn internal_registered_balance_of(&self, account_id: &AccountId) -> u128 {
+        self.accounts
+            .get(account_id)
+            .unwrap_or_else(|| self.internal_panic(ErrorCode::NotRegistered(account_id)))
+    }
+
     /// Internal method for depositing some amount of FTs into an account.
-    pub(crate) fn internal_deposit(&mut self, account_id: &AccountId, amount: NearToken) {
+    pub(crate) fn internal_deposit(&mut self, account_id: &AccountId, amount: u128) {
         // Get the current balance of the account. If they're not registered, panic.
-        let balance = self.internal_unwrap_balance_of(account_id);
+        let balance = self.internal_registered_balance_of(account_id);
 
         // Add the amount to the balance and insert the new balance into the accounts map
         if let Some(new_balance) = balance.checked_add(amount) {
+            self.internal_checkpoint_balance(account_id, balance);
             self.accounts.insert(account_id, &new_balance);
+            self.internal_track_supply_category(account_id, amount, true);
+            #[cfg(feature = "audit")]
+            self.internal_audit_deposit(account_id, amount);
         } else {
             env::panic_str("Balance overflow");
         }
     }
 
     /// Internal method for withdrawing some amount of FTs from an account.
-    pub(crate) fn internal_withdraw(&mut self, account_id: &AccountId, amount: NearToken) {
+    pub(crate) fn internal_withdraw(&mut self, account_id: &AccountId, amount: u128) {
         // Get the current balance of the account. If they're not registered, panic.
-        let balance = self.internal_unwrap_balance_of(account_id);
+        let balance = self.internal_registered_balance_of(account_id);
 
         // Decrease the amount from the balance and insert the new balance into the accounts map
         if let Some(new_balance) = balance.checked_sub(amount) {
+            self.internal_checkpoint_balance(account_id, balance);
             self.accounts.insert(account_id, &new_balance);
+            self.internal_track_supply_category(account_id, amount, false);
+            #[cfg(feature = "audit")]
+            self.internal_audit_withdraw(account_id, amount);
         } else {
-            env::panic_str("The account doesn't have enough balance");
+            self.internal_panic(ErrorCode::NotEnoughBalance);
         }
     }
 
@@ -45,8 +311,62 @@ impl Contract {
         &mut self,
         sender_id: &AccountId,
         receiver_id: &AccountId,
-        amount: NearToken,
-        memo: Option<String>,
+        amount: u128,
+        memo: Option<&str>,
+    ) {
+        self.internal_move_balance(sender_id, receiver_id, amount);
+        Self::internal_emit_transfer(sender_id, receiver_id, amount, memo);
+    }
+
+    /// Internal method for a transfer the contract makes on its own, such as moving a fee share,
+    /// refunding a transfer call once it's resolved or slashing a frozen account. Frozen
+    /// accounts don't block it.
+    pub(crate) fn internal_contract_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+    ) {
+        self.internal_move_unfrozen_balance(sender_id, receiver_id, amount);
+        Self::internal_emit_transfer(sender_id, receiver_id, amount, memo);
+    }
+
+    fn internal_emit_transfer(
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+    ) {
+        // Emit a Transfer event
+        FtTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            amount: &U128(Self::internal_to_amount(amount)),
+            memo,
+        }
+        .emit();
+    }
+
+    /// Internal method for moving FTs from one account to another without logging the transfer,
+    /// for callers logging their transfers together. Frozen accounts can neither send nor
+    /// receive tokens.
+    pub(crate) fn internal_move_balance(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+    ) {
+        self.internal_assert_not_frozen(sender_id);
+        self.internal_assert_not_frozen(receiver_id);
+        self.internal_move_unfrozen_balance(sender_id, receiver_id, amount);
+    }
+
+    fn internal_move_unfrozen_balance(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
     ) {
         // Ensure the sender can't transfer to themselves
         require!(
@@ -54,39 +374,80 @@ impl Contract {
             "Sender and receiver should be different"
         );
         // Ensure the sender can't transfer 0 tokens
-        require!(
-            amount.gt(&ZERO_TOKEN),
-            "The amount should be a positive number"
-        );
+        require!(amount > 0, "The amount should be a positive number");
 
         // Withdraw from the sender and deposit into the receiver
         self.internal_withdraw(sender_id, amount);
         self.internal_deposit(receiver_id, amount);
 
-        // Emit a Transfer event
-        FtTransfer {
-            old_owner_id: sender_id,
-            new_owner_id: receiver_id,
-            amount: &amount,
-            memo: memo.as_deref(),
+        self.metrics.transfers += 1;
+    }
+
+    /// Internal method for minting some amount of FTs to an account, increasing the total supply.
+    pub(crate) fn internal_mint(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+    ) {
+        self.total_supply = self
+            .total_supply
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Total supply overflow"));
+        require!(
+            self.max_supply
+                .is_none_or(|max_supply| Self::internal_to_amount(self.total_supply) <= max_supply),
+            "The mint exceeds the max supply"
+        );
+        self.internal_deposit(account_id, amount);
+        let minted = Self::internal_to_amount(amount);
+        self.supply.minted = self.supply.minted.saturating_add(minted);
+        self.internal_record_supply();
+
+        self.metrics.mints += 1;
+
+        // Emit a Mint event
+        FtMint {
+            owner_id: account_id,
+            amount: &U128(minted),
+            memo,
+        }
+        .emit();
+    }
+
+    /// Internal method for burning some amount of FTs from an account, reducing the total supply.
+    pub(crate) fn internal_burn(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+    ) {
+        self.internal_withdraw(account_id, amount);
+        self.total_supply = self
+            .total_supply
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("Total supply overflow"));
+        let burned = Self::internal_to_amount(amount);
+        self.supply.burned = self.supply.burned.saturating_add(burned);
+        self.internal_record_supply();
+        self.internal_track_burn(account_id, burned);
+
+        self.metrics.burns += 1;
+
+        // Emit a Burn event
+        FtBurn {
+            owner_id: account_id,
+            amount: &U128(burned),
+            memo,
         }
         .emit();
     }
 
     /// Internal method for registering an account with the contract.
     pub(crate) fn internal_register_account(&mut self, account_id: &AccountId) {
-        if self.accounts.insert(account_id, &ZERO_TOKEN).is_some() {
+        if self.accounts.insert(account_id, &0).is_some() {
             env::panic_str("The account is already registered");
         }
-    }
-
-    /// Internal method for measuring how many bytes it takes to insert the longest possible account ID into our map
-    /// This will insert the account, measure the storage, and remove the account. It is called in the initialization function.
-    pub(crate) fn measure_bytes_for_longest_account_id(&mut self) {
-        let initial_storage_usage = env::storage_usage();
-        let tmp_account_id = AccountId::from_str(&"a".repeat(64)).unwrap();
-        self.accounts.insert(&tmp_account_id, &ZERO_TOKEN);
-        self.bytes_for_longest_account_id = env::storage_usage() - initial_storage_usage;
-        self.accounts.remove(&tmp_account_id);
+        self.metrics.active_accounts += 1;
     }
 }
@@ -0,0 +1,303 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+pub type JobId = u64;
+
+/// The number of items processed by a step when the caller doesn't say.
+const DEFAULT_JOB_STEP: u64 = 50;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Pays the payouts of the job out of the tokens escrowed by its creator.
+    Distribution,
+    /// Purges the expired allowances granted by `owner_id`.
+    AllowanceSweep { owner_id: AccountId },
+}
+
+// An operation too large to fit in a single call, processed in steps. The job stores the
+// cursor of the next item to process, and anyone can run the next step with `continue_job`,
+// earning a small bounty escrowed by the creator of the job.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Job {
+    pub creator_id: AccountId,
+    pub kind: JobKind,
+    /// The index of the next item to process.
+    pub cursor: u64,
+    /// The number of items of a distribution.
+    pub len: u64,
+    /// The bounty paid to the caller of every step.
+    pub bounty_per_step: U128,
+    /// The tokens left to pay the bounties, refunded to the creator once the job is done.
+    pub bounty_budget: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Starts a distribution of the caller's tokens, escrowing the payouts and the bounty budget.
+    /// More payouts can be added with `extend_distribution_job` until the job is done.
+    #[payable]
+    pub fn create_distribution_job(
+        &mut self,
+        payouts: Vec<(AccountId, U128)>,
+        bounty_per_step: U128,
+        bounty_budget: U128,
+    ) -> JobId {
+        assert_one_yocto();
+        let job_id =
+            self.internal_create_job(JobKind::Distribution, bounty_per_step, bounty_budget);
+        self.internal_extend_distribution(job_id, payouts);
+        job_id
+    }
+
+    /// Adds payouts to a distribution, escrowing them from the caller. Must be called by the
+    /// creator of the job.
+    #[payable]
+    pub fn extend_distribution_job(&mut self, job_id: JobId, payouts: Vec<(AccountId, U128)>) {
+        assert_one_yocto();
+        let job = self.internal_get_job(job_id);
+        require!(
+            env::predecessor_account_id() == job.creator_id,
+            "Only the creator can extend the job"
+        );
+        require!(
+            matches!(job.kind, JobKind::Distribution),
+            "The job isn't a distribution"
+        );
+        self.internal_extend_distribution(job_id, payouts);
+    }
+
+    /// Starts a sweep of the expired allowances granted by `owner_id`, escrowing the bounty
+    /// budget from the caller.
+    #[payable]
+    pub fn create_allowance_sweep_job(
+        &mut self,
+        owner_id: AccountId,
+        bounty_per_step: U128,
+        bounty_budget: U128,
+    ) -> JobId {
+        assert_one_yocto();
+        self.internal_create_job(
+            JobKind::AllowanceSweep { owner_id },
+            bounty_per_step,
+            bounty_budget,
+        )
+    }
+
+    /// Processes up to `limit` items of a job, paying the step bounty to the caller if it's
+    /// registered. Returns whether the job is done, in which case it's removed and the rest of
+    /// the bounty budget is refunded to its creator.
+    pub fn continue_job(&mut self, job_id: JobId, limit: Option<u64>) -> bool {
+        let mut job = self.internal_get_job(job_id);
+        let limit = limit.unwrap_or(DEFAULT_JOB_STEP);
+        require!(limit > 0, "The limit should be positive");
+        let (processed, done) = match job.kind.clone() {
+            JobKind::Distribution => self.internal_distribution_step(job_id, &mut job, limit),
+            JobKind::AllowanceSweep { owner_id } => {
+                self.internal_allowance_sweep_step(&owner_id, &mut job, limit)
+            }
+        };
+
+        let caller_id = env::predecessor_account_id();
+        let bounty = std::cmp::min(job.bounty_per_step.0, job.bounty_budget.0);
+        if processed > 0 && bounty > 0 && self.accounts.contains_key(&caller_id) {
+            job.bounty_budget = U128(job.bounty_budget.0.saturating_sub(bounty));
+            self.internal_transfer(
+                &env::current_account_id(),
+                &caller_id,
+                bounty,
+                Some(&format!("Bounty of job {}", job_id)),
+            );
+        }
+        log!(
+            "Job {}: {} items processed, cursor at {}",
+            job_id,
+            processed,
+            job.cursor
+        );
+
+        if done {
+            self.jobs.remove(&job_id);
+            if job.bounty_budget.0 > 0 {
+                self.internal_transfer(
+                    &env::current_account_id(),
+                    &job.creator_id,
+                    job.bounty_budget.0,
+                    Some(&format!("Bounty refund of job {}", job_id)),
+                );
+            }
+        } else {
+            self.jobs.insert(&job_id, &job);
+        }
+        done
+    }
+
+    /// Returns a job, or `null` if it doesn't exist or is done.
+    pub fn get_job(&self, job_id: JobId) -> Option<Job> {
+        self.jobs.get(&job_id).map(|mut job| {
+            job.bounty_per_step = U128(Self::internal_to_amount(job.bounty_per_step.0));
+            job.bounty_budget = U128(Self::internal_to_amount(job.bounty_budget.0));
+            job
+        })
+    }
+}
+
+impl Contract {
+    fn internal_get_job(&self, job_id: JobId) -> Job {
+        self.jobs
+            .get(&job_id)
+            .unwrap_or_else(|| env::panic_str("The job doesn't exist"))
+    }
+
+    fn internal_create_job(
+        &mut self,
+        kind: JobKind,
+        bounty_per_step: U128,
+        bounty_budget: U128,
+    ) -> JobId {
+        let creator_id = env::predecessor_account_id();
+        let bounty_per_step = U128(Self::internal_to_shares(bounty_per_step.0));
+        let bounty_budget = U128(Self::internal_to_shares(bounty_budget.0));
+        let job_id = self.next_job_id;
+        self.next_job_id += 1;
+        if bounty_budget.0 > 0 {
+            self.internal_transfer(
+                &creator_id,
+                &env::current_account_id(),
+                bounty_budget.0,
+                Some(&format!("Bounty budget of job {}", job_id)),
+            );
+        }
+        let job = Job {
+            creator_id,
+            kind,
+            cursor: 0,
+            len: 0,
+            bounty_per_step,
+            bounty_budget,
+        };
+        self.jobs.insert(&job_id, &job);
+        job_id
+    }
+
+    fn internal_extend_distribution(&mut self, job_id: JobId, payouts: Vec<(AccountId, U128)>) {
+        let mut job = self.internal_get_job(job_id);
+        let payouts: Vec<(AccountId, u128)> = payouts
+            .into_iter()
+            .map(|(account_id, amount)| (account_id, Self::internal_to_shares(amount.0)))
+            .collect();
+        require!(
+            !payouts.is_empty() && payouts.iter().all(|(_, amount)| *amount > 0),
+            "The payouts should be positive amounts"
+        );
+        let total = payouts
+            .iter()
+            .try_fold(0u128, |total, (_, amount)| total.checked_add(*amount))
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+        self.internal_transfer(
+            &job.creator_id,
+            &env::current_account_id(),
+            total,
+            Some(&format!("Escrow of job {}", job_id)),
+        );
+        for (account_id, amount) in payouts {
+            self.job_items
+                .insert(&(job_id, job.len), &(account_id, amount));
+            job.len += 1;
+        }
+        self.jobs.insert(&job_id, &job);
+    }
+
+    /// Internal method for paying the next payouts of a distribution. The payouts to accounts
+    /// that can't receive tokens (unregistered or frozen) are refunded to the creator.
+    fn internal_distribution_step(
+        &mut self,
+        job_id: JobId,
+        job: &mut Job,
+        limit: u64,
+    ) -> (u64, bool) {
+        let end = std::cmp::min(job.len, job.cursor.saturating_add(limit));
+        let mut refund = 0u128;
+        let memo = format!("Distribution of job {}", job_id);
+        let mut events = FtEventBatch::new();
+        for index in job.cursor..end {
+            let Some((account_id, amount)) = self.job_items.remove(&(job_id, index)) else {
+                continue;
+            };
+            if self.accounts.contains_key(&account_id)
+                && self.internal_active_freeze(&account_id).is_none()
+            {
+                self.internal_move_balance(&env::current_account_id(), &account_id, amount);
+                events.transfer(
+                    &env::current_account_id(),
+                    &account_id,
+                    Self::internal_to_amount(amount),
+                    Some(&memo),
+                );
+            } else {
+                refund = refund.saturating_add(amount);
+            }
+        }
+        if refund > 0 {
+            self.internal_move_balance(&env::current_account_id(), &job.creator_id, refund);
+            events.transfer(
+                &env::current_account_id(),
+                &job.creator_id,
+                Self::internal_to_amount(refund),
+                Some(&format!("Refund of job {}", job_id)),
+            );
+        }
+        events.emit();
+        let processed = end - job.cursor;
+        job.cursor = end;
+        (processed, job.cursor >= job.len)
+    }
+
+    /// Internal method for purging the next expired allowances of an owner. Purging an
+    /// allowance moves the last one in its place, so the cursor only moves past the allowances
+    /// that are kept.
+    fn internal_allowance_sweep_step(
+        &mut self,
+        owner_id: &AccountId,
+        job: &mut Job,
+        limit: u64,
+    ) -> (u64, bool) {
+        let Some(mut allowances) = self.allowances.get(owner_id) else {
+            return (0, true);
+        };
+        let now = env::block_timestamp();
+        let initial_storage_usage = env::storage_usage();
+        let mut processed = 0;
+        while processed < limit && job.cursor < allowances.len() {
+            let spender_id = allowances.keys_as_vector().get(job.cursor).unwrap();
+            let allowance = allowances.get(&spender_id).unwrap();
+            if Self::internal_allowance_expired(&allowance, now) {
+                allowances.remove(&spender_id);
+                AllowanceRevoked {
+                    owner_id,
+                    spender_id: &spender_id,
+                }
+                .emit();
+            } else {
+                job.cursor += 1;
+            }
+            processed += 1;
+        }
+        let done = job.cursor >= allowances.len();
+        if allowances.is_empty() {
+            self.allowances.remove(owner_id);
+        } else {
+            self.allowances.insert(owner_id, &allowances);
+        }
+        Self::internal_refund_storage(owner_id, initial_storage_usage);
+        (processed, done)
+    }
+}
@@ -0,0 +1,152 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+/// The jurisdiction accounts without an attested tag are matched as
+pub const UNTAGGED_JURISDICTION: &str = "untagged";
+
+/// The jurisdiction matching any jurisdiction in a rule
+pub const ANY_JURISDICTION: &str = "*";
+
+/// The maximum length of a jurisdiction tag
+const MAX_JURISDICTION_LEN: usize = 16;
+
+// What happens to the transfers between two jurisdictions. Transfers between jurisdictions
+// without a rule are allowed.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum JurisdictionRule {
+    /// No transfer is allowed.
+    Deny,
+    /// Only transfers of at most this amount are allowed.
+    MaxAmount(U128),
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Grants or revokes the right to attest the jurisdiction of accounts.
+    #[payable]
+    pub fn set_jurisdiction_attestor(&mut self, attestor_id: AccountId, enabled: bool) {
+        assert_one_yocto();
+        self.assert_owner();
+        if enabled {
+            self.jurisdiction_attestors.insert(&attestor_id);
+        } else {
+            self.jurisdiction_attestors.remove(&attestor_id);
+        }
+    }
+
+    /// Tags an account with its jurisdiction, or removes its tag when `None` is passed.
+    /// Must be called by a registered attestor.
+    pub fn attest_jurisdiction(&mut self, account_id: AccountId, jurisdiction: Option<String>) {
+        require!(
+            self.jurisdiction_attestors
+                .contains(&env::predecessor_account_id()),
+            "Only a jurisdiction attestor can call this method"
+        );
+        match jurisdiction {
+            Some(jurisdiction) => {
+                Self::internal_assert_jurisdiction(&jurisdiction);
+                require!(
+                    jurisdiction != ANY_JURISDICTION && jurisdiction != UNTAGGED_JURISDICTION,
+                    "This jurisdiction can't be attested"
+                );
+                self.account_jurisdictions
+                    .insert(&account_id, &jurisdiction);
+            }
+            None => {
+                self.account_jurisdictions.remove(&account_id);
+            }
+        }
+    }
+
+    /// Sets the rule applied to the transfers from `from` to `to`, or removes it when `None`
+    /// is passed. Either side can be `*` to match any jurisdiction, or `untagged` to match the
+    /// accounts without an attested jurisdiction.
+    #[payable]
+    pub fn set_jurisdiction_rule(
+        &mut self,
+        from: String,
+        to: String,
+        rule: Option<JurisdictionRule>,
+    ) {
+        assert_one_yocto();
+        self.assert_owner();
+        Self::internal_assert_jurisdiction(&from);
+        Self::internal_assert_jurisdiction(&to);
+        match rule {
+            Some(rule) => self.jurisdiction_rules.insert(&(from, to), &rule),
+            None => self.jurisdiction_rules.remove(&(from, to)),
+        };
+    }
+
+    /// Returns the attested jurisdiction of an account, if any.
+    pub fn get_jurisdiction(&self, account_id: AccountId) -> Option<String> {
+        self.account_jurisdictions.get(&account_id)
+    }
+
+    /// Returns every jurisdiction rule as `(from, to, rule)`.
+    pub fn get_jurisdiction_rules(&self) -> Vec<(String, String, JurisdictionRule)> {
+        self.jurisdiction_rules
+            .iter()
+            .map(|((from, to), rule)| (from, to, rule))
+            .collect()
+    }
+
+    /// Returns the accounts allowed to attest jurisdictions.
+    pub fn get_jurisdiction_attestors(&self) -> Vec<AccountId> {
+        self.jurisdiction_attestors.to_vec()
+    }
+}
+
+impl Contract {
+    /// Internal method for asserting that the jurisdiction rules allow a transfer. The rule of
+    /// the exact pair applies first, then `(from, *)`, then `(*, to)`.
+    pub(crate) fn internal_assert_jurisdiction_rules(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+    ) {
+        if self.jurisdiction_rules.is_empty() {
+            return;
+        }
+        let jurisdiction_of = |account_id: &AccountId| {
+            self.account_jurisdictions
+                .get(account_id)
+                .unwrap_or_else(|| UNTAGGED_JURISDICTION.to_string())
+        };
+        let from = jurisdiction_of(sender_id);
+        let to = jurisdiction_of(receiver_id);
+        let rule = [
+            (from.clone(), to.clone()),
+            (from, ANY_JURISDICTION.to_string()),
+            (ANY_JURISDICTION.to_string(), to),
+        ]
+        .iter()
+        .find_map(|pair| self.jurisdiction_rules.get(pair));
+
+        match rule {
+            Some(JurisdictionRule::Deny) => {
+                env::panic_str("Transfers between these jurisdictions are not allowed")
+            }
+            Some(JurisdictionRule::MaxAmount(max_amount)) => require!(
+                amount <= max_amount.0,
+                "The amount exceeds the limit between these jurisdictions"
+            ),
+            None => {}
+        }
+    }
+
+    fn internal_assert_jurisdiction(jurisdiction: &str) {
+        require!(
+            !jurisdiction.is_empty() && jurisdiction.len() <= MAX_JURISDICTION_LEN,
+            "The jurisdiction should be between 1 and 16 bytes long"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+// The bounties paid from the keeper pool to whoever makes a maintenance call first once it's
+// due, so that the time-based features don't depend on a trusted cron.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct KeeperBounties {
+    /// Paid for every successful `execute_mining_emission`.
+    pub mining_emission: U128,
+    /// Paid for cleaning up an expired pending transfer or treasury spend.
+    pub expiry_cleanup: U128,
+}
+
+// The scheduled actions users can tip keepers to execute, on top of the bounties.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum KeeperTask {
+    /// Pushing what's left of a stream to its receiver once it's fully accrued, with
+    /// `push_stream`.
+    Stream(StreamId),
+}
+
+// A tip escrowed by a user for the keeper executing a task first, so that the task is
+// executed on time without a centralized scheduler.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct KeeperTip {
+    /// The account that escrowed the tip, refunded if the task is cancelled.
+    pub tipper_id: AccountId,
+    pub amount: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the bounties paid to keepers.
+    #[payable]
+    pub fn set_keeper_bounties(&mut self, bounties: KeeperBounties) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.keeper_bounties = bounties;
+    }
+
+    /// Moves `amount` tokens from the caller into the keeper pool.
+    #[payable]
+    pub fn fund_keeper_pool(&mut self, amount: U128) {
+        assert_one_yocto();
+        let amount = Self::internal_to_shares(amount.0);
+        self.internal_transfer(
+            &env::predecessor_account_id(),
+            &env::current_account_id(),
+            amount,
+            Some("Keeper pool funding"),
+        );
+        self.keeper_pool = self
+            .keeper_pool
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+    }
+
+    /// Returns the bounties paid to keepers.
+    pub fn get_keeper_bounties(&self) -> KeeperBounties {
+        self.keeper_bounties.clone()
+    }
+
+    /// Returns the tokens left in the keeper pool.
+    pub fn get_keeper_pool(&self) -> U128 {
+        U128(Self::internal_to_amount(self.keeper_pool))
+    }
+
+    /// Escrows `amount` of the caller's tokens as a tip for the keeper executing `task` first
+    /// once it's due, or adds them to the caller's tip. A task is tipped by a single account.
+    #[payable]
+    pub fn add_keeper_tip(&mut self, task: KeeperTask, amount: U128) {
+        assert_one_yocto();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let amount = Self::internal_to_shares(amount.0);
+        match &task {
+            KeeperTask::Stream(stream_id) => {
+                require!(
+                    self.streams.contains_key(stream_id),
+                    "The stream doesn't exist"
+                );
+            }
+        }
+        let tipper_id = env::predecessor_account_id();
+        let mut tip = self.keeper_tips.get(&task).unwrap_or(KeeperTip {
+            tipper_id: tipper_id.clone(),
+            amount: U128(0),
+        });
+        require!(
+            tip.tipper_id == tipper_id,
+            "The task is already tipped by another account"
+        );
+        self.internal_transfer(
+            &tipper_id,
+            &env::current_account_id(),
+            amount,
+            Some("Keeper tip"),
+        );
+        tip.amount = U128(
+            tip.amount
+                .0
+                .checked_add(amount)
+                .unwrap_or_else(|| env::panic_str("Balance overflow")),
+        );
+        self.keeper_tips.insert(&task, &tip);
+    }
+
+    /// Returns the tip of a task, or `null` if it isn't tipped.
+    pub fn get_keeper_tip(&self, task: KeeperTask) -> Option<KeeperTip> {
+        self.keeper_tips.get(&task).map(|mut tip| {
+            tip.amount = U128(Self::internal_to_amount(tip.amount.0));
+            tip
+        })
+    }
+}
+
+impl Contract {
+    /// Internal method for paying `bounty` tokens to a keeper from the keeper pool, as far as
+    /// the pool allows. Unregistered keepers aren't paid.
+    pub(crate) fn internal_reward_keeper(
+        &mut self,
+        keeper_id: &AccountId,
+        task: &str,
+        bounty: u128,
+    ) {
+        let amount = std::cmp::min(Self::internal_to_shares(bounty), self.keeper_pool);
+        if amount == 0 || !self.accounts.contains_key(keeper_id) {
+            return;
+        }
+        self.keeper_pool = self.keeper_pool.saturating_sub(amount);
+        self.internal_transfer(
+            &env::current_account_id(),
+            keeper_id,
+            amount,
+            Some(&format!("Keeper bounty for {}", task)),
+        );
+        KeeperReward {
+            keeper_id,
+            task,
+            amount: &U128(Self::internal_to_amount(amount)),
+        }
+        .emit();
+    }
+
+    /// Internal method for paying the tip of a task to the keeper that executed it. An
+    /// unregistered keeper isn't paid, and the tip goes back to its tipper.
+    pub(crate) fn internal_pay_keeper_tip(&mut self, keeper_id: &AccountId, task: &KeeperTask) {
+        let Some(tip) = self.keeper_tips.remove(task) else {
+            return;
+        };
+        if !self.accounts.contains_key(keeper_id) {
+            self.internal_refund_keeper_tip(tip);
+            return;
+        }
+        self.internal_transfer(
+            &env::current_account_id(),
+            keeper_id,
+            tip.amount.0,
+            Some("Keeper tip"),
+        );
+        KeeperReward {
+            keeper_id,
+            task: "tip",
+            amount: &U128(Self::internal_to_amount(tip.amount.0)),
+        }
+        .emit();
+    }
+
+    /// Internal method for refunding the tip of a cancelled task to its tipper, if any.
+    pub(crate) fn internal_cancel_keeper_tip(&mut self, task: &KeeperTask) {
+        if let Some(tip) = self.keeper_tips.remove(task) {
+            self.internal_refund_keeper_tip(tip);
+        }
+    }
+
+    fn internal_refund_keeper_tip(&mut self, tip: KeeperTip) {
+        self.internal_transfer(
+            &env::current_account_id(),
+            &tip.tipper_id,
+            tip.amount.0,
+            Some("Keeper tip refund"),
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Labels attached to accounts for support and operations.
+//!
+//! An account can label itself, and the owner can attach a compliance label to any account.
+//! Reading the labels needs a caller, so `get_account_labels` has to be called in a
+//! transaction rather than as a view. The contract state stays public though: the labels
+//! aren't confidential, only kept out of the regular views.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+/// The maximum length of a label, in bytes
+const MAX_LABEL_LEN: usize = 32;
+
+// The labels of an account. The record is removed once both are unset.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountLabels {
+    /// The label the account gave itself.
+    pub self_label: Option<String>,
+    /// The label the owner attached to the account. Only the owner can read it.
+    pub compliance_label: Option<String>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the caller's own label, or removes it when `None` is passed. The caller must be
+    /// registered.
+    #[payable]
+    pub fn set_self_label(&mut self, label: Option<String>) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&account_id);
+        self.internal_update_account_labels(&account_id, |labels| labels.self_label = label);
+    }
+
+    /// Attaches a compliance label to an account, or removes it when `None` is passed (owner
+    /// only).
+    #[payable]
+    pub fn set_compliance_label(&mut self, account_id: AccountId, label: Option<String>) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.internal_update_account_labels(&account_id, |labels| labels.compliance_label = label);
+    }
+
+    /// Returns the labels of an account the caller can see: the owner sees both, the account
+    /// only its own label.
+    pub fn get_account_labels(&self, account_id: AccountId) -> AccountLabels {
+        let caller_id = env::predecessor_account_id();
+        let labels = self.account_labels.get(&account_id).unwrap_or_default();
+        if caller_id == self.owner_id {
+            labels
+        } else {
+            require!(
+                caller_id == account_id,
+                "Only the owner and the account can read its labels"
+            );
+            AccountLabels {
+                compliance_label: None,
+                ..labels
+            }
+        }
+    }
+}
+
+impl Contract {
+    /// Internal method for updating the labels of an account.
+    fn internal_update_account_labels(
+        &mut self,
+        account_id: &AccountId,
+        update: impl FnOnce(&mut AccountLabels),
+    ) {
+        let mut labels = self.account_labels.get(account_id).unwrap_or_default();
+        update(&mut labels);
+        for label in [&labels.self_label, &labels.compliance_label]
+            .into_iter()
+            .flatten()
+        {
+            require!(label.len() <= MAX_LABEL_LEN, "The label is too long");
+        }
+        if labels.self_label.is_none() && labels.compliance_label.is_none() {
+            self.account_labels.remove(account_id);
+        } else {
+            self.account_labels.insert(account_id, &labels);
+        }
+    }
+}
@@ -1,25 +1,177 @@
 // Find all our documentation at https://docs.near.org
 
+pub mod access;
+pub mod account_blobs;
+pub mod airdrop;
+pub mod approvals;
+pub mod attestations;
+#[cfg(feature = "audit")]
+pub mod audit;
+pub mod bonds;
+pub mod borsh_msg;
+pub mod bounty;
+pub mod budgets;
+pub mod burns;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod council;
+pub mod decimals;
+pub mod dust;
+pub mod encrypted_memo;
+pub mod escrow;
 pub mod events;
+pub mod factory;
+pub mod fees;
+pub mod freezing;
 pub mod ft_core;
+pub mod grants;
+pub mod guard;
+pub mod health;
+pub mod insurance;
 pub mod internal;
+pub mod jobs;
+pub mod jurisdictions;
+pub mod keepers;
+pub mod labels;
+pub mod linking;
+pub mod locales;
+pub mod locks;
+pub mod memos;
+pub mod merkle;
 pub mod metadata;
+pub mod metrics;
+pub mod mining;
+pub mod mint_limits;
+pub mod mint_requests;
+pub mod multicall;
+pub mod netting;
+pub mod onboarding;
+pub mod oracle;
+pub mod payment_intents;
+pub mod payment_keys;
+pub mod permits;
+pub mod preferences;
+pub mod psm;
+pub mod quarantine;
+pub mod quotes;
+pub mod rand;
+#[cfg(feature = "rebase")]
+pub mod rebase;
+pub mod rebates;
+pub mod receiver;
+pub mod receiver_list;
+pub mod recovery;
+pub mod referendum;
+pub mod relayer_gas;
+pub mod rescue;
+pub mod reservations;
+pub mod royalties;
+#[cfg(feature = "sandbox")]
+pub mod sandbox;
+pub mod scheduled;
+pub mod self_check;
+#[cfg(feature = "shielded")]
+pub mod shielded;
+pub mod signatures;
+pub mod snapshot;
+pub mod sponsorship;
+#[cfg(feature = "stablecoin")]
+pub mod stablecoin;
 pub mod storage;
+pub mod storage_keys;
+pub mod streams;
+pub mod supply;
+pub mod templates;
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
+pub mod tokenomics;
+pub mod travel_rule;
+pub mod treasury;
+pub mod upgrade;
+pub mod versioned;
+pub mod views;
+pub mod vouchers;
+pub mod wallet_policy;
+pub mod wrapped;
 
 use near_sdk::borsh::BorshDeserialize;
 use near_sdk::borsh::BorshSerialize;
 use near_sdk::collections::LazyOption;
 use near_sdk::collections::LookupMap;
+use near_sdk::collections::LookupSet;
+use near_sdk::collections::UnorderedMap;
+use near_sdk::collections::UnorderedSet;
+use near_sdk::collections::Vector;
+use near_sdk::env;
 use near_sdk::json_types::U128;
 use near_sdk::near_bindgen;
+use near_sdk::require;
 use near_sdk::AccountId;
-use near_sdk::BorshStorageKey;
 use near_sdk::NearToken;
 use near_sdk::PanicOnDefault;
-use near_sdk::StorageUsage;
+use near_sdk::PublicKey;
 
+use crate::access::*;
+use crate::airdrop::*;
+use crate::approvals::*;
+use crate::attestations::*;
+use crate::bonds::*;
+use crate::bounty::*;
+use crate::budgets::*;
+use crate::burns::*;
+use crate::council::*;
+use crate::escrow::*;
 use crate::events::*;
+use crate::factory::*;
+use crate::fees::*;
+use crate::freezing::*;
+use crate::ft_core::*;
+use crate::grants::*;
+use crate::guard::*;
+use crate::insurance::*;
+use crate::jobs::*;
+use crate::jurisdictions::*;
+use crate::keepers::*;
+use crate::labels::*;
+use crate::locales::*;
+use crate::locks::*;
+use crate::memos::*;
+use crate::merkle::*;
 use crate::metadata::*;
+use crate::metrics::*;
+use crate::mining::*;
+use crate::mint_limits::*;
+use crate::mint_requests::*;
+use crate::netting::*;
+use crate::oracle::*;
+use crate::payment_intents::*;
+use crate::payment_keys::*;
+use crate::preferences::*;
+use crate::psm::*;
+use crate::quarantine::*;
+use crate::quotes::*;
+use crate::rand::*;
+use crate::rebates::*;
+use crate::receiver_list::*;
+use crate::recovery::*;
+use crate::referendum::*;
+use crate::relayer_gas::*;
+use crate::reservations::*;
+use crate::royalties::*;
+use crate::scheduled::*;
+use crate::snapshot::*;
+use crate::sponsorship::*;
+use crate::storage::StorageSweepStats;
+use crate::storage_keys::*;
+use crate::streams::*;
+use crate::supply::*;
+use crate::templates::*;
+use crate::tokenomics::*;
+use crate::treasury::*;
+use crate::upgrade::*;
+use crate::versioned::VersionedLookupMap;
+use crate::vouchers::*;
+use crate::wallet_policy::*;
 
 const DATA_IMAGE_SVG_GT_ICON: &str = "data:image/svg+xml;base64,PD94bWwgdmVyc2lvbj0iMS4wIiBlbmNvZGluZz0idXRmLTgiPz4KPCEtLSBHZW5lcmF0b3I6IEFkb2JlIElsbHVzdHJhdG9yIDI0LjAuMCwgU1ZHIEV4cG9ydCBQbHVnLUluIC4gU1ZHIFZlcnNpb246IDYuMDAgQnVpbGQgMCkgIC0tPgo8c3ZnIHZlcnNpb249IjEuMSIgaWQ9IkxheWVyXzEiIHhtbG5zPSJodHRwOi8vd3d3LnczLm9yZy8yMDAwL3N2ZyIgeG1sbnM6eGxpbms9Imh0dHA6Ly93d3cudzMub3JnLzE5OTkveGxpbmsiIHg9IjBweCIgeT0iMHB4IgoJIHZpZXdCb3g9IjAgMCA5MC4xIDkwIiBzdHlsZT0iZW5hYmxlLWJhY2tncm91bmQ6bmV3IDAgMCA5MC4xIDkwOyIgeG1sOnNwYWNlPSJwcmVzZXJ2ZSI+CjxwYXRoIGQ9Ik03Mi4yLDQuNkw1My40LDMyLjVjLTEuMywxLjksMS4yLDQuMiwzLDIuNkw3NC45LDE5YzAuNS0wLjQsMS4yLTAuMSwxLjIsMC42djUwLjNjMCwwLjctMC45LDEtMS4zLDAuNWwtNTYtNjcKCUMxNywxLjIsMTQuNCwwLDExLjUsMGgtMkM0LjMsMCwwLDQuMywwLDkuNnY3MC44QzAsODUuNyw0LjMsOTAsOS42LDkwYzMuMywwLDYuNC0xLjcsOC4yLTQuNmwxOC44LTI3LjljMS4zLTEuOS0xLjItNC4yLTMtMi42CglsLTE4LjUsMTZjLTAuNSwwLjQtMS4yLDAuMS0xLjItMC42VjIwLjFjMC0wLjcsMC45LTEsMS4zLTAuNWw1Niw2N2MxLjgsMi4yLDQuNSwzLjQsNy4zLDMuNGgyYzUuMywwLDkuNi00LjMsOS42LTkuNlY5LjYKCWMwLTUuMy00LjMtOS42LTkuNi05LjZDNzcuMSwwLDc0LDEuNyw3Mi4yLDQuNnoiLz4KPC9zdmc+"; // Base64 encoded SVG image
 
@@ -28,31 +180,487 @@ pub const FT_METADATA_SPEC: &str = "ft-1.0.0";
 
 pub const ZERO_TOKEN: NearToken = NearToken::from_yoctonear(0);
 
+/// The denominator of every rate expressed in basis points
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
 // Implement the contract structure
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 #[borsh(crate = "near_sdk::borsh")]
 pub struct Contract {
-    /// Keep track of each account's balances
-    pub accounts: LookupMap<AccountId, NearToken>,
+    /// The account allowed to call privileged methods. The baseline layout had none: `migrate`
+    /// makes the contract account the owner, which can then hand the ownership over.
+    pub owner_id: AccountId,
 
-    /// Total supply of all tokens.
-    pub total_supply: NearToken,
+    /// Keep track of each account's balances, in the smallest unit of the token. They used to be
+    /// stored as `NearToken`, whose borsh encoding is the same as `u128`'s.
+    pub accounts: LookupMap<AccountId, u128>,
 
-    /// The bytes for the largest possible account ID that can be registered on the contract
-    pub bytes_for_longest_account_id: StorageUsage,
+    /// Total supply of all tokens.
+    pub total_supply: u128,
 
     /// Metadata for the contract itself
     pub metadata: LazyOption<FungibleTokenMetadata>,
-}
 
-/// Helper structure for keys of the persistent collections.
-#[derive(BorshSerialize, BorshStorageKey)]
-#[borsh(crate = "near_sdk::borsh")]
-pub enum StorageKey {
-    Accounts,
-    Metadata,
+    /// Liquidity mining programs scheduling emissions to external staking contracts
+    pub mining_programs: UnorderedMap<ProgramId, MiningProgram>,
+
+    /// The ID that will be assigned to the next liquidity mining program
+    pub next_mining_program_id: ProgramId,
+
+    /// Bond markets selling the token at a discount
+    pub bond_markets: VersionedLookupMap<BondMarketId, BondMarket>,
+
+    /// The ID that will be assigned to the next bond market
+    pub next_bond_market_id: BondMarketId,
+
+    /// The vesting bonds of each account
+    pub bond_positions: LookupMap<AccountId, Vec<BondPosition>>,
+
+    /// The transfer fee configuration, `None` while the fee switch is off
+    pub fee_config: Option<FeeConfig>,
+
+    /// The running totals of the fee buckets
+    pub fee_accumulators: FeeAccumulators,
+
+    /// The fee rate in basis points of each counterparty category
+    pub fee_category_rates: UnorderedMap<String, u16>,
+
+    /// The counterparty category of the accounts charged a category rate
+    pub account_fee_categories: LookupMap<AccountId, String>,
+
+    /// The keys allowed to sign relayed messages on behalf of each account
+    pub relay_keys: LookupMap<AccountId, PublicKey>,
+
+    /// The nonce the next relayed message of each account must use
+    pub relay_nonces: LookupMap<AccountId, u64>,
+
+    /// The NEAR balance each domain account set aside to pay for the storage of its sub-accounts
+    pub storage_sponsors: LookupMap<AccountId, NearToken>,
+
+    /// Operational counters exposed through the `metrics` view
+    pub metrics: Metrics,
+
+    /// The leaf of each registered account in the balance merkle tree
+    pub balance_leaf_indexes: LookupMap<AccountId, u64>,
+
+    /// The index of the leaf the next registered account will get
+    pub next_balance_leaf_index: u64,
+
+    /// The non-empty nodes of the balance merkle tree, keyed by level and index
+    pub balance_tree: LookupMap<(u8, u64), MerkleHash>,
+
+    /// The public keys encrypted memos sent to each account are sealed with
+    pub memo_keys: LookupMap<AccountId, Vec<u8>>,
+
+    /// The payment keys published by each account
+    pub payment_pubkeys: LookupMap<AccountId, PaymentPubkey>,
+
+    /// Point-of-sale payment intents
+    pub payment_intents: VersionedLookupMap<IntentId, PaymentIntent>,
+
+    /// The ID that will be assigned to the next payment intent
+    pub next_payment_intent_id: IntentId,
+
+    /// The co-signer each account requires for its large transfers
+    pub co_signers: LookupMap<AccountId, CoSignerConfig>,
+
+    /// Transfers waiting for their co-signer's confirmation
+    pub pending_transfers: VersionedLookupMap<PendingTransferId, PendingTransfer>,
+
+    /// The ID that will be assigned to the next pending transfer
+    pub next_pending_transfer_id: PendingTransferId,
+
+    /// The hot/cold wallet policy of each account
+    pub wallet_policies: LookupMap<AccountId, WalletPolicy>,
+
+    /// The timelocked wallet policy changes of each account
+    pub wallet_policy_changes: LookupMap<AccountId, WalletPolicyChange>,
+
+    /// The spending budgets of organizational accounts
+    pub budgets: LookupMap<AccountId, Vec<Budget>>,
+
+    /// The allowances granted by each account, keyed by spender
+    pub allowances: LookupMap<AccountId, UnorderedMap<AccountId, Allowance>>,
+
+    /// The pool paying for the purge of expired allowances
+    pub hygiene_pool: HygienePool,
+
+    /// The supply counters behind `ft_supply_breakdown`
+    pub supply: SupplyCounters,
+
+    /// The supply category of the accounts whose balances aren't circulating
+    pub supply_categories: LookupMap<AccountId, SupplyCategory>,
+
+    /// The total supply after the mints and burns, compacted into hourly buckets
+    pub supply_history: Vector<SupplyPoint>,
+
+    /// The total amount of tokens burned by each account
+    pub burned_by_account: LookupMap<AccountId, u128>,
+
+    /// The accounts that burned the most tokens, in decreasing order
+    pub burn_leaderboard: LazyOption<Vec<(AccountId, u128)>>,
+
+    /// The receipts of the burns made with `burn_with_receipt`
+    pub burn_receipts: VersionedLookupMap<BurnReceiptId, BurnReceipt>,
+
+    /// The ID that will be assigned to the next burn receipt
+    pub next_burn_receipt_id: BurnReceiptId,
+
+    /// The accounts allowed to submit mint requests
+    pub mint_proposers: UnorderedSet<AccountId>,
+
+    /// The accounts allowed to approve and reject mint requests
+    pub mint_approvers: UnorderedSet<AccountId>,
+
+    /// The number of approvals a mint request needs to be executed
+    pub mint_approval_threshold: u32,
+
+    /// Every mint request ever submitted, indexed by ID
+    pub mint_requests: Vector<MintRequest>,
+
+    /// The accounts frozen for compliance reasons
+    pub frozen_accounts: VersionedLookupMap<AccountId, Freeze>,
+
+    /// The accounts allowed to attest jurisdictions
+    pub jurisdiction_attestors: UnorderedSet<AccountId>,
+
+    /// The attested jurisdiction of each account
+    pub account_jurisdictions: LookupMap<AccountId, String>,
+
+    /// The rules applied to the transfers between two jurisdictions
+    pub jurisdiction_rules: UnorderedMap<(String, String), JurisdictionRule>,
+
+    /// The name of each registered Virtual Asset Service Provider
+    pub vasps: LookupMap<AccountId, String>,
+
+    /// The amount above which transfers between VASPs need travel rule data, if any
+    pub travel_rule_threshold: Option<u128>,
+
+    /// The attestation registry gating some actions, if any
+    pub attestation_config: Option<AttestationConfig>,
+
+    /// Incremented every time the registry or the credential changes, invalidating the cache
+    pub attestation_epoch: u64,
+
+    /// The last registry check of each account, along with the epoch it was made in
+    pub attestation_cache: LookupMap<AccountId, (u64, CachedAttestation)>,
+
+    /// The fee rebate program for high-volume accounts, if any
+    pub rebate_program: Option<RebateProgram>,
+
+    /// The rebate share of the collected fees, escrowed by the contract account
+    pub rebate_pool: u128,
+
+    /// The volume and rebates of each account
+    pub rebate_accounts: VersionedLookupMap<AccountId, RebateAccount>,
+
+    /// The gas usage of the protocol-owned relayers
+    pub relayer_usage: UnorderedMap<AccountId, RelayerUsage>,
+
+    /// The NEAR set aside to reimburse the protocol-owned relayers
+    pub relayer_gas_pool: NearToken,
+
+    /// The price (in yoctoNEAR per gas unit) relayers are reimbursed at
+    pub relayer_gas_price: u128,
+
+    /// Tokens locked by their owners
+    pub locks: VersionedLookupMap<LockId, Lock>,
+
+    /// The ID that will be assigned to the next lock
+    pub next_lock_id: LockId,
+
+    /// The receipt tokens of each lock series, keyed by lock and holder
+    pub receipt_balances: LookupMap<(LockId, AccountId), u128>,
+
+    /// Vesting grants, also surfaced as NEP-171 tokens
+    pub grants: VersionedLookupMap<GrantId, VestingGrant>,
+
+    /// The ID that will be assigned to the next vesting grant
+    pub next_grant_id: GrantId,
+
+    /// The grants held by each account
+    pub grants_per_holder: LookupMap<AccountId, Vec<GrantId>>,
+
+    /// The royalties paid to issuers on position transfers
+    pub position_royalties: PositionRoyalties,
+
+    /// The contracts `ft_transfer_then` can send tokens to and call
+    pub multicall_targets: UnorderedSet<AccountId>,
+
+    /// The two-man rule applied to the treasury spends, if any
+    pub treasury_guard: Option<TreasuryGuard>,
+
+    /// The treasury spends waiting for the approver's co-signature
+    pub treasury_spends: UnorderedMap<TreasurySpendId, TreasurySpend>,
+
+    /// The ID that will be assigned to the next treasury spend
+    pub next_treasury_spend_id: TreasurySpendId,
+
+    /// The long operations processed in steps
+    pub jobs: LookupMap<JobId, Job>,
+
+    /// The ID that will be assigned to the next job
+    pub next_job_id: JobId,
+
+    /// The items of the distribution jobs not processed yet
+    pub job_items: LookupMap<(JobId, u64), (AccountId, u128)>,
+
+    /// The bounties paid to keepers for maintenance calls
+    pub keeper_bounties: KeeperBounties,
+
+    /// The tokens held by the contract account to pay the keeper bounties
+    pub keeper_pool: u128,
+
+    /// The timestamps (in nanoseconds) at which accounts asked to be force-unregistered
+    pub force_unregister_requests: LookupMap<AccountId, u64>,
+
+    /// Force-unregistering an account holding more than this needs a confirmed request
+    pub force_unregister_threshold: u128,
+
+    /// The wasm deployed by the token factory
+    pub factory_code: LazyOption<Vec<u8>>,
+
+    /// The registry of the tokens launched by the factory
+    pub factory_tokens: Vector<FactoryToken>,
+
+    /// The quarantine the token entered when it was last renamed
+    pub rename_quarantine: LazyOption<RenameQuarantine>,
+
+    /// The timestamps (in nanoseconds) of the latest large transfer of each account in quarantine
+    pub quarantine_large_transfers: LookupMap<AccountId, u64>,
+
+    /// The account receiving the remainders too small to be represented, if any
+    pub dust_account_id: Option<AccountId>,
+
+    /// The groups of methods paused by the owner
+    pub pause_flags: PauseFlags,
+
+    /// The labels attached to accounts by themselves and by the owner
+    pub account_labels: LookupMap<AccountId, AccountLabels>,
+
+    /// The language of the failures of each account's calls, English if unset
+    pub account_locales: LookupMap<AccountId, Locale>,
+
+    /// The airdrops committing to their allocations with a merkle root
+    pub airdrops: VersionedLookupMap<AirdropId, Airdrop>,
+
+    /// The ID that will be assigned to the next airdrop
+    pub next_airdrop_id: AirdropId,
+
+    /// The claimed allocations of each airdrop, as bitmaps of 64 allocations
+    pub airdrop_claims: LookupMap<(AirdropId, u64), u64>,
+
+    /// The version of this layout, read by `migrate` to convert the state of older versions
+    pub state_version: u32,
+
+    /// The preferences set by each account
+    pub account_preferences: VersionedLookupMap<AccountId, AccountPreferences>,
+
+    /// The account the ownership was proposed to, until it accepts it
+    pub pending_owner_id: Option<AccountId>,
+
+    /// The roles granted to each account besides the owner
+    pub account_roles: LookupMap<AccountId, RoleSet>,
+
+    /// The commit-reveal randomness rounds
+    pub randomness_rounds: VersionedLookupMap<RandomnessRoundId, RandomnessRound>,
+
+    /// The ID that will be assigned to the next randomness round
+    pub next_randomness_round_id: RandomnessRoundId,
+
+    /// The commitments of each randomness round not revealed yet
+    pub randomness_commitments: LookupMap<(RandomnessRoundId, AccountId), [u8; 32]>,
+
+    /// Whether the tokens are wrapped NEAR, minted and burned by `near_deposit` and `near_withdraw`
+    pub wrapped: bool,
+
+    /// The council allowed to rotate the owner after its inactivity
+    pub recovery_config: Option<RecoveryConfig>,
+
+    /// The owner recovery proposed by the council, if any
+    pub owner_recovery: Option<OwnerRecovery>,
+
+    /// The timestamp (in nanoseconds) of the owner's last recorded activity
+    pub owner_active_at: u64,
+
+    /// The open token streams
+    pub streams: VersionedLookupMap<StreamId, Stream>,
+
+    /// The ID that will be assigned to the next stream
+    pub next_stream_id: StreamId,
+
+    /// The tokens held in the streams rather than in the accounts
+    pub streamed_supply: u128,
+
+    /// The snapshots of the balances, indexed by their ID
+    pub snapshots: Vector<Snapshot>,
+
+    /// The balances of each account at the snapshots taken before they changed
+    pub balance_checkpoints: LookupMap<AccountId, Vec<(SnapshotId, u128)>>,
+
+    /// The limits of the cross-contract calls, if any.
+    pub call_caps: Option<CallCaps>,
+
+    /// The receivers accepting `msg` payloads in Borsh
+    pub borsh_msg_receivers: LookupSet<AccountId>,
+
+    /// The most tokens that can ever be in circulation, if capped
+    pub max_supply: Option<u128>,
+
+    /// The limits of the mints of each minter, if any
+    pub mint_limits: Option<MintLimits>,
+
+    /// The day (since the epoch) of each minter's last mint and how much it minted that day
+    pub daily_mints: LookupMap<AccountId, (u64, u128)>,
+
+    /// The netting window (in blocks) of the receivers netting their transfer calls
+    pub netting_windows: LookupMap<AccountId, u64>,
+
+    /// The transfer calls each netting receiver wasn't notified of yet
+    pub netted_batches: LookupMap<AccountId, NettedBatch>,
+
+    /// The escrowed transfers waiting for their receivers
+    pub escrows: VersionedLookupMap<EscrowId, EscrowedTransfer>,
+
+    /// The ID of the next escrowed transfer
+    pub next_escrow_id: EscrowId,
+
+    /// The tips paid to the keeper executing each task first once it's due
+    pub keeper_tips: LookupMap<KeeperTask, KeeperTip>,
+
+    /// The token contract honoring the balance vouchers, if any
+    pub successor_id: Option<AccountId>,
+
+    /// The balance vouchers the successor contract didn't redeem yet
+    pub balance_vouchers: LookupMap<u64, BalanceVoucher>,
+
+    /// The nonce of the next balance voucher
+    pub next_voucher_nonce: u64,
+
+    /// The template the contract was initialized from, if any
+    pub template: Option<TokenTemplate>,
+
+    /// The council approving the privileged actions, if any
+    pub council: Option<CouncilConfig>,
+
+    /// The actions proposed to the council
+    pub council_proposals: LookupMap<CouncilProposalId, CouncilProposal>,
+
+    /// The ID of the next council proposal
+    pub next_council_proposal_id: CouncilProposalId,
+
+    /// The tokenomics of the token, stored next to its metadata
+    pub tokenomics: LazyOption<TokenomicsConfig>,
+
+    /// The configuration of the peg stability module, if it's on
+    pub psm_config: Option<PsmConfig>,
+
+    /// The reference tokens held by the peg stability module
+    pub psm_reserves: u128,
+
+    /// The tokens minted by the peg stability module and not redeemed yet
+    pub psm_debt: u128,
+
+    /// The NEAR paying the storage deposit of unregistered transfer receivers.
+    pub sponsorship_pool: NearToken,
+
+    /// How many receivers each sender can get registered by the sponsorship pool.
+    pub sponsorship_cap: u32,
+
+    /// The number of receivers the sponsorship pool registered for each sender.
+    pub sponsored_registrations: LookupMap<AccountId, u32>,
+
+    /// The accounts whose storage deposit was paid by the sponsorship pool.
+    pub sponsored_accounts: LookupSet<AccountId>,
+
+    /// The cap per claim and the timelock of the insurance payouts, if they're on.
+    pub insurance_config: Option<InsuranceConfig>,
+
+    /// The tokens of the insurance fund available for payouts, held by the contract account.
+    pub insurance_fund: u128,
+
+    /// The tokens of the approved insurance payouts not executed yet.
+    pub insurance_reserved: u128,
+
+    /// The approved insurance payouts, by ID.
+    pub insurance_claims: LookupMap<InsuranceClaimId, InsuranceClaim>,
+
+    /// The ID of the next approved insurance payout.
+    pub next_insurance_claim_id: InsuranceClaimId,
+
+    /// The tokens of the bug bounty pool, held by the contract account.
+    pub bounty_pool: u128,
+
+    /// The vulnerability disclosures, by ID.
+    pub disclosures: LookupMap<DisclosureId, Disclosure>,
+
+    /// The ID of the next vulnerability disclosure.
+    pub next_disclosure_id: DisclosureId,
+
+    /// The quorum of the holder referenda against the timelocked admin actions, if they're on.
+    pub referendum_config: Option<ReferendumConfig>,
+
+    /// The holder referenda, by ID.
+    pub referenda: LookupMap<ReferendumId, Referendum>,
+
+    /// The tokens staked by each holder in each referendum.
+    pub referendum_stakes: LookupMap<(ReferendumId, AccountId), u128>,
+
+    /// The last referendum opened against each action.
+    pub open_referenda: LookupMap<ChallengedAction, ReferendumId>,
+
+    /// The ID of the next holder referendum.
+    pub next_referendum_id: ReferendumId,
+
+    /// How the transfer calls treat the listed receivers.
+    pub receiver_list_mode: ReceiverListMode,
+
+    /// The receivers allowed or denied the transfer calls, depending on the mode.
+    pub listed_receivers: LookupSet<AccountId>,
+
+    /// The balance reservations waiting for their beneficiaries.
+    pub reservations: VersionedLookupMap<ReservationId, Reservation>,
+
+    /// The ID of the next balance reservation.
+    pub next_reservation_id: ReservationId,
+
+    /// The sum of the storage sponsorship balances.
+    pub storage_sponsorship_total: NearToken,
+
+    /// The ID of the next fee quote.
+    pub next_fee_quote_id: FeeQuoteId,
+
+    /// The current fee quote of every sender.
+    pub fee_quotes: LookupMap<AccountId, FeeQuote>,
+
+    /// The accounts which paid the storage deposit of another account.
+    pub storage_depositors: LookupMap<AccountId, AccountId>,
+
+    /// The totals of the accounts swept by `storage_sweep`.
+    pub storage_sweep_stats: StorageSweepStats,
+
+    /// The vesting grants created by the claims of the vesting airdrops, by airdrop and index.
+    pub airdrop_grants: LookupMap<(AirdropId, u64), GrantId>,
+
+    /// The length limit and the format of the transfer memos.
+    pub memo_config: MemoConfig,
+
+    /// The price feeds aggregated from several oracles.
+    pub price_feeds: LookupMap<String, PriceFeed>,
+
+    /// The scheduled transfers waiting for their unlock time
+    pub scheduled_transfers: VersionedLookupMap<ScheduledTransferId, ScheduledTransfer>,
+
+    /// The ID of the next scheduled transfer
+    pub next_scheduled_transfer_id: ScheduledTransferId,
+
+    /// The number of pending escrows, reservations, scheduled, streamed and co-signed transfers
+    /// paying out to or refunding each account, which `storage_sweep` and `storage_unregister`
+    /// keep registered.
+    pub pending_references: LookupMap<AccountId, u32>,
 }
+
 #[near_bindgen]
 impl Contract {
     /// Initializes the contract with the given total supply owned by the given `owner_id` with
@@ -72,36 +680,51 @@ impl Contract {
                 reference_hash: None,
                 decimals: 24,
             },
+            None,
         )
     }
 
     /// Initializes the contract with the given total supply owned by the given `owner_id` with
-    /// the given fungible token metadata.
+    /// the given fungible token metadata. The mints can never take the supply above
+    /// `max_supply`, if any.
     #[init]
-    pub fn new(owner_id: AccountId, total_supply: U128, metadata: FungibleTokenMetadata) -> Self {
-        let casted_total_supply = NearToken::from_yoctonear(total_supply.0);
+    pub fn new(
+        owner_id: AccountId,
+        total_supply: U128,
+        metadata: FungibleTokenMetadata,
+        max_supply: Option<U128>,
+    ) -> Self {
+        require!(
+            max_supply.is_none_or(|max_supply| total_supply.0 <= max_supply.0),
+            "The total supply exceeds the max supply"
+        );
         // Create a variable of type Self with all the fields initialized.
         let mut this = Self {
             // Set the total supply
-            total_supply: casted_total_supply,
-            // Set the bytes for the longest account ID to 0 temporarily until it's calculated later
-            bytes_for_longest_account_id: 0,
-            // Storage keys are simply the prefixes used for the collections. This helps avoid data collision
-            accounts: LookupMap::new(StorageKey::Accounts),
-            metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
+            total_supply: total_supply.0,
+            max_supply: max_supply.map(|max_supply| max_supply.0),
+            ..Self::internal_new_state(owner_id.clone())
         };
-
-        // Measure the bytes for the longest account ID and store it in the contract.
-        this.measure_bytes_for_longest_account_id();
+        this.metadata.set(&metadata);
 
         // Register the owner's account and set their balance to the total supply.
         this.internal_register_account(&owner_id);
-        this.internal_deposit(&owner_id, casted_total_supply);
+        this.internal_deposit(&owner_id, total_supply.0);
+
+        // Register the contract's own account so it can hold tokens escrowed by the extension modules.
+        let contract_id = env::current_account_id();
+        if contract_id != owner_id {
+            this.internal_register_account(&contract_id);
+        }
+
+        this.metrics.mints += 1;
+        this.supply.minted = total_supply.0;
+        this.internal_record_supply();
 
         // Emit an event showing that the FTs were minted
         FtMint {
             owner_id: &owner_id,
-            amount: &casted_total_supply,
+            amount: &U128(total_supply.0),
             memo: Some("Initial token supply is minted"),
         }
         .emit();
@@ -0,0 +1,88 @@
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::{env, near_bindgen, require};
+
+use crate::signatures::LINK_DOMAIN;
+use crate::*;
+
+/// The maximum number of locks that can be moved by a single merge
+const MAX_MERGED_LOCKS: usize = 50;
+
+#[near_bindgen]
+impl Contract {
+    /// Moves the balance, the vesting bonds, the vesting grants and the given locks (with the
+    /// receipt tokens of their series) of `old_account_id` to the caller. The old account
+    /// consents by signing `(old_account_id, new_account_id, nonce, expires_at)` with its relay key,
+    /// which lets users rotating to a new named account bring their holdings along.
+    ///
+    /// The balance moves like a transfer, under the same pauses, freezes, transfer policies and
+    /// co-signing threshold. The new account pays its fee, since the old one is left empty.
+    /// The grants their transfer policy keeps with their holder stay with the old account.
+    pub fn link_and_merge(
+        &mut self,
+        old_account_id: AccountId,
+        nonce: u64,
+        expires_at: Option<U64>,
+        signature_by_old_key: Base64VecU8,
+        lock_ids: Option<Vec<LockId>>,
+    ) -> U128 {
+        let new_account_id = env::predecessor_account_id();
+        require!(
+            old_account_id != new_account_id,
+            "Can't merge an account into itself"
+        );
+        let lock_ids = lock_ids.unwrap_or_default();
+        require!(
+            lock_ids.len() <= MAX_MERGED_LOCKS,
+            "Too many locks to merge at once"
+        );
+        // The new account must be registered to receive the balance.
+        self.internal_unwrap_balance_of(&new_account_id);
+        // The positions don't move through a balance transfer, so the freezes are checked
+        // even when there's no balance to move.
+        self.internal_assert_not_frozen(&old_account_id);
+        self.internal_assert_not_frozen(&new_account_id);
+
+        let expires_at = expires_at.map(|expires_at| expires_at.0);
+        self.internal_verify_signed(
+            &old_account_id,
+            LINK_DOMAIN,
+            &(&old_account_id, &new_account_id, nonce, expires_at),
+            nonce,
+            expires_at,
+            &signature_by_old_key.0,
+        );
+
+        // Move the whole balance
+        let balance = self.internal_unwrap_balance_of(&old_account_id);
+        if balance > 0 {
+            self.internal_transfer_with_fee_mode(
+                &old_account_id,
+                &new_account_id,
+                balance,
+                Some("Account merge"),
+                FeeMode::ReceiverPays,
+            );
+        }
+
+        // Move the vesting bonds
+        if let Some(old_positions) = self.bond_positions.remove(&old_account_id) {
+            let mut positions = self.bond_positions.get(&new_account_id).unwrap_or_default();
+            positions.extend(old_positions);
+            self.bond_positions.insert(&new_account_id, &positions);
+        }
+
+        // Move the vesting grants and the locks
+        self.internal_merge_grants(&old_account_id, &new_account_id);
+        for lock_id in lock_ids {
+            self.internal_merge_lock(lock_id, &old_account_id, &new_account_id);
+        }
+
+        AccountMerge {
+            old_account_id: &old_account_id,
+            new_account_id: &new_account_id,
+            amount: &U128(Self::internal_to_amount(balance)),
+        }
+        .emit();
+        U128(Self::internal_to_amount(balance))
+    }
+}
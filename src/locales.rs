@@ -0,0 +1,211 @@
+//! Translated messages for the most common failures.
+//!
+//! Accounts can pick the language of the failures of their own calls. The messages are
+//! prefixed with a stable error code, so that wallets can match the failures whatever the
+//! language.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, NearSchema, NearToken};
+
+use crate::*;
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+    Fr,
+    Vi,
+}
+
+// The failures with translated messages.
+#[derive(Clone, Copy)]
+pub(crate) enum ErrorCode<'a> {
+    NotRegistered(&'a AccountId),
+    NotEnoughBalance,
+    AccountFrozen(&'a AccountId),
+    TransfersPaused,
+    MintsPaused,
+    StoragePaused,
+    CallGasCapExceeded(u64),
+    CallDepositCapExceeded(NearToken),
+    NotEnoughGas(u64),
+}
+
+impl ErrorCode<'_> {
+    /// The code identifying the failure, which never changes.
+    pub(crate) fn code(self) -> &'static str {
+        match self {
+            ErrorCode::NotRegistered(_) => "E_NOT_REGISTERED",
+            ErrorCode::NotEnoughBalance => "E_NOT_ENOUGH_BALANCE",
+            ErrorCode::AccountFrozen(_) => "E_ACCOUNT_FROZEN",
+            ErrorCode::TransfersPaused => "E_TRANSFERS_PAUSED",
+            ErrorCode::MintsPaused => "E_MINTS_PAUSED",
+            ErrorCode::StoragePaused => "E_STORAGE_PAUSED",
+            ErrorCode::CallGasCapExceeded(_) => "E_CALL_GAS_CAP_EXCEEDED",
+            ErrorCode::CallDepositCapExceeded(_) => "E_CALL_DEPOSIT_CAP_EXCEEDED",
+            ErrorCode::NotEnoughGas(_) => "E_NOT_ENOUGH_GAS",
+        }
+    }
+
+    /// The message of the failure in `locale`.
+    pub(crate) fn message(self, locale: Locale) -> String {
+        match (self, locale) {
+            (ErrorCode::NotRegistered(account_id), Locale::En) => {
+                format!("The account {} is not registered", account_id)
+            }
+            (ErrorCode::NotRegistered(account_id), Locale::Es) => {
+                format!("La cuenta {} no está registrada", account_id)
+            }
+            (ErrorCode::NotRegistered(account_id), Locale::Fr) => {
+                format!("Le compte {} n'est pas enregistré", account_id)
+            }
+            (ErrorCode::NotRegistered(account_id), Locale::Vi) => {
+                format!("Tài khoản {} chưa được đăng ký", account_id)
+            }
+            (ErrorCode::NotEnoughBalance, Locale::En) => {
+                "The account doesn't have enough balance".to_string()
+            }
+            (ErrorCode::NotEnoughBalance, Locale::Es) => {
+                "La cuenta no tiene saldo suficiente".to_string()
+            }
+            (ErrorCode::NotEnoughBalance, Locale::Fr) => {
+                "Le compte n'a pas un solde suffisant".to_string()
+            }
+            (ErrorCode::NotEnoughBalance, Locale::Vi) => "Tài khoản không đủ số dư".to_string(),
+            (ErrorCode::AccountFrozen(account_id), Locale::En) => {
+                format!("The account {} is frozen", account_id)
+            }
+            (ErrorCode::AccountFrozen(account_id), Locale::Es) => {
+                format!("La cuenta {} está congelada", account_id)
+            }
+            (ErrorCode::AccountFrozen(account_id), Locale::Fr) => {
+                format!("Le compte {} est gelé", account_id)
+            }
+            (ErrorCode::AccountFrozen(account_id), Locale::Vi) => {
+                format!("Tài khoản {} đang bị đóng băng", account_id)
+            }
+            (ErrorCode::TransfersPaused, Locale::En) => "Transfers are paused".to_string(),
+            (ErrorCode::TransfersPaused, Locale::Es) => {
+                "Las transferencias están en pausa".to_string()
+            }
+            (ErrorCode::TransfersPaused, Locale::Fr) => "Les transferts sont suspendus".to_string(),
+            (ErrorCode::TransfersPaused, Locale::Vi) => {
+                "Các giao dịch chuyển đang tạm dừng".to_string()
+            }
+            (ErrorCode::MintsPaused, Locale::En) => "Mints and burns are paused".to_string(),
+            (ErrorCode::MintsPaused, Locale::Es) => {
+                "Las emisiones y quemas están en pausa".to_string()
+            }
+            (ErrorCode::MintsPaused, Locale::Fr) => {
+                "Les émissions et destructions sont suspendues".to_string()
+            }
+            (ErrorCode::MintsPaused, Locale::Vi) => {
+                "Việc phát hành và đốt đang tạm dừng".to_string()
+            }
+            (ErrorCode::StoragePaused, Locale::En) => "Storage operations are paused".to_string(),
+            (ErrorCode::StoragePaused, Locale::Es) => {
+                "Las operaciones de almacenamiento están en pausa".to_string()
+            }
+            (ErrorCode::StoragePaused, Locale::Fr) => {
+                "Les opérations de stockage sont suspendues".to_string()
+            }
+            (ErrorCode::StoragePaused, Locale::Vi) => {
+                "Các thao tác lưu trữ đang tạm dừng".to_string()
+            }
+            (ErrorCode::CallGasCapExceeded(cap), Locale::En) => {
+                format!("A call can't attach more than {} TGas", cap)
+            }
+            (ErrorCode::CallGasCapExceeded(cap), Locale::Es) => {
+                format!("Una llamada no puede adjuntar más de {} TGas", cap)
+            }
+            (ErrorCode::CallGasCapExceeded(cap), Locale::Fr) => {
+                format!("Un appel ne peut pas joindre plus de {} TGas", cap)
+            }
+            (ErrorCode::CallGasCapExceeded(cap), Locale::Vi) => {
+                format!("Một lệnh gọi không thể đính kèm quá {} TGas", cap)
+            }
+            (ErrorCode::CallDepositCapExceeded(cap), Locale::En) => {
+                format!(
+                    "A call can't attach more than {}",
+                    cap.exact_amount_display()
+                )
+            }
+            (ErrorCode::CallDepositCapExceeded(cap), Locale::Es) => {
+                format!(
+                    "Una llamada no puede adjuntar más de {}",
+                    cap.exact_amount_display()
+                )
+            }
+            (ErrorCode::CallDepositCapExceeded(cap), Locale::Fr) => {
+                format!(
+                    "Un appel ne peut pas joindre plus de {}",
+                    cap.exact_amount_display()
+                )
+            }
+            (ErrorCode::CallDepositCapExceeded(cap), Locale::Vi) => {
+                format!(
+                    "Một lệnh gọi không thể đính kèm quá {}",
+                    cap.exact_amount_display()
+                )
+            }
+            (ErrorCode::NotEnoughGas(required), Locale::En) => {
+                format!("The call needs at least {} TGas left", required)
+            }
+            (ErrorCode::NotEnoughGas(required), Locale::Es) => {
+                format!("La llamada necesita al menos {} TGas restantes", required)
+            }
+            (ErrorCode::NotEnoughGas(required), Locale::Fr) => {
+                format!("L'appel a besoin d'au moins {} TGas restants", required)
+            }
+            (ErrorCode::NotEnoughGas(required), Locale::Vi) => {
+                format!("Lệnh gọi cần còn lại ít nhất {} TGas", required)
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the language of the failures of the caller's calls, or goes back to English when
+    /// `None` is passed.
+    pub fn set_locale(&mut self, locale: Option<Locale>) {
+        let account_id = env::predecessor_account_id();
+        match locale {
+            Some(locale) if locale != Locale::default() => {
+                self.account_locales.insert(&account_id, &locale);
+            }
+            _ => {
+                self.account_locales.remove(&account_id);
+            }
+        }
+    }
+
+    /// Returns the language of the failures of an account's calls.
+    pub fn get_locale(&self, account_id: AccountId) -> Locale {
+        self.account_locales.get(&account_id).unwrap_or_default()
+    }
+}
+
+impl Contract {
+    /// Internal method for failing with the message of `code` in the caller's language. Must
+    /// only be called by change methods, views having no caller.
+    pub(crate) fn internal_panic(&self, code: ErrorCode) -> ! {
+        let locale = self.get_locale(env::predecessor_account_id());
+        env::panic_str(&format!("{}: {}", code.code(), code.message(locale)))
+    }
+}
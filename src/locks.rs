@@ -0,0 +1,292 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, NearSchema};
+
+use crate::versioned::versioned_record;
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+pub type LockId = u64;
+
+// Tokens locked until `unlock_at`, escrowed by the contract account. A lock created with a
+// receipt is represented by its own series of receipt tokens, which can be transferred like
+// the token itself; whoever holds them when the lock ends can redeem them 1:1.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Lock {
+    /// The account that locked the tokens.
+    pub owner_id: AccountId,
+    pub amount: U128,
+    /// The timestamp (in nanoseconds) when the tokens can be withdrawn.
+    pub unlock_at: U64,
+    /// Whether the position is represented by receipt tokens.
+    pub with_receipt: bool,
+    /// The tokens already withdrawn or redeemed.
+    pub released: U128,
+}
+
+versioned_record!(VersionedLock, Lock);
+
+#[near_bindgen]
+impl Contract {
+    /// Locks `amount` of the caller's tokens for `duration_sec` seconds. With `mint_receipt`,
+    /// the caller gets the same amount of transferable receipt tokens of the new lock's series.
+    /// The attached deposit (at least 1 yoctoNEAR) pays for the storage of the lock; the rest of
+    /// it is refunded.
+    #[payable]
+    pub fn lock_tokens(
+        &mut self,
+        amount: U128,
+        duration_sec: u64,
+        mint_receipt: Option<bool>,
+    ) -> LockId {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        self.internal_assert_not_paused(Pausable::Transfers);
+        require!(duration_sec > 0, "The lock duration should be positive");
+        let initial_storage_usage = env::storage_usage();
+        let amount = Self::internal_to_shares(amount.0);
+        let owner_id = env::predecessor_account_id();
+        let lock_id = self.next_lock_id;
+        self.next_lock_id += 1;
+        self.internal_transfer(
+            &owner_id,
+            &env::current_account_id(),
+            amount,
+            Some(&format!("Lock {}", lock_id)),
+        );
+
+        let with_receipt = mint_receipt.unwrap_or(false);
+        if with_receipt {
+            self.receipt_balances
+                .insert(&(lock_id, owner_id.clone()), &amount);
+            ReceiptTransfer {
+                lock_id,
+                old_owner_id: None,
+                new_owner_id: Some(&owner_id),
+                amount: &U128(Self::internal_to_amount(amount)),
+            }
+            .emit();
+        }
+        let unlock_at =
+            env::block_timestamp().saturating_add(duration_sec.saturating_mul(NANOS_PER_SECOND));
+        self.locks.insert(
+            &lock_id,
+            &Lock {
+                owner_id,
+                amount: U128(amount),
+                unlock_at: U64(unlock_at),
+                with_receipt,
+                released: U128(0),
+            },
+        );
+        Self::internal_charge_storage(initial_storage_usage);
+        lock_id
+    }
+
+    /// Withdraws the tokens of an ended lock without receipt. Must be called by its owner.
+    pub fn unlock_tokens(&mut self, lock_id: LockId) -> U128 {
+        let mut lock = self.internal_get_ended_lock(lock_id);
+        require!(
+            !lock.with_receipt,
+            "This lock is represented by receipt tokens, redeem them instead"
+        );
+        require!(
+            env::predecessor_account_id() == lock.owner_id,
+            "Only the owner can unlock the tokens"
+        );
+        let amount = lock.amount.0.saturating_sub(lock.released.0);
+        require!(amount > 0, "The tokens are already unlocked");
+        lock.released = lock.amount;
+        self.locks.insert(&lock_id, &lock);
+        self.internal_transfer(
+            &env::current_account_id(),
+            &lock.owner_id,
+            amount,
+            Some(&format!("Unlock {}", lock_id)),
+        );
+        U128(Self::internal_to_amount(amount))
+    }
+
+    /// Transfers receipt tokens of a lock to another registered account, under the same
+    /// policies as a transfer of the token. The attached deposit (at least 1 yoctoNEAR) pays for
+    /// the storage of the receiver's receipt balance; the rest of it is refunded.
+    #[payable]
+    pub fn receipt_transfer(&mut self, lock_id: LockId, receiver_id: AccountId, amount: U128) {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let amount = Self::internal_to_shares(amount.0);
+        let sender_id = env::predecessor_account_id();
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(amount > 0, "The amount should be a positive number");
+        self.internal_unwrap_balance_of(&receiver_id);
+        self.internal_assert_not_frozen(&sender_id);
+        self.internal_assert_not_frozen(&receiver_id);
+        self.internal_authorize_transfer(&sender_id, &receiver_id, amount, None, None);
+
+        self.internal_withdraw_receipt(lock_id, &sender_id, amount);
+        let issuer_id = self
+            .locks
+            .get(&lock_id)
+            .unwrap_or_else(|| env::panic_str("The lock doesn't exist"))
+            .owner_id;
+        let royalty_bps = self.position_royalties.lock_receipts_bps;
+        self.internal_pay_position_royalty(
+            "lock",
+            lock_id,
+            &sender_id,
+            &issuer_id,
+            amount,
+            royalty_bps,
+        );
+        let receiver_balance = self
+            .receipt_balances
+            .get(&(lock_id, receiver_id.clone()))
+            .unwrap_or(0);
+        self.receipt_balances.insert(
+            &(lock_id, receiver_id.clone()),
+            &receiver_balance.saturating_add(amount),
+        );
+        ReceiptTransfer {
+            lock_id,
+            old_owner_id: Some(&sender_id),
+            new_owner_id: Some(&receiver_id),
+            amount: &U128(Self::internal_to_amount(amount)),
+        }
+        .emit();
+        Self::internal_refund_storage(&sender_id, initial_storage_usage);
+        Self::internal_charge_storage(initial_storage_usage);
+    }
+
+    /// Redeems receipt tokens of an ended lock for the same amount of tokens. The storage of a
+    /// receipt balance redeemed in full is refunded.
+    pub fn redeem_receipt(&mut self, lock_id: LockId, amount: U128) {
+        let initial_storage_usage = env::storage_usage();
+        let amount = Self::internal_to_shares(amount.0);
+        let mut lock = self.internal_get_ended_lock(lock_id);
+        let account_id = env::predecessor_account_id();
+        self.internal_withdraw_receipt(lock_id, &account_id, amount);
+        lock.released = U128(lock.released.0.saturating_add(amount));
+        self.locks.insert(&lock_id, &lock);
+        ReceiptTransfer {
+            lock_id,
+            old_owner_id: Some(&account_id),
+            new_owner_id: None,
+            amount: &U128(Self::internal_to_amount(amount)),
+        }
+        .emit();
+        self.internal_transfer(
+            &env::current_account_id(),
+            &account_id,
+            amount,
+            Some(&format!("Redemption of lock {}", lock_id)),
+        );
+        Self::internal_refund_storage(&account_id, initial_storage_usage);
+    }
+
+    /// Returns a lock, or `null` if it doesn't exist.
+    pub fn get_lock(&self, lock_id: LockId) -> Option<Lock> {
+        self.locks.get(&lock_id).map(|mut lock| {
+            lock.amount = U128(Self::internal_to_amount(lock.amount.0));
+            lock.released = U128(Self::internal_to_amount(lock.released.0));
+            lock
+        })
+    }
+
+    /// Returns the receipt tokens of a lock held by an account.
+    pub fn receipt_balance_of(&self, lock_id: LockId, account_id: AccountId) -> U128 {
+        U128(Self::internal_to_amount(
+            self.receipt_balances
+                .get(&(lock_id, account_id))
+                .unwrap_or(0),
+        ))
+    }
+}
+
+impl Contract {
+    /// Internal method for getting a lock that ended, panicking otherwise.
+    fn internal_get_ended_lock(&self, lock_id: LockId) -> Lock {
+        let lock = self
+            .locks
+            .get(&lock_id)
+            .unwrap_or_else(|| env::panic_str("The lock doesn't exist"));
+        require!(
+            env::block_timestamp() >= lock.unlock_at.0,
+            "The tokens are still locked"
+        );
+        lock
+    }
+
+    /// Internal method for moving a lock and the receipt tokens of its series held by a merged
+    /// account to the account it's merged into. The merged account must own the lock or hold
+    /// some of its receipt tokens.
+    pub(crate) fn internal_merge_lock(
+        &mut self,
+        lock_id: LockId,
+        old_account_id: &AccountId,
+        new_account_id: &AccountId,
+    ) {
+        let mut lock = self
+            .locks
+            .get(&lock_id)
+            .unwrap_or_else(|| env::panic_str("The lock doesn't exist"));
+        let receipts = self
+            .receipt_balances
+            .get(&(lock_id, old_account_id.clone()))
+            .unwrap_or(0);
+        require!(
+            &lock.owner_id == old_account_id || receipts > 0,
+            "The merged account has no part in the lock"
+        );
+        if &lock.owner_id == old_account_id {
+            lock.owner_id = new_account_id.clone();
+            self.locks.insert(&lock_id, &lock);
+        }
+        if receipts > 0 {
+            self.internal_withdraw_receipt(lock_id, old_account_id, receipts);
+            let balance = self
+                .receipt_balances
+                .get(&(lock_id, new_account_id.clone()))
+                .unwrap_or(0);
+            self.receipt_balances.insert(
+                &(lock_id, new_account_id.clone()),
+                &balance.saturating_add(receipts),
+            );
+            ReceiptTransfer {
+                lock_id,
+                old_owner_id: Some(old_account_id),
+                new_owner_id: Some(new_account_id),
+                amount: &U128(Self::internal_to_amount(receipts)),
+            }
+            .emit();
+        }
+    }
+
+    /// Internal method for removing receipt tokens from an account's balance.
+    fn internal_withdraw_receipt(&mut self, lock_id: LockId, account_id: &AccountId, amount: u128) {
+        let balance = self
+            .receipt_balances
+            .get(&(lock_id, account_id.clone()))
+            .unwrap_or(0);
+        let new_balance = balance
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The account doesn't have enough receipt tokens"));
+        let key = (lock_id, account_id.clone());
+        if new_balance == 0 {
+            self.receipt_balances.remove(&key);
+        } else {
+            self.receipt_balances.insert(&key, &new_balance);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Memo validation of the user transfers.
+//!
+//! The memos of every user transfer, from `ft_transfer` to the allowance, travel rule and
+//! payment intent transfers, end up in the event logs, where a huge or malformed memo can blow
+//! past the log limits and break the indexers reading them. The owner caps their length and picks the format they must follow. Memos are always
+//! valid UTF-8, as they are decoded from the call arguments; `Printable` additionally rejects
+//! control characters, and `Json` requires a JSON object.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::{self, Value};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum MemoFormat {
+    /// Any text.
+    #[default]
+    Any,
+    /// Text without control characters, such as line breaks.
+    Printable,
+    /// A JSON object.
+    Json,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MemoConfig {
+    /// The longest memo accepted, in bytes, or `None` for no limit.
+    pub max_len: Option<u32>,
+    pub format: MemoFormat,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the length limit and the format of the transfer memos (owner only).
+    #[payable]
+    pub fn set_memo_config(&mut self, config: MemoConfig) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.memo_config = config;
+    }
+
+    /// Returns the length limit and the format of the transfer memos.
+    pub fn get_memo_config(&self) -> MemoConfig {
+        self.memo_config.clone()
+    }
+}
+
+impl Contract {
+    /// Internal method for rejecting a transfer memo over the length limit or not following
+    /// the memo format.
+    pub(crate) fn internal_assert_valid_memo(&self, memo: Option<&str>) {
+        let Some(memo) = memo else {
+            return;
+        };
+        if let Some(max_len) = self.memo_config.max_len {
+            require!(
+                memo.len() <= max_len as usize,
+                format!("The memo can't be longer than {} bytes", max_len)
+            );
+        }
+        match self.memo_config.format {
+            MemoFormat::Any => {}
+            MemoFormat::Printable => require!(
+                !memo.chars().any(char::is_control),
+                "The memo can't contain control characters"
+            ),
+            MemoFormat::Json => {
+                if !matches!(serde_json::from_str(memo), Ok(Value::Object(_))) {
+                    env::panic_str("The memo should be a JSON object");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Merkle tree of the balances.
+//!
+//! Hashing the path of a leaf takes 20 `sha256` calls and writes as many nodes, which is too
+//! much for every balance change. The tree is instead synced on demand: `sync_balance_tree`
+//! updates the leaves of the given accounts to their current balance, its caller paying for the
+//! storage of the new nodes. Indexers and replicas cross-check the `state_root` of the accounts
+//! they synced, and a proof is only given while the leaf matches the current balance.
+
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+/// The most accounts a single `sync_balance_tree` call can sync
+pub const MAX_SYNCED_LEAVES: usize = 50;
+
+/// Depth of the balance tree. Every registered account gets a leaf, so the tree can hold
+/// up to 2^20 (~1M) accounts.
+pub const BALANCE_TREE_DEPTH: u8 = 20;
+
+/// A node of the balance tree
+pub type MerkleHash = [u8; 32];
+
+// A merkle proof of the balance of an account, verifiable against `state_root`.
+// `siblings` lists the sibling of every node on the path from the leaf to the root.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BalanceProof {
+    pub account_id: AccountId,
+    pub balance: U128,
+    pub leaf_index: U64,
+    pub siblings: Vec<Base64VecU8>,
+    pub root: Base64VecU8,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Updates the leaves of the given accounts to their current balance, appending the
+    /// accounts without a leaf and emptying the leaves of the unregistered accounts. The
+    /// attached deposit must cover the storage of the new nodes; the rest is refunded.
+    #[payable]
+    pub fn sync_balance_tree(&mut self, account_ids: Vec<AccountId>) {
+        require!(
+            account_ids.len() <= MAX_SYNCED_LEAVES,
+            "Too many accounts to sync at once"
+        );
+        let initial_storage_usage = env::storage_usage();
+        for account_id in account_ids.iter() {
+            match self.accounts.get(account_id) {
+                Some(balance) => self.internal_update_balance_leaf(account_id, Some(balance)),
+                None if self.balance_leaf_indexes.contains_key(account_id) => {
+                    self.internal_update_balance_leaf(account_id, None)
+                }
+                None => {}
+            }
+        }
+        Self::internal_charge_storage(initial_storage_usage);
+    }
+
+    /// Returns the root of the merkle tree committing to the balances of the accounts as they
+    /// were last synced. Each leaf is `sha256(borsh(account_id, balance))`, empty leaves are all
+    /// zeroes and each inner node is `sha256(left || right)`.
+    pub fn state_root(&self) -> Base64VecU8 {
+        Base64VecU8(self.internal_balance_node(BALANCE_TREE_DEPTH, 0).to_vec())
+    }
+
+    /// Returns a proof of the balance of a registered account against the current `state_root`.
+    /// The leaf of the account must be synced with its current balance.
+    pub fn prove_balance(&self, account_id: AccountId) -> BalanceProof {
+        let balance = self.internal_unwrap_balance_of(&account_id);
+        let leaf_index = self
+            .balance_leaf_indexes
+            .get(&account_id)
+            .unwrap_or_else(|| env::panic_str("The account has no leaf in the balance tree"));
+        require!(
+            self.internal_balance_node(0, leaf_index)
+                == Self::internal_balance_leaf_hash(&account_id, balance),
+            "The balance changed since the leaf was synced"
+        );
+
+        let mut index = leaf_index;
+        let mut siblings = Vec::with_capacity(BALANCE_TREE_DEPTH as usize);
+        for level in 0..BALANCE_TREE_DEPTH {
+            siblings.push(Base64VecU8(
+                self.internal_balance_node(level, index ^ 1).to_vec(),
+            ));
+            index /= 2;
+        }
+
+        BalanceProof {
+            account_id,
+            balance: U128(balance),
+            leaf_index: U64(leaf_index),
+            siblings,
+            root: self.state_root(),
+        }
+    }
+
+    /// Checks that a balance proof is consistent with the root it carries. Off-chain verifiers
+    /// should also compare `proof.root` with a `state_root` they trust.
+    pub fn verify_balance_proof(&self, proof: BalanceProof) -> bool {
+        if proof.siblings.len() != BALANCE_TREE_DEPTH as usize {
+            return false;
+        }
+        let mut index = proof.leaf_index.0;
+        let mut hash = Self::internal_balance_leaf_hash(&proof.account_id, proof.balance.0);
+        for sibling in proof.siblings.iter() {
+            let Ok(sibling) = MerkleHash::try_from(sibling.0.as_slice()) else {
+                return false;
+            };
+            hash = if index & 1 == 0 {
+                Self::internal_hash_pair(&hash, &sibling)
+            } else {
+                Self::internal_hash_pair(&sibling, &hash)
+            };
+            index /= 2;
+        }
+        hash.as_slice() == proof.root.0.as_slice()
+    }
+}
+
+impl Contract {
+    /// Internal method for updating the leaf of an account, and every node on the path to the
+    /// root.
+    fn internal_update_balance_leaf(&mut self, account_id: &AccountId, balance: Option<u128>) {
+        let mut index = match self.balance_leaf_indexes.get(account_id) {
+            Some(index) => index,
+            None => {
+                // Append the account to the tree
+                let index = self.next_balance_leaf_index;
+                if index >= 1 << BALANCE_TREE_DEPTH {
+                    env::panic_str("The balance tree is full");
+                }
+                self.next_balance_leaf_index += 1;
+                self.balance_leaf_indexes.insert(account_id, &index);
+                index
+            }
+        };
+
+        let mut hash = match balance {
+            Some(balance) => Self::internal_balance_leaf_hash(account_id, balance),
+            None => [0; 32],
+        };
+        let mut empty_hash = [0; 32];
+        for level in 0..BALANCE_TREE_DEPTH {
+            self.balance_tree.insert(&(level, index), &hash);
+            let sibling = self
+                .balance_tree
+                .get(&(level, index ^ 1))
+                .unwrap_or(empty_hash);
+            hash = if index & 1 == 0 {
+                Self::internal_hash_pair(&hash, &sibling)
+            } else {
+                Self::internal_hash_pair(&sibling, &hash)
+            };
+            empty_hash = Self::internal_hash_pair(&empty_hash, &empty_hash);
+            index /= 2;
+        }
+        self.balance_tree.insert(&(BALANCE_TREE_DEPTH, 0), &hash);
+    }
+
+    /// Internal method for reading a node of the balance tree, defaulting to the empty subtree hash.
+    pub(crate) fn internal_balance_node(&self, level: u8, index: u64) -> MerkleHash {
+        self.balance_tree
+            .get(&(level, index))
+            .unwrap_or_else(|| Self::internal_empty_subtree_hash(level))
+    }
+
+    pub(crate) fn internal_balance_leaf_hash(account_id: &AccountId, balance: u128) -> MerkleHash {
+        env::sha256_array(
+            &near_sdk::borsh::to_vec(&(account_id, balance)).unwrap_or_else(|_| env::abort()),
+        )
+    }
+
+    pub(crate) fn internal_hash_pair(left: &MerkleHash, right: &MerkleHash) -> MerkleHash {
+        let mut data = [0; 64];
+        data[..32].copy_from_slice(left);
+        data[32..].copy_from_slice(right);
+        env::sha256_array(&data)
+    }
+
+    /// The hash of a subtree of the given height where every leaf is empty.
+    fn internal_empty_subtree_hash(level: u8) -> MerkleHash {
+        (0..level).fold([0; 32], |hash, _| Self::internal_hash_pair(&hash, &hash))
+    }
+}
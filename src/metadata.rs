@@ -1,7 +1,7 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::Base64VecU8;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{near_bindgen, NearSchema};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
 
 use crate::*;
 
@@ -18,14 +18,91 @@ pub struct FungibleTokenMetadata {
     pub decimals: u8, // used in frontends to show the proper significant digits of a token. This concept is explained well in this OpenZeppelin post. https://docs.openzeppelin.com/contracts/3.x/erc20#a-note-on-decimals
 }
 
+// The metadata along with the quarantine the token is in after a rename, if any.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FungibleTokenMetadataView {
+    #[serde(flatten)]
+    pub metadata: FungibleTokenMetadata,
+    /// Set while the token is in quarantine, so that wallets can warn about the rename.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_change: Option<RenameQuarantine>,
+}
+
 pub trait FungibleTokenMetadataProvider {
     // View call for returning the contract metadata
-    fn ft_metadata(&self) -> FungibleTokenMetadata;
+    fn ft_metadata() -> FungibleTokenMetadataView;
 }
 
 #[near_bindgen]
 impl FungibleTokenMetadataProvider for Contract {
-    fn ft_metadata(&self) -> FungibleTokenMetadata {
-        self.metadata.get().unwrap()
+    fn ft_metadata() -> FungibleTokenMetadataView {
+        FungibleTokenMetadataView {
+            metadata: Self::stateless_metadata().get().unwrap(),
+            pending_change: Self::stateless_rename_quarantine()
+                .get()
+                .filter(|quarantine| env::block_timestamp() < quarantine.ends_at.0),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets or clears the icon of the token (owner and metadata admins).
+    #[payable]
+    pub fn set_icon(&mut self, icon: Option<String>) {
+        assert_one_yocto();
+        self.assert_role(Role::MetadataAdmin);
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.icon = icon;
+        self.metadata.set(&metadata);
+        MetadataUpdated {
+            updated_fields: &["icon"],
+        }
+        .emit();
+    }
+
+    /// Sets or clears the reference of the token along with the sha256 hash of the referenced
+    /// file (owner and metadata admins).
+    #[payable]
+    pub fn set_reference(
+        &mut self,
+        reference: Option<String>,
+        reference_hash: Option<Base64VecU8>,
+    ) {
+        assert_one_yocto();
+        self.assert_role(Role::MetadataAdmin);
+        require!(
+            reference.is_some() == reference_hash.is_some(),
+            "The reference and its hash should be set together"
+        );
+        require!(
+            reference_hash
+                .as_ref()
+                .is_none_or(|hash| hash.0.len() == 32),
+            "The reference hash should be 32 bytes long"
+        );
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.reference = reference;
+        metadata.reference_hash = reference_hash;
+        self.metadata.set(&metadata);
+        MetadataUpdated {
+            updated_fields: &["reference", "reference_hash"],
+        }
+        .emit();
+    }
+
+    /// Renames the token (owner and metadata admins), starting a quarantine like
+    /// `rename_token`.
+    #[payable]
+    pub fn set_name(&mut self, name: String) {
+        self.rename_token(Some(name), None);
+    }
+
+    /// Changes the symbol of the token (owner and metadata admins), starting a quarantine like
+    /// `rename_token`.
+    #[payable]
+    pub fn set_symbol(&mut self, symbol: String) {
+        self.rename_token(None, Some(symbol));
     }
 }
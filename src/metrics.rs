@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::near_bindgen;
+
+use crate::*;
+
+// Operational counters maintained by the core methods, exposed through `metrics` so that an
+// off-chain exporter can scrape them each block.
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct Metrics {
+    /// Number of transfers, including fee legs and refunds.
+    pub transfers: u64,
+    /// Number of mints.
+    pub mints: u64,
+    /// Number of burns.
+    pub burns: u64,
+    /// Number of `ft_transfer_call`s whose receiver failed or returned an invalid value.
+    pub failed_transfer_calls: u64,
+    /// Number of registered accounts.
+    pub active_accounts: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the operational counters of the contract as a flat key/value map.
+    pub fn metrics(&self) -> BTreeMap<String, U128> {
+        [
+            ("transfers_total", self.metrics.transfers as u128),
+            ("mints_total", self.metrics.mints as u128),
+            ("burns_total", self.metrics.burns as u128),
+            (
+                "failed_transfer_calls_total",
+                self.metrics.failed_transfer_calls as u128,
+            ),
+            ("active_accounts", self.metrics.active_accounts as u128),
+            ("total_supply", self.total_supply),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), U128(value)))
+        .collect()
+    }
+}
@@ -0,0 +1,360 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_one_yocto, env, near_bindgen, require, Gas, NearSchema, Promise, PromiseResult,
+};
+
+use crate::ft_core::ext_ft_receiver;
+use crate::*;
+
+const GAS_FOR_MINING_RESOLVE: Gas = Gas::from_tgas(10);
+const GAS_FOR_MINING_EMISSION: Gas = Gas::from_tgas(30).saturating_add(GAS_FOR_MINING_RESOLVE);
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+pub type ProgramId = u64;
+
+// A liquidity mining program. The owner registers an external staking contract (or pool)
+// and the tokens it should receive each epoch. Emissions are held in escrow by the contract
+// account and are pushed to the pool through `ft_transfer_call` whenever an epoch becomes due.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MiningProgram {
+    /// The staking contract receiving the emissions.
+    pub pool_id: AccountId,
+    /// The `msg` passed along to the pool's `ft_on_transfer`.
+    pub msg: String,
+    /// The tokens emitted to the pool for every epoch.
+    pub amount_per_epoch: U128,
+    /// The length of an epoch in seconds.
+    pub epoch_duration_sec: u64,
+    /// The timestamp (in nanoseconds) when the first epoch starts.
+    pub start_at: U64,
+    /// The number of epochs the program runs for.
+    pub total_epochs: u32,
+    /// The number of epochs already paid out to the pool.
+    pub epochs_paid: u32,
+    /// Tokens escrowed for this program and not yet emitted.
+    pub funded: U128,
+    /// Tokens accepted by the pool so far.
+    pub total_emitted: U128,
+    /// Tokens returned by the pool as unused.
+    pub total_refunded: U128,
+    /// How many emission attempts failed and had to be retried.
+    pub failed_attempts: u32,
+    /// Whether an emission is waiting for its callback.
+    pub in_flight: bool,
+    /// Whether the owner cancelled the program.
+    pub cancelled: bool,
+}
+
+// The accounting view returned for a liquidity mining program.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MiningProgramView {
+    pub program_id: ProgramId,
+    #[serde(flatten)]
+    pub program: MiningProgram,
+    pub due_epochs: u32,
+    pub due_amount: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Registers a new liquidity mining program emitting `amount_per_epoch` to `pool_id` for
+    /// `total_epochs` epochs. The first epoch becomes due `epoch_duration_sec` after `start_at`
+    /// (defaults to now). The program must be funded with `fund_mining_program` before emitting.
+    #[payable]
+    pub fn create_mining_program(
+        &mut self,
+        pool_id: AccountId,
+        msg: Option<String>,
+        amount_per_epoch: U128,
+        epoch_duration_sec: u64,
+        total_epochs: u32,
+        start_at: Option<U64>,
+    ) -> ProgramId {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(
+            amount_per_epoch.0 > 0,
+            "The amount per epoch should be a positive number"
+        );
+        require!(
+            epoch_duration_sec > 0,
+            "The epoch duration should be positive"
+        );
+        require!(
+            total_epochs > 0,
+            "The program should run for at least one epoch"
+        );
+
+        let start_at = start_at.map_or_else(env::block_timestamp, |start_at| start_at.0);
+        let program_id = self.next_mining_program_id;
+        self.next_mining_program_id += 1;
+        self.mining_programs.insert(
+            &program_id,
+            &MiningProgram {
+                pool_id,
+                msg: msg.unwrap_or_default(),
+                amount_per_epoch,
+                epoch_duration_sec,
+                start_at: U64(start_at),
+                total_epochs,
+                epochs_paid: 0,
+                funded: U128(0),
+                total_emitted: U128(0),
+                total_refunded: U128(0),
+                failed_attempts: 0,
+                in_flight: false,
+                cancelled: false,
+            },
+        );
+        program_id
+    }
+
+    /// Moves `amount` tokens from the caller into the escrow of the given program.
+    #[payable]
+    pub fn fund_mining_program(&mut self, program_id: ProgramId, amount: U128) {
+        assert_one_yocto();
+        let mut program = self.internal_get_mining_program(program_id);
+        require!(!program.cancelled, "The mining program is cancelled");
+
+        let amount = Self::internal_to_shares(amount.0);
+        self.internal_transfer(
+            &env::predecessor_account_id(),
+            &env::current_account_id(),
+            amount,
+            Some(&format!("Funding of mining program {}", program_id)),
+        );
+        program.funded = U128(
+            program
+                .funded
+                .0
+                .checked_add(amount)
+                .unwrap_or_else(|| env::panic_str("Balance overflow")),
+        );
+        self.mining_programs.insert(&program_id, &program);
+    }
+
+    /// Cancels a program and returns the tokens left in its escrow to the owner.
+    #[payable]
+    pub fn cancel_mining_program(&mut self, program_id: ProgramId) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut program = self.internal_get_mining_program(program_id);
+        require!(
+            !program.cancelled,
+            "The mining program is already cancelled"
+        );
+        require!(
+            !program.in_flight,
+            "An emission is in progress, try again later"
+        );
+
+        let remaining = program.funded.0;
+        program.funded = U128(0);
+        program.cancelled = true;
+        self.mining_programs.insert(&program_id, &program);
+
+        if remaining > 0 {
+            self.internal_transfer(
+                &env::current_account_id(),
+                &self.owner_id.clone(),
+                remaining,
+                Some(&format!("Cancellation of mining program {}", program_id)),
+            );
+        }
+    }
+
+    /// Emits every due epoch of the program to its pool through `ft_transfer_call`.
+    /// Anyone can call this method, and earns the keeper bounty if the emission succeeds. If the
+    /// pool rejects the transfer, the epochs stay due and the emission is retried on the next
+    /// call.
+    pub fn execute_mining_emission(&mut self, program_id: ProgramId) -> Promise {
+        let mut program = self.internal_get_mining_program(program_id);
+        require!(!program.cancelled, "The mining program is cancelled");
+        require!(!program.in_flight, "An emission is already in progress");
+
+        let (epochs, due_amount) = Self::internal_mining_due(&program);
+        require!(epochs > 0, "No epoch is due for this program");
+        let amount = Self::internal_to_shares(due_amount);
+        require!(
+            program.funded.0 >= amount,
+            "The mining program doesn't have enough funds for the due epochs"
+        );
+
+        self.internal_promise_budget()
+            .call(GAS_FOR_MINING_EMISSION, ZERO_TOKEN)
+            .call(GAS_FOR_MINING_RESOLVE, ZERO_TOKEN)
+            .check();
+        // Move the due amount out of escrow before calling the pool, like `ft_transfer_call` does.
+        program.funded = U128(program.funded.0.saturating_sub(amount));
+        program.in_flight = true;
+        self.mining_programs.insert(&program_id, &program);
+
+        let contract_id = env::current_account_id();
+        self.internal_transfer(
+            &contract_id,
+            &program.pool_id,
+            amount,
+            Some(&format!("Emission of mining program {}", program_id)),
+        );
+
+        ext_ft_receiver::ext(program.pool_id.clone())
+            .with_static_gas(GAS_FOR_MINING_EMISSION)
+            .ft_on_transfer(
+                contract_id.clone(),
+                U128(Self::internal_to_amount(amount)),
+                program.msg.clone(),
+            )
+            .then(
+                Self::ext(contract_id)
+                    .with_static_gas(GAS_FOR_MINING_RESOLVE)
+                    .on_mining_emission_resolved(
+                        program_id,
+                        epochs,
+                        U128(amount),
+                        env::predecessor_account_id(),
+                    ),
+            )
+    }
+
+    /// Callback resolving an emission of `amount` shares. Unused tokens go back to the program
+    /// escrow; a failed call refunds everything and leaves the epochs due so they can be
+    /// retried.
+    #[private]
+    pub fn on_mining_emission_resolved(
+        &mut self,
+        program_id: ProgramId,
+        epochs: u32,
+        amount: U128,
+        keeper_id: AccountId,
+    ) -> U128 {
+        let amount = amount.0;
+        let mut program = self.internal_get_mining_program(program_id);
+        program.in_flight = false;
+
+        // Get the unused amount the same way `ft_resolve_transfer` does.
+        let (unused_amount, failed) = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                match near_sdk::serde_json::from_slice::<U128>(&value) {
+                    Ok(unused_amount) => (
+                        std::cmp::min(amount, Self::internal_to_shares(unused_amount.0)),
+                        false,
+                    ),
+                    Err(_) => (amount, true),
+                }
+            }
+            PromiseResult::Failed => (amount, true),
+        };
+
+        // The pool can only give back what it still holds.
+        let pool_balance = self.accounts.get(&program.pool_id).unwrap_or(0);
+        let refund_amount = std::cmp::min(pool_balance, unused_amount);
+        if refund_amount > 0 {
+            // The pool may have been frozen since the emission: the callback must not fail, or
+            // the program would stay in flight for good.
+            self.internal_contract_transfer(
+                &program.pool_id,
+                &env::current_account_id(),
+                refund_amount,
+                Some("Refund"),
+            );
+            program.funded = U128(program.funded.0.saturating_add(refund_amount));
+        }
+        let used_amount = amount.saturating_sub(refund_amount);
+
+        if failed {
+            program.failed_attempts += 1;
+            MiningEmissionFailed {
+                program_id,
+                pool_id: &program.pool_id,
+                epochs,
+                amount: &U128(Self::internal_to_amount(amount)),
+            }
+            .emit();
+        } else {
+            program.epochs_paid += epochs;
+            program.total_emitted = U128(program.total_emitted.0.saturating_add(used_amount));
+            program.total_refunded = U128(program.total_refunded.0.saturating_add(refund_amount));
+            MiningEmission {
+                program_id,
+                pool_id: &program.pool_id,
+                epochs,
+                amount: &U128(Self::internal_to_amount(used_amount)),
+                refunded: &U128(Self::internal_to_amount(refund_amount)),
+            }
+            .emit();
+            let bounty = self.keeper_bounties.mining_emission.0;
+            self.internal_reward_keeper(&keeper_id, "mining_emission", bounty);
+        }
+
+        self.mining_programs.insert(&program_id, &program);
+        U128(Self::internal_to_amount(used_amount))
+    }
+
+    /// Returns the accounting view of a program, or `null` if it doesn't exist.
+    pub fn get_mining_program(&self, program_id: ProgramId) -> Option<MiningProgramView> {
+        self.mining_programs
+            .get(&program_id)
+            .map(|program| Self::internal_mining_program_view(program_id, program))
+    }
+
+    /// Paginates through all the liquidity mining programs.
+    pub fn get_mining_programs(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<MiningProgramView> {
+        self.mining_programs
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(program_id, program)| Self::internal_mining_program_view(program_id, program))
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Internal method for getting a mining program, panicking if it doesn't exist.
+    pub(crate) fn internal_get_mining_program(&self, program_id: ProgramId) -> MiningProgram {
+        self.mining_programs
+            .get(&program_id)
+            .unwrap_or_else(|| env::panic_str("The mining program doesn't exist"))
+    }
+
+    /// Internal method for computing the epochs (and the amount) due but not yet paid.
+    pub(crate) fn internal_mining_due(program: &MiningProgram) -> (u32, u128) {
+        let now = env::block_timestamp();
+        let epoch_duration = program.epoch_duration_sec.saturating_mul(NANOS_PER_SECOND);
+        let elapsed_epochs = if now < program.start_at.0 {
+            0
+        } else {
+            (now - program.start_at.0) / epoch_duration
+        };
+        let due_epochs = std::cmp::min(elapsed_epochs, program.total_epochs as u64) as u32;
+        let epochs = due_epochs.saturating_sub(program.epochs_paid);
+        (
+            epochs,
+            program.amount_per_epoch.0.saturating_mul(epochs as u128),
+        )
+    }
+
+    fn internal_mining_program_view(
+        program_id: ProgramId,
+        mut program: MiningProgram,
+    ) -> MiningProgramView {
+        let (due_epochs, due_amount) = Self::internal_mining_due(&program);
+        program.funded = U128(Self::internal_to_amount(program.funded.0));
+        program.total_emitted = U128(Self::internal_to_amount(program.total_emitted.0));
+        program.total_refunded = U128(Self::internal_to_amount(program.total_refunded.0));
+        MiningProgramView {
+            program_id,
+            program,
+            due_epochs,
+            due_amount: U128(due_amount),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Limits of the mints.
+//!
+//! The supply can be capped at init by `max_supply`, which no mint can exceed. On top of it,
+//! the owner can limit what each minter mints per transaction and per day, so that a
+//! compromised minter key can only inflate the supply that much. The limits apply to the
+//! callers of `ft_mint` and to the proposers of the mint requests.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+const NANOS_PER_DAY: u64 = 86_400 * 1_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintLimits {
+    /// The most tokens a minter can mint at once, if limited.
+    pub per_transaction: Option<U128>,
+    /// The most tokens a minter can mint per day (UTC), if limited.
+    pub per_day: Option<U128>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the limits of the mints of each minter, or removes them when `None` is passed (owner
+    /// only).
+    #[payable]
+    pub fn set_mint_limits(&mut self, limits: Option<MintLimits>) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.mint_limits = limits;
+    }
+
+    pub fn get_mint_limits(&self) -> Option<MintLimits> {
+        self.mint_limits.clone()
+    }
+
+    /// Returns the max supply, or `null` if the supply isn't capped.
+    pub fn get_max_supply(&self) -> Option<U128> {
+        self.max_supply.map(U128)
+    }
+
+    /// Returns the most tokens that can be minted now, by `minter_id` if given: what's left
+    /// under the max supply, capped by the minter's limits.
+    pub fn remaining_mintable(&self, minter_id: Option<AccountId>) -> U128 {
+        let mut remaining = self.max_supply.map_or(u128::MAX, |max_supply| {
+            max_supply.saturating_sub(Self::internal_to_amount(self.total_supply))
+        });
+        // The limits count in shares, like the mints they are checked against.
+        if let (Some(limits), Some(minter_id)) = (&self.mint_limits, minter_id) {
+            if let Some(per_transaction) = limits.per_transaction {
+                remaining = remaining.min(Self::internal_to_amount(per_transaction.0));
+            }
+            if let Some(per_day) = limits.per_day {
+                remaining = remaining.min(Self::internal_to_amount(
+                    per_day
+                        .0
+                        .saturating_sub(self.internal_minted_today(&minter_id)),
+                ));
+            }
+        }
+        U128(remaining)
+    }
+}
+
+impl Contract {
+    /// Internal method for checking a mint of `amount` tokens by `minter_id` against the mint
+    /// limits, and counting it in the minter's daily mints.
+    pub(crate) fn internal_use_mint_limits(&mut self, minter_id: &AccountId, amount: u128) {
+        let Some(limits) = &self.mint_limits else {
+            return;
+        };
+        if let Some(per_transaction) = limits.per_transaction {
+            require!(
+                amount <= per_transaction.0,
+                "The mint exceeds the per-transaction limit"
+            );
+        }
+        if let Some(per_day) = limits.per_day {
+            let minted_today = self.internal_minted_today(minter_id) + amount;
+            require!(
+                minted_today <= per_day.0,
+                "The mint exceeds the daily limit of the minter"
+            );
+            let today = env::block_timestamp() / NANOS_PER_DAY;
+            self.daily_mints.insert(minter_id, &(today, minted_today));
+        }
+    }
+
+    // Returns what a minter minted since the start of the day.
+    fn internal_minted_today(&self, minter_id: &AccountId) -> u128 {
+        match self.daily_mints.get(minter_id) {
+            Some((day, minted)) if day == env::block_timestamp() / NANOS_PER_DAY => minted,
+            _ => 0,
+        }
+    }
+}
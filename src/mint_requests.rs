@@ -0,0 +1,227 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+/// The maximum length of the justification of a mint request
+const MAX_JUSTIFICATION_LEN: usize = 1024;
+
+pub type MintRequestId = u64;
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum MintRequestStatus {
+    Pending,
+    Executed,
+    Rejected,
+}
+
+// A request to mint new tokens, submitted by a proposer. It's executed as soon as enough
+// approvers approved it, or closed as soon as one of them rejects it.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintRequest {
+    pub proposer_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub justification: String,
+    /// The approvers that approved the request so far.
+    pub approvals: Vec<AccountId>,
+    pub status: MintRequestStatus,
+    /// The approver that rejected the request, along with the reason it gave.
+    pub rejection: Option<(AccountId, Option<String>)>,
+    /// The timestamp (in nanoseconds) when the request was submitted.
+    pub created_at: U64,
+    /// The timestamp (in nanoseconds) when the request was executed or rejected.
+    pub resolved_at: Option<U64>,
+}
+
+// The accounts taking part in the mint workflow.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintRoles {
+    pub proposers: Vec<AccountId>,
+    pub approvers: Vec<AccountId>,
+    /// The number of approvals a request needs to be executed.
+    pub approval_threshold: u32,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Grants or revokes the right to submit mint requests.
+    #[payable]
+    pub fn set_mint_proposer(&mut self, account_id: AccountId, enabled: bool) {
+        assert_one_yocto();
+        self.assert_owner();
+        if enabled {
+            self.mint_proposers.insert(&account_id);
+        } else {
+            self.mint_proposers.remove(&account_id);
+        }
+    }
+
+    /// Grants or revokes the right to approve and reject mint requests.
+    #[payable]
+    pub fn set_mint_approver(&mut self, account_id: AccountId, enabled: bool) {
+        assert_one_yocto();
+        self.assert_owner();
+        if enabled {
+            self.mint_approvers.insert(&account_id);
+        } else {
+            self.mint_approvers.remove(&account_id);
+        }
+    }
+
+    /// Sets the number of approvals a mint request needs to be executed.
+    #[payable]
+    pub fn set_mint_approval_threshold(&mut self, approval_threshold: u32) {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(
+            approval_threshold > 0,
+            "The approval threshold should be positive"
+        );
+        require!(
+            approval_threshold as u64 <= self.mint_approvers.len(),
+            "The approval threshold is higher than the number of approvers"
+        );
+        self.mint_approval_threshold = approval_threshold;
+    }
+
+    /// Submits a request to mint `amount` tokens to `receiver_id`. Must be called by a proposer.
+    pub fn propose_mint(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        justification: String,
+    ) -> MintRequestId {
+        let proposer_id = env::predecessor_account_id();
+        require!(
+            self.mint_proposers.contains(&proposer_id),
+            "Only a mint proposer can call this method"
+        );
+        self.internal_assert_not_wrapped();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        require!(
+            justification.len() <= MAX_JUSTIFICATION_LEN,
+            "The justification is too long"
+        );
+        self.internal_unwrap_balance_of(&receiver_id);
+
+        self.mint_requests.push(&MintRequest {
+            proposer_id,
+            receiver_id,
+            amount,
+            justification,
+            approvals: vec![],
+            status: MintRequestStatus::Pending,
+            rejection: None,
+            created_at: U64(env::block_timestamp()),
+            resolved_at: None,
+        });
+        self.mint_requests.len() - 1
+    }
+
+    /// Approves a pending mint request, executing it once it reaches the approval threshold.
+    /// Must be called by an approver other than the proposer.
+    pub fn approve_mint(&mut self, request_id: MintRequestId) -> MintRequestStatus {
+        self.internal_assert_not_paused(Pausable::Mints);
+        let approver_id = env::predecessor_account_id();
+        let mut request = self.internal_get_pending_mint_request(request_id, &approver_id);
+        require!(
+            !request.approvals.contains(&approver_id),
+            "The mint request is already approved by this approver"
+        );
+        request.approvals.push(approver_id);
+
+        // Approvers revoked since they approved don't count anymore.
+        let approvals = request
+            .approvals
+            .iter()
+            .filter(|approver_id| self.mint_approvers.contains(approver_id))
+            .count();
+        if approvals >= self.mint_approval_threshold as usize {
+            request.status = MintRequestStatus::Executed;
+            request.resolved_at = Some(U64(env::block_timestamp()));
+            let memo = format!("Mint request {}", request_id);
+            self.internal_assert_not_wrapped();
+            let amount = Self::internal_to_shares(request.amount.0);
+            self.internal_use_mint_limits(&request.proposer_id, amount);
+            self.internal_mint(&request.receiver_id, amount, Some(&memo));
+        }
+        self.mint_requests.replace(request_id, &request);
+        request.status
+    }
+
+    /// Rejects a pending mint request. Must be called by an approver other than the proposer.
+    pub fn reject_mint(&mut self, request_id: MintRequestId, reason: Option<String>) {
+        let approver_id = env::predecessor_account_id();
+        let mut request = self.internal_get_pending_mint_request(request_id, &approver_id);
+        request.status = MintRequestStatus::Rejected;
+        request.rejection = Some((approver_id, reason));
+        request.resolved_at = Some(U64(env::block_timestamp()));
+        self.mint_requests.replace(request_id, &request);
+    }
+
+    /// Returns a mint request, or `null` if it doesn't exist.
+    pub fn get_mint_request(&self, request_id: MintRequestId) -> Option<MintRequest> {
+        self.mint_requests.get(request_id)
+    }
+
+    /// Paginates through the history of mint requests.
+    pub fn get_mint_requests(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<MintRequest> {
+        self.mint_requests
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+
+    /// Returns the proposers, the approvers and the approval threshold of the mint workflow.
+    pub fn get_mint_roles(&self) -> MintRoles {
+        MintRoles {
+            proposers: self.mint_proposers.to_vec(),
+            approvers: self.mint_approvers.to_vec(),
+            approval_threshold: self.mint_approval_threshold,
+        }
+    }
+}
+
+impl Contract {
+    /// Internal method for getting a pending mint request on behalf of one of its approvers,
+    /// panicking if it can't be approved or rejected by `approver_id`.
+    fn internal_get_pending_mint_request(
+        &self,
+        request_id: MintRequestId,
+        approver_id: &AccountId,
+    ) -> MintRequest {
+        require!(
+            self.mint_approvers.contains(approver_id),
+            "Only a mint approver can call this method"
+        );
+        let request = self
+            .mint_requests
+            .get(request_id)
+            .unwrap_or_else(|| env::panic_str("The mint request doesn't exist"));
+        require!(
+            request.status == MintRequestStatus::Pending,
+            "The mint request is not pending"
+        );
+        require!(
+            &request.proposer_id != approver_id,
+            "The proposer can't approve or reject its own mint request"
+        );
+        request
+    }
+}
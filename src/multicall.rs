@@ -0,0 +1,147 @@
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_one_yocto, env, log, near_bindgen, require, Gas, NearSchema, Promise, PromiseResult,
+};
+
+use crate::*;
+
+const MAX_FOLLOW_UP_ACTIONS: usize = 4;
+const MAX_GAS_PER_ACTION: Gas = Gas::from_tgas(50);
+const GAS_FOR_RESOLVE_TRANSFER_THEN: Gas = Gas::from_tgas(10);
+
+// A follow-up call made by `ft_transfer_then` once the transfer is done.
+#[derive(Serialize, Deserialize, NearSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PromiseAction {
+    /// The contract to call. Must be whitelisted by the owner.
+    pub contract_id: AccountId,
+    pub method_name: String,
+    pub args: Base64VecU8,
+    /// The NEAR attached to the call, paid from the deposit attached to `ft_transfer_then`.
+    pub deposit: NearToken,
+    /// The gas attached to the call, in TGas.
+    pub gas_tgas: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers `amount` tokens to `receiver_id`, then makes the follow-up `actions` calls in
+    /// parallel. If any of them fails, the transfer is reverted as far as the receiver's balance
+    /// allows. The receiver and every called contract must be whitelisted. The attached deposit
+    /// must be 1 yoctoNEAR plus the deposits of the actions.
+    ///
+    /// Returns a promise which will result in the amount of tokens kept by the receiver.
+    #[payable]
+    pub fn ft_transfer_then(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        actions: Vec<PromiseAction>,
+    ) -> Promise {
+        require!(
+            !actions.is_empty() && actions.len() <= MAX_FOLLOW_UP_ACTIONS,
+            "Between 1 and 4 follow-up actions are allowed"
+        );
+        require!(
+            self.multicall_targets.contains(&receiver_id),
+            "The receiver isn't whitelisted"
+        );
+        let mut deposits = NearToken::from_yoctonear(1);
+        let mut budget = self.internal_promise_budget();
+        for action in actions.iter() {
+            require!(
+                self.multicall_targets.contains(&action.contract_id),
+                "The called contract isn't whitelisted"
+            );
+            require!(
+                Gas::from_tgas(action.gas_tgas) <= MAX_GAS_PER_ACTION,
+                "Too much gas attached to a follow-up action"
+            );
+            deposits = deposits.saturating_add(action.deposit);
+            budget = budget.call(Gas::from_tgas(action.gas_tgas), action.deposit);
+        }
+        budget
+            .call(GAS_FOR_RESOLVE_TRANSFER_THEN, ZERO_TOKEN)
+            .check();
+        require!(
+            env::attached_deposit() == deposits,
+            "The attached deposit should cover exactly the deposits of the actions and 1 yoctoNEAR"
+        );
+
+        let amount = Self::internal_to_shares(amount.0);
+        let sender_id = env::predecessor_account_id();
+        // The follow-up calls need the transfer to happen now, so it can't wait for a co-signer.
+        require!(
+            self.internal_co_signer_required(&sender_id, amount)
+                .is_none(),
+            "The transfer needs the co-signer's confirmation"
+        );
+        self.internal_transfer_with_fee(&sender_id, &receiver_id, amount, memo.as_deref());
+
+        let calls = actions
+            .into_iter()
+            .map(|action| {
+                Promise::new(action.contract_id).function_call(
+                    action.method_name,
+                    action.args.0,
+                    action.deposit,
+                    Gas::from_tgas(action.gas_tgas),
+                )
+            })
+            .reduce(Promise::and)
+            .unwrap_or_else(|| env::abort());
+        calls.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_TRANSFER_THEN)
+                .ft_resolve_transfer_then(sender_id, receiver_id, U128(amount)),
+        )
+    }
+
+    /// Finalizes an `ft_transfer_then`, reverting the transfer if any follow-up call failed.
+    /// Takes the shares transferred, and returns the amount of tokens kept by the receiver.
+    #[private]
+    pub fn ft_resolve_transfer_then(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        let amount = amount.0;
+        let failed = (0..env::promise_results_count())
+            .any(|index| matches!(env::promise_result(index), PromiseResult::Failed));
+        if !failed {
+            return U128(Self::internal_to_amount(amount));
+        }
+        self.metrics.failed_transfer_calls += 1;
+        let receiver_balance = self.accounts.get(&receiver_id).unwrap_or(0);
+        let refund = std::cmp::min(receiver_balance, amount);
+        if refund > 0 {
+            self.internal_contract_transfer(&receiver_id, &sender_id, refund, Some("Refund"));
+        }
+        log!(
+            "A follow-up call failed, {} refunded to {}",
+            refund,
+            sender_id
+        );
+        U128(Self::internal_to_amount(amount.saturating_sub(refund)))
+    }
+
+    /// Allows or disallows `ft_transfer_then` to send tokens to and call a contract.
+    #[payable]
+    pub fn set_multicall_target(&mut self, contract_id: AccountId, allowed: bool) {
+        assert_one_yocto();
+        self.assert_owner();
+        if allowed {
+            self.multicall_targets.insert(&contract_id);
+        } else {
+            self.multicall_targets.remove(&contract_id);
+        }
+    }
+
+    /// Returns the contracts `ft_transfer_then` can send tokens to and call.
+    pub fn get_multicall_targets(&self) -> Vec<AccountId> {
+        self.multicall_targets.to_vec()
+    }
+}
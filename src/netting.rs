@@ -0,0 +1,211 @@
+//! Netting of the transfer calls.
+//!
+//! A receiver contract handling many transfer calls, like an order book, can opt in to netting:
+//! the transfer calls it receives within a window of blocks are then notified to it at once,
+//! through a single `ft_on_transfer_batch` call. The tokens are credited to the receiver right
+//! away, like any transfer call, and `ft_transfer_call` returns the whole amount: the unused
+//! amounts are refunded to their senders once the batch is resolved.
+//!
+//! The batch is notified by the first transfer call after the window, or by anyone calling
+//! `flush_netted_transfers` once it's over.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, ext_contract, near_bindgen, require, Gas, NearSchema, Promise, PromiseResult};
+
+use crate::*;
+
+/// The most transfer calls notified in a single `ft_on_transfer_batch` call
+const MAX_NETTED_TRANSFERS: usize = 50;
+/// The longest netting window, in blocks
+const MAX_NETTING_WINDOW: u64 = 1_000;
+const GAS_FOR_FT_TRANSFER_BATCH_CALL: Gas = Gas::from_tgas(100);
+const GAS_FOR_RESOLVE_NETTED_TRANSFERS: Gas = Gas::from_tgas(50);
+
+// A transfer call the receiver wasn't notified of yet.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct NettedTransfer {
+    pub sender_id: AccountId,
+    /// The amount credited to the receiver.
+    pub amount: U128,
+    pub msg: String,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct NettedBatch {
+    /// The height of the block of the first transfer call of the batch.
+    pub opened_at: U64,
+    pub transfers: Vec<NettedTransfer>,
+}
+
+#[ext_contract(ext_ft_batch_receiver)]
+pub trait FungibleTokenBatchReceiver {
+    /// Called by the token contract with the transfer calls received within a netting window,
+    /// like `ft_on_transfer` for each of them. Returns the amount of unused tokens of each
+    /// transfer, in the same order, to be returned to their senders.
+    fn ft_on_transfer_batch(&mut self, transfers: Vec<NettedTransfer>) -> Vec<U128>;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Makes the caller receive the transfer calls of each window of `window_blocks` blocks
+    /// through a single `ft_on_transfer_batch` call, or stops netting them when `None` is
+    /// passed. The caller must be registered and have no pending batch.
+    pub fn set_netting_window(&mut self, window_blocks: Option<u64>) {
+        let account_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&account_id);
+        require!(
+            !self.netted_batches.contains_key(&account_id),
+            "The pending batch should be flushed first"
+        );
+        match window_blocks {
+            Some(window_blocks) => {
+                require!(
+                    window_blocks > 0 && window_blocks <= MAX_NETTING_WINDOW,
+                    "The netting window should be between 1 and 1000 blocks"
+                );
+                self.netting_windows.insert(&account_id, &window_blocks);
+            }
+            None => {
+                self.netting_windows.remove(&account_id);
+            }
+        }
+    }
+
+    /// Returns the netting window (in blocks) of a receiver, or `null` if it doesn't net its
+    /// transfer calls.
+    pub fn get_netting_window(&self, account_id: AccountId) -> Option<u64> {
+        self.netting_windows.get(&account_id)
+    }
+
+    /// Returns the transfer calls a receiver wasn't notified of yet, if any.
+    pub fn get_netted_batch(&self, receiver_id: AccountId) -> Option<NettedBatch> {
+        self.netted_batches
+            .get(&receiver_id)
+            .map(Self::internal_netted_batch_amounts)
+    }
+
+    /// Notifies a receiver of its pending batch once its netting window is over. Anyone can
+    /// call it.
+    pub fn flush_netted_transfers(&mut self, receiver_id: AccountId) -> Promise {
+        let batch = self
+            .netted_batches
+            .get(&receiver_id)
+            .unwrap_or_else(|| env::panic_str("There is no pending batch"));
+        require!(
+            self.internal_netting_window_over(&receiver_id, &batch),
+            "The netting window isn't over"
+        );
+        self.internal_flush_netted_transfers(receiver_id)
+    }
+
+    /// Callback refunding the unused amounts of a batch to their senders, given the shares of
+    /// each transfer. A failed call or an invalid result refunds every transfer. Returns the
+    /// amount spent of each transfer.
+    #[private]
+    pub fn ft_resolve_netted_transfers(
+        &mut self,
+        receiver_id: AccountId,
+        transfers: Vec<(AccountId, U128)>,
+    ) -> Vec<U128> {
+        let unused_amounts = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<Vec<U128>>(&value)
+                    .ok()
+                    .filter(|unused_amounts| unused_amounts.len() == transfers.len())
+            }
+            PromiseResult::Failed => None,
+        };
+        if unused_amounts.is_none() {
+            self.metrics.failed_transfer_calls += 1;
+        }
+        transfers
+            .into_iter()
+            .enumerate()
+            .map(|(index, (sender_id, amount))| {
+                let unused_amount = unused_amounts.as_ref().map_or(amount.0, |unused_amounts| {
+                    Self::internal_to_shares(unused_amounts[index].0).min(amount.0)
+                });
+                let used_amount =
+                    self.internal_refund_unused(&sender_id, &receiver_id, amount.0, unused_amount);
+                U128(Self::internal_to_amount(used_amount))
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Internal method for adding a transfer call to the pending batch of a netting receiver,
+    /// notifying it of the previous batch first if its window is over or it's full.
+    pub(crate) fn internal_net_transfer_call(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: u128,
+        msg: String,
+    ) {
+        if let Some(batch) = self.netted_batches.get(&receiver_id) {
+            if self.internal_netting_window_over(&receiver_id, &batch)
+                || batch.transfers.len() >= MAX_NETTED_TRANSFERS
+            {
+                self.internal_flush_netted_transfers(receiver_id.clone());
+            }
+        }
+        let mut batch = self
+            .netted_batches
+            .get(&receiver_id)
+            .unwrap_or_else(|| NettedBatch {
+                opened_at: U64(env::block_height()),
+                transfers: vec![],
+            });
+        batch.transfers.push(NettedTransfer {
+            sender_id,
+            amount: U128(amount),
+            msg,
+        });
+        self.netted_batches.insert(&receiver_id, &batch);
+    }
+
+    fn internal_netting_window_over(&self, receiver_id: &AccountId, batch: &NettedBatch) -> bool {
+        self.netting_windows
+            .get(receiver_id)
+            .is_none_or(|window_blocks| env::block_height() >= batch.opened_at.0 + window_blocks)
+    }
+
+    fn internal_flush_netted_transfers(&mut self, receiver_id: AccountId) -> Promise {
+        let batch = self
+            .netted_batches
+            .remove(&receiver_id)
+            .unwrap_or_else(|| env::abort());
+        self.internal_promise_budget()
+            .call(GAS_FOR_FT_TRANSFER_BATCH_CALL, ZERO_TOKEN)
+            .call(GAS_FOR_RESOLVE_NETTED_TRANSFERS, ZERO_TOKEN)
+            .check();
+        let transfers = batch
+            .transfers
+            .iter()
+            .map(|transfer| (transfer.sender_id.clone(), transfer.amount))
+            .collect();
+        ext_ft_batch_receiver::ext(receiver_id.clone())
+            .with_static_gas(GAS_FOR_FT_TRANSFER_BATCH_CALL)
+            .ft_on_transfer_batch(Self::internal_netted_batch_amounts(batch).transfers)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_NETTED_TRANSFERS)
+                    .ft_resolve_netted_transfers(receiver_id, transfers),
+            )
+    }
+
+    // The transfers of a batch count in shares, and are shown in token amounts.
+    fn internal_netted_batch_amounts(mut batch: NettedBatch) -> NettedBatch {
+        for transfer in batch.transfers.iter_mut() {
+            transfer.amount = U128(Self::internal_to_amount(transfer.amount.0));
+        }
+        batch
+    }
+}
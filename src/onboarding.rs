@@ -0,0 +1,71 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, log, near_bindgen, require, NearSchema};
+
+use crate::signatures::ONBOARDING_DOMAIN;
+use crate::*;
+
+// The bundle a token holder signs off-chain to onboard a new account. A relayer submits it
+// together with the storage deposit, so that registering the receiver and sending it its
+// first tokens happen in a single transaction.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct OnboardingBundle {
+    /// The token holder funding the first transfer. Must have registered a relay key.
+    pub sender_id: AccountId,
+    /// The new account to register and fund.
+    pub receiver_id: AccountId,
+    /// The amount of tokens sent to the new account.
+    pub amount: U128,
+    pub memo: Option<String>,
+    /// Must be at least the sender's current relay nonce.
+    pub nonce: u64,
+    /// The timestamp (in nanoseconds) after which the bundle can't be used anymore.
+    pub expires_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Registers `bundle.receiver_id` with the attached deposit and transfers it `bundle.amount`
+    /// tokens from `bundle.sender_id`, who authorized the transfer by signing the bundle.
+    /// The caller (relayer) pays for the storage like `storage_deposit` does, and gets any
+    /// excess deposit back.
+    #[payable]
+    pub fn onboard(&mut self, bundle: OnboardingBundle, signature: Base64VecU8) {
+        self.internal_assert_not_paused(Pausable::Storage);
+        let relayer_id = env::predecessor_account_id();
+        require!(
+            !self.accounts.contains_key(&bundle.receiver_id),
+            "The account is already registered"
+        );
+
+        // Verify the holder's signature and consume the nonce so the bundle can't be replayed.
+        self.internal_verify_signed(
+            &bundle.sender_id,
+            ONBOARDING_DOMAIN,
+            &bundle,
+            bundle.nonce,
+            Some(bundle.expires_at.0),
+            &signature.0,
+        );
+
+        // Register the new account with the relayer's deposit and refund the excess.
+        self.internal_storage_register(&bundle.receiver_id, env::attached_deposit());
+
+        self.internal_transfer_with_fee(
+            &bundle.sender_id,
+            &bundle.receiver_id,
+            Self::internal_to_shares(bundle.amount.0),
+            bundle.memo.as_deref(),
+        );
+        log!(
+            "Account {} onboarded by {} through relayer {}",
+            bundle.receiver_id,
+            bundle.sender_id,
+            relayer_id
+        );
+        self.internal_record_relayer_gas(&relayer_id);
+    }
+}
@@ -0,0 +1,195 @@
+//! Price feeds aggregated from several oracles.
+//!
+//! A single oracle account is a single point of failure for the features valuing something in
+//! tokens. The owner instead configures a feed with a set of oracles, each pushing its own
+//! answer with `push_oracle_price`. The price of the feed is the median of the answers not older
+//! than `max_age_sec`, and only exists while at least `min_answers` of them are that fresh. An
+//! answer diverging from the median of the other fresh answers by more than
+//! `max_divergence_bps` is still recorded, the median ignoring outliers, but logs an
+//! `oracle_divergence` event for the monitoring to pick up.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::internal::pro_rata;
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// The most oracles a feed can have.
+pub const MAX_FEED_ORACLES: usize = 16;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceFeedConfig {
+    pub oracle_ids: Vec<AccountId>,
+    /// How many fresh answers the feed needs to have a price.
+    pub min_answers: u32,
+    /// How long an answer counts for, in seconds.
+    pub max_age_sec: u64,
+    /// How far an answer can be from the median of the others before it's reported, in basis
+    /// points of the median.
+    pub max_divergence_bps: u32,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct OracleAnswer {
+    pub oracle_id: AccountId,
+    pub price: U128,
+    /// The timestamp (in nanoseconds) when the oracle pushed the price.
+    pub updated_at: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceFeed {
+    pub config: PriceFeedConfig,
+    /// The latest answer of every oracle which pushed one.
+    pub answers: Vec<OracleAnswer>,
+}
+
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AggregatedPrice {
+    /// The median of the fresh answers.
+    pub price: U128,
+    /// The timestamp (in nanoseconds) of the oldest fresh answer.
+    pub updated_at: U64,
+    /// The number of fresh answers.
+    pub answers: u32,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Configures the feed `feed_id`, dropping its answers, or removes it when `None` is passed
+    /// (owner only).
+    #[payable]
+    pub fn set_price_feed(&mut self, feed_id: String, config: Option<PriceFeedConfig>) {
+        assert_one_yocto();
+        self.assert_owner();
+        let Some(config) = config else {
+            self.price_feeds.remove(&feed_id);
+            return;
+        };
+        require!(
+            !config.oracle_ids.is_empty() && config.oracle_ids.len() <= MAX_FEED_ORACLES,
+            format!("A feed should have 1 to {} oracles", MAX_FEED_ORACLES)
+        );
+        require!(
+            config
+                .oracle_ids
+                .iter()
+                .enumerate()
+                .all(|(i, oracle_id)| !config.oracle_ids[..i].contains(oracle_id)),
+            "The oracles should be different"
+        );
+        require!(
+            config.min_answers > 0 && config.min_answers as usize <= config.oracle_ids.len(),
+            "The minimum answers should be between 1 and the number of oracles"
+        );
+        self.price_feeds.insert(
+            &feed_id,
+            &PriceFeed {
+                config,
+                answers: vec![],
+            },
+        );
+    }
+
+    /// Pushes the caller's answer to the feed `feed_id` (its oracles only).
+    pub fn push_oracle_price(&mut self, feed_id: String, price: U128) {
+        let mut feed = self
+            .price_feeds
+            .get(&feed_id)
+            .unwrap_or_else(|| env::panic_str("The price feed doesn't exist"));
+        let oracle_id = env::predecessor_account_id();
+        require!(
+            feed.config.oracle_ids.contains(&oracle_id),
+            "Only the oracles of the feed can call this method"
+        );
+        require!(price.0 > 0, "The price should be positive");
+
+        feed.answers.retain(|answer| answer.oracle_id != oracle_id);
+        let others = Self::internal_fresh_answers(&feed);
+        if let Some(median) = Self::internal_median(&others) {
+            let divergence = price.0.abs_diff(median);
+            if divergence
+                > pro_rata(
+                    median,
+                    feed.config.max_divergence_bps.into(),
+                    BPS_DENOMINATOR,
+                )
+            {
+                OracleDivergence {
+                    feed_id: &feed_id,
+                    oracle_id: &oracle_id,
+                    price: &price,
+                    median: &U128(median),
+                }
+                .emit();
+            }
+        }
+        feed.answers.push(OracleAnswer {
+            oracle_id,
+            price,
+            updated_at: U64(env::block_timestamp()),
+        });
+        self.price_feeds.insert(&feed_id, &feed);
+    }
+
+    /// Returns a feed along with the latest answers of its oracles, or `null` if it doesn't
+    /// exist.
+    pub fn get_price_feed(&self, feed_id: String) -> Option<PriceFeed> {
+        self.price_feeds.get(&feed_id)
+    }
+
+    /// Returns the price of a feed, or `null` if it doesn't have enough fresh answers.
+    pub fn get_oracle_price(&self, feed_id: String) -> Option<AggregatedPrice> {
+        self.internal_oracle_price(&feed_id)
+    }
+}
+
+impl Contract {
+    /// Internal method for aggregating the fresh answers of a feed, if there are enough.
+    pub(crate) fn internal_oracle_price(&self, feed_id: &str) -> Option<AggregatedPrice> {
+        let feed = self.price_feeds.get(&feed_id.to_string())?;
+        let answers = Self::internal_fresh_answers(&feed);
+        if answers.len() < feed.config.min_answers as usize {
+            return None;
+        }
+        Some(AggregatedPrice {
+            price: U128(Self::internal_median(&answers)?),
+            updated_at: U64(answers.iter().map(|answer| answer.updated_at.0).min()?),
+            answers: answers.len() as u32,
+        })
+    }
+
+    /// Internal method for getting the answers of a feed still counting.
+    fn internal_fresh_answers(feed: &PriceFeed) -> Vec<&OracleAnswer> {
+        let max_age = feed.config.max_age_sec.saturating_mul(NANOS_PER_SECOND);
+        feed.answers
+            .iter()
+            .filter(|answer| env::block_timestamp() <= answer.updated_at.0.saturating_add(max_age))
+            .collect()
+    }
+
+    /// Internal method for computing the median price of answers, averaging the two middle
+    /// ones of an even count.
+    fn internal_median(answers: &[&OracleAnswer]) -> Option<u128> {
+        let mut prices: Vec<u128> = answers.iter().map(|answer| answer.price.0).collect();
+        prices.sort_unstable();
+        let middle = prices.len() / 2;
+        match prices.len() {
+            0 => None,
+            len if len % 2 == 1 => Some(prices[middle]),
+            // Sorted, so the difference can't underflow, nor the sum overflow.
+            _ => Some(prices[middle - 1] + (prices[middle] - prices[middle - 1]) / 2),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, NearSchema, NearToken};
+
+use crate::versioned::versioned_record;
+use crate::*;
+
+pub type IntentId = u64;
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentIntentStatus {
+    Pending,
+    Fulfilled { payer_id: AccountId, at: U64 },
+    Cancelled,
+}
+
+// A request for payment created by a merchant (point of sale), to be paid by whoever scans it.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PaymentIntent {
+    /// The account that created the intent and can cancel it.
+    pub creator_id: AccountId,
+    /// The account receiving the payment.
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub memo: Option<String>,
+    /// The timestamp (in nanoseconds) after which the intent can't be fulfilled anymore.
+    pub expires_at: U64,
+    pub status: PaymentIntentStatus,
+}
+
+versioned_record!(VersionedPaymentIntent, PaymentIntent);
+
+// The intent along with its id and the URI to encode in a QR code or a deep link.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PaymentIntentView {
+    pub intent_id: IntentId,
+    pub uri: String,
+    #[serde(flatten)]
+    pub intent: PaymentIntent,
+    pub expired: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creates an intent to receive `amount` tokens on `receiver` (the caller by default)
+    /// before `expiry`. The attached deposit must cover the storage of the intent; the rest of
+    /// it is refunded.
+    #[payable]
+    pub fn create_payment_intent(
+        &mut self,
+        amount: U128,
+        receiver: Option<AccountId>,
+        expiry: U64,
+        memo: Option<String>,
+    ) -> PaymentIntentView {
+        let initial_storage_usage = env::storage_usage();
+        let creator_id = env::predecessor_account_id();
+        let receiver_id = receiver.unwrap_or_else(|| creator_id.clone());
+        self.internal_unwrap_balance_of(&receiver_id);
+        require!(amount.0 > 0, "The amount should be a positive number");
+        require!(
+            expiry.0 > env::block_timestamp(),
+            "The expiry should be in the future"
+        );
+
+        let intent_id = self.next_payment_intent_id;
+        self.next_payment_intent_id += 1;
+        let intent = PaymentIntent {
+            creator_id,
+            receiver_id,
+            amount,
+            memo,
+            expires_at: expiry,
+            status: PaymentIntentStatus::Pending,
+        };
+        self.payment_intents.insert(&intent_id, &intent);
+        Self::internal_charge_storage(initial_storage_usage);
+        Self::internal_payment_intent_view(intent_id, intent)
+    }
+
+    /// Pays a pending intent from the caller's balance. The attached deposit (at least 1
+    /// yoctoNEAR) must cover the storage of the payer recorded in the intent; the rest of it is
+    /// refunded.
+    #[payable]
+    pub fn fulfill_intent(&mut self, intent_id: IntentId) {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let mut intent = self.internal_get_payment_intent(intent_id);
+        require!(
+            intent.status == PaymentIntentStatus::Pending,
+            "The payment intent is not pending"
+        );
+        require!(
+            env::block_timestamp() <= intent.expires_at.0,
+            "The payment intent has expired"
+        );
+
+        let payer_id = env::predecessor_account_id();
+        let memo = intent
+            .memo
+            .clone()
+            .unwrap_or_else(|| format!("Payment intent {}", intent_id));
+        self.internal_transfer_with_fee(
+            &payer_id,
+            &intent.receiver_id,
+            Self::internal_to_shares(intent.amount.0),
+            Some(&memo),
+        );
+
+        intent.status = PaymentIntentStatus::Fulfilled {
+            payer_id,
+            at: U64(env::block_timestamp()),
+        };
+        self.payment_intents.insert(&intent_id, &intent);
+        Self::internal_charge_storage(initial_storage_usage);
+    }
+
+    /// Cancels a pending intent. Only its creator can cancel it.
+    pub fn cancel_payment_intent(&mut self, intent_id: IntentId) {
+        let mut intent = self.internal_get_payment_intent(intent_id);
+        require!(
+            env::predecessor_account_id() == intent.creator_id,
+            "Only the creator can cancel the payment intent"
+        );
+        require!(
+            intent.status == PaymentIntentStatus::Pending,
+            "The payment intent is not pending"
+        );
+        intent.status = PaymentIntentStatus::Cancelled;
+        self.payment_intents.insert(&intent_id, &intent);
+    }
+
+    /// Returns an intent and its status, or `null` if it doesn't exist.
+    pub fn get_payment_intent(&self, intent_id: IntentId) -> Option<PaymentIntentView> {
+        self.payment_intents
+            .get(&intent_id)
+            .map(|intent| Self::internal_payment_intent_view(intent_id, intent))
+    }
+}
+
+impl Contract {
+    /// Internal method for getting a payment intent, panicking if it doesn't exist.
+    pub(crate) fn internal_get_payment_intent(&self, intent_id: IntentId) -> PaymentIntent {
+        self.payment_intents
+            .get(&intent_id)
+            .unwrap_or_else(|| env::panic_str("The payment intent doesn't exist"))
+    }
+
+    fn internal_payment_intent_view(
+        intent_id: IntentId,
+        intent: PaymentIntent,
+    ) -> PaymentIntentView {
+        PaymentIntentView {
+            intent_id,
+            uri: format!(
+                "near:{}/fulfill_intent?intent_id={}&amount={}",
+                env::current_account_id(),
+                intent_id,
+                intent.amount.0
+            ),
+            expired: intent.status == PaymentIntentStatus::Pending
+                && env::block_timestamp() > intent.expires_at.0,
+            intent,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentKeyType {
+    /// Verification key for signed payment requests.
+    Ed25519,
+    /// Verification key for signed payment requests (uncompressed, without the prefix byte).
+    Secp256k1,
+    /// Encryption key, e.g. for sealing memos.
+    X25519,
+}
+
+impl PaymentKeyType {
+    fn key_len(&self) -> usize {
+        match self {
+            PaymentKeyType::Ed25519 | PaymentKeyType::X25519 => 32,
+            PaymentKeyType::Secp256k1 => 64,
+        }
+    }
+}
+
+// A public key a holder publishes so that wallets and payment tools can discover how to
+// encrypt data for it or verify the payment requests it signs.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PaymentPubkey {
+    pub key_type: PaymentKeyType,
+    pub key: Base64VecU8,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Publishes the caller's payment key, or removes it when `None` is passed. The attached
+    /// deposit must cover the storage of the key, the rest of it is refunded, and the storage is
+    /// refunded when the key is removed.
+    #[payable]
+    pub fn register_payment_pubkey(&mut self, pk: Option<PaymentPubkey>) {
+        let account_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&account_id);
+        let initial_storage_usage = env::storage_usage();
+        match pk {
+            Some(pk) => {
+                require!(
+                    pk.key.0.len() == pk.key_type.key_len(),
+                    "Invalid key length for the key type"
+                );
+                self.payment_pubkeys.insert(&account_id, &pk);
+            }
+            None => {
+                self.payment_pubkeys.remove(&account_id);
+            }
+        }
+        Self::internal_settle_storage(&account_id, initial_storage_usage);
+    }
+
+    /// Returns the payment key of an account, if any.
+    pub fn get_payment_pubkey(&self, account_id: AccountId) -> Option<PaymentPubkey> {
+        self.payment_pubkeys.get(&account_id)
+    }
+}
+
+impl Contract {
+    /// Internal method for getting the key memos for an account are sealed with: its memo key,
+    /// or else its x25519 payment key.
+    pub(crate) fn internal_memo_key(&self, account_id: &AccountId) -> Option<Vec<u8>> {
+        self.memo_keys.get(account_id).or_else(|| {
+            self.payment_pubkeys
+                .get(account_id)
+                .filter(|pk| pk.key_type == PaymentKeyType::X25519)
+                .map(|pk| pk.key.0)
+        })
+    }
+}
@@ -0,0 +1,71 @@
+//! Transfers authorized by signed permits.
+//!
+//! A permit is signed off-chain with an ed25519 key and moves tokens out of the implicit account
+//! of that key, the hex encoding of its 32 bytes. Any relayer can submit it and pay the gas, so
+//! holders that never had NEAR can still spend their tokens. Permits are verified like the other
+//! signed messages: they consume the relay nonce of the implicit account, so a permit can't be
+//! replayed.
+
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::{env, near_bindgen, require, CurveType, PublicKey};
+
+use crate::signatures::PERMIT_DOMAIN;
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers `amount` tokens from the implicit account of `owner_pk` to `receiver_id`. The
+    /// owner authorized the transfer by signing `(owner_id, receiver_id, amount, nonce,
+    /// deadline)`, `deadline` being the timestamp (in nanoseconds) after which the permit can't
+    /// be used anymore. The caller (relayer) pays for the gas.
+    pub fn ft_transfer_with_permit(
+        &mut self,
+        owner_pk: PublicKey,
+        signature: Base64VecU8,
+        nonce: u64,
+        deadline: U64,
+        receiver_id: AccountId,
+        amount: U128,
+    ) {
+        let owner_id = Self::internal_implicit_account_id(&owner_pk);
+        self.internal_verify_signed_with_key(
+            &owner_id,
+            &owner_pk,
+            PERMIT_DOMAIN,
+            &(&owner_id, &receiver_id, amount.0, nonce, deadline.0),
+            nonce,
+            Some(deadline.0),
+            &signature.0,
+        );
+
+        let amount = Self::internal_to_shares(amount.0);
+        // The relayer can't wait for a co-signer's confirmation on the owner's behalf.
+        require!(
+            self.internal_co_signer_required(&owner_id, amount)
+                .is_none(),
+            "The transfer needs the co-signer's confirmation"
+        );
+        self.internal_transfer_with_fee(&owner_id, &receiver_id, amount, Some("Permit"));
+        self.internal_record_relayer_gas(&env::predecessor_account_id());
+    }
+
+    /// Returns the lowest nonce the next permit of an account can use, which is its relay nonce.
+    pub fn get_permit_nonce(&self, account_id: AccountId) -> u64 {
+        self.get_relay_nonce(account_id)
+    }
+}
+
+impl Contract {
+    /// Internal method for getting the implicit account of an ed25519 key.
+    fn internal_implicit_account_id(public_key: &PublicKey) -> AccountId {
+        require!(
+            public_key.curve_type() == CurveType::ED25519,
+            "Only ed25519 permits are supported"
+        );
+        let hex: String = public_key.as_bytes()[1..]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        hex.parse().unwrap_or_else(|_| env::abort())
+    }
+}
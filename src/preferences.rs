@@ -0,0 +1,78 @@
+//! Per-account preferences.
+//!
+//! The opt-in behaviors an account can ask for (holding incoming transfers until they're
+//! claimed, rounding up its transfers, requiring a memo, ...) along with its notification,
+//! privacy and limit settings are stored in a single record, set with `set_preferences`. The
+//! contract only stores them: wallets and off-chain services read them with `get_preferences`,
+//! and the features honoring them from `account_preferences`.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::versioned::versioned_record;
+use crate::*;
+
+/// The maximum size of the preferences of an account, in bytes
+pub const MAX_PREFERENCES_LEN: usize = 512;
+
+// The preferences of an account. Every field is optional in the JSON, so that callers only
+// pass the settings they use.
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default, Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountPreferences {
+    /// Whether incoming transfers should be held until the account claims them.
+    #[serde(default)]
+    pub claim_required: bool,
+    /// Whether the account's transfers should be rounded up, setting the difference aside.
+    #[serde(default)]
+    pub round_up: bool,
+    /// Whether incoming transfers should carry a memo.
+    #[serde(default)]
+    pub memo_required: bool,
+    /// The URL off-chain services should notify the account's transfers to, if any.
+    #[serde(default)]
+    pub notification_url: Option<String>,
+    /// Whether the account asks to be left out of the public listings, like the burn
+    /// leaderboard.
+    #[serde(default)]
+    pub hide_from_listings: bool,
+    /// The amount above which wallets should ask for a confirmation of a transfer, if any.
+    #[serde(default)]
+    pub transfer_limit: Option<U128>,
+}
+
+versioned_record!(VersionedAccountPreferences, AccountPreferences);
+
+#[near_bindgen]
+impl Contract {
+    /// Replaces the caller's preferences, or removes them when `None` is passed. The caller
+    /// must be registered.
+    #[payable]
+    pub fn set_preferences(&mut self, preferences: Option<AccountPreferences>) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&account_id);
+        match preferences {
+            Some(preferences) => {
+                require!(
+                    borsh::to_vec(&preferences).unwrap().len() <= MAX_PREFERENCES_LEN,
+                    "The preferences are too large"
+                );
+                self.account_preferences.insert(&account_id, &preferences);
+            }
+            None => {
+                self.account_preferences.remove(&account_id);
+            }
+        }
+    }
+
+    /// Returns the preferences of an account, or `None` if it hasn't set any.
+    pub fn get_preferences(&self, account_id: AccountId) -> Option<AccountPreferences> {
+        self.account_preferences.get(&account_id)
+    }
+}
@@ -0,0 +1,204 @@
+//! Peg stability module.
+//!
+//! The PSM swaps a whitelisted reference token (e.g. a bridged stablecoin) 1:1 for this token,
+//! minus a small fee, which keeps the token at parity with it. Users swap in by sending the
+//! reference token with `ft_transfer_call` and the `"psm_swap"` message: the PSM keeps it in its
+//! reserves and mints this token in exchange, up to the debt ceiling. They swap out with
+//! `psm_redeem`, which burns this token and sends the reference token back out of the reserves. Amounts are converted between
+//! the decimals of both tokens, rounding down, and the fees stay in the reserves.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_one_yocto, env, is_promise_success, near_bindgen, require, Gas, NearSchema, Promise,
+};
+
+use crate::decimals::{scale_from_bridged, scale_to_bridged};
+use crate::ft_core::ext_ft_core;
+use crate::internal::pro_rata;
+use crate::*;
+
+/// The highest swap fee the owner can set, in basis points (1%).
+pub const MAX_PSM_FEE_BPS: u16 = 100;
+const GAS_FOR_PSM_REDEMPTION: Gas = Gas::from_tgas(10);
+const GAS_FOR_RESOLVE_PSM_REDEMPTION: Gas = Gas::from_tgas(5);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PsmConfig {
+    /// The token swapped 1:1 for this one.
+    pub reference_token_id: AccountId,
+    /// The decimals of the reference token, at most the decimals of this token.
+    pub reference_decimals: u8,
+    /// The fee of both swap directions, in basis points of the swapped amount.
+    pub fee_bps: u16,
+    /// The most tokens the PSM can have minted and not redeemed at once.
+    pub debt_ceiling: U128,
+}
+
+// The state of the peg stability module.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PsmView {
+    pub config: Option<PsmConfig>,
+    /// The reference tokens held, in their smallest unit.
+    pub reserves: U128,
+    /// The tokens minted and not redeemed yet.
+    pub debt: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Turns the PSM on with the given configuration, or off when `None` is passed (owner only).
+    /// The reference token can't change while the PSM holds reserves.
+    #[payable]
+    pub fn set_psm_config(&mut self, config: Option<PsmConfig>) {
+        assert_one_yocto();
+        self.assert_owner();
+        if let Some(config) = &config {
+            self.internal_assert_not_wrapped();
+            require!(config.fee_bps <= MAX_PSM_FEE_BPS, "The PSM fee is too high");
+            require!(
+                config.reference_decimals <= self.internal_decimals(),
+                "The reference token can't have more decimals than the token"
+            );
+        }
+        let same_reference = match (&self.psm_config, &config) {
+            (Some(current), Some(config)) => {
+                current.reference_token_id == config.reference_token_id
+                    && current.reference_decimals == config.reference_decimals
+            }
+            _ => false,
+        };
+        require!(
+            self.psm_reserves == 0 || same_reference,
+            "The PSM still holds reserves of its reference token"
+        );
+        self.psm_config = config;
+    }
+
+    /// Burns `amount` of the caller's tokens and sends them back in reference tokens, minus the
+    /// fee.
+    #[payable]
+    pub fn psm_redeem(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        let config = self.internal_psm_config();
+        let account_id = env::predecessor_account_id();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        require!(
+            amount.0 <= self.psm_debt,
+            "The redemption exceeds the PSM debt"
+        );
+        let fee = pro_rata(amount.0, config.fee_bps.into(), BPS_DENOMINATOR);
+        let (out, _) = scale_to_bridged(
+            amount.0 - fee,
+            self.internal_decimals(),
+            config.reference_decimals,
+        );
+        require!(out > 0, "The redemption is too small");
+        require!(out <= self.psm_reserves, "The PSM reserves are too low");
+        self.internal_promise_budget()
+            .call(GAS_FOR_PSM_REDEMPTION, NearToken::from_yoctonear(1))
+            .call(GAS_FOR_RESOLVE_PSM_REDEMPTION, ZERO_TOKEN)
+            .check();
+
+        self.internal_burn(
+            &account_id,
+            Self::internal_to_shares(amount.0),
+            Some("PSM redemption"),
+        );
+        self.psm_debt -= amount.0;
+        self.psm_reserves -= out;
+        ext_ft_core::ext(config.reference_token_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_PSM_REDEMPTION)
+            .ft_transfer(
+                account_id.clone(),
+                U128(out),
+                Some("PSM redemption".to_string()),
+                None,
+                None,
+                None,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_RESOLVE_PSM_REDEMPTION)
+                    .psm_resolve_redeem(account_id, amount, U128(out)),
+            )
+    }
+
+    /// Restores the burned tokens and the reserves if the reference tokens couldn't be sent.
+    #[private]
+    pub fn psm_resolve_redeem(&mut self, account_id: AccountId, amount: U128, out: U128) -> bool {
+        if is_promise_success() {
+            return true;
+        }
+        self.psm_reserves += out.0;
+        // If the account unregistered in the meantime, its tokens stay burned.
+        if self.accounts.contains_key(&account_id) {
+            self.psm_debt += amount.0;
+            self.internal_mint(
+                &account_id,
+                Self::internal_to_shares(amount.0),
+                Some("PSM redemption refund"),
+            );
+        }
+        false
+    }
+
+    /// Returns the configuration, the reserves and the debt of the PSM.
+    pub fn get_psm(&self) -> PsmView {
+        PsmView {
+            config: self.psm_config.clone(),
+            reserves: U128(self.psm_reserves),
+            debt: U128(self.psm_debt),
+        }
+    }
+}
+
+impl Contract {
+    /// Internal method for swapping `amount` reference tokens sent by `sender_id` for this
+    /// token, minus the fee.
+    pub(crate) fn internal_psm_swap(
+        &mut self,
+        token_id: &AccountId,
+        sender_id: &AccountId,
+        amount: U128,
+    ) {
+        let config = self.internal_psm_config();
+        require!(
+            token_id == &config.reference_token_id,
+            "The PSM doesn't accept this token"
+        );
+        self.internal_assert_not_paused(Pausable::Mints);
+        self.internal_assert_not_wrapped();
+        self.internal_unwrap_balance_of(sender_id);
+        let scaled = scale_from_bridged(
+            amount.0,
+            self.internal_decimals(),
+            config.reference_decimals,
+        );
+        let minted = scaled - pro_rata(scaled, config.fee_bps.into(), BPS_DENOMINATOR);
+        require!(
+            self.psm_debt + minted <= config.debt_ceiling.0,
+            "The swap exceeds the PSM debt ceiling"
+        );
+        self.psm_reserves += amount.0;
+        self.psm_debt += minted;
+        if minted > 0 {
+            self.internal_mint(
+                sender_id,
+                Self::internal_to_shares(minted),
+                Some("PSM swap"),
+            );
+        }
+    }
+
+    fn internal_psm_config(&self) -> PsmConfig {
+        self.psm_config
+            .clone()
+            .unwrap_or_else(|| env::panic_str("The PSM is off"))
+    }
+}
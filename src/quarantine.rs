@@ -0,0 +1,116 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::internal::pro_rata;
+use crate::*;
+
+const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+/// The number of days a renamed token stays in quarantine.
+const RENAME_QUARANTINE_DAYS: u64 = 7;
+/// During a quarantine, transfers of more than this share of the total supply are large.
+const QUARANTINE_LARGE_TRANSFER_BPS: u128 = 100;
+
+// The quarantine a token enters when its name or symbol changes, so that wallets can warn their
+// users about a possible rug-style rebrand. Until `ends_at`, each account can only make one
+// large transfer per day.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct RenameQuarantine {
+    /// The name of the token before the quarantine started.
+    pub previous_name: String,
+    /// The symbol of the token before the quarantine started.
+    pub previous_symbol: String,
+    /// The timestamp (in nanoseconds) of the latest rename.
+    pub started_at: U64,
+    /// The timestamp (in nanoseconds) at which the quarantine ends.
+    pub ends_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Renames the token, keeping the current name or symbol when `None` is passed. A change
+    /// puts the token in quarantine for 7 days, or extends the ongoing quarantine.
+    #[payable]
+    pub fn rename_token(&mut self, name: Option<String>, symbol: Option<String>) {
+        assert_one_yocto();
+        self.assert_role(Role::MetadataAdmin);
+        let mut metadata = self.metadata.get().unwrap();
+        let name = name.unwrap_or_else(|| metadata.name.clone());
+        let symbol = symbol.unwrap_or_else(|| metadata.symbol.clone());
+        require!(
+            !name.is_empty() && !symbol.is_empty(),
+            "The name and symbol shouldn't be empty"
+        );
+        if name == metadata.name && symbol == metadata.symbol {
+            return;
+        }
+
+        // A rename during the quarantine keeps the names the token had before it started.
+        let now = env::block_timestamp();
+        let (previous_name, previous_symbol) = match self.internal_active_rename_quarantine(now) {
+            Some(quarantine) => (quarantine.previous_name, quarantine.previous_symbol),
+            None => (metadata.name.clone(), metadata.symbol.clone()),
+        };
+        self.rename_quarantine.set(&RenameQuarantine {
+            previous_name,
+            previous_symbol,
+            started_at: U64(now),
+            ends_at: U64(now + RENAME_QUARANTINE_DAYS * NANOS_PER_DAY),
+        });
+        let updated_fields: Vec<&str> = [
+            ("name", name != metadata.name),
+            ("symbol", symbol != metadata.symbol),
+        ]
+        .into_iter()
+        .filter_map(|(field, updated)| updated.then_some(field))
+        .collect();
+        metadata.name = name;
+        metadata.symbol = symbol;
+        self.metadata.set(&metadata);
+        MetadataUpdated {
+            updated_fields: &updated_fields,
+        }
+        .emit();
+    }
+}
+
+impl Contract {
+    /// Internal method for getting the quarantine the token is in at `now`, if any.
+    pub(crate) fn internal_active_rename_quarantine(&self, now: u64) -> Option<RenameQuarantine> {
+        self.rename_quarantine
+            .get()
+            .filter(|quarantine| now < quarantine.ends_at.0)
+    }
+
+    /// Internal method for rate-limiting the large transfers of `sender_id` while the token is
+    /// in quarantine, panicking if the sender already made one in the last day.
+    pub(crate) fn internal_assert_rename_quarantine(
+        &mut self,
+        sender_id: &AccountId,
+        amount: u128,
+    ) {
+        let now = env::block_timestamp();
+        let Some(quarantine) = self.internal_active_rename_quarantine(now) else {
+            return;
+        };
+        let large_transfer = pro_rata(
+            self.total_supply,
+            QUARANTINE_LARGE_TRANSFER_BPS,
+            BPS_DENOMINATOR,
+        );
+        if amount <= large_transfer {
+            return;
+        }
+        // Transfers made before the latest rename don't count.
+        if let Some(last) = self.quarantine_large_transfers.get(sender_id) {
+            require!(
+                last < quarantine.started_at.0 || now >= last + NANOS_PER_DAY,
+                "The token was renamed recently: only one large transfer per day is allowed"
+            );
+        }
+        self.quarantine_large_transfers.insert(sender_id, &now);
+    }
+}
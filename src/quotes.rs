@@ -0,0 +1,131 @@
+//! Fee quotes.
+//!
+//! The fee of a transfer depends on the fee switch, the counterparty categories and the
+//! tokenomics, which the admins can change at any time, so the cost an integrator displays may
+//! drift before the transfer executes. A sender instead asks for a quote with `quote_transfer`,
+//! which locks in the fee and the amount credited to the receiver for `FEE_QUOTE_VALIDITY_BLOCKS`
+//! blocks, and executes it with `ft_transfer_quoted`. The transfer then charges exactly the
+//! quoted terms, or reverts if they changed or the quote expired. A sender holds a single quote:
+//! asking for a new one replaces it, so the quote id tells which terms are being executed.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema, NearToken};
+
+use crate::*;
+
+/// The number of blocks a fee quote stays valid for.
+pub const FEE_QUOTE_VALIDITY_BLOCKS: u64 = 100;
+
+pub type FeeQuoteId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeQuote {
+    pub quote_id: U64,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    /// The fee debited from the sender on top of the amount.
+    pub fee: U128,
+    /// The amount credited to the receiver, once the burn set by the tokenomics is deducted.
+    pub received: U128,
+    /// The last block height at which the quote can be executed.
+    pub valid_until_block: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Quotes a transfer of `amount` tokens from the caller to `receiver_id`, replacing the
+    /// caller's previous quote. The caller must be registered, and pays for the storage of the
+    /// quote, which is refunded once it's executed.
+    #[payable]
+    pub fn quote_transfer(&mut self, receiver_id: AccountId, amount: U128) -> FeeQuote {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(amount.0 > 0, "The amount should be a positive number");
+        self.internal_unwrap_balance_of(&sender_id);
+
+        let quote_id = self.next_fee_quote_id;
+        self.next_fee_quote_id += 1;
+        let (fee, received) = self.internal_quote_terms(&sender_id, &receiver_id, amount.0);
+        let quote = FeeQuote {
+            quote_id: U64(quote_id),
+            fee,
+            received,
+            receiver_id,
+            amount,
+            valid_until_block: U64(env::block_height() + FEE_QUOTE_VALIDITY_BLOCKS),
+        };
+        self.fee_quotes.insert(&sender_id, &quote);
+        Self::internal_settle_storage(&sender_id, initial_storage_usage);
+        quote
+    }
+
+    /// Executes the caller's quote `quote_id`, charging exactly the quoted fee. Panics if the
+    /// quote expired, was replaced, or its terms can't be honored anymore.
+    #[payable]
+    pub fn ft_transfer_quoted(&mut self, quote_id: U64, memo: Option<String>) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        let initial_storage_usage = env::storage_usage();
+        let quote = self
+            .fee_quotes
+            .remove(&sender_id)
+            .filter(|quote| quote.quote_id == quote_id)
+            .unwrap_or_else(|| env::panic_str("The quote doesn't exist"));
+        Self::internal_refund_storage(&sender_id, initial_storage_usage);
+        require!(
+            env::block_height() <= quote.valid_until_block.0,
+            "The quote has expired"
+        );
+        require!(
+            self.internal_quote_terms(&sender_id, &quote.receiver_id, quote.amount.0)
+                == (quote.fee, quote.received),
+            "The quoted terms changed"
+        );
+        self.internal_assert_not_co_signed(&sender_id, quote.amount.0);
+        self.internal_ft_transfer(
+            quote.receiver_id,
+            quote.amount,
+            memo,
+            None,
+            Some(quote.received),
+            Some(FeeMode::SenderPays),
+        );
+    }
+
+    /// Returns the current quote of a sender, or `null` if it has none.
+    pub fn get_fee_quote(&self, sender_id: AccountId) -> Option<FeeQuote> {
+        self.fee_quotes.get(&sender_id)
+    }
+}
+
+impl Contract {
+    /// Internal method for computing the fee and the amount received of a transfer of `amount`
+    /// tokens, in tokens. A rebase changes them like any other fee parameter.
+    fn internal_quote_terms(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+    ) -> (U128, U128) {
+        let shares = Self::internal_to_shares(amount);
+        let fee = self.internal_transfer_fee(sender_id, receiver_id, shares, FeeMode::SenderPays);
+        let received =
+            self.internal_received_amount(sender_id, receiver_id, shares, FeeMode::SenderPays);
+        (
+            U128(Self::internal_to_amount(fee)),
+            U128(Self::internal_to_amount(received)),
+        )
+    }
+}
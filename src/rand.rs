@@ -0,0 +1,264 @@
+//! Commit-reveal randomness.
+//!
+//! A randomness round collects commitments `sha256(account_id || secret)` until `commit_ends_at`,
+//! then the secrets until `reveal_ends_at`. Once the reveal phase is over, anyone can finalize
+//! the round: its seed is `sha256(block_seed || xor of the revealed secrets)`, where
+//! `block_seed` is the random seed of the block the round is finalized in. A single honest
+//! participant is enough for the seed to be unpredictable to the others, and the block seed
+//! isn't known to the participants when they reveal. A participant can still withhold its
+//! secret once it has seen the others', choosing between two seeds, which is why the unrevealed
+//! commitments are counted in the round.
+//!
+//! `Rng` expands a seed into deterministic draws, for the features selecting accounts (raffles,
+//! fair distributions, keeper selection) to pick the same winners when replayed.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema, NearToken};
+
+use crate::versioned::versioned_record;
+use crate::*;
+
+pub type RandomnessRoundId = u64;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// Returns the commitment of `account_id` to `secret`.
+pub fn randomness_commitment(account_id: &AccountId, secret: &[u8]) -> [u8; 32] {
+    let mut preimage = account_id.as_bytes().to_vec();
+    preimage.extend_from_slice(secret);
+    env::sha256_array(&preimage)
+}
+
+// A commit-reveal round. The seed is set once the round is finalized.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct RandomnessRound {
+    /// The timestamp (in nanoseconds) until which commitments are accepted.
+    pub commit_ends_at: U64,
+    /// The timestamp (in nanoseconds) until which secrets can be revealed.
+    pub reveal_ends_at: U64,
+    /// The number of commitments.
+    pub commitments: u32,
+    /// The number of revealed secrets.
+    pub reveals: u32,
+    /// The xor of the revealed secrets.
+    pub entropy: Base64VecU8,
+    pub seed: Option<Base64VecU8>,
+}
+
+versioned_record!(VersionedRandomnessRound, RandomnessRound);
+
+#[near_bindgen]
+impl Contract {
+    /// Opens a randomness round taking commitments for `commit_duration_sec` seconds, then
+    /// reveals for `reveal_duration_sec` seconds (owner only).
+    #[payable]
+    pub fn create_randomness_round(
+        &mut self,
+        commit_duration_sec: u64,
+        reveal_duration_sec: u64,
+    ) -> RandomnessRoundId {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(
+            commit_duration_sec > 0 && reveal_duration_sec > 0,
+            "The commit and reveal phases should last"
+        );
+        let commit_ends_at = env::block_timestamp() + commit_duration_sec * NANOS_PER_SEC;
+        let round_id = self.next_randomness_round_id;
+        self.next_randomness_round_id += 1;
+        self.randomness_rounds.insert(
+            &round_id,
+            &RandomnessRound {
+                commit_ends_at: U64(commit_ends_at),
+                reveal_ends_at: U64(commit_ends_at + reveal_duration_sec * NANOS_PER_SEC),
+                commitments: 0,
+                reveals: 0,
+                entropy: Base64VecU8(vec![0; 32]),
+                seed: None,
+            },
+        );
+        round_id
+    }
+
+    /// Commits the caller to a secret, with `sha256(account_id || secret)` (see
+    /// `randomness_commitment`). The caller must be registered, and pays for the storage of the
+    /// commitment, which is refunded when the secret is revealed.
+    #[payable]
+    pub fn commit_randomness(&mut self, round_id: RandomnessRoundId, commitment: Base64VecU8) {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&account_id);
+        let mut round = self.internal_get_randomness_round(round_id);
+        require!(
+            env::block_timestamp() < round.commit_ends_at.0,
+            "The commit phase is over"
+        );
+        let commitment: [u8; 32] = commitment
+            .0
+            .try_into()
+            .unwrap_or_else(|_| env::panic_str("The commitment should be 32 bytes long"));
+        let key = (round_id, account_id);
+        require!(
+            self.randomness_commitments
+                .insert(&key, &commitment)
+                .is_none(),
+            "The account already committed"
+        );
+        round.commitments += 1;
+        self.randomness_rounds.insert(&round_id, &round);
+        Self::internal_charge_storage(initial_storage_usage);
+    }
+
+    /// Reveals the secret the caller committed to, mixing it into the round's entropy, and
+    /// refunds the storage of the commitment.
+    #[payable]
+    pub fn reveal_randomness(&mut self, round_id: RandomnessRoundId, secret: Base64VecU8) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut round = self.internal_get_randomness_round(round_id);
+        let now = env::block_timestamp();
+        require!(
+            round.commit_ends_at.0 <= now && now < round.reveal_ends_at.0,
+            "The round isn't in its reveal phase"
+        );
+        require!(secret.0.len() == 32, "The secret should be 32 bytes long");
+        let initial_storage_usage = env::storage_usage();
+        let key = (round_id, account_id);
+        let commitment = self
+            .randomness_commitments
+            .remove(&key)
+            .unwrap_or_else(|| env::panic_str("No commitment to reveal"));
+        Self::internal_refund_storage(&key.1, initial_storage_usage);
+        require!(
+            randomness_commitment(&key.1, &secret.0) == commitment,
+            "The secret doesn't match the commitment"
+        );
+        for (entropy, byte) in round.entropy.0.iter_mut().zip(secret.0) {
+            *entropy ^= byte;
+        }
+        round.reveals += 1;
+        self.randomness_rounds.insert(&round_id, &round);
+    }
+
+    /// Sets the seed of a round whose reveal phase is over, and returns it. Anyone can call it.
+    pub fn finalize_randomness_round(&mut self, round_id: RandomnessRoundId) -> Base64VecU8 {
+        self.internal_finalize_randomness_round(round_id)
+            .to_vec()
+            .into()
+    }
+
+    pub fn get_randomness_round(&self, round_id: RandomnessRoundId) -> Option<RandomnessRound> {
+        self.randomness_rounds.get(&round_id)
+    }
+}
+
+impl Contract {
+    /// Internal method for getting the seed of a round, finalizing it if needed.
+    pub(crate) fn internal_finalize_randomness_round(
+        &mut self,
+        round_id: RandomnessRoundId,
+    ) -> [u8; 32] {
+        let mut round = self.internal_get_randomness_round(round_id);
+        if let Some(seed) = round.seed {
+            return seed.0.try_into().unwrap();
+        }
+        require!(
+            round.reveal_ends_at.0 <= env::block_timestamp(),
+            "The reveal phase isn't over"
+        );
+        let mut preimage = env::random_seed_array().to_vec();
+        preimage.extend_from_slice(&round.entropy.0);
+        let seed = env::sha256_array(&preimage);
+        round.seed = Some(seed.to_vec().into());
+        self.randomness_rounds.insert(&round_id, &round);
+        seed
+    }
+
+    fn internal_get_randomness_round(&self, round_id: RandomnessRoundId) -> RandomnessRound {
+        self.randomness_rounds
+            .get(&round_id)
+            .unwrap_or_else(|| env::panic_str("Randomness round not found"))
+    }
+}
+
+/// Deterministic draws expanded from a seed: the `n`-th 64-bit word is the first 8 bytes of
+/// `sha256(seed || n)`.
+pub struct Rng {
+    seed: [u8; 32],
+    counter: u64,
+}
+
+impl Rng {
+    pub fn new(seed: [u8; 32]) -> Self {
+        Self { seed, counter: 0 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut preimage = self.seed.to_vec();
+        preimage.extend_from_slice(&self.counter.to_le_bytes());
+        self.counter += 1;
+        let hash = env::sha256_array(&preimage);
+        u64::from_le_bytes(hash[..8].try_into().unwrap())
+    }
+
+    /// Draws a number in `0..bound` without modulo bias: the words falling in the incomplete
+    /// last multiple of `bound` are rejected, so the draw takes two words or more with a
+    /// probability of `(2^64 mod bound) / 2^64`, below one half.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        require!(bound > 0, "The bound should be positive");
+        let rejected = bound.wrapping_neg() % bound;
+        loop {
+            let word = self.next_u64();
+            if word >= rejected {
+                return word % bound;
+            }
+        }
+    }
+
+    /// Draws an index with a probability proportional to its weight, e.g. the balances of the
+    /// candidates. The weights can't be all zero.
+    pub fn weighted_index(&mut self, weights: &[u128]) -> usize {
+        let total = weights
+            .iter()
+            .try_fold(0u128, |total, weight| total.checked_add(*weight))
+            .unwrap_or_else(|| env::panic_str("The weights overflow"));
+        require!(total > 0, "The weights can't be all zero");
+        let mut target = self.below_u128(total);
+        for (index, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                return index;
+            }
+            target -= weight;
+        }
+        unreachable!()
+    }
+
+    /// Shuffles `items` in place with the Fisher-Yates algorithm.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    fn below_u128(&mut self, bound: u128) -> u128 {
+        if let Ok(bound) = u64::try_from(bound) {
+            return self.below(bound) as u128;
+        }
+        let rejected = bound.wrapping_neg() % bound;
+        loop {
+            let word = (self.next_u64() as u128) << 64 | self.next_u64() as u128;
+            if word >= rejected {
+                return word % bound;
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Rebasing mode, compiled with the `rebase` feature.
+//!
+//! Staking derivatives and yield-bearing tokens grow every balance at once, which can't be done
+//! by writing each of them. In this mode, the balances and the total supply are stored as
+//! shares, and the token amounts are the shares times the index, which the owner or the rebase
+//! oracle moves with `rebase`. Every method taking or returning token amounts converts them at
+//! its boundary: `ft_balance_of`, `ft_total_supply`, the transfers of every kind (permits,
+//! allowances, escrows, reservations, scheduled and co-signed transfers, payment intents,
+//! onboarding bundles, locks, streams, netted batches and bridge transfers), the fee quotes,
+//! the mints, mint requests, wrapping and burns, the payouts of the treasury, bonds, mining,
+//! grants, airdrops, vouchers, bounties, insurance, the PSM and the stablecoin vaults, the
+//! shielded pool, the supply views and the amounts of the events. The records and the internal
+//! methods count in shares, so the tokens held in a pending record grow with the index like a
+//! balance. The thresholds and caps the admins and the accounts configure are compared with
+//! shares, except for `max_supply`, which caps the token amount of the total supply. The
+//! balances of a snapshot are converted at the index recorded when it was taken, and the
+//! pegged debts and historical totals are kept in token amounts.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LazyOption, LookupMap};
+use near_sdk::json_types::U128;
+use near_sdk::{assert_one_yocto, env, near_bindgen, require};
+
+use crate::*;
+
+/// The index at which a share is worth one token.
+pub const REBASE_INDEX_ONE: u128 = 1_000_000_000_000_000_000;
+
+/// The highest index, up to which the conversions can't overflow their intermediate products.
+pub const MAX_REBASE_INDEX: u128 = u128::MAX / REBASE_INDEX_ONE;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+struct RebaseState {
+    index: u128,
+    oracle_id: Option<AccountId>,
+}
+
+impl Default for RebaseState {
+    fn default() -> Self {
+        Self {
+            index: REBASE_INDEX_ONE,
+            oracle_id: None,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the account which can rebase besides the owner, or removes it when `None` is passed
+    /// (owner only).
+    #[payable]
+    pub fn set_rebase_oracle(&mut self, oracle_id: Option<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut state = Self::internal_rebase_state();
+        state.oracle_id = oracle_id;
+        Self::internal_save_rebase_state(&state);
+    }
+
+    /// Sets the index converting the shares into token amounts, where `REBASE_INDEX_ONE` is
+    /// one token per share (owner and rebase oracle only).
+    #[payable]
+    pub fn rebase(&mut self, new_index: U128) {
+        assert_one_yocto();
+        let mut state = Self::internal_rebase_state();
+        let caller_id = env::predecessor_account_id();
+        require!(
+            caller_id == self.owner_id || state.oracle_id.as_ref() == Some(&caller_id),
+            "Only the owner and the rebase oracle can call this method"
+        );
+        require!(
+            new_index.0 > 0 && new_index.0 <= MAX_REBASE_INDEX,
+            "The index should be positive and at most MAX_REBASE_INDEX"
+        );
+        let old_index = state.index;
+        state.index = new_index.0;
+        Self::internal_save_rebase_state(&state);
+        self.internal_record_supply();
+        Rebase {
+            old_index: &U128(old_index),
+            new_index: &new_index,
+            total_supply: &U128(Self::internal_to_amount(self.total_supply)),
+        }
+        .emit();
+    }
+
+    /// Returns the index converting the shares into token amounts.
+    pub fn get_rebase_index(&self) -> U128 {
+        U128(Self::internal_rebase_state().index)
+    }
+
+    /// Returns the account which can rebase besides the owner, if any.
+    pub fn get_rebase_oracle(&self) -> Option<AccountId> {
+        Self::internal_rebase_state().oracle_id
+    }
+
+    /// Returns the shares of an account.
+    pub fn shares_of(account_id: AccountId) -> U128 {
+        U128(Self::stateless_accounts().get(&account_id).unwrap_or(0))
+    }
+}
+
+impl Contract {
+    // The state lives under its own storage key instead of fields of `Contract`, so that the
+    // contract state has the same layout with or without the feature.
+    fn internal_rebase_state() -> RebaseState {
+        LazyOption::<RebaseState>::new(StorageKey::Rebase(RebaseKey::RebaseState), None)
+            .get()
+            .unwrap_or_default()
+    }
+
+    fn internal_save_rebase_state(state: &RebaseState) {
+        LazyOption::<RebaseState>::new(StorageKey::Rebase(RebaseKey::RebaseState), None).set(state);
+    }
+
+    /// Internal method for converting stored shares into token amounts, rounding down.
+    pub(crate) fn internal_to_amount(shares: u128) -> u128 {
+        let index = Self::internal_rebase_state().index;
+        checked_mul_div(shares, index, REBASE_INDEX_ONE)
+            .unwrap_or_else(|| env::panic_str("Rebase overflow"))
+    }
+
+    /// Internal method for converting token amounts into shares to store, rounding down.
+    pub(crate) fn internal_to_shares(amount: u128) -> u128 {
+        let index = Self::internal_rebase_state().index;
+        checked_mul_div(amount, REBASE_INDEX_ONE, index)
+            .unwrap_or_else(|| env::panic_str("Rebase overflow"))
+    }
+
+    /// Internal method for keeping the current index along with a snapshot, for its balances
+    /// to be converted at the index they were taken at.
+    pub(crate) fn internal_record_snapshot_index(snapshot_id: SnapshotId) {
+        let index = Self::internal_rebase_state().index;
+        Self::internal_snapshot_indexes().insert(&snapshot_id, &index);
+    }
+
+    /// Internal method for converting the shares of a snapshot into token amounts at the index
+    /// of the snapshot, rounding down.
+    pub(crate) fn internal_to_amount_at(shares: u128, snapshot_id: SnapshotId) -> u128 {
+        let index = Self::internal_snapshot_indexes()
+            .get(&snapshot_id)
+            .unwrap_or_else(|| Self::internal_rebase_state().index);
+        checked_mul_div(shares, index, REBASE_INDEX_ONE)
+            .unwrap_or_else(|| env::panic_str("Rebase overflow"))
+    }
+
+    fn internal_snapshot_indexes() -> LookupMap<SnapshotId, u128> {
+        LookupMap::new(StorageKey::Rebase(RebaseKey::SnapshotIndexes))
+    }
+}
+
+// Computes `value * numerator / denominator` rounding down, or `None` if it overflows. The
+// remainder is below `denominator`, so its product can only overflow for an index above
+// `MAX_REBASE_INDEX`, which `rebase` rejects.
+fn checked_mul_div(value: u128, numerator: u128, denominator: u128) -> Option<u128> {
+    (value / denominator)
+        .checked_mul(numerator)?
+        .checked_add((value % denominator).checked_mul(numerator)? / denominator)
+}
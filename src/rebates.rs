@@ -0,0 +1,174 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::internal::pro_rata;
+use crate::versioned::versioned_record;
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// The maximum number of volume tiers of the rebate program
+const MAX_REBATE_TIERS: usize = 10;
+
+// A volume tier: accounts that sent at least `min_volume` tokens during an epoch get
+// `rebate_bps` of the fees they paid during that epoch back.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct RebateTier {
+    pub min_volume: U128,
+    pub rebate_bps: u16,
+}
+
+// The fee rebate program. Rebates are paid from the rebate share of the collected fees.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct RebateProgram {
+    /// The length of an epoch in seconds.
+    pub epoch_duration_sec: u64,
+    /// The volume tiers, by increasing `min_volume`.
+    pub tiers: Vec<RebateTier>,
+    /// The timestamp (in nanoseconds) when the first epoch started. Set when the program is set.
+    #[serde(default)]
+    pub start_at: U64,
+}
+
+// The volume and rebates of an account. Rebates accrue when the epoch they were earned in ends.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct RebateAccount {
+    /// The epoch `volume` and `fees_paid` refer to.
+    pub epoch: u64,
+    /// The tokens sent during `epoch`.
+    pub volume: U128,
+    /// The fees paid during `epoch`.
+    pub fees_paid: U128,
+    /// The rebates of the past epochs that weren't claimed yet.
+    pub accrued: U128,
+    /// The rebates claimed so far.
+    pub claimed: U128,
+}
+
+versioned_record!(VersionedRebateAccount, RebateAccount);
+
+#[near_bindgen]
+impl Contract {
+    /// Starts the fee rebate program with the given tiers, or stops it when `None` is passed.
+    /// The rebates accrued so far stay claimable.
+    #[payable]
+    pub fn set_rebate_program(&mut self, program: Option<RebateProgram>) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.rebate_program = program.map(|mut program| {
+            require!(
+                program.epoch_duration_sec > 0,
+                "The epoch duration should be positive"
+            );
+            require!(program.tiers.len() <= MAX_REBATE_TIERS, "Too many tiers");
+            require!(
+                program
+                    .tiers
+                    .windows(2)
+                    .all(|tiers| tiers[0].min_volume.0 < tiers[1].min_volume.0),
+                "The tiers should be sorted by increasing volume"
+            );
+            require!(
+                program
+                    .tiers
+                    .iter()
+                    .all(|tier| tier.rebate_bps as u128 <= BPS_DENOMINATOR),
+                "A rebate can't be higher than 100%"
+            );
+            program.start_at = U64(env::block_timestamp());
+            program
+        });
+    }
+
+    /// Sends the caller the rebates it accrued, up to what the rebate pool holds.
+    pub fn claim_fee_rebate(&mut self) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let mut rebates = self.internal_rebate_account(&account_id);
+        let amount = std::cmp::min(rebates.accrued.0, self.rebate_pool);
+        require!(amount > 0, "There is no rebate to claim");
+
+        rebates.accrued = U128(rebates.accrued.0.saturating_sub(amount));
+        rebates.claimed = U128(rebates.claimed.0.saturating_add(amount));
+        self.rebate_accounts.insert(&account_id, &rebates);
+        self.rebate_pool = self.rebate_pool.saturating_sub(amount);
+        self.internal_transfer(
+            &env::current_account_id(),
+            &account_id,
+            amount,
+            Some("Fee rebate"),
+        );
+        U128(Self::internal_to_amount(amount))
+    }
+
+    /// Returns the fee rebate program, if any.
+    pub fn get_rebate_program(&self) -> Option<RebateProgram> {
+        self.rebate_program.clone()
+    }
+
+    /// Returns the volume of an account during the current epoch and the rebates it accrued.
+    pub fn get_fee_rebate(&self, account_id: AccountId) -> RebateAccount {
+        let mut rebates = self.internal_rebate_account(&account_id);
+        rebates.volume = U128(Self::internal_to_amount(rebates.volume.0));
+        rebates.fees_paid = U128(Self::internal_to_amount(rebates.fees_paid.0));
+        rebates.accrued = U128(Self::internal_to_amount(rebates.accrued.0));
+        rebates.claimed = U128(Self::internal_to_amount(rebates.claimed.0));
+        rebates
+    }
+
+    /// Returns the tokens available to pay rebates.
+    pub fn get_rebate_pool(&self) -> U128 {
+        U128(Self::internal_to_amount(self.rebate_pool))
+    }
+}
+
+impl Contract {
+    /// Internal method for adding a transfer to the volume of the current epoch. Called every
+    /// time a transfer fee is charged.
+    pub(crate) fn internal_track_rebate_volume(
+        &mut self,
+        sender_id: &AccountId,
+        amount: u128,
+        fee: u128,
+    ) {
+        if self.rebate_program.is_none() {
+            return;
+        }
+        let mut rebates = self.internal_rebate_account(sender_id);
+        rebates.volume = U128(rebates.volume.0.saturating_add(amount));
+        rebates.fees_paid = U128(rebates.fees_paid.0.saturating_add(fee));
+        self.rebate_accounts.insert(sender_id, &rebates);
+    }
+
+    /// Internal method for getting the rebates of an account, accruing the rebate of the epoch
+    /// it was last active in if that epoch is over.
+    fn internal_rebate_account(&self, account_id: &AccountId) -> RebateAccount {
+        let mut rebates = self.rebate_accounts.get(account_id).unwrap_or_default();
+        let Some(program) = self.rebate_program.as_ref() else {
+            return rebates;
+        };
+        let epoch_duration = program.epoch_duration_sec.saturating_mul(NANOS_PER_SECOND);
+        let epoch = env::block_timestamp().saturating_sub(program.start_at.0) / epoch_duration;
+        if epoch != rebates.epoch {
+            let rebate_bps = program
+                .tiers
+                .iter()
+                .rev()
+                .find(|tier| rebates.volume.0 >= tier.min_volume.0)
+                .map_or(0, |tier| tier.rebate_bps);
+            let rebate = pro_rata(rebates.fees_paid.0, rebate_bps as u128, BPS_DENOMINATOR);
+            rebates.accrued = U128(rebates.accrued.0.saturating_add(rebate));
+            rebates.epoch = epoch;
+            rebates.volume = U128(0);
+            rebates.fees_paid = U128(0);
+        }
+        rebates
+    }
+}
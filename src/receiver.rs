@@ -0,0 +1,61 @@
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, NearSchema, PromiseOrValue};
+
+use crate::bonds::BondMarketId;
+use crate::ft_core::FungibleTokenReceiver;
+use crate::*;
+
+// The messages this contract understands when other fungible tokens are sent to it
+// through `ft_transfer_call`. The `msg` is the JSON encoding of one of the variants, e.g.
+// `{"bond": {"market_id": 0}}`.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum TokenReceiverMessage {
+    /// Buys a bond from a market quoted in the sent token.
+    Bond {
+        market_id: BondMarketId,
+        min_payout: Option<U128>,
+    },
+    /// Swaps the sent token for this one through the peg stability module.
+    PsmSwap,
+    /// Misbehaves as told, see `chaos.rs`.
+    #[cfg(feature = "chaos")]
+    Chaos(crate::chaos::ChaosBehavior),
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        // The token contract calling us is the token that was sent.
+        let token_id = env::predecessor_account_id();
+        let message = near_sdk::serde_json::from_str::<TokenReceiverMessage>(&msg)
+            .unwrap_or_else(|_| env::panic_str("Invalid message"));
+
+        match message {
+            TokenReceiverMessage::Bond {
+                market_id,
+                min_payout,
+            } => {
+                let market = self.internal_get_bond_market(market_id);
+                if market.quote_token_id.as_ref() != Some(&token_id) {
+                    env::panic_str("This bond market doesn't accept this token");
+                }
+                self.internal_bond(&sender_id, market_id, amount.0, min_payout.map(|min| min.0));
+            }
+            TokenReceiverMessage::PsmSwap => self.internal_psm_swap(&token_id, &sender_id, amount),
+            #[cfg(feature = "chaos")]
+            TokenReceiverMessage::Chaos(behavior) => {
+                return self.internal_chaos_on_transfer(token_id, amount, behavior);
+            }
+        }
+
+        // The whole amount was used
+        PromiseOrValue::Value(U128(0))
+    }
+}
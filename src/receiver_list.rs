@@ -0,0 +1,97 @@
+//! Receiver list of the transfer calls.
+//!
+//! Regulated deployments may need to restrict which contracts the token flows into. The owner
+//! lists receiver accounts and picks how `ft_transfer_call` (and its Borsh variant) treats them:
+//! with `Allowlist`, only the listed accounts can be called; with `Denylist`, every account but
+//! them can. The list is the same in both modes, so switching the mode reinterprets it. Plain
+//! transfers aren't restricted.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiverListMode {
+    /// Any account can be called.
+    #[default]
+    Open,
+    /// Only the listed accounts can be called.
+    Allowlist,
+    /// The listed accounts can't be called.
+    Denylist,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets how the transfer calls treat the listed receivers (owner only).
+    #[payable]
+    pub fn set_receiver_list_mode(&mut self, mode: ReceiverListMode) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.receiver_list_mode = mode;
+    }
+
+    /// Adds an account to the receiver list (owner only).
+    #[payable]
+    pub fn add_listed_receiver(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.listed_receivers.insert(&account_id);
+    }
+
+    /// Removes an account from the receiver list (owner only).
+    #[payable]
+    pub fn remove_listed_receiver(&mut self, account_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.listed_receivers.remove(&account_id);
+    }
+
+    pub fn get_receiver_list_mode(&self) -> ReceiverListMode {
+        self.receiver_list_mode
+    }
+
+    /// Returns whether an account is in the receiver list.
+    pub fn is_listed_receiver(&self, account_id: AccountId) -> bool {
+        self.listed_receivers.contains(&account_id)
+    }
+
+    /// Returns whether the transfer calls can target an account in the current mode.
+    pub fn can_receive_transfer_calls(&self, account_id: AccountId) -> bool {
+        self.internal_can_receive_transfer_calls(&account_id)
+    }
+}
+
+impl Contract {
+    /// Internal method for asserting that the transfer calls can target `receiver_id`.
+    pub(crate) fn internal_assert_call_receiver(&self, receiver_id: &AccountId) {
+        require!(
+            self.internal_can_receive_transfer_calls(receiver_id),
+            "The receiver can't be called with transfers"
+        );
+    }
+
+    fn internal_can_receive_transfer_calls(&self, account_id: &AccountId) -> bool {
+        match self.receiver_list_mode {
+            ReceiverListMode::Open => true,
+            ReceiverListMode::Allowlist => self.listed_receivers.contains(account_id),
+            ReceiverListMode::Denylist => !self.listed_receivers.contains(account_id),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Social recovery of the ownership.
+//!
+//! The owner designates a recovery council. Once the owner hasn't shown any activity for
+//! `inactivity_sec`, a council member can propose a new owner; when `threshold` members
+//! approved it, the recovery can be executed after a `timelock_sec` delay. Until then, the owner
+//! vetoes it by calling `owner_heartbeat`, or any other method recording its activity.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct RecoveryConfig {
+    /// The accounts allowed to propose and approve a new owner.
+    pub council: Vec<AccountId>,
+    /// The number of council members approving a recovery for it to be executable.
+    pub threshold: u32,
+    /// How long the owner has to be inactive for a recovery to be proposed, in seconds.
+    pub inactivity_sec: u64,
+    /// How long the owner can veto an approved recovery, in seconds.
+    pub timelock_sec: u64,
+}
+
+// A recovery proposed by the council.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnerRecovery {
+    pub new_owner_id: AccountId,
+    /// The council members who approved the recovery, starting with its proposer.
+    pub approvals: Vec<AccountId>,
+    /// The timestamp (in nanoseconds) from which the recovery can be executed, set once enough
+    /// members approved it.
+    pub executable_at: Option<U64>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the recovery council, or removes it when `None` is passed (owner only). Cancels the
+    /// pending recovery, if any.
+    #[payable]
+    pub fn set_recovery_config(&mut self, config: Option<RecoveryConfig>) {
+        assert_one_yocto();
+        self.assert_owner();
+        if let Some(config) = &config {
+            require!(
+                config.threshold > 0 && config.threshold as usize <= config.council.len(),
+                "The threshold should be between 1 and the size of the council"
+            );
+            require!(
+                !config.council.contains(&self.owner_id),
+                "The owner can't be in the recovery council"
+            );
+        }
+        self.recovery_config = config;
+        self.internal_record_owner_activity();
+    }
+
+    /// Records that the owner is active, vetoing the pending recovery, if any (owner only).
+    #[payable]
+    pub fn owner_heartbeat(&mut self) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.internal_record_owner_activity();
+    }
+
+    /// Proposes `new_owner_id` as the owner replacing the inactive one (recovery council only).
+    #[payable]
+    pub fn propose_owner_recovery(&mut self, new_owner_id: AccountId) {
+        assert_one_yocto();
+        let config = self.internal_assert_recovery_council();
+        require!(
+            self.owner_recovery.is_none(),
+            "A recovery is already pending"
+        );
+        require!(
+            env::block_timestamp() >= self.owner_active_at + config.inactivity_sec * NANOS_PER_SEC,
+            "The owner isn't inactive"
+        );
+        let proposer_id = env::predecessor_account_id();
+        OwnerRecoveryProposed {
+            proposer_id: &proposer_id,
+            new_owner_id: &new_owner_id,
+        }
+        .emit();
+        let mut recovery = OwnerRecovery {
+            new_owner_id,
+            approvals: vec![proposer_id],
+            executable_at: None,
+        };
+        Self::internal_start_timelock(&config, &mut recovery);
+        self.owner_recovery = Some(recovery);
+    }
+
+    /// Approves the pending recovery (recovery council only).
+    #[payable]
+    pub fn approve_owner_recovery(&mut self) {
+        assert_one_yocto();
+        let config = self.internal_assert_recovery_council();
+        let mut recovery = self
+            .owner_recovery
+            .take()
+            .unwrap_or_else(|| env::panic_str("No recovery is pending"));
+        let account_id = env::predecessor_account_id();
+        require!(
+            !recovery.approvals.contains(&account_id),
+            "The account already approved the recovery"
+        );
+        recovery.approvals.push(account_id);
+        Self::internal_start_timelock(&config, &mut recovery);
+        self.owner_recovery = Some(recovery);
+    }
+
+    /// Transfers the ownership to the account of the recovery once its timelock is over. Anyone
+    /// can call it.
+    pub fn execute_owner_recovery(&mut self) {
+        let recovery = self
+            .owner_recovery
+            .take()
+            .unwrap_or_else(|| env::panic_str("No recovery is pending"));
+        require!(
+            recovery
+                .executable_at
+                .is_some_and(|executable_at| env::block_timestamp() >= executable_at.0),
+            "The recovery isn't executable yet"
+        );
+        OwnershipTransferred {
+            old_owner_id: &self.owner_id,
+            new_owner_id: &recovery.new_owner_id,
+        }
+        .emit();
+        self.owner_id = recovery.new_owner_id;
+        self.pending_owner_id = None;
+        self.owner_active_at = env::block_timestamp();
+    }
+
+    pub fn get_recovery_config(&self) -> Option<RecoveryConfig> {
+        self.recovery_config.clone()
+    }
+
+    pub fn get_owner_recovery(&self) -> Option<OwnerRecovery> {
+        self.owner_recovery.clone()
+    }
+
+    /// Returns the timestamp (in nanoseconds) of the owner's last recorded activity.
+    pub fn get_owner_active_at(&self) -> U64 {
+        U64(self.owner_active_at)
+    }
+}
+
+impl Contract {
+    /// Internal method for recording that the owner is active, which vetoes the pending
+    /// recovery.
+    pub(crate) fn internal_record_owner_activity(&mut self) {
+        self.owner_active_at = env::block_timestamp();
+        if let Some(recovery) = self.owner_recovery.take() {
+            OwnerRecoveryVetoed {
+                owner_id: &self.owner_id,
+                new_owner_id: &recovery.new_owner_id,
+            }
+            .emit();
+        }
+    }
+
+    fn internal_assert_recovery_council(&self) -> RecoveryConfig {
+        let config = self
+            .recovery_config
+            .clone()
+            .unwrap_or_else(|| env::panic_str("No recovery council is set"));
+        require!(
+            config.council.contains(&env::predecessor_account_id()),
+            "Only the recovery council can call this method"
+        );
+        config
+    }
+
+    fn internal_start_timelock(config: &RecoveryConfig, recovery: &mut OwnerRecovery) {
+        if recovery.executable_at.is_none() && recovery.approvals.len() >= config.threshold as usize
+        {
+            let executable_at = env::block_timestamp() + config.timelock_sec * NANOS_PER_SEC;
+            recovery.executable_at = Some(U64(executable_at));
+            log!(
+                "The recovery to {} can be executed from {}",
+                recovery.new_owner_id,
+                executable_at
+            );
+        }
+    }
+}
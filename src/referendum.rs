@@ -0,0 +1,230 @@
+//! Holder referenda against the timelocked admin actions.
+//!
+//! The timelock of an admin action gives the holders a chance to object to it. Any holder can
+//! challenge an approved insurance payout or an approved owner recovery while it waits for its
+//! timelock, by staking tokens against it with `vote_against_admin_action`. As soon as the
+//! stakes reach `quorum_bps` of the total supply, the action is cancelled. The stakes are held
+//! by the contract account, and their holders withdraw them once the referendum is over: the
+//! action was cancelled, executed, or its timelock elapsed.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, NearSchema};
+
+use crate::internal::pro_rata;
+use crate::*;
+
+pub type ReferendumId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReferendumConfig {
+    /// The share of the total supply staked against an action that cancels it, in basis points.
+    pub quorum_bps: u16,
+}
+
+// The timelocked admin actions the holders can challenge.
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengedAction {
+    InsurancePayout {
+        claim_id: InsuranceClaimId,
+    },
+    /// The pending owner recovery.
+    OwnerRecovery,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Referendum {
+    pub action: ChallengedAction,
+    /// The end of the timelock of the action (in nanoseconds), after which it can't be
+    /// cancelled anymore.
+    pub deadline: U64,
+    /// The tokens staked against the action.
+    pub staked_against: U128,
+    /// Whether the stakes reached the quorum and cancelled the action.
+    pub cancelled: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Lets the holders challenge the timelocked admin actions with the given quorum, or stops
+    /// new votes when `None` is passed (owner only).
+    #[payable]
+    pub fn set_referendum_config(&mut self, config: Option<ReferendumConfig>) {
+        assert_one_yocto();
+        self.assert_owner();
+        if let Some(config) = &config {
+            require!(
+                config.quorum_bps > 0 && config.quorum_bps as u128 <= BPS_DENOMINATOR,
+                "The quorum should be between 0 and 100%"
+            );
+        }
+        self.referendum_config = config;
+    }
+
+    /// Stakes `amount` of the caller's tokens against a timelocked admin action, opening its
+    /// referendum if needed. Cancels the action once the stakes reach the quorum.
+    #[payable]
+    pub fn vote_against_admin_action(
+        &mut self,
+        action: ChallengedAction,
+        amount: U128,
+    ) -> ReferendumId {
+        assert_one_yocto();
+        let config = self
+            .referendum_config
+            .clone()
+            .unwrap_or_else(|| env::panic_str("The referenda are off"));
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let deadline = self
+            .internal_challenged_action_deadline(&action)
+            .unwrap_or_else(|| env::panic_str("The action isn't waiting for its timelock"));
+        require!(
+            env::block_timestamp() < deadline,
+            "The timelock of the action is over"
+        );
+        // The referendum of an earlier action with the same target (e.g. a vetoed recovery)
+        // is over: a new one is opened.
+        let referendum_id = match self.open_referenda.get(&action) {
+            Some(referendum_id)
+                if self
+                    .referenda
+                    .get(&referendum_id)
+                    .is_some_and(|referendum| referendum.deadline.0 == deadline) =>
+            {
+                referendum_id
+            }
+            _ => {
+                let referendum_id = self.next_referendum_id;
+                self.next_referendum_id += 1;
+                self.open_referenda.insert(&action, &referendum_id);
+                referendum_id
+            }
+        };
+        let mut referendum = self.referenda.get(&referendum_id).unwrap_or(Referendum {
+            action: action.clone(),
+            deadline: U64(deadline),
+            staked_against: U128(0),
+            cancelled: false,
+        });
+
+        let voter_id = env::predecessor_account_id();
+        let amount = Self::internal_to_shares(amount.0);
+        self.internal_transfer(
+            &voter_id,
+            &env::current_account_id(),
+            amount,
+            Some("Referendum stake"),
+        );
+        let key = (referendum_id, voter_id);
+        let stake = self.referendum_stakes.get(&key).unwrap_or(0);
+        self.referendum_stakes.insert(&key, &(stake + amount));
+        referendum.staked_against = U128(referendum.staked_against.0 + amount);
+
+        let quorum = pro_rata(self.total_supply, config.quorum_bps.into(), BPS_DENOMINATOR);
+        if referendum.staked_against.0 >= quorum {
+            referendum.cancelled = true;
+            self.internal_cancel_challenged_action(&action);
+        }
+        self.referenda.insert(&referendum_id, &referendum);
+        referendum_id
+    }
+
+    /// Returns the caller's stake once the referendum is over.
+    #[payable]
+    pub fn withdraw_referendum_stake(&mut self, referendum_id: ReferendumId) -> U128 {
+        assert_one_yocto();
+        let referendum = self
+            .referenda
+            .get(&referendum_id)
+            .unwrap_or_else(|| env::panic_str("The referendum doesn't exist"));
+        require!(
+            !self.internal_referendum_open(&referendum),
+            "The referendum isn't over yet"
+        );
+        let voter_id = env::predecessor_account_id();
+        let stake = self
+            .referendum_stakes
+            .remove(&(referendum_id, voter_id.clone()))
+            .unwrap_or_else(|| env::panic_str("The account has no stake in the referendum"));
+        self.internal_transfer(
+            &env::current_account_id(),
+            &voter_id,
+            stake,
+            Some("Referendum stake"),
+        );
+        U128(Self::internal_to_amount(stake))
+    }
+
+    pub fn get_referendum_config(&self) -> Option<ReferendumConfig> {
+        self.referendum_config.clone()
+    }
+
+    /// Returns a referendum, or `null` if it doesn't exist.
+    pub fn get_referendum(&self, referendum_id: ReferendumId) -> Option<Referendum> {
+        self.referenda.get(&referendum_id).map(|mut referendum| {
+            referendum.staked_against = U128(Self::internal_to_amount(referendum.staked_against.0));
+            referendum
+        })
+    }
+
+    /// Returns the tokens an account staked in a referendum and didn't withdraw yet.
+    pub fn get_referendum_stake(&self, referendum_id: ReferendumId, account_id: AccountId) -> U128 {
+        U128(Self::internal_to_amount(
+            self.referendum_stakes
+                .get(&(referendum_id, account_id))
+                .unwrap_or(0),
+        ))
+    }
+}
+
+impl Contract {
+    /// Internal method for getting the end of the timelock of an action, if it's waiting for it.
+    fn internal_challenged_action_deadline(&self, action: &ChallengedAction) -> Option<u64> {
+        match action {
+            ChallengedAction::InsurancePayout { claim_id } => self
+                .insurance_claims
+                .get(claim_id)
+                .map(|claim| claim.executable_at.0),
+            ChallengedAction::OwnerRecovery => self
+                .owner_recovery
+                .as_ref()
+                .and_then(|recovery| recovery.executable_at)
+                .map(|executable_at| executable_at.0),
+        }
+    }
+
+    fn internal_referendum_open(&self, referendum: &Referendum) -> bool {
+        !referendum.cancelled
+            && env::block_timestamp() < referendum.deadline.0
+            && self.internal_challenged_action_deadline(&referendum.action)
+                == Some(referendum.deadline.0)
+    }
+
+    fn internal_cancel_challenged_action(&mut self, action: &ChallengedAction) {
+        match action {
+            ChallengedAction::InsurancePayout { claim_id } => {
+                self.internal_cancel_insurance_payout(*claim_id);
+                log!("The holders cancelled the insurance payout {}", claim_id);
+            }
+            ChallengedAction::OwnerRecovery => {
+                if let Some(recovery) = self.owner_recovery.take() {
+                    OwnerRecoveryVetoed {
+                        owner_id: &self.owner_id,
+                        new_owner_id: &recovery.new_owner_id,
+                    }
+                    .emit();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema, Promise};
+
+use crate::*;
+
+/// The price relayers are reimbursed at until the owner sets one: the minimum gas price of the
+/// protocol, in yoctoNEAR per gas unit
+pub const DEFAULT_RELAYER_GAS_PRICE: u128 = 100_000_000;
+
+// The gas a protocol-owned relayer burnt submitting meta-transactions, and what it was
+// reimbursed for it.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct RelayerUsage {
+    /// The number of meta-transactions submitted.
+    pub calls: u64,
+    /// The gas burnt by these calls that wasn't reimbursed yet.
+    pub unreimbursed_gas: u64,
+    /// The NEAR reimbursed so far.
+    pub reimbursed: NearToken,
+    /// The most NEAR the relayer can ever be reimbursed.
+    pub cap: NearToken,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Registers a protocol-owned relayer with the given reimbursement cap, updates the cap of
+    /// an existing one, or unregisters it when `None` is passed.
+    #[payable]
+    pub fn set_protocol_relayer(&mut self, relayer_id: AccountId, cap: Option<NearToken>) {
+        assert_one_yocto();
+        self.assert_owner();
+        match (cap, self.relayer_usage.get(&relayer_id)) {
+            (Some(cap), Some(mut usage)) => {
+                usage.cap = cap;
+                self.relayer_usage.insert(&relayer_id, &usage);
+            }
+            (Some(cap), None) => {
+                self.relayer_usage.insert(
+                    &relayer_id,
+                    &RelayerUsage {
+                        calls: 0,
+                        unreimbursed_gas: 0,
+                        reimbursed: ZERO_TOKEN,
+                        cap,
+                    },
+                );
+            }
+            (None, _) => {
+                self.relayer_usage.remove(&relayer_id);
+            }
+        }
+    }
+
+    /// Sets the price (in yoctoNEAR per gas unit) relayers are reimbursed at.
+    #[payable]
+    pub fn set_relayer_gas_price(&mut self, gas_price: U128) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.relayer_gas_price = gas_price.0;
+    }
+
+    /// Adds the attached deposit to the NEAR pool relayers are reimbursed from.
+    #[payable]
+    pub fn fund_relayer_gas_pool(&mut self) {
+        self.relayer_gas_pool = self
+            .relayer_gas_pool
+            .saturating_add(env::attached_deposit());
+    }
+
+    /// Reimburses a relayer for the gas it burnt since its last reimbursement, within its cap
+    /// and what the pool holds. Returns the reimbursed amount.
+    pub fn reimburse_relayer(&mut self, relayer_id: AccountId) -> NearToken {
+        self.assert_owner();
+        let mut usage = self
+            .relayer_usage
+            .get(&relayer_id)
+            .unwrap_or_else(|| env::panic_str("The relayer is not registered"));
+        let owed = (usage.unreimbursed_gas as u128).saturating_mul(self.relayer_gas_price);
+        let amount = [
+            owed,
+            usage.cap.saturating_sub(usage.reimbursed).as_yoctonear(),
+            self.relayer_gas_pool.as_yoctonear(),
+        ]
+        .into_iter()
+        .min()
+        .unwrap_or(0);
+        require!(amount > 0, "There is nothing to reimburse");
+
+        // Only the gas actually paid for is considered reimbursed.
+        let reimbursed_gas = if amount == owed {
+            usage.unreimbursed_gas
+        } else {
+            (amount / self.relayer_gas_price) as u64
+        };
+        let amount = NearToken::from_yoctonear(amount);
+        usage.unreimbursed_gas -= reimbursed_gas;
+        usage.reimbursed = usage.reimbursed.saturating_add(amount);
+        self.relayer_usage.insert(&relayer_id, &usage);
+        self.relayer_gas_pool = self.relayer_gas_pool.saturating_sub(amount);
+        Promise::new(relayer_id).transfer(amount);
+        amount
+    }
+
+    /// Returns the usage report of a protocol-owned relayer, or `null` if it isn't registered.
+    pub fn get_relayer_usage(&self, relayer_id: AccountId) -> Option<RelayerUsage> {
+        self.relayer_usage.get(&relayer_id)
+    }
+
+    /// Paginates through the usage reports of the protocol-owned relayers.
+    pub fn get_relayer_usages(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, RelayerUsage)> {
+        self.relayer_usage
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+
+    /// Returns the NEAR available to reimburse relayers and the price gas is reimbursed at.
+    pub fn get_relayer_gas_pool(&self) -> (NearToken, U128) {
+        (self.relayer_gas_pool, U128(self.relayer_gas_price))
+    }
+}
+
+impl Contract {
+    /// Internal method for recording the gas burnt so far by a meta-transaction submitted by
+    /// `relayer_id`. Does nothing for relayers that are not protocol-owned. Called at the end
+    /// of the relayed methods.
+    pub(crate) fn internal_record_relayer_gas(&mut self, relayer_id: &AccountId) {
+        let Some(mut usage) = self.relayer_usage.get(relayer_id) else {
+            return;
+        };
+        usage.calls += 1;
+        usage.unreimbursed_gas = usage
+            .unreimbursed_gas
+            .saturating_add(env::used_gas().as_gas());
+        self.relayer_usage.insert(relayer_id, &usage);
+    }
+}
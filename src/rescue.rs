@@ -0,0 +1,103 @@
+//! Rescue of the assets sent to the contract account by mistake.
+//!
+//! NEAR and other fungible tokens sent to the contract account outside of its methods would
+//! otherwise be stuck there. The owner sends them to a receiver with `rescue_near` and
+//! `rescue_ft`. Only the NEAR the contract doesn't owe can be rescued: the NEAR staked for its
+//! storage, the pools funded by the users, the storage sponsorships, the wrapped NEAR and the
+//! stablecoin collateral stay. Likewise, the reference token of the PSM can only be rescued once
+//! the PSM holds no reserves, and this token itself never: the tokens of the contract account are
+//! held on behalf of the other modules.
+
+use near_sdk::json_types::U128;
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, Gas, Promise};
+
+use crate::ft_core::ext_ft_core;
+use crate::*;
+
+const GAS_FOR_RESCUE_FT: Gas = Gas::from_tgas(10);
+
+#[near_bindgen]
+impl Contract {
+    /// Sends `amount` of the NEAR the contract doesn't owe to `receiver_id` (owner only).
+    #[payable]
+    pub fn rescue_near(&mut self, amount: NearToken, receiver_id: AccountId) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(
+            amount.le(&self.get_rescuable_near()),
+            "The amount exceeds the rescuable NEAR"
+        );
+        log!("Rescued {} to {}", amount, receiver_id);
+        Promise::new(receiver_id).transfer(amount)
+    }
+
+    /// Sends `amount` of the `token_id` tokens held by the contract account to `receiver_id`
+    /// (owner only).
+    #[payable]
+    pub fn rescue_ft(
+        &mut self,
+        token_id: AccountId,
+        amount: U128,
+        receiver_id: AccountId,
+    ) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(
+            token_id != env::current_account_id(),
+            "This token can't be rescued"
+        );
+        require!(
+            self.psm_reserves == 0
+                || self
+                    .psm_config
+                    .as_ref()
+                    .is_none_or(|config| config.reference_token_id != token_id),
+            "The PSM reserves can't be rescued"
+        );
+        require!(amount.0 > 0, "The amount should be a positive number");
+        self.internal_promise_budget()
+            .call(GAS_FOR_RESCUE_FT, NearToken::from_yoctonear(1))
+            .check();
+        log!("Rescued {} {} to {}", amount.0, token_id, receiver_id);
+        ext_ft_core::ext(token_id)
+            .with_attached_deposit(NearToken::from_yoctonear(1))
+            .with_static_gas(GAS_FOR_RESCUE_FT)
+            .ft_transfer(
+                receiver_id,
+                amount,
+                Some("Rescue".to_string()),
+                None,
+                None,
+                None,
+            )
+    }
+
+    /// Returns the NEAR of the contract account it doesn't owe, which the owner can rescue.
+    pub fn get_rescuable_near(&self) -> NearToken {
+        let storage_cost = env::storage_byte_cost().saturating_mul(env::storage_usage().into());
+        env::account_balance()
+            .saturating_sub(storage_cost)
+            .saturating_sub(self.internal_owed_near())
+    }
+}
+
+impl Contract {
+    /// Internal method for summing the NEAR the contract account holds for others: the pools,
+    /// the sponsored storage, and the NEAR backing the tokens.
+    pub(crate) fn internal_owed_near(&self) -> NearToken {
+        let mut owed = self
+            .sponsorship_pool
+            .saturating_add(self.relayer_gas_pool)
+            .saturating_add(self.storage_sponsorship_total);
+        if self.wrapped {
+            owed = owed.saturating_add(NearToken::from_yoctonear(self.total_supply));
+        }
+        #[cfg(feature = "stablecoin")]
+        {
+            owed = owed.saturating_add(NearToken::from_yoctonear(
+                Self::internal_stablecoin_state().total_collateral,
+            ));
+        }
+        owed
+    }
+}
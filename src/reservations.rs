@@ -0,0 +1,211 @@
+//! Balance reservations.
+//!
+//! Protocols spanning several receipts (and shards) can't rely on a balance staying put between
+//! their steps, and optimistic transfers need refunds when a later step fails. A holder instead
+//! reserves tokens for the protocol with `reserve`: the contract account holds them until the
+//! beneficiary either commits the reservation, receiving the tokens, or releases it, returning
+//! them to the holder. Once the reservation expires, the beneficiary can't commit it anymore and
+//! the holder can release it too.
+//!
+//! The transfer fee is reserved along with the amount, so that the beneficiary can commit the
+//! reservation whatever is left in the holder's balance, and the holder pays for the storage of
+//! the reservation until it's committed or released.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, require, NearSchema, NearToken};
+
+use crate::versioned::Versioned;
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+pub type ReservationId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Reservation {
+    pub holder_id: AccountId,
+    pub beneficiary_id: AccountId,
+    pub amount: U128,
+    /// The timestamp (in nanoseconds) after which the beneficiary can't commit the reservation
+    /// anymore, and the holder can release it.
+    pub expires_at: U64,
+    /// The fee reserved along with the amount.
+    pub fee: U128,
+}
+
+// A reservation made before its fee was reserved.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ReservationV1 {
+    pub holder_id: AccountId,
+    pub beneficiary_id: AccountId,
+    pub amount: U128,
+    pub expires_at: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedReservation {
+    V1(ReservationV1),
+    V2(Reservation),
+}
+
+impl Versioned for Reservation {
+    type Record = VersionedReservation;
+
+    fn from_record(record: VersionedReservation) -> Self {
+        match record {
+            VersionedReservation::V1(reservation) => Self {
+                holder_id: reservation.holder_id,
+                beneficiary_id: reservation.beneficiary_id,
+                amount: reservation.amount,
+                expires_at: reservation.expires_at,
+                fee: U128(0),
+            },
+            VersionedReservation::V2(reservation) => reservation,
+        }
+    }
+
+    fn into_record(self) -> VersionedReservation {
+        VersionedReservation::V2(self)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Reserves `amount` of the caller's tokens and the transfer fee for `beneficiary_id` for
+    /// `ttl_sec` seconds. The beneficiary must be registered. The attached deposit (at least 1
+    /// yoctoNEAR) must cover the storage of the reservation; the rest of it is refunded.
+    #[payable]
+    pub fn reserve(
+        &mut self,
+        amount: U128,
+        beneficiary_id: AccountId,
+        ttl_sec: u64,
+    ) -> ReservationId {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let holder_id = env::predecessor_account_id();
+        require!(
+            holder_id != beneficiary_id,
+            "Holder and beneficiary should be different"
+        );
+        require!(ttl_sec > 0, "The TTL should be positive");
+        self.internal_unwrap_balance_of(&beneficiary_id);
+        let amount = U128(Self::internal_to_shares(amount.0));
+        self.internal_authorize_transfer(&holder_id, &beneficiary_id, amount.0, None, None);
+
+        let reservation_id = self.next_reservation_id;
+        self.next_reservation_id += 1;
+        let fee =
+            self.internal_transfer_fee(&holder_id, &beneficiary_id, amount.0, FeeMode::SenderPays);
+        self.internal_transfer(
+            &holder_id,
+            &env::current_account_id(),
+            amount
+                .0
+                .checked_add(fee)
+                .unwrap_or_else(|| env::panic_str("Balance overflow")),
+            Some(&format!("Reservation {}", reservation_id)),
+        );
+        self.internal_add_pending_references(&[&holder_id, &beneficiary_id]);
+        self.reservations.insert(
+            &reservation_id,
+            &Reservation {
+                holder_id,
+                beneficiary_id,
+                amount,
+                expires_at: U64(env::block_timestamp() + ttl_sec.saturating_mul(NANOS_PER_SECOND)),
+                fee: U128(fee),
+            },
+        );
+        Self::internal_charge_storage(initial_storage_usage);
+        reservation_id
+    }
+
+    /// Sends the reserved tokens to the beneficiary, which must be the caller, before the
+    /// reservation expires. The fee reserved with the tokens is charged, even if the fee config
+    /// changed since. Returns the amount left to the beneficiary once the tokenomics burned
+    /// their share.
+    pub fn commit(&mut self, reservation_id: ReservationId) -> U128 {
+        self.internal_assert_not_paused(Pausable::Transfers);
+        let reservation = self.internal_take_reservation(reservation_id);
+        require!(
+            env::predecessor_account_id() == reservation.beneficiary_id,
+            "Only the beneficiary can commit the reservation"
+        );
+        require!(
+            env::block_timestamp() <= reservation.expires_at.0,
+            "The reservation has expired"
+        );
+        self.internal_transfer(
+            &env::current_account_id(),
+            &reservation.beneficiary_id,
+            reservation.amount.0,
+            Some(&format!("Reservation {}", reservation_id)),
+        );
+        let received = self.internal_settle_escrowed_transfer(
+            &reservation.holder_id,
+            &reservation.beneficiary_id,
+            reservation.amount.0,
+            reservation.fee.0,
+            &format!("Fee of reservation {}", reservation_id),
+        );
+        U128(Self::internal_to_amount(received))
+    }
+
+    /// Returns the reserved tokens and fee to the holder. The beneficiary can release the reservation at
+    /// any time, the holder once it expired.
+    pub fn release(&mut self, reservation_id: ReservationId) -> U128 {
+        let reservation = self.internal_take_reservation(reservation_id);
+        let caller_id = env::predecessor_account_id();
+        require!(
+            caller_id == reservation.beneficiary_id
+                || (caller_id == reservation.holder_id
+                    && env::block_timestamp() > reservation.expires_at.0),
+            "Only the beneficiary, or the holder once it expired, can release the reservation"
+        );
+        self.internal_transfer(
+            &env::current_account_id(),
+            &reservation.holder_id,
+            reservation.amount.0 + reservation.fee.0,
+            Some(&format!("Release of reservation {}", reservation_id)),
+        );
+        U128(Self::internal_to_amount(reservation.amount.0))
+    }
+
+    /// Returns a reservation, or `null` if it doesn't exist (anymore).
+    pub fn get_reservation(&self, reservation_id: ReservationId) -> Option<Reservation> {
+        self.reservations
+            .get(&reservation_id)
+            .map(|mut reservation| {
+                reservation.amount = U128(Self::internal_to_amount(reservation.amount.0));
+                reservation.fee = U128(Self::internal_to_amount(reservation.fee.0));
+                reservation
+            })
+    }
+}
+
+impl Contract {
+    /// Internal method for removing a reservation, refunding its storage to the holder.
+    fn internal_take_reservation(&mut self, reservation_id: ReservationId) -> Reservation {
+        let initial_storage_usage = env::storage_usage();
+        let reservation = self
+            .reservations
+            .remove(&reservation_id)
+            .unwrap_or_else(|| env::panic_str("The reservation doesn't exist"));
+        self.internal_remove_pending_references(&[
+            &reservation.holder_id,
+            &reservation.beneficiary_id,
+        ]);
+        Self::internal_refund_storage(&reservation.holder_id, initial_storage_usage);
+        reservation
+    }
+}
@@ -0,0 +1,79 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, near_bindgen, require, NearSchema};
+
+use crate::internal::pro_rata;
+use crate::*;
+
+// The royalties paid to the issuer of a position when it changes hands, in basis points of the
+// value of the transferred position. Like NFT royalties, they are paid by the seller, here in
+// tokens taken from the sender's balance.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct PositionRoyalties {
+    /// The royalty on receipt token transfers, paid to the owner of the lock.
+    pub lock_receipts_bps: u16,
+    /// The royalty on grant transfers, paid to the issuer of the grant on its unclaimed tokens.
+    pub grants_bps: u16,
+}
+
+/// Royalties can't take more than half of a position.
+const MAX_ROYALTY_BPS: u16 = 5_000;
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the royalties charged on position transfers.
+    #[payable]
+    pub fn set_position_royalties(&mut self, royalties: PositionRoyalties) {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(
+            royalties.lock_receipts_bps <= MAX_ROYALTY_BPS
+                && royalties.grants_bps <= MAX_ROYALTY_BPS,
+            "The royalty should be at most 50%"
+        );
+        self.position_royalties = royalties;
+    }
+
+    /// Returns the royalties charged on position transfers.
+    pub fn get_position_royalties(&self) -> PositionRoyalties {
+        self.position_royalties.clone()
+    }
+}
+
+impl Contract {
+    /// Internal method for charging `payer_id` the royalty of a position transfer worth `value`
+    /// shares. Issuers transferring their own positions don't pay royalties.
+    pub(crate) fn internal_pay_position_royalty(
+        &mut self,
+        position: &str,
+        position_id: u64,
+        payer_id: &AccountId,
+        issuer_id: &AccountId,
+        value: u128,
+        royalty_bps: u16,
+    ) {
+        if payer_id == issuer_id || royalty_bps == 0 {
+            return;
+        }
+        let amount = pro_rata(value, royalty_bps as u128, BPS_DENOMINATOR);
+        if amount == 0 {
+            return;
+        }
+        self.internal_transfer(
+            payer_id,
+            issuer_id,
+            amount,
+            Some(&format!("Royalty on {} {}", position, position_id)),
+        );
+        PositionRoyalty {
+            position,
+            position_id,
+            payer_id,
+            issuer_id,
+            amount: &U128(Self::internal_to_amount(amount)),
+        }
+        .emit();
+    }
+}
@@ -0,0 +1,175 @@
+//! Sandbox harness for the integration tests of the token and of the crates calling it, only
+//! compiled with the `sandbox` feature.
+//!
+//! `TokenHarness` compiles the contract, deploys it to a fresh `near-workspaces` sandbox and
+//! wraps the calls the tests make the most. Its mock receiver is a copy of the contract built
+//! with the `chaos` feature, so `ft_transfer_call` round-trips can keep, refund or fail as told
+//! by `chaos_msg`, covering the refunds unit tests can't.
+
+use near_sdk::NearToken;
+use near_workspaces::network::Sandbox;
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::{Account, AccountId, Contract, Worker};
+use serde_json::{json, Value};
+
+/// The errors of the sandbox, the build and the calls.
+pub type HarnessResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// The deposit covering the storage of any registration.
+pub const STORAGE_DEPOSIT: NearToken = NearToken::from_millinear(100);
+
+const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+
+/// Builds the contract wasm, with the given comma-separated features, into `out_dir`.
+pub fn compile_token(features: Option<&str>, out_dir: &str) -> HarnessResult<Vec<u8>> {
+    let manifest_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+    let artifact = cargo_near_build::build(cargo_near_build::BuildOpts {
+        no_locked: true,
+        manifest_path: Some(
+            cargo_near_build::camino::Utf8PathBuf::from_path_buf(manifest_path)
+                .map_err(|_| "The manifest path isn't UTF-8")?,
+        ),
+        features: features.map(str::to_string),
+        out_dir: Some(out_dir.into()),
+        ..Default::default()
+    })?;
+    Ok(std::fs::read(artifact.path)?)
+}
+
+/// Returns the `ft_transfer_call` message making the mock receiver behave as told, e.g.
+/// `json!({ "refund": { "unused": "30" } })` or `json!("panic")`.
+pub fn chaos_msg(behavior: Value) -> String {
+    json!({ "chaos": behavior }).to_string()
+}
+
+/// Returns the NEP-297 events logged by every receipt of a transaction.
+pub fn event_logs(outcome: &ExecutionFinalResult) -> Vec<Value> {
+    outcome
+        .logs()
+        .into_iter()
+        .filter_map(|log| log.strip_prefix(EVENT_JSON_PREFIX))
+        .filter_map(|event| serde_json::from_str(event).ok())
+        .collect()
+}
+
+pub struct TokenHarness {
+    pub sandbox: Worker<Sandbox>,
+    pub token: Contract,
+    /// The owner, holding the whole initial supply.
+    pub owner: Account,
+}
+
+impl TokenHarness {
+    /// Deploys the contract to a new sandbox, owned by a new account holding `total_supply`.
+    pub async fn new(total_supply: u128) -> HarnessResult<Self> {
+        let wasm = compile_token(None, "./target/near")?;
+        let sandbox = near_workspaces::sandbox().await?;
+        let token = sandbox.dev_deploy(&wasm).await?;
+        let owner = sandbox.dev_create_account().await?;
+        token
+            .call("new_default_meta")
+            .args_json(json!({ "owner_id": owner.id(), "total_supply": total_supply.to_string() }))
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(Self {
+            sandbox,
+            token,
+            owner,
+        })
+    }
+
+    /// Creates an account, registered with the token if `registered`.
+    pub async fn create_account(&self, registered: bool) -> HarnessResult<Account> {
+        let account = self.sandbox.dev_create_account().await?;
+        if registered {
+            self.register(account.id()).await?.into_result()?;
+        }
+        Ok(account)
+    }
+
+    /// Deploys the mock receiver and registers it with the token.
+    pub async fn deploy_mock_receiver(&self) -> HarnessResult<Contract> {
+        let wasm = compile_token(Some("chaos"), "./target/near/chaos")?;
+        let receiver = self.sandbox.dev_deploy(&wasm).await?;
+        receiver
+            .call("new_default_meta")
+            .args_json(json!({ "owner_id": receiver.id(), "total_supply": "0" }))
+            .transact()
+            .await?
+            .into_result()?;
+        self.register(receiver.id()).await?.into_result()?;
+        Ok(receiver)
+    }
+
+    /// Registers `account_id` with the token, paid by the owner.
+    pub async fn register(&self, account_id: &AccountId) -> HarnessResult<ExecutionFinalResult> {
+        Ok(self
+            .owner
+            .call(self.token.id(), "storage_deposit")
+            .args_json(json!({ "account_id": account_id }))
+            .deposit(STORAGE_DEPOSIT)
+            .transact()
+            .await?)
+    }
+
+    /// Transfers `amount` tokens from `sender` to `receiver_id`.
+    pub async fn ft_transfer(
+        &self,
+        sender: &Account,
+        receiver_id: &AccountId,
+        amount: u128,
+    ) -> HarnessResult<ExecutionFinalResult> {
+        Ok(sender
+            .call(self.token.id(), "ft_transfer")
+            .args_json(json!({ "receiver_id": receiver_id, "amount": amount.to_string() }))
+            .deposit(NearToken::from_yoctonear(1))
+            .transact()
+            .await?)
+    }
+
+    /// Transfers `amount` tokens from `sender` to the `receiver_id` contract with `msg`.
+    pub async fn ft_transfer_call(
+        &self,
+        sender: &Account,
+        receiver_id: &AccountId,
+        amount: u128,
+        msg: &str,
+    ) -> HarnessResult<ExecutionFinalResult> {
+        Ok(sender
+            .call(self.token.id(), "ft_transfer_call")
+            .args_json(json!({
+                "receiver_id": receiver_id,
+                "amount": amount.to_string(),
+                "msg": msg,
+            }))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    pub async fn balance_of(&self, account_id: &AccountId) -> HarnessResult<u128> {
+        let balance = self
+            .token
+            .view("ft_balance_of")
+            .args_json(json!({ "account_id": account_id }))
+            .await?
+            .json::<Value>()?;
+        Ok(balance
+            .as_str()
+            .ok_or("The balance isn't a string")?
+            .parse()?)
+    }
+
+    /// Returns whether `account_id` is registered with the token.
+    pub async fn is_registered(&self, account_id: &AccountId) -> HarnessResult<bool> {
+        let balance = self
+            .token
+            .view("storage_balance_of")
+            .args_json(json!({ "account_id": account_id }))
+            .await?
+            .json::<Value>()?;
+        Ok(!balance.is_null())
+    }
+}
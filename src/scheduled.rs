@@ -0,0 +1,235 @@
+//! Scheduled transfers.
+//!
+//! `ft_transfer_scheduled` holds the tokens in the contract account until the unlock time, after
+//! which anyone can execute the transfer with `execute_scheduled`, so that a payout doesn't need
+//! the sender to be online nor an off-chain cron job. Until then, the sender can cancel it. The
+//! receiver must be registered when the transfer is scheduled; if it unregistered in the
+//! meantime, the execution returns the tokens to the sender instead.
+//!
+//! The transfer fee is escrowed along with the amount, so that the execution doesn't depend on
+//! what's left in the sender's balance, and the sender pays for the storage of the scheduled
+//! transfer until it's executed or cancelled.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, NearSchema, NearToken};
+
+use crate::versioned::Versioned;
+use crate::*;
+
+pub type ScheduledTransferId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct ScheduledTransfer {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    /// The timestamp (in nanoseconds) from which the transfer can be executed, and can't be
+    /// cancelled anymore.
+    pub execute_after: U64,
+    /// The fee escrowed along with the amount.
+    pub fee: U128,
+}
+
+// A scheduled transfer made before its fee was escrowed.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct ScheduledTransferV1 {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub execute_after: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub enum VersionedScheduledTransfer {
+    V1(ScheduledTransferV1),
+    V2(ScheduledTransfer),
+}
+
+impl Versioned for ScheduledTransfer {
+    type Record = VersionedScheduledTransfer;
+
+    fn from_record(record: VersionedScheduledTransfer) -> Self {
+        match record {
+            VersionedScheduledTransfer::V1(transfer) => Self {
+                sender_id: transfer.sender_id,
+                receiver_id: transfer.receiver_id,
+                amount: transfer.amount,
+                execute_after: transfer.execute_after,
+                fee: U128(0),
+            },
+            VersionedScheduledTransfer::V2(transfer) => transfer,
+        }
+    }
+
+    fn into_record(self) -> VersionedScheduledTransfer {
+        VersionedScheduledTransfer::V2(self)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Schedules the transfer of `amount` of the caller's tokens to `receiver_id`, executable by
+    /// anyone from `execute_after_timestamp` (in nanoseconds). The receiver must be registered.
+    /// The amount and the transfer fee are escrowed, and the attached deposit must cover the
+    /// storage of the scheduled transfer; the rest of it is refunded.
+    #[payable]
+    pub fn ft_transfer_scheduled(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        execute_after_timestamp: U64,
+    ) -> ScheduledTransferId {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(
+            execute_after_timestamp.0 > env::block_timestamp(),
+            "The unlock time should be in the future"
+        );
+        self.internal_registered_balance_of(&receiver_id);
+        let amount = U128(Self::internal_to_shares(amount.0));
+        self.internal_authorize_transfer(&sender_id, &receiver_id, amount.0, None, None);
+
+        let scheduled_id = self.next_scheduled_transfer_id;
+        self.next_scheduled_transfer_id += 1;
+        let fee =
+            self.internal_transfer_fee(&sender_id, &receiver_id, amount.0, FeeMode::SenderPays);
+        self.internal_transfer(
+            &sender_id,
+            &env::current_account_id(),
+            amount
+                .0
+                .checked_add(fee)
+                .unwrap_or_else(|| env::panic_str("Balance overflow")),
+            Some(&format!("Scheduled transfer {}", scheduled_id)),
+        );
+        TransferScheduled {
+            scheduled_id,
+            sender_id: &sender_id,
+            receiver_id: &receiver_id,
+            amount: &U128(Self::internal_to_amount(amount.0)),
+            execute_after: &execute_after_timestamp,
+        }
+        .emit();
+        self.internal_add_pending_references(&[&sender_id, &receiver_id]);
+        self.scheduled_transfers.insert(
+            &scheduled_id,
+            &ScheduledTransfer {
+                sender_id,
+                receiver_id,
+                amount,
+                execute_after: execute_after_timestamp,
+                fee: U128(fee),
+            },
+        );
+        Self::internal_charge_storage(initial_storage_usage);
+        scheduled_id
+    }
+
+    /// Executes a scheduled transfer once it's unlocked. Can be called by anyone. The fee
+    /// escrowed with the transfer is charged, even if the fee config changed since.
+    pub fn execute_scheduled(&mut self, scheduled_id: ScheduledTransferId) {
+        self.internal_assert_not_paused(Pausable::Transfers);
+        let transfer = self.internal_take_scheduled_transfer(scheduled_id);
+        require!(
+            env::block_timestamp() >= transfer.execute_after.0,
+            "The scheduled transfer is still locked"
+        );
+        if !self.accounts.contains_key(&transfer.receiver_id) {
+            log!(
+                "{} unregistered, returning the scheduled transfer to {}",
+                transfer.receiver_id,
+                transfer.sender_id
+            );
+            self.internal_contract_transfer(
+                &env::current_account_id(),
+                &transfer.sender_id,
+                transfer.amount.0 + transfer.fee.0,
+                Some(&format!("Refund of scheduled transfer {}", scheduled_id)),
+            );
+            return;
+        }
+
+        self.internal_transfer(
+            &env::current_account_id(),
+            &transfer.receiver_id,
+            transfer.amount.0,
+            Some(&format!("Scheduled transfer {}", scheduled_id)),
+        );
+        // Only the escrowed fee is charged, nothing for the transfers scheduled without one: the
+        // execution mustn't depend on the sender's balance, nor on it being frozen since.
+        self.internal_charge_escrowed_fee(
+            &transfer.sender_id,
+            transfer.amount.0,
+            transfer.fee.0,
+            &format!("Fee of scheduled transfer {}", scheduled_id),
+        );
+        self.internal_burn_on_transfer(&transfer.receiver_id, transfer.amount.0);
+    }
+
+    /// Cancels a scheduled transfer before its unlock time, returning the tokens and the fee to
+    /// the sender, which must be the caller.
+    #[payable]
+    pub fn cancel_scheduled(&mut self, scheduled_id: ScheduledTransferId) {
+        assert_one_yocto();
+        let transfer = self.internal_take_scheduled_transfer(scheduled_id);
+        require!(
+            env::predecessor_account_id() == transfer.sender_id,
+            "Only the sender can cancel the transfer"
+        );
+        require!(
+            env::block_timestamp() < transfer.execute_after.0,
+            "The scheduled transfer is already unlocked"
+        );
+        self.internal_transfer(
+            &env::current_account_id(),
+            &transfer.sender_id,
+            transfer.amount.0 + transfer.fee.0,
+            Some(&format!("Cancel of scheduled transfer {}", scheduled_id)),
+        );
+    }
+
+    /// Returns a scheduled transfer, or `null` if it doesn't exist (anymore).
+    pub fn get_scheduled_transfer(
+        &self,
+        scheduled_id: ScheduledTransferId,
+    ) -> Option<ScheduledTransfer> {
+        self.scheduled_transfers
+            .get(&scheduled_id)
+            .map(|mut transfer| {
+                transfer.amount = U128(Self::internal_to_amount(transfer.amount.0));
+                transfer.fee = U128(Self::internal_to_amount(transfer.fee.0));
+                transfer
+            })
+    }
+}
+
+impl Contract {
+    /// Internal method for removing a scheduled transfer, refunding its storage to the sender.
+    fn internal_take_scheduled_transfer(
+        &mut self,
+        scheduled_id: ScheduledTransferId,
+    ) -> ScheduledTransfer {
+        let initial_storage_usage = env::storage_usage();
+        let transfer = self
+            .scheduled_transfers
+            .remove(&scheduled_id)
+            .unwrap_or_else(|| env::panic_str("The scheduled transfer doesn't exist"));
+        self.internal_remove_pending_references(&[&transfer.sender_id, &transfer.receiver_id]);
+        Self::internal_refund_storage(&transfer.sender_id, initial_storage_usage);
+        transfer
+    }
+}
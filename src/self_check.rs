@@ -0,0 +1,73 @@
+use near_sdk::{env, log, near_bindgen, require};
+
+use crate::*;
+
+/// The most decimals a token can sensibly have: NEAR itself has 24
+const MAX_DECIMALS: u8 = 24;
+
+#[near_bindgen]
+impl Contract {
+    /// Checks that a freshly initialized contract is usable: the metadata is sane, and moving
+    /// tokens from the owner to a scratch account and back restores every balance and the total
+    /// supply. The scratch account is removed afterwards, so the check leaves no trace. Batch it
+    /// right after the `new` call: if it fails, the whole transaction fails, deployment included.
+    #[private]
+    pub fn self_check(&mut self) {
+        let metadata = self
+            .metadata
+            .get()
+            .unwrap_or_else(|| env::panic_str("Self-check: the metadata is missing"));
+        require!(
+            metadata.spec == FT_METADATA_SPEC,
+            "Self-check: unsupported metadata spec"
+        );
+        require!(
+            !metadata.name.is_empty() && !metadata.symbol.is_empty(),
+            "Self-check: the token has no name or symbol"
+        );
+        require!(
+            metadata.decimals <= MAX_DECIMALS,
+            "Self-check: the token has too many decimals"
+        );
+        require!(
+            metadata.reference.is_some() == metadata.reference_hash.is_some(),
+            "Self-check: the reference and its hash should be set together"
+        );
+
+        let owner_id = self.owner_id.clone();
+        let owner_balance = self.internal_unwrap_balance_of(&owner_id);
+        require!(owner_balance > 0, "Self-check: the owner holds no tokens");
+        let total_supply = self.total_supply;
+        let scratch_id: AccountId = format!("self-check.{}", env::current_account_id())
+            .parse()
+            .unwrap_or_else(|_| env::panic_str("Self-check: the contract account ID is too long"));
+        let amount = 1;
+
+        // Transfer and refund
+        self.internal_register_account(&scratch_id);
+        self.internal_withdraw(&owner_id, amount);
+        self.internal_deposit(&scratch_id, amount);
+        require!(
+            self.internal_unwrap_balance_of(&scratch_id) == amount
+                && self.internal_unwrap_balance_of(&owner_id)
+                    == owner_balance.saturating_sub(amount),
+            "Self-check: the transfer credited the wrong amounts"
+        );
+        self.internal_withdraw(&scratch_id, amount);
+        self.internal_deposit(&owner_id, amount);
+
+        // Clean up
+        self.accounts.remove(&scratch_id);
+        self.metrics.active_accounts -= 1;
+
+        require!(
+            self.internal_unwrap_balance_of(&owner_id) == owner_balance,
+            "Self-check: the refund didn't restore the owner's balance"
+        );
+        require!(
+            self.total_supply == total_supply,
+            "Self-check: the total supply changed"
+        );
+        log!("Self-check passed");
+    }
+}
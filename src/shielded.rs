@@ -0,0 +1,210 @@
+//! Experimental shielded pool scaffold, compiled with the `shielded` feature.
+//!
+//! `shield` moves tokens into a pool held by the contract account and records an opaque
+//! commitment. `unshield` asks a pluggable verifier contract to check a proof that the caller
+//! knows the secret behind one of the commitments, and releases the tokens to `receiver_id`
+//! once. Amounts are still public: this only lays out the storage and the verification flow
+//! private transfers will be built on.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LazyOption, LookupMap, Vector};
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, near_bindgen, require, Gas, NearSchema, Promise,
+    PromiseResult,
+};
+
+use crate::*;
+
+const GAS_FOR_VERIFY_UNSHIELD: Gas = Gas::from_tgas(50);
+const GAS_FOR_ON_UNSHIELD_VERIFIED: Gas = Gas::from_tgas(10);
+
+#[ext_contract(ext_shielded_verifier)]
+pub trait ShieldedVerifier {
+    /// Returns whether `proof` shows that the caller can spend `amount` from one of the first
+    /// `commitments_count` commitments of the pool, under the given `nullifier`, to `receiver_id`.
+    fn verify_unshield(
+        &self,
+        commitments_count: u64,
+        nullifier: Base64VecU8,
+        receiver_id: AccountId,
+        amount: U128,
+        proof: Base64VecU8,
+    ) -> bool;
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct ShieldedPool {
+    /// The contract verifying unshield proofs.
+    pub verifier_id: Option<AccountId>,
+    /// The tokens currently held by the pool.
+    pub balance: U128,
+    /// The number of commitments in the pool.
+    pub commitments_count: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, PartialEq)]
+#[borsh(crate = "near_sdk::borsh")]
+enum NullifierState {
+    Pending,
+    Spent,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the contract verifying unshield proofs.
+    #[payable]
+    pub fn set_shielded_verifier(&mut self, verifier_id: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut pool = Self::internal_shielded_pool();
+        pool.verifier_id = Some(verifier_id);
+        Self::internal_save_shielded_pool(&pool);
+    }
+
+    /// Moves `amount` tokens from the caller into the shielded pool under `commitment`.
+    /// Returns the index of the commitment.
+    #[payable]
+    pub fn shield(&mut self, amount: U128, commitment: Base64VecU8) -> u64 {
+        assert_one_yocto();
+        require!(
+            commitment.0.len() == 32,
+            "The commitment should be 32 bytes"
+        );
+        let shares = Self::internal_to_shares(amount.0);
+        self.internal_transfer(
+            &env::predecessor_account_id(),
+            &env::current_account_id(),
+            shares,
+            Some("Shield"),
+        );
+
+        let mut commitments: Vector<Vec<u8>> =
+            Vector::new(StorageKey::Shielded(ShieldedKey::ShieldedCommitments));
+        commitments.push(&commitment.0);
+        let mut pool = Self::internal_shielded_pool();
+        pool.balance = U128(pool.balance.0.saturating_add(shares));
+        pool.commitments_count = commitments.len();
+        Self::internal_save_shielded_pool(&pool);
+        commitments.len() - 1
+    }
+
+    /// Releases `amount` tokens from the pool to `receiver_id` if the verifier accepts `proof`.
+    /// Each nullifier can only be used once.
+    pub fn unshield(
+        &mut self,
+        nullifier: Base64VecU8,
+        proof: Base64VecU8,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> Promise {
+        let pool = Self::internal_shielded_pool();
+        let verifier_id = pool
+            .verifier_id
+            .unwrap_or_else(|| env::panic_str("The shielded verifier is not set"));
+        let shares = U128(Self::internal_to_shares(amount.0));
+        require!(
+            pool.balance.0 >= shares.0,
+            "The shielded pool balance is too low"
+        );
+        self.internal_unwrap_balance_of(&receiver_id);
+        self.internal_promise_budget()
+            .call(GAS_FOR_VERIFY_UNSHIELD, ZERO_TOKEN)
+            .call(GAS_FOR_ON_UNSHIELD_VERIFIED, ZERO_TOKEN)
+            .check();
+
+        // Lock the nullifier while the proof is being verified.
+        let mut nullifiers = Self::internal_shielded_nullifiers();
+        require!(
+            nullifiers.get(&nullifier.0).is_none(),
+            "The nullifier was already used"
+        );
+        nullifiers.insert(&nullifier.0, &NullifierState::Pending);
+
+        ext_shielded_verifier::ext(verifier_id)
+            .with_static_gas(GAS_FOR_VERIFY_UNSHIELD)
+            .verify_unshield(
+                pool.commitments_count,
+                nullifier.clone(),
+                receiver_id.clone(),
+                amount,
+                proof,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_ON_UNSHIELD_VERIFIED)
+                    .on_unshield_verified(nullifier, receiver_id, shares),
+            )
+    }
+
+    /// Callback releasing the shares once the verifier accepted the proof, or unlocking
+    /// the nullifier otherwise.
+    #[private]
+    pub fn on_unshield_verified(
+        &mut self,
+        nullifier: Base64VecU8,
+        receiver_id: AccountId,
+        shares: U128,
+    ) -> bool {
+        let verified = matches!(
+            env::promise_result(0),
+            PromiseResult::Successful(value)
+                if near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(false)
+        );
+        let mut nullifiers = Self::internal_shielded_nullifiers();
+        let mut pool = Self::internal_shielded_pool();
+        if !verified || pool.balance.0 < shares.0 {
+            nullifiers.remove(&nullifier.0);
+            return false;
+        }
+
+        nullifiers.insert(&nullifier.0, &NullifierState::Spent);
+        pool.balance = U128(pool.balance.0.saturating_sub(shares.0));
+        Self::internal_save_shielded_pool(&pool);
+        self.internal_transfer(
+            &env::current_account_id(),
+            &receiver_id,
+            shares.0,
+            Some("Unshield"),
+        );
+        true
+    }
+
+    /// Returns the state of the shielded pool.
+    pub fn get_shielded_pool(&self) -> ShieldedPool {
+        let mut pool = Self::internal_shielded_pool();
+        pool.balance = U128(Self::internal_to_amount(pool.balance.0));
+        pool
+    }
+
+    /// Returns whether a nullifier was already spent.
+    pub fn is_nullifier_spent(&self, nullifier: Base64VecU8) -> bool {
+        Self::internal_shielded_nullifiers().get(&nullifier.0) == Some(NullifierState::Spent)
+    }
+}
+
+impl Contract {
+    // The pool lives under its own storage keys instead of fields of `Contract`, so that the
+    // contract state has the same layout with or without the feature.
+    fn internal_shielded_pool() -> ShieldedPool {
+        LazyOption::<ShieldedPool>::new(StorageKey::Shielded(ShieldedKey::ShieldedPool), None)
+            .get()
+            .unwrap_or(ShieldedPool {
+                verifier_id: None,
+                balance: U128(0),
+                commitments_count: 0,
+            })
+    }
+
+    fn internal_save_shielded_pool(pool: &ShieldedPool) {
+        LazyOption::<ShieldedPool>::new(StorageKey::Shielded(ShieldedKey::ShieldedPool), None)
+            .set(pool);
+    }
+
+    fn internal_shielded_nullifiers() -> LookupMap<Vec<u8>, NullifierState> {
+        LookupMap::new(StorageKey::Shielded(ShieldedKey::ShieldedNullifiers))
+    }
+}
@@ -0,0 +1,152 @@
+//! Shared verification of messages signed off-chain by token holders and submitted by someone
+//! else (relayed onboarding, account merges, ...).
+//!
+//! Every signed message is `borsh((SIGNATURE_PREFIX, contract_id, domain, payload))`, so a
+//! signature can't be replayed on another contract or for another feature. Each account has a
+//! single monotonic nonce shared by all the features: a message is accepted if its nonce is at
+//! least the account's current nonce, which then moves past it. Gaps are allowed so that several
+//! messages can be signed in advance, and bumping the nonce invalidates all of them at once.
+
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::{env, near_bindgen, require, CurveType, PublicKey};
+
+use crate::*;
+
+/// Prefix of every signed message
+const SIGNATURE_PREFIX: &str = "near-ft";
+
+/// Domain of the bundles accepted by `onboard`
+pub(crate) const ONBOARDING_DOMAIN: &str = "onboard";
+
+/// Domain of the consents accepted by `link_and_merge`
+pub(crate) const LINK_DOMAIN: &str = "link_and_merge";
+
+/// Domain of the permits accepted by `ft_transfer_with_permit`
+pub(crate) const PERMIT_DOMAIN: &str = "ft_transfer_with_permit";
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the ed25519 key allowed to sign relayed messages on behalf of the caller,
+    /// or removes it when `None` is passed.
+    pub fn set_relay_key(&mut self, public_key: Option<PublicKey>) {
+        let account_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&account_id);
+        match public_key {
+            Some(public_key) => {
+                require!(
+                    public_key.curve_type() == CurveType::ED25519,
+                    "Only ed25519 relay keys are supported"
+                );
+                self.relay_keys.insert(&account_id, &public_key);
+            }
+            None => {
+                self.relay_keys.remove(&account_id);
+            }
+        }
+    }
+
+    /// Returns the relay key of an account, if any.
+    pub fn get_relay_key(&self, account_id: AccountId) -> Option<PublicKey> {
+        self.relay_keys.get(&account_id)
+    }
+
+    /// Returns the lowest nonce the next message relayed on behalf of an account can use.
+    pub fn get_relay_nonce(&self, account_id: AccountId) -> u64 {
+        self.relay_nonces.get(&account_id).unwrap_or(0)
+    }
+
+    /// Moves the caller's nonce up to `nonce`, invalidating every message signed with a lower one.
+    pub fn bump_relay_nonce(&mut self, nonce: u64) {
+        let account_id = env::predecessor_account_id();
+        require!(
+            nonce > self.relay_nonces.get(&account_id).unwrap_or(0),
+            "The nonce can only move forward"
+        );
+        self.relay_nonces.insert(&account_id, &nonce);
+    }
+}
+
+impl Contract {
+    /// Internal method for verifying a message signed with the relay key of `signer_id` for the
+    /// given domain. Rejects expired messages and consumes `nonce`.
+    pub(crate) fn internal_verify_signed<T: BorshSerialize>(
+        &mut self,
+        signer_id: &AccountId,
+        domain: &str,
+        payload: &T,
+        nonce: u64,
+        expires_at: Option<u64>,
+        signature: &[u8],
+    ) {
+        let public_key = self
+            .relay_keys
+            .get(signer_id)
+            .unwrap_or_else(|| env::panic_str("The signer has no relay key"));
+        self.internal_verify_signed_with_key(
+            signer_id,
+            &public_key,
+            domain,
+            payload,
+            nonce,
+            expires_at,
+            signature,
+        );
+    }
+
+    /// Internal method for verifying a message signed on behalf of `signer_id` with
+    /// `public_key` rather than its relay key, like the key of an implicit account. Rejects
+    /// expired messages and consumes `nonce`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn internal_verify_signed_with_key<T: BorshSerialize>(
+        &mut self,
+        signer_id: &AccountId,
+        public_key: &PublicKey,
+        domain: &str,
+        payload: &T,
+        nonce: u64,
+        expires_at: Option<u64>,
+        signature: &[u8],
+    ) {
+        if let Some(expires_at) = expires_at {
+            require!(
+                env::block_timestamp() <= expires_at,
+                "The signed message has expired"
+            );
+        }
+        let min_nonce = self.relay_nonces.get(signer_id).unwrap_or(0);
+        require!(nonce >= min_nonce, "The nonce was already used");
+        Self::internal_verify_ed25519(public_key, domain, payload, signature);
+
+        let next_nonce = nonce
+            .checked_add(1)
+            .unwrap_or_else(|| env::panic_str("The nonce is out of range"));
+        self.relay_nonces.insert(signer_id, &next_nonce);
+    }
+
+    /// Internal method for verifying a message signed with the ed25519 `public_key` for the
+    /// given domain.
+    pub(crate) fn internal_verify_ed25519<T: BorshSerialize>(
+        public_key: &PublicKey,
+        domain: &str,
+        payload: &T,
+        signature: &[u8],
+    ) {
+        let message = near_sdk::borsh::to_vec(&(
+            SIGNATURE_PREFIX,
+            env::current_account_id(),
+            domain,
+            payload,
+        ))
+        .unwrap_or_else(|_| env::abort());
+        let signature: [u8; 64] = signature
+            .try_into()
+            .unwrap_or_else(|_| env::panic_str("Invalid signature length"));
+        let public_key: [u8; 32] = public_key.as_bytes()[1..]
+            .try_into()
+            .unwrap_or_else(|_| env::panic_str("Invalid relay key"));
+        require!(
+            env::ed25519_verify(&signature, &message, &public_key),
+            "Invalid signature"
+        );
+    }
+}
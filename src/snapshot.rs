@@ -0,0 +1,103 @@
+//! Balance snapshots.
+//!
+//! The owner takes a snapshot to freeze the balances of an epoch, e.g. the voting power of a
+//! proposal. Balances aren't copied: when the balance of an account changes for the first time
+//! after a snapshot, its previous balance is checkpointed for that snapshot. The balance of an
+//! account at a snapshot is then the one of its first checkpoint from that snapshot on, or its
+//! current balance if it didn't change since. In the rebasing mode, the balances are converted
+//! at the index the snapshot was taken at.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+pub type SnapshotId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Snapshot {
+    /// The height of the block the snapshot was taken in.
+    pub block_height: U64,
+    pub total_supply: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Takes a snapshot of the balances and the total supply (owner only).
+    #[payable]
+    pub fn snapshot(&mut self) -> SnapshotId {
+        assert_one_yocto();
+        self.assert_owner();
+        let snapshot_id = self.snapshots.len();
+        let block_height = env::block_height();
+        self.snapshots.push(&Snapshot {
+            block_height: U64(block_height),
+            total_supply: U128(Self::internal_to_amount(self.total_supply)),
+        });
+        Self::internal_record_snapshot_index(snapshot_id);
+        SnapshotTaken {
+            snapshot_id,
+            block_height: &U64(block_height),
+        }
+        .emit();
+        snapshot_id
+    }
+
+    /// Returns the balance of an account when a snapshot was taken.
+    pub fn balance_of_at(&self, account_id: AccountId, snapshot_id: SnapshotId) -> U128 {
+        require!(
+            snapshot_id < self.snapshots.len(),
+            "The snapshot doesn't exist"
+        );
+        let checkpoints = self
+            .balance_checkpoints
+            .get(&account_id)
+            .unwrap_or_default();
+        let index = checkpoints.partition_point(|(id, _)| *id < snapshot_id);
+        let shares = match checkpoints.get(index) {
+            Some((_, balance)) => *balance,
+            None => self.accounts.get(&account_id).unwrap_or(0),
+        };
+        U128(Self::internal_to_amount_at(shares, snapshot_id))
+    }
+
+    /// Returns the total supply when a snapshot was taken.
+    pub fn total_supply_at(&self, snapshot_id: SnapshotId) -> U128 {
+        self.get_snapshot(snapshot_id)
+            .unwrap_or_else(|| env::panic_str("The snapshot doesn't exist"))
+            .total_supply
+    }
+
+    /// Returns a snapshot, or `null` if it doesn't exist.
+    pub fn get_snapshot(&self, snapshot_id: SnapshotId) -> Option<Snapshot> {
+        self.snapshots.get(snapshot_id)
+    }
+
+    /// Returns the number of snapshots taken, the ID of the next one.
+    pub fn get_snapshot_count(&self) -> u64 {
+        self.snapshots.len()
+    }
+}
+
+impl Contract {
+    /// Internal method for checkpointing the balance of an account about to change, if it
+    /// didn't change since the last snapshot.
+    pub(crate) fn internal_checkpoint_balance(&mut self, account_id: &AccountId, balance: u128) {
+        let Some(snapshot_id) = self.snapshots.len().checked_sub(1) else {
+            return;
+        };
+        let mut checkpoints = self.balance_checkpoints.get(account_id).unwrap_or_default();
+        if checkpoints
+            .last()
+            .is_some_and(|(last_id, _)| *last_id == snapshot_id)
+        {
+            return;
+        }
+        checkpoints.push((snapshot_id, balance));
+        self.balance_checkpoints.insert(account_id, &checkpoints);
+    }
+}
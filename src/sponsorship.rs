@@ -0,0 +1,121 @@
+//! Storage sponsorship pool.
+//!
+//! Sending tokens to an account that never registered fails, which is a poor first experience
+//! for new users. Anyone can fund a pool of NEAR with `fund_sponsorship_pool`: when a transfer
+//! targets an unregistered account, the pool pays its storage deposit and registers it first.
+//! Each sender can only get `sponsorship_cap` receivers registered this way, so that a single
+//! account can't drain the pool. The deposit of a sponsored account goes back to the pool when
+//! it unregisters, rather than to the account.
+
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, NearSchema, Promise};
+
+use crate::storage::StorageManagement;
+use crate::*;
+
+/// How many receivers a sender can get registered by the pool until the owner changes it.
+pub const DEFAULT_SPONSORSHIP_CAP: u32 = 10;
+
+// The state of the sponsorship pool.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SponsorshipPoolView {
+    /// The NEAR left in the pool.
+    pub balance: NearToken,
+    /// How many receivers each sender can get registered by the pool.
+    pub cap_per_account: u32,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Adds the attached NEAR to the sponsorship pool. Anyone can call it.
+    #[payable]
+    pub fn fund_sponsorship_pool(&mut self) -> NearToken {
+        let amount = env::attached_deposit();
+        require!(
+            amount.gt(&ZERO_TOKEN),
+            "Requires a positive attached deposit"
+        );
+        self.sponsorship_pool = self.sponsorship_pool.saturating_add(amount);
+        self.sponsorship_pool
+    }
+
+    /// Sends `amount` of the pool to the owner (owner only).
+    #[payable]
+    pub fn withdraw_sponsorship_pool(&mut self, amount: NearToken) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        self.sponsorship_pool = self
+            .sponsorship_pool
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The sponsorship pool is too low"));
+        Promise::new(self.owner_id.clone()).transfer(amount)
+    }
+
+    /// Sets how many receivers each sender can get registered by the pool (owner only). A cap
+    /// of 0 turns the sponsorship off.
+    #[payable]
+    pub fn set_sponsorship_cap(&mut self, cap_per_account: u32) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.sponsorship_cap = cap_per_account;
+    }
+
+    /// Returns the NEAR left in the sponsorship pool and the cap of each sender.
+    pub fn get_sponsorship_pool(&self) -> SponsorshipPoolView {
+        SponsorshipPoolView {
+            balance: self.sponsorship_pool,
+            cap_per_account: self.sponsorship_cap,
+        }
+    }
+
+    /// Returns how many receivers the pool registered for a sender.
+    pub fn get_sponsored_registrations(&self, account_id: AccountId) -> u32 {
+        self.sponsored_registrations.get(&account_id).unwrap_or(0)
+    }
+}
+
+impl Contract {
+    /// Internal method for registering `receiver_id` at the expense of the pool before
+    /// `sender_id` transfers to it. Does nothing if the receiver is registered, the sender
+    /// reached its cap or the pool can't afford the deposit, the transfer failing as usual then.
+    pub(crate) fn internal_sponsor_receiver(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+    ) {
+        if self.accounts.contains_key(receiver_id) {
+            return;
+        }
+        let count = self.sponsored_registrations.get(sender_id).unwrap_or(0);
+        if count >= self.sponsorship_cap {
+            return;
+        }
+        let Some(remaining) = self
+            .sponsorship_pool
+            .checked_sub(Self::storage_balance_bounds().min)
+        else {
+            return;
+        };
+        self.sponsorship_pool = remaining;
+        self.sponsored_registrations.insert(sender_id, &(count + 1));
+        self.sponsored_accounts.insert(receiver_id);
+        log!(
+            "The storage of {} is paid by the sponsorship pool",
+            receiver_id
+        );
+        self.internal_register_account(receiver_id);
+    }
+
+    /// Internal method for returning the deposit of an unregistered account to the pool if the
+    /// pool paid it. Returns whether it did.
+    pub(crate) fn internal_refund_sponsorship(&mut self, account_id: &AccountId) -> bool {
+        if !self.sponsored_accounts.remove(account_id) {
+            return false;
+        }
+        self.sponsorship_pool = self
+            .sponsorship_pool
+            .saturating_add(Self::storage_balance_bounds().min);
+        true
+    }
+}
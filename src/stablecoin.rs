@@ -0,0 +1,444 @@
+//! Collateralized mode, compiled with the `stablecoin` feature.
+//!
+//! Users deposit NEAR into a vault and mint tokens against it, up to the value of the collateral
+//! divided by the collateral ratio. The collateral is valued at the price pushed by the oracle
+//! account, which must not be older than `max_price_age_sec` for mints and withdrawals. Once the
+//! owner configures the `COLLATERAL_PRICE_FEED` price feed, the collateral is valued at the
+//! median of its oracles instead, as long as it has enough fresh answers. Burning
+//! tokens repays the debt of the vault and frees its collateral. Once the collateral of a vault
+//! is worth less than its debt times the liquidation ratio, anyone can liquidate it: the
+//! liquidator burns the whole debt and receives its value in collateral, plus a bonus.
+//!
+//! Collateral values are computed in nanoNEAR, rounding the collateral down, so that the products
+//! with the price don't overflow.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LazyOption, LookupMap};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema, Promise};
+
+use crate::internal::pro_rata;
+use crate::*;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+const YOCTO_PER_NANO_NEAR: u128 = 1_000_000_000_000_000;
+const NANO_NEAR_PER_NEAR: u128 = 1_000_000_000;
+
+/// The price feed of NEAR, in tokens, valuing the collateral once configured.
+pub const COLLATERAL_PRICE_FEED: &str = "near";
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct StablecoinConfig {
+    /// The account pushing the price of NEAR.
+    pub oracle_id: AccountId,
+    /// The collateral value a vault needs after a mint or a withdrawal, in basis points of its
+    /// debt.
+    pub collateral_ratio_bps: u32,
+    /// The collateral value below which a vault can be liquidated, in basis points of its debt.
+    pub liquidation_ratio_bps: u32,
+    /// The share of the repaid debt the liquidator receives on top of it, in basis points.
+    pub liquidation_bonus_bps: u32,
+    /// How long a price can be used for, in seconds.
+    pub max_price_age_sec: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollateralPrice {
+    /// The tokens (in their smallest unit) 1 NEAR is worth.
+    pub tokens_per_near: U128,
+    /// The timestamp (in nanoseconds) when the oracle pushed the price.
+    pub updated_at: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Vault {
+    /// The NEAR deposited, in yoctoNEAR.
+    pub collateral: U128,
+    /// The tokens minted against the collateral and not repaid yet.
+    pub debt: U128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+pub(crate) struct StablecoinState {
+    config: Option<StablecoinConfig>,
+    price: Option<CollateralPrice>,
+    pub(crate) total_collateral: u128,
+    total_debt: u128,
+}
+
+// The health of the whole system, for monitoring.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StablecoinHealth {
+    /// The NEAR deposited in all the vaults, in yoctoNEAR.
+    pub total_collateral: U128,
+    /// The tokens minted against collateral and not repaid yet.
+    pub total_debt: U128,
+    pub price: Option<CollateralPrice>,
+    /// Whether the price can be used for mints and withdrawals.
+    pub price_is_fresh: bool,
+    /// The value of the whole collateral in basis points of the whole debt, if there's a price
+    /// and a debt.
+    pub collateral_ratio_bps: Option<U128>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the oracle and the ratios of the collateralized mode (owner only).
+    #[payable]
+    pub fn set_stablecoin_config(&mut self, config: StablecoinConfig) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.internal_assert_not_wrapped();
+        require!(
+            config.liquidation_ratio_bps as u128 >= BPS_DENOMINATOR
+                && config.collateral_ratio_bps >= config.liquidation_ratio_bps,
+            "The collateral ratio should be at least the liquidation ratio, itself at least 100%"
+        );
+        let mut state = Self::internal_stablecoin_state();
+        state.config = Some(config);
+        Self::internal_save_stablecoin_state(&state);
+    }
+
+    /// Pushes the price of NEAR, in tokens (oracle only).
+    pub fn push_collateral_price(&mut self, tokens_per_near: U128) {
+        let mut state = Self::internal_stablecoin_state();
+        let config = Self::internal_stablecoin_config(&state);
+        require!(
+            env::predecessor_account_id() == config.oracle_id,
+            "Only the oracle can call this method"
+        );
+        require!(tokens_per_near.0 > 0, "The price should be positive");
+        require!(
+            self.price_feeds
+                .get(&COLLATERAL_PRICE_FEED.to_string())
+                .is_none(),
+            "The collateral price comes from the price feed"
+        );
+        state.price = Some(CollateralPrice {
+            tokens_per_near,
+            updated_at: U64(env::block_timestamp()),
+        });
+        Self::internal_save_stablecoin_state(&state);
+    }
+
+    /// Adds the attached NEAR to the caller's vault. The caller must be registered.
+    #[payable]
+    pub fn deposit_collateral(&mut self) -> Vault {
+        let account_id = env::predecessor_account_id();
+        let amount = env::attached_deposit().as_yoctonear();
+        require!(amount > 0, "Requires positive attached deposit");
+        self.internal_unwrap_balance_of(&account_id);
+        let mut state = Self::internal_stablecoin_state();
+        Self::internal_stablecoin_config(&state);
+        let mut vault = Self::internal_vaults().get(&account_id).unwrap_or_default();
+        vault.collateral = U128(vault.collateral.0 + amount);
+        state.total_collateral += amount;
+        Self::internal_save_stablecoin_state(&state);
+        Self::internal_vaults().insert(&account_id, &vault);
+        vault
+    }
+
+    /// Mints `amount` tokens to the caller against the collateral of its vault.
+    #[payable]
+    pub fn mint_against_collateral(&mut self, amount: U128) -> Vault {
+        assert_one_yocto();
+        self.internal_assert_not_paused(Pausable::Mints);
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let account_id = env::predecessor_account_id();
+        self.internal_assert_not_frozen(&account_id);
+        let mut state = Self::internal_stablecoin_state();
+        let mut vault = Self::internal_get_vault(&account_id);
+        vault.debt = U128(vault.debt.0 + amount.0);
+        self.internal_assert_collateralized(&state, &vault);
+        state.total_debt += amount.0;
+        Self::internal_save_stablecoin_state(&state);
+        Self::internal_vaults().insert(&account_id, &vault);
+        self.internal_mint(
+            &account_id,
+            Self::internal_to_shares(amount.0),
+            Some("Collateralized mint"),
+        );
+        vault
+    }
+
+    /// Burns `amount` of the caller's tokens to repay the debt of its vault.
+    #[payable]
+    pub fn repay_debt(&mut self, amount: U128) -> Vault {
+        assert_one_yocto();
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let account_id = env::predecessor_account_id();
+        let mut state = Self::internal_stablecoin_state();
+        let mut vault = Self::internal_get_vault(&account_id);
+        require!(amount.0 <= vault.debt.0, "The amount exceeds the debt");
+        vault.debt = U128(vault.debt.0 - amount.0);
+        state.total_debt -= amount.0;
+        Self::internal_save_stablecoin_state(&state);
+        Self::internal_vaults().insert(&account_id, &vault);
+        self.internal_burn(
+            &account_id,
+            Self::internal_to_shares(amount.0),
+            Some("Debt repayment"),
+        );
+        vault
+    }
+
+    /// Sends `amount` yoctoNEAR of the caller's collateral back, as long as the vault stays
+    /// collateralized. The vault is removed once it's empty.
+    #[payable]
+    pub fn withdraw_collateral(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        self.internal_assert_not_paused(Pausable::Mints);
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let account_id = env::predecessor_account_id();
+        self.internal_assert_not_frozen(&account_id);
+        let mut state = Self::internal_stablecoin_state();
+        let mut vault = Self::internal_get_vault(&account_id);
+        require!(
+            amount.0 <= vault.collateral.0,
+            "The amount exceeds the collateral"
+        );
+        vault.collateral = U128(vault.collateral.0 - amount.0);
+        if vault.debt.0 > 0 {
+            self.internal_assert_collateralized(&state, &vault);
+        }
+        state.total_collateral -= amount.0;
+        Self::internal_save_stablecoin_state(&state);
+        if vault.collateral.0 == 0 && vault.debt.0 == 0 {
+            Self::internal_vaults().remove(&account_id);
+        } else {
+            Self::internal_vaults().insert(&account_id, &vault);
+        }
+        Promise::new(account_id).transfer(NearToken::from_yoctonear(amount.0))
+    }
+
+    /// Liquidates a vault worth less than its debt times the liquidation ratio: burns the whole
+    /// debt from the caller's tokens, and sends the caller its value in collateral plus the
+    /// liquidation bonus. The rest of the collateral stays in the vault.
+    #[payable]
+    pub fn liquidate_vault(&mut self, account_id: AccountId) -> Promise {
+        assert_one_yocto();
+        let liquidator_id = env::predecessor_account_id();
+        let mut state = Self::internal_stablecoin_state();
+        let config = Self::internal_stablecoin_config(&state);
+        let price = self.internal_fresh_price(&state);
+        let mut vault = Self::internal_get_vault(&account_id);
+        require!(vault.debt.0 > 0, "The vault has no debt");
+        require!(
+            Self::internal_collateral_value(vault.collateral.0, price)
+                < pro_rata(
+                    vault.debt.0,
+                    config.liquidation_ratio_bps.into(),
+                    BPS_DENOMINATOR
+                ),
+            "The vault is collateralized enough"
+        );
+
+        let debt = vault.debt.0;
+        let owed = pro_rata(
+            debt,
+            BPS_DENOMINATOR + config.liquidation_bonus_bps as u128,
+            BPS_DENOMINATOR,
+        );
+        let collateral = std::cmp::min(
+            pro_rata(owed, NANO_NEAR_PER_NEAR, price).saturating_mul(YOCTO_PER_NANO_NEAR),
+            vault.collateral.0,
+        );
+        vault.debt = U128(0);
+        vault.collateral = U128(vault.collateral.0 - collateral);
+        state.total_debt -= debt;
+        state.total_collateral -= collateral;
+        Self::internal_save_stablecoin_state(&state);
+        if vault.collateral.0 == 0 {
+            Self::internal_vaults().remove(&account_id);
+        } else {
+            Self::internal_vaults().insert(&account_id, &vault);
+        }
+        self.internal_burn(
+            &liquidator_id,
+            Self::internal_to_shares(debt),
+            Some("Vault liquidation"),
+        );
+        VaultLiquidated {
+            account_id: &account_id,
+            liquidator_id: &liquidator_id,
+            debt: &U128(debt),
+            collateral: &U128(collateral),
+        }
+        .emit();
+        Promise::new(liquidator_id).transfer(NearToken::from_yoctonear(collateral))
+    }
+
+    /// Returns the configuration of the collateralized mode, or `null` if it isn't set.
+    pub fn get_stablecoin_config(&self) -> Option<StablecoinConfig> {
+        Self::internal_stablecoin_state().config
+    }
+
+    /// Returns the vault of an account, or `null` if it has none.
+    pub fn get_vault(&self, account_id: AccountId) -> Option<Vault> {
+        Self::internal_vaults().get(&account_id)
+    }
+
+    /// Returns the collateral value of a vault in basis points of its debt, or `null` if it has
+    /// no debt or there's no price.
+    pub fn get_vault_ratio(&self, account_id: AccountId) -> Option<U128> {
+        let state = Self::internal_stablecoin_state();
+        let vault = Self::internal_vaults().get(&account_id)?;
+        self.internal_ratio_bps(&state, vault.collateral.0, vault.debt.0)
+    }
+
+    /// Returns the totals of all the vaults along with the price.
+    pub fn get_stablecoin_health(&self) -> StablecoinHealth {
+        let state = Self::internal_stablecoin_state();
+        let (price, price_is_fresh) = match self.internal_collateral_price(&state) {
+            Some((price, is_fresh)) => (Some(price), is_fresh),
+            None => (None, false),
+        };
+        StablecoinHealth {
+            total_collateral: U128(state.total_collateral),
+            total_debt: U128(state.total_debt),
+            collateral_ratio_bps: self.internal_ratio_bps(
+                &state,
+                state.total_collateral,
+                state.total_debt,
+            ),
+            price,
+            price_is_fresh,
+        }
+    }
+}
+
+impl Contract {
+    // The state lives under its own storage keys instead of fields of `Contract`, so that the
+    // contract state has the same layout with or without the feature.
+    pub(crate) fn internal_stablecoin_state() -> StablecoinState {
+        LazyOption::<StablecoinState>::new(
+            StorageKey::Stablecoin(StablecoinKey::StablecoinState),
+            None,
+        )
+        .get()
+        .unwrap_or_default()
+    }
+
+    fn internal_save_stablecoin_state(state: &StablecoinState) {
+        LazyOption::<StablecoinState>::new(
+            StorageKey::Stablecoin(StablecoinKey::StablecoinState),
+            None,
+        )
+        .set(state);
+    }
+
+    fn internal_vaults() -> LookupMap<AccountId, Vault> {
+        LookupMap::new(StorageKey::Stablecoin(StablecoinKey::Vaults))
+    }
+
+    fn internal_get_vault(account_id: &AccountId) -> Vault {
+        Self::internal_vaults()
+            .get(account_id)
+            .unwrap_or_else(|| env::panic_str("The account has no vault"))
+    }
+
+    fn internal_stablecoin_config(state: &StablecoinState) -> StablecoinConfig {
+        state
+            .config
+            .clone()
+            .unwrap_or_else(|| env::panic_str("The collateralized mode isn't configured"))
+    }
+
+    fn internal_price_is_fresh(config: &StablecoinConfig, price: &CollateralPrice) -> bool {
+        env::block_timestamp() <= price.updated_at.0 + config.max_price_age_sec * NANOS_PER_SEC
+    }
+
+    /// Internal method for getting the price of NEAR along with whether it's fresh: the price
+    /// of the feed if it's configured, which only has a price while it's fresh, or else the
+    /// price pushed by the oracle account.
+    fn internal_collateral_price(
+        &self,
+        state: &StablecoinState,
+    ) -> Option<(CollateralPrice, bool)> {
+        if self
+            .price_feeds
+            .get(&COLLATERAL_PRICE_FEED.to_string())
+            .is_some()
+        {
+            return self
+                .internal_oracle_price(COLLATERAL_PRICE_FEED)
+                .map(|price| {
+                    (
+                        CollateralPrice {
+                            tokens_per_near: price.price,
+                            updated_at: price.updated_at,
+                        },
+                        true,
+                    )
+                });
+        }
+        let price = state.price.clone()?;
+        let is_fresh = state
+            .config
+            .as_ref()
+            .is_some_and(|config| Self::internal_price_is_fresh(config, &price));
+        Some((price, is_fresh))
+    }
+
+    /// Internal method for checking whether the collateralized mode is configured without a
+    /// fresh price, which blocks the operations valuing the collateral.
+    pub(crate) fn internal_collateral_price_is_stale(&self) -> bool {
+        let state = Self::internal_stablecoin_state();
+        state.config.is_some() && !matches!(self.internal_collateral_price(&state), Some((_, true)))
+    }
+
+    /// Internal method for getting the price of NEAR, panicking if it's too old.
+    fn internal_fresh_price(&self, state: &StablecoinState) -> u128 {
+        match self.internal_collateral_price(state) {
+            Some((price, true)) => price.tokens_per_near.0,
+            _ => env::panic_str("The collateral price is stale"),
+        }
+    }
+
+    /// Internal method for valuing `collateral` yoctoNEAR in tokens.
+    fn internal_collateral_value(collateral: u128, tokens_per_near: u128) -> u128 {
+        pro_rata(
+            collateral / YOCTO_PER_NANO_NEAR,
+            tokens_per_near,
+            NANO_NEAR_PER_NEAR,
+        )
+    }
+
+    /// Internal method for asserting that a vault is collateralized at the collateral ratio.
+    fn internal_assert_collateralized(&self, state: &StablecoinState, vault: &Vault) {
+        let config = Self::internal_stablecoin_config(state);
+        let price = self.internal_fresh_price(state);
+        require!(
+            Self::internal_collateral_value(vault.collateral.0, price)
+                >= pro_rata(
+                    vault.debt.0,
+                    config.collateral_ratio_bps.into(),
+                    BPS_DENOMINATOR
+                ),
+            "The vault would be undercollateralized"
+        );
+    }
+
+    fn internal_ratio_bps(
+        &self,
+        state: &StablecoinState,
+        collateral: u128,
+        debt: u128,
+    ) -> Option<U128> {
+        let (price, _) = self.internal_collateral_price(state)?;
+        if debt == 0 {
+            return None;
+        }
+        let value = Self::internal_collateral_value(collateral, price.tokens_per_near.0);
+        Some(U128(pro_rata(value, BPS_DENOMINATOR, debt)))
+    }
+}
@@ -1,79 +1,47 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, log, AccountId, NearSchema, Promise};
+use near_sdk::{assert_one_yocto, env, log, require, AccountId, NearSchema, Promise, StorageUsage};
+
+pub use near_ft_interface::storage::{
+    ext_storage_management, StorageBalance, StorageBalanceBounds, StorageManagement,
+};
 
 use crate::*;
 
-// The structure that will be returned for the methods:
-// * `storage_deposit`
-// * `storage_withdraw`
-// * `storage_balance_of`
-// The `total` and `available` values are string representations of unsigned
-// 128-bit integers showing the balance of a specific account in yoctoⓃ.
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
-#[borsh(crate = "near_sdk::borsh")]
-#[serde(crate = "near_sdk::serde")]
-pub struct StorageBalance {
-    pub total: NearToken,
-    pub available: NearToken,
-}
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// The storage an entry of the `accounts` map takes for the longest possible account ID: the
+/// 1-byte prefix and the 4-byte length and 64 bytes of the borsh-serialized key, the 16-byte
+/// balance, and the 40 bytes the runtime charges on top of every record. The baseline contract
+/// measured it on initialization and stored it in its state, which `migrate` drops.
+pub const BYTES_FOR_LONGEST_ACCOUNT_ID: StorageUsage = 1 + 4 + 64 + 16 + 40;
+
+/// The storage an entry of the `storage_depositors` map takes for the longest possible account
+/// IDs: the 1-byte prefix, the borsh-serialized registered and depositor account IDs, and the 40
+/// bytes the runtime charges on top of every record.
+pub const BYTES_FOR_LONGEST_DEPOSITOR_RECORD: StorageUsage = 1 + 4 + 64 + 4 + 64 + 40;
+
+/// The most storage a registration takes: the entry of the account, and the record of who paid
+/// for it when it didn't. A registration paid by the sponsorship pool records the account in a
+/// set instead, which takes less.
+pub const BYTES_PER_REGISTRATION: StorageUsage =
+    BYTES_FOR_LONGEST_ACCOUNT_ID + BYTES_FOR_LONGEST_DEPOSITOR_RECORD;
 
-// The below structure will be returned for the method `storage_balance_bounds`.
-// Both `min` and `max` are string representations of unsigned 128-bit integers.
-//
-// `min` is the amount of tokens required to start using this contract at all
-// (eg to register with the contract). If a new contract user attaches `min`
-// NEAR to a `storage_deposit` call, subsequent calls to `storage_balance_of`
-// for this user must show their `total` equal to `min` and `available=0` .
-//
-// A contract may implement `max` equal to `min` if it only charges for initial
-// registration, and does not adjust per-user storage over time. A contract
-// which implements `max` must refund deposits that would increase a user's
-// storage balance beyond this amount.
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema)]
+/// How long a force unregister request has to wait before it can be confirmed
+pub const FORCE_UNREGISTER_DELAY_SEC: u64 = 86_400;
+
+/// The most accounts a single `storage_sweep` call can unregister
+pub const MAX_SWEPT_ACCOUNTS: usize = 100;
+
+// The totals of the zero-balance accounts unregistered by `storage_sweep`.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Default)]
 #[borsh(crate = "near_sdk::borsh")]
 #[serde(crate = "near_sdk::serde")]
-pub struct StorageBalanceBounds {
-    pub min: NearToken,
-    pub max: Option<NearToken>,
-}
-
-pub trait StorageManagement {
-    /************************************/
-    /* CHANGE METHODS on fungible token */
-    /************************************/
-    // Payable method that receives an attached deposit of Ⓝ for a given account.
-    //
-    // If `account_id` is omitted, the deposit MUST go toward predecessor account.
-    // If provided, deposit MUST go toward this account. If invalid, contract MUST
-    // panic.
-    //
-    // If `registration_only=true`, contract MUST refund above the minimum balance
-    // if the account wasn't registered and refund full deposit if already
-    // registered.
-    //
-    // The `storage_balance_of.total` + `attached_deposit` in excess of
-    // `storage_balance_bounds.max` must be refunded to predecessor account.
-    //
-    // Returns the StorageBalance structure showing updated balances.
-    fn storage_deposit(
-        &mut self,
-        account_id: Option<AccountId>,
-        registration_only: Option<bool>,
-    ) -> StorageBalance;
-
-    /****************/
-    /* VIEW METHODS */
-    /****************/
-    // Returns minimum and maximum allowed balance amounts to interact with this
-    // contract. See StorageBalanceBounds.
-    fn storage_balance_bounds(&self) -> StorageBalanceBounds;
-
-    // Returns the StorageBalance structure of the valid `account_id`
-    // provided. Must panic if `account_id` is invalid.
-    //
-    // If `account_id` is not registered, must return `null`.
-    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
+pub struct StorageSweepStats {
+    pub swept_accounts: U64,
+    /// The storage deposits refunded to the depositors.
+    pub refunded: NearToken,
 }
 
 #[near_bindgen]
@@ -85,6 +53,7 @@ impl StorageManagement for Contract {
         account_id: Option<AccountId>,
         registration_only: Option<bool>,
     ) -> StorageBalance {
+        self.internal_assert_not_paused(Pausable::Storage);
         // Get the amount of $NEAR to deposit
         let amount = env::attached_deposit();
         // If an account was specified, use that. Otherwise, use the predecessor account.
@@ -98,32 +67,98 @@ impl StorageManagement for Contract {
             }
         // Register the account and refund any excess $NEAR
         } else {
-            // Get the minimum required storage and ensure the deposit is at least that amount
-            let min_balance = self.storage_balance_bounds().min;
-            if amount < min_balance {
-                env::panic_str("The attached deposit is less than the minimum storage balance");
-            }
-
-            // Register the account
-            self.internal_register_account(&account_id);
-            // Perform a refund
-            let refund = amount.saturating_sub(min_balance);
-            if refund.gt(&ZERO_TOKEN) {
-                Promise::new(env::predecessor_account_id()).transfer(refund);
-            }
+            self.internal_storage_register(&account_id, amount);
         }
 
         // Return the storage balance of the account
         StorageBalance {
-            total: self.storage_balance_bounds().min,
+            total: Self::storage_balance_bounds().min,
             available: ZERO_TOKEN,
         }
     }
 
-    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
-        // Calculate the required storage balance by taking the bytes for the longest account ID and multiplying by the current byte cost
+    /// A stub kept for NEP-145 compliance: the storage balance is always exactly the minimum,
+    /// so nothing is ever available and any positive amount is rejected. The deposit is only
+    /// returned by `storage_unregister`.
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<NearToken>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let storage_balance = Self::storage_balance_of(account_id.clone()).unwrap_or_else(|| {
+            env::panic_str(format!("The account {} is not registered", account_id).as_str())
+        });
+        if amount.is_some_and(|amount| !amount.is_zero()) {
+            env::panic_str(
+                "Nothing can be withdrawn: the storage balance is always exactly the minimum, unregister to get it back",
+            );
+        }
+        storage_balance
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        self.internal_assert_not_paused(Pausable::Storage);
+        let account_id = env::predecessor_account_id();
+        let Some(balance) = self.accounts.get(&account_id) else {
+            log!("The account {} is not registered", account_id);
+            return false;
+        };
+        // Even a forced unregistration would leave the pending records paying out to or
+        // refunding an account that no longer exists.
+        require!(
+            self.pending_references.get(&account_id).unwrap_or(0) == 0,
+            "Can't unregister the account while pending transfers refer to it"
+        );
+
+        if balance > 0 {
+            require!(
+                force.unwrap_or(false),
+                "Can't unregister the account with a positive balance without force"
+            );
+            // A single mistaken flag must not burn a large balance: it needs a request that
+            // waited out the delay.
+            if balance > self.force_unregister_threshold {
+                let requested_at = self
+                    .force_unregister_requests
+                    .get(&account_id)
+                    .unwrap_or_else(|| {
+                        env::panic_str(
+                            "Burning this balance needs a force unregister request first",
+                        )
+                    });
+                require!(
+                    env::block_timestamp()
+                        >= requested_at + FORCE_UNREGISTER_DELAY_SEC * NANOS_PER_SECOND,
+                    "The force unregister request is still pending"
+                );
+            }
+            self.internal_burn(&account_id, balance, Some("Force unregister"));
+        }
+
+        // The deposit goes back to whoever paid it, or to the sponsorship pool if it did. The
+        // attached yoctoNEAR goes back to the account.
+        let one_yocto = NearToken::from_yoctonear(1);
+        match self.internal_unregister_account(&account_id) {
+            Some(depositor_id) if depositor_id == account_id => {
+                Promise::new(account_id)
+                    .transfer(Self::storage_balance_bounds().min.saturating_add(one_yocto));
+            }
+            Some(depositor_id) => {
+                Promise::new(depositor_id).transfer(Self::storage_balance_bounds().min);
+                Promise::new(account_id).transfer(one_yocto);
+            }
+            None => {
+                Promise::new(account_id).transfer(one_yocto);
+            }
+        }
+        true
+    }
+
+    fn storage_balance_bounds() -> StorageBalanceBounds {
+        // Calculate the required storage balance by taking the bytes of the largest registration and multiplying by the current byte cost
         let required_storage_balance =
-            env::storage_byte_cost().saturating_mul(self.bytes_for_longest_account_id.into());
+            env::storage_byte_cost().saturating_mul(BYTES_PER_REGISTRATION.into());
 
         // Storage balance bounds will have min == max == required_storage_balance
         StorageBalanceBounds {
@@ -132,11 +167,11 @@ impl StorageManagement for Contract {
         }
     }
 
-    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+    fn storage_balance_of(account_id: AccountId) -> Option<StorageBalance> {
         // Get the storage balance of the account. Available will always be 0 since you can't overpay for storage.
-        if self.accounts.contains_key(&account_id) {
+        if Self::stateless_accounts().contains_key(&account_id) {
             Some(StorageBalance {
-                total: self.storage_balance_bounds().min,
+                total: Self::storage_balance_bounds().min,
                 available: ZERO_TOKEN,
             })
         } else {
@@ -144,3 +179,307 @@ impl StorageManagement for Contract {
         }
     }
 }
+
+#[near_bindgen]
+impl Contract {
+    /// Declares the caller as the storage payer of all its sub-accounts (e.g. `game.near` for
+    /// `alice.game.near`) and adds the attached deposit to its sponsorship balance.
+    /// `storage_deposit` calls for matching accounts are then charged to the sponsor.
+    #[payable]
+    pub fn register_storage_sponsor(&mut self) -> NearToken {
+        self.internal_assert_not_paused(Pausable::Storage);
+        let domain = env::predecessor_account_id();
+        let balance = self
+            .storage_sponsors
+            .get(&domain)
+            .unwrap_or(ZERO_TOKEN)
+            .saturating_add(env::attached_deposit());
+        self.storage_sponsors.insert(&domain, &balance);
+        self.storage_sponsorship_total = self
+            .storage_sponsorship_total
+            .saturating_add(env::attached_deposit());
+        balance
+    }
+
+    /// Withdraws `amount` (or everything) from the caller's sponsorship balance. The caller
+    /// stops sponsoring its sub-accounts once the balance is empty.
+    pub fn withdraw_storage_sponsorship(&mut self, amount: Option<NearToken>) -> NearToken {
+        self.internal_assert_not_paused(Pausable::Storage);
+        let domain = env::predecessor_account_id();
+        let balance = self
+            .storage_sponsors
+            .get(&domain)
+            .unwrap_or_else(|| env::panic_str("The account is not a storage sponsor"));
+        let amount = amount.unwrap_or(balance);
+        let remaining = balance
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The sponsorship balance is too low"));
+
+        if remaining.gt(&ZERO_TOKEN) {
+            self.storage_sponsors.insert(&domain, &remaining);
+        } else {
+            self.storage_sponsors.remove(&domain);
+        }
+        self.storage_sponsorship_total = self.storage_sponsorship_total.saturating_sub(amount);
+        if amount.gt(&ZERO_TOKEN) {
+            Promise::new(domain).transfer(amount);
+        }
+        remaining
+    }
+
+    /// Unregisters the given accounts whose balance is zero, refunding their storage deposits to
+    /// whoever paid them. Callable by anyone. The accounts holding a positive balance, the fee
+    /// buckets, the dust account and the accounts pending records pay out to or refund are
+    /// skipped. Returns the number of unregistered accounts.
+    pub fn storage_sweep(&mut self, accounts: Vec<AccountId>) -> u32 {
+        self.internal_assert_not_paused(Pausable::Storage);
+        require!(
+            accounts.len() <= MAX_SWEPT_ACCOUNTS,
+            format!("Can't sweep more than {} accounts", MAX_SWEPT_ACCOUNTS)
+        );
+        let min_balance = Self::storage_balance_bounds().min;
+        let mut swept = 0;
+        for account_id in accounts {
+            if self.accounts.get(&account_id) != Some(0)
+                || self.internal_is_unsweepable(&account_id)
+            {
+                continue;
+            }
+            if let Some(depositor_id) = self.internal_unregister_account(&account_id) {
+                self.storage_sweep_stats.refunded = self
+                    .storage_sweep_stats
+                    .refunded
+                    .saturating_add(min_balance);
+                Promise::new(depositor_id).transfer(min_balance);
+            }
+            swept += 1;
+        }
+        self.storage_sweep_stats.swept_accounts =
+            U64(self.storage_sweep_stats.swept_accounts.0 + swept as u64);
+        log!("Swept {} accounts", swept);
+        swept
+    }
+
+    /// Returns the totals of the accounts swept so far.
+    pub fn get_storage_sweep_stats(&self) -> StorageSweepStats {
+        self.storage_sweep_stats.clone()
+    }
+
+    /// Returns the account which paid the storage deposit of `account_id`, if it isn't the
+    /// account itself.
+    pub fn get_storage_depositor(&self, account_id: AccountId) -> Option<AccountId> {
+        self.storage_depositors.get(&account_id)
+    }
+
+    /// Returns the sponsorship balance left for the sub-accounts of `domain`, if any.
+    pub fn get_storage_sponsorship(&self, domain: AccountId) -> Option<NearToken> {
+        self.storage_sponsors.get(&domain)
+    }
+
+    /// Starts the delay after which the caller can force-unregister its account although it
+    /// holds more than `force_unregister_threshold`. Returns the timestamp (in nanoseconds)
+    /// from which `storage_unregister(force=true)` can be called.
+    #[payable]
+    pub fn request_force_unregister(&mut self) -> U64 {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        self.internal_unwrap_balance_of(&account_id);
+        let now = env::block_timestamp();
+        self.force_unregister_requests.insert(&account_id, &now);
+        let confirmable_at = now + FORCE_UNREGISTER_DELAY_SEC * NANOS_PER_SECOND;
+        log!(
+            "Force unregister of {} can be confirmed from {}",
+            account_id,
+            confirmable_at
+        );
+        U64(confirmable_at)
+    }
+
+    /// Drops the caller's force unregister request.
+    pub fn cancel_force_unregister(&mut self) {
+        let account_id = env::predecessor_account_id();
+        require!(
+            self.force_unregister_requests.remove(&account_id).is_some(),
+            "There is no force unregister request"
+        );
+    }
+
+    /// Returns the timestamp (in nanoseconds) of the force unregister request of an account, if any.
+    pub fn get_force_unregister_request(&self, account_id: AccountId) -> Option<U64> {
+        self.force_unregister_requests.get(&account_id).map(U64)
+    }
+
+    /// Sets the balance above which force-unregistering an account needs a delayed request
+    /// (owner only).
+    #[payable]
+    pub fn set_force_unregister_threshold(&mut self, threshold: U128) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.force_unregister_threshold = threshold.0;
+    }
+
+    /// Returns the balance above which force-unregistering an account needs a delayed request.
+    pub fn get_force_unregister_threshold(&self) -> U128 {
+        U128(self.force_unregister_threshold)
+    }
+}
+
+impl Contract {
+    /// Internal method for removing a registered account. Returns the account to refund the
+    /// storage deposit to, or `None` if it went back to the sponsorship pool.
+    pub(crate) fn internal_unregister_account(
+        &mut self,
+        account_id: &AccountId,
+    ) -> Option<AccountId> {
+        self.force_unregister_requests.remove(account_id);
+        self.accounts.remove(account_id);
+        // The accounts migrated from the baseline state were never counted.
+        self.metrics.active_accounts = self.metrics.active_accounts.saturating_sub(1);
+        let depositor_id = self
+            .storage_depositors
+            .remove(account_id)
+            .unwrap_or_else(|| account_id.clone());
+        if self.internal_refund_sponsorship(account_id) {
+            None
+        } else {
+            Some(depositor_id)
+        }
+    }
+
+    /// Internal method for registering an account with the `amount` deposited by the caller,
+    /// refunding the excess to the caller. A sponsor registered for the account's domain pays
+    /// when the deposit is short, and whoever paid is recorded as the depositor.
+    pub(crate) fn internal_storage_register(&mut self, account_id: &AccountId, amount: NearToken) {
+        // Get the minimum required storage and ensure the deposit is at least that amount,
+        // unless a sponsor registered for the account's domain pays for it.
+        let min_balance = Self::storage_balance_bounds().min;
+        let mut refund = amount.saturating_sub(min_balance);
+        let mut depositor_id = env::predecessor_account_id();
+        if amount < min_balance {
+            let sponsor_id = self
+                .internal_charge_storage_sponsor(account_id, min_balance)
+                .unwrap_or_else(|| {
+                    env::panic_str("The attached deposit is less than the minimum storage balance")
+                });
+            log!("The storage of {} is paid by {}", account_id, sponsor_id);
+            refund = amount;
+            depositor_id = sponsor_id;
+        }
+
+        // Register the account, remembering who paid for it if it didn't
+        self.internal_register_account(account_id);
+        if &depositor_id != account_id {
+            self.storage_depositors.insert(account_id, &depositor_id);
+        }
+        // Perform a refund
+        if refund.gt(&ZERO_TOKEN) {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+
+    /// Internal method for charging the storage written since `initial_storage_usage` to the
+    /// attached deposit, refunding the rest of it to the caller. Returns the cost charged.
+    pub(crate) fn internal_charge_storage(initial_storage_usage: StorageUsage) -> NearToken {
+        let bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+        let cost = env::storage_byte_cost().saturating_mul(bytes.into());
+        let deposit = env::attached_deposit();
+        require!(
+            deposit >= cost,
+            format!(
+                "The attached deposit should cover the storage cost of {}",
+                cost.exact_amount_display()
+            )
+        );
+        let refund = deposit.saturating_sub(cost);
+        if !refund.is_zero() {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        cost
+    }
+
+    /// Internal method for refunding the storage released since `initial_storage_usage` to
+    /// `account_id`, who paid for it.
+    pub(crate) fn internal_refund_storage(
+        account_id: &AccountId,
+        initial_storage_usage: StorageUsage,
+    ) {
+        let bytes = initial_storage_usage.saturating_sub(env::storage_usage());
+        if bytes > 0 {
+            Promise::new(account_id.clone())
+                .transfer(env::storage_byte_cost().saturating_mul(bytes.into()));
+        }
+    }
+
+    /// Internal method for settling a call that can both write and release storage: charging
+    /// the storage used since `initial_storage_usage` to the attached deposit, or refunding the
+    /// storage released to `account_id`, who paid for it.
+    pub(crate) fn internal_settle_storage(
+        account_id: &AccountId,
+        initial_storage_usage: StorageUsage,
+    ) {
+        if env::storage_usage() > initial_storage_usage {
+            Self::internal_charge_storage(initial_storage_usage);
+        } else {
+            Self::internal_refund_storage(account_id, initial_storage_usage);
+        }
+    }
+
+    /// Internal method for telling whether the sweep must keep an account registered: the
+    /// contract account and the fee and dust accounts receive tokens without registering, and
+    /// the pending records referencing an account pay out to it or refund it later.
+    fn internal_is_unsweepable(&self, account_id: &AccountId) -> bool {
+        account_id == &env::current_account_id()
+            || self.dust_account_id.as_ref() == Some(account_id)
+            || self.fee_config.as_ref().is_some_and(|config| {
+                account_id == &config.stakers_pool_id || account_id == &config.treasury_id
+            })
+            || self.pending_references.contains_key(account_id)
+    }
+
+    /// Internal method for recording a pending record paying out to or refunding `account_ids`.
+    pub(crate) fn internal_add_pending_references(&mut self, account_ids: &[&AccountId]) {
+        for account_id in account_ids {
+            let count = self.pending_references.get(account_id).unwrap_or(0);
+            self.pending_references.insert(account_id, &(count + 1));
+        }
+    }
+
+    /// Internal method for releasing the references of a settled record to `account_ids`.
+    pub(crate) fn internal_remove_pending_references(&mut self, account_ids: &[&AccountId]) {
+        for account_id in account_ids {
+            match self.pending_references.get(account_id) {
+                Some(count) if count > 1 => {
+                    self.pending_references.insert(account_id, &(count - 1));
+                }
+                _ => {
+                    self.pending_references.remove(account_id);
+                }
+            }
+        }
+    }
+
+    /// Internal method for charging `cost` to the closest parent domain of `account_id` that
+    /// sponsors storage and can afford it. Returns the sponsor, if one was charged.
+    pub(crate) fn internal_charge_storage_sponsor(
+        &mut self,
+        account_id: &AccountId,
+        cost: NearToken,
+    ) -> Option<AccountId> {
+        let mut domain = account_id.as_str();
+        while let Some((_, parent)) = domain.split_once('.') {
+            domain = parent;
+            let Ok(sponsor_id) = domain.parse::<AccountId>() else {
+                continue;
+            };
+            if let Some(balance) = self.storage_sponsors.get(&sponsor_id) {
+                if let Some(remaining) = balance.checked_sub(cost) {
+                    self.storage_sponsors.insert(&sponsor_id, &remaining);
+                    self.storage_sponsorship_total =
+                        self.storage_sponsorship_total.saturating_sub(cost);
+                    return Some(sponsor_id);
+                }
+            }
+        }
+        None
+    }
+}
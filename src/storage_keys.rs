@@ -0,0 +1,520 @@
+//! Keys of the persistent collections, grouped by the module owning them.
+//!
+//! Every collection gets a one-byte prefix, assigned here and nowhere else. The prefixes match
+//! the indices of the flat `StorageKey` enum this layout replaced, so existing state is read
+//! back unchanged. New collections must take the next free byte: the uniqueness of the
+//! prefixes is checked at compile time.
+
+use near_sdk::serde::Serialize;
+use near_sdk::{near_bindgen, CryptoHash, IntoStorageKey, NearSchema};
+
+use crate::*;
+
+macro_rules! storage_keys {
+    ($module:literal, $name:ident { $($variant:ident = $prefix:literal,)* }) => {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub enum $name {
+            $($variant,)*
+        }
+
+        impl $name {
+            /// The module owning the collections.
+            pub const MODULE: &'static str = $module;
+            /// The name and prefix of every collection of the module.
+            pub const PREFIXES: &'static [(&'static str, u8)] = &[$((stringify!($variant), $prefix),)*];
+
+            pub const fn prefix(self) -> u8 {
+                match self {
+                    $(Self::$variant => $prefix,)*
+                }
+            }
+        }
+    };
+}
+
+storage_keys!("core", CoreKey {
+    Accounts = 0,
+    Metadata = 1,
+    Tokenomics = 77,
+});
+storage_keys!("mining", MiningKey {
+    MiningPrograms = 2,
+});
+storage_keys!("bonds", BondsKey {
+    BondMarkets = 3,
+    BondPositions = 4,
+});
+storage_keys!("fees", FeesKey {
+    FeeCategoryRates = 5,
+    AccountFeeCategories = 6,
+});
+storage_keys!("signatures", SignaturesKey {
+    RelayKeys = 7,
+    RelayNonces = 8,
+});
+storage_keys!("storage", StorageManagementKey {
+    StorageSponsors = 9,
+    ForceUnregisterRequests = 50,
+    Depositors = 90,
+    PendingReferences = 95,
+});
+storage_keys!("merkle", MerkleKey {
+    BalanceLeafIndexes = 10,
+    BalanceTree = 11,
+});
+storage_keys!("shielded", ShieldedKey {
+    ShieldedPool = 12,
+    ShieldedCommitments = 13,
+    ShieldedNullifiers = 14,
+});
+storage_keys!("encrypted_memo", EncryptedMemoKey {
+    MemoKeys = 15,
+});
+storage_keys!("payment_keys", PaymentKeysKey {
+    PaymentPubkeys = 16,
+});
+storage_keys!("payment_intents", PaymentIntentsKey {
+    PaymentIntents = 17,
+});
+storage_keys!("ft_core", CoSigningKey {
+    CoSigners = 18,
+    PendingTransfers = 19,
+});
+storage_keys!("wallet_policy", WalletPolicyKey {
+    WalletPolicies = 20,
+    WalletPolicyChanges = 21,
+});
+storage_keys!("budgets", BudgetsKey {
+    Budgets = 22,
+});
+storage_keys!("approvals", ApprovalsKey {
+    Allowances = 23,
+    AllowancesPerOwner = 24,
+});
+storage_keys!("supply", SupplyKey {
+    SupplyCategories = 25,
+    SupplyHistory = 26,
+});
+storage_keys!("burns", BurnsKey {
+    BurnedByAccount = 27,
+    BurnLeaderboard = 28,
+    BurnReceipts = 29,
+});
+storage_keys!("mint_requests", MintRequestsKey {
+    MintProposers = 30,
+    MintApprovers = 31,
+    MintRequests = 32,
+});
+storage_keys!("freezing", FreezingKey {
+    FrozenAccounts = 33,
+});
+storage_keys!("jurisdictions", JurisdictionsKey {
+    JurisdictionAttestors = 34,
+    AccountJurisdictions = 35,
+    JurisdictionRules = 36,
+});
+storage_keys!("travel_rule", TravelRuleKey {
+    Vasps = 37,
+});
+storage_keys!("attestations", AttestationsKey {
+    AttestationCache = 38,
+});
+storage_keys!("rebates", RebatesKey {
+    RebateAccounts = 39,
+});
+storage_keys!("relayer_gas", RelayerGasKey {
+    RelayerUsage = 40,
+});
+storage_keys!("locks", LocksKey {
+    Locks = 41,
+    ReceiptBalances = 42,
+});
+storage_keys!("grants", GrantsKey {
+    Grants = 43,
+    GrantsPerHolder = 44,
+});
+storage_keys!("multicall", MulticallKey {
+    MulticallTargets = 45,
+});
+storage_keys!("treasury", TreasuryKey {
+    TreasurySpends = 46,
+});
+storage_keys!("account_blobs", AccountBlobsKey {
+    AccountBlobs = 47,
+});
+storage_keys!("jobs", JobsKey {
+    Jobs = 48,
+    JobItems = 49,
+});
+storage_keys!("factory", FactoryKey {
+    FactoryCode = 51,
+    FactoryTokens = 52,
+});
+storage_keys!("quarantine", QuarantineKey {
+    RenameQuarantine = 53,
+    QuarantineLargeTransfers = 54,
+});
+storage_keys!("labels", LabelsKey {
+    AccountLabels = 55,
+});
+storage_keys!("locales", LocalesKey {
+    AccountLocales = 56,
+});
+storage_keys!("airdrop", AirdropKey {
+    Airdrops = 57,
+    AirdropClaims = 58,
+    AirdropGrants = 91,
+});
+storage_keys!("preferences", PreferencesKey {
+    AccountPreferences = 59,
+});
+storage_keys!("access", AccessKey {
+    AccountRoles = 60,
+});
+storage_keys!("rand", RandKey {
+    RandomnessRounds = 61,
+    RandomnessCommitments = 62,
+});
+storage_keys!("streams", StreamsKey {
+    Streams = 63,
+});
+storage_keys!("snapshot", SnapshotKey {
+    Snapshots = 64,
+    BalanceCheckpoints = 65,
+});
+// Retired: the permits consume the relay nonces now. Kept so that the prefix isn't reused.
+storage_keys!("permits", PermitsKey {
+    PermitNonces = 66,
+});
+storage_keys!("borsh_msg", BorshMsgKey {
+    BorshMsgReceivers = 67,
+});
+storage_keys!("mint_limits", MintLimitsKey {
+    DailyMints = 68,
+});
+storage_keys!("netting", NettingKey {
+    NettingWindows = 69,
+    NettedBatches = 70,
+});
+storage_keys!("escrow", EscrowKey {
+    Escrows = 71,
+});
+storage_keys!("keepers", KeepersKey {
+    KeeperTips = 72,
+});
+storage_keys!("vouchers", VouchersKey {
+    BalanceVouchers = 73,
+});
+storage_keys!("council", CouncilKey {
+    Proposals = 74,
+});
+storage_keys!("stablecoin", StablecoinKey {
+    StablecoinState = 75,
+    Vaults = 76,
+});
+storage_keys!("sponsorship", SponsorshipKey {
+    Registrations = 78,
+    Accounts = 79,
+});
+storage_keys!("insurance", InsuranceKey {
+    Claims = 80,
+});
+storage_keys!("bounty", BountyKey {
+    Disclosures = 81,
+});
+storage_keys!("audit", AuditKey {
+    AuditState = 82,
+    Accounts = 83,
+});
+storage_keys!("referendum", ReferendumKey {
+    Referenda = 84,
+    Stakes = 85,
+    OpenReferenda = 86,
+});
+storage_keys!("receiver_list", ReceiverListKey {
+    Receivers = 87,
+});
+storage_keys!("reservations", ReservationsKey {
+    Reservations = 88,
+});
+storage_keys!("quotes", QuotesKey {
+    Quotes = 89,
+});
+storage_keys!("oracle", OracleKey {
+    PriceFeeds = 92,
+});
+storage_keys!("rebase", RebaseKey {
+    RebaseState = 93,
+    SnapshotIndexes = 96,
+});
+storage_keys!("scheduled", ScheduledKey {
+    ScheduledTransfers = 94,
+});
+
+/// Every module's key enum, as `(module, [(collection, prefix)])`.
+pub const STORAGE_LAYOUT: &[(&str, &[(&str, u8)])] = &[
+    (CoreKey::MODULE, CoreKey::PREFIXES),
+    (MiningKey::MODULE, MiningKey::PREFIXES),
+    (BondsKey::MODULE, BondsKey::PREFIXES),
+    (FeesKey::MODULE, FeesKey::PREFIXES),
+    (SignaturesKey::MODULE, SignaturesKey::PREFIXES),
+    (StorageManagementKey::MODULE, StorageManagementKey::PREFIXES),
+    (MerkleKey::MODULE, MerkleKey::PREFIXES),
+    (ShieldedKey::MODULE, ShieldedKey::PREFIXES),
+    (EncryptedMemoKey::MODULE, EncryptedMemoKey::PREFIXES),
+    (PaymentKeysKey::MODULE, PaymentKeysKey::PREFIXES),
+    (PaymentIntentsKey::MODULE, PaymentIntentsKey::PREFIXES),
+    (CoSigningKey::MODULE, CoSigningKey::PREFIXES),
+    (WalletPolicyKey::MODULE, WalletPolicyKey::PREFIXES),
+    (BudgetsKey::MODULE, BudgetsKey::PREFIXES),
+    (ApprovalsKey::MODULE, ApprovalsKey::PREFIXES),
+    (SupplyKey::MODULE, SupplyKey::PREFIXES),
+    (BurnsKey::MODULE, BurnsKey::PREFIXES),
+    (MintRequestsKey::MODULE, MintRequestsKey::PREFIXES),
+    (FreezingKey::MODULE, FreezingKey::PREFIXES),
+    (JurisdictionsKey::MODULE, JurisdictionsKey::PREFIXES),
+    (TravelRuleKey::MODULE, TravelRuleKey::PREFIXES),
+    (AttestationsKey::MODULE, AttestationsKey::PREFIXES),
+    (RebatesKey::MODULE, RebatesKey::PREFIXES),
+    (RelayerGasKey::MODULE, RelayerGasKey::PREFIXES),
+    (LocksKey::MODULE, LocksKey::PREFIXES),
+    (GrantsKey::MODULE, GrantsKey::PREFIXES),
+    (MulticallKey::MODULE, MulticallKey::PREFIXES),
+    (TreasuryKey::MODULE, TreasuryKey::PREFIXES),
+    (AccountBlobsKey::MODULE, AccountBlobsKey::PREFIXES),
+    (JobsKey::MODULE, JobsKey::PREFIXES),
+    (FactoryKey::MODULE, FactoryKey::PREFIXES),
+    (QuarantineKey::MODULE, QuarantineKey::PREFIXES),
+    (LabelsKey::MODULE, LabelsKey::PREFIXES),
+    (LocalesKey::MODULE, LocalesKey::PREFIXES),
+    (AirdropKey::MODULE, AirdropKey::PREFIXES),
+    (PreferencesKey::MODULE, PreferencesKey::PREFIXES),
+    (AccessKey::MODULE, AccessKey::PREFIXES),
+    (RandKey::MODULE, RandKey::PREFIXES),
+    (StreamsKey::MODULE, StreamsKey::PREFIXES),
+    (SnapshotKey::MODULE, SnapshotKey::PREFIXES),
+    (PermitsKey::MODULE, PermitsKey::PREFIXES),
+    (BorshMsgKey::MODULE, BorshMsgKey::PREFIXES),
+    (MintLimitsKey::MODULE, MintLimitsKey::PREFIXES),
+    (NettingKey::MODULE, NettingKey::PREFIXES),
+    (EscrowKey::MODULE, EscrowKey::PREFIXES),
+    (KeepersKey::MODULE, KeepersKey::PREFIXES),
+    (VouchersKey::MODULE, VouchersKey::PREFIXES),
+    (CouncilKey::MODULE, CouncilKey::PREFIXES),
+    (StablecoinKey::MODULE, StablecoinKey::PREFIXES),
+    (SponsorshipKey::MODULE, SponsorshipKey::PREFIXES),
+    (InsuranceKey::MODULE, InsuranceKey::PREFIXES),
+    (BountyKey::MODULE, BountyKey::PREFIXES),
+    (AuditKey::MODULE, AuditKey::PREFIXES),
+    (ReferendumKey::MODULE, ReferendumKey::PREFIXES),
+    (ReceiverListKey::MODULE, ReceiverListKey::PREFIXES),
+    (ReservationsKey::MODULE, ReservationsKey::PREFIXES),
+    (QuotesKey::MODULE, QuotesKey::PREFIXES),
+    (OracleKey::MODULE, OracleKey::PREFIXES),
+    (RebaseKey::MODULE, RebaseKey::PREFIXES),
+    (ScheduledKey::MODULE, ScheduledKey::PREFIXES),
+];
+
+/// Returns whether no prefix of `layout` is used twice.
+pub const fn prefixes_are_unique(layout: &[(&str, &[(&str, u8)])]) -> bool {
+    let mut seen = [false; 256];
+    let mut module = 0;
+    while module < layout.len() {
+        let prefixes = layout[module].1;
+        let mut collection = 0;
+        while collection < prefixes.len() {
+            let prefix = prefixes[collection].1 as usize;
+            if seen[prefix] {
+                return false;
+            }
+            seen[prefix] = true;
+            collection += 1;
+        }
+        module += 1;
+    }
+    true
+}
+
+const _: () = assert!(
+    prefixes_are_unique(STORAGE_LAYOUT),
+    "Two collections share a storage prefix"
+);
+
+/// Helper structure for keys of the persistent collections.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StorageKey {
+    Core(CoreKey),
+    Mining(MiningKey),
+    Bonds(BondsKey),
+    Fees(FeesKey),
+    Signatures(SignaturesKey),
+    Storage(StorageManagementKey),
+    Merkle(MerkleKey),
+    Shielded(ShieldedKey),
+    EncryptedMemo(EncryptedMemoKey),
+    PaymentKeys(PaymentKeysKey),
+    PaymentIntents(PaymentIntentsKey),
+    CoSigning(CoSigningKey),
+    WalletPolicy(WalletPolicyKey),
+    Budgets(BudgetsKey),
+    Approvals(ApprovalsKey),
+    /// The allowances of a single owner, keyed by the hash of its account ID.
+    AllowancesPerOwner {
+        account_hash: CryptoHash,
+    },
+    Supply(SupplyKey),
+    Burns(BurnsKey),
+    MintRequests(MintRequestsKey),
+    Freezing(FreezingKey),
+    Jurisdictions(JurisdictionsKey),
+    TravelRule(TravelRuleKey),
+    Attestations(AttestationsKey),
+    Rebates(RebatesKey),
+    RelayerGas(RelayerGasKey),
+    Locks(LocksKey),
+    Grants(GrantsKey),
+    Multicall(MulticallKey),
+    Treasury(TreasuryKey),
+    /// The blob of rarely-read data of a single account, keyed by the hash of its account ID.
+    AccountBlob {
+        account_hash: CryptoHash,
+    },
+    Jobs(JobsKey),
+    Factory(FactoryKey),
+    Quarantine(QuarantineKey),
+    Labels(LabelsKey),
+    Locales(LocalesKey),
+    Airdrop(AirdropKey),
+    Preferences(PreferencesKey),
+    Access(AccessKey),
+    Rand(RandKey),
+    Streams(StreamsKey),
+    Snapshot(SnapshotKey),
+    Permits(PermitsKey),
+    BorshMsg(BorshMsgKey),
+    MintLimits(MintLimitsKey),
+    Netting(NettingKey),
+    Escrow(EscrowKey),
+    Keepers(KeepersKey),
+    Vouchers(VouchersKey),
+    Council(CouncilKey),
+    Stablecoin(StablecoinKey),
+    Sponsorship(SponsorshipKey),
+    Insurance(InsuranceKey),
+    Bounty(BountyKey),
+    Audit(AuditKey),
+    Referendum(ReferendumKey),
+    ReceiverList(ReceiverListKey),
+    Reservations(ReservationsKey),
+    Quotes(QuotesKey),
+    Oracle(OracleKey),
+    Rebase(RebaseKey),
+    Scheduled(ScheduledKey),
+}
+
+impl IntoStorageKey for StorageKey {
+    fn into_storage_key(self) -> Vec<u8> {
+        let prefix = match self {
+            StorageKey::Core(key) => key.prefix(),
+            StorageKey::Mining(key) => key.prefix(),
+            StorageKey::Bonds(key) => key.prefix(),
+            StorageKey::Fees(key) => key.prefix(),
+            StorageKey::Signatures(key) => key.prefix(),
+            StorageKey::Storage(key) => key.prefix(),
+            StorageKey::Merkle(key) => key.prefix(),
+            StorageKey::Shielded(key) => key.prefix(),
+            StorageKey::EncryptedMemo(key) => key.prefix(),
+            StorageKey::PaymentKeys(key) => key.prefix(),
+            StorageKey::PaymentIntents(key) => key.prefix(),
+            StorageKey::CoSigning(key) => key.prefix(),
+            StorageKey::WalletPolicy(key) => key.prefix(),
+            StorageKey::Budgets(key) => key.prefix(),
+            StorageKey::Approvals(key) => key.prefix(),
+            StorageKey::AllowancesPerOwner { account_hash } => {
+                let mut key = vec![ApprovalsKey::AllowancesPerOwner.prefix()];
+                key.extend_from_slice(&account_hash);
+                return key;
+            }
+            StorageKey::Supply(key) => key.prefix(),
+            StorageKey::Burns(key) => key.prefix(),
+            StorageKey::MintRequests(key) => key.prefix(),
+            StorageKey::Freezing(key) => key.prefix(),
+            StorageKey::Jurisdictions(key) => key.prefix(),
+            StorageKey::TravelRule(key) => key.prefix(),
+            StorageKey::Attestations(key) => key.prefix(),
+            StorageKey::Rebates(key) => key.prefix(),
+            StorageKey::RelayerGas(key) => key.prefix(),
+            StorageKey::Locks(key) => key.prefix(),
+            StorageKey::Grants(key) => key.prefix(),
+            StorageKey::Multicall(key) => key.prefix(),
+            StorageKey::Treasury(key) => key.prefix(),
+            StorageKey::AccountBlob { account_hash } => {
+                let mut key = vec![AccountBlobsKey::AccountBlobs.prefix()];
+                key.extend_from_slice(&account_hash);
+                return key;
+            }
+            StorageKey::Jobs(key) => key.prefix(),
+            StorageKey::Factory(key) => key.prefix(),
+            StorageKey::Quarantine(key) => key.prefix(),
+            StorageKey::Labels(key) => key.prefix(),
+            StorageKey::Locales(key) => key.prefix(),
+            StorageKey::Airdrop(key) => key.prefix(),
+            StorageKey::Preferences(key) => key.prefix(),
+            StorageKey::Access(key) => key.prefix(),
+            StorageKey::Rand(key) => key.prefix(),
+            StorageKey::Streams(key) => key.prefix(),
+            StorageKey::Snapshot(key) => key.prefix(),
+            StorageKey::Permits(key) => key.prefix(),
+            StorageKey::BorshMsg(key) => key.prefix(),
+            StorageKey::MintLimits(key) => key.prefix(),
+            StorageKey::Netting(key) => key.prefix(),
+            StorageKey::Escrow(key) => key.prefix(),
+            StorageKey::Keepers(key) => key.prefix(),
+            StorageKey::Vouchers(key) => key.prefix(),
+            StorageKey::Council(key) => key.prefix(),
+            StorageKey::Stablecoin(key) => key.prefix(),
+            StorageKey::Sponsorship(key) => key.prefix(),
+            StorageKey::Insurance(key) => key.prefix(),
+            StorageKey::Bounty(key) => key.prefix(),
+            StorageKey::Audit(key) => key.prefix(),
+            StorageKey::Referendum(key) => key.prefix(),
+            StorageKey::ReceiverList(key) => key.prefix(),
+            StorageKey::Reservations(key) => key.prefix(),
+            StorageKey::Quotes(key) => key.prefix(),
+            StorageKey::Oracle(key) => key.prefix(),
+            StorageKey::Rebase(key) => key.prefix(),
+            StorageKey::Scheduled(key) => key.prefix(),
+        };
+        vec![prefix]
+    }
+}
+
+// A collection of the storage layout.
+#[derive(Serialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageLayoutEntry {
+    pub module: String,
+    pub collection: String,
+    pub prefix: u8,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Debug view listing the storage prefix of every collection, sorted by prefix.
+    pub fn storage_layout(&self) -> Vec<StorageLayoutEntry> {
+        let mut layout: Vec<StorageLayoutEntry> = STORAGE_LAYOUT
+            .iter()
+            .flat_map(|(module, prefixes)| {
+                prefixes
+                    .iter()
+                    .map(|(collection, prefix)| StorageLayoutEntry {
+                        module: module.to_string(),
+                        collection: collection.to_string(),
+                        prefix: *prefix,
+                    })
+            })
+            .collect();
+        layout.sort_by_key(|entry| entry.prefix);
+        layout
+    }
+}
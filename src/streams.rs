@@ -0,0 +1,225 @@
+//! Token streams.
+//!
+//! A sender deposits tokens into a stream paying `tokens_per_second` to its receiver, who
+//! withdraws what accrued at any time. Either party can cancel the stream: the receiver gets
+//! what accrued and the sender the rest. The deposits are held in the streams rather than in an
+//! account, `streamed_supply` counting them in the supply, so that `ft_balance_of` only shows
+//! the tokens the accounts can spend.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::versioned::versioned_record;
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+pub type StreamId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Stream {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub tokens_per_second: U128,
+    /// The tokens deposited by the sender, the most the stream pays.
+    pub deposit: U128,
+    /// The timestamp (in nanoseconds) when the stream was opened.
+    pub started_at: U64,
+    /// The tokens the receiver already withdrew.
+    pub withdrawn: U128,
+}
+
+impl Stream {
+    /// Returns the tokens paid by the stream up to `now`, whole seconds only.
+    pub fn accrued(&self, now: u64) -> u128 {
+        let seconds = now.saturating_sub(self.started_at.0) / NANOS_PER_SECOND;
+        self.tokens_per_second
+            .0
+            .saturating_mul(seconds.into())
+            .min(self.deposit.0)
+    }
+}
+
+versioned_record!(VersionedStream, Stream);
+
+#[near_bindgen]
+impl Contract {
+    /// Opens a stream paying `tokens_per_second` to `receiver_id` out of `deposit` of the
+    /// caller's tokens, starting now.
+    #[payable]
+    pub fn open_stream(
+        &mut self,
+        receiver_id: AccountId,
+        tokens_per_second: U128,
+        deposit: U128,
+    ) -> StreamId {
+        assert_one_yocto();
+        self.internal_assert_not_paused(Pausable::Transfers);
+        let sender_id = env::predecessor_account_id();
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        self.internal_assert_not_frozen(&sender_id);
+        self.internal_assert_not_frozen(&receiver_id);
+        self.internal_unwrap_balance_of(&receiver_id);
+        let shares = U128(Self::internal_to_shares(deposit.0));
+        let shares_per_second = U128(Self::internal_to_shares(tokens_per_second.0));
+        require!(
+            shares.0 > 0 && shares_per_second.0 > 0,
+            "The rate and the deposit should be positive"
+        );
+        self.internal_withdraw(&sender_id, shares.0);
+        self.streamed_supply += shares.0;
+
+        let stream_id = self.next_stream_id;
+        self.next_stream_id += 1;
+        StreamOpened {
+            stream_id,
+            sender_id: &sender_id,
+            receiver_id: &receiver_id,
+            tokens_per_second: &tokens_per_second,
+            deposit: &deposit,
+        }
+        .emit();
+        self.internal_add_pending_references(&[&sender_id, &receiver_id]);
+        self.streams.insert(
+            &stream_id,
+            &Stream {
+                sender_id,
+                receiver_id,
+                tokens_per_second: shares_per_second,
+                deposit: shares,
+                started_at: U64(env::block_timestamp()),
+                withdrawn: U128(0),
+            },
+        );
+        stream_id
+    }
+
+    /// Withdraws the tokens accrued to the receiver of a stream, which must be the caller. The
+    /// stream is removed once it paid its whole deposit.
+    pub fn withdraw_from_stream(&mut self, stream_id: StreamId) -> U128 {
+        self.internal_assert_not_paused(Pausable::Transfers);
+        let stream = self.internal_get_stream(stream_id);
+        require!(
+            env::predecessor_account_id() == stream.receiver_id,
+            "Only the receiver can withdraw from the stream"
+        );
+        self.internal_withdraw_from_stream(stream_id, stream)
+    }
+
+    /// Pays what's left of a fully accrued stream to its receiver, and the stream's keeper tip,
+    /// if any, to the caller. Anyone can call it.
+    pub fn push_stream(&mut self, stream_id: StreamId) -> U128 {
+        self.internal_assert_not_paused(Pausable::Transfers);
+        let stream = self.internal_get_stream(stream_id);
+        require!(
+            stream.accrued(env::block_timestamp()) == stream.deposit.0,
+            "The stream isn't fully accrued yet"
+        );
+        self.internal_pay_keeper_tip(
+            &env::predecessor_account_id(),
+            &KeeperTask::Stream(stream_id),
+        );
+        self.internal_withdraw_from_stream(stream_id, stream)
+    }
+
+    /// Cancels a stream, paying the receiver what accrued and refunding the rest to the sender.
+    /// Must be called by one of them.
+    #[payable]
+    pub fn cancel_stream(&mut self, stream_id: StreamId) {
+        assert_one_yocto();
+        self.internal_assert_not_paused(Pausable::Transfers);
+        let stream = self.internal_get_stream(stream_id);
+        let caller_id = env::predecessor_account_id();
+        require!(
+            caller_id == stream.sender_id || caller_id == stream.receiver_id,
+            "Only the sender and the receiver can cancel the stream"
+        );
+        let accrued = stream.accrued(env::block_timestamp());
+        let receiver_amount = accrued - stream.withdrawn.0;
+        let sender_refund = stream.deposit.0 - accrued;
+        self.streams.remove(&stream_id);
+        self.internal_remove_pending_references(&[&stream.sender_id, &stream.receiver_id]);
+        self.internal_cancel_keeper_tip(&KeeperTask::Stream(stream_id));
+        if receiver_amount > 0 {
+            self.internal_release_streamed(&stream.receiver_id, receiver_amount);
+        }
+        if sender_refund > 0 {
+            self.internal_release_streamed(&stream.sender_id, sender_refund);
+        }
+        StreamCancelled {
+            stream_id,
+            cancelled_by: &caller_id,
+            receiver_amount: &U128(Self::internal_to_amount(receiver_amount)),
+            sender_refund: &U128(Self::internal_to_amount(sender_refund)),
+        }
+        .emit();
+    }
+
+    /// Returns a stream, or `null` if it doesn't exist (anymore).
+    pub fn get_stream(&self, stream_id: StreamId) -> Option<Stream> {
+        self.streams.get(&stream_id).map(|mut stream| {
+            stream.tokens_per_second = U128(Self::internal_to_amount(stream.tokens_per_second.0));
+            stream.deposit = U128(Self::internal_to_amount(stream.deposit.0));
+            stream.withdrawn = U128(Self::internal_to_amount(stream.withdrawn.0));
+            stream
+        })
+    }
+
+    /// Returns the tokens the receiver of a stream can withdraw now.
+    pub fn stream_withdrawable(&self, stream_id: StreamId) -> U128 {
+        let stream = self.internal_get_stream(stream_id);
+        U128(Self::internal_to_amount(
+            stream.accrued(env::block_timestamp()) - stream.withdrawn.0,
+        ))
+    }
+
+    /// Returns the tokens held in the streams, part of the total supply.
+    pub fn get_streamed_supply(&self) -> U128 {
+        U128(Self::internal_to_amount(self.streamed_supply))
+    }
+}
+
+impl Contract {
+    fn internal_get_stream(&self, stream_id: StreamId) -> Stream {
+        self.streams
+            .get(&stream_id)
+            .unwrap_or_else(|| env::panic_str("The stream doesn't exist"))
+    }
+
+    // Pays the tokens accrued to the receiver of a stream since its last withdrawal.
+    fn internal_withdraw_from_stream(&mut self, stream_id: StreamId, mut stream: Stream) -> U128 {
+        self.internal_assert_not_frozen(&stream.receiver_id);
+        let amount = stream.accrued(env::block_timestamp()) - stream.withdrawn.0;
+        require!(amount > 0, "Nothing accrued since the last withdrawal");
+        self.internal_release_streamed(&stream.receiver_id, amount);
+        stream.withdrawn = U128(stream.withdrawn.0 + amount);
+        if stream.withdrawn == stream.deposit {
+            self.streams.remove(&stream_id);
+            self.internal_remove_pending_references(&[&stream.sender_id, &stream.receiver_id]);
+            // The receiver withdrew it all before any keeper pushed the stream.
+            self.internal_cancel_keeper_tip(&KeeperTask::Stream(stream_id));
+        } else {
+            self.streams.insert(&stream_id, &stream);
+        }
+        StreamWithdrawn {
+            stream_id,
+            receiver_id: &stream.receiver_id,
+            amount: &U128(Self::internal_to_amount(amount)),
+        }
+        .emit();
+        U128(Self::internal_to_amount(amount))
+    }
+
+    // Moves streamed tokens back to an account.
+    fn internal_release_streamed(&mut self, account_id: &AccountId, amount: u128) {
+        self.streamed_supply -= amount;
+        self.internal_deposit(account_id, amount);
+    }
+}
@@ -0,0 +1,197 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, NearSchema};
+
+use crate::*;
+
+/// The length of the buckets the supply history is compacted into: only the last total supply
+/// of each bucket is kept
+const SUPPLY_HISTORY_BUCKET_NANOS: u64 = 3_600_000_000_000;
+
+// The categories of accounts whose balances are not considered circulating.
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum SupplyCategory {
+    /// Staking contracts.
+    Staked,
+    /// Bridge custody accounts holding the tokens bridged to other chains.
+    BridgedOut,
+}
+
+// The supply counters maintained incrementally by the core and extension methods. In the
+// rebasing mode, the mints and burns are counted in the tokens they were worth, and the other
+// counters in shares.
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct SupplyCounters {
+    /// Tokens minted since the contract was initialized.
+    pub minted: u128,
+    /// Tokens burned since the contract was initialized.
+    pub burned: u128,
+    /// Tokens of bonds and vesting grants not vested and claimed yet.
+    pub locked_in_vesting: u128,
+    /// Tokens held by the accounts of the `Staked` category.
+    pub staked: u128,
+    /// Tokens held by the accounts of the `BridgedOut` category.
+    pub bridged_out: u128,
+}
+
+// The breakdown of the total supply returned by `ft_supply_breakdown`.
+#[derive(Serialize, Deserialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SupplyBreakdown {
+    pub total_supply: U128,
+    pub minted: U128,
+    pub burned: U128,
+    pub locked_in_vesting: U128,
+    pub staked: U128,
+    pub bridged_out: U128,
+    /// Tokens escrowed by the contract account for the extension modules (mining programs,
+    /// unsold bond capacity, pending transfers, ...), excluding the ones locked in vesting.
+    pub escrowed: U128,
+    /// The total supply minus everything above.
+    pub circulating: U128,
+}
+
+// The total supply at a point in time.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct SupplyPoint {
+    /// The timestamp (in nanoseconds) of the last mint or burn of the bucket.
+    pub timestamp: U64,
+    pub total_supply: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Puts an account in a supply category, or takes it out of its category when `None` is
+    /// passed. Its current balance is moved to the matching counter.
+    #[payable]
+    pub fn set_supply_category(&mut self, account_id: AccountId, category: Option<SupplyCategory>) {
+        assert_one_yocto();
+        self.assert_owner();
+        let balance = self.internal_unwrap_balance_of(&account_id);
+        if let Some(current) = self.supply_categories.get(&account_id) {
+            *self.internal_supply_counter(&current) = self
+                .internal_supply_counter(&current)
+                .saturating_sub(balance);
+        }
+        match category {
+            Some(category) => {
+                *self.internal_supply_counter(&category) = self
+                    .internal_supply_counter(&category)
+                    .saturating_add(balance);
+                self.supply_categories.insert(&account_id, &category);
+            }
+            None => {
+                self.supply_categories.remove(&account_id);
+            }
+        }
+    }
+
+    /// Returns the supply category of an account, if any.
+    pub fn get_supply_category(&self, account_id: AccountId) -> Option<SupplyCategory> {
+        self.supply_categories.get(&account_id)
+    }
+
+    /// Returns the breakdown of the total supply.
+    pub fn ft_supply_breakdown(&self) -> SupplyBreakdown {
+        let supply = &self.supply;
+        let escrowed = self
+            .accounts
+            .get(&env::current_account_id())
+            .unwrap_or(0)
+            .saturating_sub(supply.locked_in_vesting);
+        let circulating = self
+            .total_supply
+            .saturating_sub(supply.locked_in_vesting)
+            .saturating_sub(supply.staked)
+            .saturating_sub(supply.bridged_out)
+            .saturating_sub(escrowed);
+        SupplyBreakdown {
+            total_supply: U128(Self::internal_to_amount(self.total_supply)),
+            minted: U128(supply.minted),
+            burned: U128(supply.burned),
+            locked_in_vesting: U128(Self::internal_to_amount(supply.locked_in_vesting)),
+            staked: U128(Self::internal_to_amount(supply.staked)),
+            bridged_out: U128(Self::internal_to_amount(supply.bridged_out)),
+            escrowed: U128(Self::internal_to_amount(escrowed)),
+            circulating: U128(Self::internal_to_amount(circulating)),
+        }
+    }
+
+    /// Returns up to `limit` points of the supply history, starting with the first one
+    /// recorded at or after `from` (a timestamp in nanoseconds).
+    pub fn supply_history(&self, from: Option<U64>, limit: Option<u64>) -> Vec<SupplyPoint> {
+        let from = from.map_or(0, |from| from.0);
+        // The points are ordered by timestamp, so the first one to return can be found with
+        // a binary search.
+        let (mut low, mut high) = (0, self.supply_history.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.supply_history.get(middle).unwrap().timestamp.0 < from {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        (low..self.supply_history.len())
+            .take(limit.unwrap_or(50) as usize)
+            .filter_map(|index| self.supply_history.get(index))
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Internal method for appending the current total supply to the supply history. Called on
+    /// every mint, burn and rebase. A point recorded in the same bucket as the last one
+    /// replaces it.
+    pub(crate) fn internal_record_supply(&mut self) {
+        let now = env::block_timestamp();
+        let point = SupplyPoint {
+            timestamp: U64(now),
+            total_supply: U128(Self::internal_to_amount(self.total_supply)),
+        };
+        let len = self.supply_history.len();
+        let same_bucket = len > 0
+            && self.supply_history.get(len - 1).unwrap().timestamp.0 / SUPPLY_HISTORY_BUCKET_NANOS
+                == now / SUPPLY_HISTORY_BUCKET_NANOS;
+        if same_bucket {
+            self.supply_history.replace(len - 1, &point);
+        } else {
+            self.supply_history.push(&point);
+        }
+    }
+
+    /// Internal method for keeping the category counters in sync with a balance change of
+    /// `account_id`. Called on every deposit and withdrawal.
+    pub(crate) fn internal_track_supply_category(
+        &mut self,
+        account_id: &AccountId,
+        amount: u128,
+        deposit: bool,
+    ) {
+        let Some(category) = self.supply_categories.get(account_id) else {
+            return;
+        };
+        let counter = self.internal_supply_counter(&category);
+        *counter = if deposit {
+            counter.saturating_add(amount)
+        } else {
+            counter.saturating_sub(amount)
+        };
+    }
+
+    fn internal_supply_counter(&mut self, category: &SupplyCategory) -> &mut u128 {
+        match category {
+            SupplyCategory::Staked => &mut self.supply.staked,
+            SupplyCategory::BridgedOut => &mut self.supply.bridged_out,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Audited initialization templates.
+//!
+//! Teams launching a token pick the template matching its use rather than filling the metadata
+//! and the supply settings themselves. The template sets the decimals and how the supply can
+//! grow, along with a default name and symbol the owner then replaces with `set_name` and
+//! `set_symbol`.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, NearSchema};
+
+use crate::*;
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone, Copy, PartialEq,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum TokenTemplate {
+    /// A governance token with 18 decimals and a fixed supply: the initial supply is the max
+    /// supply.
+    Governance,
+    /// A token tracking a currency, with 6 decimals and an uncapped supply, minted as the
+    /// reserves grow.
+    Stablecoin,
+    /// An in-game currency with no decimals and an uncapped supply.
+    GameCurrency,
+}
+
+impl TokenTemplate {
+    /// The metadata of the template.
+    pub fn metadata(self) -> FungibleTokenMetadata {
+        let (name, symbol, decimals) = match self {
+            TokenTemplate::Governance => ("Governance Token", "GOV", 18),
+            TokenTemplate::Stablecoin => ("Stable Token", "STBL", 6),
+            TokenTemplate::GameCurrency => ("Game Coin", "COIN", 0),
+        };
+        FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals,
+        }
+    }
+
+    /// The max supply of a token of the template starting with `total_supply`.
+    pub fn max_supply(self, total_supply: U128) -> Option<U128> {
+        match self {
+            TokenTemplate::Governance => Some(total_supply),
+            TokenTemplate::Stablecoin | TokenTemplate::GameCurrency => None,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Initializes the contract from a template, with the given total supply owned by
+    /// `owner_id`.
+    #[init]
+    pub fn new_from_template(
+        template_id: TokenTemplate,
+        owner_id: AccountId,
+        total_supply: U128,
+    ) -> Self {
+        let mut this = Self::new(
+            owner_id,
+            total_supply,
+            template_id.metadata(),
+            template_id.max_supply(total_supply),
+        );
+        this.template = Some(template_id);
+        this
+    }
+
+    /// Returns the template the contract was initialized from, if any.
+    pub fn get_template(&self) -> Option<TokenTemplate> {
+        self.template
+    }
+}
@@ -0,0 +1,107 @@
+//! Fixtures for the unit tests of the crates embedding the token, only compiled with the
+//! `test_utils` feature.
+//!
+//! `ContractBuilder` sets up the mocked blockchain and initializes a contract holding the given
+//! balances, so integration logic can be tested without a sandbox. Balances are minted, keeping
+//! the total supply consistent, and any other module state is set up with `with`, which gets
+//! the contract along with the owner as the caller.
+
+use near_sdk::test_utils::VMContextBuilder;
+use near_sdk::{testing_env, NearToken};
+
+use crate::*;
+
+/// A step setting up the state of the contract.
+type Setup = Box<dyn FnOnce(&mut Contract)>;
+
+pub struct ContractBuilder {
+    context: VMContextBuilder,
+    owner_id: AccountId,
+    metadata: FungibleTokenMetadata,
+    balances: Vec<(AccountId, u128)>,
+    setups: Vec<Setup>,
+}
+
+impl ContractBuilder {
+    /// Starts a contract owned by `owner_id`, with the default metadata and no tokens.
+    pub fn new(owner_id: AccountId) -> Self {
+        let mut context = VMContextBuilder::new();
+        context.predecessor_account_id(owner_id.clone());
+        Self {
+            context,
+            owner_id,
+            metadata: FungibleTokenMetadata {
+                spec: FT_METADATA_SPEC.to_string(),
+                name: "Test Token".to_string(),
+                symbol: "TEST".to_string(),
+                icon: None,
+                reference: None,
+                reference_hash: None,
+                decimals: 24,
+            },
+            balances: vec![],
+            setups: vec![],
+        }
+    }
+
+    /// Deploys the contract on `contract_id` rather than the default account of the context.
+    pub fn contract_id(mut self, contract_id: AccountId) -> Self {
+        self.context.current_account_id(contract_id);
+        self
+    }
+
+    pub fn metadata(mut self, metadata: FungibleTokenMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Registers `account_id` if needed and mints `amount` to it.
+    pub fn balance(mut self, account_id: AccountId, amount: u128) -> Self {
+        self.balances.push((account_id, amount));
+        self
+    }
+
+    pub fn fee_config(self, fee_config: FeeConfig) -> Self {
+        self.with(move |contract| contract.fee_config = Some(fee_config))
+    }
+
+    pub fn pause_flags(self, pause_flags: PauseFlags) -> Self {
+        self.with(move |contract| contract.pause_flags = pause_flags)
+    }
+
+    /// Runs `setup` on the contract once the balances are minted, as the owner attaching one
+    /// yoctoNEAR.
+    pub fn with(mut self, setup: impl FnOnce(&mut Contract) + 'static) -> Self {
+        self.setups.push(Box::new(setup));
+        self
+    }
+
+    /// Initializes the contract, and returns it along with the context, still calling as the
+    /// owner with one yoctoNEAR attached. The logs of the setup are cleared.
+    pub fn build(self) -> (Contract, VMContextBuilder) {
+        let Self {
+            mut context,
+            owner_id,
+            metadata,
+            balances,
+            setups,
+        } = self;
+        testing_env!(context
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+        let mut contract = Contract::new(owner_id, U128(0), metadata, None);
+        for (account_id, amount) in balances {
+            if !contract.accounts.contains_key(&account_id) {
+                contract.internal_register_account(&account_id);
+            }
+            if amount > 0 {
+                contract.internal_mint(&account_id, amount, None);
+            }
+        }
+        for setup in setups {
+            setup(&mut contract);
+        }
+        testing_env!(context.build());
+        (contract, context)
+    }
+}
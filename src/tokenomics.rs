@@ -0,0 +1,98 @@
+//! Deflationary tokenomics.
+//!
+//! A token initialized with `new_with_tokenomics` burns `burn_bps` of every user transfer: the
+//! receiver is credited the transferred amount minus the burn, which is logged as an `ft_burn`
+//! event right after the `ft_transfer` one. The burn rate is fixed at init, unless the
+//! config is `adjustable`, in which case the owner can change it afterwards.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, near_bindgen, require, NearSchema};
+
+use crate::internal::pro_rata;
+use crate::*;
+
+/// The highest share of a transfer that can be burned, in basis points (10%).
+pub const MAX_BURN_ON_TRANSFER_BPS: u16 = 1_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenomicsConfig {
+    /// The share of every transfer burned, in basis points of the transferred amount.
+    pub burn_bps: u16,
+    /// Whether the owner can change `burn_bps` after init.
+    pub adjustable: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Initializes the contract like `new`, burning a share of every transfer as set by
+    /// `tokenomics`.
+    #[init]
+    pub fn new_with_tokenomics(
+        owner_id: AccountId,
+        total_supply: U128,
+        metadata: FungibleTokenMetadata,
+        max_supply: Option<U128>,
+        tokenomics: TokenomicsConfig,
+    ) -> Self {
+        Self::assert_valid_burn_bps(tokenomics.burn_bps);
+        let mut this = Self::new(owner_id, total_supply, metadata, max_supply);
+        this.tokenomics.set(&tokenomics);
+        this
+    }
+
+    /// Changes the share of every transfer burned (owner only), if the tokenomics are
+    /// adjustable.
+    #[payable]
+    pub fn set_burn_on_transfer_bps(&mut self, burn_bps: u16) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut tokenomics = self
+            .tokenomics
+            .get()
+            .filter(|tokenomics| tokenomics.adjustable)
+            .unwrap_or_else(|| env::panic_str("The tokenomics aren't adjustable"));
+        Self::assert_valid_burn_bps(burn_bps);
+        tokenomics.burn_bps = burn_bps;
+        self.tokenomics.set(&tokenomics);
+    }
+
+    /// Returns the tokenomics of the token, or `null` if it doesn't burn on transfer.
+    pub fn get_tokenomics(&self) -> Option<TokenomicsConfig> {
+        self.tokenomics.get()
+    }
+}
+
+impl Contract {
+    fn assert_valid_burn_bps(burn_bps: u16) {
+        require!(
+            burn_bps <= MAX_BURN_ON_TRANSFER_BPS,
+            "The burn rate is too high"
+        );
+    }
+
+    /// Internal method for computing the share of a transfer of `amount` tokens burned by the
+    /// tokenomics.
+    pub(crate) fn internal_transfer_burn(&self, amount: u128) -> u128 {
+        self.tokenomics.get().map_or(0, |tokenomics| {
+            pro_rata(amount, tokenomics.burn_bps.into(), BPS_DENOMINATOR)
+        })
+    }
+
+    /// Internal method for burning the share of a transfer of `amount` tokens set by the
+    /// tokenomics, out of what the receiver was credited. Returns the burned amount.
+    pub(crate) fn internal_burn_on_transfer(
+        &mut self,
+        receiver_id: &AccountId,
+        amount: u128,
+    ) -> u128 {
+        let burned = self.internal_transfer_burn(amount);
+        if burned > 0 {
+            self.internal_burn(receiver_id, burned, Some("Transfer burn"));
+        }
+        burned
+    }
+}
@@ -0,0 +1,110 @@
+use near_sdk::json_types::U128;
+use near_sdk::{assert_one_yocto, env, near_bindgen, require};
+
+use crate::*;
+
+/// The maximum length of a travel rule data pointer
+const MAX_TRAVEL_RULE_REF_LEN: usize = 256;
+
+/// The maximum length of the name of a VASP
+const MAX_VASP_NAME_LEN: usize = 64;
+
+#[near_bindgen]
+impl Contract {
+    /// Registers an account as a Virtual Asset Service Provider under the given name, or
+    /// unregisters it when `None` is passed.
+    #[payable]
+    pub fn set_vasp(&mut self, account_id: AccountId, name: Option<String>) {
+        assert_one_yocto();
+        self.assert_owner();
+        match name {
+            Some(name) => {
+                require!(name.len() <= MAX_VASP_NAME_LEN, "The VASP name is too long");
+                self.vasps.insert(&account_id, &name);
+            }
+            None => {
+                self.vasps.remove(&account_id);
+            }
+        }
+    }
+
+    /// Sets the amount above which transfers between VASPs must carry a travel rule data
+    /// pointer, or turns the requirement off when `None` is passed.
+    #[payable]
+    pub fn set_travel_rule_threshold(&mut self, threshold: Option<U128>) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.travel_rule_threshold = threshold.map(|threshold| threshold.0);
+    }
+
+    /// Transfers tokens like `ft_transfer`, recording `travel_rule_ref` (a pointer to the
+    /// encrypted travel rule data exchanged off-chain) in the event log.
+    #[payable]
+    pub fn ft_transfer_with_travel_rule(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        travel_rule_ref: String,
+    ) {
+        assert_one_yocto();
+        let amount = Self::internal_to_shares(amount.0);
+        require!(
+            !travel_rule_ref.is_empty() && travel_rule_ref.len() <= MAX_TRAVEL_RULE_REF_LEN,
+            "The travel rule reference should be between 1 and 256 bytes long"
+        );
+        let sender_id = env::predecessor_account_id();
+        self.internal_authorize_transfer(
+            &sender_id,
+            &receiver_id,
+            amount,
+            memo.as_deref(),
+            Some(&travel_rule_ref),
+        );
+        self.internal_transfer(&sender_id, &receiver_id, amount, memo.as_deref());
+        self.internal_settle_transfer(&sender_id, &receiver_id, amount, FeeMode::SenderPays);
+
+        TravelRuleTransfer {
+            sender_id: &sender_id,
+            receiver_id: &receiver_id,
+            amount: &U128(Self::internal_to_amount(amount)),
+            travel_rule_ref: &travel_rule_ref,
+        }
+        .emit();
+    }
+
+    /// Returns the name an account is registered as a VASP under, if any.
+    pub fn get_vasp(&self, account_id: AccountId) -> Option<String> {
+        self.vasps.get(&account_id)
+    }
+
+    /// Returns the amount above which transfers between VASPs need travel rule data, if any.
+    pub fn get_travel_rule_threshold(&self) -> Option<U128> {
+        self.travel_rule_threshold.map(U128)
+    }
+}
+
+impl Contract {
+    /// Internal method for asserting that a transfer between two VASPs above the threshold
+    /// carries a travel rule data pointer.
+    pub(crate) fn internal_assert_travel_rule(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        travel_rule_ref: Option<&str>,
+    ) {
+        let Some(threshold) = self.travel_rule_threshold else {
+            return;
+        };
+        if amount > threshold
+            && travel_rule_ref.is_none()
+            && self.vasps.contains_key(sender_id)
+            && self.vasps.contains_key(receiver_id)
+        {
+            env::panic_str(
+                "Transfers between VASPs above the threshold need a travel rule reference",
+            );
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema, NearToken};
+
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+pub type TreasurySpendId = u64;
+
+// The two-man rule applied to the treasury account. Its spends above `threshold` can't be made
+// with a plain transfer: they are queued with `propose_treasury_spend` and only executed once
+// the approver co-signs them within the window.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasuryGuard {
+    pub treasury_id: AccountId,
+    /// The second role co-signing the large spends.
+    pub approver_id: AccountId,
+    /// Spends of more than this amount need to be co-signed.
+    pub threshold: U128,
+    /// The number of seconds the approver has to co-sign a spend.
+    pub window_sec: u64,
+}
+
+// A treasury spend waiting for the approver's co-signature. The amount is escrowed by the
+// contract account until the spend is executed or cancelled.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasurySpend {
+    pub treasury_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub memo: Option<String>,
+    pub approver_id: AccountId,
+    /// The timestamp (in nanoseconds) after which the spend can't be co-signed anymore.
+    pub expires_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Puts the treasury account under the two-man rule, or lifts it when `None` is passed.
+    #[payable]
+    pub fn set_treasury_guard(&mut self, guard: Option<TreasuryGuard>) {
+        assert_one_yocto();
+        self.assert_owner();
+        if let Some(guard) = guard.as_ref() {
+            require!(
+                guard.approver_id != guard.treasury_id,
+                "The treasury can't be its own approver"
+            );
+            require!(
+                guard.window_sec > 0,
+                "The co-signing window should be positive"
+            );
+        }
+        self.treasury_guard = guard;
+    }
+
+    /// Escrows a spend of the treasury until the approver co-signs it. Must be called by the
+    /// treasury account, the transfer policies applying as to a transfer. The attached deposit
+    /// must cover the storage of the spend; the rest of it is refunded.
+    #[payable]
+    pub fn propose_treasury_spend(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) -> TreasurySpendId {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let amount = Self::internal_to_shares(amount.0);
+        let guard = self
+            .treasury_guard
+            .clone()
+            .unwrap_or_else(|| env::panic_str("The treasury isn't guarded"));
+        let treasury_id = env::predecessor_account_id();
+        require!(
+            treasury_id == guard.treasury_id,
+            "Only the treasury can propose spends"
+        );
+        require!(amount > 0, "The amount should be a positive number");
+        self.internal_unwrap_balance_of(&receiver_id);
+        self.internal_apply_spend_policy(&treasury_id, &receiver_id, amount, memo.as_deref(), None);
+
+        let spend_id = self.next_treasury_spend_id;
+        self.next_treasury_spend_id += 1;
+        self.internal_transfer(
+            &treasury_id,
+            &env::current_account_id(),
+            amount,
+            Some(&format!("Escrow of treasury spend {}", spend_id)),
+        );
+        let expires_at = env::block_timestamp()
+            .saturating_add(guard.window_sec.saturating_mul(NANOS_PER_SECOND));
+        let spend = TreasurySpend {
+            treasury_id,
+            receiver_id,
+            amount: U128(amount),
+            memo,
+            approver_id: guard.approver_id,
+            expires_at: U64(expires_at),
+        };
+        self.treasury_spends.insert(&spend_id, &spend);
+        TreasurySpendQueued {
+            spend_id,
+            treasury_id: &spend.treasury_id,
+            receiver_id: &spend.receiver_id,
+            amount: &U128(Self::internal_to_amount(amount)),
+            approver_id: &spend.approver_id,
+            expires_at: spend.expires_at,
+        }
+        .emit();
+        Self::internal_charge_storage(initial_storage_usage);
+        spend_id
+    }
+
+    /// Executes a pending treasury spend. Must be called by its approver before it expires.
+    #[payable]
+    pub fn approve_treasury_spend(&mut self, spend_id: TreasurySpendId) {
+        assert_one_yocto();
+        self.internal_assert_not_paused(Pausable::Transfers);
+        let spend = self.internal_take_treasury_spend(spend_id);
+        require!(
+            env::predecessor_account_id() == spend.approver_id,
+            "Only the approver can co-sign the spend"
+        );
+        require!(
+            env::block_timestamp() <= spend.expires_at.0,
+            "The treasury spend has expired"
+        );
+        self.internal_transfer(
+            &env::current_account_id(),
+            &spend.receiver_id,
+            spend.amount.0,
+            spend.memo.as_deref(),
+        );
+    }
+
+    /// Cancels a pending treasury spend and returns the escrowed tokens to the treasury. The
+    /// treasury and the approver can cancel it at any time, anyone else once it has expired.
+    pub fn cancel_treasury_spend(&mut self, spend_id: TreasurySpendId) {
+        let spend = self.internal_take_treasury_spend(spend_id);
+        let caller_id = env::predecessor_account_id();
+        require!(
+            caller_id == spend.treasury_id
+                || caller_id == spend.approver_id
+                || env::block_timestamp() > spend.expires_at.0,
+            "Only the treasury or the approver can cancel the spend before it expires"
+        );
+        if caller_id != spend.treasury_id && caller_id != spend.approver_id {
+            let bounty = self.keeper_bounties.expiry_cleanup.0;
+            self.internal_reward_keeper(&caller_id, "treasury_spend_expiry", bounty);
+        }
+        self.internal_transfer(
+            &env::current_account_id(),
+            &spend.treasury_id,
+            spend.amount.0,
+            Some(&format!("Cancellation of treasury spend {}", spend_id)),
+        );
+    }
+
+    /// Returns the two-man rule of the treasury, or `null` if it isn't guarded.
+    pub fn get_treasury_guard(&self) -> Option<TreasuryGuard> {
+        self.treasury_guard.clone()
+    }
+
+    /// Returns the pending treasury spends, including the expired ones not cancelled yet.
+    pub fn get_treasury_spends(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(TreasurySpendId, TreasurySpend)> {
+        self.treasury_spends
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(spend_id, mut spend)| {
+                spend.amount = U128(Self::internal_to_amount(spend.amount.0));
+                (spend_id, spend)
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Internal method for rejecting the transfers of the treasury that should have been
+    /// proposed as spends.
+    pub(crate) fn internal_assert_treasury_spend(&self, sender_id: &AccountId, amount: u128) {
+        if let Some(guard) = self.treasury_guard.as_ref() {
+            require!(
+                sender_id != &guard.treasury_id || amount <= guard.threshold.0,
+                "Treasury spends above the threshold must be proposed"
+            );
+        }
+    }
+
+    /// Internal method for removing a treasury spend, refunding its storage to the treasury.
+    fn internal_take_treasury_spend(&mut self, spend_id: TreasurySpendId) -> TreasurySpend {
+        let initial_storage_usage = env::storage_usage();
+        let spend = self
+            .treasury_spends
+            .remove(&spend_id)
+            .unwrap_or_else(|| env::panic_str("The treasury spend doesn't exist"));
+        Self::internal_refund_storage(&spend.treasury_id, initial_storage_usage);
+        spend
+    }
+}
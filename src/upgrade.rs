@@ -0,0 +1,585 @@
+use std::ops::RangeInclusive;
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, Gas, Promise, StorageUsage};
+
+use crate::*;
+
+/// The version of the layout of `Contract`. Every version appends its fields to the previous
+/// one, unless noted otherwise.
+///
+/// - Baseline: `BaselineContract`, without an owner or a version tag.
+/// - 0: `OldContract`, without a version tag.
+/// - 1: `OldContract`, then the tag and the account preferences.
+/// - 2: the minter and freeze admin removed from `OldContract` and replaced by roles, then the
+///   tag, the account preferences, the pending owner and the account roles.
+/// - 3: the randomness rounds.
+/// - 4: the wrapped flag.
+/// - 5: the owner recovery.
+/// - 6: the streams.
+/// - 7: the snapshots.
+/// - 8: the call caps.
+/// - 9: the borsh message receivers.
+/// - 10: the max supply and mint limits.
+/// - 11: the netting windows.
+/// - 12: the escrows.
+/// - 13: the keeper tips.
+/// - 14: the successor and balance vouchers.
+/// - 15: the template.
+/// - 16: the council.
+/// - 17: the tokenomics.
+/// - 18: the peg stability module.
+/// - 19: the registration sponsorship.
+/// - 20: the insurance, and the insurance share inserted in the fee split and accumulators.
+/// - 21: the bug bounty.
+/// - 22: the referenda.
+/// - 23: the receiver list.
+/// - 24: the reservations.
+/// - 25: the storage sponsorship total.
+/// - 26: the fee quotes.
+/// - 27: the storage depositors and sweep stats, then the pending references after every later
+///   field.
+/// - 28: the airdrop grants.
+/// - 29: the memo config.
+/// - 30: the price feeds.
+/// - 31: the scheduled transfers.
+pub const STATE_VERSION: u32 = 31;
+
+/// The version that inserted the insurance share in the fee split and accumulators.
+const INSURANCE_VERSION: u32 = 20;
+
+/// The version that added the pending references.
+const PENDING_REFERENCES_VERSION: u32 = 27;
+
+const GAS_FOR_MIGRATE: Gas = Gas::from_tgas(100);
+
+/// The key near-sdk stores the contract struct under.
+const STATE_KEY: &[u8] = b"STATE";
+
+// The layout of the contract the extensions were built on, as written by `new_default_meta`.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct BaselineContract {
+    pub accounts: LookupMap<AccountId, NearToken>,
+    pub total_supply: NearToken,
+    pub bytes_for_longest_account_id: StorageUsage,
+    pub metadata: LazyOption<FungibleTokenMetadata>,
+}
+
+// The fields every versioned layout starts with. The versions 0 and 1 stored the minter and the
+// freeze admin as `Option<AccountId>`, the later ones don't store them (`()`), and the versions
+// before 20 stored the fee config and accumulators without the insurance share.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct StatePrefix<C, A, R> {
+    pub owner_id: AccountId,
+    pub accounts: LookupMap<AccountId, u128>,
+    pub total_supply: u128,
+    pub metadata: LazyOption<FungibleTokenMetadata>,
+    pub mining_programs: UnorderedMap<ProgramId, MiningProgram>,
+    pub next_mining_program_id: ProgramId,
+    pub bond_markets: VersionedLookupMap<BondMarketId, BondMarket>,
+    pub next_bond_market_id: BondMarketId,
+    pub bond_positions: LookupMap<AccountId, Vec<BondPosition>>,
+    pub fee_config: Option<C>,
+    pub fee_accumulators: A,
+    pub fee_category_rates: UnorderedMap<String, u16>,
+    pub account_fee_categories: LookupMap<AccountId, String>,
+    pub relay_keys: LookupMap<AccountId, PublicKey>,
+    pub relay_nonces: LookupMap<AccountId, u64>,
+    pub storage_sponsors: LookupMap<AccountId, NearToken>,
+    pub metrics: Metrics,
+    pub balance_leaf_indexes: LookupMap<AccountId, u64>,
+    pub next_balance_leaf_index: u64,
+    pub balance_tree: LookupMap<(u8, u64), MerkleHash>,
+    pub memo_keys: LookupMap<AccountId, Vec<u8>>,
+    pub payment_pubkeys: LookupMap<AccountId, PaymentPubkey>,
+    pub payment_intents: VersionedLookupMap<IntentId, PaymentIntent>,
+    pub next_payment_intent_id: IntentId,
+    pub co_signers: LookupMap<AccountId, CoSignerConfig>,
+    pub pending_transfers: VersionedLookupMap<PendingTransferId, PendingTransfer>,
+    pub next_pending_transfer_id: PendingTransferId,
+    pub wallet_policies: LookupMap<AccountId, WalletPolicy>,
+    pub wallet_policy_changes: LookupMap<AccountId, WalletPolicyChange>,
+    pub budgets: LookupMap<AccountId, Vec<Budget>>,
+    pub allowances: LookupMap<AccountId, UnorderedMap<AccountId, Allowance>>,
+    pub hygiene_pool: HygienePool,
+    pub supply: SupplyCounters,
+    pub supply_categories: LookupMap<AccountId, SupplyCategory>,
+    pub supply_history: Vector<SupplyPoint>,
+    pub burned_by_account: LookupMap<AccountId, u128>,
+    pub burn_leaderboard: LazyOption<Vec<(AccountId, u128)>>,
+    pub burn_receipts: VersionedLookupMap<BurnReceiptId, BurnReceipt>,
+    pub next_burn_receipt_id: BurnReceiptId,
+    pub mint_proposers: UnorderedSet<AccountId>,
+    pub mint_approvers: UnorderedSet<AccountId>,
+    pub mint_approval_threshold: u32,
+    pub mint_requests: Vector<MintRequest>,
+    pub frozen_accounts: VersionedLookupMap<AccountId, Freeze>,
+    pub jurisdiction_attestors: UnorderedSet<AccountId>,
+    pub account_jurisdictions: LookupMap<AccountId, String>,
+    pub jurisdiction_rules: UnorderedMap<(String, String), JurisdictionRule>,
+    pub vasps: LookupMap<AccountId, String>,
+    pub travel_rule_threshold: Option<u128>,
+    pub attestation_config: Option<AttestationConfig>,
+    pub attestation_epoch: u64,
+    pub attestation_cache: LookupMap<AccountId, (u64, CachedAttestation)>,
+    pub rebate_program: Option<RebateProgram>,
+    pub rebate_pool: u128,
+    pub rebate_accounts: VersionedLookupMap<AccountId, RebateAccount>,
+    pub relayer_usage: UnorderedMap<AccountId, RelayerUsage>,
+    pub relayer_gas_pool: NearToken,
+    pub relayer_gas_price: u128,
+    pub locks: VersionedLookupMap<LockId, Lock>,
+    pub next_lock_id: LockId,
+    pub receipt_balances: LookupMap<(LockId, AccountId), u128>,
+    pub grants: VersionedLookupMap<GrantId, VestingGrant>,
+    pub next_grant_id: GrantId,
+    pub grants_per_holder: LookupMap<AccountId, Vec<GrantId>>,
+    pub position_royalties: PositionRoyalties,
+    pub multicall_targets: UnorderedSet<AccountId>,
+    pub treasury_guard: Option<TreasuryGuard>,
+    pub treasury_spends: UnorderedMap<TreasurySpendId, TreasurySpend>,
+    pub next_treasury_spend_id: TreasurySpendId,
+    pub jobs: LookupMap<JobId, Job>,
+    pub next_job_id: JobId,
+    pub job_items: LookupMap<(JobId, u64), (AccountId, u128)>,
+    pub keeper_bounties: KeeperBounties,
+    pub keeper_pool: u128,
+    pub force_unregister_requests: LookupMap<AccountId, u64>,
+    pub force_unregister_threshold: u128,
+    pub minter_id: R,
+    pub factory_code: LazyOption<Vec<u8>>,
+    pub factory_tokens: Vector<FactoryToken>,
+    pub rename_quarantine: LazyOption<RenameQuarantine>,
+    pub quarantine_large_transfers: LookupMap<AccountId, u64>,
+    pub dust_account_id: Option<AccountId>,
+    pub pause_flags: PauseFlags,
+    pub freeze_admin_id: R,
+    pub account_labels: LookupMap<AccountId, AccountLabels>,
+    pub account_locales: LookupMap<AccountId, Locale>,
+    pub airdrops: VersionedLookupMap<AirdropId, Airdrop>,
+    pub next_airdrop_id: AirdropId,
+    pub airdrop_claims: LookupMap<(AirdropId, u64), u64>,
+}
+
+// The layout of the contracts deployed before the state was versioned, which the version 1
+// extended.
+pub type OldContract = StatePrefix<OldFeeConfig, OldFeeAccumulators, Option<AccountId>>;
+
+// The fee config of the versions before 20.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldFeeConfig {
+    pub fee_bps: u16,
+    pub split: OldFeeSplit,
+    pub stakers_pool_id: AccountId,
+    pub treasury_id: AccountId,
+}
+
+// The fee split of the versions before 20.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldFeeSplit {
+    pub stakers_bps: u16,
+    pub treasury_bps: u16,
+    pub burn_bps: u16,
+    pub rebates_bps: u16,
+}
+
+// The fee buckets of the versions before 20, before the insurance share was tracked.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct OldFeeAccumulators {
+    pub to_stakers: U128,
+    pub to_treasury: U128,
+    pub burned: U128,
+    pub to_rebates: U128,
+}
+
+impl From<OldFeeConfig> for FeeConfig {
+    fn from(old: OldFeeConfig) -> Self {
+        Self {
+            fee_bps: old.fee_bps,
+            split: FeeSplit {
+                stakers_bps: old.split.stakers_bps,
+                treasury_bps: old.split.treasury_bps,
+                burn_bps: old.split.burn_bps,
+                rebates_bps: old.split.rebates_bps,
+                insurance_bps: 0,
+            },
+            stakers_pool_id: old.stakers_pool_id,
+            treasury_id: old.treasury_id,
+        }
+    }
+}
+
+impl From<OldFeeAccumulators> for FeeAccumulators {
+    fn from(old: OldFeeAccumulators) -> Self {
+        Self {
+            to_stakers: old.to_stakers,
+            to_treasury: old.to_treasury,
+            burned: old.burned,
+            to_rebates: old.to_rebates,
+            to_insurance: U128(0),
+        }
+    }
+}
+
+impl From<BaselineContract> for Contract {
+    fn from(baseline: BaselineContract) -> Self {
+        // The baseline measured the storage of the longest account ID on initialization and
+        // persisted it. That measurement is now `BYTES_FOR_LONGEST_ACCOUNT_ID`, computed the same
+        // way at compile time, so the persisted value is dropped.
+        // The balances were stored as `NearToken`, whose borsh encoding is the same as `u128`'s,
+        // under the prefix of the current `accounts` map, so they are read in place.
+        let BaselineContract {
+            accounts: _,
+            total_supply,
+            bytes_for_longest_account_id: _,
+            metadata,
+        } = baseline;
+        let total_supply = total_supply.as_yoctonear();
+        let mut this = Self {
+            total_supply,
+            metadata,
+            // The baseline had no owner. Only the contract account can call `migrate`, so it
+            // takes the ownership until it proposes another owner.
+            ..Self::internal_new_state(env::current_account_id())
+        };
+        // Register the contract's own account so it can hold tokens escrowed by the extension
+        // modules.
+        if !this.accounts.contains_key(&this.owner_id) {
+            let owner_id = this.owner_id.clone();
+            this.internal_register_account(&owner_id);
+        }
+        this.supply.minted = total_supply;
+        this.internal_record_supply();
+        this
+    }
+}
+
+// Moves the fields of the prefix, the later fields taking their initial values.
+impl<C: Into<FeeConfig>, A: Into<FeeAccumulators>, R> From<StatePrefix<C, A, R>> for Contract {
+    fn from(prefix: StatePrefix<C, A, R>) -> Self {
+        Self {
+            accounts: prefix.accounts,
+            total_supply: prefix.total_supply,
+            metadata: prefix.metadata,
+            mining_programs: prefix.mining_programs,
+            next_mining_program_id: prefix.next_mining_program_id,
+            bond_markets: prefix.bond_markets,
+            next_bond_market_id: prefix.next_bond_market_id,
+            bond_positions: prefix.bond_positions,
+            fee_config: prefix.fee_config.map(Into::into),
+            fee_accumulators: prefix.fee_accumulators.into(),
+            fee_category_rates: prefix.fee_category_rates,
+            account_fee_categories: prefix.account_fee_categories,
+            relay_keys: prefix.relay_keys,
+            relay_nonces: prefix.relay_nonces,
+            storage_sponsors: prefix.storage_sponsors,
+            metrics: prefix.metrics,
+            balance_leaf_indexes: prefix.balance_leaf_indexes,
+            next_balance_leaf_index: prefix.next_balance_leaf_index,
+            balance_tree: prefix.balance_tree,
+            memo_keys: prefix.memo_keys,
+            payment_pubkeys: prefix.payment_pubkeys,
+            payment_intents: prefix.payment_intents,
+            next_payment_intent_id: prefix.next_payment_intent_id,
+            co_signers: prefix.co_signers,
+            pending_transfers: prefix.pending_transfers,
+            next_pending_transfer_id: prefix.next_pending_transfer_id,
+            wallet_policies: prefix.wallet_policies,
+            wallet_policy_changes: prefix.wallet_policy_changes,
+            budgets: prefix.budgets,
+            allowances: prefix.allowances,
+            hygiene_pool: prefix.hygiene_pool,
+            supply: prefix.supply,
+            supply_categories: prefix.supply_categories,
+            supply_history: prefix.supply_history,
+            burned_by_account: prefix.burned_by_account,
+            burn_leaderboard: prefix.burn_leaderboard,
+            burn_receipts: prefix.burn_receipts,
+            next_burn_receipt_id: prefix.next_burn_receipt_id,
+            mint_proposers: prefix.mint_proposers,
+            mint_approvers: prefix.mint_approvers,
+            mint_approval_threshold: prefix.mint_approval_threshold,
+            mint_requests: prefix.mint_requests,
+            frozen_accounts: prefix.frozen_accounts,
+            jurisdiction_attestors: prefix.jurisdiction_attestors,
+            account_jurisdictions: prefix.account_jurisdictions,
+            jurisdiction_rules: prefix.jurisdiction_rules,
+            vasps: prefix.vasps,
+            travel_rule_threshold: prefix.travel_rule_threshold,
+            attestation_config: prefix.attestation_config,
+            attestation_epoch: prefix.attestation_epoch,
+            attestation_cache: prefix.attestation_cache,
+            rebate_program: prefix.rebate_program,
+            rebate_pool: prefix.rebate_pool,
+            rebate_accounts: prefix.rebate_accounts,
+            relayer_usage: prefix.relayer_usage,
+            relayer_gas_pool: prefix.relayer_gas_pool,
+            relayer_gas_price: prefix.relayer_gas_price,
+            locks: prefix.locks,
+            next_lock_id: prefix.next_lock_id,
+            receipt_balances: prefix.receipt_balances,
+            grants: prefix.grants,
+            next_grant_id: prefix.next_grant_id,
+            grants_per_holder: prefix.grants_per_holder,
+            position_royalties: prefix.position_royalties,
+            multicall_targets: prefix.multicall_targets,
+            treasury_guard: prefix.treasury_guard,
+            treasury_spends: prefix.treasury_spends,
+            next_treasury_spend_id: prefix.next_treasury_spend_id,
+            jobs: prefix.jobs,
+            next_job_id: prefix.next_job_id,
+            job_items: prefix.job_items,
+            keeper_bounties: prefix.keeper_bounties,
+            keeper_pool: prefix.keeper_pool,
+            force_unregister_requests: prefix.force_unregister_requests,
+            force_unregister_threshold: prefix.force_unregister_threshold,
+            factory_code: prefix.factory_code,
+            factory_tokens: prefix.factory_tokens,
+            rename_quarantine: prefix.rename_quarantine,
+            quarantine_large_transfers: prefix.quarantine_large_transfers,
+            dust_account_id: prefix.dust_account_id,
+            pause_flags: prefix.pause_flags,
+            account_labels: prefix.account_labels,
+            account_locales: prefix.account_locales,
+            airdrops: prefix.airdrops,
+            next_airdrop_id: prefix.next_airdrop_id,
+            airdrop_claims: prefix.airdrop_claims,
+            ..Self::internal_new_state(prefix.owner_id)
+        }
+    }
+}
+
+impl Contract {
+    /// Reads a state of the version 2 or later, whose prefix has the fee config `C` and fee
+    /// accumulators `A`, if its tag is in `versions`.
+    fn internal_read_tagged_state<C, A>(state: &[u8], versions: RangeInclusive<u32>) -> Option<Self>
+    where
+        C: BorshDeserialize + Into<FeeConfig>,
+        A: BorshDeserialize + Into<FeeAccumulators>,
+    {
+        let mut reader = state;
+        let prefix = StatePrefix::<C, A, ()>::deserialize(&mut reader).ok()?;
+        let version = u32::deserialize(&mut reader).ok()?;
+        if !versions.contains(&version) {
+            return None;
+        }
+        let mut this = Self::from(prefix);
+        this.internal_read_fields(&mut reader, version).ok()?;
+        reader.is_empty().then_some(this)
+    }
+
+    /// Reads the fields the versions up to `version` appended after the tag, the later ones
+    /// keeping their initial values.
+    fn internal_read_fields(&mut self, reader: &mut &[u8], version: u32) -> borsh::io::Result<()> {
+        macro_rules! read {
+            ($($field:ident),+) => {
+                $(self.$field = BorshDeserialize::deserialize(reader)?;)+
+            };
+        }
+        read!(account_preferences, pending_owner_id, account_roles);
+        if version >= 3 {
+            read!(
+                randomness_rounds,
+                next_randomness_round_id,
+                randomness_commitments
+            );
+        }
+        if version >= 4 {
+            read!(wrapped);
+        }
+        if version >= 5 {
+            read!(recovery_config, owner_recovery, owner_active_at);
+        }
+        if version >= 6 {
+            read!(streams, next_stream_id, streamed_supply);
+        }
+        if version >= 7 {
+            read!(snapshots, balance_checkpoints);
+        }
+        if version >= 8 {
+            read!(call_caps);
+        }
+        if version >= 9 {
+            read!(borsh_msg_receivers);
+        }
+        if version >= 10 {
+            read!(max_supply, mint_limits, daily_mints);
+        }
+        if version >= 11 {
+            read!(netting_windows, netted_batches);
+        }
+        if version >= 12 {
+            read!(escrows, next_escrow_id);
+        }
+        if version >= 13 {
+            read!(keeper_tips);
+        }
+        if version >= 14 {
+            read!(successor_id, balance_vouchers, next_voucher_nonce);
+        }
+        if version >= 15 {
+            read!(template);
+        }
+        if version >= 16 {
+            read!(council, council_proposals, next_council_proposal_id);
+        }
+        if version >= 17 {
+            read!(tokenomics);
+        }
+        if version >= 18 {
+            read!(psm_config, psm_reserves, psm_debt);
+        }
+        if version >= 19 {
+            read!(
+                sponsorship_pool,
+                sponsorship_cap,
+                sponsored_registrations,
+                sponsored_accounts
+            );
+        }
+        if version >= INSURANCE_VERSION {
+            read!(
+                insurance_config,
+                insurance_fund,
+                insurance_reserved,
+                insurance_claims,
+                next_insurance_claim_id
+            );
+        }
+        if version >= 21 {
+            read!(bounty_pool, disclosures, next_disclosure_id);
+        }
+        if version >= 22 {
+            read!(
+                referendum_config,
+                referenda,
+                referendum_stakes,
+                open_referenda,
+                next_referendum_id
+            );
+        }
+        if version >= 23 {
+            read!(receiver_list_mode, listed_receivers);
+        }
+        if version >= 24 {
+            read!(reservations, next_reservation_id);
+        }
+        // The balances of the sponsors registered before aren't counted.
+        if version >= 25 {
+            read!(storage_sponsorship_total);
+        }
+        if version >= 26 {
+            read!(next_fee_quote_id, fee_quotes);
+        }
+        // The deposits paid before are refunded to the accounts themselves.
+        if version >= PENDING_REFERENCES_VERSION {
+            read!(storage_depositors, storage_sweep_stats);
+        }
+        if version >= 28 {
+            read!(airdrop_grants);
+        }
+        if version >= 29 {
+            read!(memo_config);
+        }
+        if version >= 30 {
+            read!(price_feeds);
+        }
+        if version >= 31 {
+            read!(scheduled_transfers, next_scheduled_transfer_id);
+        }
+        // The records opened before aren't counted.
+        if version >= PENDING_REFERENCES_VERSION {
+            read!(pending_references);
+        }
+        Ok(())
+    }
+
+    /// Reads a state of the version 0 or 1.
+    fn internal_read_old_state(state: &[u8]) -> Option<Self> {
+        let mut reader = state;
+        let old = OldContract::deserialize(&mut reader).ok()?;
+        let minter_id = old.minter_id.clone();
+        let freeze_admin_id = old.freeze_admin_id.clone();
+        let mut this = Self::from(old);
+        if !reader.is_empty() {
+            if u32::deserialize(&mut reader).ok()? != 1 {
+                return None;
+            }
+            // The first states of the version 1 were written before the preferences were added.
+            if !reader.is_empty() {
+                this.account_preferences = BorshDeserialize::deserialize(&mut reader).ok()?;
+            }
+            if !reader.is_empty() {
+                return None;
+            }
+        }
+        // The single minter and freeze admin became roles in the version 2.
+        if let Some(minter_id) = minter_id {
+            this.internal_grant_role(&minter_id, Role::Minter);
+        }
+        if let Some(freeze_admin_id) = freeze_admin_id {
+            this.internal_grant_role(&freeze_admin_id, Role::FreezeAdmin);
+        }
+        Some(this)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Deploys `code` on the contract account and calls `migrate` on it (owner only). Both
+    /// actions are in the same receipt, so a failing migration reverts the deployment.
+    #[payable]
+    pub fn upgrade(&mut self, code: Base64VecU8) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        require!(!code.0.is_empty(), "The code is empty");
+        self.internal_promise_budget()
+            .call(GAS_FOR_MIGRATE, ZERO_TOKEN)
+            .check();
+        Promise::new(env::current_account_id())
+            .deploy_contract(code.0)
+            .function_call("migrate".to_string(), vec![], ZERO_TOKEN, GAS_FOR_MIGRATE)
+    }
+
+    /// Reads the state written by any previous version of the contract and converts it to the
+    /// current layout. Does nothing if the state is already up to date.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(STATE_KEY)
+            .unwrap_or_else(|| env::panic_str("The contract isn't initialized"));
+        if let Some(this) = Self::internal_read_tagged_state::<FeeConfig, FeeAccumulators>(
+            &state,
+            INSURANCE_VERSION..=STATE_VERSION,
+        ) {
+            return this;
+        }
+        if let Some(this) = Self::internal_read_tagged_state::<OldFeeConfig, OldFeeAccumulators>(
+            &state,
+            2..=INSURANCE_VERSION - 1,
+        ) {
+            return this;
+        }
+        if let Some(this) = Self::internal_read_old_state(&state) {
+            return this;
+        }
+        if let Ok(baseline) = BaselineContract::try_from_slice(&state) {
+            return Self::from(baseline);
+        }
+        let mut reader = state.as_slice();
+        match StatePrefix::<FeeConfig, FeeAccumulators, ()>::deserialize(&mut reader)
+            .and_then(|_| u32::deserialize(&mut reader))
+        {
+            Ok(version) if version > STATE_VERSION => {
+                env::panic_str(&format!("Unknown state version {}", version))
+            }
+            _ => env::panic_str("Cannot read the state of the contract"),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Versioned storage of the persisted records.
+//!
+//! The records of the large maps are stored wrapped in a versioned enum, and upgraded to the
+//! current version when they are read, so a field can be added to a record without migrating
+//! the whole map. To change a record, keep its current struct as the previous version, add a
+//! variant holding the new struct and upgrade the old variants in `Versioned::from_record`.
+
+use std::marker::PhantomData;
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::IntoStorageKey;
+
+/// A record stored through its versioned enum.
+pub trait Versioned: Sized {
+    /// The versioned enum the record is stored as.
+    type Record: BorshSerialize + BorshDeserialize;
+
+    /// Upgrades a stored record of any version to the current one.
+    fn from_record(record: Self::Record) -> Self;
+
+    /// Wraps the record into the current version.
+    fn into_record(self) -> Self::Record;
+}
+
+/// Declares the versioned enum of a record that only has one version so far.
+macro_rules! versioned_record {
+    ($versioned:ident, $current:ident) => {
+        #[derive(near_sdk::borsh::BorshDeserialize, near_sdk::borsh::BorshSerialize)]
+        #[borsh(crate = "near_sdk::borsh")]
+        pub enum $versioned {
+            V1($current),
+        }
+
+        impl $crate::versioned::Versioned for $current {
+            type Record = $versioned;
+
+            fn from_record(record: $versioned) -> Self {
+                match record {
+                    $versioned::V1(current) => current,
+                }
+            }
+
+            fn into_record(self) -> $versioned {
+                $versioned::V1(self)
+            }
+        }
+    };
+}
+pub(crate) use versioned_record;
+
+/// A `LookupMap` storing its values as versioned records, with the same interface.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[borsh(crate = "near_sdk::borsh")]
+pub struct VersionedLookupMap<K, V: Versioned> {
+    #[borsh(bound(serialize = "", deserialize = ""))]
+    map: LookupMap<K, V::Record>,
+    #[borsh(skip, bound(deserialize = ""))]
+    value: PhantomData<V>,
+}
+
+impl<K, V> VersionedLookupMap<K, V>
+where
+    K: BorshSerialize,
+    V: Versioned + Clone,
+{
+    pub fn new<S: IntoStorageKey>(key_prefix: S) -> Self {
+        Self {
+            map: LookupMap::new(key_prefix),
+            value: PhantomData,
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.map.get(key).map(V::from_record)
+    }
+
+    /// Returns the record as it's stored, for the lazy migrations needing the fields its
+    /// current version dropped.
+    pub fn get_record(&self, key: &K) -> Option<V::Record> {
+        self.map.get(key)
+    }
+
+    pub fn insert(&mut self, key: &K, value: &V) -> Option<V> {
+        self.map
+            .insert(key, &value.clone().into_record())
+            .map(V::from_record)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.map.remove(key).map(V::from_record)
+    }
+}
@@ -0,0 +1,31 @@
+//! The stateless read path.
+//!
+//! A method taking `&self` deserializes the whole contract state before running, however few
+//! of its fields it reads. The hottest views (`ft_balance_of`, `ft_metadata`,
+//! `storage_balance_of`, `storage_balance_bounds`) are declared without a receiver instead and
+//! open only the collection they need, straight from its storage key, so that wallets can poll
+//! them cheaply.
+
+use near_sdk::collections::{LazyOption, LookupMap};
+
+use crate::*;
+
+impl Contract {
+    /// The `accounts` map, opened without loading the contract state.
+    pub(crate) fn stateless_accounts() -> LookupMap<AccountId, u128> {
+        LookupMap::new(StorageKey::Core(CoreKey::Accounts))
+    }
+
+    /// The `metadata` cell, opened without loading the contract state.
+    pub(crate) fn stateless_metadata() -> LazyOption<FungibleTokenMetadata> {
+        LazyOption::new(StorageKey::Core(CoreKey::Metadata), None)
+    }
+
+    /// The `rename_quarantine` cell, opened without loading the contract state.
+    pub(crate) fn stateless_rename_quarantine() -> LazyOption<RenameQuarantine> {
+        LazyOption::new(
+            StorageKey::Quarantine(QuarantineKey::RenameQuarantine),
+            None,
+        )
+    }
+}
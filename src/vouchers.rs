@@ -0,0 +1,103 @@
+//! Balance vouchers for the successor token contract.
+//!
+//! When the owner deploys a new version of the token as a separate contract, it configures it
+//! as the successor. Holders then migrate on their own: `export_balance_voucher` burns their
+//! balance and records a voucher for it, which the successor honors by redeeming it. A contract
+//! holds no key to sign the voucher with, so the successor authenticates it by reading it from
+//! this contract with `redeem_balance_voucher`, which only it can call and which consumes it.
+
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct BalanceVoucher {
+    /// The number of the voucher, unique across all the accounts.
+    pub nonce: u64,
+    pub account_id: AccountId,
+    /// The balance burned for the voucher.
+    pub amount: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the token contract honoring the balance vouchers, or stops the exports when `None`
+    /// is passed (owner only).
+    #[payable]
+    pub fn set_successor_contract(&mut self, successor_id: Option<AccountId>) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.successor_id = successor_id;
+    }
+
+    pub fn get_successor_contract(&self) -> Option<AccountId> {
+        self.successor_id.clone()
+    }
+
+    /// Burns the caller's whole balance and records a voucher for it, to be honored by the
+    /// successor contract. The storage of the voucher is charged to the attached deposit, and
+    /// refunded to the caller once the voucher is redeemed.
+    #[payable]
+    pub fn export_balance_voucher(&mut self) -> BalanceVoucher {
+        require!(
+            env::attached_deposit() >= NearToken::from_yoctonear(1),
+            "Requires attached deposit of at least 1 yoctoNEAR"
+        );
+        let initial_storage_usage = env::storage_usage();
+        self.internal_assert_not_paused(Pausable::Mints);
+        let successor_id = self
+            .successor_id
+            .clone()
+            .unwrap_or_else(|| env::panic_str("There is no successor contract"));
+        let account_id = env::predecessor_account_id();
+        self.internal_assert_not_frozen(&account_id);
+        let shares = self.internal_unwrap_balance_of(&account_id);
+        require!(shares > 0, "There is no balance to export");
+        // The voucher is worth the burned tokens, which stop growing with the index.
+        let amount = Self::internal_to_amount(shares);
+        self.internal_burn(&account_id, shares, Some("Balance voucher"));
+
+        let voucher = BalanceVoucher {
+            nonce: self.next_voucher_nonce,
+            account_id,
+            amount: U128(amount),
+        };
+        self.next_voucher_nonce += 1;
+        BalanceVoucherExported {
+            nonce: voucher.nonce,
+            account_id: &voucher.account_id,
+            amount: &voucher.amount,
+            successor_id: &successor_id,
+        }
+        .emit();
+        self.balance_vouchers.insert(&voucher.nonce, &voucher);
+        Self::internal_charge_storage(initial_storage_usage);
+        voucher
+    }
+
+    /// Consumes a voucher and returns it, for the successor contract to credit its account, and
+    /// refunds its storage to the account. Must be called by the successor contract.
+    pub fn redeem_balance_voucher(&mut self, nonce: u64) -> BalanceVoucher {
+        require!(
+            self.successor_id.as_ref() == Some(&env::predecessor_account_id()),
+            "Only the successor contract can redeem a voucher"
+        );
+        let initial_storage_usage = env::storage_usage();
+        let voucher = self
+            .balance_vouchers
+            .remove(&nonce)
+            .unwrap_or_else(|| env::panic_str("The voucher doesn't exist or was redeemed"));
+        Self::internal_refund_storage(&voucher.account_id, initial_storage_usage);
+        voucher
+    }
+
+    /// Returns a voucher the successor contract didn't redeem yet, or `null`.
+    pub fn get_balance_voucher(&self, nonce: u64) -> Option<BalanceVoucher> {
+        self.balance_vouchers.get(&nonce)
+    }
+}
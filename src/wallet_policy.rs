@@ -0,0 +1,164 @@
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, NearSchema};
+
+use crate::*;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// The shortest timelock a wallet policy can be changed with
+const MIN_WALLET_POLICY_TIMELOCK_SEC: u64 = 86_400;
+
+// A hot/cold wallet policy. Outgoing transfers above `large_transfer_threshold` may only go to
+// the pre-registered cold account, and changing the policy takes `timelock_sec`, which limits
+// what an attacker holding the account's (hot) key can take.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct WalletPolicy {
+    /// The only account large transfers can be sent to.
+    pub cold_account_id: AccountId,
+    /// Transfers of more than this amount may only go to the cold account.
+    pub large_transfer_threshold: U128,
+    /// The number of seconds a policy change has to wait before it can be executed.
+    pub timelock_sec: u64,
+}
+
+// A change of wallet policy waiting for its timelock. `policy == None` removes the policy.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, NearSchema, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct WalletPolicyChange {
+    pub policy: Option<WalletPolicy>,
+    /// The timestamp (in nanoseconds) from which the change can be executed.
+    pub executable_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the caller's wallet policy. The first policy applies right away, while changing or
+    /// removing an existing one is queued for the current policy's timelock and has to be
+    /// executed with `execute_wallet_policy_change`. Replaces any change already queued.
+    #[payable]
+    pub fn set_wallet_policy(&mut self, policy: Option<WalletPolicy>) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        if let Some(policy) = policy.as_ref() {
+            self.internal_unwrap_balance_of(&policy.cold_account_id);
+            require!(
+                policy.cold_account_id != account_id,
+                "The cold account should be different from the account"
+            );
+            require!(
+                policy.timelock_sec >= MIN_WALLET_POLICY_TIMELOCK_SEC,
+                "The wallet policy timelock is too short"
+            );
+        }
+
+        match self.wallet_policies.get(&account_id) {
+            None => {
+                let policy = policy.unwrap_or_else(|| env::panic_str("There is no wallet policy"));
+                self.wallet_policies.insert(&account_id, &policy);
+            }
+            Some(current) => {
+                let executable_at =
+                    env::block_timestamp() + current.timelock_sec.saturating_mul(NANOS_PER_SECOND);
+                self.wallet_policy_changes.insert(
+                    &account_id,
+                    &WalletPolicyChange {
+                        policy,
+                        executable_at: U64(executable_at),
+                    },
+                );
+                log!(
+                    "Wallet policy change of {} executable at {}",
+                    account_id,
+                    executable_at
+                );
+            }
+        }
+    }
+
+    /// Applies the caller's queued wallet policy change once its timelock has passed.
+    #[payable]
+    pub fn execute_wallet_policy_change(&mut self) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let change = self
+            .wallet_policy_changes
+            .get(&account_id)
+            .unwrap_or_else(|| env::panic_str("There is no wallet policy change"));
+        require!(
+            env::block_timestamp() >= change.executable_at.0,
+            "The wallet policy change is still timelocked"
+        );
+
+        self.wallet_policy_changes.remove(&account_id);
+        match change.policy {
+            Some(policy) => self.wallet_policies.insert(&account_id, &policy),
+            None => self.wallet_policies.remove(&account_id),
+        };
+    }
+
+    /// Drops the caller's queued wallet policy change.
+    pub fn cancel_wallet_policy_change(&mut self) {
+        let account_id = env::predecessor_account_id();
+        require!(
+            self.wallet_policy_changes.remove(&account_id).is_some(),
+            "There is no wallet policy change"
+        );
+    }
+
+    /// Returns the wallet policy of an account, if any.
+    pub fn get_wallet_policy(&self, account_id: AccountId) -> Option<WalletPolicy> {
+        self.wallet_policies.get(&account_id)
+    }
+
+    /// Returns the queued wallet policy change of an account, if any.
+    pub fn get_wallet_policy_change(&self, account_id: AccountId) -> Option<WalletPolicyChange> {
+        self.wallet_policy_changes.get(&account_id)
+    }
+}
+
+impl Contract {
+    /// Internal method for applying the transfer policies (pauses, jurisdiction rules, travel
+    /// rule, attestations, the treasury two-man rule, the rename quarantine, and the wallet
+    /// policy and budgets of `sender_id`) to a user transfer, panicking if they don't allow it.
+    pub(crate) fn internal_apply_transfer_policy(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+        travel_rule_ref: Option<&str>,
+    ) {
+        self.internal_assert_treasury_spend(sender_id, amount);
+        self.internal_apply_spend_policy(sender_id, receiver_id, amount, memo, travel_rule_ref);
+    }
+
+    /// Internal method for applying the transfer policies but the treasury two-man rule, to the
+    /// treasury spends proposed for the approver's co-signature.
+    pub(crate) fn internal_apply_spend_policy(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: u128,
+        memo: Option<&str>,
+        travel_rule_ref: Option<&str>,
+    ) {
+        self.internal_assert_not_paused(Pausable::Transfers);
+        self.internal_assert_jurisdiction_rules(sender_id, receiver_id, amount);
+        self.internal_assert_travel_rule(sender_id, receiver_id, amount, travel_rule_ref);
+        self.internal_assert_attested_for_transfer(sender_id, amount);
+        self.internal_assert_rename_quarantine(sender_id, amount);
+        if let Some(policy) = self.wallet_policies.get(sender_id) {
+            require!(
+                amount <= policy.large_transfer_threshold.0
+                    || receiver_id == &policy.cold_account_id,
+                "Large transfers may only go to the cold account"
+            );
+        }
+        self.internal_spend_budget(sender_id, amount, memo);
+    }
+}
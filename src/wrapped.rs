@@ -0,0 +1,85 @@
+//! Wrapped NEAR mode.
+//!
+//! A contract initialized with `new_wrapped` is a wNEAR-style token: `near_deposit` mints one
+//! token per yoctoNEAR attached and `near_withdraw` burns tokens to send the NEAR back, so every
+//! token in supply is backed by the NEAR the contract holds. `ft_mint`, the mint requests, the
+//! PSM and the collateralized mints are disabled in this mode for the peg to hold.
+
+use near_sdk::{assert_one_yocto, env, log, near_bindgen, require, Promise};
+
+use crate::storage::StorageManagement;
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Initializes a wrapped NEAR contract with no supply, owned by `owner_id`.
+    #[init]
+    pub fn new_wrapped(owner_id: AccountId, metadata: FungibleTokenMetadata) -> Self {
+        let mut this = Self::new(owner_id, U128(0), metadata, None);
+        this.wrapped = true;
+        this
+    }
+
+    /// Mints tokens 1:1 for the attached NEAR to the caller. An unregistered caller is
+    /// registered first, its storage being paid out of the deposit.
+    #[payable]
+    pub fn near_deposit(&mut self) {
+        self.internal_assert_wrapped();
+        self.internal_assert_not_paused(Pausable::Mints);
+        let account_id = env::predecessor_account_id();
+        self.internal_assert_not_frozen(&account_id);
+        let mut amount = env::attached_deposit();
+        if !self.accounts.contains_key(&account_id) {
+            let min_balance = Self::storage_balance_bounds().min;
+            amount = amount.checked_sub(min_balance).unwrap_or_else(|| {
+                env::panic_str("The attached deposit is less than the minimum storage balance")
+            });
+            self.internal_register_account(&account_id);
+            log!(
+                "Registered {} for {} out of the deposit",
+                account_id,
+                min_balance
+            );
+        }
+        require!(!amount.is_zero(), "The attached deposit should be positive");
+        self.internal_mint(
+            &account_id,
+            Self::internal_to_shares(amount.as_yoctonear()),
+            Some("Wrapped NEAR deposit"),
+        );
+    }
+
+    /// Burns `amount` of the caller's tokens and sends as much NEAR back to it.
+    #[payable]
+    pub fn near_withdraw(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        self.internal_assert_wrapped();
+        self.internal_assert_not_paused(Pausable::Mints);
+        let account_id = env::predecessor_account_id();
+        self.internal_assert_not_frozen(&account_id);
+        require!(amount.0 > 0, "The amount should be a positive number");
+        self.internal_burn(
+            &account_id,
+            Self::internal_to_shares(amount.0),
+            Some("Wrapped NEAR withdrawal"),
+        );
+        Promise::new(account_id).transfer(NearToken::from_yoctonear(amount.0))
+    }
+
+    /// Returns whether the tokens are wrapped NEAR.
+    pub fn is_wrapped(&self) -> bool {
+        self.wrapped
+    }
+}
+
+impl Contract {
+    fn internal_assert_wrapped(&self) {
+        require!(self.wrapped, "The contract isn't in the wrapped NEAR mode");
+    }
+
+    /// Internal method for rejecting the mints that aren't backed by a NEAR deposit. Called by
+    /// every method minting tokens, other than `near_deposit`.
+    pub(crate) fn internal_assert_not_wrapped(&self) {
+        require!(!self.wrapped, "Wrapped NEAR is only minted by near_deposit");
+    }
+}
@@ -0,0 +1,116 @@
+use near_ft::access::Role;
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::testing_env;
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build()
+}
+
+#[test]
+fn test_ownership_is_transferred_once_accepted() {
+    let (mut contract, mut context) = setup();
+    contract.propose_owner(Some(accounts(1)));
+    assert_eq!(contract.get_owner(), accounts(0));
+    assert_eq!(contract.get_pending_owner(), Some(accounts(1)));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.accept_ownership();
+
+    assert_eq!(contract.get_owner(), accounts(1));
+    assert_eq!(contract.get_pending_owner(), None);
+    assert!(get_logs()[0].contains("\"event\":\"ownership_transferred\""));
+    // The new owner holds every role.
+    contract.set_icon(None);
+}
+
+#[test]
+#[should_panic(expected = "The ownership wasn't proposed to the caller")]
+fn test_ownership_is_accepted_by_the_proposed_account() {
+    let (mut contract, mut context) = setup();
+    contract.propose_owner(Some(accounts(1)));
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.accept_ownership();
+}
+
+#[test]
+fn test_roles_delegate_privileged_methods() {
+    let (mut contract, mut context) = setup();
+    contract.grant_role(accounts(1), Role::Minter);
+    contract.grant_role(accounts(1), Role::Pauser);
+    assert_eq!(
+        contract.get_roles(accounts(1)),
+        vec![Role::Minter, Role::Pauser]
+    );
+    assert!(contract.has_role(accounts(0), Role::FeeAdmin));
+    assert!(!contract.has_role(accounts(1), Role::FeeAdmin));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_mint(accounts(1), U128(100), None);
+    contract.pause();
+    assert!(contract.get_pause_flags().mints);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+
+    contract.renounce_role(Role::Pauser);
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.revoke_role(accounts(1), Role::Minter);
+    assert!(contract.get_roles(accounts(1)).is_empty());
+    let logs = get_logs();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].contains("\"event\":\"role_revoked\""));
+    assert!(logs[0].contains("\"role\":\"minter\""));
+}
+
+#[test]
+#[should_panic(expected = "Only the owner and the pausers can call this method")]
+fn test_roles_are_checked() {
+    let (mut contract, mut context) = setup();
+    contract.grant_role(accounts(1), Role::Minter);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.pause();
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can call this method")]
+fn test_roles_are_granted_by_the_owner() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.grant_role(accounts(1), Role::Minter);
+}
+
+#[test]
+fn test_mints_and_burns_need_the_minter_role() {
+    let (mut contract, mut context) = setup();
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    for result in [
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.ft_mint(accounts(1), U128(100), None)
+        })),
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.ft_burn(U128(40), None)
+        })),
+    ] {
+        assert!(result
+            .unwrap_err()
+            .downcast_ref::<String>()
+            .unwrap()
+            .contains("Only the owner and the minters can call this method"));
+    }
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert_eq!(contract.ft_total_supply(), U128(1_000));
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.grant_role(accounts(1), Role::Minter);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_mint(accounts(1), U128(100), None);
+    contract.ft_burn(U128(40), None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(160));
+    assert_eq!(contract.ft_total_supply(), U128(1_060));
+}
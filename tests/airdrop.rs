@@ -0,0 +1,106 @@
+use near_ft::airdrop::AirdropVesting;
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::grants::GrantTransferPolicy;
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, NearToken};
+
+fn leaf(index: u64, account_id: &AccountId, amount: u128) -> [u8; 32] {
+    env::sha256_array(&near_sdk::borsh::to_vec(&(index, account_id, amount)).unwrap())
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    env::sha256_array(&[left.as_slice(), right.as_slice()].concat())
+}
+
+#[test]
+#[should_panic(expected = "The allocation was already claimed")]
+fn test_airdrop_allocations_are_claimed_once() {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+
+    let left = leaf(0, &accounts(1), 100);
+    let right = leaf(1, &accounts(2), 50);
+    let root = hash_pair(&left, &right);
+    let airdrop_id = contract.create_airdrop(Base64VecU8(root.to_vec()), U128(150), None);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    let proof = vec![Base64VecU8(right.to_vec())];
+    contract.claim_airdrop(airdrop_id, 0, U128(100), proof.clone());
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert!(contract.is_airdrop_claimed(airdrop_id, 0));
+    assert!(!contract.is_airdrop_claimed(airdrop_id, 1));
+
+    contract.claim_airdrop(airdrop_id, 0, U128(100), proof);
+}
+
+#[test]
+#[should_panic(expected = "The allocation proof is invalid")]
+fn test_airdrop_rejects_an_inflated_allocation() {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+
+    let left = leaf(0, &accounts(1), 100);
+    let right = leaf(1, &accounts(2), 50);
+    let root = hash_pair(&left, &right);
+    let airdrop_id = contract.create_airdrop(Base64VecU8(root.to_vec()), U128(150), None);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.claim_airdrop(airdrop_id, 1, U128(150), vec![Base64VecU8(left.to_vec())]);
+}
+
+#[test]
+fn test_vesting_airdrop_claims_create_grants() {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+
+    let left = leaf(0, &accounts(1), 100);
+    let right = leaf(1, &accounts(2), 50);
+    let root = hash_pair(&left, &right);
+    let airdrop_id = contract.create_airdrop(
+        Base64VecU8(root.to_vec()),
+        U128(150),
+        Some(AirdropVesting {
+            cliff_sec: 0,
+            duration_sec: 100,
+            transfer_policy: GrantTransferPolicy::NonTransferable,
+        }),
+    );
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.claim_airdrop(airdrop_id, 0, U128(100), vec![Base64VecU8(right.to_vec())]);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+
+    testing_env!(context.block_timestamp(50 * 1_000_000_000).build());
+    let claim = contract.get_airdrop_claim(airdrop_id, 0);
+    assert!(claim.claimed);
+    let grant = claim.grant.unwrap();
+    assert_eq!(grant.owner_id, accounts(1));
+    assert_eq!(grant.claimable, U128(50));
+    assert!(contract.get_airdrop_claim(airdrop_id, 1).grant.is_none());
+}
@@ -0,0 +1,361 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, NearToken};
+
+const SECOND: u64 = 1_000_000_000;
+
+// Alice holds 1,000 tokens. Bob and charlie are registered without tokens.
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build()
+}
+
+// Grants an allowance from the predecessor, attaching enough for its storage. Returns the
+// storage cost charged.
+fn approve(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    spender_id: AccountId,
+    amount: u128,
+    expires_at: Option<u64>,
+) -> NearToken {
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.ft_approve(spender_id, U128(amount), expires_at.map(U64));
+    let cost = NearToken::from_near(1).saturating_sub(refunded_to(&env::predecessor_account_id()));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    cost
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_increase_and_decrease_allowance() {
+    let (mut contract, mut context) = setup();
+    approve(&mut contract, &mut context, accounts(1), 100, None);
+    contract.ft_increase_allowance(accounts(1), U128(50), None);
+    contract.ft_decrease_allowance(accounts(1), U128(30));
+    assert_eq!(contract.ft_allowance(accounts(0), accounts(1)), U128(120));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer_from(accounts(0), accounts(2), U128(20), None);
+    assert_eq!(contract.ft_allowance(accounts(0), accounts(1)), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(20));
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.ft_decrease_allowance(accounts(1), U128(500));
+    assert!(contract.allowances_of(accounts(0), None, None).is_empty());
+}
+
+#[test]
+fn test_approvals_pay_for_their_storage() {
+    let (mut contract, mut context) = setup();
+    let cost = approve(&mut contract, &mut context, accounts(1), 100, None);
+    assert!(cost > NearToken::from_yoctonear(0));
+    assert!(cost < NearToken::from_millinear(10));
+
+    // Revoking refunds the storage to the owner.
+    contract.ft_revoke(accounts(1));
+    assert_eq!(refunded_to(&accounts(0)), cost);
+    assert!(contract.allowances_of(accounts(0), None, None).is_empty());
+}
+
+#[test]
+fn test_using_up_the_allowance_refunds_the_owner() {
+    let (mut contract, mut context) = setup();
+    let cost = approve(&mut contract, &mut context, accounts(1), 100, None);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer_from(accounts(0), accounts(1), U128(100), None);
+    assert_eq!(refunded_to(&accounts(0)), cost);
+    assert_eq!(refunded_to(&accounts(1)), NearToken::from_yoctonear(0));
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_approvals_need_the_storage_deposit() {
+    let (mut contract, _) = setup();
+    contract.ft_approve(accounts(1), U128(100), None);
+}
+
+#[test]
+#[should_panic(expected = "Requires attached deposit of at least 1 yoctoNEAR")]
+fn test_approvals_need_a_full_access_key() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.ft_increase_allowance(accounts(1), U128(100), None);
+}
+
+#[test]
+#[should_panic(expected = "The account danny is not registered")]
+fn test_approvals_need_a_registered_owner() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .attached_deposit(NearToken::from_near(1))
+        .build());
+    contract.ft_approve_batch(vec![(accounts(1), U128(100))], None);
+}
+
+#[test]
+#[should_panic(expected = "The owner can't approve itself")]
+fn test_owners_cant_approve_themselves() {
+    let (mut contract, mut context) = setup();
+    approve(&mut contract, &mut context, accounts(0), 100, None);
+}
+
+#[test]
+fn test_batch_approvals_and_revocations() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.ft_approve_batch(
+        vec![
+            (accounts(1), U128(100)),
+            (accounts(2), U128(200)),
+            (accounts(3), U128(300)),
+        ],
+        None,
+    );
+    let cost = NearToken::from_near(1).saturating_sub(refunded_to(&accounts(0)));
+    assert_eq!(contract.allowances_of(accounts(0), None, None).len(), 3);
+    let page = contract.allowances_of(accounts(0), Some(1), Some(1));
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].0, accounts(2));
+    assert_eq!(page[0].1.amount, U128(200));
+
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_revoke_batch(vec![accounts(1), accounts(2), accounts(3)]);
+    assert!(contract.allowances_of(accounts(0), None, None).is_empty());
+    assert_eq!(refunded_to(&accounts(0)), cost);
+}
+
+#[test]
+#[should_panic(expected = "Too many approvals in a single batch")]
+fn test_batch_approvals_are_capped() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.ft_approve_batch(vec![(accounts(1), U128(1)); 101], None);
+}
+
+#[test]
+fn test_compare_and_set_approvals() {
+    let (mut contract, mut context) = setup();
+    approve(&mut contract, &mut context, accounts(1), 100, None);
+    contract.ft_approve_from_to(accounts(1), U128(100), U128(50), None);
+    assert_eq!(contract.ft_allowance(accounts(0), accounts(1)), U128(50));
+}
+
+#[test]
+#[should_panic(expected = "The allowance changed")]
+fn test_compare_and_set_approvals_fail_after_a_spend() {
+    let (mut contract, mut context) = setup();
+    approve(&mut contract, &mut context, accounts(1), 100, None);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer_from(accounts(0), accounts(1), U128(60), None);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.ft_approve_from_to(accounts(1), U128(100), U128(50), None);
+}
+
+#[test]
+fn test_compare_and_set_approvals_need_the_current_allowance() {
+    let (mut contract, mut context) = setup();
+    approve(&mut contract, &mut context, accounts(1), 100, None);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer_from(accounts(0), accounts(1), U128(60), None);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    let error = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        contract.ft_approve_from_to(accounts(1), U128(100), U128(50), None)
+    }))
+    .unwrap_err();
+    assert!(error
+        .downcast_ref::<String>()
+        .unwrap()
+        .contains("The allowance changed"));
+    assert_eq!(contract.ft_allowance(accounts(0), accounts(1)), U128(40));
+
+    contract.ft_approve_from_to(accounts(1), U128(40), U128(50), None);
+    assert_eq!(contract.ft_allowance(accounts(0), accounts(1)), U128(50));
+}
+
+#[test]
+#[should_panic(expected = "The allowance is too low")]
+fn test_transfer_from_is_capped_by_the_allowance() {
+    let (mut contract, mut context) = setup();
+    approve(&mut contract, &mut context, accounts(1), 100, None);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer_from(accounts(0), accounts(1), U128(101), None);
+}
+
+#[test]
+#[should_panic(expected = "There is no allowance")]
+fn test_expired_allowances_cant_be_used() {
+    let (mut contract, mut context) = setup();
+    approve(
+        &mut contract,
+        &mut context,
+        accounts(1),
+        100,
+        Some(3_600 * SECOND),
+    );
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .block_timestamp(3_601 * SECOND)
+        .build());
+    contract.ft_transfer_from(accounts(0), accounts(1), U128(10), None);
+}
+
+#[test]
+#[should_panic(expected = "There is no allowance")]
+fn test_allowances_cant_be_used_at_their_expiry() {
+    let (mut contract, mut context) = setup();
+    approve(
+        &mut contract,
+        &mut context,
+        accounts(1),
+        100,
+        Some(3_600 * SECOND),
+    );
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .block_timestamp(3_600 * SECOND)
+        .build());
+    contract.ft_transfer_from(accounts(0), accounts(1), U128(10), None);
+}
+
+#[test]
+fn test_increasing_an_allowance_keeps_its_expiry() {
+    let (mut contract, mut context) = setup();
+    approve(
+        &mut contract,
+        &mut context,
+        accounts(1),
+        100,
+        Some(3_600 * SECOND),
+    );
+    testing_env!(context.block_timestamp(1_800 * SECOND).build());
+    contract.ft_increase_allowance(accounts(1), U128(50), None);
+    assert_eq!(contract.ft_allowance(accounts(0), accounts(1)), U128(150));
+
+    testing_env!(context.block_timestamp(3_600 * SECOND).build());
+    assert_eq!(contract.ft_allowance(accounts(0), accounts(1)), U128(0));
+}
+
+#[test]
+#[should_panic(expected = "The allowance has expired, a new expiry should be set")]
+fn test_increasing_an_expired_allowance_needs_a_new_expiry() {
+    let (mut contract, mut context) = setup();
+    approve(
+        &mut contract,
+        &mut context,
+        accounts(1),
+        100,
+        Some(3_600 * SECOND),
+    );
+    testing_env!(context.block_timestamp(3_601 * SECOND).build());
+    contract.ft_increase_allowance(accounts(1), U128(50), None);
+}
+
+#[test]
+fn test_increasing_an_expired_allowance_starts_over() {
+    let (mut contract, mut context) = setup();
+    approve(
+        &mut contract,
+        &mut context,
+        accounts(1),
+        100,
+        Some(3_600 * SECOND),
+    );
+    testing_env!(context.block_timestamp(3_601 * SECOND).build());
+    contract.ft_increase_allowance(accounts(1), U128(50), Some(U64(7_201 * SECOND)));
+    assert_eq!(contract.ft_allowance(accounts(0), accounts(1)), U128(50));
+
+    testing_env!(context.block_timestamp(7_201 * SECOND).build());
+    assert_eq!(contract.ft_allowance(accounts(0), accounts(1)), U128(0));
+}
+
+#[test]
+fn test_sweeping_expired_allowances() {
+    let (mut contract, mut context) = setup();
+    contract.set_hygiene_bounty(U128(5));
+    contract.fund_hygiene_pool(U128(100));
+    approve(&mut contract, &mut context, accounts(2), 100, None);
+    let cost = approve(
+        &mut contract,
+        &mut context,
+        accounts(1),
+        100,
+        Some(3_600 * SECOND),
+    );
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(3_601 * SECOND)
+        .build());
+    assert_eq!(contract.sweep_expired_allowances(accounts(0), None), 1);
+    assert_eq!(refunded_to(&accounts(0)), cost);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(5));
+    assert_eq!(contract.get_hygiene_pool().balance, U128(95));
+    assert_eq!(contract.allowances_of(accounts(0), None, None).len(), 1);
+
+    // Nothing is left to sweep.
+    assert_eq!(contract.sweep_expired_allowances(accounts(0), None), 0);
+}
+
+#[test]
+fn test_owners_dont_earn_the_bounty_for_their_own_allowances() {
+    let (mut contract, mut context) = setup();
+    contract.set_hygiene_bounty(U128(5));
+    contract.fund_hygiene_pool(U128(100));
+    let cost = approve(
+        &mut contract,
+        &mut context,
+        accounts(1),
+        100,
+        Some(3_600 * SECOND),
+    );
+
+    testing_env!(context.block_timestamp(3_601 * SECOND).build());
+    assert_eq!(contract.sweep_expired_allowances(accounts(0), None), 1);
+    assert_eq!(refunded_to(&accounts(0)), cost);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(900));
+    assert_eq!(contract.get_hygiene_pool().balance, U128(100));
+}
+
+#[test]
+#[should_panic(expected = "An expiring allowance should last at least an hour")]
+fn test_allowances_last_at_least_an_hour() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(SECOND).build());
+    approve(
+        &mut contract,
+        &mut context,
+        accounts(1),
+        100,
+        Some(3_600 * SECOND),
+    );
+}
@@ -0,0 +1,129 @@
+use near_ft::attestations::AttestationConfig;
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken, PromiseResult, RuntimeFeesConfig};
+
+const SECOND: u64 = 1_000_000_000;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Bob must hold the "kyc" credential for an hour to send more than 100 tokens or to buy
+// bonds from alice's NEAR market. The context is left as bob.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 1_000)
+        .balance(accounts(2), 0)
+        .with(|contract| {
+            contract.set_attestation_config(Some(AttestationConfig {
+                registry_id: "registry.near".parse().unwrap(),
+                credential: "kyc".to_string(),
+                ttl_sec: 3_600,
+                large_transfer_threshold: Some(U128(100)),
+                gate_bonds: true,
+            }))
+        })
+        .build();
+    contract.create_bond_market(None, U128(1), U128(1), 1_000, 10, U128(500));
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    (contract, context)
+}
+
+// Caches the registry answer for bob, then goes back to bob.
+fn check_bob(contract: &mut Contract, context: &mut VMContextBuilder, attested: bool) {
+    contract.refresh_attestation(accounts(1));
+    testing_env!(
+        context.predecessor_account_id(contract_id()).build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(attested.to_string().into_bytes())]
+    );
+    assert_eq!(
+        contract.on_attestation_checked(accounts(1), contract.attestation_epoch),
+        attested
+    );
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+}
+
+fn buy_bond(contract: &mut Contract, context: &mut VMContextBuilder) {
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.bond_with_near(0, None);
+}
+
+#[test]
+fn test_small_transfers_need_no_attestation() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(2), U128(100), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(100));
+}
+
+#[test]
+#[should_panic(expected = "The account is not attested, call refresh_attestation first")]
+fn test_large_transfers_need_an_attestation() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(2), U128(101), None, None, None, None);
+}
+
+#[test]
+fn test_attested_accounts_send_large_transfers() {
+    let (mut contract, mut context) = setup();
+    check_bob(&mut contract, &mut context, true);
+    contract.ft_transfer(accounts(2), U128(500), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(500));
+}
+
+#[test]
+#[should_panic(expected = "The account is not attested, call refresh_attestation first")]
+fn test_rejected_accounts_cannot_send_large_transfers() {
+    let (mut contract, mut context) = setup();
+    check_bob(&mut contract, &mut context, false);
+    contract.ft_transfer(accounts(2), U128(500), None, None, None, None);
+}
+
+#[test]
+#[should_panic(expected = "The account is not attested, call refresh_attestation first")]
+fn test_attestations_expire_after_their_ttl() {
+    let (mut contract, mut context) = setup();
+    check_bob(&mut contract, &mut context, true);
+    testing_env!(context.block_timestamp(3_600 * SECOND).build());
+    assert!(contract.get_cached_attestation(accounts(1)).is_none());
+    contract.ft_transfer(accounts(2), U128(500), None, None, None, None);
+}
+
+#[test]
+#[should_panic(expected = "The account is not attested, call refresh_attestation first")]
+fn test_bonds_need_an_attestation() {
+    let (mut contract, mut context) = setup();
+    buy_bond(&mut contract, &mut context);
+}
+
+#[test]
+fn test_attested_accounts_buy_bonds() {
+    let (mut contract, mut context) = setup();
+    check_bob(&mut contract, &mut context, true);
+    buy_bond(&mut contract, &mut context);
+    assert_eq!(contract.get_bond_positions(accounts(1)).0.len(), 1);
+}
+
+#[test]
+fn test_changing_the_credential_drops_the_attestations() {
+    let (mut contract, mut context) = setup();
+    check_bob(&mut contract, &mut context, true);
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    let mut config = contract.get_attestation_config().unwrap();
+    config.credential = "accredited".to_string();
+    contract.set_attestation_config(Some(config));
+    assert!(contract.get_cached_attestation(accounts(1)).is_none());
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "audit")]
+
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build()
+}
+
+#[test]
+fn test_registered_supply_follows_every_balance_change() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(1), U128(300), None, None, None, None);
+    contract.ft_mint(accounts(2), U128(200), None);
+    contract.ft_burn(U128(100), None);
+    contract.open_stream(accounts(1), U128(1), U128(50));
+
+    let supply = contract.ft_total_registered_supply();
+    assert_eq!(supply.recomputed, U128(1_050));
+    assert_eq!(supply.running, U128(1_050));
+    assert_eq!(supply.streamed, U128(50));
+    assert_eq!(supply.total_supply, U128(1_100));
+    assert!(supply.consistent);
+}
+
+#[test]
+fn test_tracking_accounts_again_counts_them_once() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(1), U128(300), None, None, None, None);
+    contract.audit_track_accounts(vec![accounts(0), accounts(1), accounts(2), accounts(3)]);
+
+    let supply = contract.ft_total_registered_supply();
+    assert_eq!(supply.running, U128(1_000));
+    assert!(supply.consistent);
+}
@@ -0,0 +1,165 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+const DAY: u64 = 86_400_000_000_000;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Alice opens a NEAR market with 500 tokens of capacity, paying one token per yoctoNEAR plus a
+// 10% discount and vesting over 10 days. Bob buys a bond with 100 yoctoNEAR.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    contract.create_bond_market(None, U128(1), U128(1), 1_000, 10, U128(500));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(500));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    let position = contract.bond_with_near(0, None);
+    assert_eq!(position.payout, U128(110));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    (contract, context)
+}
+
+#[test]
+fn test_bonds_vest_linearly() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(5 * DAY).build());
+    assert_eq!(contract.get_bond_positions(accounts(1)).1, U128(55));
+    assert_eq!(contract.claim_bonds(), U128(55));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(55));
+
+    testing_env!(context.block_timestamp(20 * DAY).build());
+    assert_eq!(contract.claim_bonds(), U128(55));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(110));
+    // Fully claimed bonds are dropped.
+    assert!(contract.get_bond_positions(accounts(1)).0.is_empty());
+}
+
+#[test]
+fn test_closing_the_market_returns_the_unsold_capacity() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.close_bond_market(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(890));
+    // The sold bonds stay escrowed until they're claimed.
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(110));
+    assert!(!contract.get_bond_market(0).unwrap().open);
+}
+
+#[test]
+fn test_the_owner_withdraws_the_proceeds() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(contract.get_bond_market(0).unwrap().proceeds, U128(100));
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.withdraw_bond_proceeds(0);
+    assert_eq!(contract.get_bond_market(0).unwrap().proceeds, U128(0));
+    let withdrawn = get_created_receipts()
+        .iter()
+        .filter(|receipt| receipt.receiver_id == accounts(0))
+        .flat_map(|receipt| receipt.actions.iter())
+        .any(|action| {
+            matches!(action, MockAction::Transfer { deposit, .. } if deposit.as_yoctonear() == 100)
+        });
+    assert!(withdrawn);
+}
+
+#[test]
+#[should_panic(expected = "The bond market capacity is exceeded")]
+fn test_bonds_are_capped_by_the_capacity() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(400))
+        .build());
+    contract.bond_with_near(0, None);
+}
+
+#[test]
+#[should_panic(expected = "The bond payout is lower than the minimum")]
+fn test_bonds_respect_the_minimum_payout() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.bond_with_near(0, Some(U128(111)));
+}
+
+#[test]
+#[should_panic(expected = "The bond market is closed")]
+fn test_closed_markets_dont_sell() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.close_bond_market(0);
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.bond_with_near(0, None);
+}
+
+#[test]
+#[should_panic(expected = "The account charlie is not registered")]
+fn test_bonds_vest_to_registered_accounts() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.bond_with_near(0, None);
+}
+
+#[test]
+#[should_panic(expected = "This bond market doesn't accept NEAR")]
+fn test_token_markets_dont_accept_near() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.create_bond_market(
+        Some("usdc.near".parse().unwrap()),
+        U128(1),
+        U128(1),
+        0,
+        10,
+        U128(100),
+    );
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.bond_with_near(1, None);
+}
+
+#[test]
+#[should_panic(expected = "The discount should be lower than 100%")]
+fn test_discounts_are_capped() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.create_bond_market(None, U128(1), U128(1), 10_000, 10, U128(100));
+}
@@ -0,0 +1,77 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.set_borsh_msg_receiver(true);
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+#[test]
+fn test_transfer_call_passes_borsh_arguments() {
+    let (mut contract, _) = setup();
+    assert!(contract.is_borsh_msg_receiver(accounts(1)));
+    let _ = contract.ft_transfer_call_borsh(
+        accounts(1),
+        U128(100),
+        None,
+        Base64VecU8(vec![1, 2, 3]),
+        None,
+        None,
+        None,
+    );
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, accounts(1));
+    let MockAction::FunctionCallWeight {
+        method_name, args, ..
+    } = &receipts[0].actions[0]
+    else {
+        panic!("Expected a function call");
+    };
+    assert_eq!(method_name, b"ft_on_transfer_borsh");
+    let (sender_id, amount, msg): (AccountId, u128, Vec<u8>) =
+        near_sdk::borsh::from_slice(args).unwrap();
+    assert_eq!((sender_id, amount, msg), (accounts(0), 100, vec![1, 2, 3]));
+}
+
+#[test]
+#[should_panic(expected = "The receiver doesn't accept Borsh messages")]
+fn test_receiver_opts_in_to_borsh() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.set_borsh_msg_receiver(false);
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    let _ = contract.ft_transfer_call_borsh(
+        accounts(1),
+        U128(100),
+        None,
+        Base64VecU8(vec![]),
+        None,
+        None,
+        None,
+    );
+}
@@ -0,0 +1,90 @@
+use near_ft::bounty::DisclosureStatus;
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{borsh, env, testing_env, AccountId};
+
+const DETAILS: &str = "Reentrancy in ft_resolve_transfer";
+
+fn commitment(researcher_id: &AccountId, details: &str) -> Base64VecU8 {
+    env::sha256(&borsh::to_vec(&(researcher_id, details)).unwrap()).into()
+}
+
+// The owner funded the pool with 500 tokens, and bob committed to a vulnerability.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    contract.fund_bounty_pool(U128(500));
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.submit_disclosure(commitment(&accounts(1), DETAILS));
+    (contract, context)
+}
+
+#[test]
+fn test_revealed_disclosures_are_paid_by_the_owner() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(500));
+    contract.reveal_and_claim(0, DETAILS.to_string());
+    assert_eq!(
+        contract.get_disclosure(0).unwrap().status,
+        DisclosureStatus::Revealed
+    );
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.approve_bounty_payout(0, U128(200));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(200));
+    assert_eq!(contract.get_bounty_pool(), U128(300));
+    let disclosure = contract.get_disclosure(0).unwrap();
+    assert_eq!(disclosure.status, DisclosureStatus::Paid);
+    assert_eq!(disclosure.details.as_deref(), Some(DETAILS));
+    assert_eq!(disclosure.payout, Some(U128(200)));
+}
+
+#[test]
+#[should_panic(expected = "The details don't match the commitment")]
+fn test_reveals_must_match_the_commitment() {
+    let (mut contract, _) = setup();
+    contract.reveal_and_claim(0, "Something else".to_string());
+}
+
+#[test]
+#[should_panic(expected = "Only the researcher can reveal the disclosure")]
+fn test_only_the_researcher_reveals() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.reveal_and_claim(0, DETAILS.to_string());
+}
+
+#[test]
+#[should_panic(expected = "The disclosure isn't waiting for a decision")]
+fn test_committed_disclosures_cant_be_paid() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.approve_bounty_payout(0, U128(200));
+}
+
+#[test]
+#[should_panic(expected = "The bounty pool is too low")]
+fn test_payouts_are_limited_to_the_pool() {
+    let (mut contract, mut context) = setup();
+    contract.reveal_and_claim(0, DETAILS.to_string());
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.approve_bounty_payout(0, U128(501));
+}
+
+#[test]
+fn test_rejected_disclosures_keep_the_pool() {
+    let (mut contract, mut context) = setup();
+    contract.reveal_and_claim(0, DETAILS.to_string());
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.reject_disclosure(0);
+    assert_eq!(
+        contract.get_disclosure(0).unwrap().status,
+        DisclosureStatus::Rejected
+    );
+    assert_eq!(contract.get_bounty_pool(), U128(500));
+}
@@ -0,0 +1,99 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+
+const DAY: u64 = 86_400_000_000_000;
+
+// Bob spends at most 300 tokens a day from his payroll budget. The context is left as bob.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(1), 1_000)
+        .balance(accounts(2), 0)
+        .build();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.set_budget("payroll".to_string(), U128(300), 86_400);
+    (contract, context)
+}
+
+fn pay(contract: &mut Contract, amount: u128, memo: &str) {
+    contract.ft_transfer(
+        accounts(2),
+        U128(amount),
+        Some(memo.to_string()),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_transfers_are_counted_against_the_referenced_budget() {
+    let (mut contract, _) = setup();
+    pay(&mut contract, 100, "#payroll March salaries");
+    pay(&mut contract, 50, "#payroll");
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(150));
+
+    let budget = &contract.get_budgets(accounts(1))[0];
+    assert_eq!(budget.budget.spent, U128(150));
+    assert_eq!(budget.budget.total_spent, U128(150));
+    assert_eq!(budget.remaining, U128(150));
+    assert_eq!(budget.period_ends_at.0, DAY);
+}
+
+#[test]
+fn test_budget_can_be_spent_up_to_its_cap() {
+    let (mut contract, _) = setup();
+    pay(&mut contract, 300, "#payroll");
+    assert_eq!(contract.get_budgets(accounts(1))[0].remaining, U128(0));
+}
+
+#[test]
+#[should_panic(expected = "The budget is exceeded")]
+fn test_exhausted_budget_rejects_transfers() {
+    let (mut contract, _) = setup();
+    pay(&mut contract, 300, "#payroll");
+    pay(&mut contract, 1, "#payroll");
+}
+
+#[test]
+fn test_budget_is_reset_the_next_period() {
+    let (mut contract, mut context) = setup();
+    pay(&mut contract, 300, "#payroll");
+
+    testing_env!(context.block_timestamp(DAY).build());
+    let budget = &contract.get_budgets(accounts(1))[0];
+    assert_eq!(budget.budget.period, 1);
+    assert_eq!(budget.remaining, U128(300));
+    assert_eq!(budget.period_ends_at.0, 2 * DAY);
+
+    pay(&mut contract, 200, "#payroll");
+    let budget = &contract.get_budgets(accounts(1))[0];
+    assert_eq!(budget.budget.spent, U128(200));
+    assert_eq!(budget.budget.total_spent, U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(500));
+}
+
+#[test]
+#[should_panic(expected = "Transfers of this account must reference a budget in their memo")]
+fn test_transfers_without_a_budget_are_rejected() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(2), U128(100), None, None, None, None);
+}
+
+#[test]
+#[should_panic(expected = "The budget doesn't exist")]
+fn test_transfers_to_an_unknown_budget_are_rejected() {
+    let (mut contract, _) = setup();
+    pay(&mut contract, 100, "#marketing");
+}
+
+#[test]
+fn test_accounts_without_budgets_transfer_freely() {
+    let (mut contract, _) = setup();
+    contract.remove_budget("payroll".to_string());
+    contract.ft_transfer(accounts(2), U128(100), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(100));
+}
@@ -0,0 +1,119 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId};
+
+// Bob, charlie and danny hold 1,000 tokens each.
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(1), 1_000)
+        .balance(accounts(2), 1_000)
+        .balance(accounts(3), 1_000)
+        .build()
+}
+
+fn burn(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    account_id: AccountId,
+    amount: u128,
+) -> u64 {
+    testing_env!(context.predecessor_account_id(account_id).build());
+    contract.burn_with_receipt(U128(amount), None)
+}
+
+fn top_burners(contract: &Contract, limit: Option<u64>) -> Vec<(AccountId, u128)> {
+    contract
+        .top_burners(limit)
+        .into_iter()
+        .map(|(account_id, burned)| (account_id, burned.0))
+        .collect()
+}
+
+#[test]
+fn test_leaderboard_is_sorted_by_amount_burned() {
+    let (mut contract, mut context) = setup();
+    assert!(top_burners(&contract, None).is_empty());
+    burn(&mut contract, &mut context, accounts(1), 100);
+    burn(&mut contract, &mut context, accounts(2), 300);
+    burn(&mut contract, &mut context, accounts(3), 200);
+    assert_eq!(
+        top_burners(&contract, None),
+        vec![(accounts(2), 300), (accounts(3), 200), (accounts(1), 100)]
+    );
+}
+
+#[test]
+fn test_burners_move_up_the_leaderboard() {
+    let (mut contract, mut context) = setup();
+    burn(&mut contract, &mut context, accounts(1), 100);
+    burn(&mut contract, &mut context, accounts(2), 300);
+    burn(&mut contract, &mut context, accounts(1), 250);
+    assert_eq!(
+        top_burners(&contract, None),
+        vec![(accounts(1), 350), (accounts(2), 300)]
+    );
+    assert_eq!(contract.burned_by(accounts(1)), U128(350));
+}
+
+#[test]
+fn test_ties_keep_the_first_burner_ahead() {
+    let (mut contract, mut context) = setup();
+    burn(&mut contract, &mut context, accounts(3), 200);
+    burn(&mut contract, &mut context, accounts(2), 200);
+    assert_eq!(
+        top_burners(&contract, None),
+        vec![(accounts(3), 200), (accounts(2), 200)]
+    );
+}
+
+#[test]
+fn test_leaderboard_is_limited() {
+    let (mut contract, mut context) = setup();
+    burn(&mut contract, &mut context, accounts(1), 100);
+    burn(&mut contract, &mut context, accounts(2), 300);
+    burn(&mut contract, &mut context, accounts(3), 200);
+    assert_eq!(
+        top_burners(&contract, Some(2)),
+        vec![(accounts(2), 300), (accounts(3), 200)]
+    );
+}
+
+#[test]
+fn test_receipts_prove_the_burns() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .block_timestamp(42)
+        .build());
+    let receipt_id = contract.burn_with_receipt(U128(100), Some("Access pass".to_string()));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(900));
+
+    let receipt = contract.verify_burn(receipt_id).unwrap();
+    assert_eq!(receipt.account_id, accounts(1));
+    assert_eq!(receipt.amount, U128(100));
+    assert_eq!(receipt.memo.as_deref(), Some("Access pass"));
+    assert_eq!(receipt.burned_at, U64(42));
+}
+
+#[test]
+fn test_receipts_are_numbered_in_order() {
+    let (mut contract, mut context) = setup();
+    let first = burn(&mut contract, &mut context, accounts(1), 100);
+    let second = burn(&mut contract, &mut context, accounts(2), 50);
+    assert_eq!(second, first + 1);
+    assert_eq!(
+        contract.verify_burn(second).unwrap().account_id,
+        accounts(2)
+    );
+    assert!(contract.verify_burn(second + 1).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The amount should be a positive number")]
+fn test_receipts_need_a_positive_amount() {
+    let (mut contract, mut context) = setup();
+    burn(&mut contract, &mut context, accounts(1), 0);
+}
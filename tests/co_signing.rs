@@ -0,0 +1,239 @@
+use near_ft::ft_core::{CoSignerConfig, FeeMode, FungibleTokenCore};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+const SECOND: u64 = 1_000_000_000;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Alice's transfers above 100 tokens need charlie's confirmation within an hour, and she has
+// queued one of 500 tokens. Transfers pay a 1% fee to danny.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .with(|contract| contract.set_transfer_fee(100, accounts(3)))
+        .build();
+    contract.set_co_signer(
+        accounts(0),
+        Some(CoSignerConfig {
+            co_signer_id: accounts(2),
+            threshold: U128(100),
+            window_sec: 3_600,
+        }),
+    );
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.ft_transfer(accounts(1), U128(500), None, None, None, None);
+    assert!(get_logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"transfer_queued\"")));
+    assert!(refunded_to(&accounts(0)) < NearToken::from_near(1));
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_transfers_above_the_threshold_escrow_the_amount_and_the_fee() {
+    let (contract, _) = setup();
+    let transfer = contract.get_pending_transfer(0).unwrap();
+    assert_eq!(transfer.amount, U128(500));
+    assert_eq!(transfer.fee, U128(5));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(505));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+}
+
+#[test]
+fn test_co_signer_confirms_the_transfer() {
+    let (mut contract, mut context) = setup();
+    // Alice spends everything else, the fee is already escrowed.
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    for _ in 0..5 {
+        contract.ft_transfer(accounts(3), U128(99), None, None, None, None);
+    }
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+
+    contract.confirm_transfer(0);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(500));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(0));
+    assert!(contract.get_pending_transfer(0).is_none());
+}
+
+#[test]
+fn test_confirmation_charges_the_escrowed_fee_after_a_fee_change() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_transfer_fee(500, accounts(3));
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+
+    contract.confirm_transfer(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(5));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(0));
+    assert_eq!(contract.get_fee_accumulators().to_treasury, U128(5));
+}
+
+#[test]
+fn test_confirmation_returns_the_fee_once_the_fee_switch_is_off() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_fee_config(None);
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+
+    contract.confirm_transfer(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(0));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(0));
+}
+
+#[test]
+fn test_cancellation_refunds_the_amount_and_the_fee() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.cancel_pending_transfer(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_000));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(0));
+    assert!(contract.get_pending_transfer(0).is_none());
+}
+
+#[test]
+fn test_small_transfers_dont_wait() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+}
+
+#[test]
+#[should_panic(expected = "Only the co-signer can confirm the transfer")]
+fn test_only_the_co_signer_confirms() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.confirm_transfer(0);
+}
+
+#[test]
+#[should_panic(expected = "The pending transfer has expired")]
+fn test_expired_transfers_cant_be_confirmed() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(3_601 * SECOND).build());
+    contract.confirm_transfer(0);
+}
+
+#[test]
+#[should_panic(
+    expected = "Only the sender or the co-signer can cancel the transfer before it expires"
+)]
+fn test_others_cancel_only_expired_transfers() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.cancel_pending_transfer(0);
+}
+
+#[test]
+#[should_panic(expected = "Transfers waiting for a co-signer can't be paid by the receiver")]
+fn test_co_signed_transfers_are_paid_by_the_sender() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.ft_transfer(
+        accounts(1),
+        U128(200),
+        None,
+        None,
+        None,
+        Some(FeeMode::ReceiverPays),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Only the current co-signer can change the co-signer")]
+fn test_the_account_cant_drop_its_co_signer() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_co_signer(accounts(0), None);
+}
+
+#[test]
+fn test_the_co_signer_can_drop_itself() {
+    let (mut contract, _) = setup();
+    contract.set_co_signer(accounts(0), None);
+    assert!(contract.get_co_signer(accounts(0)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_queued_transfers_pay_their_storage() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.ft_transfer(accounts(1), U128(200), None, None, None, None);
+}
+
+#[test]
+#[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+fn test_small_transfers_still_attach_one_yocto() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_near(1))
+        .build());
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+}
+
+#[test]
+fn test_confirmation_refunds_the_storage_to_the_sender() {
+    let (mut contract, _) = setup();
+    contract.confirm_transfer(0);
+    assert!(refunded_to(&accounts(0)) > NearToken::from_yoctonear(0));
+}
+
+#[test]
+fn test_cancellation_refunds_the_storage_to_the_sender() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.cancel_pending_transfer(0);
+    assert!(refunded_to(&accounts(0)) > NearToken::from_yoctonear(0));
+}
+
+#[test]
+#[should_panic(expected = "Transfers above the co-signing threshold must be made with ft_transfer")]
+fn test_registering_transfers_arent_queued() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_near(1))
+        .build());
+    contract.ft_transfer_and_register(accounts(4), U128(200), None);
+}
@@ -0,0 +1,152 @@
+use near_ft::council::{CouncilAction, CouncilConfig, CouncilProposalStatus};
+use near_ft::guard::PauseFlags;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken, PromiseResult, RuntimeFeesConfig};
+
+// Bob, charlie and danny form a council approving proposals by 2, for an hour.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    contract.set_council(Some(CouncilConfig {
+        members: vec![accounts(1), accounts(2), accounts(3)],
+        threshold: 2,
+        proposal_ttl_sec: 3_600,
+    }));
+    (contract, context)
+}
+
+fn call_as(context: &mut VMContextBuilder, account_id: AccountId) {
+    testing_env!(context.predecessor_account_id(account_id).build());
+}
+
+fn resolve(context: &mut VMContextBuilder, contract: &mut Contract, result: PromiseResult) {
+    testing_env!(
+        context
+            .predecessor_account_id(context.context.current_account_id.clone())
+            .build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![result]
+    );
+    contract.council_resolve_proposal(0);
+}
+
+#[test]
+fn test_council_takes_over_the_ownership() {
+    let (mut contract, mut context) = setup();
+    let contract_id = context.context.current_account_id.clone();
+    contract.propose_owner(Some(contract_id.clone()));
+
+    call_as(&mut context, accounts(1));
+    assert_eq!(
+        contract.propose_council_action(CouncilAction::AcceptOwnership),
+        0
+    );
+    call_as(&mut context, accounts(2));
+    contract.approve_council_proposal(0);
+    call_as(&mut context, accounts(4));
+    let _ = contract.execute_council_proposal(0);
+    assert_eq!(
+        contract.get_council_proposal(0).unwrap().status,
+        CouncilProposalStatus::InProgress
+    );
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, contract_id);
+    let MockAction::FunctionCallWeight {
+        method_name,
+        attached_deposit,
+        ..
+    } = &receipts[0].actions[0]
+    else {
+        panic!("Expected a function call");
+    };
+    assert_eq!(method_name, b"accept_ownership");
+    assert_eq!(*attached_deposit, NearToken::from_yoctonear(1));
+
+    // The contract calls itself.
+    call_as(&mut context, contract_id.clone());
+    contract.accept_ownership();
+    resolve(
+        &mut context,
+        &mut contract,
+        PromiseResult::Successful(vec![]),
+    );
+    assert_eq!(contract.get_owner(), contract_id);
+    assert_eq!(
+        contract.get_council_proposal(0).unwrap().status,
+        CouncilProposalStatus::Executed
+    );
+}
+
+#[test]
+fn test_failed_actions_are_recorded() {
+    let (mut contract, mut context) = setup();
+    call_as(&mut context, accounts(1));
+    contract.propose_council_action(CouncilAction::SetPauseFlags {
+        flags: PauseFlags::default(),
+    });
+    call_as(&mut context, accounts(3));
+    contract.approve_council_proposal(0);
+    let _ = contract.execute_council_proposal(0);
+    resolve(&mut context, &mut contract, PromiseResult::Failed);
+    assert_eq!(
+        contract.get_council_proposal(0).unwrap().status,
+        CouncilProposalStatus::Failed
+    );
+}
+
+#[test]
+#[should_panic(expected = "The proposal doesn't have enough approvals")]
+fn test_proposals_need_enough_approvals() {
+    let (mut contract, mut context) = setup();
+    call_as(&mut context, accounts(1));
+    contract.propose_council_action(CouncilAction::FtMint {
+        receiver_id: accounts(1),
+        amount: U128(100),
+        memo: None,
+    });
+    let _ = contract.execute_council_proposal(0);
+}
+
+#[test]
+#[should_panic(expected = "The proposal doesn't have enough approvals")]
+fn test_removed_members_approvals_dont_count() {
+    let (mut contract, mut context) = setup();
+    call_as(&mut context, accounts(1));
+    contract.propose_council_action(CouncilAction::SetIcon { icon: None });
+    call_as(&mut context, accounts(2));
+    contract.approve_council_proposal(0);
+    call_as(&mut context, accounts(0));
+    contract.set_council(Some(CouncilConfig {
+        members: vec![accounts(1), accounts(3)],
+        threshold: 2,
+        proposal_ttl_sec: 3_600,
+    }));
+    let _ = contract.execute_council_proposal(0);
+}
+
+#[test]
+#[should_panic(expected = "The proposal has expired")]
+fn test_proposals_expire() {
+    let (mut contract, mut context) = setup();
+    call_as(&mut context, accounts(1));
+    contract.propose_council_action(CouncilAction::SetIcon { icon: None });
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(3_601 * 1_000_000_000)
+        .build());
+    contract.approve_council_proposal(0);
+}
+
+#[test]
+#[should_panic(expected = "Only a council member can call this method")]
+fn test_only_members_propose() {
+    let (mut contract, _) = setup();
+    contract.propose_council_action(CouncilAction::SetIcon { icon: None });
+}
@@ -0,0 +1,58 @@
+use near_ft::decimals::{scale_from_bridged, scale_to_bridged, BridgedAmount};
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+#[test]
+fn test_bridged_conversions_keep_the_dust() {
+    assert_eq!(scale_to_bridged(1_234_567, 12, 6), (1, 234_567));
+    assert_eq!(scale_from_bridged(1, 12, 6), 1_000_000);
+    assert_eq!(scale_to_bridged(42, 6, 6), (42, 0));
+
+    testing_env!(VMContextBuilder::new().build());
+    let contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    let one_token = 10u128.pow(24);
+    assert_eq!(
+        contract.to_bridged_amount(U128(one_token + 5), 18),
+        BridgedAmount {
+            amount: U128(10u128.pow(18)),
+            dust: U128(5),
+        }
+    );
+    assert_eq!(
+        contract.from_bridged_amount(U128(1_500_000), 6),
+        U128(one_token + one_token / 2)
+    );
+}
+
+#[test]
+#[should_panic(expected = "can't have more decimals than the token")]
+fn test_bridged_representation_cant_have_more_decimals() {
+    scale_to_bridged(1, 6, 18);
+}
+
+#[test]
+fn test_transfer_to_bridge_routes_the_dust() {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(10u128.pow(25)));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    contract.storage_deposit(Some(accounts(2)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_dust_account(Some(accounts(2)));
+
+    let bridged = contract.ft_transfer_to_bridge(accounts(1), U128(10u128.pow(18) + 7), 6, None);
+
+    assert_eq!(bridged.amount, U128(1));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(10u128.pow(18)));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(7));
+}
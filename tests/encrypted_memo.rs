@@ -0,0 +1,74 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+// Bob registered a memo key, paying for its storage. The context is left as alice.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_near(1))
+        .build());
+    contract.register_memo_key(Some(Base64VecU8(vec![7; 32])));
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_sealed_memos_are_only_logged() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_with_encrypted_memo(accounts(1), U128(100), Base64VecU8(vec![1; 64]));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert!(get_logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"encrypted_memo\"")));
+}
+
+#[test]
+fn test_the_key_storage_is_refunded_on_removal() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.register_memo_key(None);
+    assert!(!refunded_to(&accounts(1)).is_zero());
+    assert!(contract.get_memo_key(accounts(1)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_the_receiver_pays_for_the_storage_of_the_key() {
+    let (mut contract, _) = setup();
+    contract.register_memo_key(Some(Base64VecU8(vec![7; 32])));
+}
+
+#[test]
+#[should_panic(expected = "The receiver has no memo key")]
+fn test_memos_are_sealed_for_a_registered_key() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer_with_encrypted_memo(accounts(0), U128(10), Base64VecU8(vec![1; 64]));
+}
@@ -0,0 +1,198 @@
+use near_ft::ft_core::{CoSignerConfig, FungibleTokenCore};
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+const SECOND: u64 = 1_000_000_000;
+
+// Alice escrows 100 tokens for bob, who isn't registered, for an hour, paying for its storage.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    escrow(&mut contract, &mut context, 100);
+    (contract, context)
+}
+
+// Escrows a transfer to bob for an hour, returning the storage cost charged.
+fn escrow(contract: &mut Contract, context: &mut VMContextBuilder, amount: u128) -> NearToken {
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.ft_transfer_escrow(accounts(1), U128(amount), 3_600);
+    assert!(get_logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"transfer_escrowed\"")));
+    let cost = NearToken::from_near(1).saturating_sub(refunded_to(&accounts(0)));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    cost
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_unregistered_receiver_accepts_the_transfer() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(900));
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .block_timestamp(3_600 * SECOND)
+        .build());
+    contract.accept_escrow(0);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert!(contract.get_escrow(0).is_none());
+}
+
+#[test]
+fn test_the_fee_is_escrowed_until_the_acceptance() {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .with(|contract| contract.set_transfer_fee(100, accounts(3)))
+        .build();
+    escrow(&mut contract, &mut context, 500);
+    assert_eq!(contract.get_escrow(0).unwrap().fee, U128(5));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    // Alice spends everything else, the fee is already escrowed.
+    contract.ft_transfer(accounts(2), U128(490), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.accept_escrow(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(
+        Contract::ft_balance_of("token.near".parse().unwrap()),
+        U128(0)
+    );
+}
+
+// Alice escrows 500 tokens for bob, paying a 1% fee to danny. The context is left as alice.
+fn setup_with_fee() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(3), 0)
+        .with(|contract| contract.set_transfer_fee(100, accounts(3)))
+        .build();
+    escrow(&mut contract, &mut context, 500);
+    (contract, context)
+}
+
+#[test]
+fn test_acceptance_charges_the_escrowed_fee_after_a_fee_change() {
+    let (mut contract, mut context) = setup_with_fee();
+    contract.set_transfer_fee(500, accounts(3));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.accept_escrow(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(5));
+    assert_eq!(
+        Contract::ft_balance_of("token.near".parse().unwrap()),
+        U128(0)
+    );
+}
+
+#[test]
+fn test_frozen_sender_gets_the_fee_back_once_the_fee_switch_is_off() {
+    let (mut contract, mut context) = setup_with_fee();
+    contract.set_fee_config(None);
+    contract.freeze_account(accounts(0), 1, 3_600);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.accept_escrow(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(0));
+}
+
+#[test]
+fn test_sender_reclaims_the_transfer_after_the_timeout() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(3_600 * SECOND + 1).build());
+    contract.reclaim_escrow(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_000));
+}
+
+#[test]
+fn test_the_sender_gets_the_storage_back() {
+    let (mut contract, mut context) = setup();
+    let cost = escrow(&mut contract, &mut context, 100);
+    assert!(!cost.is_zero());
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.accept_escrow(1);
+    assert_eq!(refunded_to(&accounts(0)), cost);
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_the_sender_pays_for_the_storage() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_escrow(accounts(1), U128(100), 3_600);
+}
+
+#[test]
+#[should_panic(expected = "The escrowed transfer hasn't expired yet")]
+fn test_transfer_is_reclaimed_after_the_timeout() {
+    let (mut contract, _) = setup();
+    contract.reclaim_escrow(0);
+}
+
+#[test]
+#[should_panic(expected = "The escrowed transfer has expired")]
+fn test_transfer_is_accepted_before_the_timeout() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .block_timestamp(3_600 * SECOND + 1)
+        .build());
+    contract.accept_escrow(0);
+}
+
+#[test]
+#[should_panic(expected = "Transfers above the co-signing threshold must be made with ft_transfer")]
+fn test_transfers_above_the_co_signing_threshold_arent_escrowed() {
+    let (mut contract, _) = setup();
+    contract.set_co_signer(
+        accounts(0),
+        Some(CoSignerConfig {
+            co_signer_id: accounts(2),
+            threshold: U128(100),
+            window_sec: 3_600,
+        }),
+    );
+    contract.ft_transfer_escrow(accounts(1), U128(101), 3_600);
+}
+
+#[test]
+#[should_panic(expected = "Only the receiver can accept the transfer")]
+fn test_transfer_is_accepted_by_the_receiver() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.accept_escrow(0);
+}
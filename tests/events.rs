@@ -0,0 +1,421 @@
+use std::collections::BTreeMap;
+
+use near_ft::access::Role;
+use near_ft::events::*;
+use near_ft::guard::PauseFlags;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde_json::{self, Value};
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::testing_env;
+
+const SCHEMAS: &str = include_str!("event_schemas.json");
+
+/// The fields of an event, with the JSON type of their values.
+type Fields = BTreeMap<String, String>;
+
+/// Emits every `near_ft` event with all its optional fields set, and returns each event's
+/// version and fields.
+fn emit_samples() -> BTreeMap<String, (String, Fields)> {
+    testing_env!(VMContextBuilder::new().build());
+    let (alice, bob) = (accounts(0), accounts(1));
+    let amount = U128(1);
+    let timestamp = U64(1);
+
+    MiningEmission {
+        program_id: 0,
+        pool_id: &alice,
+        epochs: 1,
+        amount: &amount,
+        refunded: &amount,
+    }
+    .emit();
+    MiningEmissionFailed {
+        program_id: 0,
+        pool_id: &alice,
+        epochs: 1,
+        amount: &amount,
+    }
+    .emit();
+    BondPurchase {
+        account_id: &alice,
+        market_id: 0,
+        amount_in: &amount,
+        payout: &amount,
+    }
+    .emit();
+    BondClaim {
+        account_id: &alice,
+        amount: &amount,
+    }
+    .emit();
+    FeeDistribution {
+        payer_id: &alice,
+        to_stakers: &amount,
+        to_treasury: &amount,
+        burned: &amount,
+        to_rebates: &amount,
+        to_insurance: &amount,
+    }
+    .emit();
+    AccountMerge {
+        old_account_id: &alice,
+        new_account_id: &bob,
+        amount: &amount,
+    }
+    .emit();
+    EncryptedMemo {
+        sender_id: &alice,
+        receiver_id: &bob,
+        encrypted_memo: &Base64VecU8(vec![1]),
+    }
+    .emit();
+    TransferQueued {
+        transfer_id: 0,
+        sender_id: &alice,
+        receiver_id: &bob,
+        amount: &amount,
+        co_signer_id: &bob,
+    }
+    .emit();
+    AllowanceSet {
+        owner_id: &alice,
+        spender_id: &bob,
+        amount: &amount,
+    }
+    .emit();
+    AllowanceRevoked {
+        owner_id: &alice,
+        spender_id: &bob,
+    }
+    .emit();
+    AccountFrozen {
+        account_id: &alice,
+        reason_code: 1,
+        expires_at: &timestamp,
+    }
+    .emit();
+    AccountUnfrozen { account_id: &alice }.emit();
+    TravelRuleTransfer {
+        sender_id: &alice,
+        receiver_id: &bob,
+        amount: &amount,
+        travel_rule_ref: "ref",
+    }
+    .emit();
+    ReceiptTransfer {
+        lock_id: 0,
+        old_owner_id: Some(&alice),
+        new_owner_id: Some(&bob),
+        amount: &amount,
+    }
+    .emit();
+    PositionRoyalty {
+        position: "grant",
+        position_id: 0,
+        payer_id: &alice,
+        issuer_id: &bob,
+        amount: &amount,
+    }
+    .emit();
+    TreasurySpendQueued {
+        spend_id: 0,
+        treasury_id: &alice,
+        receiver_id: &bob,
+        amount: &amount,
+        approver_id: &bob,
+        expires_at: timestamp,
+    }
+    .emit();
+    KeeperReward {
+        keeper_id: &alice,
+        task: "task",
+        amount: &amount,
+    }
+    .emit();
+    TokenDeployed {
+        token_id: &alice,
+        owner_id: &bob,
+        total_supply: &amount,
+    }
+    .emit();
+    DustRouted {
+        from_id: &alice,
+        dust_account_id: &bob,
+        amount: &amount,
+        reason: "reason",
+    }
+    .emit();
+    PauseChanged {
+        flags: &PauseFlags::default(),
+    }
+    .emit();
+    AirdropClaim {
+        airdrop_id: 0,
+        account_id: &alice,
+        amount: &amount,
+    }
+    .emit();
+    MetadataUpdated {
+        updated_fields: &["icon"],
+    }
+    .emit();
+    OwnershipTransferred {
+        old_owner_id: &alice,
+        new_owner_id: &bob,
+    }
+    .emit();
+    RoleGranted {
+        account_id: &alice,
+        role: &Role::Minter,
+    }
+    .emit();
+    RoleRevoked {
+        account_id: &alice,
+        role: &Role::Minter,
+    }
+    .emit();
+    OwnerRecoveryProposed {
+        proposer_id: &alice,
+        new_owner_id: &bob,
+    }
+    .emit();
+    OwnerRecoveryVetoed {
+        owner_id: &alice,
+        new_owner_id: &bob,
+    }
+    .emit();
+    StreamOpened {
+        stream_id: 0,
+        sender_id: &alice,
+        receiver_id: &bob,
+        tokens_per_second: &amount,
+        deposit: &amount,
+    }
+    .emit();
+    StreamWithdrawn {
+        stream_id: 0,
+        receiver_id: &bob,
+        amount: &amount,
+    }
+    .emit();
+    StreamCancelled {
+        stream_id: 0,
+        cancelled_by: &alice,
+        receiver_amount: &amount,
+        sender_refund: &amount,
+    }
+    .emit();
+    SnapshotTaken {
+        snapshot_id: 0,
+        block_height: &U64(0),
+    }
+    .emit();
+    TransferEscrowed {
+        escrow_id: 0,
+        sender_id: &alice,
+        receiver_id: &bob,
+        amount: &amount,
+        expires_at: &U64(0),
+    }
+    .emit();
+    BalanceVoucherExported {
+        nonce: 0,
+        account_id: &alice,
+        amount: &amount,
+        successor_id: &bob,
+    }
+    .emit();
+    VaultLiquidated {
+        account_id: &alice,
+        liquidator_id: &bob,
+        debt: &amount,
+        collateral: &amount,
+    }
+    .emit();
+    OracleDivergence {
+        feed_id: "near",
+        oracle_id: &alice,
+        price: &amount,
+        median: &amount,
+    }
+    .emit();
+    Rebase {
+        old_index: &amount,
+        new_index: &amount,
+        total_supply: &amount,
+    }
+    .emit();
+    TransferScheduled {
+        scheduled_id: 0,
+        sender_id: &alice,
+        receiver_id: &bob,
+        amount: &amount,
+        execute_after: &U64(0),
+    }
+    .emit();
+
+    get_logs()
+        .into_iter()
+        .map(|log| {
+            let event: Value =
+                serde_json::from_str(log.strip_prefix("EVENT_JSON:").unwrap()).unwrap();
+            assert_eq!(event["standard"], "near_ft");
+            let fields = event["data"][0]
+                .as_object()
+                .unwrap()
+                .iter()
+                .map(|(name, value)| (name.clone(), json_type(value).to_string()))
+                .collect();
+            (
+                event["event"].as_str().unwrap().to_string(),
+                (event["version"].as_str().unwrap().to_string(), fields),
+            )
+        })
+        .collect()
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn parse_version(version: &str) -> (u64, u64, u64) {
+    let parts: Vec<u64> = version
+        .split('.')
+        .map(|part| part.parse().unwrap())
+        .collect();
+    let [major, minor, patch] = parts[..] else {
+        panic!("Invalid version {}", version);
+    };
+    (major, minor, patch)
+}
+
+/// The recorded versions of every event, oldest first.
+fn recorded_schemas() -> BTreeMap<String, Vec<(String, Fields)>> {
+    let schemas: BTreeMap<String, BTreeMap<String, Fields>> =
+        serde_json::from_str(SCHEMAS).unwrap();
+    schemas
+        .into_iter()
+        .map(|(event, versions)| {
+            let mut versions: Vec<(String, Fields)> = versions.into_iter().collect();
+            versions.sort_by_key(|(version, _)| parse_version(version));
+            (event, versions)
+        })
+        .collect()
+}
+
+#[test]
+fn test_events_match_their_latest_recorded_schema() {
+    let emitted = emit_samples();
+    let recorded = recorded_schemas();
+
+    assert_eq!(
+        emitted.keys().collect::<Vec<_>>(),
+        recorded.keys().collect::<Vec<_>>(),
+        "Every event needs a sample and a recorded schema"
+    );
+    for (event, (version, fields)) in emitted {
+        let (latest_version, latest_fields) = recorded[&event].last().unwrap();
+        assert_eq!(
+            &version, latest_version,
+            "{} is emitted with a version that isn't its latest recorded one",
+            event
+        );
+        assert_eq!(
+            &fields, latest_fields,
+            "The fields of {} {} changed, record a new version",
+            event, version
+        );
+    }
+}
+
+#[test]
+fn test_minor_versions_only_add_fields() {
+    for (event, versions) in recorded_schemas() {
+        for pair in versions.windows(2) {
+            let [(old_version, old_fields), (new_version, new_fields)] = pair else {
+                unreachable!();
+            };
+            if parse_version(old_version).0 != parse_version(new_version).0 {
+                continue;
+            }
+            for (name, json_type) in old_fields {
+                assert_eq!(
+                    new_fields.get(name),
+                    Some(json_type),
+                    "{} {} breaks the field {} of {}, bump the major version",
+                    event,
+                    new_version,
+                    name,
+                    old_version
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_nep141_events_exact_json() {
+    testing_env!(VMContextBuilder::new().build());
+    let (alice, bob) = (accounts(0), accounts(1));
+    FtMint {
+        owner_id: &alice,
+        amount: &U128(100),
+        memo: None,
+    }
+    .emit();
+    FtTransfer {
+        old_owner_id: &alice,
+        new_owner_id: &bob,
+        amount: &U128(10),
+        memo: Some("memo"),
+    }
+    .emit();
+    FtBurn::emit_many(&[
+        FtBurn {
+            owner_id: &alice,
+            amount: &U128(1),
+            memo: None,
+        },
+        FtBurn {
+            owner_id: &bob,
+            amount: &U128(2),
+            memo: None,
+        },
+    ]);
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{"owner_id":"alice","amount":"100"}]}"#,
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","amount":"10","memo":"memo"}]}"#,
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"alice","amount":"1"},{"owner_id":"bob","amount":"2"}]}"#,
+        ]
+    );
+}
+
+#[test]
+fn test_event_batches_log_one_event_per_kind() {
+    testing_env!(VMContextBuilder::new().build());
+    let (alice, bob, charlie) = (accounts(0), accounts(1), accounts(2));
+    let mut events = FtEventBatch::new();
+    events.transfer(&alice, &bob, 1, None);
+    events.burn(&alice, 3, Some("burn"));
+    events.transfer(&alice, &charlie, 2, None);
+    events.emit();
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","amount":"1"},{"old_owner_id":"alice","new_owner_id":"charlie","amount":"2"}]}"#,
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{"owner_id":"alice","amount":"3","memo":"burn"}]}"#,
+        ]
+    );
+
+    FtEventBatch::new().emit();
+    assert_eq!(get_logs().len(), 2);
+}
@@ -0,0 +1,251 @@
+use near_ft::factory::FactoryToken;
+use near_ft::metadata::FungibleTokenMetadata;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::{Contract, FT_METADATA_SPEC};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken, PromiseResult, RuntimeFeesConfig};
+
+const CODE: &[u8] = b"\0asm fake token";
+
+fn contract_id() -> AccountId {
+    "factory.near".parse().unwrap()
+}
+
+fn token_id() -> AccountId {
+    "alpha.factory.near".parse().unwrap()
+}
+
+fn metadata() -> FungibleTokenMetadata {
+    FungibleTokenMetadata {
+        spec: FT_METADATA_SPEC.to_string(),
+        name: "Alpha".to_string(),
+        symbol: "ALPHA".to_string(),
+        icon: None,
+        reference: None,
+        reference_hash: None,
+        decimals: 18,
+    }
+}
+
+fn factory_token(token_id: AccountId) -> FactoryToken {
+    FactoryToken {
+        token_id,
+        name: "Alpha".to_string(),
+        symbol: "ALPHA".to_string(),
+        decimals: 18,
+        owner_id: accounts(1),
+        deployed_at: U64(0),
+    }
+}
+
+// Alice owns the factory and uploaded its code. The context is left as alice, attaching
+// 1 NEAR to fund the deployments.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .build();
+    contract.set_factory_code(Base64VecU8(CODE.to_vec()));
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    (contract, context)
+}
+
+// Resolves the deployment of `token` with `result`.
+fn resolve_with(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    token: FactoryToken,
+    result: PromiseResult,
+) -> bool {
+    testing_env!(
+        context.predecessor_account_id(contract_id()).build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![result]
+    );
+    contract.on_token_deployed(token, U128(1_000), accounts(0), NearToken::from_near(1))
+}
+
+#[test]
+fn test_tokens_are_deployed_to_sub_accounts() {
+    let (mut contract, _) = setup();
+    assert_eq!(
+        contract.get_factory_token_id("alpha".to_string()),
+        token_id()
+    );
+    contract.deploy_token("alpha".to_string(), accounts(1), U128(1_000), metadata());
+
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, token_id());
+    let actions = &receipts[0].actions;
+    assert!(matches!(actions[0], MockAction::CreateAccount { .. }));
+    assert!(matches!(
+        actions[1],
+        MockAction::Transfer { deposit, .. } if deposit == NearToken::from_near(1)
+    ));
+    assert!(matches!(
+        &actions[2],
+        MockAction::DeployContract { code, .. } if code == CODE
+    ));
+    let MockAction::FunctionCallWeight {
+        method_name, args, ..
+    } = &actions[3]
+    else {
+        panic!("The token isn't initialized");
+    };
+    assert_eq!(method_name, b"new");
+    let args = String::from_utf8(args.clone()).unwrap();
+    assert!(args.contains(&format!("\"owner_id\":\"{}\"", accounts(1))));
+    assert!(args.contains("\"total_supply\":\"1000\""));
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can call this method")]
+fn test_only_the_owner_deploys_tokens() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.deploy_token("alpha".to_string(), accounts(1), U128(1_000), metadata());
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can call this method")]
+fn test_only_the_owner_sets_the_code() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_factory_code(Base64VecU8(CODE.to_vec()));
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit doesn't cover the storage of the code")]
+fn test_deposit_covers_the_code() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.deploy_token("alpha".to_string(), accounts(1), U128(1_000), metadata());
+}
+
+#[test]
+#[should_panic(expected = "The factory code isn't set")]
+fn test_tokens_need_the_code() {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .build();
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.deploy_token("alpha".to_string(), accounts(1), U128(1_000), metadata());
+}
+
+#[test]
+#[should_panic(expected = "The prefix should be a single account ID part")]
+fn test_tokens_are_direct_sub_accounts() {
+    let (mut contract, _) = setup();
+    contract.deploy_token(
+        "alpha.beta".to_string(),
+        accounts(1),
+        U128(1_000),
+        metadata(),
+    );
+}
+
+#[test]
+fn test_deployed_tokens_are_announced() {
+    let (mut contract, mut context) = setup();
+    assert!(resolve_with(
+        &mut contract,
+        &mut context,
+        factory_token(token_id()),
+        PromiseResult::Successful(vec![]),
+    ));
+    let logs = get_logs();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].contains("\"event\":\"token_deployed\""));
+    assert!(logs[0].contains(&format!("\"token_id\":\"{}\"", token_id())));
+    assert!(logs[0].contains(&format!("\"owner_id\":\"{}\"", accounts(1))));
+    assert!(logs[0].contains("\"total_supply\":\"1000\""));
+}
+
+#[test]
+fn test_failed_deployments_refund_the_deposit() {
+    let (mut contract, mut context) = setup();
+    assert!(!resolve_with(
+        &mut contract,
+        &mut context,
+        factory_token(token_id()),
+        PromiseResult::Failed,
+    ));
+    assert!(!get_logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"token_deployed\"")));
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, accounts(0));
+    assert!(matches!(
+        receipts[0].actions[0],
+        MockAction::Transfer { deposit, .. } if deposit == NearToken::from_near(1)
+    ));
+}
+
+#[test]
+fn test_deployed_tokens_are_registered() {
+    let (mut contract, mut context) = setup();
+    assert!(contract.list_tokens(None, None).is_empty());
+    testing_env!(context.block_timestamp(42).build());
+    resolve_with(
+        &mut contract,
+        &mut context,
+        factory_token(token_id()),
+        PromiseResult::Successful(vec![]),
+    );
+
+    let tokens = contract.list_tokens(None, None);
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token_id, token_id());
+    assert_eq!(tokens[0].symbol, "ALPHA");
+    assert_eq!(tokens[0].decimals, 18);
+    assert_eq!(tokens[0].owner_id, accounts(1));
+    assert_eq!(tokens[0].deployed_at, U64(42));
+}
+
+#[test]
+fn test_failed_deployments_are_not_registered() {
+    let (mut contract, mut context) = setup();
+    resolve_with(
+        &mut contract,
+        &mut context,
+        factory_token(token_id()),
+        PromiseResult::Failed,
+    );
+    assert!(contract.list_tokens(None, None).is_empty());
+}
+
+#[test]
+fn test_registry_is_paginated_in_deployment_order() {
+    let (mut contract, mut context) = setup();
+    let token_ids: Vec<AccountId> = (0..5)
+        .map(|index| format!("token{}.factory.near", index).parse().unwrap())
+        .collect();
+    for token_id in &token_ids {
+        resolve_with(
+            &mut contract,
+            &mut context,
+            factory_token(token_id.clone()),
+            PromiseResult::Successful(vec![]),
+        );
+    }
+
+    let listed = |from_index, limit| -> Vec<AccountId> {
+        contract
+            .list_tokens(from_index, limit)
+            .into_iter()
+            .map(|token| token.token_id)
+            .collect()
+    };
+    assert_eq!(listed(None, None), token_ids);
+    assert_eq!(listed(Some(1), Some(2)), token_ids[1..3]);
+    assert_eq!(listed(Some(4), Some(10)), token_ids[4..]);
+    assert!(listed(Some(5), None).is_empty());
+}
@@ -0,0 +1,341 @@
+use near_ft::fees::{FeeConfig, FeeSplit};
+use near_ft::ft_core::{FeeMode, FungibleTokenCore, TransferArg};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::with_mocked_blockchain;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .build()
+}
+
+#[test]
+fn test_transfer_fee_is_credited_to_the_collector() {
+    let (mut contract, _) = setup();
+    contract.set_transfer_fee(100, accounts(2));
+
+    contract.ft_transfer(accounts(1), U128(500), None, None, None, None);
+
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(5));
+    let transfers: Vec<String> = get_logs()
+        .into_iter()
+        .filter(|log| log.contains("\"event\":\"ft_transfer\""))
+        .collect();
+    assert_eq!(transfers.len(), 2);
+    assert!(transfers[0].contains("\"new_owner_id\":\"bob\",\"amount\":\"500\""));
+    assert!(transfers[1].contains("\"new_owner_id\":\"charlie\",\"amount\":\"5\""));
+}
+
+#[test]
+fn test_batch_transfers_log_the_fees_after_the_transfers() {
+    let (mut contract, _) = setup();
+    contract.set_transfer_fee(100, accounts(2));
+
+    contract.ft_transfer_batch(vec![
+        TransferArg {
+            receiver_id: accounts(1),
+            amount: U128(500),
+            memo: None,
+        },
+        TransferArg {
+            receiver_id: accounts(3),
+            amount: U128(200),
+            memo: None,
+        },
+    ]);
+
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(293));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(7));
+    let transfers: Vec<String> = get_logs()
+        .into_iter()
+        .filter(|log| log.contains("\"event\":\"ft_transfer\""))
+        .collect();
+    assert_eq!(transfers.len(), 3);
+    assert!(transfers[0].contains("\"new_owner_id\":\"bob\",\"amount\":\"500\""));
+    assert!(transfers[0].contains("\"new_owner_id\":\"danny\",\"amount\":\"200\""));
+    assert!(transfers[1].contains("\"new_owner_id\":\"charlie\",\"amount\":\"5\""));
+    assert!(transfers[2].contains("\"new_owner_id\":\"charlie\",\"amount\":\"2\""));
+}
+
+#[test]
+#[should_panic(expected = "The fee can't be higher than 5%")]
+fn test_transfer_fee_is_capped() {
+    let (mut contract, _) = setup();
+    contract.set_transfer_fee(501, accounts(2));
+}
+
+#[test]
+#[should_panic(expected = "Only the owner and the fee admins can call this method")]
+fn test_transfer_fee_is_set_by_the_owner() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.set_transfer_fee(100, accounts(2));
+}
+
+#[test]
+fn test_receiver_paying_into_the_collector_pays_no_fee() {
+    let (mut contract, _) = setup();
+    contract.set_transfer_fee(100, accounts(2));
+
+    contract.ft_transfer(
+        accounts(2),
+        U128(500),
+        None,
+        None,
+        None,
+        Some(FeeMode::ReceiverPays),
+    );
+
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(500));
+}
+
+#[test]
+fn test_receiver_pays_the_transfers_of_the_collector() {
+    let (mut contract, mut context) = setup();
+    contract.set_transfer_fee(100, accounts(2));
+    contract.ft_transfer(accounts(2), U128(300), None, None, None, None);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.ft_transfer(
+        accounts(1),
+        U128(200),
+        None,
+        None,
+        None,
+        Some(FeeMode::ReceiverPays),
+    );
+
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(198));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(105));
+}
+
+#[test]
+fn test_contract_receiving_with_the_receiver_paying_keeps_its_shares() {
+    let contract_id: AccountId = "token.near".parse().unwrap();
+    let (mut contract, _) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id.clone())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(2), 0)
+        .build();
+    contract.set_fee_config(Some(FeeConfig {
+        fee_bps: 100,
+        split: FeeSplit {
+            stakers_bps: 0,
+            treasury_bps: 5_000,
+            burn_bps: 0,
+            rebates_bps: 5_000,
+            insurance_bps: 0,
+        },
+        stakers_pool_id: accounts(2),
+        treasury_id: accounts(2),
+    }));
+
+    contract.ft_transfer(
+        contract_id.clone(),
+        U128(400),
+        None,
+        None,
+        None,
+        Some(FeeMode::ReceiverPays),
+    );
+
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(2));
+    assert_eq!(Contract::ft_balance_of(contract_id), U128(398));
+    assert_eq!(contract.get_rebate_pool(), U128(2));
+}
+
+// A 1% fee split between charlie's staking pool, danny's treasury and a burn.
+fn split_fee(stakers_bps: u16, treasury_bps: u16, burn_bps: u16) -> FeeConfig {
+    FeeConfig {
+        fee_bps: 100,
+        split: FeeSplit {
+            stakers_bps,
+            treasury_bps,
+            burn_bps,
+            rebates_bps: 0,
+            insurance_bps: 0,
+        },
+        stakers_pool_id: accounts(2),
+        treasury_id: accounts(3),
+    }
+}
+
+#[test]
+fn test_fee_split_is_accumulated_and_logged() {
+    let (mut contract, _) = setup();
+    contract.set_fee_config(Some(split_fee(5_000, 3_000, 2_000)));
+
+    contract.ft_transfer(accounts(1), U128(500), None, None, None, None);
+
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(2));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(1));
+    // The 50% of a fee of 5 rounds down, and the remainder is burned with the burn share.
+    assert_eq!(contract.ft_total_supply(), U128(998));
+    let accumulators = contract.get_fee_accumulators();
+    assert_eq!(accumulators.to_stakers, U128(2));
+    assert_eq!(accumulators.to_treasury, U128(1));
+    assert_eq!(accumulators.burned, U128(2));
+    assert!(get_logs().iter().any(|log| log.contains(
+        r#""event":"fee_distribution","data":[{"payer_id":"alice","to_stakers":"2","to_treasury":"1","burned":"2""#
+    )));
+}
+
+#[test]
+fn test_fee_split_remainder_goes_to_the_dust_account() {
+    let (mut contract, _) = setup();
+    contract.set_fee_config(Some(split_fee(5_000, 3_000, 2_000)));
+    contract.set_dust_account(Some(accounts(1)));
+
+    contract.ft_transfer(accounts(3), U128(500), None, None, None, None);
+
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(1));
+    assert_eq!(contract.ft_total_supply(), U128(999));
+    assert_eq!(contract.get_fee_accumulators().burned, U128(1));
+}
+
+#[test]
+#[should_panic(expected = "The fee split should add up to 100%")]
+fn test_fee_split_adds_up_to_the_fee() {
+    let (mut contract, _) = setup();
+    contract.set_fee_config(Some(split_fee(5_000, 3_000, 1_000)));
+}
+
+#[test]
+#[should_panic(expected = "The account eugene is not registered")]
+fn test_fee_buckets_are_registered() {
+    let (mut contract, _) = setup();
+    let mut config = split_fee(5_000, 3_000, 2_000);
+    config.treasury_id = accounts(4);
+    contract.set_fee_config(Some(config));
+}
+
+#[test]
+fn test_batch_transfers_are_charged_like_single_transfers() {
+    let transfers = [(accounts(1), 333), (accounts(4), 777)];
+    let setup = || {
+        // Both contracts start from an empty storage: `testing_env!` carries it over.
+        with_mocked_blockchain(|blockchain| blockchain.take_storage());
+        let (mut contract, context) = ContractBuilder::new(accounts(0))
+            .balance(accounts(0), 2_000)
+            .balance(accounts(1), 0)
+            .balance(accounts(2), 0)
+            .balance(accounts(3), 0)
+            .balance(accounts(4), 0)
+            .build();
+        contract.set_fee_config(Some(split_fee(5_000, 3_000, 2_000)));
+        contract.set_fee_category_rate("merchant".to_string(), Some(250));
+        contract.set_account_fee_category(accounts(4), Some("merchant".to_string()));
+        (contract, context)
+    };
+    let state = |contract: &Contract| {
+        let balances: Vec<U128> = (0..5)
+            .map(|index| Contract::ft_balance_of(accounts(index)))
+            .collect();
+        let accumulators = contract.get_fee_accumulators();
+        (
+            balances,
+            contract.ft_total_supply(),
+            accumulators.to_stakers,
+            accumulators.to_treasury,
+            accumulators.burned,
+        )
+    };
+
+    let (mut contract, _) = setup();
+    for (receiver_id, amount) in transfers.iter() {
+        contract.ft_transfer(receiver_id.clone(), U128(*amount), None, None, None, None);
+    }
+    let single = state(&contract);
+
+    let (mut contract, _) = setup();
+    contract.ft_transfer_batch(
+        transfers
+            .iter()
+            .map(|(receiver_id, amount)| TransferArg {
+                receiver_id: receiver_id.clone(),
+                amount: U128(*amount),
+                memo: None,
+            })
+            .collect(),
+    );
+
+    assert_eq!(state(&contract), single);
+    // 1% of 333 and 2.5% of 777, each split and rounded on its own.
+    assert_eq!(single.0[0], U128(2_000 - 333 - 3 - 777 - 19));
+}
+
+#[test]
+fn test_receiver_paid_fee_is_checked_against_the_minimum_received() {
+    let (mut contract, _) = setup();
+    contract.set_transfer_fee(100, accounts(2));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        contract.ft_transfer(
+            accounts(1),
+            U128(500),
+            None,
+            None,
+            Some(U128(496)),
+            Some(FeeMode::ReceiverPays),
+        )
+    }));
+    let error = result.err().unwrap();
+    assert!(error
+        .downcast_ref::<String>()
+        .unwrap()
+        .contains("The receiver would be credited less than the minimum received"));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_000));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(0));
+
+    // The received amount net of the fee is enough.
+    contract.ft_transfer(
+        accounts(1),
+        U128(500),
+        None,
+        None,
+        Some(U128(495)),
+        Some(FeeMode::ReceiverPays),
+    );
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(495));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(5));
+}
+
+#[test]
+fn test_receiver_category_rate_wins_over_the_sender_one() {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 10_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .build();
+    // 1% by default, 0.5% for exchanges like alice and 2% for merchants like bob.
+    contract.set_transfer_fee(100, accounts(3));
+    contract.set_fee_category_rate("exchange".to_string(), Some(50));
+    contract.set_fee_category_rate("merchant".to_string(), Some(200));
+    contract.set_account_fee_category(accounts(0), Some("exchange".to_string()));
+    contract.set_account_fee_category(accounts(1), Some("merchant".to_string()));
+
+    contract.ft_transfer(accounts(1), U128(1_000), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(20));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(0), U128(400), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(22));
+
+    // The sender's category applies when the receiver has none.
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.ft_transfer(accounts(2), U128(1_000), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(27));
+}
@@ -0,0 +1,220 @@
+use near_ft::access::Role;
+use near_ft::freezing::{FreezeAppeal, FreezeV1, VersionedFreeze};
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::guard::PauseFlags;
+use near_ft::storage_keys::{FreezingKey, StorageKey};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::borsh;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, IntoStorageKey};
+
+const SECOND: u64 = 1_000_000_000;
+
+// Alice owns the contract and bob holds 100 tokens. Charlie is a freeze admin and has frozen
+// bob for an hour.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 100)
+        .balance(accounts(3), 0)
+        .build();
+    contract.grant_role(accounts(2), Role::FreezeAdmin);
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.freeze_account(accounts(1), 7, 3_600);
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    (contract, context)
+}
+
+#[test]
+#[should_panic(expected = "The account bob is frozen")]
+fn test_freeze_admin_blocks_transfers_to_frozen_accounts() {
+    let (mut contract, _) = setup();
+    assert!(contract.is_frozen(accounts(1)));
+    contract.ft_transfer(accounts(1), U128(10), None, None, None, None);
+}
+
+#[test]
+#[should_panic(expected = "The account bob is frozen")]
+fn test_frozen_accounts_cant_send() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(0), U128(10), None, None, None, None);
+}
+
+#[test]
+#[should_panic(expected = "The account bob is frozen")]
+fn test_frozen_accounts_cant_lock_their_tokens() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.lock_tokens(U128(10), 60, None);
+}
+
+#[test]
+#[should_panic(expected = "Transfers are paused")]
+fn test_locks_are_paused_with_the_transfers() {
+    let (mut contract, _) = setup();
+    contract.set_pause_flags(PauseFlags {
+        transfers: true,
+        ..Default::default()
+    });
+    contract.lock_tokens(U128(10), 60, None);
+}
+
+#[test]
+fn test_fees_reach_a_frozen_collector() {
+    let (mut contract, _) = setup();
+    contract.set_transfer_fee(100, accounts(1));
+    contract.ft_transfer(accounts(3), U128(500), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(105));
+}
+
+#[test]
+fn test_freezes_expire_unless_renewed() {
+    let (mut contract, mut context) = setup();
+    let freeze = contract.get_freeze(accounts(1)).unwrap();
+    assert_eq!(freeze.reason_code, 7);
+    assert_eq!(freeze.expires_at.0, 3_600 * SECOND);
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(1_800 * SECOND)
+        .build());
+    contract.renew_freeze(accounts(1), 3_600);
+    testing_env!(context.block_timestamp(3_601 * SECOND).build());
+    assert!(contract.is_frozen(accounts(1)));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .block_timestamp(5_401 * SECOND)
+        .build());
+    assert!(!contract.is_frozen(accounts(1)));
+    contract.ft_transfer(accounts(1), U128(10), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(110));
+}
+
+#[test]
+fn test_unfreezing_drops_the_appeal() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.file_freeze_appeal("The transfer was a refund".to_string());
+    assert_eq!(
+        contract.get_freeze_appeal(accounts(1)).unwrap().note,
+        "The transfer was a refund"
+    );
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.unfreeze_account(accounts(1));
+    assert!(!contract.is_frozen(accounts(1)));
+    assert!(contract.get_freeze_appeal(accounts(1)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The account is not frozen")]
+fn test_only_frozen_accounts_appeal() {
+    let (mut contract, _) = setup();
+    contract.file_freeze_appeal("Nothing to appeal".to_string());
+}
+
+#[test]
+#[should_panic(expected = "The account is not frozen")]
+fn test_expired_freezes_cant_be_renewed() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(3_600 * SECOND)
+        .build());
+    contract.renew_freeze(accounts(1), 3_600);
+}
+
+#[test]
+#[should_panic(expected = "The freeze duration should be positive")]
+fn test_freezes_last_some_time() {
+    let (mut contract, _) = setup();
+    contract.freeze_account(accounts(3), 1, 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner and the")]
+fn test_only_freeze_admins_freeze() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    contract.freeze_account(accounts(0), 1, 3_600);
+}
+
+fn freeze_key(account_id: &AccountId) -> Vec<u8> {
+    let mut key = StorageKey::Freezing(FreezingKey::FrozenAccounts).into_storage_key();
+    key.extend(borsh::to_vec(account_id).unwrap());
+    key
+}
+
+fn has_blob(account_id: &AccountId) -> bool {
+    env::storage_has_key(
+        &StorageKey::AccountBlob {
+            account_hash: env::sha256_array(account_id.as_bytes()),
+        }
+        .into_storage_key(),
+    )
+}
+
+// Danny was frozen for an hour and appealed before the appeals moved to the account blobs.
+// The context is left as charlie, a freeze admin.
+fn setup_legacy_appeal() -> (Contract, VMContextBuilder) {
+    let (contract, mut context) = setup();
+    let freeze = VersionedFreeze::V1(FreezeV1 {
+        reason_code: 3,
+        frozen_at: U64(0),
+        expires_at: U64(3_600 * SECOND),
+        appeal: Some(FreezeAppeal {
+            note: "Stolen keys, already rotated".to_string(),
+            filed_at: U64(60 * SECOND),
+        }),
+    });
+    env::storage_write(&freeze_key(&accounts(3)), &borsh::to_vec(&freeze).unwrap());
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    (contract, context)
+}
+
+#[test]
+fn test_appeals_filed_before_the_blobs_are_read() {
+    let (contract, _) = setup_legacy_appeal();
+    assert!(contract.is_frozen(accounts(3)));
+    assert_eq!(contract.get_freeze(accounts(3)).unwrap().reason_code, 3);
+    let appeal = contract.get_freeze_appeal(accounts(3)).unwrap();
+    assert_eq!(appeal.note, "Stolen keys, already rotated");
+    assert_eq!(appeal.filed_at, U64(60 * SECOND));
+    // Reading doesn't write anything.
+    assert!(!has_blob(&accounts(3)));
+}
+
+#[test]
+fn test_appeals_filed_before_the_blobs_move_on_first_update() {
+    let (mut contract, _) = setup_legacy_appeal();
+    contract.renew_freeze(accounts(3), 7_200);
+    assert!(has_blob(&accounts(3)));
+    let record = env::storage_read(&freeze_key(&accounts(3))).unwrap();
+    assert!(matches!(
+        borsh::from_slice::<VersionedFreeze>(&record).unwrap(),
+        VersionedFreeze::V2(_)
+    ));
+
+    assert_eq!(
+        contract.get_freeze_appeal(accounts(3)).unwrap().note,
+        "Stolen keys, already rotated"
+    );
+    assert_eq!(
+        contract.get_freeze(accounts(3)).unwrap().expires_at,
+        U64(7_200 * SECOND)
+    );
+}
+
+#[test]
+fn test_appeals_filed_before_the_blobs_are_dropped_with_the_freeze() {
+    let (mut contract, _) = setup_legacy_appeal();
+    contract.unfreeze_account(accounts(3));
+    assert!(contract.get_freeze_appeal(accounts(3)).is_none());
+    assert!(!has_blob(&accounts(3)));
+}
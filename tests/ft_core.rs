@@ -0,0 +1,204 @@
+use near_ft::ft_core::{FungibleTokenCore, TransferArg};
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, NearToken, PromiseResult, RuntimeFeesConfig};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build();
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    (contract, context)
+}
+
+fn resolve_with(context: &mut VMContextBuilder, result: PromiseResult) {
+    testing_env!(
+        context
+            .predecessor_account_id(context.context.current_account_id.clone())
+            .attached_deposit(NearToken::from_yoctonear(0))
+            .build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![result]
+    );
+}
+
+#[test]
+fn test_resolve_transfer_refunds_the_unused_amount() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(2), U128(100), None, None, None, None);
+
+    resolve_with(&mut context, PromiseResult::Successful(b"\"30\"".to_vec()));
+    let used = contract.ft_resolve_transfer(&accounts(1), accounts(2), U128(100));
+
+    assert_eq!(used, U128(70));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(30));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(70));
+}
+
+#[test]
+fn test_resolve_transfer_burns_the_refund_of_an_unregistered_sender() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(2), U128(100), None, None, None, None);
+    // The sender unregisters while `ft_on_transfer` runs.
+    assert!(contract.storage_unregister(None));
+
+    resolve_with(&mut context, PromiseResult::Failed);
+    let used = contract.ft_resolve_transfer(&accounts(1), accounts(2), U128(100));
+
+    assert_eq!(used, U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(0));
+    assert_eq!(contract.ft_total_supply(), U128(900));
+}
+
+#[test]
+fn test_transfer_batch_logs_a_single_event() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_batch(vec![
+        TransferArg {
+            receiver_id: accounts(1),
+            amount: U128(10),
+            memo: None,
+        },
+        TransferArg {
+            receiver_id: accounts(2),
+            amount: U128(20),
+            memo: Some("airdrop".to_string()),
+        },
+    ]);
+
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(110));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(20));
+    // The setup logged its own transfer first.
+    let logs = get_logs();
+    assert_eq!(logs.len(), 2);
+    assert!(logs[1].contains(r#""new_owner_id":"bob","amount":"10""#));
+    assert!(logs[1].contains(r#""new_owner_id":"charlie","amount":"20","memo":"airdrop""#));
+}
+
+#[test]
+#[should_panic(expected = "The account danny is not registered")]
+fn test_transfer_batch_needs_every_receiver_registered() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_batch(vec![
+        TransferArg {
+            receiver_id: accounts(1),
+            amount: U128(10),
+            memo: None,
+        },
+        TransferArg {
+            receiver_id: accounts(3),
+            amount: U128(10),
+            memo: None,
+        },
+    ]);
+}
+
+#[test]
+fn test_transfer_and_register_registers_the_receiver() {
+    let (mut contract, mut context) = setup();
+    let deposit = Contract::storage_balance_bounds()
+        .min
+        .saturating_add(NearToken::from_yoctonear(5));
+    testing_env!(context.attached_deposit(deposit).build());
+    contract.ft_transfer_and_register(accounts(3), U128(10), None);
+
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(10));
+    // The excess deposit is refunded.
+    let receipts = get_created_receipts();
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(receipts[0].receiver_id, accounts(0));
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit is less than the minimum storage balance")]
+fn test_transfer_and_register_needs_the_storage_deposit() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_and_register(accounts(3), U128(10), None);
+}
+
+// Moves the block to `timestamp` (in nanoseconds), still calling as alice with one yoctoNEAR.
+fn at(context: &mut VMContextBuilder, timestamp: u64) {
+    testing_env!(context.block_timestamp(timestamp).build());
+}
+
+// Returns whether the last call scheduled `ft_on_transfer` on bob.
+fn calls_receiver() -> bool {
+    get_created_receipts().iter().any(|receipt| {
+        receipt.receiver_id == accounts(1)
+            && receipt.actions.iter().any(|action| {
+                matches!(
+                    action,
+                    MockAction::FunctionCallWeight { method_name, .. }
+                        if method_name == b"ft_on_transfer"
+                )
+            })
+    })
+}
+
+#[test]
+fn test_transfers_are_made_until_the_deadline() {
+    let (mut contract, mut context) = setup();
+    at(&mut context, 100);
+    contract.ft_transfer(accounts(1), U128(10), None, Some(U64(100)), None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(110));
+}
+
+#[test]
+#[should_panic(expected = "The transfer deadline has passed")]
+fn test_expired_transfers_are_rejected() {
+    let (mut contract, mut context) = setup();
+    at(&mut context, 101);
+    contract.ft_transfer(accounts(1), U128(10), None, Some(U64(100)), None, None);
+}
+
+#[test]
+fn test_transfer_calls_are_made_until_the_deadline() {
+    let (mut contract, mut context) = setup();
+    at(&mut context, 100);
+    let _ = contract.ft_transfer_call(
+        accounts(1),
+        U128(10),
+        None,
+        String::new(),
+        Some(U64(100)),
+        None,
+        None,
+    );
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(110));
+    assert!(calls_receiver());
+}
+
+#[test]
+fn test_expired_transfer_calls_dont_call_the_receiver() {
+    let (mut contract, mut context) = setup();
+    at(&mut context, 101);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        contract.ft_transfer_call(
+            accounts(1),
+            U128(10),
+            None,
+            String::new(),
+            Some(U64(100)),
+            None,
+            None,
+        )
+    }));
+    let error = result.err().unwrap();
+    assert!(error
+        .downcast_ref::<String>()
+        .unwrap()
+        .contains("The transfer deadline has passed"));
+    assert!(!calls_receiver());
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(900));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+}
@@ -0,0 +1,64 @@
+use near_ft::ft_core::{CallCaps, FungibleTokenCore};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, Gas, NearToken};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build()
+}
+
+fn transfer_call(contract: &mut Contract) {
+    let _ = contract.ft_transfer_call(
+        accounts(1),
+        U128(100),
+        None,
+        "".to_string(),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_calls_within_the_caps() {
+    let (mut contract, _) = setup();
+    contract.set_call_caps(Some(CallCaps {
+        max_gas_tgas: 30,
+        max_deposit: NearToken::from_yoctonear(1),
+    }));
+    assert_eq!(contract.get_call_caps().unwrap().max_gas_tgas, 30);
+    transfer_call(&mut contract);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+}
+
+#[test]
+#[should_panic(expected = "E_CALL_GAS_CAP_EXCEEDED: A call can't attach more than 20 TGas")]
+fn test_call_over_the_gas_cap() {
+    let (mut contract, _) = setup();
+    contract.set_call_caps(Some(CallCaps {
+        max_gas_tgas: 20,
+        max_deposit: NearToken::from_yoctonear(1),
+    }));
+    transfer_call(&mut contract);
+}
+
+#[test]
+#[should_panic(expected = "E_NOT_ENOUGH_GAS: The call needs at least 40 TGas left")]
+fn test_call_without_enough_prepaid_gas() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.prepaid_gas(Gas::from_tgas(30)).build());
+    transfer_call(&mut contract);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can call this method")]
+fn test_call_caps_are_set_by_the_owner() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.set_call_caps(None);
+}
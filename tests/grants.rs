@@ -0,0 +1,140 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::grants::GrantTransferPolicy;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId};
+
+const SECOND: u64 = 1_000_000_000;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Alice grants bob 1,000 tokens vesting over 100 seconds with a 10 seconds cliff, which bob can
+// transfer after the cliff (grant 0). The context is left as bob.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 2_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build();
+    let grant_id = contract.create_vesting_grant(
+        accounts(1),
+        U128(1_000),
+        None,
+        10,
+        100,
+        GrantTransferPolicy::AfterCliff,
+    );
+    assert_eq!(grant_id, 0);
+    assert!(get_logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"nft_mint\"")));
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    (contract, context)
+}
+
+#[test]
+fn test_grants_vest_linearly_after_the_cliff() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(1_000));
+    testing_env!(context.block_timestamp(50 * SECOND).build());
+    assert_eq!(
+        contract.nft_token("0".to_string()).unwrap().claimable,
+        U128(500)
+    );
+    assert_eq!(contract.claim_vesting_grant(0), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+
+    testing_env!(context.block_timestamp(200 * SECOND).build());
+    assert_eq!(contract.claim_vesting_grant(0), U128(500));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(0));
+}
+
+#[test]
+fn test_grants_move_with_their_holder() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(10 * SECOND).build());
+    contract.nft_transfer(accounts(2), "0".to_string(), None, None);
+    assert!(contract
+        .nft_tokens_for_owner(accounts(1), None, None)
+        .is_empty());
+    let grants = contract.nft_tokens_for_owner(accounts(2), None, None);
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].owner_id, accounts(2));
+    // The tokens vested before the transfer go with the grant.
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(20 * SECOND)
+        .build());
+    assert_eq!(contract.claim_vesting_grant(0), U128(200));
+}
+
+#[test]
+#[should_panic(expected = "Nothing is claimable yet")]
+fn test_nothing_vests_before_the_cliff() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(10 * SECOND - 1).build());
+    contract.claim_vesting_grant(0);
+}
+
+#[test]
+#[should_panic(expected = "The vesting grant can't be transferred before its cliff")]
+fn test_grants_are_transferred_after_the_cliff() {
+    let (mut contract, _) = setup();
+    contract.transfer_grant(0, accounts(2), None);
+}
+
+#[test]
+#[should_panic(expected = "The vesting grant is not transferable")]
+fn test_non_transferable_grants_stay_with_their_holder() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.create_vesting_grant(
+        accounts(1),
+        U128(100),
+        None,
+        0,
+        100,
+        GrantTransferPolicy::NonTransferable,
+    );
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.transfer_grant(1, accounts(2), None);
+}
+
+#[test]
+#[should_panic(expected = "Only the holder can claim the grant")]
+fn test_only_the_holder_claims() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(50 * SECOND)
+        .build());
+    contract.claim_vesting_grant(0);
+}
+
+#[test]
+#[should_panic(expected = "Approvals are not supported")]
+fn test_grant_approvals_arent_supported() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(10 * SECOND).build());
+    contract.nft_transfer(accounts(2), "0".to_string(), Some(0), None);
+}
+
+#[test]
+#[should_panic(expected = "The cliff should be shorter than the vesting duration")]
+fn test_the_cliff_is_within_the_vesting() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.create_vesting_grant(
+        accounts(1),
+        U128(100),
+        None,
+        101,
+        100,
+        GrantTransferPolicy::Transferable,
+    );
+}
@@ -0,0 +1,45 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::guard::PauseFlags;
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+fn setup() -> Contract {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract
+}
+
+#[test]
+#[should_panic(expected = "Transfers are paused")]
+fn test_pause_blocks_transfers() {
+    let mut contract = setup();
+    contract.pause();
+    contract.ft_transfer(accounts(1), U128(10), None, None, None, None);
+}
+
+#[test]
+fn test_pause_flags_are_granular() {
+    let mut contract = setup();
+    contract.set_pause_flags(PauseFlags {
+        mints: true,
+        ..Default::default()
+    });
+    contract.ft_transfer(accounts(1), U128(10), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(10));
+
+    contract.unpause();
+    contract.ft_mint(accounts(1), U128(5), None);
+    assert_eq!(contract.ft_total_supply(), U128(1_005));
+}
@@ -0,0 +1,86 @@
+use near_ft::guard::PauseFlags;
+use near_ft::health::{HealthIssue, HealthStatus};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U64;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build()
+}
+
+#[test]
+fn test_fresh_contract_is_ok() {
+    let (contract, _) = setup();
+    let report = contract.health_check();
+    assert_eq!(report.status, HealthStatus::Ok);
+    assert!(report.issues.is_empty());
+}
+
+#[test]
+fn test_paused_transfers_page_as_paused() {
+    let (mut contract, _) = setup();
+    let flags = PauseFlags {
+        transfers: false,
+        mints: true,
+        storage: false,
+    };
+    contract.set_pause_flags(flags.clone());
+    let report = contract.health_check();
+    assert_eq!(report.status, HealthStatus::Degraded);
+    assert_eq!(report.issues, vec![HealthIssue::Paused { flags }]);
+
+    contract.pause();
+    assert_eq!(contract.health_check().status, HealthStatus::Paused);
+
+    contract.unpause();
+    assert_eq!(contract.health_check().status, HealthStatus::Ok);
+}
+
+#[test]
+fn test_migration_and_quarantine_degrade() {
+    let (mut contract, mut context) = setup();
+    contract.set_successor_contract(Some(accounts(3)));
+    contract.rename_token(Some("Renamed Token".to_string()), None);
+
+    let report = contract.health_check();
+    assert_eq!(report.status, HealthStatus::Degraded);
+    assert_eq!(
+        report.issues,
+        vec![
+            HealthIssue::Migrating {
+                successor_id: accounts(3)
+            },
+            HealthIssue::RenameQuarantine {
+                ends_at: U64(7 * NANOS_PER_DAY)
+            },
+        ]
+    );
+
+    testing_env!(context.block_timestamp(7 * NANOS_PER_DAY).build());
+    assert_eq!(
+        contract.health_check().issues,
+        vec![HealthIssue::Migrating {
+            successor_id: accounts(3)
+        }]
+    );
+}
+
+#[test]
+fn test_near_shortfall_is_reported() {
+    let (contract, mut context) = setup();
+    testing_env!(context
+        .account_balance(NearToken::from_yoctonear(0))
+        .build());
+    let report = contract.health_check();
+    assert_eq!(report.status, HealthStatus::Degraded);
+    assert!(matches!(
+        report.issues[..],
+        [HealthIssue::NearShortfall { .. }]
+    ));
+}
@@ -0,0 +1,139 @@
+use near_ft::access::Role;
+use near_ft::fees::{FeeConfig, FeeSplit};
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::insurance::{InsuranceConfig, MIN_INSURANCE_TIMELOCK_SEC};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+
+const TIMELOCK_NANOS: u64 = MIN_INSURANCE_TIMELOCK_SEC * 1_000_000_000;
+
+// Half of the 1% fee funds the insurance, and bob's transfer of 100_000 funded it with 500.
+// Danny adjudicates claims of up to 300.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build();
+    contract.ft_transfer(accounts(1), U128(200_000), None, None, None, None);
+    contract.set_fee_config(Some(FeeConfig {
+        fee_bps: 100,
+        split: FeeSplit {
+            stakers_bps: 0,
+            treasury_bps: 5_000,
+            burn_bps: 0,
+            rebates_bps: 0,
+            insurance_bps: 5_000,
+        },
+        stakers_pool_id: accounts(2),
+        treasury_id: accounts(2),
+    }));
+    contract.set_insurance_config(Some(InsuranceConfig {
+        max_payout_per_claim: U128(300),
+        timelock_sec: MIN_INSURANCE_TIMELOCK_SEC,
+    }));
+    contract.grant_role(accounts(3), Role::Adjudicator);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(0), U128(100_000), None, None, None, None);
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    (contract, context)
+}
+
+#[test]
+fn test_fee_share_funds_the_insurance() {
+    let (contract, _) = setup();
+    assert_eq!(contract.get_insurance_fund().balance, U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(500));
+    let accumulators = contract.get_fee_accumulators();
+    assert_eq!(accumulators.to_insurance, U128(500));
+    assert_eq!(accumulators.to_treasury, U128(500));
+}
+
+#[test]
+fn test_payouts_wait_for_the_timelock() {
+    let (mut contract, mut context) = setup();
+    let claim_id = contract.approve_insurance_payout(accounts(2), U128(300), "hack".to_string());
+    let fund = contract.get_insurance_fund();
+    assert_eq!(fund.balance, U128(200));
+    assert_eq!(fund.reserved, U128(300));
+
+    testing_env!(context.block_timestamp(TIMELOCK_NANOS).build());
+    contract.execute_insurance_payout(claim_id);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(800));
+    assert_eq!(contract.get_insurance_fund().reserved, U128(0));
+    assert!(contract.get_insurance_claim(claim_id).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The payout is still timelocked")]
+fn test_payouts_cant_skip_the_timelock() {
+    let (mut contract, mut context) = setup();
+    let claim_id = contract.approve_insurance_payout(accounts(2), U128(300), "hack".to_string());
+    testing_env!(context.block_timestamp(TIMELOCK_NANOS - 1).build());
+    contract.execute_insurance_payout(claim_id);
+}
+
+#[test]
+#[should_panic(expected = "The payout exceeds the cap per claim")]
+fn test_payouts_are_capped_per_claim() {
+    let (mut contract, _) = setup();
+    contract.approve_insurance_payout(accounts(2), U128(301), "hack".to_string());
+}
+
+#[test]
+#[should_panic(expected = "The insurance fund is too low")]
+fn test_payouts_cant_exceed_the_fund() {
+    let (mut contract, _) = setup();
+    contract.approve_insurance_payout(accounts(2), U128(300), "hack".to_string());
+    contract.approve_insurance_payout(accounts(2), U128(300), "hack".to_string());
+}
+
+#[test]
+#[should_panic(expected = "Only the owner and the adjudicators can call this method")]
+fn test_only_adjudicators_approve_payouts() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.approve_insurance_payout(accounts(1), U128(100), "hack".to_string());
+}
+
+#[test]
+fn test_cancelled_payouts_return_to_the_fund() {
+    let (mut contract, mut context) = setup();
+    let claim_id = contract.approve_insurance_payout(accounts(2), U128(300), "hack".to_string());
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.cancel_insurance_payout(claim_id);
+    let fund = contract.get_insurance_fund();
+    assert_eq!(fund.balance, U128(500));
+    assert_eq!(fund.reserved, U128(0));
+}
+
+#[test]
+#[should_panic(expected = "The payout timelock is too short")]
+fn test_timelocks_are_mandatory() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_insurance_config(Some(InsuranceConfig {
+        max_payout_per_claim: U128(300),
+        timelock_sec: MIN_INSURANCE_TIMELOCK_SEC - 1,
+    }));
+}
+
+#[test]
+fn test_frozen_accounts_are_slashed_into_the_fund() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.freeze_account(accounts(1), 1, 3_600);
+    contract.slash_to_insurance(accounts(1), U128(1_000), "Exploit proceeds".to_string());
+    assert_eq!(contract.get_insurance_fund().balance, U128(1_500));
+}
+
+#[test]
+#[should_panic(expected = "Only frozen accounts can be slashed")]
+fn test_unfrozen_accounts_cant_be_slashed() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.slash_to_insurance(accounts(1), U128(1_000), "Exploit proceeds".to_string());
+}
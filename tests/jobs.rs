@@ -0,0 +1,97 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId};
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Alice distributes 100 tokens to bob, charlie, eugene and an unregistered account, with a
+// bounty of 2 tokens per step out of a budget of 10. Danny runs the steps, 2 payouts at a
+// time. The context is left as danny.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .balance(accounts(4), 0)
+        .build();
+    let payouts = [
+        accounts(1),
+        accounts(2),
+        accounts(4),
+        "ghost.near".parse().unwrap(),
+    ]
+    .into_iter()
+    .map(|account_id| (account_id, U128(100)))
+    .collect();
+    let job_id = contract.create_distribution_job(payouts, U128(2), U128(10));
+    assert_eq!(job_id, 0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(590));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(410));
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    (contract, context)
+}
+
+#[test]
+fn test_job_runs_over_several_calls() {
+    let (mut contract, _) = setup();
+    assert!(!contract.continue_job(0, Some(2)));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(4)), U128(0));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(2));
+
+    assert!(contract.continue_job(0, Some(2)));
+    assert_eq!(Contract::ft_balance_of(accounts(4)), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(4));
+}
+
+#[test]
+fn test_job_resumes_from_its_cursor() {
+    let (mut contract, _) = setup();
+    contract.continue_job(0, Some(1));
+    let job = contract.get_job(0).unwrap();
+    assert_eq!(job.cursor, 1);
+    assert_eq!(job.len, 4);
+    assert_eq!(job.bounty_budget, U128(8));
+
+    contract.continue_job(0, Some(1));
+    assert_eq!(contract.get_job(0).unwrap().cursor, 2);
+    // Every payout is made once.
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(100));
+}
+
+#[test]
+fn test_completed_job_is_cleaned_up() {
+    let (mut contract, _) = setup();
+    assert!(contract.continue_job(0, None));
+    assert!(contract.get_job(0).is_none());
+    // The unregistered payout and the rest of the bounty budget go back to alice.
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(590 + 100 + 8));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(0));
+}
+
+#[test]
+#[should_panic(expected = "The job doesn't exist")]
+fn test_completed_job_cannot_be_continued() {
+    let (mut contract, _) = setup();
+    contract.continue_job(0, None);
+    contract.continue_job(0, None);
+}
+
+#[test]
+fn test_unregistered_callers_earn_no_bounty() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id("keeper.near".parse().unwrap())
+        .build());
+    contract.continue_job(0, Some(2));
+    assert_eq!(contract.get_job(0).unwrap().bounty_budget, U128(10));
+}
@@ -0,0 +1,94 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::jurisdictions::JurisdictionRule;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+
+// Danny attests bob as "us" and charlie as "kp". Nothing goes from "us" to "kp", and
+// untagged accounts can't receive more than 100 tokens. Eugene stays untagged. The context
+// is left as bob.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(1), 1_000)
+        .balance(accounts(2), 1_000)
+        .balance(accounts(4), 1_000)
+        .with(|contract| {
+            contract.set_jurisdiction_attestor(accounts(3), true);
+            contract.set_jurisdiction_rule(
+                "us".to_string(),
+                "kp".to_string(),
+                Some(JurisdictionRule::Deny),
+            );
+            contract.set_jurisdiction_rule(
+                "*".to_string(),
+                "untagged".to_string(),
+                Some(JurisdictionRule::MaxAmount(U128(100))),
+            );
+        })
+        .build();
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    contract.attest_jurisdiction(accounts(1), Some("us".to_string()));
+    contract.attest_jurisdiction(accounts(2), Some("kp".to_string()));
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    (contract, context)
+}
+
+#[test]
+#[should_panic(expected = "Transfers between these jurisdictions are not allowed")]
+fn test_transfers_between_blocked_jurisdictions_are_rejected() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(2), U128(1), None, None, None, None);
+}
+
+#[test]
+fn test_transfers_between_allowed_jurisdictions_go_through() {
+    let (mut contract, mut context) = setup();
+    // The rule only applies from "us" to "kp".
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.ft_transfer(accounts(1), U128(500), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(1_500));
+}
+
+#[test]
+fn test_untagged_accounts_follow_the_untagged_rules() {
+    let (mut contract, _) = setup();
+    assert_eq!(contract.get_jurisdiction(accounts(4)), None);
+    contract.ft_transfer(accounts(4), U128(100), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(4)), U128(1_100));
+}
+
+#[test]
+#[should_panic(expected = "The amount exceeds the limit between these jurisdictions")]
+fn test_untagged_accounts_receive_up_to_the_limit() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(4), U128(101), None, None, None, None);
+}
+
+#[test]
+fn test_untagged_senders_are_not_limited_by_the_receiver_rules() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(4)).build());
+    contract.ft_transfer(accounts(2), U128(500), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(1_500));
+}
+
+#[test]
+fn test_removing_the_tag_makes_the_account_untagged() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    contract.attest_jurisdiction(accounts(2), None);
+    assert_eq!(contract.get_jurisdiction(accounts(2)), None);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(2), U128(100), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(1_100));
+}
+
+#[test]
+#[should_panic(expected = "Only a jurisdiction attestor can call this method")]
+fn test_only_attestors_tag_accounts() {
+    let (mut contract, _) = setup();
+    contract.attest_jurisdiction(accounts(1), Some("ch".to_string()));
+}
@@ -0,0 +1,208 @@
+use near_ft::ft_core::{CoSignerConfig, FungibleTokenCore};
+use near_ft::keepers::KeeperBounties;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken, PromiseResult, RuntimeFeesConfig};
+
+const SECOND: u64 = 1_000_000_000;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Keepers earn 10 tokens per mining emission and 5 per expired pending transfer, out of a
+// pool of `pool` tokens funded by alice. The context is left as alice.
+fn setup(pool: u128) -> (Contract, VMContextBuilder) {
+    let (mut contract, context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .balance(accounts(4), 0)
+        .with(|contract| {
+            contract.set_keeper_bounties(KeeperBounties {
+                mining_emission: U128(10),
+                expiry_cleanup: U128(5),
+            })
+        })
+        .build();
+    if pool > 0 {
+        contract.fund_keeper_pool(U128(pool));
+    }
+    (contract, context)
+}
+
+// Starts a program emitting 100 tokens a minute to bob's pool for 5 minutes, funded by alice.
+fn start_mining(contract: &mut Contract) {
+    contract.create_mining_program(accounts(1), None, U128(100), 60, 5, None);
+    contract.fund_mining_program(0, U128(500));
+}
+
+// Executes the emission of the epochs due at `at_sec` as `keeper_id`, and resolves it.
+fn emit(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    keeper_id: AccountId,
+    at_sec: u64,
+    result: PromiseResult,
+) {
+    testing_env!(context
+        .predecessor_account_id(keeper_id.clone())
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(at_sec * SECOND)
+        .build());
+    contract.execute_mining_emission(0);
+    testing_env!(
+        context.predecessor_account_id(contract_id()).build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![result]
+    );
+    contract.on_mining_emission_resolved(0, 1, U128(100), keeper_id);
+}
+
+// Alice's transfers above 100 tokens need charlie's confirmation within an hour, and she has
+// queued one of 300 tokens to bob.
+fn queue_transfer(contract: &mut Contract, context: &mut VMContextBuilder) {
+    contract.set_co_signer(
+        accounts(0),
+        Some(CoSignerConfig {
+            co_signer_id: accounts(2),
+            threshold: U128(100),
+            window_sec: 3_600,
+        }),
+    );
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.ft_transfer(accounts(1), U128(300), None, None, None, None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(3_601 * SECOND)
+        .build());
+}
+
+#[test]
+fn test_emissions_pay_the_keeper_bounty() {
+    let (mut contract, mut context) = setup(100);
+    start_mining(&mut contract);
+    emit(
+        &mut contract,
+        &mut context,
+        accounts(2),
+        60,
+        PromiseResult::Successful(b"\"0\"".to_vec()),
+    );
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(10));
+    assert_eq!(contract.get_keeper_pool(), U128(90));
+}
+
+#[test]
+fn test_bounties_are_capped_by_the_pool() {
+    let (mut contract, mut context) = setup(12);
+    start_mining(&mut contract);
+    for at_sec in [60, 120, 180] {
+        emit(
+            &mut contract,
+            &mut context,
+            accounts(2),
+            at_sec,
+            PromiseResult::Successful(b"\"0\"".to_vec()),
+        );
+    }
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(12));
+    assert_eq!(contract.get_keeper_pool(), U128(0));
+    // Emissions keep going once the pool is empty.
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(300));
+}
+
+#[test]
+fn test_empty_pool_pays_no_bounty() {
+    let (mut contract, mut context) = setup(0);
+    start_mining(&mut contract);
+    emit(
+        &mut contract,
+        &mut context,
+        accounts(2),
+        60,
+        PromiseResult::Successful(b"\"0\"".to_vec()),
+    );
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(0));
+}
+
+#[test]
+fn test_failed_emissions_pay_no_bounty() {
+    let (mut contract, mut context) = setup(100);
+    start_mining(&mut contract);
+    emit(
+        &mut contract,
+        &mut context,
+        accounts(2),
+        60,
+        PromiseResult::Failed,
+    );
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(0));
+    assert_eq!(contract.get_keeper_pool(), U128(100));
+}
+
+#[test]
+fn test_unregistered_keepers_earn_no_bounty() {
+    let (mut contract, mut context) = setup(100);
+    start_mining(&mut contract);
+    emit(
+        &mut contract,
+        &mut context,
+        "keeper.near".parse().unwrap(),
+        60,
+        PromiseResult::Successful(b"\"0\"".to_vec()),
+    );
+    assert_eq!(contract.get_keeper_pool(), U128(100));
+}
+
+#[test]
+#[should_panic(expected = "No epoch is due for this program")]
+fn test_epochs_are_claimed_once() {
+    let (mut contract, mut context) = setup(100);
+    start_mining(&mut contract);
+    emit(
+        &mut contract,
+        &mut context,
+        accounts(2),
+        60,
+        PromiseResult::Successful(b"\"0\"".to_vec()),
+    );
+    testing_env!(context.predecessor_account_id(accounts(4)).build());
+    contract.execute_mining_emission(0);
+}
+
+#[test]
+fn test_expired_transfers_pay_the_cleanup_bounty() {
+    let (mut contract, mut context) = setup(100);
+    queue_transfer(&mut contract, &mut context);
+    testing_env!(context.predecessor_account_id(accounts(4)).build());
+    contract.cancel_pending_transfer(0);
+    assert_eq!(Contract::ft_balance_of(accounts(4)), U128(5));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(900));
+    assert_eq!(contract.get_keeper_pool(), U128(95));
+}
+
+#[test]
+fn test_senders_earn_no_cleanup_bounty() {
+    let (mut contract, mut context) = setup(100);
+    queue_transfer(&mut contract, &mut context);
+    contract.cancel_pending_transfer(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(900));
+    assert_eq!(contract.get_keeper_pool(), U128(100));
+}
+
+#[test]
+#[should_panic(expected = "The pending transfer doesn't exist")]
+fn test_expired_transfers_are_cleaned_up_once() {
+    let (mut contract, mut context) = setup(100);
+    queue_transfer(&mut contract, &mut context);
+    testing_env!(context.predecessor_account_id(accounts(4)).build());
+    contract.cancel_pending_transfer(0);
+    contract.cancel_pending_transfer(0);
+}
@@ -0,0 +1,31 @@
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+#[test]
+fn test_compliance_labels_are_only_visible_to_the_owner() {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_compliance_label(accounts(1), Some("kyc-tier-2".to_string()));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.set_self_label(Some("market maker".to_string()));
+    let labels = contract.get_account_labels(accounts(1));
+    assert_eq!(labels.self_label.as_deref(), Some("market maker"));
+    assert!(labels.compliance_label.is_none());
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    let labels = contract.get_account_labels(accounts(1));
+    assert_eq!(labels.compliance_label.as_deref(), Some("kyc-tier-2"));
+}
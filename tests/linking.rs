@@ -0,0 +1,218 @@
+use near_crypto::{KeyType, SecretKey, Signature};
+use near_ft::ft_core::{CoSignerConfig, FungibleTokenCore};
+use near_ft::grants::GrantTransferPolicy;
+use near_ft::guard::PauseFlags;
+use near_ft::locks::LockId;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, CurveType, NearToken, PublicKey};
+
+fn old_key() -> SecretKey {
+    SecretKey::from_seed(KeyType::ED25519, "bob")
+}
+
+fn sign(old_account_id: &AccountId, new_account_id: &AccountId, nonce: u64) -> Base64VecU8 {
+    let payload = (old_account_id, new_account_id, nonce, None::<u64>);
+    let message = near_sdk::borsh::to_vec(&(
+        "near-ft",
+        env::current_account_id(),
+        "link_and_merge",
+        &payload,
+    ))
+    .unwrap();
+    let Signature::ED25519(signature) = old_key().sign(&message) else {
+        unreachable!()
+    };
+    Base64VecU8(signature.to_bytes().to_vec())
+}
+
+// Bob holds 300 tokens, a plain lock of 50 (lock 0), a lock of 20 with receipts (lock 1), a
+// transferable grant (grant 0) and a non-transferable one (grant 1). Bob's relay key signs the
+// consent to merge into charlie, and the context is left as charlie.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 370)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .build();
+    contract.create_vesting_grant(
+        accounts(1),
+        U128(100),
+        None,
+        0,
+        3_600,
+        GrantTransferPolicy::Transferable,
+    );
+    contract.create_vesting_grant(
+        accounts(1),
+        U128(100),
+        None,
+        0,
+        3_600,
+        GrantTransferPolicy::NonTransferable,
+    );
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    let public_key = PublicKey::from_parts(
+        CurveType::ED25519,
+        old_key().public_key().key_data().to_vec(),
+    )
+    .unwrap();
+    contract.set_relay_key(Some(public_key));
+    testing_env!(context
+        .attached_deposit(NearToken::from_millinear(10))
+        .build());
+    contract.lock_tokens(U128(50), 60, None);
+    contract.lock_tokens(U128(20), 60, Some(true));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+fn merge(contract: &mut Contract, lock_ids: Vec<LockId>) -> U128 {
+    contract.link_and_merge(
+        accounts(1),
+        0,
+        None,
+        sign(&accounts(1), &accounts(2), 0),
+        Some(lock_ids),
+    )
+}
+
+#[test]
+fn test_merge_moves_the_balance_and_the_positions() {
+    let (mut contract, _) = setup();
+    assert_eq!(merge(&mut contract, vec![0, 1]), U128(300));
+
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(300));
+    assert_eq!(contract.get_lock(0).unwrap().owner_id, accounts(2));
+    assert_eq!(contract.get_lock(1).unwrap().owner_id, accounts(2));
+    assert_eq!(contract.receipt_balance_of(1, accounts(1)), U128(0));
+    assert_eq!(contract.receipt_balance_of(1, accounts(2)), U128(20));
+    // The non-transferable grant stays with bob.
+    let grants = contract.nft_tokens_for_owner(accounts(2), None, None);
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].token_id, "0");
+    assert_eq!(grants[0].grant.holder_id, accounts(2));
+    let grants = contract.nft_tokens_for_owner(accounts(1), None, None);
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].token_id, "1");
+    assert_eq!(contract.get_relay_nonce(accounts(1)), 1);
+}
+
+#[test]
+fn test_the_new_account_pays_the_transfer_fee() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_transfer_fee(100, accounts(3));
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    assert_eq!(merge(&mut contract, vec![]), U128(300));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(297));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(3));
+}
+
+#[test]
+#[should_panic(expected = "Transfers above the co-signing threshold must be made with ft_transfer")]
+fn test_balances_above_the_co_signing_threshold_arent_merged() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.set_co_signer(
+        accounts(1),
+        Some(CoSignerConfig {
+            co_signer_id: accounts(0),
+            threshold: U128(100),
+            window_sec: 3_600,
+        }),
+    );
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    merge(&mut contract, vec![]);
+}
+
+#[test]
+fn test_merged_locks_are_unlocked_by_the_new_account() {
+    let (mut contract, mut context) = setup();
+    merge(&mut contract, vec![0]);
+    testing_env!(context.block_timestamp(60_000_000_000).build());
+    assert_eq!(contract.unlock_tokens(0), U128(50));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(350));
+}
+
+#[test]
+#[should_panic(expected = "The merged account has no part in the lock")]
+fn test_only_the_locks_of_the_old_account_are_merged() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_millinear(10))
+        .build());
+    contract.lock_tokens(U128(10), 60, None);
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    merge(&mut contract, vec![2]);
+}
+
+#[test]
+#[should_panic(expected = "Invalid signature")]
+fn test_the_consent_names_the_new_account() {
+    let (mut contract, _) = setup();
+    contract.link_and_merge(
+        accounts(1),
+        0,
+        None,
+        sign(&accounts(1), &accounts(3), 0),
+        None,
+    );
+}
+
+#[test]
+#[should_panic(expected = "The nonce was already used")]
+fn test_the_consent_cant_be_replayed() {
+    let (mut contract, _) = setup();
+    merge(&mut contract, vec![]);
+    merge(&mut contract, vec![]);
+}
+
+#[test]
+#[should_panic(expected = "The account bob is frozen")]
+fn test_frozen_accounts_cant_be_merged() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.freeze_account(accounts(1), 1, 3_600);
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    merge(&mut contract, vec![]);
+}
+
+#[test]
+#[should_panic(expected = "Transfers are paused")]
+fn test_merges_are_paused_with_the_transfers() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_pause_flags(PauseFlags {
+        transfers: true,
+        ..Default::default()
+    });
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    merge(&mut contract, vec![]);
+}
+
+#[test]
+#[should_panic(expected = "Can't merge an account into itself")]
+fn test_accounts_cant_merge_into_themselves() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.link_and_merge(
+        accounts(1),
+        0,
+        None,
+        sign(&accounts(1), &accounts(1), 0),
+        None,
+    );
+}
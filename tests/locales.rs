@@ -0,0 +1,22 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::locales::Locale;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+#[test]
+#[should_panic(expected = "E_NOT_REGISTERED: La cuenta bob no está registrada")]
+fn test_failures_use_the_callers_locale() {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    contract.set_locale(Some(Locale::Es));
+    assert!(contract.get_locale(accounts(0)) == Locale::Es);
+
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_transfer(accounts(1), U128(10), None, None, None, None);
+}
@@ -0,0 +1,192 @@
+use near_ft::ft_core::{CoSignerConfig, FungibleTokenCore};
+use near_ft::guard::PauseFlags;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+const SECOND: u64 = 1_000_000_000;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Bob locks 100 tokens for a minute (lock 0), and 100 more with receipts (lock 1), paying for
+// their storage. The context is left as bob.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(1), 300)
+        .balance(accounts(2), 0)
+        .build();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_millinear(10))
+        .build());
+    assert_eq!(contract.lock_tokens(U128(100), 60, None), 0);
+    assert_eq!(contract.lock_tokens(U128(100), 60, Some(true)), 1);
+    (contract, context)
+}
+
+#[test]
+fn test_locked_tokens_are_escrowed() {
+    let (contract, _) = setup();
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(200));
+    assert_eq!(contract.receipt_balance_of(0, accounts(1)), U128(0));
+    assert_eq!(contract.receipt_balance_of(1, accounts(1)), U128(100));
+    assert!(get_logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"receipt_transfer\"")));
+}
+
+#[test]
+fn test_owners_unlock_ended_locks() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(60 * SECOND).build());
+    assert_eq!(contract.unlock_tokens(0), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(200));
+    assert_eq!(contract.get_lock(0).unwrap().released, U128(100));
+}
+
+#[test]
+fn test_receipts_are_redeemed_by_their_holders() {
+    let (mut contract, mut context) = setup();
+    contract.receipt_transfer(1, accounts(2), U128(30));
+    assert_eq!(contract.receipt_balance_of(1, accounts(1)), U128(70));
+    assert_eq!(contract.receipt_balance_of(1, accounts(2)), U128(30));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(60 * SECOND)
+        .build());
+    contract.redeem_receipt(1, U128(30));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(30));
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.redeem_receipt(1, U128(70));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(170));
+    assert_eq!(contract.get_lock(1).unwrap().released, U128(100));
+}
+
+#[test]
+#[should_panic(expected = "The tokens are still locked")]
+fn test_locks_end_at_the_unlock_time() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(60 * SECOND - 1).build());
+    contract.unlock_tokens(0);
+}
+
+#[test]
+#[should_panic(expected = "The tokens are already unlocked")]
+fn test_locks_are_unlocked_once() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(60 * SECOND).build());
+    contract.unlock_tokens(0);
+    contract.unlock_tokens(0);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can unlock the tokens")]
+fn test_only_the_owner_unlocks() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(60 * SECOND)
+        .build());
+    contract.unlock_tokens(0);
+}
+
+#[test]
+#[should_panic(expected = "This lock is represented by receipt tokens, redeem them instead")]
+fn test_locks_with_receipts_are_redeemed() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(60 * SECOND).build());
+    contract.unlock_tokens(1);
+}
+
+#[test]
+#[should_panic(expected = "The account doesn't have enough receipt tokens")]
+fn test_redemptions_are_capped_by_the_receipts() {
+    let (mut contract, mut context) = setup();
+    contract.receipt_transfer(1, accounts(2), U128(30));
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(60 * SECOND)
+        .build());
+    contract.redeem_receipt(1, U128(31));
+}
+
+#[test]
+#[should_panic(expected = "The account danny is not registered")]
+fn test_receipts_go_to_registered_accounts() {
+    let (mut contract, _) = setup();
+    contract.receipt_transfer(1, accounts(3), U128(30));
+}
+
+#[test]
+#[should_panic(expected = "The lock duration should be positive")]
+fn test_locks_last_some_time() {
+    let (mut contract, _) = setup();
+    contract.lock_tokens(U128(10), 0, Some(true));
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_the_owner_pays_for_the_storage_of_the_lock() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.lock_tokens(U128(10), 60, None);
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_the_sender_pays_for_the_storage_of_the_receipts() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.receipt_transfer(1, accounts(2), U128(30));
+}
+
+#[test]
+#[should_panic(expected = "Transfers are paused")]
+fn test_receipt_transfers_are_paused_with_the_transfers() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_pause_flags(PauseFlags {
+        transfers: true,
+        ..Default::default()
+    });
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_millinear(10))
+        .build());
+    contract.receipt_transfer(1, accounts(2), U128(30));
+}
+
+#[test]
+#[should_panic(expected = "Transfers above the co-signing threshold must be made with ft_transfer")]
+fn test_receipt_transfers_are_held_to_the_co_signing_threshold() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_co_signer(
+        accounts(1),
+        Some(CoSignerConfig {
+            co_signer_id: accounts(0),
+            threshold: U128(10),
+            window_sec: 3_600,
+        }),
+    );
+    testing_env!(context
+        .attached_deposit(NearToken::from_millinear(10))
+        .build());
+    contract.receipt_transfer(1, accounts(2), U128(30));
+}
@@ -0,0 +1,98 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::memos::{MemoConfig, MemoFormat};
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+fn setup(config: MemoConfig) -> Contract {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_memo_config(config);
+    contract
+}
+
+fn transfer_with_memo(contract: &mut Contract, memo: &str) {
+    contract.ft_transfer(
+        accounts(1),
+        U128(100),
+        Some(memo.to_string()),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_memos_within_the_limits_are_accepted() {
+    let mut contract = setup(MemoConfig {
+        max_len: Some(32),
+        format: MemoFormat::Json,
+    });
+    transfer_with_memo(&mut contract, r#"{"order":42}"#);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert_eq!(contract.get_memo_config().max_len, Some(32));
+}
+
+#[test]
+#[should_panic(expected = "The memo can't be longer than 8 bytes")]
+fn test_long_memos_are_rejected() {
+    let mut contract = setup(MemoConfig {
+        max_len: Some(8),
+        format: MemoFormat::Any,
+    });
+    transfer_with_memo(&mut contract, "Invoice 123456");
+}
+
+#[test]
+#[should_panic(expected = "The memo can't contain control characters")]
+fn test_printable_memos_reject_line_breaks() {
+    let mut contract = setup(MemoConfig {
+        max_len: None,
+        format: MemoFormat::Printable,
+    });
+    transfer_with_memo(&mut contract, "Invoice\n123");
+}
+
+#[test]
+#[should_panic(expected = "The memo should be a JSON object")]
+fn test_json_memos_reject_plain_text() {
+    let mut contract = setup(MemoConfig {
+        max_len: None,
+        format: MemoFormat::Json,
+    });
+    let _ = contract.ft_transfer_call(
+        accounts(1),
+        U128(100),
+        Some("Invoice 123".to_string()),
+        String::new(),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+#[should_panic(expected = "The memo can't be longer than 8 bytes")]
+fn test_travel_rule_transfers_check_the_memo() {
+    let mut contract = setup(MemoConfig {
+        max_len: Some(8),
+        format: MemoFormat::Any,
+    });
+    contract.ft_transfer_with_travel_rule(
+        accounts(1),
+        U128(100),
+        Some("Invoice 123456".to_string()),
+        "ref".to_string(),
+    );
+}
@@ -0,0 +1,106 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::merkle::MAX_SYNCED_LEAVES;
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build()
+}
+
+fn sync(contract: &mut Contract, context: &mut VMContextBuilder, account_ids: Vec<AccountId>) {
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.sync_balance_tree(account_ids);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+}
+
+#[test]
+fn test_root_only_changes_when_synced() {
+    let (mut contract, mut context) = setup();
+    let empty_root = contract.state_root();
+
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    assert_eq!(contract.state_root(), empty_root);
+
+    sync(&mut contract, &mut context, vec![accounts(0), accounts(1)]);
+    let synced_root = contract.state_root();
+    assert_ne!(synced_root, empty_root);
+    assert_eq!(contract.next_balance_leaf_index, 2);
+
+    // Syncing unchanged balances again keeps the root and the leaves.
+    sync(&mut contract, &mut context, vec![accounts(1), accounts(0)]);
+    assert_eq!(contract.state_root(), synced_root);
+    assert_eq!(contract.next_balance_leaf_index, 2);
+}
+
+#[test]
+fn test_proofs_verify_against_the_root() {
+    let (mut contract, mut context) = setup();
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    sync(&mut contract, &mut context, vec![accounts(0), accounts(1)]);
+
+    let proof = contract.prove_balance(accounts(1));
+    assert_eq!(proof.balance, U128(100));
+    assert_eq!(proof.root, contract.state_root());
+    assert!(contract.verify_balance_proof(proof));
+
+    let mut forged = contract.prove_balance(accounts(0));
+    forged.balance = U128(2_000);
+    assert!(!contract.verify_balance_proof(forged));
+}
+
+#[test]
+fn test_unregistered_accounts_get_an_empty_leaf() {
+    let (mut contract, mut context) = setup();
+    sync(&mut contract, &mut context, vec![accounts(0)]);
+    let owner_only_root = contract.state_root();
+    sync(&mut contract, &mut context, vec![accounts(1)]);
+    assert_ne!(contract.state_root(), owner_only_root);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    assert!(contract.storage_unregister(None));
+    sync(&mut contract, &mut context, vec![accounts(1)]);
+    assert_eq!(contract.state_root(), owner_only_root);
+}
+
+#[test]
+#[should_panic(expected = "The balance changed since the leaf was synced")]
+fn test_stale_leaves_arent_proven() {
+    let (mut contract, mut context) = setup();
+    sync(&mut contract, &mut context, vec![accounts(0)]);
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    contract.prove_balance(accounts(0));
+}
+
+#[test]
+#[should_panic(expected = "The account has no leaf in the balance tree")]
+fn test_unsynced_accounts_arent_proven() {
+    let (contract, _) = setup();
+    contract.prove_balance(accounts(1));
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_sync_pays_for_the_new_nodes() {
+    let (mut contract, _) = setup();
+    contract.sync_balance_tree(vec![accounts(0)]);
+}
+
+#[test]
+#[should_panic(expected = "Too many accounts to sync at once")]
+fn test_sync_is_capped() {
+    let (mut contract, mut context) = setup();
+    sync(
+        &mut contract,
+        &mut context,
+        vec![accounts(0); MAX_SYNCED_LEAVES + 1],
+    );
+}
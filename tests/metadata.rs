@@ -0,0 +1,77 @@
+use near_ft::metadata::FungibleTokenMetadataProvider;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::testing_env;
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build()
+}
+
+fn updated_fields_logs() -> Vec<String> {
+    get_logs()
+        .into_iter()
+        .filter(|log| log.contains("\"event\":\"metadata_updated\""))
+        .collect()
+}
+
+#[test]
+fn test_metadata_setters() {
+    let (mut contract, _) = setup();
+
+    contract.set_icon(None);
+    contract.set_reference(
+        Some("https://example.com/token.json".to_string()),
+        Some(Base64VecU8(vec![7; 32])),
+    );
+    contract.set_symbol("NEW".to_string());
+
+    let metadata = Contract::ft_metadata();
+    assert_eq!(metadata.metadata.icon, None);
+    assert_eq!(
+        metadata.metadata.reference.as_deref(),
+        Some("https://example.com/token.json")
+    );
+    assert_eq!(
+        metadata.metadata.reference_hash,
+        Some(Base64VecU8(vec![7; 32]))
+    );
+    assert_eq!(metadata.metadata.symbol, "NEW");
+    assert_eq!(metadata.metadata.decimals, 24);
+    // Symbol changes go through the rename quarantine.
+    assert_eq!(metadata.pending_change.unwrap().previous_symbol, "TEST");
+
+    let logs = updated_fields_logs();
+    assert_eq!(logs.len(), 3);
+    assert!(logs[0].contains("\"updated_fields\":[\"icon\"]"));
+    assert!(logs[1].contains("\"updated_fields\":[\"reference\",\"reference_hash\"]"));
+    assert!(logs[2].contains("\"updated_fields\":[\"symbol\"]"));
+}
+
+#[test]
+fn test_unchanged_name_isnt_logged() {
+    let (mut contract, _) = setup();
+    contract.set_name("Test Token".to_string());
+    assert!(updated_fields_logs().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "The reference hash should be 32 bytes long")]
+fn test_reference_hash_length_is_checked() {
+    let (mut contract, _) = setup();
+    contract.set_reference(
+        Some("https://example.com/token.json".to_string()),
+        Some(Base64VecU8(vec![7; 16])),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Only the owner and the metadata admins can call this method")]
+fn test_metadata_is_set_by_the_owner() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.set_icon(None);
+}
@@ -0,0 +1,62 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+// Alice and bob are registered, and alice holds 1,000 tokens.
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build()
+}
+
+fn metric(contract: &Contract, key: &str) -> u128 {
+    contract.metrics()[key].0
+}
+
+#[test]
+fn test_registrations_are_counted() {
+    let (mut contract, mut context) = setup();
+    let active_accounts = metric(&contract, "active_accounts");
+
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(2)), None);
+    assert_eq!(metric(&contract, "active_accounts"), active_accounts + 1);
+    // Registering an account twice doesn't count it twice.
+    contract.storage_deposit(Some(accounts(2)), None);
+    assert_eq!(metric(&contract, "active_accounts"), active_accounts + 1);
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    assert!(contract.storage_unregister(None));
+    assert_eq!(metric(&contract, "active_accounts"), active_accounts);
+    // Unregistered accounts can't be unregistered again.
+    assert!(!contract.storage_unregister(None));
+    assert_eq!(metric(&contract, "active_accounts"), active_accounts);
+}
+
+#[test]
+fn test_transfers_are_counted() {
+    let (mut contract, mut context) = setup();
+    let transfers = metric(&contract, "transfers_total");
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(0), U128(50), None, None, None, None);
+    assert_eq!(metric(&contract, "transfers_total"), transfers + 2);
+    assert_eq!(metric(&contract, "total_supply"), 1_000);
+
+    // Bob can leave once his tokens are gone.
+    contract.ft_transfer(accounts(0), U128(50), None, None, None, None);
+    let active_accounts = metric(&contract, "active_accounts");
+    assert!(contract.storage_unregister(None));
+    assert_eq!(metric(&contract, "active_accounts"), active_accounts - 1);
+    assert_eq!(metric(&contract, "transfers_total"), transfers + 3);
+}
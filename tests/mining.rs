@@ -0,0 +1,234 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken, PromiseResult, RuntimeFeesConfig};
+
+const SECOND: u64 = 1_000_000_000;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Alice funds a program emitting 100 tokens a minute to bob's pool for 5 minutes. Two epochs
+// are due and charlie executes the emission.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build();
+    contract.create_mining_program(accounts(1), None, U128(100), 60, 5, None);
+    contract.fund_mining_program(0, U128(500));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(120 * SECOND)
+        .build());
+    contract.execute_mining_emission(0);
+    (contract, context)
+}
+
+fn resolve_with(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    result: PromiseResult,
+) -> U128 {
+    testing_env!(
+        context.predecessor_account_id(contract_id()).build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![result]
+    );
+    contract.on_mining_emission_resolved(0, 2, U128(200), accounts(2))
+}
+
+#[test]
+fn test_emission_pays_the_due_epochs() {
+    let (mut contract, mut context) = setup();
+    let program = contract.get_mining_program(0).unwrap();
+    assert!(program.program.in_flight);
+    assert_eq!(program.program.funded, U128(300));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(200));
+
+    let used = resolve_with(
+        &mut contract,
+        &mut context,
+        PromiseResult::Successful(b"\"0\"".to_vec()),
+    );
+    assert_eq!(used, U128(200));
+    let program = contract.get_mining_program(0).unwrap();
+    assert!(!program.program.in_flight);
+    assert_eq!(program.program.epochs_paid, 2);
+    assert_eq!(program.program.total_emitted, U128(200));
+    assert_eq!(program.due_epochs, 0);
+}
+
+#[test]
+fn test_unused_tokens_go_back_to_the_escrow() {
+    let (mut contract, mut context) = setup();
+    let used = resolve_with(
+        &mut contract,
+        &mut context,
+        PromiseResult::Successful(b"\"30\"".to_vec()),
+    );
+    assert_eq!(used, U128(170));
+    let program = contract.get_mining_program(0).unwrap().program;
+    assert_eq!(program.funded, U128(330));
+    assert_eq!(program.total_refunded, U128(30));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(170));
+}
+
+#[test]
+fn test_failed_emissions_are_retried() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(
+        resolve_with(&mut contract, &mut context, PromiseResult::Failed),
+        U128(0)
+    );
+    let program = contract.get_mining_program(0).unwrap();
+    assert!(!program.program.in_flight);
+    assert_eq!(program.program.failed_attempts, 1);
+    assert_eq!(program.program.epochs_paid, 0);
+    assert_eq!(program.program.funded, U128(500));
+    assert_eq!(program.due_epochs, 2);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+
+    // The next call emits the same epochs again.
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.execute_mining_emission(0);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(200));
+}
+
+#[test]
+#[should_panic(expected = "An emission is already in progress")]
+fn test_emissions_dont_overlap() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(180 * SECOND).build());
+    contract.execute_mining_emission(0);
+}
+
+#[test]
+#[should_panic(expected = "An emission is in progress, try again later")]
+fn test_programs_arent_cancelled_during_an_emission() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.cancel_mining_program(0);
+}
+
+#[test]
+fn test_cancellation_returns_the_escrow_to_the_owner() {
+    let (mut contract, mut context) = setup();
+    resolve_with(
+        &mut contract,
+        &mut context,
+        PromiseResult::Successful(b"\"0\"".to_vec()),
+    );
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.cancel_mining_program(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(800));
+    assert!(contract.get_mining_program(0).unwrap().program.cancelled);
+}
+
+#[test]
+#[should_panic(expected = "No epoch is due for this program")]
+fn test_emissions_wait_for_the_epochs() {
+    let (mut contract, mut context) = setup();
+    resolve_with(
+        &mut contract,
+        &mut context,
+        PromiseResult::Successful(b"\"0\"".to_vec()),
+    );
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.execute_mining_emission(0);
+}
+
+#[test]
+#[should_panic(expected = "The mining program doesn't have enough funds for the due epochs")]
+fn test_emissions_need_the_funds() {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    contract.create_mining_program(accounts(1), None, U128(100), 60, 5, None);
+    contract.fund_mining_program(0, U128(100));
+    testing_env!(context.block_timestamp(120 * SECOND).build());
+    contract.execute_mining_emission(0);
+}
+
+#[test]
+#[should_panic(expected = "The program should run for at least one epoch")]
+fn test_programs_run_for_an_epoch() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.create_mining_program(accounts(1), None, U128(100), 60, 0, None);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can call this method")]
+fn test_only_the_owner_creates_programs() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.create_mining_program(accounts(1), None, U128(100), 60, 5, None);
+}
+
+#[test]
+fn test_frozen_pool_doesnt_block_the_resolution() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.freeze_account(accounts(1), 7, 3_600);
+
+    let used = resolve_with(
+        &mut contract,
+        &mut context,
+        PromiseResult::Successful(b"\"30\"".to_vec()),
+    );
+    assert_eq!(used, U128(170));
+    let program = contract.get_mining_program(0).unwrap().program;
+    assert!(!program.in_flight);
+    assert_eq!(program.funded, U128(330));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(170));
+
+    // The program can still be cancelled.
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.cancel_mining_program(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(830));
+}
+
+#[test]
+#[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+fn test_programs_are_cancelled_with_one_yocto() {
+    let (mut contract, mut context) = setup();
+    resolve_with(
+        &mut contract,
+        &mut context,
+        PromiseResult::Successful(b"\"0\"".to_vec()),
+    );
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.cancel_mining_program(0);
+}
@@ -0,0 +1,60 @@
+use near_ft::mint_limits::MintLimits;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+
+const DAY: u64 = 86_400 * 1_000_000_000;
+
+// A token capped at 2,000, whose minters can mint 300 per transaction and 500 per day.
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .with(|contract| {
+            contract.max_supply = Some(2_000);
+            contract.set_mint_limits(Some(MintLimits {
+                per_transaction: Some(U128(300)),
+                per_day: Some(U128(500)),
+            }));
+        })
+        .build()
+}
+
+#[test]
+fn test_daily_limit_resets_every_day() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(contract.remaining_mintable(None), U128(1_000));
+    contract.ft_mint(accounts(0), U128(300), None);
+    contract.ft_mint(accounts(0), U128(200), None);
+    assert_eq!(contract.remaining_mintable(Some(accounts(0))), U128(0));
+
+    testing_env!(context.block_timestamp(DAY).build());
+    assert_eq!(contract.remaining_mintable(Some(accounts(0))), U128(300));
+    contract.ft_mint(accounts(0), U128(300), None);
+    assert_eq!(contract.get_max_supply(), Some(U128(2_000)));
+    assert_eq!(contract.remaining_mintable(None), U128(200));
+}
+
+#[test]
+#[should_panic(expected = "The mint exceeds the daily limit of the minter")]
+fn test_mint_over_the_daily_limit() {
+    let (mut contract, _) = setup();
+    contract.ft_mint(accounts(0), U128(300), None);
+    contract.ft_mint(accounts(0), U128(201), None);
+}
+
+#[test]
+#[should_panic(expected = "The mint exceeds the per-transaction limit")]
+fn test_mint_over_the_per_transaction_limit() {
+    let (mut contract, _) = setup();
+    contract.ft_mint(accounts(0), U128(301), None);
+}
+
+#[test]
+#[should_panic(expected = "The mint exceeds the max supply")]
+fn test_mint_over_the_max_supply() {
+    let (mut contract, _) = setup();
+    contract.set_mint_limits(None);
+    contract.ft_mint(accounts(0), U128(1_001), None);
+}
@@ -0,0 +1,134 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::guard::PauseFlags;
+use near_ft::mint_requests::MintRequestStatus;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+
+// Bob proposes mints, which charlie and danny both have to approve. Bob has proposed to mint
+// 500 tokens to eugene, and the context is left as charlie.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(4), 0)
+        .build();
+    contract.set_mint_proposer(accounts(1), true);
+    contract.set_mint_approver(accounts(2), true);
+    contract.set_mint_approver(accounts(3), true);
+    contract.set_mint_approval_threshold(2);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    let request_id = contract.propose_mint(accounts(4), U128(500), "Payroll".to_string());
+    assert_eq!(request_id, 0);
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    (contract, context)
+}
+
+#[test]
+fn test_requests_are_executed_at_the_threshold() {
+    let (mut contract, mut context) = setup();
+    assert!(contract.approve_mint(0) == MintRequestStatus::Pending);
+    assert_eq!(Contract::ft_balance_of(accounts(4)), U128(0));
+
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    assert!(contract.approve_mint(0) == MintRequestStatus::Executed);
+    assert_eq!(Contract::ft_balance_of(accounts(4)), U128(500));
+    assert_eq!(contract.ft_total_supply(), U128(1_500));
+    let request = contract.get_mint_request(0).unwrap();
+    assert_eq!(request.approvals, vec![accounts(2), accounts(3)]);
+    assert!(request.resolved_at.is_some());
+}
+
+#[test]
+fn test_revoked_approvers_dont_count() {
+    let (mut contract, mut context) = setup();
+    contract.approve_mint(0);
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_mint_approver(accounts(2), false);
+    contract.set_mint_approver(accounts(5), true);
+
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    assert!(contract.approve_mint(0) == MintRequestStatus::Pending);
+    testing_env!(context.predecessor_account_id(accounts(5)).build());
+    assert!(contract.approve_mint(0) == MintRequestStatus::Executed);
+}
+
+#[test]
+fn test_a_single_rejection_closes_the_request() {
+    let (mut contract, _) = setup();
+    contract.reject_mint(0, Some("No invoice".to_string()));
+    let request = contract.get_mint_request(0).unwrap();
+    assert!(request.status == MintRequestStatus::Rejected);
+    assert_eq!(
+        request.rejection,
+        Some((accounts(2), Some("No invoice".to_string())))
+    );
+    assert_eq!(contract.get_mint_requests(None, None).len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "The mint request is not pending")]
+fn test_rejected_requests_cant_be_approved() {
+    let (mut contract, mut context) = setup();
+    contract.reject_mint(0, None);
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    contract.approve_mint(0);
+}
+
+#[test]
+#[should_panic(expected = "Mints and burns are paused")]
+fn test_requests_arent_approved_while_mints_are_paused() {
+    let (mut contract, mut context) = setup();
+    contract.approve_mint(0);
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_pause_flags(PauseFlags {
+        transfers: false,
+        mints: true,
+        storage: false,
+    });
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    contract.approve_mint(0);
+}
+
+#[test]
+#[should_panic(expected = "The mint request is already approved by this approver")]
+fn test_approvers_approve_once() {
+    let (mut contract, _) = setup();
+    contract.approve_mint(0);
+    contract.approve_mint(0);
+}
+
+#[test]
+#[should_panic(expected = "The proposer can't approve or reject its own mint request")]
+fn test_proposers_dont_approve_their_own_requests() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_mint_approver(accounts(1), true);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.approve_mint(0);
+}
+
+#[test]
+#[should_panic(expected = "Only a mint approver can call this method")]
+fn test_only_approvers_approve() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(4)).build());
+    contract.approve_mint(0);
+}
+
+#[test]
+#[should_panic(expected = "Only a mint proposer can call this method")]
+fn test_only_proposers_propose() {
+    let (mut contract, _) = setup();
+    contract.propose_mint(accounts(4), U128(500), "Payroll".to_string());
+}
+
+#[test]
+#[should_panic(expected = "The approval threshold is higher than the number of approvers")]
+fn test_the_threshold_is_reachable() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_mint_approval_threshold(3);
+}
@@ -0,0 +1,185 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::multicall::PromiseAction;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken, PromiseResult, RuntimeFeesConfig};
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+fn action(contract_id: AccountId, deposit: u128, gas_tgas: u64) -> PromiseAction {
+    PromiseAction {
+        contract_id,
+        method_name: "deposit".to_string(),
+        args: Base64VecU8(b"{}".to_vec()),
+        deposit: NearToken::from_yoctonear(deposit),
+        gas_tgas,
+    }
+}
+
+// Bob is a whitelisted pool, and charlie a whitelisted contract that isn't registered.
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .with(|contract| {
+            contract.set_multicall_target(accounts(1), true);
+            contract.set_multicall_target(accounts(2), true);
+        })
+        .build()
+}
+
+fn resolve_with(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    results: Vec<PromiseResult>,
+) -> U128 {
+    testing_env!(
+        context
+            .predecessor_account_id(contract_id())
+            .attached_deposit(NearToken::from_yoctonear(0))
+            .build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        results
+    );
+    contract.ft_resolve_transfer_then(accounts(0), accounts(1), U128(100))
+}
+
+#[test]
+fn test_transfer_then_calls_the_actions() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(11))
+        .build());
+    contract.ft_transfer_then(
+        accounts(1),
+        U128(100),
+        None,
+        vec![action(accounts(1), 0, 20), action(accounts(2), 10, 20)],
+    );
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    let calls: Vec<AccountId> = get_created_receipts()
+        .into_iter()
+        .filter(|receipt| {
+            receipt
+                .actions
+                .iter()
+                .any(|action| matches!(action, MockAction::FunctionCallWeight { .. }))
+        })
+        .map(|receipt| receipt.receiver_id)
+        .collect();
+    assert_eq!(calls, vec![accounts(1), accounts(2), contract_id()]);
+
+    let kept = resolve_with(
+        &mut contract,
+        &mut context,
+        vec![
+            PromiseResult::Successful(vec![]),
+            PromiseResult::Successful(vec![]),
+        ],
+    );
+    assert_eq!(kept, U128(100));
+}
+
+#[test]
+fn test_failed_actions_revert_the_transfer() {
+    let (mut contract, mut context) = setup();
+    contract.ft_transfer_then(
+        accounts(1),
+        U128(100),
+        None,
+        vec![action(accounts(1), 0, 20)],
+    );
+
+    let kept = resolve_with(&mut contract, &mut context, vec![PromiseResult::Failed]);
+    assert_eq!(kept, U128(0));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_000));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+}
+
+#[test]
+fn test_the_refund_is_capped_by_the_receiver_balance() {
+    let (mut contract, mut context) = setup();
+    contract.ft_transfer_then(
+        accounts(1),
+        U128(100),
+        None,
+        vec![action(accounts(1), 0, 20)],
+    );
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(0), U128(40), None, None, None, None);
+
+    let kept = resolve_with(&mut contract, &mut context, vec![PromiseResult::Failed]);
+    assert_eq!(kept, U128(40));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_000));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+}
+
+#[test]
+#[should_panic(expected = "The receiver isn't whitelisted")]
+fn test_the_receiver_is_whitelisted() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_then(
+        accounts(3),
+        U128(100),
+        None,
+        vec![action(accounts(1), 0, 20)],
+    );
+}
+
+#[test]
+#[should_panic(expected = "The called contract isn't whitelisted")]
+fn test_the_called_contracts_are_whitelisted() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_then(
+        accounts(1),
+        U128(100),
+        None,
+        vec![action(accounts(3), 0, 20)],
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "The attached deposit should cover exactly the deposits of the actions and 1 yoctoNEAR"
+)]
+fn test_the_deposit_covers_the_actions() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_then(
+        accounts(1),
+        U128(100),
+        None,
+        vec![action(accounts(2), 10, 20)],
+    );
+}
+
+#[test]
+#[should_panic(expected = "Too much gas attached to a follow-up action")]
+fn test_the_gas_of_the_actions_is_capped() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_then(
+        accounts(1),
+        U128(100),
+        None,
+        vec![action(accounts(1), 0, 51)],
+    );
+}
+
+#[test]
+#[should_panic(expected = "Between 1 and 4 follow-up actions are allowed")]
+fn test_the_number_of_actions_is_capped() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_then(
+        accounts(1),
+        U128(100),
+        None,
+        vec![action(accounts(1), 0, 5); 5],
+    );
+}
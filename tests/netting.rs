@@ -0,0 +1,98 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, PromiseOrValue, PromiseResult, RuntimeFeesConfig};
+
+// Charlie nets its transfer calls over 10 blocks, and alice and bob hold 500 tokens each.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build();
+    contract.ft_transfer(accounts(1), U128(500), None, None, None, None);
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.set_netting_window(Some(10));
+    (contract, context)
+}
+
+fn transfer_call(contract: &mut Contract, context: &mut VMContextBuilder, sender: usize) {
+    testing_env!(context.predecessor_account_id(accounts(sender)).build());
+    let used = contract.ft_transfer_call(
+        accounts(2),
+        U128(100),
+        None,
+        format!("order of {}", sender),
+        None,
+        None,
+        None,
+    );
+    assert!(matches!(used, PromiseOrValue::Value(U128(100))));
+}
+
+#[test]
+fn test_transfer_calls_are_notified_at_once() {
+    let (mut contract, mut context) = setup();
+    transfer_call(&mut contract, &mut context, 0);
+    transfer_call(&mut contract, &mut context, 1);
+    assert!(get_created_receipts().is_empty());
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(200));
+    assert_eq!(
+        contract
+            .get_netted_batch(accounts(2))
+            .unwrap()
+            .transfers
+            .len(),
+        2
+    );
+
+    testing_env!(context.block_height(10).build());
+    let _ = contract.flush_netted_transfers(accounts(2));
+    assert!(contract.get_netted_batch(accounts(2)).is_none());
+    let receipts = get_created_receipts();
+    let MockAction::FunctionCallWeight { method_name, .. } = &receipts[0].actions[0] else {
+        panic!("Expected a function call");
+    };
+    assert_eq!(method_name, b"ft_on_transfer_batch");
+
+    testing_env!(
+        context
+            .predecessor_account_id(context.context.current_account_id.clone())
+            .build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(b"[\"0\", \"40\"]".to_vec())]
+    );
+    let used = contract.ft_resolve_netted_transfers(
+        accounts(2),
+        vec![(accounts(0), U128(100)), (accounts(1), U128(100))],
+    );
+    assert_eq!(used, vec![U128(100), U128(60)]);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(440));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(160));
+}
+
+#[test]
+fn test_first_transfer_call_after_the_window_flushes_the_batch() {
+    let (mut contract, mut context) = setup();
+    transfer_call(&mut contract, &mut context, 0);
+    testing_env!(context.block_height(10).build());
+    transfer_call(&mut contract, &mut context, 1);
+    assert_eq!(get_created_receipts().len(), 2);
+    let batch = contract.get_netted_batch(accounts(2)).unwrap();
+    assert_eq!(batch.opened_at.0, 10);
+    assert_eq!(batch.transfers[0].sender_id, accounts(1));
+}
+
+#[test]
+#[should_panic(expected = "The netting window isn't over")]
+fn test_batch_is_flushed_after_the_window() {
+    let (mut contract, mut context) = setup();
+    transfer_call(&mut contract, &mut context, 0);
+    testing_env!(context.block_height(9).build());
+    let _ = contract.flush_netted_transfers(accounts(2));
+}
@@ -0,0 +1,187 @@
+use near_crypto::{KeyType, SecretKey, Signature};
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::guard::PauseFlags;
+use near_ft::onboarding::OnboardingBundle;
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, CurveType, NearToken, PublicKey};
+
+const EXPIRES_AT: u64 = 1_000_000_000;
+
+fn relay_key() -> SecretKey {
+    SecretKey::from_seed(KeyType::ED25519, "alice")
+}
+
+fn sign(bundle: &OnboardingBundle) -> Base64VecU8 {
+    let message =
+        near_sdk::borsh::to_vec(&("near-ft", env::current_account_id(), "onboard", bundle))
+            .unwrap();
+    let Signature::ED25519(signature) = relay_key().sign(&message) else {
+        unreachable!()
+    };
+    Base64VecU8(signature.to_bytes().to_vec())
+}
+
+fn bundle(receiver_id: AccountId, nonce: u64) -> OnboardingBundle {
+    OnboardingBundle {
+        sender_id: accounts(0),
+        receiver_id,
+        amount: U128(100),
+        memo: None,
+        nonce,
+        expires_at: U64(EXPIRES_AT),
+    }
+}
+
+// Alice holds 1,000 tokens and signs the bundles with her relay key. Charlie relays them.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    let public_key = PublicKey::from_parts(
+        CurveType::ED25519,
+        relay_key().public_key().key_data().to_vec(),
+    )
+    .unwrap();
+    contract.set_relay_key(Some(public_key));
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    (contract, context)
+}
+
+#[test]
+fn test_onboard_registers_and_funds_the_receiver() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(
+            Contract::storage_balance_bounds()
+                .min
+                .saturating_add(NearToken::from_yoctonear(5))
+        )
+        .build());
+    let bundle = bundle(accounts(1), 3);
+    contract.onboard(bundle.clone(), sign(&bundle));
+
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(900));
+    assert_eq!(contract.get_relay_nonce(accounts(0)), 4);
+    // The relayer paid for the storage, so it gets the deposit back when bob unregisters.
+    assert_eq!(
+        contract.get_storage_depositor(accounts(1)),
+        Some(accounts(2))
+    );
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, accounts(2));
+    assert!(matches!(
+        receipts[0].actions[0],
+        MockAction::Transfer { deposit, .. } if deposit == NearToken::from_yoctonear(5)
+    ));
+}
+
+#[test]
+fn test_onboard_charges_the_storage_sponsor() {
+    let (mut contract, mut context) = setup();
+    let sponsor_id: AccountId = "game.near".parse().unwrap();
+    let receiver_id: AccountId = "bob.game.near".parse().unwrap();
+    testing_env!(context
+        .predecessor_account_id(sponsor_id.clone())
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.register_storage_sponsor();
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    let bundle = bundle(receiver_id.clone(), 0);
+    contract.onboard(bundle.clone(), sign(&bundle));
+
+    assert_eq!(Contract::ft_balance_of(receiver_id.clone()), U128(100));
+    assert_eq!(
+        contract.get_storage_depositor(receiver_id),
+        Some(sponsor_id.clone())
+    );
+    assert_eq!(
+        contract.get_storage_sponsorship(sponsor_id),
+        Some(NearToken::from_yoctonear(0))
+    );
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit is less than the minimum storage balance")]
+fn test_onboard_needs_the_storage_deposit() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    let bundle = bundle(accounts(1), 0);
+    contract.onboard(bundle.clone(), sign(&bundle));
+}
+
+#[test]
+#[should_panic(expected = "Storage operations are paused")]
+fn test_onboard_is_paused_with_the_storage() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_pause_flags(PauseFlags {
+        storage: true,
+        ..Default::default()
+    });
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    let bundle = bundle(accounts(1), 0);
+    contract.onboard(bundle.clone(), sign(&bundle));
+}
+
+#[test]
+#[should_panic(expected = "The nonce was already used")]
+fn test_onboard_bundles_cant_be_replayed() {
+    let (mut contract, _) = setup();
+    let first = bundle(accounts(1), 0);
+    contract.onboard(first.clone(), sign(&first));
+    let replayed = bundle(accounts(3), 0);
+    contract.onboard(replayed.clone(), sign(&replayed));
+}
+
+#[test]
+#[should_panic(expected = "The account is already registered")]
+fn test_onboard_rejects_registered_receivers() {
+    let (mut contract, _) = setup();
+    let bundle = bundle(accounts(0), 0);
+    contract.onboard(bundle.clone(), sign(&bundle));
+}
+
+#[test]
+#[should_panic(expected = "The signed message has expired")]
+fn test_onboard_rejects_expired_bundles() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(EXPIRES_AT + 1).build());
+    let bundle = bundle(accounts(1), 0);
+    contract.onboard(bundle.clone(), sign(&bundle));
+}
+
+#[test]
+#[should_panic(expected = "Invalid signature")]
+fn test_onboard_rejects_altered_bundles() {
+    let (mut contract, _) = setup();
+    let signed = bundle(accounts(1), 0);
+    let signature = sign(&signed);
+    contract.onboard(
+        OnboardingBundle {
+            amount: U128(900),
+            ..signed
+        },
+        signature,
+    );
+}
@@ -0,0 +1,105 @@
+use near_ft::oracle::PriceFeedConfig;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::testing_env;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+// Bob, charlie and danny answer the `near` feed, which needs 2 answers of at most an hour and
+// reports answers 10% away from the others.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    contract.set_price_feed(
+        "near".to_string(),
+        Some(PriceFeedConfig {
+            oracle_ids: vec![accounts(1), accounts(2), accounts(3)],
+            min_answers: 2,
+            max_age_sec: 3_600,
+            max_divergence_bps: 1_000,
+        }),
+    );
+    (contract, context)
+}
+
+fn push(contract: &mut Contract, context: &mut VMContextBuilder, oracle: usize, price: u128) {
+    testing_env!(context.predecessor_account_id(accounts(oracle)).build());
+    contract.push_oracle_price("near".to_string(), U128(price));
+}
+
+#[test]
+fn test_feed_price_is_the_median_of_the_answers() {
+    let (mut contract, mut context) = setup();
+    push(&mut contract, &mut context, 1, 100);
+    assert!(contract.get_oracle_price("near".to_string()).is_none());
+    push(&mut contract, &mut context, 2, 104);
+    assert_eq!(
+        contract.get_oracle_price("near".to_string()).unwrap().price,
+        U128(102)
+    );
+    push(&mut contract, &mut context, 3, 103);
+    let price = contract.get_oracle_price("near".to_string()).unwrap();
+    assert_eq!(price.price, U128(103));
+    assert_eq!(price.answers, 3);
+}
+
+#[test]
+fn test_stale_answers_are_ignored() {
+    let (mut contract, mut context) = setup();
+    push(&mut contract, &mut context, 1, 100);
+    testing_env!(context.block_timestamp(3_000 * NANOS_PER_SEC).build());
+    push(&mut contract, &mut context, 2, 101);
+    push(&mut contract, &mut context, 3, 102);
+    testing_env!(context.block_timestamp(4_000 * NANOS_PER_SEC).build());
+    let price = contract.get_oracle_price("near".to_string()).unwrap();
+    assert_eq!(price.answers, 2);
+    assert_eq!(price.updated_at.0, 3_000 * NANOS_PER_SEC);
+
+    testing_env!(context.block_timestamp(7_000 * NANOS_PER_SEC).build());
+    assert!(contract.get_oracle_price("near".to_string()).is_none());
+}
+
+#[test]
+fn test_diverging_answers_are_reported() {
+    let (mut contract, mut context) = setup();
+    push(&mut contract, &mut context, 1, 100);
+    push(&mut contract, &mut context, 2, 105);
+    assert!(get_logs().is_empty());
+    push(&mut contract, &mut context, 3, 200);
+    assert_eq!(
+        get_logs(),
+        vec![
+            r#"EVENT_JSON:{"standard":"near_ft","version":"1.0.0","event":"oracle_divergence","data":[{"feed_id":"near","oracle_id":"danny","price":"200","median":"102"}]}"#
+        ]
+    );
+    // The median shrugs the outlier off.
+    assert_eq!(
+        contract.get_oracle_price("near".to_string()).unwrap().price,
+        U128(105)
+    );
+}
+
+#[test]
+#[should_panic(expected = "Only the oracles of the feed can call this method")]
+fn test_only_the_oracles_answer() {
+    let (mut contract, mut context) = setup();
+    push(&mut contract, &mut context, 4, 100);
+}
+
+#[test]
+#[should_panic(expected = "The minimum answers should be between 1 and the number of oracles")]
+fn test_feeds_need_reachable_minimum_answers() {
+    let (mut contract, _) = setup();
+    contract.set_price_feed(
+        "near".to_string(),
+        Some(PriceFeedConfig {
+            oracle_ids: vec![accounts(1)],
+            min_answers: 2,
+            max_age_sec: 3_600,
+            max_divergence_bps: 1_000,
+        }),
+    );
+}
@@ -0,0 +1,80 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::payment_intents::PaymentIntentStatus;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+const SECOND: u64 = 1_000_000_000;
+
+// Bob, a merchant, asks for 100 tokens within a minute, paying for the storage of the intent.
+// The context is left as alice, the payer.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_millinear(10))
+        .build());
+    let intent = contract.create_payment_intent(U128(100), None, U64(60 * SECOND), None);
+    assert_eq!(intent.intent_id, 0);
+    assert!(intent
+        .uri
+        .ends_with("fulfill_intent?intent_id=0&amount=100"));
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    (contract, context)
+}
+
+#[test]
+fn test_payers_fulfill_the_intent() {
+    let (mut contract, _) = setup();
+    contract.fulfill_intent(0);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    let intent = contract.get_payment_intent(0).unwrap();
+    assert!(matches!(
+        intent.intent.status,
+        PaymentIntentStatus::Fulfilled { payer_id, .. } if payer_id == accounts(0)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "The payment intent is not pending")]
+fn test_cancelled_intents_cant_be_fulfilled() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.cancel_payment_intent(0);
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.fulfill_intent(0);
+}
+
+#[test]
+#[should_panic(expected = "The payment intent has expired")]
+fn test_expired_intents_cant_be_fulfilled() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(60 * SECOND + 1).build());
+    contract.fulfill_intent(0);
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_the_creator_pays_for_the_storage() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.create_payment_intent(U128(100), None, U64(60 * SECOND), None);
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_the_payer_pays_for_the_storage_of_the_payment() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.fulfill_intent(0);
+}
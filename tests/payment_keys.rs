@@ -0,0 +1,71 @@
+use near_ft::payment_keys::{PaymentKeyType, PaymentPubkey};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build()
+}
+
+fn x25519_key() -> PaymentPubkey {
+    PaymentPubkey {
+        key_type: PaymentKeyType::X25519,
+        key: Base64VecU8(vec![7; 32]),
+    }
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_the_key_storage_is_refunded_on_removal() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.register_payment_pubkey(Some(x25519_key()));
+    let cost = NearToken::from_near(1).saturating_sub(refunded_to(&accounts(0)));
+    assert!(!cost.is_zero());
+    // The x25519 payment key doubles as the memo key.
+    assert_eq!(
+        contract.get_memo_key(accounts(0)),
+        Some(Base64VecU8(vec![7; 32]))
+    );
+
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.register_payment_pubkey(None);
+    assert_eq!(refunded_to(&accounts(0)), cost);
+    assert!(contract.get_payment_pubkey(accounts(0)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_the_holder_pays_for_the_storage_of_the_key() {
+    let (mut contract, _) = setup();
+    contract.register_payment_pubkey(Some(x25519_key()));
+}
+
+#[test]
+#[should_panic(expected = "Invalid key length for the key type")]
+fn test_the_key_length_matches_the_key_type() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.register_payment_pubkey(Some(PaymentPubkey {
+        key_type: PaymentKeyType::Secp256k1,
+        key: Base64VecU8(vec![7; 32]),
+    }));
+}
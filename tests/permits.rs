@@ -0,0 +1,138 @@
+use near_crypto::{KeyType, SecretKey, Signature};
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, CurveType, NearToken, PublicKey};
+
+const DEADLINE: u64 = 1_000_000_000;
+
+struct Holder {
+    key: SecretKey,
+    public_key: PublicKey,
+    account_id: AccountId,
+}
+
+impl Holder {
+    fn new() -> Self {
+        let key = SecretKey::from_seed(KeyType::ED25519, "holder");
+        let bytes = key.public_key().key_data().to_vec();
+        let public_key = PublicKey::from_parts(CurveType::ED25519, bytes.clone()).unwrap();
+        let account_id = bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+            .parse()
+            .unwrap();
+        Self {
+            key,
+            public_key,
+            account_id,
+        }
+    }
+
+    fn sign(&self, receiver_id: &AccountId, amount: u128, nonce: u64) -> Base64VecU8 {
+        let message = near_sdk::borsh::to_vec(&(
+            "near-ft",
+            env::current_account_id(),
+            "ft_transfer_with_permit",
+            (&self.account_id, receiver_id, amount, nonce, DEADLINE),
+        ))
+        .unwrap();
+        let Signature::ED25519(signature) = self.key.sign(&message) else {
+            unreachable!()
+        };
+        Base64VecU8(signature.to_bytes().to_vec())
+    }
+}
+
+// The implicit account of the holder gets 500 tokens.
+fn setup() -> (Contract, VMContextBuilder, Holder) {
+    let holder = Holder::new();
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    contract.storage_deposit(Some(holder.account_id.clone()), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_transfer(holder.account_id.clone(), U128(500), None, None, None, None);
+    // The relayer
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    (contract, context, holder)
+}
+
+fn transfer_with_permit(contract: &mut Contract, holder: &Holder, amount: u128, nonce: u64) {
+    contract.ft_transfer_with_permit(
+        holder.public_key.clone(),
+        holder.sign(&accounts(1), amount, nonce),
+        nonce,
+        U64(DEADLINE),
+        accounts(1),
+        U128(amount),
+    );
+}
+
+#[test]
+fn test_relayer_transfers_with_a_permit() {
+    let (mut contract, _, holder) = setup();
+    transfer_with_permit(&mut contract, &holder, 100, 0);
+    transfer_with_permit(&mut contract, &holder, 50, 3);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(150));
+    assert_eq!(
+        Contract::ft_balance_of(holder.account_id.clone()),
+        U128(350)
+    );
+    assert_eq!(contract.get_permit_nonce(holder.account_id), 4);
+}
+
+#[test]
+#[should_panic(expected = "The nonce was already used")]
+fn test_permit_is_not_replayed() {
+    let (mut contract, _, holder) = setup();
+    transfer_with_permit(&mut contract, &holder, 100, 0);
+    transfer_with_permit(&mut contract, &holder, 100, 0);
+}
+
+#[test]
+#[should_panic(expected = "The nonce was already used")]
+fn test_bumping_the_relay_nonce_revokes_the_permits() {
+    let (mut contract, mut context, holder) = setup();
+    testing_env!(context
+        .predecessor_account_id(holder.account_id.clone())
+        .build());
+    contract.bump_relay_nonce(5);
+    assert_eq!(contract.get_permit_nonce(holder.account_id.clone()), 5);
+    transfer_with_permit(&mut contract, &holder, 100, 4);
+}
+
+#[test]
+#[should_panic(expected = "Invalid signature")]
+fn test_permit_covers_the_amount() {
+    let (mut contract, _, holder) = setup();
+    contract.ft_transfer_with_permit(
+        holder.public_key.clone(),
+        holder.sign(&accounts(1), 100, 0),
+        0,
+        U64(DEADLINE),
+        accounts(1),
+        U128(400),
+    );
+}
+
+#[test]
+#[should_panic(expected = "The signed message has expired")]
+fn test_permit_expires() {
+    let (mut contract, mut context, holder) = setup();
+    testing_env!(context.block_timestamp(DEADLINE + 1).build());
+    transfer_with_permit(&mut contract, &holder, 100, 0);
+}
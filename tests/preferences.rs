@@ -0,0 +1,57 @@
+use near_ft::preferences::AccountPreferences;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::{self, json};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    let (contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+#[test]
+fn test_preferences_are_set_and_cleared() {
+    let (mut contract, _) = setup();
+    assert!(contract.get_preferences(accounts(1)).is_none());
+
+    // The settings left out of the JSON keep their defaults.
+    let preferences: AccountPreferences =
+        serde_json::from_value(json!({ "memo_required": true, "transfer_limit": "100" })).unwrap();
+    contract.set_preferences(Some(preferences));
+
+    let preferences = contract.get_preferences(accounts(1)).unwrap();
+    assert!(preferences.memo_required);
+    assert!(!preferences.claim_required);
+    assert_eq!(preferences.transfer_limit, Some(U128(100)));
+    assert!(contract.get_preferences(accounts(0)).is_none());
+
+    contract.set_preferences(None);
+    assert!(contract.get_preferences(accounts(1)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The preferences are too large")]
+fn test_preferences_are_capped() {
+    let (mut contract, _) = setup();
+    contract.set_preferences(Some(AccountPreferences {
+        notification_url: Some(format!("https://example.com/{}", "a".repeat(512))),
+        ..Default::default()
+    }));
+}
+
+#[test]
+#[should_panic(expected = "The account charlie is not registered")]
+fn test_preferences_need_a_registration() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.set_preferences(Some(AccountPreferences::default()));
+}
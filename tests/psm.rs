@@ -0,0 +1,125 @@
+use near_ft::ft_core::{FungibleTokenCore, FungibleTokenReceiver};
+use near_ft::psm::PsmConfig;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::serde_json::{self, Value};
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken, PromiseResult, RuntimeFeesConfig};
+
+const USD: u128 = 1_000_000;
+const TOKEN: u128 = 10u128.pow(24);
+
+// Danny is a 6-decimals stablecoin swapped for the token (24 decimals) with a 0.1% fee, and
+// bob swapped 1 danny for 0.999 tokens.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    contract.set_psm_config(Some(PsmConfig {
+        reference_token_id: accounts(3),
+        reference_decimals: 6,
+        fee_bps: 10,
+        debt_ceiling: U128(10 * TOKEN),
+    }));
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    let _ = contract.ft_on_transfer(accounts(1), U128(USD), "\"psm_swap\"".to_string());
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+#[test]
+fn test_swaps_mint_against_reserves() {
+    let (contract, _) = setup();
+    assert_eq!(
+        Contract::ft_balance_of(accounts(1)),
+        U128(TOKEN - TOKEN / 1_000)
+    );
+    let psm = contract.get_psm();
+    assert_eq!(psm.reserves, U128(USD));
+    assert_eq!(psm.debt, U128(TOKEN - TOKEN / 1_000));
+}
+
+#[test]
+fn test_redemptions_send_reference_tokens() {
+    let (mut contract, _) = setup();
+    let _ = contract.psm_redeem(U128(TOKEN / 2));
+    assert_eq!(
+        Contract::ft_balance_of(accounts(1)),
+        U128(TOKEN / 2 - TOKEN / 1_000)
+    );
+    assert_eq!(contract.get_psm().reserves, U128(USD - 499_500));
+
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, accounts(3));
+    let MockAction::FunctionCallWeight {
+        method_name, args, ..
+    } = &receipts[0].actions[0]
+    else {
+        panic!("Expected a function call");
+    };
+    assert_eq!(method_name, b"ft_transfer");
+    let args: Value = serde_json::from_slice(args).unwrap();
+    assert_eq!(args["receiver_id"], "bob");
+    assert_eq!(args["amount"], "499500");
+}
+
+#[test]
+fn test_failed_redemptions_are_refunded() {
+    let (mut contract, mut context) = setup();
+    let _ = contract.psm_redeem(U128(TOKEN / 2));
+    testing_env!(
+        context
+            .predecessor_account_id(context.context.current_account_id.clone())
+            .build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Failed]
+    );
+    assert!(!contract.psm_resolve_redeem(accounts(1), U128(TOKEN / 2), U128(499_500)));
+    assert_eq!(
+        Contract::ft_balance_of(accounts(1)),
+        U128(TOKEN - TOKEN / 1_000)
+    );
+    assert_eq!(contract.get_psm().reserves, U128(USD));
+}
+
+#[test]
+#[should_panic(expected = "The swap exceeds the PSM debt ceiling")]
+fn test_swaps_are_capped_by_the_debt_ceiling() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    let _ = contract.ft_on_transfer(accounts(1), U128(10 * USD), "\"psm_swap\"".to_string());
+}
+
+#[test]
+#[should_panic(expected = "The PSM doesn't accept this token")]
+fn test_only_the_reference_token_is_swapped() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    let _ = contract.ft_on_transfer(accounts(1), U128(USD), "\"psm_swap\"".to_string());
+}
+
+#[test]
+#[should_panic(expected = "The redemption exceeds the PSM debt")]
+fn test_redemptions_are_capped_by_the_debt() {
+    let (mut contract, _) = setup();
+    let _ = contract.psm_redeem(U128(TOKEN));
+}
+
+#[test]
+#[should_panic(expected = "The PSM still holds reserves of its reference token")]
+fn test_reference_token_is_kept_while_holding_reserves() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_psm_config(None);
+}
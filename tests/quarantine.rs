@@ -0,0 +1,48 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::metadata::FungibleTokenMetadataProvider;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+
+const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build()
+}
+
+#[test]
+fn test_rename_flags_the_metadata_for_a_week() {
+    let (mut contract, mut context) = setup();
+    assert!(Contract::ft_metadata().pending_change.is_none());
+
+    contract.rename_token(Some("Renamed Token".to_string()), None);
+
+    let metadata = Contract::ft_metadata();
+    assert_eq!(metadata.metadata.name, "Renamed Token");
+    let pending_change = metadata.pending_change.unwrap();
+    assert_eq!(pending_change.previous_name, "Test Token");
+    assert_eq!(pending_change.previous_symbol, "TEST");
+
+    testing_env!(context.block_timestamp(7 * NANOS_PER_DAY).build());
+    assert!(Contract::ft_metadata().pending_change.is_none());
+}
+
+#[test]
+#[should_panic(expected = "only one large transfer per day is allowed")]
+fn test_quarantine_rate_limits_large_transfers() {
+    let (mut contract, mut context) = setup();
+    contract.rename_token(None, Some("rugNEAR".to_string()));
+
+    // Small transfers aren't limited.
+    contract.ft_transfer(accounts(1), U128(10), None, None, None, None);
+    contract.ft_transfer(accounts(1), U128(10), None, None, None, None);
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    testing_env!(context.block_timestamp(NANOS_PER_DAY).build());
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+}
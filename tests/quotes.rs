@@ -0,0 +1,91 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::quotes::FEE_QUOTE_VALIDITY_BLOCKS;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+// A 1% fee is collected by charlie, and the owner quoted a transfer of 100 tokens to bob.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build();
+    contract.set_transfer_fee(100, accounts(2));
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    let quote = contract.quote_transfer(accounts(1), U128(100));
+    assert_eq!(quote.quote_id, U64(0));
+    assert_eq!(quote.fee, U128(1));
+    assert_eq!(quote.received, U128(100));
+    assert!(refunded_to(&accounts(0)) < NearToken::from_near(1));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_quoted_transfer_charges_the_quoted_fee() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_quoted(U64(0), None);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(899));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(1));
+    assert!(contract.get_fee_quote(accounts(0)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The quoted terms changed")]
+fn test_fee_changes_revert_the_quoted_transfer() {
+    let (mut contract, _) = setup();
+    contract.set_transfer_fee(200, accounts(2));
+    contract.ft_transfer_quoted(U64(0), None);
+}
+
+#[test]
+#[should_panic(expected = "The quote has expired")]
+fn test_expired_quotes_cant_be_executed() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_height(FEE_QUOTE_VALIDITY_BLOCKS + 1).build());
+    contract.ft_transfer_quoted(U64(0), None);
+}
+
+#[test]
+#[should_panic(expected = "The quote doesn't exist")]
+fn test_new_quotes_replace_the_previous_one() {
+    let (mut contract, _) = setup();
+    assert_eq!(
+        contract.quote_transfer(accounts(1), U128(200)).quote_id,
+        U64(1)
+    );
+    contract.ft_transfer_quoted(U64(0), None);
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_quotes_pay_their_storage() {
+    let (mut contract, _) = setup();
+    contract.quote_transfer(accounts(2), U128(100));
+}
+
+#[test]
+fn test_executed_quotes_refund_their_storage() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_quoted(U64(0), None);
+    assert!(refunded_to(&accounts(0)) > NearToken::from_yoctonear(0));
+}
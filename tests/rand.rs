@@ -0,0 +1,227 @@
+use near_ft::rand::{randomness_commitment, Rng};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, NearToken};
+
+const SEC: u64 = 1_000_000_000;
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build()
+}
+
+// Each word costs a sha256 host call, so the bias tests reset the context every thousand draws
+// to stay under the gas limit.
+fn repeat(times: u32, mut draw: impl FnMut()) {
+    for i in 0..times {
+        if i % 1_000 == 0 {
+            testing_env!(VMContextBuilder::new().build());
+        }
+        draw();
+    }
+}
+
+fn commit(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    account: usize,
+    secret: &[u8; 32],
+) {
+    testing_env!(context
+        .predecessor_account_id(accounts(account))
+        .attached_deposit(NearToken::from_near(1))
+        .build());
+    let commitment = randomness_commitment(&accounts(account), secret);
+    contract.commit_randomness(0, Base64VecU8(commitment.to_vec()));
+    assert!(refunded_to(&accounts(account)) < NearToken::from_near(1));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+}
+
+fn reveal(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    account: usize,
+    secret: &[u8; 32],
+) {
+    testing_env!(context.predecessor_account_id(accounts(account)).build());
+    contract.reveal_randomness(0, Base64VecU8(secret.to_vec()));
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_randomness_round_mixes_the_revealed_secrets() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(contract.create_randomness_round(60, 60), 0);
+    commit(&mut contract, &mut context, 1, &[1; 32]);
+    commit(&mut contract, &mut context, 2, &[2; 32]);
+
+    testing_env!(context.block_timestamp(60 * SEC).build());
+    reveal(&mut contract, &mut context, 1, &[1; 32]);
+    reveal(&mut contract, &mut context, 2, &[2; 32]);
+    let round = contract.get_randomness_round(0).unwrap();
+    assert_eq!((round.commitments, round.reveals), (2, 2));
+    assert_eq!(round.entropy.0, vec![3; 32]);
+
+    testing_env!(context.block_timestamp(120 * SEC).build());
+    let seed = contract.finalize_randomness_round(0);
+    let mut preimage = env::random_seed_array().to_vec();
+    preimage.extend_from_slice(&[3; 32]);
+    assert_eq!(seed.0, env::sha256(&preimage));
+    // The seed doesn't change once the round is finalized.
+    testing_env!(context.random_seed([7; 32]).build());
+    assert_eq!(contract.finalize_randomness_round(0), seed);
+}
+
+#[test]
+#[should_panic(expected = "The secret doesn't match the commitment")]
+fn test_randomness_reveal_checks_the_commitment() {
+    let (mut contract, mut context) = setup();
+    contract.create_randomness_round(60, 60);
+    commit(&mut contract, &mut context, 1, &[1; 32]);
+    testing_env!(context.block_timestamp(60 * SEC).build());
+    reveal(&mut contract, &mut context, 1, &[2; 32]);
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_randomness_commitments_pay_their_storage() {
+    let (mut contract, mut context) = setup();
+    contract.create_randomness_round(60, 60);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    let commitment = randomness_commitment(&accounts(1), &[1; 32]);
+    contract.commit_randomness(0, Base64VecU8(commitment.to_vec()));
+}
+
+#[test]
+fn test_randomness_reveals_refund_the_commitment_storage() {
+    let (mut contract, mut context) = setup();
+    contract.create_randomness_round(60, 60);
+    commit(&mut contract, &mut context, 1, &[1; 32]);
+    testing_env!(context.block_timestamp(60 * SEC).build());
+    reveal(&mut contract, &mut context, 1, &[1; 32]);
+    assert!(refunded_to(&accounts(1)) > NearToken::from_yoctonear(0));
+}
+
+#[test]
+#[should_panic(expected = "The commit phase is over")]
+fn test_randomness_commitments_close() {
+    let (mut contract, mut context) = setup();
+    contract.create_randomness_round(60, 60);
+    testing_env!(context.block_timestamp(60 * SEC).build());
+    commit(&mut contract, &mut context, 1, &[1; 32]);
+}
+
+#[test]
+#[should_panic(expected = "The reveal phase isn't over")]
+fn test_randomness_round_is_finalized_after_the_reveals() {
+    let (mut contract, mut context) = setup();
+    contract.create_randomness_round(60, 60);
+    testing_env!(context.block_timestamp(60 * SEC).build());
+    contract.finalize_randomness_round(0);
+}
+
+#[test]
+fn test_rng_is_deterministic() {
+    testing_env!(VMContextBuilder::new().build());
+    let draws = |seed| {
+        let mut rng = Rng::new(seed);
+        (0..8).map(|_| rng.next_u64()).collect::<Vec<_>>()
+    };
+    assert_eq!(draws([1; 32]), draws([1; 32]));
+    assert_ne!(draws([1; 32]), draws([2; 32]));
+}
+
+// The bias analysis draws from many seeds and checks every outcome against its expected
+// frequency. With `n` draws of probability `p`, the count has a standard deviation of
+// `sqrt(n * p * (1 - p))`; the tolerances below are about five of them, so an unbiased `Rng`
+// passes whatever the seeds while a bias of a few percent fails.
+
+#[test]
+fn test_rng_below_is_uniform() {
+    let mut rng = Rng::new([0; 32]);
+    // 3 doesn't divide 2^64, so reducing the words modulo 3 would favor 0, though only by
+    // 2^-64. The bias is only measurable for bounds close to 2^64, see below.
+    let mut counts = [0u32; 3];
+    repeat(30_000, || counts[rng.below(3) as usize] += 1);
+    for count in counts {
+        assert!(count.abs_diff(10_000) < 400, "{counts:?}");
+    }
+
+    // With a bound of 3/4 of 2^64, the words above the bound would wrap onto the lower third
+    // of the range, drawing it half of the time with a plain modulo. Rejecting the words below
+    // `2^64 mod bound` brings it back to a third.
+    let bound = u64::MAX / 4 * 3;
+    let mut lower = 0u32;
+    repeat(20_000, || {
+        if rng.below(bound) < bound / 3 {
+            lower += 1;
+        }
+    });
+    assert!(lower.abs_diff(6_667) < 350, "{lower}");
+}
+
+#[test]
+fn test_rng_weighted_index_follows_the_weights() {
+    let mut rng = Rng::new([1; 32]);
+    let weights = [1, 0, 2, 7];
+    let mut counts = [0u32; 4];
+    repeat(20_000, || counts[rng.weighted_index(&weights)] += 1);
+    assert_eq!(counts[1], 0);
+    assert!(counts[0].abs_diff(2_000) < 250, "{counts:?}");
+    assert!(counts[2].abs_diff(4_000) < 300, "{counts:?}");
+    assert!(counts[3].abs_diff(14_000) < 350, "{counts:?}");
+
+    // The weights above 2^64 draw 128-bit numbers.
+    let weights = [u128::MAX / 2, u128::MAX / 2];
+    let mut first = 0u32;
+    repeat(10_000, || {
+        if rng.weighted_index(&weights) == 0 {
+            first += 1;
+        }
+    });
+    assert!(first.abs_diff(5_000) < 250, "{first}");
+}
+
+#[test]
+fn test_rng_shuffle_is_uniform() {
+    let mut rng = Rng::new([2; 32]);
+    // Each of the 6 permutations of 3 items should come up as often. The naive shuffle swapping
+    // every item with any position would draw 27 equally likely paths, which can't split evenly
+    // over 6 permutations.
+    let mut counts = std::collections::HashMap::new();
+    repeat(30_000, || {
+        let mut items = [0, 1, 2];
+        rng.shuffle(&mut items);
+        *counts.entry(items).or_insert(0u32) += 1;
+    });
+    assert_eq!(counts.len(), 6);
+    for count in counts.values() {
+        assert!(count.abs_diff(5_000) < 350, "{counts:?}");
+    }
+}
+
+#[test]
+#[should_panic(expected = "The weights can't be all zero")]
+fn test_rng_weighted_index_needs_a_weight() {
+    testing_env!(VMContextBuilder::new().build());
+    Rng::new([0; 32]).weighted_index(&[0, 0]);
+}
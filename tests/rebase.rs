@@ -0,0 +1,527 @@
+#![cfg(feature = "rebase")]
+
+use near_ft::access::Role;
+use near_ft::fees::{FeeConfig, FeeSplit};
+use near_ft::ft_core::{FungibleTokenCore, FungibleTokenReceiver};
+use near_ft::insurance::{InsuranceConfig, MIN_INSURANCE_TIMELOCK_SEC};
+use near_ft::metadata::FungibleTokenMetadata;
+use near_ft::mint_requests::MintRequestStatus;
+use near_ft::psm::PsmConfig;
+use near_ft::rebase::{MAX_REBASE_INDEX, REBASE_INDEX_ONE};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::treasury::TreasuryGuard;
+use near_ft::{Contract, FT_METADATA_SPEC};
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, NearToken, PromiseResult, RuntimeFeesConfig};
+
+const SECOND: u64 = 1_000_000_000;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build()
+}
+
+// The same token, with a registered contract account to escrow the tokens of the extensions,
+// after a rebase doubling every balance. Alice holds 2,000 tokens in 1,000 shares.
+fn setup_doubled() -> (Contract, VMContextBuilder) {
+    let (mut contract, context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .balance(contract_id(), 0)
+        .build();
+    contract.rebase(U128(2 * REBASE_INDEX_ONE));
+    (contract, context)
+}
+
+#[test]
+fn test_rebase_scales_balances_and_supply() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(1), U128(400), None, None, None, None);
+
+    contract.rebase(U128(2 * REBASE_INDEX_ONE));
+    assert_eq!(contract.get_rebase_index(), U128(2 * REBASE_INDEX_ONE));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_200));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(800));
+    assert_eq!(contract.ft_total_supply(), U128(2_000));
+    assert_eq!(Contract::shares_of(accounts(1)), U128(400));
+
+    let logs = get_logs();
+    assert!(logs.last().unwrap().contains(&format!(
+        r#""event":"rebase","data":[{{"old_index":"{}","new_index":"{}","total_supply":"2000"}}]"#,
+        REBASE_INDEX_ONE,
+        2 * REBASE_INDEX_ONE
+    )));
+}
+
+#[test]
+fn test_transfers_count_in_token_amounts() {
+    let (mut contract, _) = setup();
+    contract.rebase(U128(2 * REBASE_INDEX_ONE));
+
+    contract.ft_transfer(accounts(1), U128(500), None, None, None, None);
+    assert_eq!(Contract::shares_of(accounts(1)), U128(250));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_500));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert!(get_logs().last().unwrap().contains(r#""amount":"500""#));
+
+    contract.ft_mint(accounts(1), U128(100), None);
+    assert_eq!(Contract::shares_of(accounts(1)), U128(300));
+    assert_eq!(contract.ft_total_supply(), U128(2_100));
+}
+
+#[test]
+fn test_rebase_oracle() {
+    let (mut contract, mut context) = setup();
+    contract.set_rebase_oracle(Some(accounts(2)));
+    assert_eq!(contract.get_rebase_oracle(), Some(accounts(2)));
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.rebase(U128(REBASE_INDEX_ONE / 2));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(500));
+}
+
+#[test]
+#[should_panic(expected = "Only the owner and the rebase oracle can call this method")]
+fn test_rebase_rejects_other_accounts() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.rebase(U128(2 * REBASE_INDEX_ONE));
+}
+
+#[test]
+fn test_escrows_count_in_token_amounts() {
+    let (mut contract, mut context) = setup();
+    contract.rebase(U128(2 * REBASE_INDEX_ONE));
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    let escrow_id = contract.ft_transfer_escrow(accounts(1), U128(500), 3_600);
+    assert_eq!(contract.get_escrow(escrow_id).unwrap().amount, U128(500));
+    assert_eq!(Contract::shares_of(accounts(0)), U128(750));
+
+    // The escrowed shares grow with the index like a balance.
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.rebase(U128(4 * REBASE_INDEX_ONE));
+    assert_eq!(contract.get_escrow(escrow_id).unwrap().amount, U128(1_000));
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.accept_escrow(escrow_id);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(1_000));
+}
+
+#[test]
+fn test_allowances_count_in_token_amounts() {
+    let (mut contract, mut context) = setup();
+    contract.rebase(U128(2 * REBASE_INDEX_ONE));
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.ft_approve(accounts(2), U128(600), None);
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_transfer_from(accounts(0), accounts(1), U128(600), None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(600));
+    assert_eq!(Contract::shares_of(accounts(1)), U128(300));
+}
+
+#[test]
+fn test_conversions_hold_up_to_the_highest_index() {
+    let (mut contract, _) = setup();
+    contract.rebase(U128(MAX_REBASE_INDEX));
+    assert_eq!(
+        contract.ft_total_supply(),
+        U128(1_000 * MAX_REBASE_INDEX / REBASE_INDEX_ONE)
+    );
+    let amount = Contract::ft_balance_of(accounts(0)).0 / 10;
+    contract.ft_transfer(accounts(1), U128(amount), None, None, None, None);
+    // The shares are rounded down, so bob gets a hair less than he was sent.
+    assert_eq!(Contract::shares_of(accounts(1)), U128(99));
+    assert!(Contract::ft_balance_of(accounts(1)).0 <= amount);
+}
+
+#[test]
+#[should_panic(expected = "The index should be positive and at most MAX_REBASE_INDEX")]
+fn test_rebase_caps_the_index() {
+    let (mut contract, _) = setup();
+    contract.rebase(U128(MAX_REBASE_INDEX + 1));
+}
+
+#[test]
+#[should_panic(expected = "The mint exceeds the max supply")]
+fn test_max_supply_caps_the_token_amount() {
+    let (mut contract, _) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .with(|contract| contract.max_supply = Some(2_500))
+        .build();
+    contract.rebase(U128(2 * REBASE_INDEX_ONE));
+    // 1,250 shares are under the cap, but they are worth 2,500 tokens.
+    contract.ft_mint(accounts(0), U128(500), None);
+    assert_eq!(contract.ft_total_supply(), U128(2_500));
+    contract.ft_mint(accounts(0), U128(2), None);
+}
+
+#[test]
+fn test_mint_requests_count_in_token_amounts() {
+    let (mut contract, mut context) = setup_doubled();
+    contract.set_mint_proposer(accounts(1), true);
+    contract.set_mint_approver(accounts(2), true);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    let request_id = contract.propose_mint(accounts(1), U128(500), "Payroll".to_string());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    assert!(contract.approve_mint(request_id) == MintRequestStatus::Executed);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::shares_of(accounts(1)), U128(250));
+    assert_eq!(contract.ft_total_supply(), U128(2_500));
+}
+
+#[test]
+fn test_wrapped_near_counts_in_token_amounts() {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_wrapped(
+        accounts(0),
+        FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: "Wrapped NEAR".to_string(),
+            symbol: "wNEAR".to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 24,
+        },
+    );
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.rebase(U128(2 * REBASE_INDEX_ONE));
+
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.near_deposit();
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(100));
+    assert_eq!(Contract::shares_of(accounts(0)), U128(50));
+
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.near_withdraw(U128(40));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(60));
+    assert_eq!(contract.ft_total_supply(), U128(60));
+}
+
+#[test]
+fn test_snapshots_keep_the_index_they_were_taken_at() {
+    let (mut contract, _) = setup_doubled();
+    assert_eq!(contract.snapshot(), 0);
+    assert_eq!(contract.total_supply_at(0), U128(2_000));
+
+    contract.rebase(U128(4 * REBASE_INDEX_ONE));
+    assert_eq!(contract.balance_of_at(accounts(0), 0), U128(2_000));
+    contract.ft_transfer(accounts(1), U128(400), None, None, None, None);
+    // The checkpointed shares are converted at the index of the snapshot too.
+    assert_eq!(contract.balance_of_at(accounts(0), 0), U128(2_000));
+    assert_eq!(contract.balance_of_at(accounts(1), 0), U128(0));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(3_600));
+}
+
+#[test]
+fn test_treasury_spends_count_in_token_amounts() {
+    let (mut contract, mut context) = setup_doubled();
+    contract.set_treasury_guard(Some(TreasuryGuard {
+        treasury_id: accounts(0),
+        approver_id: accounts(2),
+        threshold: U128(100),
+        window_sec: 60,
+    }));
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    let spend_id = contract.propose_treasury_spend(accounts(1), U128(500), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    assert_eq!(Contract::shares_of(contract_id()), U128(250));
+    assert_eq!(
+        contract.get_treasury_spends(None, None)[0].1.amount,
+        U128(500)
+    );
+
+    // The escrowed shares grow with the index like a balance.
+    contract.rebase(U128(4 * REBASE_INDEX_ONE));
+    assert_eq!(
+        contract.get_treasury_spends(None, None)[0].1.amount,
+        U128(1_000)
+    );
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.approve_treasury_spend(spend_id);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(1_000));
+}
+
+#[test]
+fn test_bonds_pay_out_token_amounts() {
+    let (mut contract, mut context) = setup_doubled();
+    contract.create_bond_market(None, U128(1), U128(1), 1_000, 10, U128(500));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(500));
+    assert_eq!(Contract::shares_of(contract_id()), U128(250));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    let position = contract.bond_with_near(0, None);
+    assert_eq!(position.payout, U128(110));
+    let market = contract.get_bond_market(0).unwrap();
+    assert_eq!((market.capacity, market.sold), (U128(500), U128(110)));
+
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(20 * 86_400 * SECOND)
+        .build());
+    assert_eq!(contract.get_bond_positions(accounts(1)).1, U128(110));
+    assert_eq!(contract.claim_bonds(), U128(110));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(110));
+}
+
+#[test]
+fn test_mining_emits_token_amounts() {
+    let (mut contract, mut context) = setup_doubled();
+    contract.create_mining_program(accounts(1), None, U128(100), 60, 5, None);
+    contract.fund_mining_program(0, U128(500));
+    assert_eq!(
+        contract.get_mining_program(0).unwrap().program.funded,
+        U128(500)
+    );
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(120 * SECOND)
+        .build());
+    contract.execute_mining_emission(0);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(200));
+
+    // The pool didn't use 50 tokens, which are 25 shares of the 100 emitted.
+    testing_env!(
+        context.predecessor_account_id(contract_id()).build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![PromiseResult::Successful(b"\"50\"".to_vec())]
+    );
+    let used = contract.on_mining_emission_resolved(0, 2, U128(100), accounts(2));
+    assert_eq!(used, U128(150));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(150));
+    let program = contract.get_mining_program(0).unwrap().program;
+    assert_eq!(program.funded, U128(350));
+    assert_eq!(program.total_emitted, U128(150));
+}
+
+#[test]
+fn test_grants_vest_token_amounts() {
+    let (mut contract, mut context) = setup_doubled();
+    contract.create_vesting_grant(
+        accounts(1),
+        U128(1_000),
+        None,
+        10,
+        100,
+        near_ft::grants::GrantTransferPolicy::AfterCliff,
+    );
+    assert_eq!(Contract::shares_of(contract_id()), U128(500));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .block_timestamp(50 * SECOND)
+        .build());
+    let token = contract.nft_token("0".to_string()).unwrap();
+    assert_eq!(
+        (token.grant.amount, token.claimable),
+        (U128(1_000), U128(500))
+    );
+    assert_eq!(contract.claim_vesting_grant(0), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+}
+
+#[test]
+fn test_airdrops_are_claimed_in_token_amounts() {
+    let (mut contract, mut context) = setup_doubled();
+    let leaf = |index: u64, account_id: &AccountId, amount: u128| {
+        env::sha256_array(&near_sdk::borsh::to_vec(&(index, account_id, amount)).unwrap())
+    };
+    let left = leaf(0, &accounts(1), 100);
+    let right = leaf(1, &accounts(2), 50);
+    let root = env::sha256_array(&[left.as_slice(), right.as_slice()].concat());
+    let airdrop_id = contract.create_airdrop(Base64VecU8(root.to_vec()), U128(150), None);
+    assert_eq!(Contract::shares_of(contract_id()), U128(75));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.claim_airdrop(airdrop_id, 0, U128(100), vec![Base64VecU8(right.to_vec())]);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    let airdrop = contract.get_airdrop(airdrop_id).unwrap();
+    assert_eq!((airdrop.total, airdrop.claimed), (U128(150), U128(100)));
+}
+
+#[test]
+fn test_vouchers_are_worth_token_amounts() {
+    let (mut contract, mut context) = setup_doubled();
+    contract.set_successor_contract(Some(accounts(3)));
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    let voucher = contract.export_balance_voucher();
+    assert_eq!(voucher.amount, U128(2_000));
+    assert_eq!(contract.ft_total_supply(), U128(0));
+}
+
+#[test]
+fn test_psm_debt_counts_in_token_amounts() {
+    const TOKEN: u128 = 10u128.pow(24);
+    let (mut contract, mut context) = setup_doubled();
+    contract.set_psm_config(Some(PsmConfig {
+        reference_token_id: accounts(3),
+        reference_decimals: 6,
+        fee_bps: 0,
+        debt_ceiling: U128(10 * TOKEN),
+    }));
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    let _ = contract.ft_on_transfer(accounts(1), U128(1_000_000), "\"psm_swap\"".to_string());
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(TOKEN));
+    assert_eq!(Contract::shares_of(accounts(1)), U128(TOKEN / 2));
+    assert_eq!(contract.get_psm().debt, U128(TOKEN));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    let _ = contract.psm_redeem(U128(TOKEN / 2));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(TOKEN / 2));
+    assert_eq!(contract.get_psm().debt, U128(TOKEN / 2));
+}
+
+#[cfg(feature = "stablecoin")]
+#[test]
+fn test_vault_debt_counts_in_token_amounts() {
+    use near_ft::stablecoin::StablecoinConfig;
+
+    let (mut contract, mut context) = setup_doubled();
+    contract.set_stablecoin_config(StablecoinConfig {
+        oracle_id: accounts(3),
+        collateral_ratio_bps: 15_000,
+        liquidation_ratio_bps: 12_000,
+        liquidation_bonus_bps: 500,
+        max_price_age_sec: 3_600,
+    });
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    contract.push_collateral_price(U128(5_000_000));
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_near(3))
+        .build());
+    contract.deposit_collateral();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+
+    contract.mint_against_collateral(U128(10_000_000));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(10_000_000));
+    assert_eq!(Contract::shares_of(accounts(1)), U128(5_000_000));
+    let vault = contract.repay_debt(U128(4_000_000));
+    assert_eq!(vault.debt, U128(6_000_000));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(6_000_000));
+}
+
+#[test]
+fn test_bounties_pay_out_token_amounts() {
+    let (mut contract, mut context) = setup_doubled();
+    assert_eq!(contract.fund_bounty_pool(U128(500)), U128(500));
+    contract.rebase(U128(4 * REBASE_INDEX_ONE));
+    assert_eq!(contract.get_bounty_pool(), U128(1_000));
+
+    let details = "Reentrancy in ft_resolve_transfer";
+    let commitment = env::sha256(&near_sdk::borsh::to_vec(&(accounts(1), details)).unwrap());
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.submit_disclosure(commitment.into());
+    contract.reveal_and_claim(0, details.to_string());
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.approve_bounty_payout(0, U128(400));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(400));
+    assert_eq!(contract.get_bounty_pool(), U128(600));
+}
+
+#[test]
+fn test_insurance_pays_out_token_amounts() {
+    let (mut contract, mut context) = setup_doubled();
+    contract.set_fee_config(Some(FeeConfig {
+        fee_bps: 100,
+        split: FeeSplit {
+            stakers_bps: 0,
+            treasury_bps: 5_000,
+            burn_bps: 0,
+            rebates_bps: 0,
+            insurance_bps: 5_000,
+        },
+        stakers_pool_id: accounts(2),
+        treasury_id: accounts(2),
+    }));
+    contract.set_insurance_config(Some(InsuranceConfig {
+        max_payout_per_claim: U128(300),
+        timelock_sec: MIN_INSURANCE_TIMELOCK_SEC,
+    }));
+    contract.grant_role(accounts(3), Role::Adjudicator);
+    // The fee of 10 tokens is 5 shares: 2 shares worth 4 tokens fund the insurance and the
+    // treasury each, and the remainder is burned.
+    contract.ft_transfer(accounts(1), U128(1_000), None, None, None, None);
+    assert_eq!(contract.get_insurance_fund().balance, U128(4));
+    assert!(get_logs().iter().any(|log| log.contains(
+        r#""event":"fee_distribution","data":[{"payer_id":"alice","to_stakers":"0","to_treasury":"4","burned":"2","to_rebates":"0","to_insurance":"4"}]"#
+    )));
+
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    let claim_id = contract.approve_insurance_payout(accounts(1), U128(4), "hack".to_string());
+    assert_eq!(
+        contract.get_insurance_claim(claim_id).unwrap().amount,
+        U128(4)
+    );
+    assert_eq!(contract.get_insurance_fund().reserved, U128(4));
+    testing_env!(context
+        .block_timestamp(MIN_INSURANCE_TIMELOCK_SEC * SECOND)
+        .build());
+    contract.execute_insurance_payout(claim_id);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(1_004));
+}
+
+#[test]
+fn test_supply_views_count_in_token_amounts() {
+    let (mut contract, _) = setup_doubled();
+    let breakdown = contract.ft_supply_breakdown();
+    assert_eq!(breakdown.total_supply, U128(2_000));
+    assert_eq!(breakdown.circulating, U128(2_000));
+    assert_eq!(
+        contract
+            .supply_history(None, None)
+            .last()
+            .unwrap()
+            .total_supply,
+        U128(2_000)
+    );
+
+    contract.ft_mint(accounts(1), U128(500), None);
+    contract.ft_burn(U128(100), None);
+    let breakdown = contract.ft_supply_breakdown();
+    assert_eq!(breakdown.total_supply, U128(2_400));
+    assert_eq!(
+        (breakdown.minted, breakdown.burned),
+        (U128(1_500), U128(100))
+    );
+}
@@ -0,0 +1,172 @@
+use near_ft::fees::{FeeConfig, FeeSplit};
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::rebates::{RebateProgram, RebateTier};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+
+const EPOCH_NANOS: u64 = 60 * 1_000_000_000;
+
+// A 1% fee, `rebates_bps` of which funds the rebates and the rest goes to charlie's treasury.
+fn fee_config(rebates_bps: u16) -> FeeConfig {
+    FeeConfig {
+        fee_bps: 100,
+        split: FeeSplit {
+            stakers_bps: 0,
+            treasury_bps: 10_000 - rebates_bps,
+            burn_bps: 0,
+            rebates_bps,
+            insurance_bps: 0,
+        },
+        stakers_pool_id: accounts(2),
+        treasury_id: accounts(2),
+    }
+}
+
+// 10% of the fees fund the rebates. Accounts sending 1,000 tokens in a minute get 5% of their
+// fees back, and 10% from 10,000 tokens. Bob holds 100,000 tokens, and the context is left as
+// bob in the first epoch.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 0)
+        .balance(accounts(1), 100_000)
+        .balance(accounts(2), 0)
+        .with(|contract| {
+            contract.set_fee_config(Some(fee_config(1_000)));
+            contract.set_rebate_program(Some(RebateProgram {
+                epoch_duration_sec: 60,
+                tiers: vec![
+                    RebateTier {
+                        min_volume: U128(1_000),
+                        rebate_bps: 500,
+                    },
+                    RebateTier {
+                        min_volume: U128(10_000),
+                        rebate_bps: 1_000,
+                    },
+                ],
+                start_at: U64(0),
+            }));
+        })
+        .build();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    (contract, context)
+}
+
+fn send(contract: &mut Contract, amount: u128) {
+    contract.ft_transfer(accounts(0), U128(amount), None, None, None, None);
+}
+
+fn at_epoch(context: &mut VMContextBuilder, epoch: u64) {
+    testing_env!(context.block_timestamp(epoch * EPOCH_NANOS).build());
+}
+
+#[test]
+fn test_volume_is_tracked_during_the_epoch() {
+    let (mut contract, _) = setup();
+    send(&mut contract, 4_000);
+    send(&mut contract, 6_000);
+    let rebates = contract.get_fee_rebate(accounts(1));
+    assert_eq!(rebates.volume, U128(10_000));
+    assert_eq!(rebates.fees_paid, U128(100));
+    // Nothing accrues before the epoch is over.
+    assert_eq!(rebates.accrued, U128(0));
+    assert_eq!(contract.get_rebate_pool(), U128(10));
+}
+
+#[test]
+fn test_rebates_accrue_at_the_tier_reached() {
+    let (mut contract, mut context) = setup();
+    send(&mut contract, 10_000);
+    at_epoch(&mut context, 1);
+    assert_eq!(contract.get_fee_rebate(accounts(1)).accrued, U128(10));
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    assert_eq!(contract.get_fee_rebate(accounts(0)).accrued, U128(0));
+}
+
+#[test]
+fn test_lower_tiers_accrue_less() {
+    let (mut contract, mut context) = setup();
+    send(&mut contract, 2_000);
+    at_epoch(&mut context, 1);
+    assert_eq!(contract.get_fee_rebate(accounts(1)).accrued, U128(1));
+}
+
+#[test]
+fn test_volume_below_the_tiers_accrues_nothing() {
+    let (mut contract, mut context) = setup();
+    send(&mut contract, 900);
+    at_epoch(&mut context, 1);
+    let rebates = contract.get_fee_rebate(accounts(1));
+    assert_eq!(rebates.accrued, U128(0));
+    assert_eq!(rebates.volume, U128(0));
+}
+
+#[test]
+fn test_epoch_rollover_starts_a_new_volume() {
+    let (mut contract, mut context) = setup();
+    send(&mut contract, 10_000);
+    at_epoch(&mut context, 1);
+    send(&mut contract, 2_000);
+    let rebates = contract.get_fee_rebate(accounts(1));
+    assert_eq!(rebates.epoch, 1);
+    assert_eq!(rebates.volume, U128(2_000));
+    assert_eq!(rebates.fees_paid, U128(20));
+    assert_eq!(rebates.accrued, U128(10));
+
+    // An epoch without any transfer accrues nothing more.
+    at_epoch(&mut context, 3);
+    let rebates = contract.get_fee_rebate(accounts(1));
+    assert_eq!(rebates.epoch, 3);
+    assert_eq!(rebates.volume, U128(0));
+    assert_eq!(rebates.accrued, U128(11));
+}
+
+#[test]
+fn test_accrued_rebates_are_claimed() {
+    let (mut contract, mut context) = setup();
+    send(&mut contract, 10_000);
+    at_epoch(&mut context, 1);
+    assert_eq!(contract.claim_fee_rebate(), U128(10));
+
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(89_910));
+    assert_eq!(contract.get_rebate_pool(), U128(0));
+    let rebates = contract.get_fee_rebate(accounts(1));
+    assert_eq!(rebates.accrued, U128(0));
+    assert_eq!(rebates.claimed, U128(10));
+}
+
+#[test]
+fn test_exhausted_pool_pays_what_it_holds() {
+    let (mut contract, mut context) = setup();
+    // Only 5% of the fees fund the rebates, which pay up to 10% of them.
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_fee_config(Some(fee_config(500)));
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    send(&mut contract, 10_000);
+    at_epoch(&mut context, 1);
+
+    assert_eq!(contract.claim_fee_rebate(), U128(5));
+    assert_eq!(contract.get_rebate_pool(), U128(0));
+    // The rest stays accrued until the pool is funded again.
+    let rebates = contract.get_fee_rebate(accounts(1));
+    assert_eq!(rebates.accrued, U128(5));
+    assert_eq!(rebates.claimed, U128(5));
+}
+
+#[test]
+#[should_panic(expected = "There is no rebate to claim")]
+fn test_exhausted_pool_has_no_rebate_to_claim() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_fee_config(Some(fee_config(500)));
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    send(&mut contract, 10_000);
+    at_epoch(&mut context, 1);
+    contract.claim_fee_rebate();
+    contract.claim_fee_rebate();
+}
@@ -0,0 +1,77 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::receiver_list::ReceiverListMode;
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+// Bob is listed, charlie isn't.
+fn setup(mode: ReceiverListMode) -> Contract {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    contract.storage_deposit(Some(accounts(2)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.add_listed_receiver(accounts(1));
+    contract.set_receiver_list_mode(mode);
+    contract
+}
+
+fn transfer_call(contract: &mut Contract, receiver_index: usize) {
+    let _ = contract.ft_transfer_call(
+        accounts(receiver_index),
+        U128(100),
+        None,
+        String::new(),
+        None,
+        None,
+        None,
+    );
+}
+
+#[test]
+fn test_allowlist_lets_listed_receivers_be_called() {
+    let mut contract = setup(ReceiverListMode::Allowlist);
+    transfer_call(&mut contract, 1);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert!(contract.can_receive_transfer_calls(accounts(1)));
+    assert!(!contract.can_receive_transfer_calls(accounts(2)));
+}
+
+#[test]
+#[should_panic(expected = "The receiver can't be called with transfers")]
+fn test_allowlist_rejects_other_receivers() {
+    let mut contract = setup(ReceiverListMode::Allowlist);
+    transfer_call(&mut contract, 2);
+}
+
+#[test]
+#[should_panic(expected = "The receiver can't be called with transfers")]
+fn test_denylist_rejects_listed_receivers() {
+    let mut contract = setup(ReceiverListMode::Denylist);
+    transfer_call(&mut contract, 1);
+}
+
+#[test]
+fn test_plain_transfers_are_not_restricted() {
+    let mut contract = setup(ReceiverListMode::Allowlist);
+    contract.ft_transfer(accounts(2), U128(100), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(100));
+}
+
+#[test]
+fn test_removed_receivers_are_not_listed() {
+    let mut contract = setup(ReceiverListMode::Denylist);
+    contract.remove_listed_receiver(accounts(1));
+    assert!(!contract.is_listed_receiver(accounts(1)));
+    transfer_call(&mut contract, 1);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+}
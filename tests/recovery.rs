@@ -0,0 +1,108 @@
+use near_ft::recovery::RecoveryConfig;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::testing_env;
+
+const DAY: u64 = 86_400 * 1_000_000_000;
+
+// A council of bob, charlie and danny, two of them rotating the owner after 180 days of
+// inactivity and a 30-day timelock.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    contract.set_recovery_config(Some(RecoveryConfig {
+        council: vec![accounts(1), accounts(2), accounts(3)],
+        threshold: 2,
+        inactivity_sec: 180 * 86_400,
+        timelock_sec: 30 * 86_400,
+    }));
+    (contract, context)
+}
+
+fn call_at(context: &mut VMContextBuilder, account: usize, timestamp: u64) {
+    testing_env!(context
+        .predecessor_account_id(accounts(account))
+        .block_timestamp(timestamp)
+        .build());
+}
+
+#[test]
+fn test_council_rotates_an_inactive_owner() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 1, 180 * DAY);
+    contract.propose_owner_recovery(accounts(4));
+    assert!(get_logs()[0].contains("\"event\":\"owner_recovery_proposed\""));
+    call_at(&mut context, 2, 181 * DAY);
+    contract.approve_owner_recovery();
+    assert_eq!(
+        contract
+            .get_owner_recovery()
+            .unwrap()
+            .executable_at
+            .unwrap()
+            .0,
+        211 * DAY
+    );
+
+    call_at(&mut context, 3, 211 * DAY);
+    contract.execute_owner_recovery();
+    assert_eq!(contract.get_owner(), accounts(4));
+    assert!(contract.get_owner_recovery().is_none());
+    assert!(get_logs()[0].contains("\"event\":\"ownership_transferred\""));
+}
+
+#[test]
+fn test_owner_vetoes_a_recovery() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 1, 180 * DAY);
+    contract.propose_owner_recovery(accounts(4));
+    call_at(&mut context, 2, 181 * DAY);
+    contract.approve_owner_recovery();
+
+    call_at(&mut context, 0, 200 * DAY);
+    contract.owner_heartbeat();
+    assert!(get_logs()[0].contains("\"event\":\"owner_recovery_vetoed\""));
+    assert!(contract.get_owner_recovery().is_none());
+    assert_eq!(contract.get_owner_active_at().0, 200 * DAY);
+    assert_eq!(contract.get_owner(), accounts(0));
+}
+
+#[test]
+#[should_panic(expected = "The owner isn't inactive")]
+fn test_recovery_needs_an_inactive_owner() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 1, 179 * DAY);
+    contract.propose_owner_recovery(accounts(4));
+}
+
+#[test]
+#[should_panic(expected = "The recovery isn't executable yet")]
+fn test_recovery_waits_for_the_timelock() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 1, 180 * DAY);
+    contract.propose_owner_recovery(accounts(4));
+    call_at(&mut context, 2, 181 * DAY);
+    contract.approve_owner_recovery();
+    call_at(&mut context, 3, 210 * DAY);
+    contract.execute_owner_recovery();
+}
+
+#[test]
+#[should_panic(expected = "The recovery isn't executable yet")]
+fn test_recovery_needs_the_threshold() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 1, 180 * DAY);
+    contract.propose_owner_recovery(accounts(4));
+    call_at(&mut context, 1, 400 * DAY);
+    contract.execute_owner_recovery();
+}
+
+#[test]
+#[should_panic(expected = "Only the recovery council can call this method")]
+fn test_recovery_is_proposed_by_the_council() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 4, 180 * DAY);
+    contract.propose_owner_recovery(accounts(4));
+}
@@ -0,0 +1,90 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::insurance::{InsuranceConfig, MIN_INSURANCE_TIMELOCK_SEC};
+use near_ft::referendum::{ChallengedAction, ReferendumConfig};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::testing_env;
+
+const TIMELOCK_NANOS: u64 = MIN_INSURANCE_TIMELOCK_SEC * 1_000_000_000;
+const PAYOUT: ChallengedAction = ChallengedAction::InsurancePayout { claim_id: 0 };
+
+// Bob and charlie hold 300 tokens each. The owner slashed 100 tokens of danny into the
+// insurance fund and approved a payout of them to itself, which the holders can cancel by
+// staking 25% of the supply against it.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .build();
+    contract.ft_transfer(accounts(1), U128(300), None, None, None, None);
+    contract.ft_transfer(accounts(2), U128(300), None, None, None, None);
+    contract.ft_transfer(accounts(3), U128(100), None, None, None, None);
+    contract.freeze_account(accounts(3), 1, 3_600);
+    contract.slash_to_insurance(accounts(3), U128(100), "Exploit proceeds".to_string());
+    contract.set_insurance_config(Some(InsuranceConfig {
+        max_payout_per_claim: U128(100),
+        timelock_sec: MIN_INSURANCE_TIMELOCK_SEC,
+    }));
+    contract.approve_insurance_payout(accounts(0), U128(100), "Not an incident".to_string());
+    contract.set_referendum_config(Some(ReferendumConfig { quorum_bps: 2_500 }));
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    (contract, context)
+}
+
+#[test]
+fn test_quorum_cancels_the_action() {
+    let (mut contract, mut context) = setup();
+    let referendum_id = contract.vote_against_admin_action(PAYOUT, U128(200));
+    assert!(!contract.get_referendum(referendum_id).unwrap().cancelled);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    assert_eq!(
+        contract.vote_against_admin_action(PAYOUT, U128(50)),
+        referendum_id
+    );
+    let referendum = contract.get_referendum(referendum_id).unwrap();
+    assert!(referendum.cancelled);
+    assert_eq!(referendum.staked_against, U128(250));
+    assert!(contract.get_insurance_claim(0).is_none());
+    assert_eq!(contract.get_insurance_fund().balance, U128(100));
+
+    assert_eq!(contract.withdraw_referendum_stake(referendum_id), U128(50));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(300));
+}
+
+#[test]
+fn test_stakes_are_returned_after_the_timelock() {
+    let (mut contract, mut context) = setup();
+    let referendum_id = contract.vote_against_admin_action(PAYOUT, U128(200));
+    testing_env!(context.block_timestamp(TIMELOCK_NANOS).build());
+    contract.execute_insurance_payout(0);
+    assert_eq!(contract.withdraw_referendum_stake(referendum_id), U128(200));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(300));
+}
+
+#[test]
+#[should_panic(expected = "The referendum isn't over yet")]
+fn test_stakes_are_locked_during_the_referendum() {
+    let (mut contract, _) = setup();
+    let referendum_id = contract.vote_against_admin_action(PAYOUT, U128(200));
+    contract.withdraw_referendum_stake(referendum_id);
+}
+
+#[test]
+#[should_panic(expected = "The timelock of the action is over")]
+fn test_votes_end_with_the_timelock() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(TIMELOCK_NANOS).build());
+    contract.vote_against_admin_action(PAYOUT, U128(200));
+}
+
+#[test]
+#[should_panic(expected = "The action isn't waiting for its timelock")]
+fn test_only_timelocked_actions_can_be_challenged() {
+    let (mut contract, _) = setup();
+    contract.vote_against_admin_action(ChallengedAction::OwnerRecovery, U128(200));
+}
@@ -0,0 +1,209 @@
+use near_crypto::{KeyType, SecretKey, Signature};
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, CurveType, NearToken, PublicKey};
+
+const DEADLINE: u64 = 1_000_000_000;
+
+struct Holder {
+    key: SecretKey,
+    public_key: PublicKey,
+    account_id: AccountId,
+}
+
+impl Holder {
+    fn new() -> Self {
+        let key = SecretKey::from_seed(KeyType::ED25519, "holder");
+        let bytes = key.public_key().key_data().to_vec();
+        let public_key = PublicKey::from_parts(CurveType::ED25519, bytes.clone()).unwrap();
+        let account_id = bytes
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>()
+            .parse()
+            .unwrap();
+        Self {
+            key,
+            public_key,
+            account_id,
+        }
+    }
+
+    fn sign(&self, receiver_id: &AccountId, amount: u128, nonce: u64) -> Base64VecU8 {
+        let message = near_sdk::borsh::to_vec(&(
+            "near-ft",
+            env::current_account_id(),
+            "ft_transfer_with_permit",
+            (&self.account_id, receiver_id, amount, nonce, DEADLINE),
+        ))
+        .unwrap();
+        let Signature::ED25519(signature) = self.key.sign(&message) else {
+            unreachable!()
+        };
+        Base64VecU8(signature.to_bytes().to_vec())
+    }
+}
+
+// The implicit account of the holder gets 500 tokens, and charlie is a protocol-owned relayer
+// reimbursed at 1 yoctoNEAR per gas unit. The context is left as charlie.
+fn setup() -> (Contract, VMContextBuilder, Holder) {
+    let holder = Holder::new();
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    contract.storage_deposit(Some(holder.account_id.clone()), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_transfer(holder.account_id.clone(), U128(500), None, None, None, None);
+    contract.set_protocol_relayer(accounts(2), Some(NearToken::from_near(1)));
+    contract.set_relayer_gas_price(U128(1));
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    (contract, context, holder)
+}
+
+fn transfer_with_permit(contract: &mut Contract, holder: &Holder, nonce: u64) {
+    contract.ft_transfer_with_permit(
+        holder.public_key.clone(),
+        holder.sign(&accounts(1), 100, nonce),
+        nonce,
+        U64(DEADLINE),
+        accounts(1),
+        U128(100),
+    );
+}
+
+// Funds the pool with `amount` yoctoNEAR and reimburses charlie as alice.
+fn reimburse(contract: &mut Contract, context: &mut VMContextBuilder, amount: u128) -> NearToken {
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(amount))
+        .build());
+    contract.fund_relayer_gas_pool();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.reimburse_relayer(accounts(2))
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_relayed_calls_record_the_gas() {
+    let (mut contract, _, holder) = setup();
+    transfer_with_permit(&mut contract, &holder, 0);
+    let gas = contract
+        .get_relayer_usage(accounts(2))
+        .unwrap()
+        .unreimbursed_gas;
+    assert!(gas > 0);
+    transfer_with_permit(&mut contract, &holder, 1);
+
+    let usage = contract.get_relayer_usage(accounts(2)).unwrap();
+    assert_eq!(usage.calls, 2);
+    assert!(usage.unreimbursed_gas > gas);
+    assert_eq!(usage.reimbursed, NearToken::from_yoctonear(0));
+}
+
+#[test]
+fn test_other_relayers_are_not_recorded() {
+    let (mut contract, mut context, holder) = setup();
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    transfer_with_permit(&mut contract, &holder, 0);
+    assert!(contract.get_relayer_usage(accounts(3)).is_none());
+    assert_eq!(contract.get_relayer_usage(accounts(2)).unwrap().calls, 0);
+}
+
+#[test]
+fn test_relayer_is_reimbursed_for_its_gas() {
+    let (mut contract, mut context, holder) = setup();
+    transfer_with_permit(&mut contract, &holder, 0);
+    let gas = contract
+        .get_relayer_usage(accounts(2))
+        .unwrap()
+        .unreimbursed_gas as u128;
+
+    let reimbursed = reimburse(&mut contract, &mut context, 2 * gas);
+    assert_eq!(reimbursed, NearToken::from_yoctonear(gas));
+    assert_eq!(refunded_to(&accounts(2)), reimbursed);
+    let usage = contract.get_relayer_usage(accounts(2)).unwrap();
+    assert_eq!(usage.unreimbursed_gas, 0);
+    assert_eq!(usage.reimbursed, reimbursed);
+    assert_eq!(
+        contract.get_relayer_gas_pool().0,
+        NearToken::from_yoctonear(gas)
+    );
+}
+
+#[test]
+fn test_drained_pool_reimburses_what_it_holds() {
+    let (mut contract, mut context, holder) = setup();
+    transfer_with_permit(&mut contract, &holder, 0);
+    let gas = contract
+        .get_relayer_usage(accounts(2))
+        .unwrap()
+        .unreimbursed_gas;
+
+    let reimbursed = reimburse(&mut contract, &mut context, 1_000);
+    assert_eq!(reimbursed, NearToken::from_yoctonear(1_000));
+    assert_eq!(
+        contract.get_relayer_gas_pool().0,
+        NearToken::from_yoctonear(0)
+    );
+    // The gas that wasn't paid for stays owed.
+    let usage = contract.get_relayer_usage(accounts(2)).unwrap();
+    assert_eq!(usage.unreimbursed_gas, gas - 1_000);
+}
+
+#[test]
+#[should_panic(expected = "There is nothing to reimburse")]
+fn test_empty_pool_reimburses_nothing() {
+    let (mut contract, mut context, holder) = setup();
+    transfer_with_permit(&mut contract, &holder, 0);
+    reimburse(&mut contract, &mut context, 0);
+}
+
+#[test]
+fn test_reimbursements_stop_at_the_cap() {
+    let (mut contract, mut context, holder) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_protocol_relayer(accounts(2), Some(NearToken::from_yoctonear(500)));
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    transfer_with_permit(&mut contract, &holder, 0);
+
+    let reimbursed = reimburse(&mut contract, &mut context, 1_000_000_000_000_000);
+    assert_eq!(reimbursed, NearToken::from_yoctonear(500));
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can call this method")]
+fn test_only_the_owner_reimburses_relayers() {
+    let (mut contract, _, holder) = setup();
+    transfer_with_permit(&mut contract, &holder, 0);
+    contract.reimburse_relayer(accounts(2));
+}
@@ -0,0 +1,81 @@
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build()
+}
+
+fn stuck_token() -> AccountId {
+    "usdc.near".parse().unwrap()
+}
+
+#[test]
+fn test_owner_rescues_tokens() {
+    let (mut contract, _) = setup();
+    let _ = contract.rescue_ft(stuck_token(), U128(50), accounts(1));
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, stuck_token());
+    let MockAction::FunctionCallWeight { method_name, .. } = &receipts[0].actions[0] else {
+        panic!("Expected a function call");
+    };
+    assert_eq!(method_name, b"ft_transfer");
+}
+
+#[test]
+#[should_panic(expected = "This token can't be rescued")]
+fn test_own_token_cant_be_rescued() {
+    let (mut contract, context) = setup();
+    let _ = contract.rescue_ft(
+        context.context.current_account_id.clone(),
+        U128(50),
+        accounts(1),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can call this method")]
+fn test_only_the_owner_rescues() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    let _ = contract.rescue_ft(stuck_token(), U128(50), accounts(1));
+}
+
+#[test]
+fn test_owner_rescues_the_near_not_owed() {
+    let (mut contract, mut context) = setup();
+    let storage_cost = NearToken::from_yoctonear(
+        near_sdk::env::storage_byte_cost().as_yoctonear() * near_sdk::env::storage_usage() as u128,
+    );
+    let stuck = NearToken::from_near(3);
+    testing_env!(context
+        .account_balance(storage_cost.saturating_add(stuck))
+        .build());
+    // The attached yoctoNEAR is part of the balance too.
+    assert_eq!(
+        contract.get_rescuable_near(),
+        stuck.saturating_add(NearToken::from_yoctonear(1))
+    );
+    let _ = contract.rescue_near(stuck, accounts(1));
+    let receipts = get_created_receipts();
+    assert!(matches!(
+        receipts[0].actions[0],
+        MockAction::Transfer { deposit, .. } if deposit == stuck
+    ));
+}
+
+#[test]
+#[should_panic(expected = "The amount exceeds the rescuable NEAR")]
+fn test_owed_near_cant_be_rescued() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.account_balance(NearToken::from_near(1)).build());
+    let amount = contract
+        .get_rescuable_near()
+        .saturating_add(NearToken::from_yoctonear(1));
+    let _ = contract.rescue_near(amount, accounts(1));
+}
@@ -0,0 +1,201 @@
+use near_ft::ft_core::{CoSignerConfig, FungibleTokenCore};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+const TTL_NANOS: u64 = 60 * 1_000_000_000;
+
+// The owner reserved 300 tokens for bob, a protocol, for a minute, paying for its storage.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    reserve(&mut contract, &mut context, 300);
+    (contract, context)
+}
+
+// Reserves tokens for bob for a minute, returning the storage cost charged.
+fn reserve(contract: &mut Contract, context: &mut VMContextBuilder, amount: u128) -> NearToken {
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.reserve(U128(amount), accounts(1), 60);
+    let cost = NearToken::from_near(1).saturating_sub(refunded_to(&accounts(0)));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    cost
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_reserved_tokens_leave_the_balance() {
+    let (contract, _) = setup();
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(700));
+    let reservation = contract.get_reservation(0).unwrap();
+    assert_eq!(reservation.beneficiary_id, accounts(1));
+    assert_eq!(reservation.amount, U128(300));
+}
+
+#[test]
+fn test_beneficiary_commits_the_reservation() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    assert_eq!(contract.commit(0), U128(300));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(300));
+    assert!(contract.get_reservation(0).is_none());
+}
+
+#[test]
+fn test_the_fee_is_reserved_until_the_commit() {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .with(|contract| contract.set_transfer_fee(100, accounts(3)))
+        .build();
+    reserve(&mut contract, &mut context, 500);
+    assert_eq!(contract.get_reservation(0).unwrap().fee, U128(5));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    // The owner spends everything else, the fee is already reserved.
+    contract.ft_transfer(accounts(2), U128(490), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    assert_eq!(contract.commit(0), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(
+        Contract::ft_balance_of("token.near".parse().unwrap()),
+        U128(0)
+    );
+}
+
+// The owner reserved 500 tokens for bob, paying a 1% fee to danny. The context is left as the
+// owner.
+fn setup_with_fee() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(3), 0)
+        .with(|contract| contract.set_transfer_fee(100, accounts(3)))
+        .build();
+    reserve(&mut contract, &mut context, 500);
+    (contract, context)
+}
+
+#[test]
+fn test_commit_charges_the_reserved_fee_after_a_fee_change() {
+    let (mut contract, mut context) = setup_with_fee();
+    contract.set_transfer_fee(500, accounts(3));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    assert_eq!(contract.commit(0), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(5));
+    assert_eq!(
+        Contract::ft_balance_of("token.near".parse().unwrap()),
+        U128(0)
+    );
+}
+
+#[test]
+fn test_frozen_holder_gets_the_fee_back_once_the_fee_switch_is_off() {
+    let (mut contract, mut context) = setup_with_fee();
+    contract.set_fee_config(None);
+    contract.freeze_account(accounts(0), 1, 3_600);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    assert_eq!(contract.commit(0), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(0));
+}
+
+#[test]
+fn test_beneficiary_releases_the_reservation() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    assert_eq!(contract.release(0), U128(300));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_000));
+}
+
+#[test]
+fn test_holder_releases_an_expired_reservation() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(TTL_NANOS + 1).build());
+    contract.release(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_000));
+}
+
+#[test]
+#[should_panic(expected = "Only the beneficiary, or the holder once it expired, can release")]
+fn test_holder_cant_release_before_the_expiry() {
+    let (mut contract, _) = setup();
+    contract.release(0);
+}
+
+#[test]
+#[should_panic(expected = "The reservation has expired")]
+fn test_expired_reservations_cant_be_committed() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .block_timestamp(TTL_NANOS + 1)
+        .build());
+    contract.commit(0);
+}
+
+#[test]
+#[should_panic(expected = "Only the beneficiary can commit the reservation")]
+fn test_only_the_beneficiary_commits() {
+    let (mut contract, _) = setup();
+    contract.commit(0);
+}
+
+#[test]
+#[should_panic(expected = "Transfers above the co-signing threshold must be made with ft_transfer")]
+fn test_reservations_above_the_co_signing_threshold_are_rejected() {
+    let (mut contract, _) = setup();
+    contract.set_co_signer(
+        accounts(0),
+        Some(CoSignerConfig {
+            co_signer_id: accounts(2),
+            threshold: U128(100),
+            window_sec: 3_600,
+        }),
+    );
+    contract.reserve(U128(101), accounts(1), 60);
+}
+
+#[test]
+fn test_the_holder_gets_the_storage_back() {
+    let (mut contract, mut context) = setup();
+    let cost = reserve(&mut contract, &mut context, 100);
+    assert!(!cost.is_zero());
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.release(1);
+    assert_eq!(refunded_to(&accounts(0)), cost);
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_the_holder_pays_for_the_storage() {
+    let (mut contract, _) = setup();
+    contract.reserve(U128(100), accounts(1), 60);
+}
@@ -0,0 +1,142 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::grants::GrantTransferPolicy;
+use near_ft::royalties::PositionRoyalties;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+const SECOND: u64 = 1_000_000_000;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Receipt transfers pay 10% to the owner of the lock, and grant transfers 5% of the unclaimed
+// tokens to the issuer of the grant. Bob and charlie hold 100 tokens each.
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 2_000)
+        .balance(accounts(1), 100)
+        .balance(accounts(2), 100)
+        .balance(accounts(3), 0)
+        .with(|contract| {
+            contract.set_position_royalties(PositionRoyalties {
+                lock_receipts_bps: 1_000,
+                grants_bps: 500,
+            })
+        })
+        .build()
+}
+
+// Alice locks 500 tokens with receipts (lock 0) and sells 200 of them to charlie. The context
+// is left as charlie.
+fn setup_lock() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_millinear(10))
+        .build());
+    assert_eq!(contract.lock_tokens(U128(500), 60, Some(true)), 0);
+    contract.receipt_transfer(0, accounts(2), U128(200));
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    (contract, context)
+}
+
+// Alice grants bob 1,000 tokens vesting over 100 seconds, transferable after a 10 seconds
+// cliff (grant 0). The context is left as bob, after the cliff.
+fn setup_grant() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = setup();
+    contract.create_vesting_grant(
+        accounts(1),
+        U128(1_000),
+        None,
+        10,
+        100,
+        GrantTransferPolicy::AfterCliff,
+    );
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .block_timestamp(50 * SECOND)
+        .build());
+    (contract, context)
+}
+
+fn royalty_logs() -> Vec<String> {
+    get_logs()
+        .into_iter()
+        .filter(|log| log.contains("\"event\":\"position_royalty\""))
+        .collect()
+}
+
+#[test]
+fn test_lock_owners_pay_no_royalty_on_their_receipts() {
+    setup_lock();
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_500));
+}
+
+#[test]
+fn test_receipt_transfers_pay_the_lock_owner() {
+    let (mut contract, _) = setup_lock();
+    contract.receipt_transfer(0, accounts(3), U128(150));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(85));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_515));
+    // The royalty is paid in tokens: the receiver gets every receipt token.
+    assert_eq!(contract.receipt_balance_of(0, accounts(3)), U128(150));
+
+    let logs = royalty_logs();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].contains("\"position\":\"lock\""));
+    assert!(logs[0].contains(&format!("\"payer_id\":\"{}\"", accounts(2))));
+    assert!(logs[0].contains(&format!("\"issuer_id\":\"{}\"", accounts(0))));
+    assert!(logs[0].contains("\"amount\":\"15\""));
+}
+
+#[test]
+#[should_panic(expected = "The account doesn't have enough balance")]
+fn test_receipt_transfers_need_the_royalty() {
+    let (mut contract, mut context) = setup_lock();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_transfer(accounts(3), U128(100), None, None, None, None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_millinear(10))
+        .build());
+    contract.receipt_transfer(0, accounts(3), U128(150));
+}
+
+#[test]
+fn test_grant_transfers_pay_the_issuer() {
+    let (mut contract, _) = setup_grant();
+    contract.nft_transfer(accounts(2), "0".to_string(), None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(50));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_050));
+
+    let logs = royalty_logs();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].contains("\"position\":\"grant\""));
+    assert!(logs[0].contains(&format!("\"payer_id\":\"{}\"", accounts(1))));
+    assert!(logs[0].contains(&format!("\"issuer_id\":\"{}\"", accounts(0))));
+    assert!(logs[0].contains("\"amount\":\"50\""));
+}
+
+#[test]
+fn test_grant_royalties_only_apply_to_the_unclaimed_tokens() {
+    let (mut contract, _) = setup_grant();
+    assert_eq!(contract.claim_vesting_grant(0), U128(500));
+    contract.nft_transfer(accounts(2), "0".to_string(), None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(575));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_025));
+}
+
+#[test]
+fn test_issuers_pay_no_royalty_on_their_grants() {
+    let (mut contract, mut context) = setup_grant();
+    contract.nft_transfer(accounts(0), "0".to_string(), None, None);
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.nft_transfer(accounts(2), "0".to_string(), None, None);
+    assert!(royalty_logs().is_empty());
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_050));
+}
@@ -0,0 +1,248 @@
+use near_ft::ft_core::{CoSignerConfig, FungibleTokenCore};
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+const SECOND: u64 = 1_000_000_000;
+
+// Alice schedules a payout of 100 tokens to bob in an hour, paying for its storage.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build();
+    schedule(&mut contract, &mut context, 100);
+    (contract, context)
+}
+
+// Schedules a payout to bob in an hour, returning the storage cost charged.
+fn schedule(contract: &mut Contract, context: &mut VMContextBuilder, amount: u128) -> NearToken {
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.ft_transfer_scheduled(accounts(1), U128(amount), U64(3_600 * SECOND));
+    assert!(get_logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"transfer_scheduled\"")));
+    let cost = NearToken::from_near(1).saturating_sub(refunded_to(&accounts(0)));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    cost
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_anyone_executes_the_transfer_after_the_unlock_time() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(900));
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(3_600 * SECOND)
+        .build());
+    contract.execute_scheduled(0);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert!(contract.get_scheduled_transfer(0).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The scheduled transfer is still locked")]
+fn test_transfer_is_executed_after_the_unlock_time() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(3_600 * SECOND - 1).build());
+    contract.execute_scheduled(0);
+}
+
+#[test]
+fn test_sender_cancels_before_the_unlock_time() {
+    let (mut contract, _) = setup();
+    contract.cancel_scheduled(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_000));
+    assert!(contract.get_scheduled_transfer(0).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The scheduled transfer is already unlocked")]
+fn test_transfer_is_cancelled_before_the_unlock_time() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(3_600 * SECOND).build());
+    contract.cancel_scheduled(0);
+}
+
+#[test]
+#[should_panic(expected = "Only the sender can cancel the transfer")]
+fn test_only_the_sender_cancels() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.cancel_scheduled(0);
+}
+
+#[test]
+#[should_panic(expected = "Can't unregister the account while pending transfers refer to it")]
+fn test_receiver_cant_unregister_before_the_execution() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.storage_unregister(Some(true));
+}
+
+#[test]
+fn test_receiver_unregisters_once_the_transfer_is_cancelled() {
+    let (mut contract, mut context) = setup();
+    contract.cancel_scheduled(0);
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    assert!(contract.storage_unregister(None));
+    assert!(Contract::storage_balance_of(accounts(1)).is_none());
+}
+
+#[test]
+fn test_the_fee_is_escrowed_until_the_execution() {
+    let (mut contract, mut context) = setup();
+    contract.cancel_scheduled(0);
+    contract.set_transfer_fee(100, accounts(2));
+    schedule(&mut contract, &mut context, 500);
+    assert_eq!(contract.get_scheduled_transfer(1).unwrap().fee, U128(5));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    // Alice spends everything else, the fee is already escrowed.
+    for _ in 0..5 {
+        contract.ft_transfer(accounts(2), U128(99), None, None, None, None);
+    }
+
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(3_600 * SECOND)
+        .build());
+    contract.execute_scheduled(1);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(0));
+}
+
+// Alice schedules a payout of 500 tokens to bob in an hour, paying a 1% fee to charlie, then
+// gets frozen. The context is left as danny, a keeper, at the unlock time.
+fn setup_frozen_sender() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .with(|contract| contract.set_transfer_fee(100, accounts(2)))
+        .build();
+    schedule(&mut contract, &mut context, 500);
+    contract.freeze_account(accounts(0), 1, 7_200);
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(3_600 * SECOND)
+        .build());
+    (contract, context)
+}
+
+#[test]
+fn test_frozen_sender_doesnt_block_the_execution() {
+    let (mut contract, mut context) = setup_frozen_sender();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_transfer_fee(500, accounts(2));
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+
+    contract.execute_scheduled(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(495));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    // The escrowed fee is charged, not the one as of the execution.
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(5));
+    assert_eq!(
+        Contract::ft_balance_of("token.near".parse().unwrap()),
+        U128(0)
+    );
+}
+
+#[test]
+fn test_frozen_sender_gets_the_fee_back_once_the_fee_switch_is_off() {
+    let (mut contract, mut context) = setup_frozen_sender();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_fee_config(None);
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+
+    contract.execute_scheduled(0);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(0));
+}
+
+#[test]
+fn test_the_sender_gets_the_storage_back() {
+    let (mut contract, mut context) = setup();
+    let cost = schedule(&mut contract, &mut context, 100);
+    assert!(cost > NearToken::from_yoctonear(0));
+
+    contract.cancel_scheduled(1);
+    assert_eq!(refunded_to(&accounts(0)), cost);
+
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .block_timestamp(3_600 * SECOND)
+        .build());
+    contract.execute_scheduled(0);
+    // The last transfer also releases the pending references the first one paid for.
+    assert!(refunded_to(&accounts(0)) > cost);
+    assert_eq!(refunded_to(&accounts(3)), NearToken::from_yoctonear(0));
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_the_sender_pays_for_the_storage() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_scheduled(accounts(1), U128(100), U64(3_600 * SECOND));
+}
+
+#[test]
+#[should_panic(expected = "Transfers above the co-signing threshold must be made with ft_transfer")]
+fn test_transfers_above_the_co_signing_threshold_arent_scheduled() {
+    let (mut contract, mut context) = setup();
+    contract.set_co_signer(
+        accounts(0),
+        Some(CoSignerConfig {
+            co_signer_id: accounts(2),
+            threshold: U128(100),
+            window_sec: 3_600,
+        }),
+    );
+    schedule(&mut contract, &mut context, 101);
+}
+
+#[test]
+#[should_panic(expected = "The unlock time should be in the future")]
+fn test_unlock_time_is_in_the_future() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(10 * SECOND).build());
+    contract.ft_transfer_scheduled(accounts(1), U128(100), U64(10 * SECOND));
+}
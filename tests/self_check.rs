@@ -0,0 +1,23 @@
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId};
+
+#[test]
+fn test_self_check_leaves_no_trace() {
+    let contract_id: AccountId = "token.near".parse().unwrap();
+    testing_env!(VMContextBuilder::new()
+        .current_account_id(contract_id.clone())
+        .predecessor_account_id(contract_id)
+        .build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    let state_root = contract.state_root();
+    let next_leaf_index = contract.next_balance_leaf_index;
+
+    contract.self_check();
+
+    assert_eq!(contract.state_root(), state_root);
+    assert_eq!(contract.next_balance_leaf_index, next_leaf_index);
+    assert!(Contract::storage_balance_of("self-check.token.near".parse().unwrap()).is_none());
+}
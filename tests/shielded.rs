@@ -0,0 +1,153 @@
+#![cfg(feature = "shielded")]
+
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken, PromiseResult, RuntimeFeesConfig};
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+fn nullifier() -> Base64VecU8 {
+    Base64VecU8(vec![7; 32])
+}
+
+// Bob shields 100 tokens, and charlie asks to unshield 60 of them to their own account.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 100)
+        .balance(accounts(2), 0)
+        .build();
+    contract.set_shielded_verifier("verifier.near".parse().unwrap());
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    assert_eq!(contract.shield(U128(100), Base64VecU8(vec![1; 32])), 0);
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.unshield(nullifier(), Base64VecU8(vec![]), accounts(2), U128(60));
+    (contract, context)
+}
+
+fn verified_with(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    result: PromiseResult,
+) -> bool {
+    testing_env!(
+        context.predecessor_account_id(contract_id()).build(),
+        near_sdk::test_vm_config(),
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![result]
+    );
+    contract.on_unshield_verified(nullifier(), accounts(2), U128(60))
+}
+
+#[test]
+fn test_shielding_moves_the_tokens_into_the_pool() {
+    let (contract, _) = setup();
+    let pool = contract.get_shielded_pool();
+    assert_eq!(pool.balance, U128(100));
+    assert_eq!(pool.commitments_count, 1);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+    assert_eq!(Contract::ft_balance_of(contract_id()), U128(100));
+}
+
+#[test]
+fn test_verified_proofs_release_the_tokens() {
+    let (mut contract, mut context) = setup();
+    assert!(verified_with(
+        &mut contract,
+        &mut context,
+        PromiseResult::Successful(b"true".to_vec())
+    ));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(60));
+    assert_eq!(contract.get_shielded_pool().balance, U128(40));
+    assert!(contract.is_nullifier_spent(nullifier()));
+}
+
+#[test]
+fn test_rejected_proofs_unlock_the_nullifier() {
+    let (mut contract, mut context) = setup();
+    assert!(!verified_with(
+        &mut contract,
+        &mut context,
+        PromiseResult::Successful(b"false".to_vec())
+    ));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(0));
+    assert!(!contract.is_nullifier_spent(nullifier()));
+
+    // The nullifier can be used with another proof.
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.unshield(nullifier(), Base64VecU8(vec![]), accounts(2), U128(60));
+}
+
+#[test]
+#[should_panic(expected = "The nullifier was already used")]
+fn test_nullifiers_are_locked_during_the_verification() {
+    let (mut contract, _) = setup();
+    contract.unshield(nullifier(), Base64VecU8(vec![]), accounts(2), U128(10));
+}
+
+#[test]
+#[should_panic(expected = "The nullifier was already used")]
+fn test_nullifiers_are_spent_once() {
+    let (mut contract, mut context) = setup();
+    verified_with(
+        &mut contract,
+        &mut context,
+        PromiseResult::Successful(b"true".to_vec()),
+    );
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.unshield(nullifier(), Base64VecU8(vec![]), accounts(2), U128(10));
+}
+
+#[test]
+#[should_panic(expected = "The shielded pool balance is too low")]
+fn test_unshielding_is_capped_by_the_pool() {
+    let (mut contract, _) = setup();
+    contract.unshield(
+        Base64VecU8(vec![8; 32]),
+        Base64VecU8(vec![]),
+        accounts(2),
+        U128(101),
+    );
+}
+
+#[test]
+#[should_panic(expected = "The commitment should be 32 bytes")]
+fn test_commitments_are_32_bytes() {
+    let (mut contract, _) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    contract.shield(U128(100), Base64VecU8(vec![1; 31]));
+}
+
+#[test]
+#[should_panic(expected = "The shielded verifier is not set")]
+fn test_unshielding_needs_a_verifier() {
+    let (mut contract, _) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    contract.unshield(nullifier(), Base64VecU8(vec![]), accounts(0), U128(0));
+}
+
+#[test]
+#[should_panic(expected = "The account bob is frozen")]
+fn test_frozen_accounts_cant_shield() {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 100)
+        .build();
+    contract.freeze_account(accounts(1), 1, 3_600);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.shield(U128(100), Base64VecU8(vec![1; 32]));
+}
@@ -0,0 +1,127 @@
+use near_crypto::{KeyType, SecretKey, Signature};
+use near_ft::onboarding::OnboardingBundle;
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, CurveType, PublicKey};
+
+fn relay_key() -> SecretKey {
+    SecretKey::from_seed(KeyType::ED25519, "alice")
+}
+
+fn relay_public_key() -> PublicKey {
+    PublicKey::from_parts(
+        CurveType::ED25519,
+        relay_key().public_key().key_data().to_vec(),
+    )
+    .unwrap()
+}
+
+// Onboarding is the simplest of the features relaying signed messages.
+fn onboard(
+    contract: &mut Contract,
+    context: &mut VMContextBuilder,
+    receiver_id: AccountId,
+    nonce: u64,
+) {
+    let bundle = OnboardingBundle {
+        sender_id: accounts(0),
+        receiver_id,
+        amount: U128(10),
+        memo: None,
+        nonce,
+        expires_at: U64(u64::MAX),
+    };
+    let message =
+        near_sdk::borsh::to_vec(&("near-ft", env::current_account_id(), "onboard", &bundle))
+            .unwrap();
+    let Signature::ED25519(signature) = relay_key().sign(&message) else {
+        unreachable!()
+    };
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.onboard(bundle, Base64VecU8(signature.to_bytes().to_vec()));
+}
+
+// Alice holds 1,000 tokens and signs with her relay key.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    contract.set_relay_key(Some(relay_public_key()));
+    (contract, context)
+}
+
+#[test]
+fn test_relay_keys() {
+    let (mut contract, _) = setup();
+    assert_eq!(
+        contract.get_relay_key(accounts(0)),
+        Some(relay_public_key())
+    );
+    contract.set_relay_key(None);
+    assert_eq!(contract.get_relay_key(accounts(0)), None);
+}
+
+#[test]
+#[should_panic(expected = "Only ed25519 relay keys are supported")]
+fn test_relay_keys_are_ed25519() {
+    let (mut contract, _) = setup();
+    contract.set_relay_key(Some(
+        PublicKey::from_parts(CurveType::SECP256K1, vec![1; 64]).unwrap(),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "The account bob is not registered")]
+fn test_relay_keys_are_set_by_registered_accounts() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.set_relay_key(Some(relay_public_key()));
+}
+
+#[test]
+fn test_nonces_can_skip_ahead() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(contract.get_relay_nonce(accounts(0)), 0);
+    onboard(&mut contract, &mut context, accounts(1), 5);
+    assert_eq!(contract.get_relay_nonce(accounts(0)), 6);
+    onboard(&mut contract, &mut context, accounts(3), 6);
+    assert_eq!(contract.get_relay_nonce(accounts(0)), 7);
+}
+
+#[test]
+#[should_panic(expected = "The nonce was already used")]
+fn test_bumping_the_nonce_invalidates_the_signed_messages() {
+    let (mut contract, mut context) = setup();
+    contract.bump_relay_nonce(10);
+    assert_eq!(contract.get_relay_nonce(accounts(0)), 10);
+    onboard(&mut contract, &mut context, accounts(1), 9);
+}
+
+#[test]
+#[should_panic(expected = "The nonce can only move forward")]
+fn test_nonces_only_move_forward() {
+    let (mut contract, _) = setup();
+    contract.bump_relay_nonce(10);
+    contract.bump_relay_nonce(10);
+}
+
+#[test]
+#[should_panic(expected = "The nonce is out of range")]
+fn test_the_last_nonce_is_rejected() {
+    let (mut contract, mut context) = setup();
+    onboard(&mut contract, &mut context, accounts(1), u64::MAX);
+}
+
+#[test]
+#[should_panic(expected = "The signer has no relay key")]
+fn test_signers_need_a_relay_key() {
+    let (mut contract, mut context) = setup();
+    contract.set_relay_key(None);
+    onboard(&mut contract, &mut context, accounts(1), 0);
+}
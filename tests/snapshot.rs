@@ -0,0 +1,71 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build()
+}
+
+#[test]
+fn test_balances_are_frozen_at_snapshots() {
+    let (mut contract, mut context) = setup();
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    testing_env!(context.block_height(10).build());
+    assert_eq!(contract.snapshot(), 0);
+    assert!(get_logs()[0].contains("\"event\":\"snapshot_taken\""));
+    assert_eq!(contract.get_snapshot(0).unwrap().block_height.0, 10);
+
+    contract.ft_transfer(accounts(1), U128(200), None, None, None, None);
+    contract.ft_transfer(accounts(1), U128(50), None, None, None, None);
+    contract.ft_mint(accounts(0), U128(500), None);
+    assert_eq!(contract.snapshot(), 1);
+    contract.ft_burn(U128(150), None);
+
+    assert_eq!(contract.balance_of_at(accounts(0), 0), U128(900));
+    assert_eq!(contract.balance_of_at(accounts(1), 0), U128(100));
+    assert_eq!(contract.total_supply_at(0), U128(1_000));
+    assert_eq!(contract.balance_of_at(accounts(0), 1), U128(1_150));
+    // Bob's balance didn't change since the second snapshot.
+    assert_eq!(contract.balance_of_at(accounts(1), 1), U128(350));
+    assert_eq!(contract.total_supply_at(1), U128(1_500));
+    assert_eq!(contract.ft_total_supply(), U128(1_350));
+}
+
+#[test]
+fn test_accounts_registered_after_a_snapshot_had_nothing() {
+    let (mut contract, mut context) = setup();
+    contract.snapshot();
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(2)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_transfer(accounts(2), U128(100), None, None, None, None);
+
+    assert_eq!(contract.balance_of_at(accounts(2), 0), U128(0));
+    assert_eq!(contract.balance_of_at(accounts(3), 0), U128(0));
+}
+
+#[test]
+#[should_panic(expected = "The snapshot doesn't exist")]
+fn test_balance_of_at_needs_a_snapshot() {
+    let (contract, _) = setup();
+    contract.balance_of_at(accounts(0), 0);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can call this method")]
+fn test_snapshot_is_taken_by_the_owner() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.snapshot();
+}
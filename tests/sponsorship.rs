@@ -0,0 +1,95 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+// Charlie funded the pool with enough NEAR for two registrations.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(Contract::storage_balance_bounds().min.saturating_mul(2))
+        .build());
+    contract.fund_sponsorship_pool();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+#[test]
+fn test_pool_registers_first_time_receivers() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert!(Contract::storage_balance_of(accounts(1)).is_some());
+    assert_eq!(
+        contract.get_sponsorship_pool().balance,
+        Contract::storage_balance_bounds().min
+    );
+    assert_eq!(contract.get_sponsored_registrations(accounts(0)), 1);
+
+    // Registered receivers cost the pool nothing.
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    assert_eq!(contract.get_sponsored_registrations(accounts(0)), 1);
+}
+
+#[test]
+#[should_panic(expected = "is not registered")]
+fn test_capped_senders_need_registered_receivers() {
+    let (mut contract, _) = setup();
+    contract.set_sponsorship_cap(1);
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    contract.ft_transfer(accounts(3), U128(100), None, None, None, None);
+}
+
+#[test]
+#[should_panic(expected = "is not registered")]
+fn test_empty_pool_registers_nobody() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    contract.ft_transfer(accounts(3), U128(100), None, None, None, None);
+    contract.ft_transfer(accounts(4), U128(100), None, None, None, None);
+}
+
+#[test]
+fn test_unregistering_refunds_the_pool() {
+    let (mut contract, mut context) = setup();
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(0), U128(100), None, None, None, None);
+    assert!(contract.storage_unregister(None));
+    assert_eq!(
+        contract.get_sponsorship_pool().balance,
+        Contract::storage_balance_bounds().min.saturating_mul(2)
+    );
+    let receipts = get_created_receipts();
+    assert!(matches!(
+        receipts.last().unwrap().actions[0],
+        MockAction::Transfer { deposit, .. } if deposit == NearToken::from_yoctonear(1)
+    ));
+}
+
+#[test]
+fn test_owner_withdraws_the_pool() {
+    let (mut contract, _) = setup();
+    contract.withdraw_sponsorship_pool(Contract::storage_balance_bounds().min);
+    assert_eq!(
+        contract.get_sponsorship_pool().balance,
+        Contract::storage_balance_bounds().min
+    );
+}
+
+#[test]
+#[should_panic(expected = "The sponsorship pool is too low")]
+fn test_withdrawals_are_limited_to_the_pool() {
+    let (mut contract, _) = setup();
+    contract.withdraw_sponsorship_pool(Contract::storage_balance_bounds().min.saturating_mul(3));
+}
@@ -0,0 +1,209 @@
+#![cfg(feature = "stablecoin")]
+
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::health::{HealthIssue, HealthStatus};
+use near_ft::oracle::PriceFeedConfig;
+use near_ft::stablecoin::{StablecoinConfig, COLLATERAL_PRICE_FEED};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+const NEAR: u128 = 10u128.pow(24);
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+// Danny pushes the price of NEAR: 5 tokens (with 6 decimals). Vaults need 150% of their debt,
+// and are liquidated below 120% with a 5% bonus. Bob deposited 3 NEAR.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build();
+    contract.set_stablecoin_config(StablecoinConfig {
+        oracle_id: accounts(3),
+        collateral_ratio_bps: 15_000,
+        liquidation_ratio_bps: 12_000,
+        liquidation_bonus_bps: 500,
+        max_price_age_sec: 3_600,
+    });
+    push_price(&mut contract, &mut context, 5_000_000);
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_near(3))
+        .build());
+    contract.deposit_collateral();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+fn push_price(contract: &mut Contract, context: &mut VMContextBuilder, tokens_per_near: u128) {
+    let caller_id = context.context.predecessor_account_id.clone();
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    contract.push_collateral_price(U128(tokens_per_near));
+    testing_env!(context.predecessor_account_id(caller_id).build());
+}
+
+#[test]
+fn test_mint_and_repay_against_collateral() {
+    let (mut contract, _) = setup();
+    let vault = contract.mint_against_collateral(U128(10_000_000));
+    assert_eq!(vault.debt, U128(10_000_000));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(10_000_000));
+    assert_eq!(contract.get_vault_ratio(accounts(1)), Some(U128(15_000)));
+
+    contract.repay_debt(U128(4_000_000));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(6_000_000));
+    let health = contract.get_stablecoin_health();
+    assert_eq!(health.total_debt, U128(6_000_000));
+    assert_eq!(health.total_collateral, U128(3 * NEAR));
+    assert!(health.price_is_fresh);
+    assert_eq!(contract.ft_total_supply(), U128(6_001_000));
+}
+
+#[test]
+#[should_panic(expected = "The vault would be undercollateralized")]
+fn test_mints_keep_the_collateral_ratio() {
+    let (mut contract, _) = setup();
+    contract.mint_against_collateral(U128(10_000_001));
+}
+
+#[test]
+#[should_panic(expected = "The collateral price is stale")]
+fn test_mints_need_a_fresh_price() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.block_timestamp(3_601 * NANOS_PER_SEC).build());
+    contract.mint_against_collateral(U128(1));
+}
+
+#[test]
+fn test_health_check_reports_a_stale_price() {
+    let (contract, mut context) = setup();
+    assert!(!contract
+        .health_check()
+        .issues
+        .contains(&HealthIssue::StaleCollateralPrice));
+
+    testing_env!(context.block_timestamp(3_601 * NANOS_PER_SEC).build());
+    let report = contract.health_check();
+    assert_eq!(report.status, HealthStatus::Degraded);
+    assert!(report.issues.contains(&HealthIssue::StaleCollateralPrice));
+}
+
+#[test]
+fn test_collateral_is_withdrawn_while_collateralized() {
+    let (mut contract, _) = setup();
+    contract.mint_against_collateral(U128(5_000_000));
+    let _ = contract.withdraw_collateral(U128(NEAR + NEAR / 2));
+    assert_eq!(
+        contract.get_vault(accounts(1)).unwrap().collateral,
+        U128(NEAR + NEAR / 2)
+    );
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, accounts(1));
+    assert!(matches!(
+        receipts[0].actions[0],
+        MockAction::Transfer { deposit, .. } if deposit == NearToken::from_yoctonear(NEAR + NEAR / 2)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "The vault would be undercollateralized")]
+fn test_withdrawals_keep_the_collateral_ratio() {
+    let (mut contract, _) = setup();
+    contract.mint_against_collateral(U128(5_000_000));
+    let _ = contract.withdraw_collateral(U128(NEAR + NEAR / 2 + 1));
+}
+
+#[test]
+fn test_undercollateralized_vaults_are_liquidated() {
+    let (mut contract, mut context) = setup();
+    contract.mint_against_collateral(U128(10_000_000));
+    contract.ft_transfer(accounts(2), U128(10_000_000), None, None, None, None);
+    // The collateral is now worth 12 tokens, less than 120% of the debt.
+    push_price(&mut contract, &mut context, 3_999_999);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    let _ = contract.liquidate_vault(accounts(1));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(0));
+    // The liquidator gets 10.5 tokens worth of NEAR.
+    let collateral = 2_625_000_656_000_000_000_000_000;
+    let vault = contract.get_vault(accounts(1)).unwrap();
+    assert_eq!(vault.debt, U128(0));
+    assert_eq!(vault.collateral, U128(3 * NEAR - collateral));
+    assert!(get_logs()
+        .last()
+        .unwrap()
+        .contains("\"event\":\"vault_liquidated\""));
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, accounts(2));
+    assert!(matches!(
+        receipts[0].actions[0],
+        MockAction::Transfer { deposit, .. } if deposit == NearToken::from_yoctonear(collateral)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "The vault is collateralized enough")]
+fn test_healthy_vaults_arent_liquidated() {
+    let (mut contract, mut context) = setup();
+    contract.mint_against_collateral(U128(10_000_000));
+    push_price(&mut contract, &mut context, 4_000_000);
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    let _ = contract.liquidate_vault(accounts(1));
+}
+
+#[test]
+#[should_panic(expected = "Only the oracle can call this method")]
+fn test_only_the_oracle_pushes_prices() {
+    let (mut contract, _) = setup();
+    contract.push_collateral_price(U128(1));
+}
+
+#[test]
+fn test_price_feed_values_the_collateral() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_price_feed(
+        COLLATERAL_PRICE_FEED.to_string(),
+        Some(PriceFeedConfig {
+            oracle_ids: vec![accounts(3), accounts(4)],
+            min_answers: 2,
+            max_age_sec: 3_600,
+            max_divergence_bps: 1_000,
+        }),
+    );
+    assert!(!contract.get_stablecoin_health().price_is_fresh);
+    for (oracle, price) in [(3, 4_000_000), (4, 4_200_000)] {
+        testing_env!(context.predecessor_account_id(accounts(oracle)).build());
+        contract.push_oracle_price(COLLATERAL_PRICE_FEED.to_string(), U128(price));
+    }
+
+    // 3 NEAR at 4.1 tokens back at most 8.2 tokens at 150%.
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.mint_against_collateral(U128(8_200_000));
+    let health = contract.get_stablecoin_health();
+    assert!(health.price_is_fresh);
+    assert_eq!(health.price.unwrap().tokens_per_near, U128(4_100_000));
+}
+
+#[test]
+#[should_panic(expected = "The collateral price comes from the price feed")]
+fn test_oracle_account_cant_push_once_the_feed_is_configured() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_price_feed(
+        COLLATERAL_PRICE_FEED.to_string(),
+        Some(PriceFeedConfig {
+            oracle_ids: vec![accounts(4)],
+            min_answers: 1,
+            max_age_sec: 3_600,
+            max_divergence_bps: 1_000,
+        }),
+    );
+    push_price(&mut contract, &mut context, 5_000_000);
+}
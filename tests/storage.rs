@@ -0,0 +1,269 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::storage::{
+    StorageManagement, BYTES_FOR_LONGEST_ACCOUNT_ID, BYTES_FOR_LONGEST_DEPOSITOR_RECORD,
+    BYTES_PER_REGISTRATION, FORCE_UNREGISTER_DELAY_SEC,
+};
+use near_ft::storage_keys::*;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, NearToken};
+
+#[test]
+fn test_bytes_for_longest_account_id() {
+    testing_env!(VMContextBuilder::new().build());
+    let mut accounts: LookupMap<AccountId, u128> =
+        LookupMap::new(StorageKey::Core(CoreKey::Accounts));
+    let longest_account_id: AccountId = "a".repeat(64).parse().unwrap();
+
+    let initial_storage_usage = env::storage_usage();
+    accounts.insert(&longest_account_id, &0);
+    assert_eq!(
+        env::storage_usage() - initial_storage_usage,
+        BYTES_FOR_LONGEST_ACCOUNT_ID
+    );
+}
+
+#[test]
+fn test_bytes_for_longest_depositor_record() {
+    testing_env!(VMContextBuilder::new().build());
+    let mut depositors: LookupMap<AccountId, AccountId> =
+        LookupMap::new(StorageKey::Storage(StorageManagementKey::Depositors));
+    let longest_account_id: AccountId = "a".repeat(64).parse().unwrap();
+
+    let initial_storage_usage = env::storage_usage();
+    depositors.insert(&longest_account_id, &longest_account_id);
+    assert_eq!(
+        env::storage_usage() - initial_storage_usage,
+        BYTES_FOR_LONGEST_DEPOSITOR_RECORD
+    );
+}
+
+#[test]
+fn test_registration_for_someone_else_is_covered() {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    let longest_account_id: AccountId = "a".repeat(64).parse().unwrap();
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+
+    let initial_storage_usage = env::storage_usage();
+    contract.storage_deposit(Some(longest_account_id), None);
+    assert!(env::storage_usage() - initial_storage_usage <= BYTES_PER_REGISTRATION);
+}
+
+#[test]
+fn test_balances_stored_as_near_tokens_are_read_as_is() {
+    // Balances used to be stored as `NearToken`, which has the same borsh encoding as `u128`,
+    // so the state written before needs no migration.
+    testing_env!(VMContextBuilder::new().build());
+    let mut balances: LookupMap<AccountId, NearToken> =
+        LookupMap::new(StorageKey::Core(CoreKey::Accounts));
+    balances.insert(&accounts(1), &NearToken::from_yoctonear(1_000));
+
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(1_000));
+}
+
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    context.attached_deposit(Contract::storage_balance_bounds().min);
+    testing_env!(context.build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    (contract, context)
+}
+
+#[test]
+fn test_storage_withdraw_has_nothing_available() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    let balance = contract.storage_withdraw(None);
+    assert_eq!(balance.total, Contract::storage_balance_bounds().min);
+    assert_eq!(balance.available, NearToken::from_yoctonear(0));
+    contract.storage_withdraw(Some(NearToken::from_yoctonear(0)));
+}
+
+#[test]
+#[should_panic(expected = "Nothing can be withdrawn")]
+fn test_storage_withdraw_more_than_available() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.storage_withdraw(Some(NearToken::from_yoctonear(1)));
+}
+
+#[test]
+fn test_storage_unregister() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    assert!(contract.storage_unregister(None));
+    assert!(Contract::storage_balance_of(accounts(1)).is_none());
+    assert!(contract.get_storage_depositor(accounts(1)).is_none());
+
+    // Alice paid for the registration of bob, so she gets the deposit back.
+    let receipts = get_created_receipts();
+    assert_eq!(receipts.len(), 2);
+    assert_eq!(receipts[0].receiver_id, accounts(0));
+    assert!(matches!(
+        receipts[0].actions[0],
+        MockAction::Transfer { deposit, .. } if deposit == Contract::storage_balance_bounds().min
+    ));
+    assert_eq!(receipts[1].receiver_id, accounts(1));
+    assert!(matches!(
+        receipts[1].actions[0],
+        MockAction::Transfer { deposit, .. } if deposit == NearToken::from_yoctonear(1)
+    ));
+
+    assert!(!contract.storage_unregister(None));
+}
+
+#[test]
+fn test_storage_unregister_refunds_a_self_paid_deposit() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(None, None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    let receipts_before = get_created_receipts().len();
+    assert!(contract.storage_unregister(None));
+
+    let receipts = get_created_receipts();
+    assert_eq!(receipts.len(), receipts_before + 1);
+    let receipt = receipts.last().unwrap();
+    assert_eq!(receipt.receiver_id, accounts(2));
+    assert!(matches!(
+        receipt.actions[0],
+        MockAction::Transfer { deposit, .. }
+            if deposit == Contract::storage_balance_bounds().min.saturating_add(NearToken::from_yoctonear(1))
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Can't unregister the account with a positive balance without force")]
+fn test_storage_unregister_with_balance() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.storage_unregister(None);
+}
+
+#[test]
+fn test_force_unregister_burns_the_balance_after_the_delay() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.request_force_unregister();
+    testing_env!(context
+        .block_timestamp(FORCE_UNREGISTER_DELAY_SEC * 1_000_000_000)
+        .build());
+    assert!(contract.storage_unregister(Some(true)));
+    assert_eq!(contract.ft_total_supply(), U128(0));
+}
+
+#[test]
+#[should_panic(expected = "Burning this balance needs a force unregister request first")]
+fn test_force_unregister_needs_a_request() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.storage_unregister(Some(true));
+}
+
+#[test]
+fn test_storage_sweep_refunds_the_depositor() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(
+        contract.get_storage_depositor(accounts(1)),
+        Some(accounts(0))
+    );
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    // The owner holds tokens and charlie isn't registered, so only bob is swept.
+    assert_eq!(
+        contract.storage_sweep(vec![accounts(0), accounts(1), accounts(2)]),
+        1
+    );
+    assert!(Contract::storage_balance_of(accounts(1)).is_none());
+    assert!(Contract::storage_balance_of(accounts(0)).is_some());
+    assert!(contract.get_storage_depositor(accounts(1)).is_none());
+
+    let min_balance = Contract::storage_balance_bounds().min;
+    let receipts = get_created_receipts();
+    assert_eq!(receipts[0].receiver_id, accounts(0));
+    assert!(matches!(
+        receipts[0].actions[0],
+        MockAction::Transfer { deposit, .. } if deposit == min_balance
+    ));
+    let stats = contract.get_storage_sweep_stats();
+    assert_eq!(stats.swept_accounts.0, 1);
+    assert_eq!(stats.refunded, min_balance);
+}
+
+#[test]
+fn test_storage_sweep_keeps_the_fee_collector() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_transfer_fee(100, accounts(1));
+    assert_eq!(contract.storage_sweep(vec![accounts(1)]), 0);
+    assert!(Contract::storage_balance_of(accounts(1)).is_some());
+}
+
+#[test]
+fn test_storage_sweep_keeps_the_receivers_of_open_streams() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    let stream_id = contract.open_stream(accounts(1), U128(1), U128(100));
+    assert_eq!(contract.storage_sweep(vec![accounts(1)]), 0);
+    assert!(Contract::storage_balance_of(accounts(1)).is_some());
+
+    // Nothing accrued yet, so bob is left without tokens once the stream is cancelled.
+    contract.cancel_stream(stream_id);
+    assert_eq!(contract.storage_sweep(vec![accounts(1)]), 1);
+}
+
+#[test]
+fn test_storage_sweep_keeps_the_senders_of_escrowed_transfers() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_transfer(accounts(1), U128(10), None, None, None, None);
+    // Bob escrows his whole balance back to the owner, who may never accept it.
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_near(1))
+        .build());
+    contract.ft_transfer_escrow(accounts(0), U128(10), 3_600);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(0));
+    assert_eq!(contract.storage_sweep(vec![accounts(1)]), 0);
+    assert!(Contract::storage_balance_of(accounts(1)).is_some());
+}
@@ -0,0 +1,45 @@
+use near_ft::storage_keys::*;
+use near_sdk::IntoStorageKey;
+
+#[test]
+fn test_storage_prefixes_are_unique() {
+    assert!(prefixes_are_unique(STORAGE_LAYOUT));
+
+    let colliding: &[(&str, &[(&str, u8)])] = &[
+        ("core", &[("Accounts", 0)]),
+        ("extension", &[("Accounts", 0)]),
+    ];
+    assert!(!prefixes_are_unique(colliding));
+}
+
+#[test]
+fn test_storage_prefixes_match_the_flat_layout() {
+    // The flat `StorageKey` enum numbered the collections in declaration order, without gaps.
+    let mut prefixes: Vec<u8> = STORAGE_LAYOUT
+        .iter()
+        .flat_map(|(_, collections)| collections.iter().map(|(_, prefix)| *prefix))
+        .collect();
+    prefixes.sort();
+    assert_eq!(prefixes, (0..prefixes.len() as u8).collect::<Vec<_>>());
+
+    assert_eq!(
+        StorageKey::Core(CoreKey::Accounts).into_storage_key(),
+        vec![0]
+    );
+    assert_eq!(
+        StorageKey::Approvals(ApprovalsKey::Allowances).into_storage_key(),
+        vec![23]
+    );
+    assert_eq!(
+        StorageKey::Treasury(TreasuryKey::TreasurySpends).into_storage_key(),
+        vec![46]
+    );
+
+    let account_hash = [7u8; 32];
+    let mut expected = vec![24];
+    expected.extend_from_slice(&account_hash);
+    assert_eq!(
+        StorageKey::AllowancesPerOwner { account_hash }.into_storage_key(),
+        expected
+    );
+}
@@ -0,0 +1,146 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::keepers::KeeperTask;
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+const SEC: u64 = 1_000_000_000;
+
+// Alice streams 10 tokens per second to bob out of a 1000-token deposit.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build();
+    assert_eq!(contract.open_stream(accounts(1), U128(10), U128(1_000)), 0);
+    (contract, context)
+}
+
+fn call_at(context: &mut VMContextBuilder, account: usize, timestamp: u64) {
+    testing_env!(context
+        .predecessor_account_id(accounts(account))
+        .block_timestamp(timestamp)
+        .build());
+}
+
+#[test]
+fn test_stream_deposit_is_held_apart_from_the_balances() {
+    let (contract, _) = setup();
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(0));
+    assert_eq!(contract.get_streamed_supply(), U128(1_000));
+    assert_eq!(contract.ft_total_supply(), U128(1_000));
+    assert!(get_logs()[0].contains("\"event\":\"stream_opened\""));
+}
+
+#[test]
+fn test_receiver_withdraws_the_accrued_tokens() {
+    let (mut contract, mut context) = setup();
+    // Only whole seconds accrue.
+    call_at(&mut context, 1, 30 * SEC + SEC / 2);
+    assert_eq!(contract.stream_withdrawable(0), U128(300));
+    assert_eq!(contract.withdraw_from_stream(0), U128(300));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(300));
+    assert_eq!(contract.get_streamed_supply(), U128(700));
+
+    // The stream stops at its deposit and is removed once paid out.
+    call_at(&mut context, 1, 500 * SEC);
+    assert_eq!(contract.withdraw_from_stream(0), U128(700));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(1_000));
+    assert!(contract.get_stream(0).is_none());
+    assert_eq!(contract.get_streamed_supply(), U128(0));
+}
+
+#[test]
+fn test_cancel_settles_pro_rata() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 1, 20 * SEC);
+    contract.withdraw_from_stream(0);
+
+    call_at(&mut context, 0, 45 * SEC);
+    contract.cancel_stream(0);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(450));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(550));
+    assert_eq!(contract.get_streamed_supply(), U128(0));
+    assert!(contract.get_stream(0).is_none());
+    assert!(get_logs()
+        .last()
+        .unwrap()
+        .contains("\"receiver_amount\":\"250\",\"sender_refund\":\"550\""));
+}
+
+#[test]
+#[should_panic(expected = "Only the receiver can withdraw from the stream")]
+fn test_only_the_receiver_withdraws() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 0, 20 * SEC);
+    contract.withdraw_from_stream(0);
+}
+
+#[test]
+#[should_panic(expected = "Only the sender and the receiver can cancel the stream")]
+fn test_only_the_parties_cancel() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 2, 20 * SEC);
+    contract.cancel_stream(0);
+}
+
+#[test]
+#[should_panic(expected = "The account doesn't have enough balance")]
+fn test_stream_needs_the_deposit() {
+    let (mut contract, _) = setup();
+    contract.open_stream(accounts(1), U128(10), U128(1));
+}
+
+// Bob withdraws 500 tokens after 50 seconds and tips 10 of them for the stream to be pushed.
+fn tip_stream(contract: &mut Contract, context: &mut VMContextBuilder) {
+    call_at(context, 1, 50 * SEC);
+    contract.withdraw_from_stream(0);
+    contract.add_keeper_tip(KeeperTask::Stream(0), U128(10));
+    assert_eq!(
+        contract
+            .get_keeper_tip(KeeperTask::Stream(0))
+            .unwrap()
+            .amount,
+        U128(10)
+    );
+}
+
+#[test]
+fn test_keeper_pushes_a_tipped_stream() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(2)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    tip_stream(&mut contract, &mut context);
+
+    call_at(&mut context, 2, 100 * SEC);
+    assert_eq!(contract.push_stream(0), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(990));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(10));
+    assert!(contract.get_keeper_tip(KeeperTask::Stream(0)).is_none());
+}
+
+#[test]
+fn test_cancelled_stream_refunds_the_tip() {
+    let (mut contract, mut context) = setup();
+    tip_stream(&mut contract, &mut context);
+    call_at(&mut context, 0, 60 * SEC);
+    contract.cancel_stream(0);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(600));
+    assert!(contract.get_keeper_tip(KeeperTask::Stream(0)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The stream isn't fully accrued yet")]
+fn test_stream_is_pushed_once_fully_accrued() {
+    let (mut contract, mut context) = setup();
+    call_at(&mut context, 2, 99 * SEC);
+    contract.push_stream(0);
+}
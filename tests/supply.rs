@@ -0,0 +1,178 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::supply::{SupplyBreakdown, SupplyCategory};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+const MINUTE: u64 = 60_000_000_000;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+fn contract_id() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+// Alice owns the 1,000 tokens of the initial supply.
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .contract_id(contract_id())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .build()
+}
+
+// Returns the breakdown after checking that it adds up to the total supply.
+fn breakdown(contract: &Contract) -> SupplyBreakdown {
+    let breakdown = contract.ft_supply_breakdown();
+    assert_eq!(breakdown.total_supply, contract.ft_total_supply());
+    assert_eq!(
+        breakdown.minted.0 - breakdown.burned.0,
+        breakdown.total_supply.0
+    );
+    assert_eq!(
+        breakdown.circulating.0
+            + breakdown.locked_in_vesting.0
+            + breakdown.staked.0
+            + breakdown.bridged_out.0
+            + breakdown.escrowed.0,
+        breakdown.total_supply.0
+    );
+    breakdown
+}
+
+#[test]
+fn test_initial_supply_circulates() {
+    let (contract, _) = setup();
+    let breakdown = breakdown(&contract);
+    assert_eq!(breakdown.total_supply, U128(1_000));
+    assert_eq!(breakdown.minted, U128(1_000));
+    assert_eq!(breakdown.circulating, U128(1_000));
+}
+
+#[test]
+fn test_mints_and_burns_are_counted() {
+    let (mut contract, _) = setup();
+    contract.ft_mint(accounts(1), U128(200), None);
+    contract.ft_burn(U128(100), None);
+
+    let breakdown = breakdown(&contract);
+    assert_eq!(breakdown.total_supply, U128(1_100));
+    assert_eq!(breakdown.minted, U128(1_200));
+    assert_eq!(breakdown.burned, U128(100));
+    assert_eq!(breakdown.circulating, U128(1_100));
+}
+
+#[test]
+fn test_bonds_are_locked_in_vesting_until_claimed() {
+    let (mut contract, mut context) = setup();
+    // 500 tokens of capacity, paying one token per yoctoNEAR plus a 10% discount over 10 days.
+    contract.create_bond_market(None, U128(1), U128(1), 1_000, 10, U128(500));
+    let before = breakdown(&contract);
+    assert_eq!(before.escrowed, U128(500));
+    assert_eq!(before.circulating, U128(500));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.bond_with_near(0, None);
+    let after_bond = breakdown(&contract);
+    assert_eq!(after_bond.locked_in_vesting, U128(110));
+    assert_eq!(after_bond.escrowed, U128(390));
+    assert_eq!(after_bond.circulating, U128(500));
+
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(5 * DAY)
+        .build());
+    assert_eq!(contract.claim_bonds(), U128(55));
+    let after_claim = breakdown(&contract);
+    assert_eq!(after_claim.locked_in_vesting, U128(55));
+    assert_eq!(after_claim.circulating, U128(555));
+    assert_eq!(after_claim.total_supply, U128(1_000));
+}
+
+#[test]
+fn test_category_counters_follow_the_balances() {
+    let (mut contract, mut context) = setup();
+    contract.ft_transfer(accounts(2), U128(300), None, None, None, None);
+    contract.set_supply_category(accounts(2), Some(SupplyCategory::Staked));
+    assert_eq!(breakdown(&contract).staked, U128(300));
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    let after = breakdown(&contract);
+    assert_eq!(after.staked, U128(200));
+    assert_eq!(after.circulating, U128(800));
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_supply_category(accounts(2), Some(SupplyCategory::BridgedOut));
+    let after = breakdown(&contract);
+    assert_eq!(after.staked, U128(0));
+    assert_eq!(after.bridged_out, U128(200));
+}
+
+// Mints 100 tokens to alice at `at` (in nanoseconds).
+fn mint_at(contract: &mut Contract, context: &mut VMContextBuilder, at: u64) {
+    testing_env!(context.block_timestamp(at).build());
+    contract.ft_mint(accounts(0), U128(100), None);
+}
+
+fn history(contract: &Contract, from: Option<u64>, limit: Option<u64>) -> Vec<(u64, u128)> {
+    contract
+        .supply_history(from.map(U64), limit)
+        .into_iter()
+        .map(|point| (point.timestamp.0, point.total_supply.0))
+        .collect()
+}
+
+#[test]
+fn test_supply_history_keeps_the_last_point_of_an_hour() {
+    let (mut contract, mut context) = setup();
+    assert_eq!(history(&contract, None, None), vec![(0, 1_000)]);
+    mint_at(&mut contract, &mut context, 10 * MINUTE);
+    mint_at(&mut contract, &mut context, 59 * MINUTE);
+    assert_eq!(history(&contract, None, None), vec![(59 * MINUTE, 1_200)]);
+}
+
+#[test]
+fn test_supply_history_adds_a_point_per_hour() {
+    let (mut contract, mut context) = setup();
+    mint_at(&mut contract, &mut context, 30 * MINUTE);
+    mint_at(&mut contract, &mut context, HOUR);
+    mint_at(&mut contract, &mut context, HOUR + 30 * MINUTE);
+    // The hours without a mint or a burn have no point.
+    mint_at(&mut contract, &mut context, 5 * HOUR);
+    testing_env!(context.block_timestamp(5 * HOUR + MINUTE).build());
+    contract.ft_burn(U128(50), None);
+
+    assert_eq!(
+        history(&contract, None, None),
+        vec![
+            (30 * MINUTE, 1_100),
+            (HOUR + 30 * MINUTE, 1_300),
+            (5 * HOUR + MINUTE, 1_350),
+        ]
+    );
+}
+
+#[test]
+fn test_supply_history_is_paginated_by_timestamp() {
+    let (mut contract, mut context) = setup();
+    for hour in 1..=4 {
+        mint_at(&mut contract, &mut context, hour * HOUR);
+    }
+    assert_eq!(history(&contract, None, None).len(), 5);
+    assert_eq!(
+        history(&contract, Some(2 * HOUR), Some(2)),
+        vec![(2 * HOUR, 1_200), (3 * HOUR, 1_300)]
+    );
+    assert_eq!(
+        history(&contract, Some(3 * HOUR + 1), None),
+        vec![(4 * HOUR, 1_400)]
+    );
+    assert!(history(&contract, Some(5 * HOUR), None).is_empty());
+}
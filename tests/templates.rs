@@ -0,0 +1,56 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::metadata::FungibleTokenMetadataProvider;
+use near_ft::templates::TokenTemplate;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+fn setup(template_id: TokenTemplate) -> (Contract, VMContextBuilder) {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let contract = Contract::new_from_template(template_id, accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+#[test]
+fn test_stablecoins_have_6_decimals() {
+    let (contract, _) = setup(TokenTemplate::Stablecoin);
+    assert_eq!(Contract::ft_metadata().metadata.decimals, 6);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(1_000));
+    assert!(contract.get_template() == Some(TokenTemplate::Stablecoin));
+}
+
+#[test]
+fn test_game_currencies_have_no_decimals() {
+    let (contract, _) = setup(TokenTemplate::GameCurrency);
+    assert_eq!(Contract::ft_metadata().metadata.decimals, 0);
+    assert_eq!(contract.get_max_supply(), None);
+}
+
+#[test]
+fn test_governance_tokens_have_a_fixed_supply() {
+    let (contract, _) = setup(TokenTemplate::Governance);
+    assert_eq!(Contract::ft_metadata().metadata.decimals, 18);
+    assert_eq!(contract.get_max_supply(), Some(U128(1_000)));
+}
+
+#[test]
+#[should_panic(expected = "The mint exceeds the max supply")]
+fn test_governance_tokens_cant_be_minted() {
+    let (mut contract, _) = setup(TokenTemplate::Governance);
+    contract.ft_mint(accounts(0), U128(1), None);
+}
+
+#[test]
+fn test_default_meta_has_no_template() {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    assert!(contract.get_template().is_none());
+}
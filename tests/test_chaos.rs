@@ -0,0 +1,110 @@
+//! The `ft_resolve_transfer` matrix, exercised against a receiver built with the `chaos`
+//! feature. Run with `cargo test --features chaos --test test_chaos`.
+#![cfg(feature = "chaos")]
+
+use near_sdk::NearToken;
+use near_workspaces::{Account, Contract};
+use serde_json::{json, Value};
+
+const AMOUNT: u128 = 100;
+
+#[tokio::test]
+async fn test_resolve_transfer_matrix() -> Result<(), Box<dyn std::error::Error>> {
+    let token_wasm = near_workspaces::compile_project("./").await?;
+    let receiver_wasm = compile_chaos_receiver()?;
+
+    let sandbox = near_workspaces::sandbox().await?;
+    let token = sandbox.dev_deploy(&token_wasm).await?;
+    let receiver = sandbox.dev_deploy(&receiver_wasm).await?;
+    let sender = sandbox.dev_create_account().await?;
+    let sink = sandbox.dev_create_account().await?;
+
+    for (contract, owner_id) in [(&token, sender.id()), (&receiver, receiver.id())] {
+        contract
+            .call("new_default_meta")
+            .args_json(json!({ "owner_id": owner_id, "total_supply": "1000000" }))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+    for account_id in [receiver.id(), sink.id()] {
+        sender
+            .call(token.id(), "storage_deposit")
+            .args_json(json!({ "account_id": account_id }))
+            .deposit(NearToken::from_millinear(100))
+            .transact()
+            .await?
+            .into_result()?;
+    }
+
+    // (behavior, tokens the receiver keeps)
+    let cases = [
+        (json!("panic"), 0),
+        (json!("exhaust_gas"), 0),
+        (json!({ "refund": { "unused": "30" } }), 70),
+        (json!({ "refund": { "unused": "1000" } }), 0),
+        (json!("invalid_value"), 0),
+        (json!({ "forward_and_refund": { "to": sink.id() } }), 0),
+        (json!({ "delete_self": { "beneficiary_id": sink.id() } }), 0),
+    ];
+    for (behavior, kept) in cases {
+        let sender_before = balance_of(&token, &sender).await?;
+        let receiver_before = balance_of(&token, receiver.as_account()).await?;
+        let sink_before = balance_of(&token, &sink).await?;
+
+        sender
+            .call(token.id(), "ft_transfer_call")
+            .args_json(json!({
+                "receiver_id": receiver.id(),
+                "amount": AMOUNT.to_string(),
+                "msg": json!({ "chaos": behavior }).to_string(),
+            }))
+            .deposit(NearToken::from_yoctonear(1))
+            .max_gas()
+            .transact()
+            .await?
+            .into_result()?;
+
+        let forwarded = balance_of(&token, &sink).await? - sink_before;
+        let received = balance_of(&token, receiver.as_account()).await? - receiver_before;
+        assert_eq!(received, kept, "{}", behavior);
+        assert_eq!(
+            sender_before - balance_of(&token, &sender).await?,
+            kept + forwarded,
+            "{}",
+            behavior
+        );
+    }
+
+    Ok(())
+}
+
+fn compile_chaos_receiver() -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let manifest_path = std::fs::canonicalize("./Cargo.toml")?;
+    let artifact = cargo_near_build::build(cargo_near_build::BuildOpts {
+        no_locked: true,
+        manifest_path: Some(
+            cargo_near_build::camino::Utf8PathBuf::from_path_buf(manifest_path)
+                .map_err(|_| "The manifest path isn't UTF-8")?,
+        ),
+        features: Some("chaos".to_string()),
+        out_dir: Some("./target/near/chaos".into()),
+        ..Default::default()
+    })?;
+    Ok(std::fs::read(artifact.path)?)
+}
+
+async fn balance_of(
+    token: &Contract,
+    account: &Account,
+) -> Result<u128, Box<dyn std::error::Error>> {
+    let balance = token
+        .view("ft_balance_of")
+        .args_json(json!({ "account_id": account.id() }))
+        .await?
+        .json::<Value>()?;
+    Ok(balance
+        .as_str()
+        .ok_or("The balance isn't a string")?
+        .parse()?)
+}
@@ -0,0 +1,96 @@
+//! Gas benchmarks of the hot paths. Each one fails when its method burns more than its budget,
+//! so regressions show up in CI. Run with `cargo test --test test_gas -- --nocapture` to print
+//! the measured gas.
+//!
+//! `test_ft_transfer_gas_against_the_deployed_release` also measures the same calls on the
+//! deployed release, prints the gas before and after the change and fails if the current code
+//! burns more. It's ignored by default: run it with `OLD_WASM=<path to the deployed wasm> cargo
+//! test --test test_gas -- --ignored`.
+
+use near_sdk::{Gas, NearToken};
+use serde_json::json;
+
+/// The gas a plain `ft_transfer` between two registered accounts may burn, in Tgas.
+const FT_TRANSFER_BUDGET_TGAS: u64 = 20;
+
+/// The `ft_transfer` calls measured, by label and memo.
+const FT_TRANSFERS: [(&str, Option<&str>); 2] = [("no memo", None), ("memo", Some("invoice 42"))];
+
+// Deploys `wasm` in its own sandbox and returns the gas burnt by each of the `FT_TRANSFERS`.
+async fn measure_ft_transfers(wasm: &[u8]) -> Result<Vec<Gas>, Box<dyn std::error::Error>> {
+    let sandbox = near_workspaces::sandbox().await?;
+    let token = sandbox.dev_deploy(wasm).await?;
+    let sender = sandbox.dev_create_account().await?;
+    let receiver = sandbox.dev_create_account().await?;
+
+    token
+        .call("new_default_meta")
+        .args_json(json!({ "owner_id": sender.id(), "total_supply": "1000000" }))
+        .transact()
+        .await?
+        .into_result()?;
+    sender
+        .call(token.id(), "storage_deposit")
+        .args_json(json!({ "account_id": receiver.id() }))
+        .deposit(NearToken::from_millinear(100))
+        .transact()
+        .await?
+        .into_result()?;
+
+    let mut burnt = vec![];
+    for (_, memo) in FT_TRANSFERS {
+        let outcome = sender
+            .call(token.id(), "ft_transfer")
+            .args_json(json!({ "receiver_id": receiver.id(), "amount": "100", "memo": memo }))
+            .deposit(NearToken::from_yoctonear(1))
+            .transact()
+            .await?;
+        burnt.push(outcome.total_gas_burnt);
+        outcome.into_result()?;
+    }
+    Ok(burnt)
+}
+
+#[tokio::test]
+async fn test_ft_transfer_gas() -> Result<(), Box<dyn std::error::Error>> {
+    let contract_wasm = near_workspaces::compile_project("./").await?;
+    let after = measure_ft_transfers(&contract_wasm).await?;
+    for ((label, _), burnt) in FT_TRANSFERS.iter().zip(after.iter()) {
+        println!("ft_transfer ({}): {}", label, burnt);
+        assert!(
+            burnt.as_tgas() < FT_TRANSFER_BUDGET_TGAS,
+            "ft_transfer ({}) burnt {}",
+            label,
+            burnt
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "needs OLD_WASM, the path to the deployed wasm"]
+async fn test_ft_transfer_gas_against_the_deployed_release(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let old_wasm_path = std::env::var("OLD_WASM")
+        .expect("OLD_WASM should be the path to the deployed wasm to compare against");
+    let before = measure_ft_transfers(&std::fs::read(old_wasm_path)?).await?;
+    let after = measure_ft_transfers(&near_workspaces::compile_project("./").await?).await?;
+    for ((label, _), (before, after)) in FT_TRANSFERS.iter().zip(before.iter().zip(after.iter())) {
+        println!(
+            "ft_transfer ({}): {} before, {} after ({:+} Ggas)",
+            label,
+            before,
+            after,
+            after.as_ggas() as i128 - before.as_ggas() as i128
+        );
+        assert!(
+            after <= before,
+            "ft_transfer ({}) burns more than the deployed release: {} before, {} after",
+            label,
+            before,
+            after
+        );
+    }
+
+    Ok(())
+}
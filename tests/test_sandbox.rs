@@ -0,0 +1,109 @@
+//! End-to-end tests of the deployed wasm, on the harness published with the `sandbox` feature.
+//! Run with `cargo test --features sandbox --test test_sandbox`.
+#![cfg(feature = "sandbox")]
+
+use near_ft::sandbox::{chaos_msg, event_logs, HarnessResult, TokenHarness};
+use near_sdk::NearToken;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_transfer_emits_the_event() -> HarnessResult<()> {
+    let harness = TokenHarness::new(1_000).await?;
+    let alice = harness.create_account(true).await?;
+
+    let outcome = harness.ft_transfer(&harness.owner, alice.id(), 100).await?;
+    assert!(outcome.is_success());
+    assert_eq!(harness.balance_of(harness.owner.id()).await?, 900);
+    assert_eq!(harness.balance_of(alice.id()).await?, 100);
+
+    let events = event_logs(&outcome);
+    let transfer = events
+        .iter()
+        .find(|event| event["event"] == "ft_transfer")
+        .ok_or("No ft_transfer event")?;
+    assert_eq!(transfer["standard"], "nep141");
+    assert_eq!(
+        transfer["data"][0],
+        json!({ "old_owner_id": harness.owner.id(), "new_owner_id": alice.id(), "amount": "100" })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_call_round_trips() -> HarnessResult<()> {
+    let harness = TokenHarness::new(1_000).await?;
+    let receiver = harness.deploy_mock_receiver().await?;
+
+    // (behavior, tokens the receiver keeps)
+    let cases = [
+        (json!({ "refund": { "unused": "0" } }), 100),
+        (json!({ "refund": { "unused": "30" } }), 70),
+        (json!("panic"), 0),
+    ];
+    for (behavior, kept) in cases {
+        let owner_before = harness.balance_of(harness.owner.id()).await?;
+        let receiver_before = harness.balance_of(receiver.id()).await?;
+        harness
+            .ft_transfer_call(
+                &harness.owner,
+                receiver.id(),
+                100,
+                &chaos_msg(behavior.clone()),
+            )
+            .await?
+            .into_result()?;
+        assert_eq!(
+            harness.balance_of(receiver.id()).await? - receiver_before,
+            kept,
+            "{}",
+            behavior
+        );
+        assert_eq!(
+            owner_before - harness.balance_of(harness.owner.id()).await?,
+            kept,
+            "{}",
+            behavior
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_storage_registration_edge_cases() -> HarnessResult<()> {
+    let harness = TokenHarness::new(1_000).await?;
+    let alice = harness.create_account(true).await?;
+    let bob = harness.create_account(false).await?;
+
+    // Registering twice refunds the deposit and keeps the account.
+    assert!(harness.register(alice.id()).await?.is_success());
+    assert!(harness.is_registered(alice.id()).await?);
+
+    // A deposit below the minimum is rejected.
+    let outcome = harness
+        .owner
+        .call(harness.token.id(), "storage_deposit")
+        .args_json(json!({ "account_id": bob.id() }))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?;
+    assert!(outcome.is_failure());
+    assert!(!harness.is_registered(bob.id()).await?);
+
+    // Unregistered accounts can't receive tokens.
+    assert!(harness
+        .ft_transfer(&harness.owner, bob.id(), 100)
+        .await?
+        .is_failure());
+    assert_eq!(harness.balance_of(harness.owner.id()).await?, 1_000);
+
+    // An empty account can unregister.
+    alice
+        .call(harness.token.id(), "storage_unregister")
+        .args_json(json!({}))
+        .deposit(NearToken::from_yoctonear(1))
+        .transact()
+        .await?
+        .into_result()?;
+    assert!(!harness.is_registered(alice.id()).await?);
+    Ok(())
+}
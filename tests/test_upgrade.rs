@@ -0,0 +1,199 @@
+//! Differential upgrade harness: replays a recorded operation trace against the previous
+//! release and the current code, each deployed in its own sandbox, and diffs the outcome of
+//! every operation (result, failure and events) along with the resulting state.
+//!
+//! Run with `OLD_WASM=<path to the deployed wasm> cargo test --test test_upgrade -- --ignored`;
+//! the test is ignored by default and fails if `OLD_WASM` isn't set. Accounts are subaccounts of
+//! the sandbox root with fixed names, so both replays write the same keys. Collections storing
+//! timestamps differ between sandboxes and are listed in the `ignore` of the trace, along with
+//! `STATE`, the contract struct.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use near_ft::storage_keys::STORAGE_LAYOUT;
+use near_sdk::serde::Deserialize;
+use near_sdk::NearToken;
+use near_workspaces::{Account, Contract};
+use serde_json::Value;
+
+const TRACE: &str = include_str!("traces/upgrade.json");
+
+// A recorded sequence of calls, with the accounts (subaccounts of the sandbox root) signing
+// them.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct Trace {
+    accounts: Vec<String>,
+    init: Operation,
+    /// The collections (or `STATE`) left out of the state diff.
+    ignore: Vec<String>,
+    operations: Vec<Operation>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct Operation {
+    /// The account signing the call, the contract itself when unset.
+    signer: Option<String>,
+    method: String,
+    args: Value,
+    /// The deposit in yoctoNEAR.
+    deposit: Option<String>,
+}
+
+// The outcome of an operation, as compared between the replays.
+#[derive(Debug, PartialEq)]
+struct OperationOutcome {
+    /// The returned value, or the failures of the transaction and its receipts.
+    result: Result<String, Vec<String>>,
+    logs: Vec<String>,
+}
+
+struct Replay {
+    outcomes: Vec<OperationOutcome>,
+    state: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+#[tokio::test]
+#[ignore = "needs OLD_WASM, the path to the deployed wasm"]
+async fn test_upgrade_preserves_behavior() -> Result<(), Box<dyn std::error::Error>> {
+    let old_wasm_path = std::env::var("OLD_WASM")
+        .expect("OLD_WASM should be the path to the deployed wasm to compare against");
+    let old_wasm = std::fs::read(old_wasm_path)?;
+    let new_wasm = near_workspaces::compile_project("./").await?;
+    let trace: Trace = serde_json::from_str(TRACE)?;
+
+    let old = replay(&old_wasm, &trace).await?;
+    let new = replay(&new_wasm, &trace).await?;
+
+    let mut diffs = vec![];
+    for (index, (operation, (old, new))) in trace
+        .operations
+        .iter()
+        .zip(old.outcomes.iter().zip(new.outcomes.iter()))
+        .enumerate()
+    {
+        if old != new {
+            diffs.push(format!(
+                "operation #{} ({}):\n  old: {:?}\n  new: {:?}",
+                index, operation.method, old, new
+            ));
+        }
+    }
+    diffs.extend(diff_state(&old.state, &new.state, &trace.ignore));
+
+    assert!(
+        diffs.is_empty(),
+        "The upgrade changes:\n{}",
+        diffs.join("\n")
+    );
+    Ok(())
+}
+
+/// Deploys `wasm` in a fresh sandbox and replays the trace against it.
+async fn replay(wasm: &[u8], trace: &Trace) -> Result<Replay, Box<dyn std::error::Error>> {
+    let sandbox = near_workspaces::sandbox().await?;
+    let root = sandbox.root_account()?;
+    let contract = root
+        .create_subaccount("token")
+        .initial_balance(NearToken::from_near(50))
+        .transact()
+        .await?
+        .into_result()?
+        .deploy(wasm)
+        .await?
+        .into_result()?;
+    let mut accounts = BTreeMap::new();
+    for name in trace.accounts.iter() {
+        let account = root
+            .create_subaccount(name)
+            .initial_balance(NearToken::from_near(10))
+            .transact()
+            .await?
+            .into_result()?;
+        accounts.insert(name.clone(), account);
+    }
+
+    let init = execute(&contract, &accounts, &trace.init).await?;
+    assert!(init.result.is_ok(), "The initialization failed: {:?}", init);
+    let mut outcomes = vec![];
+    for operation in trace.operations.iter() {
+        outcomes.push(execute(&contract, &accounts, operation).await?);
+    }
+
+    let state = contract.view_state().await?.into_iter().collect();
+    Ok(Replay { outcomes, state })
+}
+
+async fn execute(
+    contract: &Contract,
+    accounts: &BTreeMap<String, Account>,
+    operation: &Operation,
+) -> Result<OperationOutcome, Box<dyn std::error::Error>> {
+    let signer = match operation.signer.as_ref() {
+        Some(name) => accounts
+            .get(name)
+            .ok_or_else(|| format!("Unknown account {}", name))?,
+        None => contract.as_account(),
+    };
+    let deposit = match operation.deposit.as_ref() {
+        Some(deposit) => NearToken::from_yoctonear(deposit.parse()?),
+        None => NearToken::from_yoctonear(0),
+    };
+    let outcome = signer
+        .call(contract.id(), &operation.method)
+        .args_json(&operation.args)
+        .deposit(deposit)
+        .max_gas()
+        .transact()
+        .await?;
+
+    let logs = outcome.logs().into_iter().map(str::to_string).collect();
+    let failures: Vec<String> = outcome
+        .failures()
+        .into_iter()
+        .filter_map(|failure| failure.clone().into_result().err())
+        .map(|error| error.to_string())
+        .collect();
+    let result = if outcome.is_success() {
+        Ok(String::from_utf8_lossy(&outcome.raw_bytes()?).into_owned())
+    } else {
+        Err(failures)
+    };
+    Ok(OperationOutcome { result, logs })
+}
+
+/// Diffs two states key by key, naming the collection of every differing key.
+fn diff_state(
+    old: &BTreeMap<Vec<u8>, Vec<u8>>,
+    new: &BTreeMap<Vec<u8>, Vec<u8>>,
+    ignore: &[String],
+) -> Vec<String> {
+    let keys: BTreeSet<&Vec<u8>> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let collection = collection_of(key);
+            if ignore.contains(&collection) {
+                return None;
+            }
+            match (old.get(key), new.get(key)) {
+                (Some(old), Some(new)) if old == new => None,
+                (old, new) => Some(format!(
+                    "state {} {:?}:\n  old: {:?}\n  new: {:?}",
+                    collection, key, old, new
+                )),
+            }
+        })
+        .collect()
+}
+
+fn collection_of(key: &[u8]) -> String {
+    if key == b"STATE" {
+        return "STATE".to_string();
+    }
+    STORAGE_LAYOUT
+        .iter()
+        .flat_map(|(_, prefixes)| prefixes.iter())
+        .find(|(_, prefix)| key.first() == Some(prefix))
+        .map_or_else(|| "unknown".to_string(), |(name, _)| name.to_string())
+}
@@ -0,0 +1,67 @@
+//! The fixtures exported to downstream crates. Run with
+//! `cargo test --features test_utils --test test_utils`.
+#![cfg(feature = "test_utils")]
+
+use near_ft::access::Role;
+use near_ft::fees::{FeeConfig, FeeSplit};
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::guard::PauseFlags;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs};
+
+#[test]
+fn test_builder_mints_the_balances() {
+    let (contract, _) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 700)
+        .balance(accounts(1), 300)
+        .build();
+
+    assert_eq!(contract.ft_total_supply(), U128(1_000));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(700));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(300));
+    assert!(get_logs().is_empty());
+}
+
+#[test]
+fn test_builder_sets_up_the_modules() {
+    let (mut contract, _) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .fee_config(FeeConfig {
+            fee_bps: 100,
+            split: FeeSplit {
+                stakers_bps: 0,
+                treasury_bps: 10_000,
+                burn_bps: 0,
+                rebates_bps: 0,
+                insurance_bps: 0,
+            },
+            stakers_pool_id: accounts(2),
+            treasury_id: accounts(2),
+        })
+        .with(|contract| contract.grant_role(accounts(1), Role::Minter))
+        .build();
+
+    contract.ft_transfer(accounts(1), U128(500), None, None, None, None);
+
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(5));
+    assert!(contract.has_role(accounts(1), Role::Minter));
+}
+
+#[test]
+#[should_panic(expected = "Transfers are paused")]
+fn test_builder_pauses_the_contract() {
+    let (mut contract, _) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .pause_flags(PauseFlags {
+            transfers: true,
+            mints: false,
+            storage: false,
+        })
+        .build();
+    contract.ft_transfer(accounts(1), U128(500), None, None, None, None);
+}
@@ -0,0 +1,28 @@
+//! Fails when the release wasm outgrows its size budget, since deploying (and upgrading) the
+//! contract costs storage staking proportional to its code size. Override the budget with the
+//! `WASM_SIZE_BUDGET` environment variable (in bytes), e.g. to try out a feature.
+
+/// The largest release wasm accepted, in bytes.
+const DEFAULT_WASM_SIZE_BUDGET: usize = 1_000_000;
+
+#[tokio::test]
+async fn test_wasm_size_budget() -> Result<(), Box<dyn std::error::Error>> {
+    let budget = match std::env::var("WASM_SIZE_BUDGET") {
+        Ok(budget) => budget.parse()?,
+        Err(_) => DEFAULT_WASM_SIZE_BUDGET,
+    };
+    let contract_wasm = near_workspaces::compile_project("./").await?;
+
+    println!(
+        "wasm size: {} bytes (budget {})",
+        contract_wasm.len(),
+        budget
+    );
+    assert!(
+        contract_wasm.len() <= budget,
+        "The wasm is {} bytes, over the budget of {} bytes",
+        contract_wasm.len(),
+        budget
+    );
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use near_ft::ft_core::{CoSignerConfig, FungibleTokenCore, TransferArg};
+use near_ft::metadata::FungibleTokenMetadata;
+use near_ft::storage::StorageManagement;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::tokenomics::TokenomicsConfig;
+use near_ft::Contract;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+const SECOND: u64 = 1_000_000_000;
+
+// 2% of every transfer is burned, and bob and charlie are registered.
+fn setup(adjustable: bool) -> (Contract, VMContextBuilder) {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_with_tokenomics(
+        accounts(0),
+        U128(10_000),
+        FungibleTokenMetadata {
+            spec: near_ft::FT_METADATA_SPEC.to_string(),
+            name: "Deflationary".to_string(),
+            symbol: "DEFL".to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 24,
+        },
+        None,
+        TokenomicsConfig {
+            burn_bps: 200,
+            adjustable,
+        },
+    );
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    contract.storage_deposit(Some(accounts(2)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    (contract, context)
+}
+
+#[test]
+fn test_transfers_burn_a_share() {
+    let (mut contract, _) = setup(false);
+    contract.ft_transfer(accounts(1), U128(1_000), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(9_000));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(980));
+    assert_eq!(contract.ft_total_supply(), U128(9_980));
+    let logs = get_logs();
+    assert!(logs[0].contains("\"event\":\"ft_transfer\""));
+    assert!(logs[1].contains(
+        "\"event\":\"ft_burn\",\"data\":[{\"owner_id\":\"bob\",\"amount\":\"20\",\"memo\":\"Transfer burn\"}]"
+    ));
+}
+
+#[test]
+fn test_batch_transfers_burn_a_share() {
+    let (mut contract, _) = setup(false);
+    contract.ft_transfer_batch(vec![
+        TransferArg {
+            receiver_id: accounts(1),
+            amount: U128(100),
+            memo: None,
+        },
+        TransferArg {
+            receiver_id: accounts(2),
+            amount: U128(200),
+            memo: None,
+        },
+    ]);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(98));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(196));
+    assert_eq!(contract.ft_total_supply(), U128(9_994));
+    // The burns are taken from the receivers, like for a single transfer.
+    let logs = get_logs();
+    assert_eq!(logs.len(), 3);
+    assert!(logs[0].contains(r#""new_owner_id":"bob","amount":"100""#));
+    assert!(logs[0].contains(r#""new_owner_id":"charlie","amount":"200""#));
+    assert!(logs[1].contains(
+        r#""event":"ft_burn","data":[{"owner_id":"bob","amount":"2","memo":"Transfer burn"}]"#
+    ));
+    assert!(logs[2].contains(r#""owner_id":"charlie","amount":"4""#));
+}
+
+#[test]
+fn test_batch_transfers_burn_from_the_receivers() {
+    let (mut contract, _) = setup(false);
+    contract.ft_transfer_batch(vec![TransferArg {
+        receiver_id: accounts(1),
+        amount: U128(100),
+        memo: None,
+    }]);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(9_900));
+    assert_eq!(contract.burned_by(accounts(0)), U128(0));
+    assert_eq!(contract.burned_by(accounts(1)), U128(2));
+}
+
+#[test]
+#[should_panic(expected = "The receiver would be credited less than the minimum received")]
+fn test_min_received_accounts_for_the_burn() {
+    let (mut contract, _) = setup(false);
+    contract.ft_transfer(accounts(1), U128(1_000), None, None, Some(U128(981)), None);
+}
+
+#[test]
+fn test_adjustable_tokenomics() {
+    let (mut contract, _) = setup(true);
+    contract.set_burn_on_transfer_bps(0);
+    assert_eq!(contract.get_tokenomics().unwrap().burn_bps, 0);
+    contract.ft_transfer(accounts(1), U128(1_000), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(1_000));
+    assert_eq!(get_logs().len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "The tokenomics aren't adjustable")]
+fn test_fixed_tokenomics() {
+    let (mut contract, _) = setup(false);
+    contract.set_burn_on_transfer_bps(100);
+}
+
+#[test]
+#[should_panic(expected = "The burn rate is too high")]
+fn test_burn_rate_is_capped() {
+    let (mut contract, _) = setup(true);
+    contract.set_burn_on_transfer_bps(1_001);
+}
+
+// Like `setup`, on its own contract account so the queued transfers can be escrowed. Alice
+// holds the 10,000 tokens, and the context is left as alice attaching 1 NEAR.
+fn setup_queued() -> (Contract, VMContextBuilder) {
+    let (contract, mut context) = ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse::<AccountId>().unwrap())
+        .balance(accounts(0), 10_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .with(|contract| {
+            contract.tokenomics.set(&TokenomicsConfig {
+                burn_bps: 200,
+                adjustable: false,
+            });
+        })
+        .build();
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    (contract, context)
+}
+
+// Checks that bob was credited the 1,000 tokens sent by alice minus the burn.
+fn assert_burned(contract: &Contract) {
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(9_000));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(980));
+    assert_eq!(contract.ft_total_supply(), U128(9_980));
+}
+
+#[test]
+fn test_travel_rule_transfers_burn_a_share() {
+    let (mut contract, mut context) = setup_queued();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_transfer_with_travel_rule(accounts(1), U128(1_000), None, "ref".to_string());
+    assert_burned(&contract);
+}
+
+#[test]
+fn test_confirmed_transfers_burn_a_share() {
+    let (mut contract, mut context) = setup_queued();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_co_signer(
+        accounts(0),
+        Some(CoSignerConfig {
+            co_signer_id: accounts(2),
+            threshold: U128(100),
+            window_sec: 3_600,
+        }),
+    );
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.ft_transfer(accounts(1), U128(1_000), None, None, None, None);
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.confirm_transfer(0);
+    assert_burned(&contract);
+}
+
+#[test]
+fn test_accepted_escrows_burn_a_share() {
+    let (mut contract, mut context) = setup_queued();
+    let escrow_id = contract.ft_transfer_escrow(accounts(1), U128(1_000), 60);
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.accept_escrow(escrow_id);
+    assert_burned(&contract);
+}
+
+#[test]
+fn test_committed_reservations_burn_a_share() {
+    let (mut contract, mut context) = setup_queued();
+    let reservation_id = contract.reserve(U128(1_000), accounts(1), 60);
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    assert_eq!(contract.commit(reservation_id), U128(980));
+    assert_burned(&contract);
+}
+
+#[test]
+fn test_executed_scheduled_transfers_burn_a_share() {
+    let (mut contract, mut context) = setup_queued();
+    let scheduled_id = contract.ft_transfer_scheduled(accounts(1), U128(1_000), U64(SECOND));
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(SECOND)
+        .build());
+    contract.execute_scheduled(scheduled_id);
+    assert_burned(&contract);
+}
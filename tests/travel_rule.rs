@@ -0,0 +1,76 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::testing_env;
+
+// Bob and charlie are VASPs exchanging travel rule data above 100 tokens. The context is left
+// as bob.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(1), 1_000)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .with(|contract| {
+            contract.set_vasp(accounts(1), Some("Bob Exchange".to_string()));
+            contract.set_vasp(accounts(2), Some("Charlie Custody".to_string()));
+            contract.set_travel_rule_threshold(Some(U128(100)));
+        })
+        .build();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    (contract, context)
+}
+
+#[test]
+fn test_transfers_up_to_the_threshold_need_no_reference() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(2), U128(100), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(100));
+}
+
+#[test]
+#[should_panic(
+    expected = "Transfers between VASPs above the threshold need a travel rule reference"
+)]
+fn test_transfers_above_the_threshold_need_a_reference() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(2), U128(101), None, None, None, None);
+}
+
+#[test]
+fn test_transfers_with_a_reference_log_it() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer_with_travel_rule(
+        accounts(2),
+        U128(500),
+        None,
+        "ipfs://travel-rule-data".to_string(),
+    );
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(500));
+    let logs = get_logs();
+    let log = logs
+        .iter()
+        .find(|log| log.contains("\"event\":\"travel_rule_transfer\""))
+        .unwrap();
+    assert!(log.contains("\"travel_rule_ref\":\"ipfs://travel-rule-data\""));
+    assert!(log.contains("\"amount\":\"500\""));
+}
+
+#[test]
+fn test_transfers_to_other_accounts_need_no_reference() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(3), U128(500), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(500));
+}
+
+#[test]
+fn test_transfers_need_no_reference_without_a_threshold() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.set_travel_rule_threshold(None);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.ft_transfer(accounts(2), U128(500), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(500));
+}
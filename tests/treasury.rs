@@ -0,0 +1,144 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::guard::PauseFlags;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::treasury::TreasuryGuard;
+use near_ft::wallet_policy::WalletPolicy;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+// Alice is the treasury, co-signed by charlie above 100 tokens.
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .balance(accounts(2), 0)
+        .with(|contract| {
+            contract.set_treasury_guard(Some(TreasuryGuard {
+                treasury_id: accounts(0),
+                approver_id: accounts(2),
+                threshold: U128(100),
+                window_sec: 60,
+            }))
+        })
+        .build()
+}
+
+// Proposes a spend of `amount` to bob, paying for its storage.
+fn propose(contract: &mut Contract, context: &mut VMContextBuilder, amount: u128) -> u64 {
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_near(1))
+        .build());
+    let spend_id = contract.propose_treasury_spend(accounts(1), U128(amount), None);
+    assert!(refunded_to(&accounts(0)) < NearToken::from_near(1));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    spend_id
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_treasury_transfers_up_to_the_threshold() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(1), U128(100), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+}
+
+#[test]
+#[should_panic(expected = "Treasury spends above the threshold must be proposed")]
+fn test_treasury_proposes_the_spends_above_the_threshold() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(1), U128(101), None, None, None, None);
+}
+
+#[test]
+fn test_approver_executes_the_spend() {
+    let (mut contract, mut context) = setup();
+    let spend_id = propose(&mut contract, &mut context, 500);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(500));
+    assert_eq!(contract.get_treasury_spends(None, None).len(), 1);
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.approve_treasury_spend(spend_id);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(500));
+    assert!(contract.get_treasury_spends(None, None).is_empty());
+    // The storage of the spend goes back to the treasury.
+    assert!(refunded_to(&accounts(0)) > NearToken::from_yoctonear(0));
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost")]
+fn test_spend_storage_is_paid_by_the_treasury() {
+    let (mut contract, _) = setup();
+    contract.propose_treasury_spend(accounts(1), U128(500), None);
+}
+
+#[test]
+#[should_panic(expected = "Large transfers may only go to the cold account")]
+fn test_spends_follow_the_transfer_policies() {
+    let (mut contract, mut context) = setup();
+    contract.set_wallet_policy(Some(WalletPolicy {
+        cold_account_id: accounts(2),
+        large_transfer_threshold: U128(200),
+        timelock_sec: 7 * 86_400,
+    }));
+    propose(&mut contract, &mut context, 500);
+}
+
+#[test]
+#[should_panic(expected = "Only the approver can co-sign the spend")]
+fn test_treasury_cannot_approve_its_own_spend() {
+    let (mut contract, mut context) = setup();
+    let spend_id = propose(&mut contract, &mut context, 500);
+    contract.approve_treasury_spend(spend_id);
+}
+
+#[test]
+#[should_panic(expected = "The treasury spend doesn't exist")]
+fn test_spend_is_approved_once() {
+    let (mut contract, mut context) = setup();
+    let spend_id = propose(&mut contract, &mut context, 500);
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.approve_treasury_spend(spend_id);
+    contract.approve_treasury_spend(spend_id);
+}
+
+#[test]
+#[should_panic(expected = "Transfers are paused")]
+fn test_spends_are_not_proposed_while_transfers_are_paused() {
+    let (mut contract, mut context) = setup();
+    contract.set_pause_flags(PauseFlags {
+        transfers: true,
+        ..Default::default()
+    });
+    propose(&mut contract, &mut context, 500);
+}
+
+#[test]
+#[should_panic(expected = "Transfers are paused")]
+fn test_spends_are_not_approved_while_transfers_are_paused() {
+    let (mut contract, mut context) = setup();
+    let spend_id = propose(&mut contract, &mut context, 500);
+    contract.set_pause_flags(PauseFlags {
+        transfers: true,
+        ..Default::default()
+    });
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.approve_treasury_spend(spend_id);
+}
@@ -0,0 +1,235 @@
+use near_ft::access::Role;
+use near_ft::fees::{FeeConfig, FeeSplit};
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::metadata::FungibleTokenMetadataProvider;
+use near_ft::storage::{StorageManagement, BYTES_FOR_LONGEST_ACCOUNT_ID};
+use near_ft::upgrade::{BaselineContract, OldContract, STATE_VERSION};
+use near_ft::Contract;
+use near_sdk::borsh::{self, BorshDeserialize};
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, NearToken};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    (contract, context)
+}
+
+// The states of a contract initialized with `new_default_meta(alice, 1000)`, bob as the minter
+// and charlie as the freeze admin, as written by the versions 0 and 1 of the contract.
+const V0_STATE: &[u8] = include_bytes!("states/v0.bin");
+const V1_STATE: &[u8] = include_bytes!("states/v1.bin");
+
+// The state of a contract initialized with `new_default_meta(alice, 1000)`, bob as the minter,
+// charlie as the freeze admin and a 1% fee paid to alice, as written by the version 2, whose fee
+// split has no insurance share.
+const V2_STATE: &[u8] = include_bytes!("states/v2.bin");
+
+// The whole storage of the contract the extensions were built on, after
+// `new_default_meta(alice, 1000)`, the registration of bob and a transfer of 100 from alice to
+// bob, as borsh-serialized `(key, value)` pairs.
+const BASELINE_STORAGE: &[u8] = include_bytes!("states/baseline.bin");
+
+fn assert_migrates(old_state: &[u8]) {
+    let (mut contract, mut context) = setup();
+    // These versions added a leaf to the balance tree on every registration.
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.sync_balance_tree(vec![accounts(0), env::current_account_id()]);
+    let state = borsh::to_vec(&contract).unwrap();
+    env::storage_write(b"STATE", old_state);
+
+    let migrated = Contract::migrate();
+
+    assert_eq!(migrated.state_version, STATE_VERSION);
+    assert_eq!(migrated.owner_id, accounts(0));
+    assert_eq!(migrated.total_supply, 1_000);
+    assert_eq!(migrated.get_roles(accounts(1)), vec![Role::Minter]);
+    assert_eq!(migrated.get_roles(accounts(2)), vec![Role::FreezeAdmin]);
+    assert_eq!(borsh::to_vec(&migrated).unwrap(), state);
+}
+
+#[test]
+fn test_migrate_adds_the_version_to_an_unversioned_state() {
+    assert_migrates(V0_STATE);
+}
+
+#[test]
+fn test_migrate_converts_the_minter_and_freeze_admin_to_roles() {
+    assert_migrates(V1_STATE);
+}
+
+#[test]
+fn test_migrate_reads_the_first_states_of_the_version_1() {
+    // The version 1 only had the tag after the old fields until the preferences were added.
+    let old = OldContract::try_from_slice(V0_STATE).unwrap();
+    let mut state = borsh::to_vec(&old).unwrap();
+    state.extend_from_slice(&1u32.to_le_bytes());
+    assert_migrates(&state);
+}
+
+#[test]
+fn test_migrate_adds_the_insurance_share_to_the_version_2() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.grant_role(accounts(1), Role::Minter);
+    contract.grant_role(accounts(2), Role::FreezeAdmin);
+    contract.set_fee_config(Some(FeeConfig {
+        fee_bps: 100,
+        split: FeeSplit {
+            stakers_bps: 5_000,
+            treasury_bps: 3_000,
+            burn_bps: 1_000,
+            rebates_bps: 1_000,
+            insurance_bps: 0,
+        },
+        stakers_pool_id: accounts(0),
+        treasury_id: accounts(0),
+    }));
+    // This version added a leaf to the balance tree on every registration.
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    contract.sync_balance_tree(vec![accounts(0), env::current_account_id()]);
+    let state = borsh::to_vec(&contract).unwrap();
+    env::storage_write(b"STATE", V2_STATE);
+
+    let migrated = Contract::migrate();
+
+    assert_eq!(migrated.state_version, STATE_VERSION);
+    assert_eq!(borsh::to_vec(&migrated).unwrap(), state);
+}
+
+fn token() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+fn migrate_baseline() -> (Contract, VMContextBuilder) {
+    let mut context = VMContextBuilder::new();
+    context
+        .current_account_id(token())
+        .predecessor_account_id(token());
+    testing_env!(context.build());
+    let storage: Vec<(Vec<u8>, Vec<u8>)> = borsh::from_slice(BASELINE_STORAGE).unwrap();
+    for (key, value) in storage {
+        env::storage_write(&key, &value);
+    }
+    (Contract::migrate(), context)
+}
+
+#[test]
+fn test_migrate_reads_the_baseline_state() {
+    let (migrated, _) = migrate_baseline();
+
+    assert_eq!(migrated.state_version, STATE_VERSION);
+    assert_eq!(migrated.ft_total_supply(), U128(1_000));
+    assert_eq!(migrated.supply.minted, 1_000);
+    assert_eq!(Contract::ft_metadata().metadata.symbol, "gtNEAR");
+}
+
+#[test]
+fn test_migrate_drops_the_storage_measured_by_the_baseline() {
+    let storage: Vec<(Vec<u8>, Vec<u8>)> = borsh::from_slice(BASELINE_STORAGE).unwrap();
+    let (_, state) = storage.iter().find(|(key, _)| key == b"STATE").unwrap();
+    let baseline = BaselineContract::try_from_slice(state).unwrap();
+    assert_eq!(
+        baseline.bytes_for_longest_account_id,
+        BYTES_FOR_LONGEST_ACCOUNT_ID
+    );
+
+    migrate_baseline();
+
+    // The metadata, stored after the dropped field, is still read.
+    assert_eq!(
+        Contract::ft_metadata().metadata.name,
+        "Team Token FT Tutorial"
+    );
+}
+
+#[test]
+fn test_migrate_reads_the_balances_stored_as_near_tokens() {
+    let balance = u128::MAX - 1;
+    assert_eq!(
+        borsh::to_vec(&NearToken::from_yoctonear(balance)).unwrap(),
+        borsh::to_vec(&balance).unwrap()
+    );
+
+    let (mut migrated, mut context) = migrate_baseline();
+
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(900));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    migrated.ft_transfer(accounts(0), U128(40), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(940));
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(60));
+}
+
+#[test]
+fn test_migrate_makes_the_contract_account_the_owner_of_the_baseline() {
+    let (mut migrated, mut context) = migrate_baseline();
+
+    assert_eq!(migrated.get_owner(), token());
+    assert!(Contract::storage_balance_of(token()).is_some());
+
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    migrated.propose_owner(Some(accounts(0)));
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    migrated.accept_ownership();
+    assert_eq!(migrated.get_owner(), accounts(0));
+}
+
+#[test]
+fn test_migrate_keeps_an_up_to_date_state() {
+    let (contract, _) = setup();
+    let state = borsh::to_vec(&contract).unwrap();
+    env::storage_write(b"STATE", &state);
+
+    let migrated = Contract::migrate();
+
+    assert_eq!(borsh::to_vec(&migrated).unwrap(), state);
+}
+
+#[test]
+#[should_panic(expected = "Unknown state version 32")]
+fn test_migrate_rejects_newer_states() {
+    let (mut contract, _) = setup();
+    contract.state_version = STATE_VERSION + 1;
+    env::storage_write(b"STATE", &borsh::to_vec(&contract).unwrap());
+
+    Contract::migrate();
+}
+
+#[test]
+#[should_panic(expected = "Only the owner can call this method")]
+fn test_upgrade_is_called_by_the_owner() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.upgrade(Base64VecU8(vec![0, 97, 115, 109]));
+}
+
+#[test]
+fn test_baseline_accounts_unregister_without_being_counted() {
+    let (mut migrated, mut context) = migrate_baseline();
+    // Only the contract account, registered by the migration, is counted.
+    assert_eq!(migrated.metrics()["active_accounts"], U128(1));
+
+    for (account_id, balance) in [(accounts(1), 100), (accounts(0), 900)] {
+        testing_env!(context
+            .predecessor_account_id(account_id)
+            .attached_deposit(NearToken::from_yoctonear(1))
+            .build());
+        migrated.ft_transfer(token(), U128(balance), None, None, None, None);
+        assert!(migrated.storage_unregister(None));
+    }
+    assert_eq!(migrated.metrics()["active_accounts"], U128(0));
+}
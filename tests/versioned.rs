@@ -0,0 +1,94 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::scheduled::{ScheduledTransfer, ScheduledTransferV1, VersionedScheduledTransfer};
+use near_ft::storage_keys::{ScheduledKey, StorageKey};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::versioned::VersionedLookupMap;
+use near_ft::Contract;
+use near_sdk::borsh;
+use near_sdk::json_types::{U128, U64};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{env, testing_env, IntoStorageKey, NearToken};
+
+const SECOND: u64 = 1_000_000_000;
+
+fn scheduled_key(scheduled_id: u64) -> Vec<u8> {
+    let mut key = StorageKey::Scheduled(ScheduledKey::ScheduledTransfers).into_storage_key();
+    key.extend(borsh::to_vec(&scheduled_id).unwrap());
+    key
+}
+
+// Writes scheduled transfer 0 of 100 tokens from alice to bob as it was stored before its fee
+// was escrowed.
+fn write_v1_record() {
+    let transfer = VersionedScheduledTransfer::V1(ScheduledTransferV1 {
+        sender_id: accounts(0),
+        receiver_id: accounts(1),
+        amount: U128(100),
+        execute_after: U64(SECOND),
+    });
+    env::storage_write(&scheduled_key(0), &borsh::to_vec(&transfer).unwrap());
+}
+
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .contract_id("token.near".parse().unwrap())
+        .balance(accounts(0), 900)
+        .balance(accounts(1), 0)
+        .balance("token.near".parse().unwrap(), 100)
+        .build()
+}
+
+#[test]
+fn test_v1_records_are_read_as_the_current_version() {
+    setup();
+    write_v1_record();
+    let map: VersionedLookupMap<u64, ScheduledTransfer> =
+        VersionedLookupMap::new(StorageKey::Scheduled(ScheduledKey::ScheduledTransfers));
+
+    let transfer = map.get(&0).unwrap();
+    assert_eq!(transfer.sender_id, accounts(0));
+    assert_eq!(transfer.receiver_id, accounts(1));
+    assert_eq!(transfer.amount, U128(100));
+    assert_eq!(transfer.execute_after, U64(SECOND));
+    assert_eq!(transfer.fee, U128(0));
+    assert!(matches!(
+        map.get_record(&0),
+        Some(VersionedScheduledTransfer::V1(_))
+    ));
+}
+
+#[test]
+fn test_records_are_written_back_as_the_current_version() {
+    setup();
+    write_v1_record();
+    let mut map: VersionedLookupMap<u64, ScheduledTransfer> =
+        VersionedLookupMap::new(StorageKey::Scheduled(ScheduledKey::ScheduledTransfers));
+
+    let mut transfer = map.get(&0).unwrap();
+    transfer.fee = U128(1);
+    let previous = map.insert(&0, &transfer).unwrap();
+    assert_eq!(previous.fee, U128(0));
+    assert!(matches!(
+        map.get_record(&0),
+        Some(VersionedScheduledTransfer::V2(ScheduledTransfer {
+            fee: U128(1),
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn test_contract_executes_v1_records() {
+    let (mut contract, mut context) = setup();
+    write_v1_record();
+    assert_eq!(contract.get_scheduled_transfer(0).unwrap().fee, U128(0));
+
+    testing_env!(context
+        .predecessor_account_id(accounts(2))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .block_timestamp(SECOND)
+        .build());
+    contract.execute_scheduled(0);
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+    assert!(contract.get_scheduled_transfer(0).is_none());
+}
@@ -0,0 +1,71 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::metadata::FungibleTokenMetadataProvider;
+use near_ft::storage::{StorageManagement, BYTES_PER_REGISTRATION};
+use near_ft::test_utils::ContractBuilder;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{borsh, env};
+
+// Alice holds 1,000 tokens and bob is registered without any. Charlie isn't registered.
+fn setup() -> (Contract, VMContextBuilder) {
+    ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .balance(accounts(1), 0)
+        .build()
+}
+
+#[test]
+fn test_balances_match_the_state() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(1), U128(300), None, None, None, None);
+    for account_id in [accounts(0), accounts(1), accounts(2)] {
+        assert_eq!(
+            Contract::ft_balance_of(account_id.clone()).0,
+            contract.accounts.get(&account_id).unwrap_or(0)
+        );
+    }
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(300));
+}
+
+#[test]
+fn test_metadata_matches_the_state() {
+    let (mut contract, _) = setup();
+    contract.rename_token(Some("Renamed Token".to_string()), None);
+
+    let view = Contract::ft_metadata();
+    assert_eq!(
+        borsh::to_vec(&view.metadata).unwrap(),
+        borsh::to_vec(&contract.metadata.get().unwrap()).unwrap()
+    );
+    assert_eq!(view.metadata.name, "Renamed Token");
+    assert_eq!(
+        borsh::to_vec(&view.pending_change).unwrap(),
+        borsh::to_vec(&contract.rename_quarantine.get()).unwrap()
+    );
+    assert!(view.pending_change.is_some());
+}
+
+#[test]
+fn test_storage_balances_match_the_state() {
+    let (contract, _) = setup();
+    let bounds = Contract::storage_balance_bounds();
+    assert_eq!(
+        bounds.min,
+        env::storage_byte_cost().saturating_mul(BYTES_PER_REGISTRATION.into())
+    );
+    assert_eq!(bounds.max, Some(bounds.min));
+
+    for account_id in [accounts(0), accounts(1), accounts(2)] {
+        let balance = Contract::storage_balance_of(account_id.clone());
+        assert_eq!(
+            balance.is_some(),
+            contract.accounts.contains_key(&account_id)
+        );
+        if let Some(balance) = balance {
+            assert_eq!(balance.total, bounds.min);
+            assert!(balance.available.is_zero());
+        }
+    }
+    assert!(Contract::storage_balance_of(accounts(2)).is_none());
+}
@@ -0,0 +1,98 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::vouchers::BalanceVoucher;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::mock::MockAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+// Bob's contract is the successor of the token.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(0), 1_000)
+        .build();
+    contract.set_successor_contract(Some(accounts(1)));
+    (contract, context)
+}
+
+// Exports alice's balance, attaching enough for the storage of the voucher. Returns the voucher
+// and the storage cost charged.
+fn export(contract: &mut Contract, context: &mut VMContextBuilder) -> (BalanceVoucher, NearToken) {
+    testing_env!(context.attached_deposit(NearToken::from_near(1)).build());
+    let voucher = contract.export_balance_voucher();
+    let cost = NearToken::from_near(1).saturating_sub(refunded_to(&accounts(0)));
+    (voucher, cost)
+}
+
+// Returns the NEAR transferred to `account_id` by the receipts of the last call.
+fn refunded_to(account_id: &AccountId) -> NearToken {
+    get_created_receipts()
+        .iter()
+        .filter(|receipt| &receipt.receiver_id == account_id)
+        .flat_map(|receipt| receipt.actions.iter())
+        .fold(NearToken::from_yoctonear(0), |total, action| match action {
+            MockAction::Transfer { deposit, .. } => total.saturating_add(*deposit),
+            _ => total,
+        })
+}
+
+#[test]
+fn test_successor_redeems_an_exported_balance() {
+    let (mut contract, mut context) = setup();
+    let (voucher, _) = export(&mut contract, &mut context);
+    assert_eq!((voucher.nonce, voucher.amount), (0, U128(1_000)));
+    assert!(get_logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"balance_voucher_exported\"")));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(0));
+    assert_eq!(contract.ft_total_supply(), U128(0));
+
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    let redeemed = contract.redeem_balance_voucher(0);
+    assert_eq!(redeemed.account_id, accounts(0));
+    assert!(contract.get_balance_voucher(0).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The voucher doesn't exist or was redeemed")]
+fn test_voucher_is_redeemed_once() {
+    let (mut contract, mut context) = setup();
+    export(&mut contract, &mut context);
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.redeem_balance_voucher(0);
+    contract.redeem_balance_voucher(0);
+}
+
+#[test]
+#[should_panic(expected = "Only the successor contract can redeem a voucher")]
+fn test_voucher_is_redeemed_by_the_successor() {
+    let (mut contract, mut context) = setup();
+    export(&mut contract, &mut context);
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.redeem_balance_voucher(0);
+}
+
+#[test]
+fn test_voucher_storage_is_refunded_on_redemption() {
+    let (mut contract, mut context) = setup();
+    let (_, cost) = export(&mut contract, &mut context);
+    assert!(!cost.is_zero());
+
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(0))
+        .build());
+    contract.redeem_balance_voucher(0);
+    // The export also paid for the records of the burn, which are kept.
+    let refund = refunded_to(&accounts(0));
+    assert!(!refund.is_zero());
+    assert!(refund <= cost);
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit should cover the storage cost of")]
+fn test_vouchers_need_the_storage_deposit() {
+    let (mut contract, _) = setup();
+    contract.export_balance_voucher();
+}
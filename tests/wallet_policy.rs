@@ -0,0 +1,107 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::test_utils::ContractBuilder;
+use near_ft::wallet_policy::WalletPolicy;
+use near_ft::Contract;
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId};
+
+const DAY: u64 = 86_400_000_000_000;
+
+fn policy(cold_account_id: AccountId, timelock_sec: u64) -> WalletPolicy {
+    WalletPolicy {
+        cold_account_id,
+        large_transfer_threshold: U128(100),
+        timelock_sec,
+    }
+}
+
+// Bob's transfers above 100 tokens may only go to charlie, and changing that takes a day. The
+// context is left as bob.
+fn setup() -> (Contract, VMContextBuilder) {
+    let (mut contract, mut context) = ContractBuilder::new(accounts(0))
+        .balance(accounts(1), 1_000)
+        .balance(accounts(2), 0)
+        .balance(accounts(3), 0)
+        .build();
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.set_wallet_policy(Some(policy(accounts(2), 86_400)));
+    (contract, context)
+}
+
+#[test]
+fn test_large_transfers_go_to_the_cold_account() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(3), U128(100), None, None, None, None);
+    contract.ft_transfer(accounts(2), U128(500), None, None, None, None);
+    assert_eq!(Contract::ft_balance_of(accounts(3)), U128(100));
+    assert_eq!(Contract::ft_balance_of(accounts(2)), U128(500));
+}
+
+#[test]
+#[should_panic(expected = "Large transfers may only go to the cold account")]
+fn test_large_transfers_elsewhere_are_rejected() {
+    let (mut contract, _) = setup();
+    contract.ft_transfer(accounts(3), U128(101), None, None, None, None);
+}
+
+#[test]
+fn test_removing_the_policy_waits_for_the_timelock() {
+    let (mut contract, mut context) = setup();
+    contract.set_wallet_policy(None);
+    assert!(contract.get_wallet_policy(accounts(1)).is_some());
+    assert_eq!(
+        contract
+            .get_wallet_policy_change(accounts(1))
+            .unwrap()
+            .executable_at
+            .0,
+        DAY
+    );
+
+    testing_env!(context.block_timestamp(DAY).build());
+    contract.execute_wallet_policy_change();
+    assert!(contract.get_wallet_policy(accounts(1)).is_none());
+    assert!(contract.get_wallet_policy_change(accounts(1)).is_none());
+    contract.ft_transfer(accounts(3), U128(500), None, None, None, None);
+}
+
+#[test]
+#[should_panic(expected = "The wallet policy change is still timelocked")]
+fn test_policy_changes_arent_executed_early() {
+    let (mut contract, mut context) = setup();
+    contract.set_wallet_policy(Some(policy(accounts(3), 86_400)));
+    testing_env!(context.block_timestamp(DAY - 1).build());
+    contract.execute_wallet_policy_change();
+}
+
+#[test]
+#[should_panic(expected = "There is no wallet policy change")]
+fn test_cancelled_changes_cant_be_executed() {
+    let (mut contract, mut context) = setup();
+    contract.set_wallet_policy(None);
+    contract.cancel_wallet_policy_change();
+    testing_env!(context.block_timestamp(DAY).build());
+    contract.execute_wallet_policy_change();
+}
+
+#[test]
+#[should_panic(expected = "The wallet policy timelock is too short")]
+fn test_policies_have_a_minimum_timelock() {
+    let (mut contract, _) = setup();
+    contract.set_wallet_policy(Some(policy(accounts(2), 86_399)));
+}
+
+#[test]
+#[should_panic(expected = "The cold account should be different from the account")]
+fn test_the_cold_account_is_another_account() {
+    let (mut contract, _) = setup();
+    contract.set_wallet_policy(Some(policy(accounts(1), 86_400)));
+}
+
+#[test]
+#[should_panic(expected = "The account eugene is not registered")]
+fn test_the_cold_account_is_registered() {
+    let (mut contract, _) = setup();
+    contract.set_wallet_policy(Some(policy(accounts(4), 86_400)));
+}
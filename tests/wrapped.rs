@@ -0,0 +1,130 @@
+use near_ft::ft_core::FungibleTokenCore;
+use near_ft::metadata::FungibleTokenMetadata;
+use near_ft::psm::PsmConfig;
+use near_ft::storage::StorageManagement;
+use near_ft::{Contract, FT_METADATA_SPEC};
+use near_sdk::json_types::U128;
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+use near_sdk::{testing_env, NearToken};
+
+fn setup() -> (Contract, VMContextBuilder) {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let contract = Contract::new_wrapped(
+        accounts(0),
+        FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: "Wrapped NEAR".to_string(),
+            symbol: "wNEAR".to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 24,
+        },
+    );
+    (contract, context)
+}
+
+#[test]
+fn test_near_deposit_registers_and_mints() {
+    let (mut contract, mut context) = setup();
+    assert!(contract.is_wrapped());
+    assert_eq!(contract.ft_total_supply(), U128(0));
+
+    let min_balance = Contract::storage_balance_bounds().min;
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(min_balance.saturating_add(NearToken::from_yoctonear(100)))
+        .build());
+    contract.near_deposit();
+    assert!(Contract::storage_balance_of(accounts(1)).is_some());
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(100));
+
+    // A registered account gets the whole deposit.
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(50))
+        .build());
+    contract.near_deposit();
+    assert_eq!(Contract::ft_balance_of(accounts(1)), U128(150));
+    assert_eq!(contract.ft_total_supply(), U128(150));
+    assert!(get_logs()[0].contains("\"event\":\"ft_mint\""));
+}
+
+#[test]
+fn test_near_withdraw_burns() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.near_deposit();
+
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.near_withdraw(U128(40));
+    assert_eq!(Contract::ft_balance_of(accounts(0)), U128(60));
+    assert_eq!(contract.ft_total_supply(), U128(60));
+    assert!(get_logs()[0].contains("\"event\":\"ft_burn\""));
+}
+
+#[test]
+#[should_panic(expected = "The attached deposit is less than the minimum storage balance")]
+fn test_near_deposit_pays_for_the_storage() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .predecessor_account_id(accounts(1))
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.near_deposit();
+}
+
+#[test]
+#[should_panic(expected = "Wrapped NEAR is only minted by near_deposit")]
+fn test_ft_mint_is_disabled_for_wrapped_near() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.ft_mint(accounts(0), U128(100), None);
+}
+
+#[test]
+#[should_panic(expected = "Wrapped NEAR is only minted by near_deposit")]
+fn test_mint_requests_are_disabled_for_wrapped_near() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_mint_proposer(accounts(0), true);
+    contract.propose_mint(accounts(0), U128(100), "Unbacked".to_string());
+}
+
+#[test]
+#[should_panic(expected = "Wrapped NEAR is only minted by near_deposit")]
+fn test_the_psm_is_disabled_for_wrapped_near() {
+    let (mut contract, mut context) = setup();
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.set_psm_config(Some(PsmConfig {
+        reference_token_id: accounts(3),
+        reference_decimals: 6,
+        fee_bps: 10,
+        debt_ceiling: U128(1_000),
+    }));
+}
+
+#[test]
+#[should_panic(expected = "The contract isn't in the wrapped NEAR mode")]
+fn test_near_deposit_needs_the_wrapped_mode() {
+    let mut context = VMContextBuilder::new();
+    context.predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(100))
+        .build());
+    contract.near_deposit();
+}
@@ -0,0 +1,190 @@
+//! Export of the Borsh state of the contract to JSON and CSV, for audits and analytics.
+//!
+//! The input is a state dump in the shape of the `view_state` RPC query: a list of base64
+//! encoded key-value pairs. Records are recognized by the prefix of their collection and
+//! decoded with the contract's own types and versioned records, so a record the contract can
+//! read is a record the export can read. Keys of the other collections are ignored.
+
+use std::fmt;
+
+use near_ft::grants::{GrantId, VersionedVestingGrant, VestingGrant};
+use near_ft::locks::{Lock, LockId, VersionedLock};
+use near_ft::storage_keys::{CoreKey, GrantsKey, LocksKey};
+use near_ft::versioned::Versioned;
+use near_sdk::borsh::BorshDeserialize;
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::{self, Value};
+use near_sdk::AccountId;
+
+/// A key-value pair of the contract's storage.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StateRecord {
+    pub key: Base64VecU8,
+    pub value: Base64VecU8,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(untagged)]
+enum StateDump {
+    Response { result: StateValues },
+    Values(StateValues),
+    Records(Vec<StateRecord>),
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct StateValues {
+    values: Vec<StateRecord>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountRow {
+    pub account_id: AccountId,
+    pub balance: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LockRow {
+    pub lock_id: LockId,
+    #[serde(flatten)]
+    pub lock: Lock,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantRow {
+    pub grant_id: GrantId,
+    #[serde(flatten)]
+    pub grant: VestingGrant,
+}
+
+// The exported tables, sorted by key.
+#[derive(Serialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StateExport {
+    pub accounts: Vec<AccountRow>,
+    pub locks: Vec<LockRow>,
+    pub grants: Vec<GrantRow>,
+}
+
+/// A record that doesn't decode as the type of its collection.
+#[derive(Debug)]
+pub struct DecodeError {
+    pub collection: &'static str,
+    pub key: Vec<u8>,
+    pub error: std::io::Error,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to decode the {} record with key {:?}: {}",
+            self.collection, self.key, self.error
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Parses a state dump: the result of a `view_state` query, the whole JSON RPC response, or
+/// the bare list of records.
+pub fn parse_state_dump(json: &str) -> Result<Vec<StateRecord>, serde_json::Error> {
+    Ok(match serde_json::from_str(json)? {
+        StateDump::Response { result } => result.values,
+        StateDump::Values(values) => values.values,
+        StateDump::Records(records) => records,
+    })
+}
+
+/// Decodes the accounts, locks and grants of the state.
+pub fn export_state(records: &[StateRecord]) -> Result<StateExport, DecodeError> {
+    let mut export = StateExport::default();
+    for record in records {
+        let (key, value) = (&record.key.0, &record.value.0);
+        let Some((&prefix, id)) = key.split_first() else {
+            continue;
+        };
+        if prefix == CoreKey::Accounts.prefix() {
+            export.accounts.push(AccountRow {
+                account_id: decode("accounts", key, id)?,
+                balance: U128(decode("accounts", key, value)?),
+            });
+        } else if prefix == LocksKey::Locks.prefix() {
+            export.locks.push(LockRow {
+                lock_id: decode("locks", key, id)?,
+                lock: Lock::from_record(decode::<VersionedLock>("locks", key, value)?),
+            });
+        } else if prefix == GrantsKey::Grants.prefix() {
+            export.grants.push(GrantRow {
+                grant_id: decode("grants", key, id)?,
+                grant: VestingGrant::from_record(decode::<VersionedVestingGrant>(
+                    "grants", key, value,
+                )?),
+            });
+        }
+    }
+    export
+        .accounts
+        .sort_by(|a, b| a.account_id.cmp(&b.account_id));
+    export.locks.sort_by_key(|row| row.lock_id);
+    export.grants.sort_by_key(|row| row.grant_id);
+    Ok(export)
+}
+
+fn decode<T: BorshDeserialize>(
+    collection: &'static str,
+    key: &[u8],
+    bytes: &[u8],
+) -> Result<T, DecodeError> {
+    T::try_from_slice(bytes).map_err(|error| DecodeError {
+        collection,
+        key: key.to_vec(),
+        error,
+    })
+}
+
+/// Formats rows as CSV, with a column per field of their JSON representation. Nested values
+/// are written as JSON.
+pub fn to_csv<T: Serialize>(rows: &[T]) -> String {
+    let rows: Vec<Value> = rows
+        .iter()
+        .map(|row| serde_json::to_value(row).expect("The rows should serialize"))
+        .collect();
+    let Some(columns) = rows.first().and_then(Value::as_object) else {
+        return String::new();
+    };
+    let columns: Vec<&String> = columns.keys().collect();
+    let mut csv = columns
+        .iter()
+        .map(|column| csv_field(column))
+        .collect::<Vec<_>>()
+        .join(",");
+    csv.push('\n');
+    for row in rows.iter() {
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|column| match &row[column.as_str()] {
+                Value::Null => String::new(),
+                Value::String(value) => csv_field(value),
+                value => csv_field(&value.to_string()),
+            })
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,49 @@
+//! Exports the accounts, locks and grants of a state dump of the contract.
+//!
+//! Usage: `cargo run -p near-ft-tools -- <dump.json> [all|accounts|locks|grants] [json|csv]`.
+//! The dump is the JSON RPC response of a `view_state` query (see the README), or its result.
+//! CSV exports a single table.
+
+use std::process::ExitCode;
+
+use near_ft_tools::{export_state, parse_state_dump, to_csv};
+use near_sdk::serde_json;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [path, rest @ ..] = args.as_slice() else {
+        eprintln!("Usage: state-export <dump.json> [all|accounts|locks|grants] [json|csv]");
+        return ExitCode::FAILURE;
+    };
+    let table = rest.first().map_or("all", String::as_str);
+    let format = rest.get(1).map_or("json", String::as_str);
+
+    let export = match std::fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|json| parse_state_dump(&json).map_err(|error| error.to_string()))
+        .and_then(|records| export_state(&records).map_err(|error| error.to_string()))
+    {
+        Ok(export) => export,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let output = match (table, format) {
+        ("all", "json") => serde_json::to_string_pretty(&export),
+        ("accounts", "json") => serde_json::to_string_pretty(&export.accounts),
+        ("locks", "json") => serde_json::to_string_pretty(&export.locks),
+        ("grants", "json") => serde_json::to_string_pretty(&export.grants),
+        ("accounts", "csv") => Ok(to_csv(&export.accounts)),
+        ("locks", "csv") => Ok(to_csv(&export.locks)),
+        ("grants", "csv") => Ok(to_csv(&export.grants)),
+        _ => {
+            eprintln!("error: unsupported export {} as {}", table, format);
+            return ExitCode::FAILURE;
+        }
+    };
+    let output = output.expect("The export should serialize");
+    println!("{}", output.trim_end());
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,109 @@
+use near_ft::grants::GrantTransferPolicy;
+use near_ft::storage::StorageManagement;
+use near_ft::Contract;
+use near_ft_tools::{export_state, parse_state_dump, to_csv, StateRecord};
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::mock::with_mocked_blockchain;
+use near_sdk::serde_json::{self, json};
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, NearToken};
+
+/// Grants and locks tokens, and returns the whole storage of the contract as a state dump.
+fn setup_state() -> Vec<StateRecord> {
+    let contract_id: AccountId = "token.near".parse().unwrap();
+    let mut context = VMContextBuilder::new();
+    context
+        .current_account_id(contract_id)
+        .predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0), U128(1_000));
+    testing_env!(context
+        .attached_deposit(Contract::storage_balance_bounds().min)
+        .build());
+    contract.storage_deposit(Some(accounts(1)), None);
+    testing_env!(context
+        .attached_deposit(NearToken::from_yoctonear(1))
+        .build());
+    contract.create_vesting_grant(
+        accounts(1),
+        U128(100),
+        None,
+        0,
+        3_600,
+        GrantTransferPolicy::Transferable,
+    );
+    testing_env!(context
+        .attached_deposit(NearToken::from_millinear(10))
+        .build());
+    contract.lock_tokens(U128(50), 60, None);
+
+    with_mocked_blockchain(|blockchain| blockchain.take_storage())
+        .into_iter()
+        .map(|(key, value)| StateRecord {
+            key: Base64VecU8(key),
+            value: Base64VecU8(value),
+        })
+        .collect()
+}
+
+#[test]
+fn test_export_decodes_the_contract_records() {
+    let export = export_state(&setup_state()).unwrap();
+
+    let balances: Vec<(String, u128)> = export
+        .accounts
+        .iter()
+        .map(|row| (row.account_id.to_string(), row.balance.0))
+        .collect();
+    assert_eq!(
+        balances,
+        vec![
+            ("alice".to_string(), 850),
+            ("bob".to_string(), 0),
+            ("token.near".to_string(), 150),
+        ]
+    );
+    assert_eq!(export.grants.len(), 1);
+    assert_eq!(export.grants[0].grant.holder_id, accounts(1));
+    assert_eq!(export.grants[0].grant.amount, U128(100));
+    assert_eq!(export.locks.len(), 1);
+    assert_eq!(export.locks[0].lock.owner_id, accounts(0));
+    assert_eq!(export.locks[0].lock.amount, U128(50));
+}
+
+#[test]
+fn test_export_formats() {
+    let records = setup_state();
+    let dump = json!({ "result": { "values": records, "block_height": 1 } }).to_string();
+    let export = export_state(&parse_state_dump(&dump).unwrap()).unwrap();
+
+    let locks = serde_json::to_value(&export.locks).unwrap();
+    assert_eq!(locks[0]["lock_id"], json!(0));
+    assert_eq!(locks[0]["owner_id"], json!("alice"));
+    assert_eq!(locks[0]["with_receipt"], json!(false));
+
+    assert_eq!(
+        to_csv(&export.accounts),
+        "account_id,balance\nalice,850\nbob,0\ntoken.near,150\n"
+    );
+    let grants = to_csv(&export.grants);
+    let header = grants.lines().next().unwrap();
+    assert!(header.starts_with("amount,claimed,cliff_at,end_at,grant_id,holder_id"));
+    assert!(grants.lines().nth(1).unwrap().starts_with("100,0,"));
+}
+
+#[test]
+fn test_export_rejects_corrupted_records() {
+    let records = vec![StateRecord {
+        key: Base64VecU8(vec![0, 3, 0, 0, 0, b'b', b'o', b'b']),
+        value: Base64VecU8(vec![1, 2, 3]),
+    }];
+    let error = export_state(&records).err().unwrap();
+    assert_eq!(error.collection, "accounts");
+    // Records of the other collections are skipped.
+    let records = vec![StateRecord {
+        key: Base64VecU8(b"STATE".to_vec()),
+        value: Base64VecU8(vec![1, 2, 3]),
+    }];
+    assert!(export_state(&records).unwrap().accounts.is_empty());
+}